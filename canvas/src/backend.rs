@@ -0,0 +1,587 @@
+//! Render backends: the drawing surface behind [`crate::render`].
+//!
+//! [`RenderBackend`] is the subset of the Canvas 2D API the renderer uses —
+//! state, transforms, paths, fills, strokes, and text. The browser draws
+//! through the [`CanvasRenderingContext2d`] implementation; [`RecordingBackend`]
+//! keeps the calls in a list so render code can be tested natively.
+//!
+//! Method names and argument order follow the Canvas 2D API, so a call in
+//! `render` reads the same as the JavaScript it replaces.
+
+#[cfg(test)]
+#[path = "backend_test.rs"]
+mod backend_test;
+
+use std::convert::Infallible;
+
+use wasm_bindgen::JsValue;
+use web_sys::{CanvasRenderingContext2d, Path2d};
+
+/// A 2D drawing surface with Canvas 2D semantics.
+///
+/// Calls that can fail on the web canvas return `Result`; the rest cannot.
+pub trait RenderBackend {
+    /// Error raised by fallible calls.
+    type Error;
+    /// A reusable path built from SVG path data.
+    type Path;
+
+    // --- State -------------------------------------------------------------
+
+    /// Push the current style and transform onto the state stack.
+    fn save(&mut self);
+    /// Pop the state stack.
+    fn restore(&mut self);
+
+    // --- Transforms --------------------------------------------------------
+
+    /// Replace the current transform with the matrix `[a c e; b d f]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the backend rejects the matrix.
+    fn set_transform(&mut self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Result<(), Self::Error>;
+    /// Multiply the current transform by the matrix `[a c e; b d f]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the backend rejects the matrix.
+    fn transform(&mut self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Result<(), Self::Error>;
+    /// Translate the current transform.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the backend rejects the transform.
+    fn translate(&mut self, x: f64, y: f64) -> Result<(), Self::Error>;
+    /// Rotate the current transform by `angle` radians.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the backend rejects the transform.
+    fn rotate(&mut self, angle: f64) -> Result<(), Self::Error>;
+    /// Scale the current transform.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the backend rejects the transform.
+    fn scale(&mut self, x: f64, y: f64) -> Result<(), Self::Error>;
+
+    // --- Styles ------------------------------------------------------------
+
+    /// Set the fill color as a CSS color string.
+    fn set_fill_style(&mut self, color: &str);
+    /// Set the stroke color as a CSS color string.
+    fn set_stroke_style(&mut self, color: &str);
+    /// Set the stroke width in current units.
+    fn set_line_width(&mut self, width: f64);
+    /// Set the stroke dash pattern; an empty slice draws solid lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the backend rejects the pattern.
+    fn set_line_dash(&mut self, segments: &[f64]) -> Result<(), Self::Error>;
+    /// Set the opacity applied to every later fill and stroke, in `[0, 1]`.
+    fn set_global_alpha(&mut self, alpha: f64);
+
+    // --- Rectangles --------------------------------------------------------
+
+    /// Clear a rectangle to transparent.
+    fn clear_rect(&mut self, x: f64, y: f64, width: f64, height: f64);
+    /// Fill a rectangle with the fill style.
+    fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64);
+    /// Stroke a rectangle with the stroke style.
+    fn stroke_rect(&mut self, x: f64, y: f64, width: f64, height: f64);
+
+    // --- Paths -------------------------------------------------------------
+
+    /// Start a new current path.
+    fn begin_path(&mut self);
+    /// Start a subpath at a point.
+    fn move_to(&mut self, x: f64, y: f64);
+    /// Add a straight segment to a point.
+    fn line_to(&mut self, x: f64, y: f64);
+    /// Close the current subpath.
+    fn close_path(&mut self);
+    /// Add a circular arc from `start` to `end` radians.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` for a negative radius.
+    fn arc(&mut self, x: f64, y: f64, radius: f64, start: f64, end: f64) -> Result<(), Self::Error>;
+    /// Add an elliptical arc from `start` to `end` radians.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` for a negative radius.
+    #[allow(clippy::too_many_arguments)]
+    fn ellipse(
+        &mut self,
+        x: f64,
+        y: f64,
+        radius_x: f64,
+        radius_y: f64,
+        rotation: f64,
+        start: f64,
+        end: f64,
+    ) -> Result<(), Self::Error>;
+    /// Fill the current path with the fill style.
+    fn fill(&mut self);
+    /// Stroke the current path with the stroke style.
+    fn stroke(&mut self);
+
+    /// Build a path from SVG path data (the `d` attribute).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the backend cannot construct the path.
+    fn path_from_svg(&mut self, d: &str) -> Result<Self::Path, Self::Error>;
+    /// Fill a path built by [`Self::path_from_svg`].
+    fn fill_path(&mut self, path: &Self::Path);
+    /// Stroke a path built by [`Self::path_from_svg`].
+    fn stroke_path(&mut self, path: &Self::Path);
+
+    // --- Text --------------------------------------------------------------
+
+    /// Set the CSS font shorthand, e.g. `"14px sans-serif"`.
+    fn set_font(&mut self, font: &str);
+    /// Set horizontal text alignment (`"left"`, `"center"`, ...).
+    fn set_text_align(&mut self, align: &str);
+    /// Set the text baseline (`"middle"`, `"auto"`, ...).
+    fn set_text_baseline(&mut self, baseline: &str);
+    /// Fill text at a point with the fill style.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the backend fails to draw the text.
+    fn fill_text(&mut self, text: &str, x: f64, y: f64) -> Result<(), Self::Error>;
+    /// Advance width of `text` in the current font.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the backend cannot measure text.
+    fn measure_text(&mut self, text: &str) -> Result<f64, Self::Error>;
+}
+
+// =============================================================
+// Web canvas
+// =============================================================
+
+/// The browser canvas. Calls are forwarded one-to-one; inherent methods are
+/// named explicitly because the trait methods share their names.
+impl RenderBackend for CanvasRenderingContext2d {
+    type Error = JsValue;
+    type Path = Path2d;
+
+    fn save(&mut self) {
+        CanvasRenderingContext2d::save(self);
+    }
+
+    fn restore(&mut self) {
+        CanvasRenderingContext2d::restore(self);
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    fn set_transform(&mut self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Result<(), JsValue> {
+        CanvasRenderingContext2d::set_transform(self, a, b, c, d, e, f)
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    fn transform(&mut self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Result<(), JsValue> {
+        CanvasRenderingContext2d::transform(self, a, b, c, d, e, f)
+    }
+
+    fn translate(&mut self, x: f64, y: f64) -> Result<(), JsValue> {
+        CanvasRenderingContext2d::translate(self, x, y)
+    }
+
+    fn rotate(&mut self, angle: f64) -> Result<(), JsValue> {
+        CanvasRenderingContext2d::rotate(self, angle)
+    }
+
+    fn scale(&mut self, x: f64, y: f64) -> Result<(), JsValue> {
+        CanvasRenderingContext2d::scale(self, x, y)
+    }
+
+    fn set_fill_style(&mut self, color: &str) {
+        self.set_fill_style_str(color);
+    }
+
+    fn set_stroke_style(&mut self, color: &str) {
+        self.set_stroke_style_str(color);
+    }
+
+    fn set_line_width(&mut self, width: f64) {
+        CanvasRenderingContext2d::set_line_width(self, width);
+    }
+
+    fn set_line_dash(&mut self, segments: &[f64]) -> Result<(), JsValue> {
+        let array = js_sys::Array::new();
+        for &segment in segments {
+            array.push(&segment.into());
+        }
+        CanvasRenderingContext2d::set_line_dash(self, &array)
+    }
+
+    fn set_global_alpha(&mut self, alpha: f64) {
+        CanvasRenderingContext2d::set_global_alpha(self, alpha);
+    }
+
+    fn clear_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        CanvasRenderingContext2d::clear_rect(self, x, y, width, height);
+    }
+
+    fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        CanvasRenderingContext2d::fill_rect(self, x, y, width, height);
+    }
+
+    fn stroke_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        CanvasRenderingContext2d::stroke_rect(self, x, y, width, height);
+    }
+
+    fn begin_path(&mut self) {
+        CanvasRenderingContext2d::begin_path(self);
+    }
+
+    fn move_to(&mut self, x: f64, y: f64) {
+        CanvasRenderingContext2d::move_to(self, x, y);
+    }
+
+    fn line_to(&mut self, x: f64, y: f64) {
+        CanvasRenderingContext2d::line_to(self, x, y);
+    }
+
+    fn close_path(&mut self) {
+        CanvasRenderingContext2d::close_path(self);
+    }
+
+    fn arc(&mut self, x: f64, y: f64, radius: f64, start: f64, end: f64) -> Result<(), JsValue> {
+        CanvasRenderingContext2d::arc(self, x, y, radius, start, end)
+    }
+
+    fn ellipse(
+        &mut self,
+        x: f64,
+        y: f64,
+        radius_x: f64,
+        radius_y: f64,
+        rotation: f64,
+        start: f64,
+        end: f64,
+    ) -> Result<(), JsValue> {
+        CanvasRenderingContext2d::ellipse(self, x, y, radius_x, radius_y, rotation, start, end)
+    }
+
+    fn fill(&mut self) {
+        CanvasRenderingContext2d::fill(self);
+    }
+
+    fn stroke(&mut self) {
+        CanvasRenderingContext2d::stroke(self);
+    }
+
+    fn path_from_svg(&mut self, d: &str) -> Result<Path2d, JsValue> {
+        Path2d::new_with_path_string(d)
+    }
+
+    fn fill_path(&mut self, path: &Path2d) {
+        self.fill_with_path_2d(path);
+    }
+
+    fn stroke_path(&mut self, path: &Path2d) {
+        self.stroke_with_path(path);
+    }
+
+    fn set_font(&mut self, font: &str) {
+        CanvasRenderingContext2d::set_font(self, font);
+    }
+
+    fn set_text_align(&mut self, align: &str) {
+        CanvasRenderingContext2d::set_text_align(self, align);
+    }
+
+    fn set_text_baseline(&mut self, baseline: &str) {
+        CanvasRenderingContext2d::set_text_baseline(self, baseline);
+    }
+
+    fn fill_text(&mut self, text: &str, x: f64, y: f64) -> Result<(), JsValue> {
+        CanvasRenderingContext2d::fill_text(self, text, x, y)
+    }
+
+    fn measure_text(&mut self, text: &str) -> Result<f64, JsValue> {
+        CanvasRenderingContext2d::measure_text(self, text).map(|metrics| metrics.width())
+    }
+}
+
+// =============================================================
+// Recording
+// =============================================================
+
+/// Default advance per character for [`RecordingBackend::measure_text`],
+/// in current units.
+pub const DEFAULT_GLYPH_WIDTH: f64 = 8.0;
+
+/// One call made against a [`RecordingBackend`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCall {
+    Save,
+    Restore,
+    SetTransform([f64; 6]),
+    Transform([f64; 6]),
+    Translate(f64, f64),
+    Rotate(f64),
+    Scale(f64, f64),
+    SetFillStyle(String),
+    SetStrokeStyle(String),
+    SetLineWidth(f64),
+    SetLineDash(Vec<f64>),
+    SetGlobalAlpha(f64),
+    ClearRect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+    FillRect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+    StrokeRect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+    BeginPath,
+    MoveTo(f64, f64),
+    LineTo(f64, f64),
+    ClosePath,
+    Arc {
+        x: f64,
+        y: f64,
+        radius: f64,
+        start: f64,
+        end: f64,
+    },
+    Ellipse {
+        x: f64,
+        y: f64,
+        radius_x: f64,
+        radius_y: f64,
+        rotation: f64,
+        start: f64,
+        end: f64,
+    },
+    Fill,
+    Stroke,
+    /// Fill of a path built from SVG path data.
+    FillPath(String),
+    /// Stroke of a path built from SVG path data.
+    StrokePath(String),
+    SetFont(String),
+    SetTextAlign(String),
+    SetTextBaseline(String),
+    FillText {
+        text: String,
+        x: f64,
+        y: f64,
+    },
+}
+
+/// A backend that draws nothing and records every call in order.
+///
+/// Text is measured as a fixed advance per character, so wrapping and
+/// ellipsis decisions are deterministic without a font.
+#[derive(Debug, Clone)]
+pub struct RecordingBackend {
+    /// Calls made so far, oldest first.
+    pub calls: Vec<DrawCall>,
+    glyph_width: f64,
+}
+
+impl Default for RecordingBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RecordingBackend {
+    /// An empty recording measuring text at [`DEFAULT_GLYPH_WIDTH`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_glyph_width(DEFAULT_GLYPH_WIDTH)
+    }
+
+    /// An empty recording measuring text at `glyph_width` per character.
+    #[must_use]
+    pub fn with_glyph_width(glyph_width: f64) -> Self {
+        Self { calls: Vec::new(), glyph_width }
+    }
+
+    /// Text of every `fill_text` call, in order.
+    #[must_use]
+    pub fn texts(&self) -> Vec<&str> {
+        self.calls
+            .iter()
+            .filter_map(|call| match call {
+                DrawCall::FillText { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn record(&mut self, call: DrawCall) {
+        self.calls.push(call);
+    }
+}
+
+impl RenderBackend for RecordingBackend {
+    type Error = Infallible;
+    type Path = String;
+
+    fn save(&mut self) {
+        self.record(DrawCall::Save);
+    }
+
+    fn restore(&mut self) {
+        self.record(DrawCall::Restore);
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    fn set_transform(&mut self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Result<(), Infallible> {
+        self.record(DrawCall::SetTransform([a, b, c, d, e, f]));
+        Ok(())
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    fn transform(&mut self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Result<(), Infallible> {
+        self.record(DrawCall::Transform([a, b, c, d, e, f]));
+        Ok(())
+    }
+
+    fn translate(&mut self, x: f64, y: f64) -> Result<(), Infallible> {
+        self.record(DrawCall::Translate(x, y));
+        Ok(())
+    }
+
+    fn rotate(&mut self, angle: f64) -> Result<(), Infallible> {
+        self.record(DrawCall::Rotate(angle));
+        Ok(())
+    }
+
+    fn scale(&mut self, x: f64, y: f64) -> Result<(), Infallible> {
+        self.record(DrawCall::Scale(x, y));
+        Ok(())
+    }
+
+    fn set_fill_style(&mut self, color: &str) {
+        self.record(DrawCall::SetFillStyle(color.to_owned()));
+    }
+
+    fn set_stroke_style(&mut self, color: &str) {
+        self.record(DrawCall::SetStrokeStyle(color.to_owned()));
+    }
+
+    fn set_line_width(&mut self, width: f64) {
+        self.record(DrawCall::SetLineWidth(width));
+    }
+
+    fn set_line_dash(&mut self, segments: &[f64]) -> Result<(), Infallible> {
+        self.record(DrawCall::SetLineDash(segments.to_vec()));
+        Ok(())
+    }
+
+    fn set_global_alpha(&mut self, alpha: f64) {
+        self.record(DrawCall::SetGlobalAlpha(alpha));
+    }
+
+    fn clear_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(DrawCall::ClearRect { x, y, width, height });
+    }
+
+    fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(DrawCall::FillRect { x, y, width, height });
+    }
+
+    fn stroke_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(DrawCall::StrokeRect { x, y, width, height });
+    }
+
+    fn begin_path(&mut self) {
+        self.record(DrawCall::BeginPath);
+    }
+
+    fn move_to(&mut self, x: f64, y: f64) {
+        self.record(DrawCall::MoveTo(x, y));
+    }
+
+    fn line_to(&mut self, x: f64, y: f64) {
+        self.record(DrawCall::LineTo(x, y));
+    }
+
+    fn close_path(&mut self) {
+        self.record(DrawCall::ClosePath);
+    }
+
+    fn arc(&mut self, x: f64, y: f64, radius: f64, start: f64, end: f64) -> Result<(), Infallible> {
+        self.record(DrawCall::Arc { x, y, radius, start, end });
+        Ok(())
+    }
+
+    fn ellipse(
+        &mut self,
+        x: f64,
+        y: f64,
+        radius_x: f64,
+        radius_y: f64,
+        rotation: f64,
+        start: f64,
+        end: f64,
+    ) -> Result<(), Infallible> {
+        self.record(DrawCall::Ellipse { x, y, radius_x, radius_y, rotation, start, end });
+        Ok(())
+    }
+
+    fn fill(&mut self) {
+        self.record(DrawCall::Fill);
+    }
+
+    fn stroke(&mut self) {
+        self.record(DrawCall::Stroke);
+    }
+
+    fn path_from_svg(&mut self, d: &str) -> Result<String, Infallible> {
+        Ok(d.to_owned())
+    }
+
+    fn fill_path(&mut self, path: &String) {
+        self.record(DrawCall::FillPath(path.clone()));
+    }
+
+    fn stroke_path(&mut self, path: &String) {
+        self.record(DrawCall::StrokePath(path.clone()));
+    }
+
+    fn set_font(&mut self, font: &str) {
+        self.record(DrawCall::SetFont(font.to_owned()));
+    }
+
+    fn set_text_align(&mut self, align: &str) {
+        self.record(DrawCall::SetTextAlign(align.to_owned()));
+    }
+
+    fn set_text_baseline(&mut self, baseline: &str) {
+        self.record(DrawCall::SetTextBaseline(baseline.to_owned()));
+    }
+
+    fn fill_text(&mut self, text: &str, x: f64, y: f64) -> Result<(), Infallible> {
+        self.record(DrawCall::FillText { text: text.to_owned(), x, y });
+        Ok(())
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn measure_text(&mut self, text: &str) -> Result<f64, Infallible> {
+        Ok(text.chars().count() as f64 * self.glyph_width)
+    }
+}
@@ -0,0 +1,58 @@
+#![allow(clippy::float_cmp)]
+
+use super::*;
+
+#[test]
+fn recording_backend_records_calls_in_order() {
+    let mut backend = RecordingBackend::new();
+    backend.save();
+    backend.translate(10.0, 20.0).unwrap();
+    backend.set_fill_style("#fff");
+    backend.fill_rect(0.0, 0.0, 5.0, 5.0);
+    backend.set_line_dash(&[4.0, 4.0]).unwrap();
+    backend.restore();
+    assert_eq!(
+        backend.calls,
+        vec![
+            DrawCall::Save,
+            DrawCall::Translate(10.0, 20.0),
+            DrawCall::SetFillStyle("#fff".to_owned()),
+            DrawCall::FillRect { x: 0.0, y: 0.0, width: 5.0, height: 5.0 },
+            DrawCall::SetLineDash(vec![4.0, 4.0]),
+            DrawCall::Restore,
+        ]
+    );
+}
+
+#[test]
+fn recording_backend_keeps_svg_path_data() {
+    let mut backend = RecordingBackend::new();
+    let path = backend.path_from_svg("M0,0L1,1").unwrap();
+    backend.fill_path(&path);
+    backend.stroke_path(&path);
+    assert_eq!(
+        backend.calls,
+        vec![
+            DrawCall::FillPath("M0,0L1,1".to_owned()),
+            DrawCall::StrokePath("M0,0L1,1".to_owned())
+        ]
+    );
+}
+
+#[test]
+fn recording_backend_measures_fixed_width_glyphs() {
+    let mut backend = RecordingBackend::with_glyph_width(5.0);
+    assert_eq!(backend.measure_text("abcd").unwrap(), 20.0);
+    assert_eq!(backend.measure_text("").unwrap(), 0.0);
+    assert_eq!(RecordingBackend::new().measure_text("ab").unwrap(), 2.0 * DEFAULT_GLYPH_WIDTH);
+    assert!(backend.calls.is_empty());
+}
+
+#[test]
+fn recording_backend_lists_filled_text() {
+    let mut backend = RecordingBackend::new();
+    backend.fill_text("one", 0.0, 0.0).unwrap();
+    backend.fill_rect(0.0, 0.0, 1.0, 1.0);
+    backend.fill_text("two", 0.0, 10.0).unwrap();
+    assert_eq!(backend.texts(), vec!["one", "two"]);
+}
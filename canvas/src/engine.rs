@@ -5,11 +5,12 @@ use crate::camera::{Camera, Point};
 use crate::consts::{MIN_SHAPE_SIZE, ZOOM_FACTOR, ZOOM_MAX, ZOOM_MIN};
 use crate::doc::{BoardObject, DocStore, ObjectId, ObjectKind, PartialBoardObject, Props, WorldBounds};
 use crate::hit::{self, EdgeEnd, HitPart, ResizeAnchor};
-use crate::input::{Button, DragAxis, InputState, Key, Modifiers, SelectionRect, Tool, UiState, WheelDelta};
+use crate::input::{
+    Button, DragAxis, GhostOverlay, InputState, Key, Modifiers, SelectionRect, Tool, UiState, WheelDelta,
+};
 use crate::render;
 
 const EDGE_ATTACH_SNAP_PX: f64 = 16.0;
-const UNDO_STACK_LIMIT: usize = 64;
 
 #[cfg(test)]
 #[path = "engine_test.rs"]
@@ -49,6 +50,10 @@ pub enum Action {
     SetCursor(String),
     /// The canvas has changed and the host should call `render()`.
     RenderNeeded,
+    /// The user pressed Cmd/Ctrl+Z; the host should ask the server to undo.
+    UndoRequested,
+    /// The user pressed Cmd/Ctrl+Shift+Z; the host should ask the server to redo.
+    RedoRequested,
 }
 
 /// Core engine state — all logic that doesn't depend on the canvas element.
@@ -71,10 +76,6 @@ pub struct EngineCore {
     pub viewport_height: f64,
     /// Device pixel ratio, used to scale canvas backing store.
     pub dpr: f64,
-    /// Bounded stack of local snapshots used by Cmd/Ctrl+Z.
-    undo_stack: Vec<UndoSnapshot>,
-    /// Snapshot captured at gesture start and committed on pointer-up if mutated.
-    pending_gesture_undo: Option<UndoSnapshot>,
 }
 
 impl Default for EngineCore {
@@ -87,18 +88,10 @@ impl Default for EngineCore {
             viewport_width: 0.0,
             viewport_height: 0.0,
             dpr: 1.0,
-            undo_stack: Vec::new(),
-            pending_gesture_undo: None,
         }
     }
 }
 
-#[derive(Clone)]
-struct UndoSnapshot {
-    objects: Vec<BoardObject>,
-    selected_ids: Vec<ObjectId>,
-}
-
 impl EngineCore {
     /// Create a new engine core with an empty document and default camera.
     #[must_use]
@@ -111,8 +104,6 @@ impl EngineCore {
     /// Hydrate the document from a server snapshot.
     pub fn load_snapshot(&mut self, objects: Vec<BoardObject>) {
         self.doc.load_snapshot(objects);
-        self.undo_stack.clear();
-        self.pending_gesture_undo = None;
     }
 
     /// Apply a server broadcast: object created.
@@ -142,6 +133,12 @@ impl EngineCore {
         self.ui.selected_ids.remove(id);
     }
 
+    /// Replace the previewed changes drawn over the board; an empty overlay
+    /// clears it.
+    pub fn set_ghosts(&mut self, ghosts: GhostOverlay) {
+        self.ui.ghosts = ghosts;
+    }
+
     // --- Tool / text ---
 
     /// Set the active tool.
@@ -160,7 +157,6 @@ impl EngineCore {
         let Some(obj) = self.doc.get(id) else {
             return Action::None;
         };
-        let undo_before = self.capture_undo_snapshot();
 
         let existing = Props::new(&obj.props);
         if existing.head() == head && existing.text() == text && existing.foot() == foot {
@@ -176,7 +172,6 @@ impl EngineCore {
             ..Default::default()
         };
         if self.doc.apply_partial(id, &partial) {
-            self.push_undo_snapshot(undo_before);
             Action::ObjectUpdated { id: *id, fields: partial }
         } else {
             Action::None
@@ -209,7 +204,6 @@ impl EngineCore {
     pub fn on_pointer_down(&mut self, screen_pt: Point, button: Button, modifiers: Modifiers) -> Vec<Action> {
         let world_pt = self.screen_to_world(screen_pt);
         let mut actions = Vec::new();
-        let undo_before = self.capture_undo_snapshot();
 
         // Middle button, space+drag, or hand tool always pans.
         if button == Button::Middle || (button == Button::Primary && (self.ui.space_pan || self.ui.tool == Tool::Hand))
@@ -237,10 +231,6 @@ impl EngineCore {
             _ => {}
         }
 
-        if self.pending_gesture_undo.is_none() && self.is_mutating_input_state() {
-            self.pending_gesture_undo = Some(undo_before);
-        }
-
         actions
     }
 
@@ -427,14 +417,6 @@ impl EngineCore {
             }
         }
 
-        if Self::actions_have_doc_mutation(&actions) {
-            if let Some(snapshot) = self.pending_gesture_undo.take() {
-                self.push_undo_snapshot(snapshot);
-            }
-        } else {
-            self.pending_gesture_undo = None;
-        }
-
         actions
     }
 
@@ -471,7 +453,6 @@ impl EngineCore {
                 self.ui.space_pan = true;
             }
             "Delete" | "Backspace" => {
-                let undo_before = self.capture_undo_snapshot();
                 let selected = self.ui.selected_ids.iter().copied().collect::<Vec<_>>();
                 let mut deleted_any = false;
                 for id in selected {
@@ -481,7 +462,6 @@ impl EngineCore {
                     deleted_any = true;
                 }
                 if deleted_any {
-                    self.push_undo_snapshot(undo_before);
                     actions.push(Action::RenderNeeded);
                 }
             }
@@ -519,7 +499,6 @@ impl EngineCore {
                 actions.push(Action::RenderNeeded);
             }
             "g" | "G" if accel && modifiers.shift => {
-                let undo_before = self.capture_undo_snapshot();
                 let mut changed_any = false;
                 for id in self.ui.selected_ids.iter().copied().collect::<Vec<_>>() {
                     let partial = PartialBoardObject { group_id: Some(None), ..Default::default() };
@@ -529,13 +508,11 @@ impl EngineCore {
                     }
                 }
                 if changed_any {
-                    self.push_undo_snapshot(undo_before);
                     actions.push(Action::RenderNeeded);
                 }
             }
             "g" | "G" if accel => {
                 if self.ui.selected_ids.len() >= 2 {
-                    let undo_before = self.capture_undo_snapshot();
                     let group_id = uuid::Uuid::new_v4();
                     let mut changed_any = false;
                     for id in self.ui.selected_ids.iter().copied().collect::<Vec<_>>() {
@@ -546,14 +523,19 @@ impl EngineCore {
                         }
                     }
                     if changed_any {
-                        self.push_undo_snapshot(undo_before);
                         actions.push(Action::RenderNeeded);
                     }
                 }
             }
-            "z" | "Z" if accel && !modifiers.shift => return self.undo(),
+            "z" | "Z" if accel => {
+                // History is server-side and per user; the host sends the request.
+                actions.push(if modifiers.shift {
+                    Action::RedoRequested
+                } else {
+                    Action::UndoRequested
+                });
+            }
             "ArrowUp" | "ArrowDown" | "ArrowLeft" | "ArrowRight" => {
-                let undo_before = self.capture_undo_snapshot();
                 let step = if modifiers.shift { 10.0 } else { 1.0 };
                 let (dx, dy) = match key.0.as_str() {
                     "ArrowUp" => (0.0, -step),
@@ -574,7 +556,6 @@ impl EngineCore {
                     }
                 }
                 if changed_any {
-                    self.push_undo_snapshot(undo_before);
                     actions.push(Action::RenderNeeded);
                 }
             }
@@ -618,99 +599,6 @@ impl EngineCore {
         self.doc.get(id)
     }
 
-    fn undo(&mut self) -> Vec<Action> {
-        let Some(target) = self.undo_stack.pop() else {
-            return Vec::new();
-        };
-        self.pending_gesture_undo = None;
-        self.input = InputState::Idle;
-        self.ui.marquee = None;
-
-        let mut actions = Vec::new();
-        let current = self.snapshot_objects_map();
-        let target_map = target
-            .objects
-            .iter()
-            .cloned()
-            .map(|obj| (obj.id, obj))
-            .collect::<std::collections::HashMap<_, _>>();
-
-        for id in current.keys() {
-            if !target_map.contains_key(id) {
-                self.doc.remove(id);
-                self.ui.selected_ids.remove(id);
-                actions.push(Action::ObjectDeleted { id: *id });
-            }
-        }
-
-        for (id, target_obj) in &target_map {
-            match current.get(id) {
-                None => {
-                    self.doc.insert(target_obj.clone());
-                    actions.push(Action::ObjectCreated(target_obj.clone()));
-                }
-                Some(current_obj) => {
-                    if let Some(partial) = diff_partial(current_obj, target_obj) {
-                        self.doc.apply_partial(id, &partial);
-                        actions.push(Action::ObjectUpdated { id: *id, fields: partial });
-                    }
-                }
-            }
-        }
-
-        self.ui.selected_ids = target
-            .selected_ids
-            .into_iter()
-            .filter(|id| self.doc.get(id).is_some())
-            .collect();
-        actions.push(Action::RenderNeeded);
-        actions
-    }
-
-    fn capture_undo_snapshot(&self) -> UndoSnapshot {
-        UndoSnapshot {
-            objects: self.doc.sorted_objects().into_iter().cloned().collect(),
-            selected_ids: self.ui.selected_ids.iter().copied().collect(),
-        }
-    }
-
-    fn push_undo_snapshot(&mut self, snapshot: UndoSnapshot) {
-        self.pending_gesture_undo = None;
-        self.undo_stack.push(snapshot);
-        if self.undo_stack.len() > UNDO_STACK_LIMIT {
-            let drain = self.undo_stack.len() - UNDO_STACK_LIMIT;
-            self.undo_stack.drain(0..drain);
-        }
-    }
-
-    fn is_mutating_input_state(&self) -> bool {
-        matches!(
-            self.input,
-            InputState::DraggingObject { .. }
-                | InputState::DrawingShape { .. }
-                | InputState::ResizingObject { .. }
-                | InputState::RotatingObject { .. }
-                | InputState::DraggingEdgeEndpoint { .. }
-        )
-    }
-
-    fn actions_have_doc_mutation(actions: &[Action]) -> bool {
-        actions.iter().any(|a| {
-            matches!(
-                a,
-                Action::ObjectCreated(_) | Action::ObjectUpdated { .. } | Action::ObjectDeleted { .. }
-            )
-        })
-    }
-
-    fn snapshot_objects_map(&self) -> std::collections::HashMap<ObjectId, BoardObject> {
-        self.doc
-            .sorted_objects()
-            .into_iter()
-            .map(|obj| (obj.id, obj.clone()))
-            .collect()
-    }
-
     // =============================================================
     // Private helpers
     // =============================================================
@@ -1206,65 +1094,6 @@ impl EngineCore {
     }
 }
 
-fn diff_partial(current: &BoardObject, target: &BoardObject) -> Option<PartialBoardObject> {
-    let mut partial = PartialBoardObject::default();
-    let mut changed = false;
-
-    if (current.x - target.x).abs() > f64::EPSILON {
-        partial.x = Some(target.x);
-        changed = true;
-    }
-    if (current.y - target.y).abs() > f64::EPSILON {
-        partial.y = Some(target.y);
-        changed = true;
-    }
-    if (current.width - target.width).abs() > f64::EPSILON {
-        partial.width = Some(target.width);
-        changed = true;
-    }
-    if (current.height - target.height).abs() > f64::EPSILON {
-        partial.height = Some(target.height);
-        changed = true;
-    }
-    if (current.rotation - target.rotation).abs() > f64::EPSILON {
-        partial.rotation = Some(target.rotation);
-        changed = true;
-    }
-    if current.z_index != target.z_index {
-        partial.z_index = Some(target.z_index);
-        changed = true;
-    }
-    if current.group_id != target.group_id {
-        partial.group_id = Some(target.group_id);
-        changed = true;
-    }
-
-    if current.props != target.props {
-        if let (Some(cur), Some(next)) = (current.props.as_object(), target.props.as_object()) {
-            let mut patch = serde_json::Map::new();
-            for (k, next_v) in next {
-                if cur.get(k) != Some(next_v) {
-                    patch.insert(k.clone(), next_v.clone());
-                }
-            }
-            for k in cur.keys() {
-                if !next.contains_key(k) {
-                    patch.insert(k.clone(), serde_json::Value::Null);
-                }
-            }
-            if !patch.is_empty() {
-                partial.props = Some(serde_json::Value::Object(patch));
-                changed = true;
-            }
-        } else {
-            partial.props = Some(target.props.clone());
-            changed = true;
-        }
-    }
-
-    if changed { Some(partial) } else { None }
-}
-
 fn normalize_angle_delta(delta: f64) -> f64 {
     ((delta + 180.0).rem_euclid(360.0)) - 180.0
 }
@@ -1368,6 +1197,11 @@ impl Engine {
         self.core.apply_delete(id);
     }
 
+    /// Replace the previewed changes drawn over the board.
+    pub fn set_ghosts(&mut self, ghosts: GhostOverlay) {
+        self.core.set_ghosts(ghosts);
+    }
+
     /// Set the active tool.
     pub fn set_tool(&mut self, tool: Tool) {
         self.core.set_tool(tool);
@@ -1436,7 +1270,7 @@ impl Engine {
     ///
     /// Returns `Err` if the 2D context cannot be obtained or any `Canvas2D` call fails.
     pub fn render(&self) -> Result<(), wasm_bindgen::JsValue> {
-        let ctx: CanvasRenderingContext2d = self
+        let mut ctx: CanvasRenderingContext2d = self
             .canvas
             .get_context("2d")?
             .ok_or_else(|| wasm_bindgen::JsValue::from_str("no 2d context"))?
@@ -1451,7 +1285,7 @@ impl Engine {
         self.canvas.set_height(phys_h);
 
         render::draw(
-            &ctx,
+            &mut ctx,
             &self.core.doc,
             &self.core.camera,
             &self.core.ui,
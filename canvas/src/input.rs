@@ -11,7 +11,7 @@
 mod input_test;
 
 use crate::camera::Point;
-use crate::doc::ObjectId;
+use crate::doc::{BoardObject, ObjectId};
 use crate::hit::{EdgeEnd, ResizeAnchor};
 use std::collections::HashSet;
 
@@ -104,6 +104,27 @@ pub struct UiState {
     pub marquee: Option<SelectionRect>,
     /// True while space is held to temporarily pan.
     pub space_pan: bool,
+    /// Proposed changes drawn over the board but not yet applied.
+    pub ghosts: GhostOverlay,
+}
+
+/// A pending preview of document changes, drawn translucently on top of the
+/// live objects until it is applied or discarded.
+#[derive(Debug, Clone, Default)]
+pub struct GhostOverlay {
+    /// Objects as they would look after the change: new objects and updated
+    /// copies of existing ones.
+    pub objects: Vec<BoardObject>,
+    /// Existing objects the change would delete.
+    pub deleted: Vec<ObjectId>,
+}
+
+impl GhostOverlay {
+    /// True when there is nothing to draw.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty() && self.deleted.is_empty()
+    }
 }
 
 /// World-space marquee rectangle.
@@ -3,7 +3,7 @@
 //! This crate is compiled to WebAssembly and runs in the browser. It owns the
 //! full lifecycle of the canvas: translating raw DOM input events into board
 //! mutations, maintaining camera state for pan/zoom, hit-testing objects, and
-//! (eventually) rendering the scene. The host JavaScript layer is responsible
+//! rendering the scene. The host JavaScript layer is responsible
 //! only for wiring DOM events to the engine and persisting the resulting
 //! [`engine::Action`]s to the server.
 //!
@@ -16,9 +16,11 @@
 //! | [`camera`] | Pan/zoom camera and coordinate conversions |
 //! | [`input`] | Input event types and the gesture state machine |
 //! | [`hit`] | Hit-testing against board objects |
-//! | [`render`] | Scene rendering (stub — not yet implemented) |
+//! | [`render`] | Scene rendering, generic over a [`backend::RenderBackend`] |
+//! | [`backend`] | Drawing surfaces: the web canvas and a call recorder for tests |
 //! | [`consts`] | Shared numeric constants (zoom limits, minimum sizes, etc.) |
 
+pub mod backend;
 pub mod camera;
 pub mod consts;
 pub mod doc;
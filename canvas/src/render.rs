@@ -1,50 +1,68 @@
-//! Rendering: draws the full canvas scene to a 2D context.
+//! Rendering: draws the full canvas scene to a 2D drawing surface.
 //!
-//! This module is the only place that touches [`web_sys::CanvasRenderingContext2d`].
-//! It receives read-only views of document state and camera state and produces
-//! pixels — it does not mutate any application state.
+//! Every draw function is generic over [`RenderBackend`]; in the browser the
+//! backend is the page's `CanvasRenderingContext2d`, and tests use a
+//! [`crate::backend::RecordingBackend`]. This module receives read-only views
+//! of document state and camera state and produces pixels — it does not
+//! mutate any application state.
 //!
-//! All fallible `Canvas2D` calls propagate errors via `Result<(), JsValue>`.
+//! All fallible backend calls propagate errors via `Result<(), B::Error>`.
 //! The top-level caller ([`crate::engine::Engine::render`]) handles the result.
 
-use std::f64::consts::PI;
+#[cfg(test)]
+#[path = "render_test.rs"]
+mod render_test;
 
-use wasm_bindgen::JsValue;
-use web_sys::{CanvasRenderingContext2d, Path2d};
+use std::f64::consts::PI;
 
+use crate::backend::RenderBackend;
 use crate::camera::{Camera, Point};
 use crate::consts::{FRAC_PI_5, HANDLE_RADIUS_PX, STAR_INNER_RATIO};
 use crate::doc::{BoardObject, DocStore, ObjectKind, Props, WorldBounds};
 use crate::hit;
-use crate::input::UiState;
+use crate::input::{GhostOverlay, UiState};
 
 /// Arrowhead length in world units.
-const ARROW_SIZE: f64 = 10.0;
+pub const ARROW_SIZE: f64 = 10.0;
 
 /// Arrowhead half-angle in radians (~30°).
-const ARROW_ANGLE: f64 = PI / 6.0;
+pub const ARROW_ANGLE: f64 = PI / 6.0;
 
 /// Selection dash segment length in screen pixels.
 const SELECTION_DASH_PX: f64 = 4.0;
 /// Small visual marker for an endpoint attached to another shape.
-const ATTACHED_ANCHOR_RADIUS_WORLD: f64 = 3.0;
-
-/// Draw the full scene: objects and selection UI.
+pub const ATTACHED_ANCHOR_RADIUS_WORLD: f64 = 3.0;
+
+/// Opacity of previewed objects drawn by the ghost overlay.
+pub const GHOST_ALPHA: f64 = 0.45;
+/// Outline of a previewed create or update.
+const GHOST_OUTLINE_COLOR: &str = "#7C3AED";
+/// Outline of an existing object a preview would delete.
+const GHOST_DELETE_COLOR: &str = "#DC2626";
+
+/// Frame body fill, very subtle so children remain visible.
+pub const FRAME_BODY_FILL: &str = "rgba(60, 64, 70, 0.06)";
+/// Frame header band fill.
+pub const FRAME_HEADER_FILL: &str = "rgba(31, 26, 23, 0.16)";
+/// Frame title text color.
+pub const FRAME_TITLE_COLOR: &str = "#1F1A17";
+
+/// Draw the full scene: objects, previewed changes, and selection UI.
 ///
 /// `viewport_w` and `viewport_h` are in CSS pixels. `dpr` is the device pixel ratio.
 ///
 /// # Errors
 ///
-/// Returns `Err` if any `Canvas2D` call fails (e.g. invalid context state).
-pub fn draw(
-    ctx: &CanvasRenderingContext2d,
+/// Returns `Err` if any backend call fails (e.g. invalid context state).
+pub fn draw<B: RenderBackend>(
+    ctx: &mut B,
     doc: &DocStore,
     camera: &Camera,
     ui: &UiState,
     viewport_w: f64,
     viewport_h: f64,
     dpr: f64,
-) -> Result<(), JsValue> {
+) -> Result<(), B::Error> {
     let viewport_center = Point::new(viewport_w * 0.5, viewport_h * 0.5);
     let viewport_bounds = viewport_world_bounds(camera, viewport_w, viewport_h, viewport_center);
     let visible = doc.sorted_objects_in_bounds(viewport_bounds);
@@ -75,7 +93,12 @@ pub fn draw(
         draw_object(ctx, obj, doc)?;
     }
 
-    // Layer 4: selection UI overlays.
+    // Layer 4: previewed changes that have not been applied yet.
+    if !ui.ghosts.is_empty() {
+        draw_ghosts(ctx, &ui.ghosts, doc, camera.zoom)?;
+    }
+
+    // Layer 5: selection UI overlays.
     let selected = ui.selected_ids.iter().copied().collect::<Vec<_>>();
     let show_handles = selected.len() == 1;
     for sel_id in selected {
@@ -121,7 +144,7 @@ fn viewport_world_bounds(camera: &Camera, viewport_w: f64, viewport_h: f64, view
 // Object dispatch
 // =============================================================
 
-fn draw_object(ctx: &CanvasRenderingContext2d, obj: &BoardObject, doc: &DocStore) -> Result<(), JsValue> {
+fn draw_object<B: RenderBackend>(ctx: &mut B, obj: &BoardObject, doc: &DocStore) -> Result<(), B::Error> {
     let props = Props::new(&obj.props);
 
     match obj.kind {
@@ -139,7 +162,7 @@ fn draw_object(ctx: &CanvasRenderingContext2d, obj: &BoardObject, doc: &DocStore
     }
 }
 
-fn draw_text_object(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_>) -> Result<(), JsValue> {
+fn draw_text_object<B: RenderBackend>(ctx: &mut B, obj: &BoardObject, props: &Props<'_>) -> Result<(), B::Error> {
     ctx.save();
     translate_and_rotate(ctx, obj)?;
     draw_text(ctx, obj, props)?;
@@ -151,11 +174,11 @@ fn draw_text_object(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &P
 // Shape renderers
 // =============================================================
 
-fn draw_rect(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_>) -> Result<(), JsValue> {
+fn draw_rect<B: RenderBackend>(ctx: &mut B, obj: &BoardObject, props: &Props<'_>) -> Result<(), B::Error> {
     ctx.save();
     translate_and_rotate(ctx, obj)?;
 
-    ctx.set_fill_style_str(props.fill());
+    ctx.set_fill_style(props.fill());
     ctx.fill_rect(-obj.width / 2.0, -obj.height / 2.0, obj.width, obj.height);
 
     apply_stroke_style(ctx, props);
@@ -166,7 +189,7 @@ fn draw_rect(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_
     Ok(())
 }
 
-fn draw_frame(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_>) -> Result<(), JsValue> {
+fn draw_frame<B: RenderBackend>(ctx: &mut B, obj: &BoardObject, props: &Props<'_>) -> Result<(), B::Error> {
     ctx.save();
     translate_and_rotate(ctx, obj)?;
 
@@ -175,7 +198,7 @@ fn draw_frame(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'
     let y = -obj.height * 0.5;
 
     // Body fill is very subtle so children remain visible.
-    ctx.set_fill_style_str("rgba(60, 64, 70, 0.06)");
+    ctx.set_fill_style(FRAME_BODY_FILL);
     ctx.fill_rect(x, y, obj.width, obj.height);
 
     // Border.
@@ -183,7 +206,7 @@ fn draw_frame(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'
     ctx.stroke_rect(x, y, obj.width, obj.height);
 
     // Header band.
-    ctx.set_fill_style_str("rgba(31, 26, 23, 0.16)");
+    ctx.set_fill_style(FRAME_HEADER_FILL);
     ctx.fill_rect(x, y, obj.width, title_h);
 
     // Title
@@ -192,7 +215,7 @@ fn draw_frame(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'
         .get("title")
         .and_then(serde_json::Value::as_str)
         .unwrap_or("Frame");
-    ctx.set_fill_style_str("#1F1A17");
+    ctx.set_fill_style(FRAME_TITLE_COLOR);
     ctx.set_text_align("left");
     ctx.set_text_baseline("middle");
     let font_size = (title_h * 0.45).clamp(10.0, 14.0);
@@ -203,7 +226,7 @@ fn draw_frame(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'
     Ok(())
 }
 
-fn draw_ellipse(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_>) -> Result<(), JsValue> {
+fn draw_ellipse<B: RenderBackend>(ctx: &mut B, obj: &BoardObject, props: &Props<'_>) -> Result<(), B::Error> {
     if obj.width <= 0.0 || obj.height <= 0.0 {
         return Ok(());
     }
@@ -213,7 +236,7 @@ fn draw_ellipse(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props
     ctx.begin_path();
     ctx.ellipse(0.0, 0.0, obj.width / 2.0, obj.height / 2.0, 0.0, 0.0, 2.0 * PI)?;
 
-    ctx.set_fill_style_str(props.fill());
+    ctx.set_fill_style(props.fill());
     ctx.fill();
 
     apply_stroke_style(ctx, props);
@@ -224,15 +247,15 @@ fn draw_ellipse(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props
     Ok(())
 }
 
-fn draw_svg_placeholder(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_>) -> Result<(), JsValue> {
-    if draw_inline_svg_paths(ctx, obj).is_ok() {
+fn draw_svg_placeholder<B: RenderBackend>(ctx: &mut B, obj: &BoardObject, props: &Props<'_>) -> Result<(), B::Error> {
+    if matches!(draw_inline_svg_paths(ctx, obj), Ok(true)) {
         return Ok(());
     }
 
     ctx.save();
     translate_and_rotate(ctx, obj)?;
 
-    ctx.set_fill_style_str(props.fill());
+    ctx.set_fill_style(props.fill());
     ctx.fill_rect(-obj.width / 2.0, -obj.height / 2.0, obj.width, obj.height);
     apply_stroke_style(ctx, props);
     ctx.stroke_rect(-obj.width / 2.0, -obj.height / 2.0, obj.width, obj.height);
@@ -242,7 +265,7 @@ fn draw_svg_placeholder(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props
         .get("title")
         .and_then(serde_json::Value::as_str)
         .unwrap_or("SVG");
-    ctx.set_fill_style_str(props.text_color());
+    ctx.set_fill_style(props.text_color());
     ctx.set_text_align("center");
     ctx.set_text_baseline("middle");
     let font_size = (obj.height / 5.0).clamp(10.0, 20.0);
@@ -253,13 +276,15 @@ fn draw_svg_placeholder(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props
     Ok(())
 }
 
-fn draw_inline_svg_paths(ctx: &CanvasRenderingContext2d, obj: &BoardObject) -> Result<(), JsValue> {
+/// Draw the object's inline SVG markup. Returns `false` without drawing when
+/// there is no markup or nothing in it is renderable.
+fn draw_inline_svg_paths<B: RenderBackend>(ctx: &mut B, obj: &BoardObject) -> Result<bool, B::Error> {
     let Some(svg) = obj.props.get("svg").and_then(serde_json::Value::as_str) else {
-        return Err(JsValue::from_str("missing svg prop"));
+        return Ok(false);
     };
     let shapes = parse_svg_shapes(svg);
     if shapes.is_empty() {
-        return Err(JsValue::from_str("no renderable elements"));
+        return Ok(false);
     }
 
     let (vb_min_x, vb_min_y, vb_w, vb_h) = svg_view_box(svg).unwrap_or((0.0, 0.0, obj.width, obj.height));
@@ -280,14 +305,14 @@ fn draw_inline_svg_paths(ctx: &CanvasRenderingContext2d, obj: &BoardObject) -> R
     }
 
     ctx.restore();
-    Ok(())
+    Ok(true)
 }
 
-/// Render a single `SvgShape` to the canvas context.
-fn render_svg_shape(ctx: &CanvasRenderingContext2d, shape: &SvgShape, stroke_scale: f64) -> Result<(), JsValue> {
+/// Render a single `SvgShape` to the backend.
+fn render_svg_shape<B: RenderBackend>(ctx: &mut B, shape: &SvgShape, stroke_scale: f64) -> Result<(), B::Error> {
     match &shape.geometry {
         SvgGeometry::Path(d) => {
-            let path = Path2d::new_with_path_string(d)?;
+            let path = ctx.path_from_svg(d)?;
             svg_fill_stroke(
                 ctx,
                 &path,
@@ -298,7 +323,7 @@ fn render_svg_shape(ctx: &CanvasRenderingContext2d, shape: &SvgShape, stroke_sca
             );
         }
         SvgGeometry::Rect { x, y, width, height, rx, ry } => {
-            let path = svg_rect_path(*x, *y, *width, *height, *rx, *ry)?;
+            let path = ctx.path_from_svg(&svg_rect_path(*x, *y, *width, *height, *rx, *ry))?;
             svg_fill_stroke(
                 ctx,
                 &path,
@@ -309,7 +334,7 @@ fn render_svg_shape(ctx: &CanvasRenderingContext2d, shape: &SvgShape, stroke_sca
             );
         }
         SvgGeometry::Circle { cx, cy, r } => {
-            let path = svg_circle_path(*cx, *cy, *r)?;
+            let path = ctx.path_from_svg(&svg_circle_path(*cx, *cy, *r))?;
             svg_fill_stroke(
                 ctx,
                 &path,
@@ -320,7 +345,7 @@ fn render_svg_shape(ctx: &CanvasRenderingContext2d, shape: &SvgShape, stroke_sca
             );
         }
         SvgGeometry::Ellipse { cx, cy, rx, ry } => {
-            let path = svg_ellipse_path(*cx, *cy, *rx, *ry)?;
+            let path = ctx.path_from_svg(&svg_ellipse_path(*cx, *cy, *rx, *ry))?;
             svg_fill_stroke(
                 ctx,
                 &path,
@@ -332,14 +357,14 @@ fn render_svg_shape(ctx: &CanvasRenderingContext2d, shape: &SvgShape, stroke_sca
         }
         SvgGeometry::Line { x1, y1, x2, y2 } => {
             let d = format!("M{x1},{y1}L{x2},{y2}");
-            let path = Path2d::new_with_path_string(&d)?;
+            let path = ctx.path_from_svg(&d)?;
             // Lines have no fill by default.
             let stroke = shape.stroke.as_deref().unwrap_or("#000000");
             if stroke != "none" {
-                ctx.set_stroke_style_str(stroke);
+                ctx.set_stroke_style(stroke);
                 let width = shape.stroke_width.unwrap_or(1.0) / stroke_scale;
                 ctx.set_line_width(width.max(0.1));
-                ctx.stroke_with_path(&path);
+                ctx.stroke_path(&path);
             }
         }
         SvgGeometry::Polygon(pts) | SvgGeometry::Polyline(pts) => {
@@ -352,7 +377,7 @@ fn render_svg_shape(ctx: &CanvasRenderingContext2d, shape: &SvgShape, stroke_sca
                 if matches!(shape.geometry, SvgGeometry::Polygon(_)) {
                     d.push('Z');
                 }
-                let path = Path2d::new_with_path_string(&d)?;
+                let path = ctx.path_from_svg(&d)?;
                 svg_fill_stroke(
                     ctx,
                     &path,
@@ -366,7 +391,7 @@ fn render_svg_shape(ctx: &CanvasRenderingContext2d, shape: &SvgShape, stroke_sca
         SvgGeometry::Text { x, y, content, font_size } => {
             let fill = shape.fill.as_deref().unwrap_or("#000000");
             if fill != "none" {
-                ctx.set_fill_style_str(fill);
+                ctx.set_fill_style(fill);
                 let size = font_size.unwrap_or(16.0);
                 ctx.set_font(&format!("{size:.0}px sans-serif"));
                 ctx.set_text_baseline("auto");
@@ -389,35 +414,35 @@ fn render_svg_shape(ctx: &CanvasRenderingContext2d, shape: &SvgShape, stroke_sca
     Ok(())
 }
 
-/// Apply fill and stroke to a `Path2d`.
-fn svg_fill_stroke(
-    ctx: &CanvasRenderingContext2d,
-    path: &Path2d,
+/// Apply fill and stroke to a backend path.
+fn svg_fill_stroke<B: RenderBackend>(
+    ctx: &mut B,
+    path: &B::Path,
     fill: Option<&str>,
     stroke: Option<&str>,
     stroke_width: Option<f64>,
     stroke_scale: f64,
 ) {
     if fill != Some("none") {
-        ctx.set_fill_style_str(fill.unwrap_or("#000000"));
-        ctx.fill_with_path_2d(path);
+        ctx.set_fill_style(fill.unwrap_or("#000000"));
+        ctx.fill_path(path);
     }
     if let Some(s) = stroke {
         if s != "none" {
-            ctx.set_stroke_style_str(s);
+            ctx.set_stroke_style(s);
             let width = stroke_width.unwrap_or(1.0) / stroke_scale;
             ctx.set_line_width(width.max(0.1));
-            ctx.stroke_with_path(path);
+            ctx.stroke_path(path);
         }
     }
 }
 
 #[allow(clippy::many_single_char_names)]
-fn svg_rect_path(x: f64, y: f64, w: f64, h: f64, rx: f64, ry: f64) -> Result<Path2d, JsValue> {
+fn svg_rect_path(x: f64, y: f64, w: f64, h: f64, rx: f64, ry: f64) -> String {
     if rx > 0.0 || ry > 0.0 {
         let rx = rx.max(ry).min(w / 2.0);
         let ry = ry.max(rx).min(h / 2.0);
-        let d = format!(
+        format!(
             "M{},{} h{} a{rx},{ry} 0 0 1 {rx},{ry} v{} a{rx},{ry} 0 0 1 -{rx},{ry} h-{} a{rx},{ry} 0 0 1 -{rx},-{ry} v-{} a{rx},{ry} 0 0 1 {rx},-{ry} Z",
             x + rx,
             y,
@@ -425,33 +450,29 @@ fn svg_rect_path(x: f64, y: f64, w: f64, h: f64, rx: f64, ry: f64) -> Result<Pat
             h - 2.0 * ry,
             w - 2.0 * rx,
             h - 2.0 * ry,
-        );
-        Path2d::new_with_path_string(&d)
+        )
     } else {
-        let d = format!("M{x},{y}h{w}v{h}h-{w}Z");
-        Path2d::new_with_path_string(&d)
+        format!("M{x},{y}h{w}v{h}h-{w}Z")
     }
 }
 
-fn svg_circle_path(cx: f64, cy: f64, r: f64) -> Result<Path2d, JsValue> {
-    let d = format!("M{},{} a{r},{r} 0 1 0 {},0 a{r},{r} 0 1 0 {},0", cx - r, cy, 2.0 * r, -2.0 * r,);
-    Path2d::new_with_path_string(&d)
+fn svg_circle_path(cx: f64, cy: f64, r: f64) -> String {
+    format!("M{},{} a{r},{r} 0 1 0 {},0 a{r},{r} 0 1 0 {},0", cx - r, cy, 2.0 * r, -2.0 * r,)
 }
 
-fn svg_ellipse_path(cx: f64, cy: f64, rx: f64, ry: f64) -> Result<Path2d, JsValue> {
-    let d = format!(
+fn svg_ellipse_path(cx: f64, cy: f64, rx: f64, ry: f64) -> String {
+    format!(
         "M{},{} a{rx},{ry} 0 1 0 {},0 a{rx},{ry} 0 1 0 {},0",
         cx - rx,
         cy,
         2.0 * rx,
         -2.0 * rx,
-    );
-    Path2d::new_with_path_string(&d)
+    )
 }
 
-/// Apply a simple SVG `transform` string to the canvas context.
+/// Apply a simple SVG `transform` string to the backend.
 /// Supports: translate(x,y), scale(x,y), rotate(deg), matrix(a,b,c,d,e,f).
-fn apply_svg_transform(ctx: &CanvasRenderingContext2d, transform: &str) -> Result<(), JsValue> {
+fn apply_svg_transform<B: RenderBackend>(ctx: &mut B, transform: &str) -> Result<(), B::Error> {
     let mut scan = transform;
     while let Some(paren_start) = scan.find('(') {
         let func_name = scan[..paren_start].trim();
@@ -936,7 +957,7 @@ fn parse_svg_number(raw: &str) -> Option<f64> {
     try_parse_f64(value)
 }
 
-fn draw_diamond(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_>) -> Result<(), JsValue> {
+fn draw_diamond<B: RenderBackend>(ctx: &mut B, obj: &BoardObject, props: &Props<'_>) -> Result<(), B::Error> {
     ctx.save();
     translate_and_rotate(ctx, obj)?;
 
@@ -950,7 +971,7 @@ fn draw_diamond(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props
     ctx.line_to(-hw, 0.0); // left
     ctx.close_path();
 
-    ctx.set_fill_style_str(props.fill());
+    ctx.set_fill_style(props.fill());
     ctx.fill();
 
     apply_stroke_style(ctx, props);
@@ -962,7 +983,7 @@ fn draw_diamond(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props
 }
 
 #[allow(clippy::similar_names)]
-fn draw_star(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_>) -> Result<(), JsValue> {
+fn draw_star<B: RenderBackend>(ctx: &mut B, obj: &BoardObject, props: &Props<'_>) -> Result<(), B::Error> {
     if obj.width <= 0.0 || obj.height <= 0.0 {
         return Ok(());
     }
@@ -994,7 +1015,7 @@ fn draw_star(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_
     }
     ctx.close_path();
 
-    ctx.set_fill_style_str(props.fill());
+    ctx.set_fill_style(props.fill());
     ctx.fill();
 
     apply_stroke_style(ctx, props);
@@ -1009,7 +1030,7 @@ fn draw_star(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_
 // Edge renderers
 // =============================================================
 
-fn draw_edge(ctx: &CanvasRenderingContext2d, obj: &BoardObject, doc: &DocStore, props: &Props<'_>, arrowhead: bool) {
+fn draw_edge<B: RenderBackend>(ctx: &mut B, obj: &BoardObject, doc: &DocStore, props: &Props<'_>, arrowhead: bool) {
     let Some(a) = hit::edge_endpoint_a_resolved(obj, doc) else {
         return;
     };
@@ -1033,7 +1054,7 @@ fn draw_edge(ctx: &CanvasRenderingContext2d, obj: &BoardObject, doc: &DocStore,
     }
 
     // Attachment marker in normal mode so snapped endpoints are visible.
-    ctx.set_fill_style_str("#fff");
+    ctx.set_fill_style("#fff");
     for (pt, attached) in [(a, a_attached), (b, b_attached)] {
         if !attached {
             continue;
@@ -1050,7 +1071,7 @@ fn draw_edge(ctx: &CanvasRenderingContext2d, obj: &BoardObject, doc: &DocStore,
     ctx.restore();
 }
 
-fn draw_arrowhead(ctx: &CanvasRenderingContext2d, tip_x: f64, tip_y: f64, angle: f64) {
+fn draw_arrowhead<B: RenderBackend>(ctx: &mut B, tip_x: f64, tip_y: f64, angle: f64) {
     let x1 = tip_x - ARROW_SIZE * (angle - ARROW_ANGLE).cos();
     let y1 = tip_y - ARROW_SIZE * (angle - ARROW_ANGLE).sin();
     let x2 = tip_x - ARROW_SIZE * (angle + ARROW_ANGLE).cos();
@@ -1074,7 +1095,7 @@ fn draw_arrowhead(ctx: &CanvasRenderingContext2d, tip_x: f64, tip_y: f64, angle:
     clippy::cast_sign_loss,
     clippy::cast_precision_loss
 )]
-fn draw_text(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_>) -> Result<(), JsValue> {
+fn draw_text<B: RenderBackend>(ctx: &mut B, obj: &BoardObject, props: &Props<'_>) -> Result<(), B::Error> {
     let head = props.head();
     let text = props.text();
     let foot = props.foot();
@@ -1093,7 +1114,7 @@ fn draw_text(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_
         .clamp(8.0, 96.0);
 
     ctx.save();
-    ctx.set_fill_style_str(props.text_color());
+    ctx.set_fill_style(props.text_color());
     ctx.set_text_align("center");
     ctx.set_text_baseline("middle");
     let font_str = format!("{font_size}px sans-serif");
@@ -1103,17 +1124,17 @@ fn draw_text(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_
     let max_w = (obj.width - 12.0).max(1.0);
     if !head.is_empty() {
         let y = -hh + font_size;
-        let head_fit = fit_text_with_ellipsis(ctx, head, max_w);
+        let head_fit = fit_text_with_ellipsis(head, max_w, &mut |t| measured_text_width(ctx, t));
         ctx.fill_text(&head_fit, 0.0, y)?;
     }
     if !text.is_empty() {
         let line_height = (font_size * 1.25).max(12.0);
         let max_lines = ((obj.height / line_height).floor() as usize).max(1);
-        let mut lines = wrap_text_lines(ctx, text, max_w);
+        let mut lines = wrap_text_lines(text, max_w, &mut |t| measured_text_width(ctx, t));
         if lines.len() > max_lines {
             lines.truncate(max_lines);
             if let Some(last) = lines.last_mut() {
-                *last = fit_text_with_ellipsis(ctx, last, max_w);
+                *last = fit_text_with_ellipsis(last, max_w, &mut |t| measured_text_width(ctx, t));
             }
         }
         let total_height = line_height * (lines.len().saturating_sub(1) as f64);
@@ -1125,7 +1146,7 @@ fn draw_text(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_
     }
     if !foot.is_empty() {
         let y = hh - font_size;
-        let foot_fit = fit_text_with_ellipsis(ctx, foot, max_w);
+        let foot_fit = fit_text_with_ellipsis(foot, max_w, &mut |t| measured_text_width(ctx, t));
         ctx.fill_text(&foot_fit, 0.0, y)?;
     }
 
@@ -1133,7 +1154,9 @@ fn draw_text(ctx: &CanvasRenderingContext2d, obj: &BoardObject, props: &Props<'_
     Ok(())
 }
 
-fn wrap_text_lines(ctx: &CanvasRenderingContext2d, text: &str, max_w: f64) -> Vec<String> {
+/// Wrap `text` into lines no wider than `max_w`, breaking words that do not
+/// fit on a line of their own. `measure` returns a string's rendered width.
+pub fn wrap_text_lines(text: &str, max_w: f64, measure: &mut impl FnMut(&str) -> f64) -> Vec<String> {
     let mut out = Vec::new();
     for raw_line in text.lines() {
         let words: Vec<&str> = raw_line.split_whitespace().collect();
@@ -1145,10 +1168,10 @@ fn wrap_text_lines(ctx: &CanvasRenderingContext2d, text: &str, max_w: f64) -> Ve
         let mut current = String::new();
         for word in words {
             if current.is_empty() {
-                if measured_text_width(ctx, word) <= max_w {
+                if measure(word) <= max_w {
                     current.push_str(word);
                 } else {
-                    let mut chunks = break_long_word(ctx, word, max_w);
+                    let mut chunks = break_long_word(word, max_w, measure);
                     if let Some(last) = chunks.pop() {
                         out.extend(chunks);
                         current = last;
@@ -1158,14 +1181,14 @@ fn wrap_text_lines(ctx: &CanvasRenderingContext2d, text: &str, max_w: f64) -> Ve
             }
 
             let candidate = format!("{current} {word}");
-            if measured_text_width(ctx, &candidate) <= max_w {
+            if measure(&candidate) <= max_w {
                 current = candidate;
             } else {
                 out.push(std::mem::take(&mut current));
-                if measured_text_width(ctx, word) <= max_w {
+                if measure(word) <= max_w {
                     word.clone_into(&mut current);
                 } else {
-                    let mut chunks = break_long_word(ctx, word, max_w);
+                    let mut chunks = break_long_word(word, max_w, measure);
                     if let Some(last) = chunks.pop() {
                         out.extend(chunks);
                         current = last;
@@ -1185,13 +1208,14 @@ fn wrap_text_lines(ctx: &CanvasRenderingContext2d, text: &str, max_w: f64) -> Ve
     out
 }
 
-fn break_long_word(ctx: &CanvasRenderingContext2d, word: &str, max_w: f64) -> Vec<String> {
+/// Split a word wider than `max_w` into chunks that each fit.
+pub fn break_long_word(word: &str, max_w: f64, measure: &mut impl FnMut(&str) -> f64) -> Vec<String> {
     let mut lines = Vec::new();
     let mut current = String::new();
     for ch in word.chars() {
         let mut candidate = current.clone();
         candidate.push(ch);
-        if !current.is_empty() && measured_text_width(ctx, &candidate) > max_w {
+        if !current.is_empty() && measure(&candidate) > max_w {
             lines.push(current);
             current = ch.to_string();
         } else {
@@ -1204,12 +1228,13 @@ fn break_long_word(ctx: &CanvasRenderingContext2d, word: &str, max_w: f64) -> Ve
     lines
 }
 
-fn fit_text_with_ellipsis(ctx: &CanvasRenderingContext2d, text: &str, max_w: f64) -> String {
+/// Trim `text` and truncate it with `...` until it fits in `max_w`.
+pub fn fit_text_with_ellipsis(text: &str, max_w: f64, measure: &mut impl FnMut(&str) -> f64) -> String {
     let trimmed = text.trim();
     if trimmed.is_empty() {
         return String::new();
     }
-    if measured_text_width(ctx, trimmed) <= max_w {
+    if measure(trimmed) <= max_w {
         return trimmed.to_owned();
     }
 
@@ -1218,43 +1243,40 @@ fn fit_text_with_ellipsis(ctx: &CanvasRenderingContext2d, text: &str, max_w: f64
     while !chars.is_empty() {
         chars.pop();
         let candidate = format!("{}{}", chars.iter().collect::<String>().trim_end(), ellipsis);
-        if measured_text_width(ctx, &candidate) <= max_w {
+        if measure(&candidate) <= max_w {
             return candidate;
         }
     }
     ellipsis.to_owned()
 }
 
-fn measured_text_width(ctx: &CanvasRenderingContext2d, text: &str) -> f64 {
-    match ctx.measure_text(text) {
-        Ok(metrics) => metrics.width(),
-        Err(_) => f64::INFINITY,
-    }
+fn measured_text_width<B: RenderBackend>(ctx: &mut B, text: &str) -> f64 {
+    ctx.measure_text(text).unwrap_or(f64::INFINITY)
 }
 
 // =============================================================
 // Selection UI
 // =============================================================
 
-fn draw_selection(
-    ctx: &CanvasRenderingContext2d,
+fn draw_selection<B: RenderBackend>(
+    ctx: &mut B,
     obj: &BoardObject,
     doc: &DocStore,
     zoom: f64,
     show_handles: bool,
-) -> Result<(), JsValue> {
+) -> Result<(), B::Error> {
     match obj.kind {
         ObjectKind::Line | ObjectKind::Arrow => draw_edge_selection(ctx, obj, doc, zoom, show_handles),
         _ => draw_node_selection(ctx, obj, zoom, show_handles),
     }
 }
 
-fn draw_node_selection(
-    ctx: &CanvasRenderingContext2d,
+fn draw_node_selection<B: RenderBackend>(
+    ctx: &mut B,
     obj: &BoardObject,
     zoom: f64,
     show_handles: bool,
-) -> Result<(), JsValue> {
+) -> Result<(), B::Error> {
     ctx.save();
 
     // Dashed bounding box (rotated with the object).
@@ -1264,15 +1286,12 @@ fn draw_node_selection(
     let hh = obj.height / 2.0;
     let dash_world = SELECTION_DASH_PX / zoom;
 
-    ctx.set_stroke_style_str("#1E90FF");
+    ctx.set_stroke_style("#1E90FF");
     ctx.set_line_width(1.0 / zoom);
-    let dash_array = js_sys::Array::new();
-    dash_array.push(&dash_world.into());
-    dash_array.push(&dash_world.into());
-    ctx.set_line_dash(&dash_array)?;
+    ctx.set_line_dash(&[dash_world, dash_world])?;
 
     ctx.stroke_rect(-hw, -hh, obj.width, obj.height);
-    ctx.set_line_dash(&js_sys::Array::new())?;
+    ctx.set_line_dash(&[])?;
 
     ctx.restore();
 
@@ -1285,8 +1304,8 @@ fn draw_node_selection(
     let handles = hit::resize_handle_positions(obj.x, obj.y, obj.width, obj.height, obj.rotation);
 
     ctx.save();
-    ctx.set_fill_style_str("#fff");
-    ctx.set_stroke_style_str("#1E90FF");
+    ctx.set_fill_style("#fff");
+    ctx.set_stroke_style("#1E90FF");
     ctx.set_line_width(1.0 / zoom);
 
     for pos in &handles {
@@ -1309,7 +1328,7 @@ fn draw_node_selection(
     let n_handle = handles[0]; // N handle
 
     // Connecting line from N handle to rotate handle.
-    ctx.set_stroke_style_str("#1E90FF");
+    ctx.set_stroke_style("#1E90FF");
     ctx.begin_path();
     ctx.move_to(n_handle.x, n_handle.y);
     ctx.line_to(rh.x, rh.y);
@@ -1318,7 +1337,7 @@ fn draw_node_selection(
     // Rotate handle circle.
     ctx.begin_path();
     ctx.arc(rh.x, rh.y, handle_size_world, 0.0, 2.0 * PI)?;
-    ctx.set_fill_style_str("#fff");
+    ctx.set_fill_style("#fff");
     ctx.fill();
     ctx.stroke();
 
@@ -1326,13 +1345,13 @@ fn draw_node_selection(
     Ok(())
 }
 
-fn draw_edge_selection(
-    ctx: &CanvasRenderingContext2d,
+fn draw_edge_selection<B: RenderBackend>(
+    ctx: &mut B,
     obj: &BoardObject,
     doc: &DocStore,
     zoom: f64,
     show_handles: bool,
-) -> Result<(), JsValue> {
+) -> Result<(), B::Error> {
     let Some(a) = hit::edge_endpoint_a_resolved(obj, doc) else {
         return Ok(());
     };
@@ -1347,8 +1366,8 @@ fn draw_edge_selection(
     };
 
     ctx.save();
-    ctx.set_fill_style_str("#fff");
-    ctx.set_stroke_style_str("#1E90FF");
+    ctx.set_fill_style("#fff");
+    ctx.set_stroke_style("#1E90FF");
     ctx.set_line_width(1.0 / zoom);
 
     for pt in &[a, b] {
@@ -1362,23 +1381,62 @@ fn draw_edge_selection(
     Ok(())
 }
 
-fn draw_marquee(
-    ctx: &CanvasRenderingContext2d,
+fn draw_ghosts<B: RenderBackend>(
+    ctx: &mut B,
+    ghosts: &GhostOverlay,
+    doc: &DocStore,
+    zoom: f64,
+) -> Result<(), B::Error> {
+    ctx.save();
+    ctx.set_global_alpha(GHOST_ALPHA);
+    for obj in &ghosts.objects {
+        draw_object(ctx, obj, doc)?;
+    }
+    ctx.restore();
+
+    for obj in &ghosts.objects {
+        draw_ghost_outline(ctx, obj, GHOST_OUTLINE_COLOR, zoom)?;
+    }
+    for id in &ghosts.deleted {
+        if let Some(obj) = doc.get(id) {
+            draw_ghost_outline(ctx, obj, GHOST_DELETE_COLOR, zoom)?;
+        }
+    }
+    Ok(())
+}
+
+fn draw_ghost_outline<B: RenderBackend>(
+    ctx: &mut B,
+    obj: &BoardObject,
+    color: &str,
+    zoom: f64,
+) -> Result<(), B::Error> {
+    ctx.save();
+    translate_and_rotate(ctx, obj)?;
+    let dash_world = SELECTION_DASH_PX / zoom;
+    ctx.set_stroke_style(color);
+    ctx.set_line_width(1.5 / zoom);
+    ctx.set_line_dash(&[dash_world, dash_world])?;
+    ctx.stroke_rect(-obj.width / 2.0, -obj.height / 2.0, obj.width, obj.height);
+    ctx.set_line_dash(&[])?;
+    ctx.restore();
+    Ok(())
+}
+
+fn draw_marquee<B: RenderBackend>(
+    ctx: &mut B,
     marquee: crate::input::SelectionRect,
     zoom: f64,
-) -> Result<(), JsValue> {
+) -> Result<(), B::Error> {
     ctx.save();
     let dash_world = SELECTION_DASH_PX / zoom;
-    let dash_array = js_sys::Array::new();
-    dash_array.push(&dash_world.into());
-    dash_array.push(&dash_world.into());
-    ctx.set_line_dash(&dash_array)?;
-    ctx.set_stroke_style_str("#1E90FF");
-    ctx.set_fill_style_str("rgba(30, 144, 255, 0.12)");
+    ctx.set_line_dash(&[dash_world, dash_world])?;
+    ctx.set_stroke_style("#1E90FF");
+    ctx.set_fill_style("rgba(30, 144, 255, 0.12)");
     ctx.set_line_width(1.0 / zoom);
     ctx.fill_rect(marquee.x, marquee.y, marquee.width, marquee.height);
     ctx.stroke_rect(marquee.x, marquee.y, marquee.width, marquee.height);
-    ctx.set_line_dash(&js_sys::Array::new())?;
+    ctx.set_line_dash(&[])?;
     ctx.restore();
     Ok(())
 }
@@ -1396,7 +1454,7 @@ fn endpoint_is_attached(obj: &BoardObject, key: &str) -> bool {
 // =============================================================
 
 /// Translate to the object's center and rotate by its rotation angle.
-fn translate_and_rotate(ctx: &CanvasRenderingContext2d, obj: &BoardObject) -> Result<(), JsValue> {
+fn translate_and_rotate<B: RenderBackend>(ctx: &mut B, obj: &BoardObject) -> Result<(), B::Error> {
     let cx = obj.x + obj.width / 2.0;
     let cy = obj.y + obj.height / 2.0;
     ctx.translate(cx, cy)?;
@@ -1405,150 +1463,7 @@ fn translate_and_rotate(ctx: &CanvasRenderingContext2d, obj: &BoardObject) -> Re
 }
 
 /// Apply stroke style and line width from props.
-fn apply_stroke_style(ctx: &CanvasRenderingContext2d, props: &Props<'_>) {
-    ctx.set_stroke_style_str(props.stroke());
+fn apply_stroke_style<B: RenderBackend>(ctx: &mut B, props: &Props<'_>) {
+    ctx.set_stroke_style(props.stroke());
     ctx.set_line_width(props.stroke_width());
 }
-
-#[cfg(test)]
-mod render_test {
-    use super::{SvgGeometry, attr_value, parse_svg_number, parse_svg_shapes, svg_view_box};
-
-    #[test]
-    fn parse_svg_shapes_extracts_path_attributes() {
-        let svg = r##"<svg viewBox="0 0 100 100"><path d="M0 0 L10 10 Z" fill="#f00" stroke="#000" stroke-width="2"/></svg>"##;
-        let shapes = parse_svg_shapes(svg);
-        assert_eq!(shapes.len(), 1);
-        if let SvgGeometry::Path(ref d) = shapes[0].geometry {
-            assert_eq!(d, "M0 0 L10 10 Z");
-        } else {
-            assert!(false, "expected Path geometry");
-        }
-        assert_eq!(shapes[0].fill.as_deref(), Some("#f00"));
-        assert_eq!(shapes[0].stroke.as_deref(), Some("#000"));
-        assert_eq!(shapes[0].stroke_width, Some(2.0));
-    }
-
-    #[test]
-    fn parse_svg_shapes_extracts_rect() {
-        let svg = r##"<svg viewBox="0 0 200 200"><rect x="10" y="20" width="100" height="50" fill="#0f0"/></svg>"##;
-        let shapes = parse_svg_shapes(svg);
-        assert_eq!(shapes.len(), 1);
-        assert!(
-            matches!(shapes[0].geometry, SvgGeometry::Rect { x, y, width, height, .. } if x == 10.0 && y == 20.0 && width == 100.0 && height == 50.0)
-        );
-    }
-
-    #[test]
-    fn parse_svg_shapes_extracts_circle() {
-        let svg = r#"<svg><circle cx="50" cy="50" r="25" fill="blue"/></svg>"#;
-        let shapes = parse_svg_shapes(svg);
-        assert_eq!(shapes.len(), 1);
-        assert!(
-            matches!(shapes[0].geometry, SvgGeometry::Circle { cx, cy, r } if cx == 50.0 && cy == 50.0 && r == 25.0)
-        );
-    }
-
-    #[test]
-    fn parse_svg_shapes_extracts_ellipse() {
-        let svg = r#"<svg><ellipse cx="50" cy="50" rx="30" ry="20"/></svg>"#;
-        let shapes = parse_svg_shapes(svg);
-        assert_eq!(shapes.len(), 1);
-        assert!(matches!(shapes[0].geometry, SvgGeometry::Ellipse { .. }));
-    }
-
-    #[test]
-    fn parse_svg_shapes_extracts_line() {
-        let svg = r#"<svg><line x1="0" y1="0" x2="100" y2="100" stroke="black"/></svg>"#;
-        let shapes = parse_svg_shapes(svg);
-        assert_eq!(shapes.len(), 1);
-        assert!(
-            matches!(shapes[0].geometry, SvgGeometry::Line { x1, y1, x2, y2 } if x1 == 0.0 && y1 == 0.0 && x2 == 100.0 && y2 == 100.0)
-        );
-    }
-
-    #[test]
-    fn parse_svg_shapes_extracts_polygon() {
-        let svg = r#"<svg><polygon points="50,0 100,100 0,100" fill="red"/></svg>"#;
-        let shapes = parse_svg_shapes(svg);
-        assert_eq!(shapes.len(), 1);
-        if let SvgGeometry::Polygon(ref pts) = shapes[0].geometry {
-            assert_eq!(pts.len(), 3);
-        } else {
-            assert!(false, "expected Polygon");
-        }
-    }
-
-    #[test]
-    fn parse_svg_shapes_extracts_text() {
-        let svg = r#"<svg><text x="10" y="30" font-size="16">Hello</text></svg>"#;
-        let shapes = parse_svg_shapes(svg);
-        assert_eq!(shapes.len(), 1);
-        if let SvgGeometry::Text { x, y, ref content, font_size } = shapes[0].geometry {
-            assert_eq!(x, 10.0);
-            assert_eq!(y, 30.0);
-            assert_eq!(content, "Hello");
-            assert_eq!(font_size, Some(16.0));
-        } else {
-            assert!(false, "expected Text");
-        }
-    }
-
-    #[test]
-    fn parse_svg_shapes_handles_group() {
-        let svg = r#"<svg><g transform="translate(10,20)"><rect x="0" y="0" width="50" height="50"/><circle cx="25" cy="25" r="10"/></g></svg>"#;
-        let shapes = parse_svg_shapes(svg);
-        assert_eq!(shapes.len(), 1);
-        if let SvgGeometry::Group(ref children) = shapes[0].geometry {
-            assert_eq!(children.len(), 2);
-        } else {
-            assert!(false, "expected Group");
-        }
-        assert_eq!(shapes[0].transform.as_deref(), Some("translate(10,20)"));
-    }
-
-    #[test]
-    fn parse_svg_shapes_landscape_like() {
-        let svg = r##"<svg viewBox="0 0 400 200">
-            <rect x="0" y="100" width="400" height="100" fill="#4a7c5c"/>
-            <rect x="0" y="0" width="400" height="100" fill="#87CEEB"/>
-            <circle cx="320" cy="40" r="30" fill="#FFD700"/>
-            <polygon points="150,100 175,40 200,100" fill="#2d5a2d"/>
-            <polygon points="220,100 250,30 280,100" fill="#1e4a1e"/>
-        </svg>"##;
-        let shapes = parse_svg_shapes(svg);
-        assert_eq!(shapes.len(), 5);
-        assert!(matches!(shapes[0].geometry, SvgGeometry::Rect { .. }));
-        assert!(matches!(shapes[1].geometry, SvgGeometry::Rect { .. }));
-        assert!(matches!(shapes[2].geometry, SvgGeometry::Circle { .. }));
-        assert!(matches!(shapes[3].geometry, SvgGeometry::Polygon(_)));
-        assert!(matches!(shapes[4].geometry, SvgGeometry::Polygon(_)));
-    }
-
-    #[test]
-    fn svg_view_box_prefers_viewbox() {
-        let svg = r#"<svg width="40" height="20" viewBox="2 3 100 50"></svg>"#;
-        assert_eq!(svg_view_box(svg), Some((2.0, 3.0, 100.0, 50.0)));
-    }
-
-    #[test]
-    fn svg_view_box_falls_back_to_dimensions() {
-        let svg = r#"<svg width="40px" height="20"></svg>"#;
-        assert_eq!(svg_view_box(svg), Some((0.0, 0.0, 40.0, 20.0)));
-    }
-
-    #[test]
-    fn attr_value_supports_single_or_double_quotes() {
-        let tag = "<path d='M0 0' fill=\"#fff\"/>";
-        assert_eq!(attr_value(tag, "d").as_deref(), Some("M0 0"));
-        assert_eq!(attr_value(tag, "fill").as_deref(), Some("#fff"));
-    }
-
-    #[test]
-    fn parse_svg_number_handles_px_and_percent() {
-        assert_eq!(parse_svg_number("10"), Some(10.0));
-        assert_eq!(parse_svg_number("10px"), Some(10.0));
-        assert_eq!(parse_svg_number("75%"), Some(75.0));
-        assert_eq!(parse_svg_number("x"), None);
-    }
-}
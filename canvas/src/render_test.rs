@@ -0,0 +1,393 @@
+#![allow(clippy::float_cmp)]
+
+use serde_json::json;
+use uuid::Uuid;
+
+use super::*;
+use crate::backend::{DrawCall, RecordingBackend};
+use crate::input::{GhostOverlay, UiState};
+
+// =============================================================
+// SVG parsing
+// =============================================================
+
+#[test]
+fn parse_svg_shapes_extracts_path_attributes() {
+    let svg =
+        r##"<svg viewBox="0 0 100 100"><path d="M0 0 L10 10 Z" fill="#f00" stroke="#000" stroke-width="2"/></svg>"##;
+    let shapes = parse_svg_shapes(svg);
+    assert_eq!(shapes.len(), 1);
+    if let SvgGeometry::Path(ref d) = shapes[0].geometry {
+        assert_eq!(d, "M0 0 L10 10 Z");
+    } else {
+        assert!(false, "expected Path geometry");
+    }
+    assert_eq!(shapes[0].fill.as_deref(), Some("#f00"));
+    assert_eq!(shapes[0].stroke.as_deref(), Some("#000"));
+    assert_eq!(shapes[0].stroke_width, Some(2.0));
+}
+
+#[test]
+fn parse_svg_shapes_extracts_rect() {
+    let svg = r##"<svg viewBox="0 0 200 200"><rect x="10" y="20" width="100" height="50" fill="#0f0"/></svg>"##;
+    let shapes = parse_svg_shapes(svg);
+    assert_eq!(shapes.len(), 1);
+    assert!(
+        matches!(shapes[0].geometry, SvgGeometry::Rect { x, y, width, height, .. } if x == 10.0 && y == 20.0 && width == 100.0 && height == 50.0)
+    );
+}
+
+#[test]
+fn parse_svg_shapes_extracts_circle() {
+    let svg = r#"<svg><circle cx="50" cy="50" r="25" fill="blue"/></svg>"#;
+    let shapes = parse_svg_shapes(svg);
+    assert_eq!(shapes.len(), 1);
+    assert!(matches!(shapes[0].geometry, SvgGeometry::Circle { cx, cy, r } if cx == 50.0 && cy == 50.0 && r == 25.0));
+}
+
+#[test]
+fn parse_svg_shapes_extracts_ellipse() {
+    let svg = r#"<svg><ellipse cx="50" cy="50" rx="30" ry="20"/></svg>"#;
+    let shapes = parse_svg_shapes(svg);
+    assert_eq!(shapes.len(), 1);
+    assert!(matches!(shapes[0].geometry, SvgGeometry::Ellipse { .. }));
+}
+
+#[test]
+fn parse_svg_shapes_extracts_line() {
+    let svg = r#"<svg><line x1="0" y1="0" x2="100" y2="100" stroke="black"/></svg>"#;
+    let shapes = parse_svg_shapes(svg);
+    assert_eq!(shapes.len(), 1);
+    assert!(
+        matches!(shapes[0].geometry, SvgGeometry::Line { x1, y1, x2, y2 } if x1 == 0.0 && y1 == 0.0 && x2 == 100.0 && y2 == 100.0)
+    );
+}
+
+#[test]
+fn parse_svg_shapes_extracts_polygon() {
+    let svg = r#"<svg><polygon points="50,0 100,100 0,100" fill="red"/></svg>"#;
+    let shapes = parse_svg_shapes(svg);
+    assert_eq!(shapes.len(), 1);
+    if let SvgGeometry::Polygon(ref pts) = shapes[0].geometry {
+        assert_eq!(pts.len(), 3);
+    } else {
+        assert!(false, "expected Polygon");
+    }
+}
+
+#[test]
+fn parse_svg_shapes_extracts_text() {
+    let svg = r#"<svg><text x="10" y="30" font-size="16">Hello</text></svg>"#;
+    let shapes = parse_svg_shapes(svg);
+    assert_eq!(shapes.len(), 1);
+    if let SvgGeometry::Text { x, y, ref content, font_size } = shapes[0].geometry {
+        assert_eq!(x, 10.0);
+        assert_eq!(y, 30.0);
+        assert_eq!(content, "Hello");
+        assert_eq!(font_size, Some(16.0));
+    } else {
+        assert!(false, "expected Text");
+    }
+}
+
+#[test]
+fn parse_svg_shapes_handles_group() {
+    let svg = r#"<svg><g transform="translate(10,20)"><rect x="0" y="0" width="50" height="50"/><circle cx="25" cy="25" r="10"/></g></svg>"#;
+    let shapes = parse_svg_shapes(svg);
+    assert_eq!(shapes.len(), 1);
+    if let SvgGeometry::Group(ref children) = shapes[0].geometry {
+        assert_eq!(children.len(), 2);
+    } else {
+        assert!(false, "expected Group");
+    }
+    assert_eq!(shapes[0].transform.as_deref(), Some("translate(10,20)"));
+}
+
+#[test]
+fn parse_svg_shapes_landscape_like() {
+    let svg = r##"<svg viewBox="0 0 400 200">
+        <rect x="0" y="100" width="400" height="100" fill="#4a7c5c"/>
+        <rect x="0" y="0" width="400" height="100" fill="#87CEEB"/>
+        <circle cx="320" cy="40" r="30" fill="#FFD700"/>
+        <polygon points="150,100 175,40 200,100" fill="#2d5a2d"/>
+        <polygon points="220,100 250,30 280,100" fill="#1e4a1e"/>
+    </svg>"##;
+    let shapes = parse_svg_shapes(svg);
+    assert_eq!(shapes.len(), 5);
+    assert!(matches!(shapes[0].geometry, SvgGeometry::Rect { .. }));
+    assert!(matches!(shapes[1].geometry, SvgGeometry::Rect { .. }));
+    assert!(matches!(shapes[2].geometry, SvgGeometry::Circle { .. }));
+    assert!(matches!(shapes[3].geometry, SvgGeometry::Polygon(_)));
+    assert!(matches!(shapes[4].geometry, SvgGeometry::Polygon(_)));
+}
+
+#[test]
+fn svg_view_box_prefers_viewbox() {
+    let svg = r#"<svg width="40" height="20" viewBox="2 3 100 50"></svg>"#;
+    assert_eq!(svg_view_box(svg), Some((2.0, 3.0, 100.0, 50.0)));
+}
+
+#[test]
+fn svg_view_box_falls_back_to_dimensions() {
+    let svg = r#"<svg width="40px" height="20"></svg>"#;
+    assert_eq!(svg_view_box(svg), Some((0.0, 0.0, 40.0, 20.0)));
+}
+
+#[test]
+fn attr_value_supports_single_or_double_quotes() {
+    let tag = "<path d='M0 0' fill=\"#fff\"/>";
+    assert_eq!(attr_value(tag, "d").as_deref(), Some("M0 0"));
+    assert_eq!(attr_value(tag, "fill").as_deref(), Some("#fff"));
+}
+
+#[test]
+fn parse_svg_number_handles_px_and_percent() {
+    assert_eq!(parse_svg_number("10"), Some(10.0));
+    assert_eq!(parse_svg_number("10px"), Some(10.0));
+    assert_eq!(parse_svg_number("75%"), Some(75.0));
+    assert_eq!(parse_svg_number("x"), None);
+}
+
+// =============================================================
+// Text layout
+// =============================================================
+
+fn mono(text: &str) -> f64 {
+    text.chars().map(|_| 10.0).sum()
+}
+
+#[test]
+fn wrap_text_lines_wraps_at_width_and_breaks_long_words() {
+    assert_eq!(wrap_text_lines("aa bb cc", 50.0, &mut mono), vec!["aa bb", "cc"]);
+    assert_eq!(wrap_text_lines("abcdefgh", 30.0, &mut mono), vec!["abc", "def", "gh"]);
+    assert_eq!(wrap_text_lines("one\n\ntwo", 100.0, &mut mono), vec!["one", "", "two"]);
+}
+
+#[test]
+fn fit_text_with_ellipsis_truncates_to_width() {
+    assert_eq!(fit_text_with_ellipsis("short", 100.0, &mut mono), "short");
+    assert_eq!(fit_text_with_ellipsis("a long title", 60.0, &mut mono), "a l...");
+}
+
+// =============================================================
+// Draw calls
+// =============================================================
+
+fn make_object(kind: ObjectKind, x: f64, y: f64, w: f64, h: f64, props: serde_json::Value) -> BoardObject {
+    BoardObject {
+        id: Uuid::new_v4(),
+        board_id: Uuid::new_v4(),
+        kind,
+        x,
+        y,
+        width: w,
+        height: h,
+        rotation: 0.0,
+        z_index: 0,
+        props,
+        created_by: None,
+        version: 1,
+        group_id: None,
+    }
+}
+
+fn record(objects: Vec<BoardObject>, ui: &UiState) -> RecordingBackend {
+    let mut doc = DocStore::new();
+    for obj in objects {
+        doc.insert(obj);
+    }
+    let mut backend = RecordingBackend::with_glyph_width(10.0);
+    draw(&mut backend, &doc, &Camera::default(), ui, 800.0, 600.0, 1.0).unwrap();
+    backend
+}
+
+fn fill_rects(backend: &RecordingBackend) -> Vec<(f64, f64, f64, f64)> {
+    backend
+        .calls
+        .iter()
+        .filter_map(|call| match *call {
+            DrawCall::FillRect { x, y, width, height } => Some((x, y, width, height)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn draw_applies_camera_before_objects() {
+    let camera = Camera { pan_x: 10.0, pan_y: 20.0, zoom: 2.0, view_rotation_deg: 0.0 };
+    let mut backend = RecordingBackend::new();
+    draw(&mut backend, &DocStore::new(), &camera, &UiState::default(), 100.0, 50.0, 2.0).unwrap();
+    assert_eq!(
+        backend.calls,
+        vec![
+            DrawCall::SetTransform([2.0, 0.0, 0.0, 2.0, 0.0, 0.0]),
+            DrawCall::ClearRect { x: 0.0, y: 0.0, width: 100.0, height: 50.0 },
+            DrawCall::Translate(50.0, 25.0),
+            DrawCall::Rotate(0.0),
+            DrawCall::Translate(-50.0, -25.0),
+            DrawCall::Translate(10.0, 20.0),
+            DrawCall::Scale(2.0, 2.0),
+        ]
+    );
+}
+
+#[test]
+fn draw_rect_fills_and_strokes_around_center() {
+    let rect = make_object(
+        ObjectKind::Rect,
+        10.0,
+        20.0,
+        100.0,
+        50.0,
+        json!({ "fill": "#00f", "strokeWidth": 2.0 }),
+    );
+    let backend = record(vec![rect], &UiState::default());
+    let drawn = &backend.calls[7..];
+    assert_eq!(
+        drawn,
+        &[
+            DrawCall::Save,
+            DrawCall::Translate(60.0, 45.0),
+            DrawCall::Rotate(0.0),
+            DrawCall::SetFillStyle("#00f".to_owned()),
+            DrawCall::FillRect { x: -50.0, y: -25.0, width: 100.0, height: 50.0 },
+            DrawCall::SetStrokeStyle("#1F1A17".to_owned()),
+            DrawCall::SetLineWidth(2.0),
+            DrawCall::StrokeRect { x: -50.0, y: -25.0, width: 100.0, height: 50.0 },
+            DrawCall::Restore,
+        ]
+    );
+}
+
+#[test]
+fn draw_puts_selected_objects_on_top_with_dashed_outline() {
+    let below = make_object(ObjectKind::Rect, 0.0, 0.0, 10.0, 10.0, json!({}));
+    let above = make_object(ObjectKind::Rect, 100.0, 0.0, 20.0, 20.0, json!({}));
+    let mut ui = UiState::default();
+    ui.selected_ids.insert(below.id);
+    let backend = record(vec![below, above], &ui);
+
+    let rects = fill_rects(&backend);
+    assert_eq!(rects[0], (-10.0, -10.0, 20.0, 20.0));
+    assert_eq!(rects[1], (-5.0, -5.0, 10.0, 10.0));
+    assert!(
+        backend
+            .calls
+            .contains(&DrawCall::SetLineDash(vec![4.0, 4.0]))
+    );
+    assert_eq!(backend.calls.last(), Some(&DrawCall::Restore));
+}
+
+#[test]
+fn draw_ghosts_translucent_above_objects_and_outlines_deletions() {
+    let doomed = make_object(ObjectKind::Rect, 0.0, 0.0, 10.0, 10.0, json!({}));
+    let proposed = make_object(ObjectKind::Rect, 100.0, 0.0, 20.0, 20.0, json!({}));
+    let ui =
+        UiState { ghosts: GhostOverlay { objects: vec![proposed], deleted: vec![doomed.id] }, ..UiState::default() };
+    let backend = record(vec![doomed], &ui);
+
+    let alpha_at = backend
+        .calls
+        .iter()
+        .position(|call| *call == DrawCall::SetGlobalAlpha(GHOST_ALPHA))
+        .unwrap();
+    let rects = fill_rects(&backend);
+    assert_eq!(rects, vec![(-5.0, -5.0, 10.0, 10.0), (-10.0, -10.0, 20.0, 20.0)]);
+    let ghost_fill = backend
+        .calls
+        .iter()
+        .rposition(|call| matches!(call, DrawCall::FillRect { .. }))
+        .unwrap();
+    assert!(alpha_at < ghost_fill);
+
+    let outlines = &backend.calls[ghost_fill..];
+    assert!(outlines.contains(&DrawCall::SetStrokeStyle(GHOST_OUTLINE_COLOR.to_owned())));
+    assert!(outlines.contains(&DrawCall::SetStrokeStyle(GHOST_DELETE_COLOR.to_owned())));
+    assert!(outlines.contains(&DrawCall::StrokeRect { x: -5.0, y: -5.0, width: 10.0, height: 10.0 }));
+}
+
+#[test]
+fn draw_edge_strokes_line_and_fills_arrowhead() {
+    let arrow = make_object(
+        ObjectKind::Arrow,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        json!({ "a": { "type": "free", "x": 0.0, "y": 0.0 }, "b": { "type": "free", "x": 100.0, "y": 0.0 } }),
+    );
+    let backend = record(vec![arrow], &UiState::default());
+    let drawn = &backend.calls[7..];
+    assert_eq!(
+        &drawn[..7],
+        &[
+            DrawCall::Save,
+            DrawCall::SetStrokeStyle("#1F1A17".to_owned()),
+            DrawCall::SetLineWidth(0.0),
+            DrawCall::BeginPath,
+            DrawCall::MoveTo(0.0, 0.0),
+            DrawCall::LineTo(100.0, 0.0),
+            DrawCall::Stroke,
+        ]
+    );
+    assert_eq!(
+        drawn[7..]
+            .iter()
+            .filter(|call| **call == DrawCall::Fill)
+            .count(),
+        1
+    );
+    assert!(drawn.contains(&DrawCall::MoveTo(100.0, 0.0)));
+}
+
+#[test]
+fn draw_text_wraps_body_and_ellipsizes_head() {
+    let rect = make_object(
+        ObjectKind::Rect,
+        0.0,
+        0.0,
+        72.0,
+        100.0,
+        json!({ "head": "a long title", "text": "aaa bbb ccc", "fontSize": 10.0 }),
+    );
+    let backend = record(vec![rect], &UiState::default());
+    assert_eq!(backend.texts(), vec!["a l...", "aaa", "bbb", "ccc"]);
+    assert!(
+        backend
+            .calls
+            .contains(&DrawCall::SetFont("10px sans-serif".to_owned()))
+    );
+}
+
+#[test]
+fn draw_svg_renders_inline_shapes_as_paths() {
+    let svg = make_object(
+        ObjectKind::Svg,
+        0.0,
+        0.0,
+        20.0,
+        20.0,
+        json!({ "svg": r##"<svg viewBox="0 0 10 10"><rect width="10" height="10" fill="#0f0"/></svg>"## }),
+    );
+    let backend = record(vec![svg], &UiState::default());
+    assert!(backend.calls.contains(&DrawCall::Scale(2.0, 2.0)));
+    assert!(
+        backend
+            .calls
+            .contains(&DrawCall::SetFillStyle("#0f0".to_owned()))
+    );
+    assert!(
+        backend
+            .calls
+            .contains(&DrawCall::FillPath("M0,0h10v10h-10Z".to_owned()))
+    );
+    assert!(fill_rects(&backend).is_empty());
+}
+
+#[test]
+fn draw_svg_falls_back_to_labelled_placeholder() {
+    let svg = make_object(ObjectKind::Svg, 0.0, 0.0, 100.0, 50.0, json!({ "svg": "not svg" }));
+    let backend = record(vec![svg], &UiState::default());
+    assert_eq!(fill_rects(&backend), vec![(-50.0, -25.0, 100.0, 50.0)]);
+    assert_eq!(backend.texts(), vec!["SVG"]);
+}
@@ -17,6 +17,7 @@ pub fn BoardCard(
     id: String,
     name: String,
     #[prop(default = Vec::new())] snapshot: Vec<BoardListPreviewObject>,
+    #[prop(default = None)] thumbnail_url: Option<String>,
     #[prop(optional)] active: bool,
     #[prop(optional)] mini: bool,
     #[prop(optional)] on_delete: Option<Callback<String>>,
@@ -24,6 +25,8 @@ pub fn BoardCard(
     let href = format!("/app/board/{id}");
     let preview_ref = NodeRef::<leptos::html::Canvas>::new();
     let snapshot_count = snapshot.len();
+    // The snapshot canvas underneath stays visible if the thumbnail fails.
+    let thumbnail_failed = RwSignal::new(false);
     let on_delete_click = Callback::new({
         let id = id.clone();
         move |()| {
@@ -69,6 +72,19 @@ pub fn BoardCard(
             </Show>
             <span class="board-card__preview">
                 <canvas class="board-card__preview-canvas" node_ref=preview_ref aria-hidden="true"></canvas>
+                {thumbnail_url
+                    .map(|src| {
+                        view! {
+                            <img
+                                class="board-card__preview-image"
+                                class:board-card__preview-image--failed=move || thumbnail_failed.get()
+                                src=src
+                                alt=""
+                                loading="lazy"
+                                on:error=move |_| thumbnail_failed.set(true)
+                            />
+                        }
+                    })}
                 <span class="board-card__preview-meta">{format!("{snapshot_count} items")}</span>
             </span>
         </a>
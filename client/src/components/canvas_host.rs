@@ -12,6 +12,8 @@ use crate::app::FrameSender;
 use crate::components::dial::{ColorDial, CompassDial, ZoomDial};
 #[cfg(feature = "hydrate")]
 use crate::net::types::{BoardObject, Frame, FrameStatus};
+#[cfg(feature = "hydrate")]
+use crate::state::ai::{AiPreview, AiState};
 use crate::state::auth::AuthState;
 use crate::state::board::BoardState;
 use crate::state::canvas_view::CanvasViewState;
@@ -39,6 +41,10 @@ use crate::util::dial_math::{
     normalize_degrees_360, signed_angle_delta_deg, snap_border_width_to_px, snap_font_size_to_px, zoom_from_dial_angle,
 };
 #[cfg(feature = "hydrate")]
+use crate::util::frame::request_frame;
+#[cfg(feature = "hydrate")]
+use crate::util::frame_emit::ObjectUpdateBatch;
+#[cfg(feature = "hydrate")]
 use crate::util::object_props::{reset_scale_props_baseline, reset_wire_object_scale_baseline};
 use crate::util::selection_actions::apply_group_scale_target;
 #[cfg(feature = "hydrate")]
@@ -83,7 +89,7 @@ use canvas::doc::{BoardObject as CanvasObject, ObjectKind as CanvasKind};
 #[cfg(feature = "hydrate")]
 use canvas::engine::{Action, Engine};
 #[cfg(feature = "hydrate")]
-use canvas::input::{InputState as CanvasInputState, Key as CanvasKey, WheelDelta};
+use canvas::input::{GhostOverlay, InputState as CanvasInputState, Key as CanvasKey, WheelDelta};
 #[cfg(feature = "hydrate")]
 use js_sys::Date;
 #[cfg(feature = "hydrate")]
@@ -168,6 +174,8 @@ fn mount_dials_into_panels() {
 #[component]
 pub fn CanvasHost() -> impl IntoView {
     let _auth = expect_context::<RwSignal<AuthState>>();
+    #[cfg(feature = "hydrate")]
+    let ai = expect_context::<RwSignal<AiState>>();
     let board = expect_context::<RwSignal<BoardState>>();
     let canvas_view = expect_context::<RwSignal<CanvasViewState>>();
     let sender = expect_context::<RwSignal<FrameSender>>();
@@ -442,6 +450,25 @@ pub fn CanvasHost() -> impl IntoView {
         });
     }
 
+    // Draw a pending `ai:preview` as a ghost overlay until it is applied or discarded.
+    #[cfg(feature = "hydrate")]
+    {
+        let engine = Rc::clone(&engine);
+        Effect::new(move || {
+            let ghosts = ai.with(|state| {
+                state
+                    .preview
+                    .as_ref()
+                    .map(|preview| board.with_untracked(|b| preview_ghosts(preview, b.board_id.as_deref())))
+                    .unwrap_or_default()
+            });
+            if let Some(engine) = engine.borrow_mut().as_mut() {
+                engine.set_ghosts(ghosts);
+            }
+            request_render(&engine, canvas_view, render_raf_pending);
+        });
+    }
+
     #[cfg(feature = "hydrate")]
     {
         let engine = Rc::clone(&engine);
@@ -2404,6 +2431,23 @@ fn to_canvas_object(obj: &crate::net::types::BoardObject, active_board_id: Optio
     })
 }
 
+#[cfg(feature = "hydrate")]
+fn preview_ghosts(preview: &AiPreview, active_board_id: Option<&str>) -> GhostOverlay {
+    GhostOverlay {
+        objects: preview
+            .created
+            .iter()
+            .chain(&preview.updated)
+            .filter_map(|obj| to_canvas_object(obj, active_board_id))
+            .collect(),
+        deleted: preview
+            .deleted
+            .iter()
+            .filter_map(|id| uuid::Uuid::parse_str(id).ok())
+            .collect(),
+    }
+}
+
 #[cfg(feature = "hydrate")]
 fn parse_or_stable_uuid(value: &str) -> uuid::Uuid {
     if let Ok(parsed) = uuid::Uuid::parse_str(value) {
@@ -2449,6 +2493,8 @@ fn process_actions(
 ) {
     const LOCAL_OBJECT_PATCH_LIMIT: usize = 500;
 
+    // Multi-select moves emit one update per object; send them together.
+    let mut updates = ObjectUpdateBatch::default();
     for action in actions {
         match action {
             Action::ObjectCreated(obj) => {
@@ -2549,18 +2595,7 @@ fn process_actions(
                     data.insert("version".to_owned(), serde_json::json!(version));
                 }
 
-                let frame = Frame {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    parent_id: None,
-                    ts: 0,
-                    board_id: Some(board_id),
-                    from: None,
-                    syscall: "object:update".to_owned(),
-                    status: FrameStatus::Request,
-                    trace: None,
-                    data: serde_json::Value::Object(data),
-                };
-                let _ = sender.get_untracked().send(&frame);
+                updates.push_fields(&board_id, serde_json::Value::Object(data));
             }
             Action::ObjectDeleted { id } => {
                 let Some(board_id) = board.get_untracked().board_id else {
@@ -2586,9 +2621,21 @@ fn process_actions(
                 };
                 let _ = sender.get_untracked().send(&frame);
             }
+            Action::UndoRequested | Action::RedoRequested => {
+                let syscall = if matches!(action, Action::UndoRequested) {
+                    "history:undo"
+                } else {
+                    "history:redo"
+                };
+                // Flush queued edits first so the server records them before undoing.
+                std::mem::take(&mut updates).send(sender);
+                let frame = request_frame(syscall, board.get_untracked().board_id, serde_json::json!({}));
+                let _ = sender.get_untracked().send(&frame);
+            }
             Action::None | Action::RenderNeeded | Action::EditTextRequested { .. } | Action::SetCursor(_) => {}
         }
     }
+    updates.send(sender);
 }
 
 #[cfg(feature = "hydrate")]
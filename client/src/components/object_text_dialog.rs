@@ -1,4 +1,9 @@
 //! Modal dialog for editing selected object text content.
+//!
+//! When the board page has a live collaborative session for the object,
+//! every keystroke is reported through `on_input` and peers' edits arrive by
+//! rewriting `value`; `caret` then carries the remapped caret so the local
+//! cursor does not jump to the end of the text.
 
 use leptos::prelude::*;
 
@@ -6,10 +11,55 @@ use leptos::prelude::*;
 #[component]
 pub fn ObjectTextDialog(
     value: RwSignal<String>,
+    /// Caret as a UTF-16 offset to restore after a remote edit.
+    caret: RwSignal<Option<u32>>,
+    /// Whether edits are shared live; replaces Cancel/Save with Done.
+    live: Signal<bool>,
+    on_input: Callback<(String, u32)>,
     on_cancel: Callback<()>,
     on_save: Callback<()>,
     on_keydown: Callback<leptos::ev::KeyboardEvent>,
 ) -> impl IntoView {
+    let textarea_ref = NodeRef::<leptos::html::Textarea>::new();
+
+    // WHY: binding `prop:value` would reset the caret to the end whenever a
+    // peer's edit lands. Write the DOM value only when it differs and then
+    // restore the remapped caret.
+    Effect::new(move || {
+        let text = value.get();
+        let restore = caret.get();
+        #[cfg(feature = "hydrate")]
+        {
+            let Some(el) = textarea_ref.get() else {
+                return;
+            };
+            if el.value() != text {
+                el.set_value(&text);
+            }
+            if let Some(pos) = restore {
+                let _ = el.set_selection_range(pos, pos);
+            }
+        }
+        #[cfg(not(feature = "hydrate"))]
+        {
+            let _ = (text, restore);
+        }
+    });
+
+    let on_textarea_input = move |ev: leptos::ev::Event| {
+        let text = event_target_value(&ev);
+        #[cfg(feature = "hydrate")]
+        let at = textarea_ref
+            .get_untracked()
+            .and_then(|el| el.selection_start().ok().flatten())
+            .unwrap_or(0);
+        #[cfg(not(feature = "hydrate"))]
+        let at = 0;
+        caret.set(None);
+        value.set(text.clone());
+        on_input.run((text, at));
+    };
+
     view! {
         <div class="dialog-backdrop" on:click=move |_| on_cancel.run(())>
             <div
@@ -21,19 +71,30 @@ pub fn ObjectTextDialog(
                     "Text"
                     <textarea
                         class="dialog__textarea"
-                        prop:value=move || value.get()
-                        on:input=move |ev| value.set(event_target_value(&ev))
+                        node_ref=textarea_ref
+                        on:input=on_textarea_input
                         on:keydown=move |ev| on_keydown.run(ev)
                         autofocus=true
                     ></textarea>
                 </label>
                 <div class="dialog__actions">
-                    <button class="btn" on:click=move |_| on_cancel.run(())>
-                        "Cancel"
-                    </button>
-                    <button class="btn btn--primary" on:click=move |_| on_save.run(())>
-                        "Save"
-                    </button>
+                    <Show
+                        when=move || live.get()
+                        fallback=move || {
+                            view! {
+                                <button class="btn" on:click=move |_| on_cancel.run(())>
+                                    "Cancel"
+                                </button>
+                                <button class="btn btn--primary" on:click=move |_| on_save.run(())>
+                                    "Save"
+                                </button>
+                            }
+                        }
+                    >
+                        <button class="btn btn--primary" on:click=move |_| on_save.run(())>
+                            "Done"
+                        </button>
+                    </Show>
                 </div>
             </div>
         </div>
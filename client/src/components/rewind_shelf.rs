@@ -37,6 +37,26 @@ pub fn RewindShelf() -> impl IntoView {
         let _ = sender.get_untracked().send(&frame);
     };
 
+    let on_restore = move |savepoint_id: String| {
+        let Some(board_id) = board.get_untracked().board_id else {
+            return;
+        };
+        let frame = Frame {
+            id: uuid::Uuid::new_v4().to_string(),
+            parent_id: None,
+            ts: 0,
+            board_id: Some(board_id),
+            from: None,
+            syscall: "board:savepoint:restore".to_owned(),
+            status: FrameStatus::Request,
+            trace: None,
+            data: serde_json::json!({
+                "savepoint_id": savepoint_id
+            }),
+        };
+        let _ = sender.get_untracked().send(&frame);
+    };
+
     view! {
         <div class="rewind-shelf">
             <div class="rewind-shelf__toolbar">
@@ -65,10 +85,17 @@ pub fn RewindShelf() -> impl IntoView {
                                 }
                             });
                             let meta = format!("{} · seq {}", sp.reason, sp.seq);
+                            let savepoint_id = sp.id.clone();
                             view! {
                                 <div class="rewind-record rewind-record--stack">
                                     <span class="rewind-record__title">{title}</span>
                                     <span class="rewind-record__meta">{meta}</span>
+                                    <button
+                                        class="rewind-record__restore"
+                                        on:click=move |_| on_restore(savepoint_id.clone())
+                                    >
+                                        "Restore"
+                                    </button>
                                 </div>
                             }
                         })
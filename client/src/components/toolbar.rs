@@ -97,7 +97,7 @@ pub fn Toolbar() -> impl IntoView {
                         return;
                     };
                     let url = format!("/api/boards/{board_id}/import.jsonl");
-                    let body = serde_json::json!({ "jsonl": jsonl }).to_string();
+                    let body = serde_json::json!({ "jsonl": jsonl, "keep_ids": true }).to_string();
                     let Ok(request) = gloo_net::http::Request::post(&url)
                         .header("Content-Type", "application/json")
                         .body(body)
@@ -24,9 +24,11 @@ mod frame_client_objects;
 mod frame_client_parse;
 #[path = "frame_client_requests.rs"]
 mod frame_client_requests;
+#[path = "frame_client_text.rs"]
+mod frame_client_text;
 
 #[cfg(feature = "hydrate")]
-use self::frame_client_ai::handle_ai_frame;
+use self::frame_client_ai::{handle_ai_frame, settle_ai_preview};
 #[cfg(feature = "hydrate")]
 use self::frame_client_chat::handle_chat_frame;
 #[cfg(feature = "hydrate")]
@@ -37,6 +39,8 @@ use self::frame_client_objects::handle_object_frame;
 use self::frame_client_requests::{
     send_board_list_request, send_board_savepoint_list_request, send_board_users_list_request,
 };
+#[cfg(feature = "hydrate")]
+use self::frame_client_text::{handle_text_frame, resync_text_session};
 
 #[cfg(test)]
 use self::frame_client_objects::{
@@ -51,6 +55,8 @@ use self::frame_client_parse::{
     parse_board_objects, parse_chat_message,
 };
 #[cfg(feature = "hydrate")]
+use self::frame_client_parse::{is_join_delta, parse_join_deleted_ids};
+#[cfg(feature = "hydrate")]
 use crate::net::types::{BoardObject, Frame, FrameStatus};
 #[cfg(any(test, feature = "hydrate"))]
 use crate::state::ai::AiState;
@@ -164,6 +170,11 @@ fn queue_live_object_frame(board: leptos::prelude::RwSignal<BoardState>, frame:
         ("object:create", crate::net::types::FrameStatus::Done)
             | ("object:update", crate::net::types::FrameStatus::Done)
             | ("object:delete", crate::net::types::FrameStatus::Done)
+            | ("object:batch", crate::net::types::FrameStatus::Done)
+            | ("history:undo", crate::net::types::FrameStatus::Done)
+            | ("history:redo", crate::net::types::FrameStatus::Done)
+            | ("ai:apply", crate::net::types::FrameStatus::Done)
+            | ("ai:revert", crate::net::types::FrameStatus::Done)
     );
     if !is_batchable {
         return false;
@@ -398,12 +409,25 @@ fn dispatch_frame(
     if handle_board_frame(frame, board, boards, tx) {
         return;
     }
+    // `ai:apply` replies are object batches handled below, so settle the
+    // pending preview before they are queued.
+    if matches!(frame.syscall.as_str(), "ai:apply" | "ai:discard") {
+        ai.update(|a| {
+            settle_ai_preview(a, frame);
+        });
+        if frame.syscall == "ai:discard" {
+            return;
+        }
+    }
     if queue_live_object_frame(board, frame) {
         return;
     }
     if handle_object_frame(frame, board) {
         return;
     }
+    if handle_text_frame(frame, board, tx) {
+        return;
+    }
     if handle_chat_frame(frame, chat) {
         return;
     }
@@ -468,6 +492,21 @@ fn handle_board_frame(
             }
             true
         }
+        Some("join") if frame.status == FrameStatus::Bulk && is_join_delta(&frame.data) => {
+            // Reconnect delta: upsert in place instead of replacing the object set.
+            let objs = parse_board_object_bulk(&frame.data);
+            if !objs.is_empty() {
+                board.update(|b| {
+                    for obj in objs {
+                        b.drag_objects.remove(&obj.id);
+                        b.drag_updated_at.remove(&obj.id);
+                        b.objects.insert(obj.id.clone(), obj);
+                    }
+                    b.bump_scene_rev();
+                });
+            }
+            true
+        }
         Some("join") if frame.status == FrameStatus::Bulk => {
             let objs = parse_board_object_bulk(&frame.data);
             if !objs.is_empty() {
@@ -504,7 +543,16 @@ fn handle_board_frame(
                 b.pending_join_request_id = None;
                 b.pending_join_started_ms = None;
 
-                if let Some(objs) = parse_board_objects(&frame.data) {
+                b.sync_seq = frame.data.get("seq").and_then(serde_json::Value::as_i64);
+                if is_join_delta(&frame.data) {
+                    for id in parse_join_deleted_ids(&frame.data) {
+                        b.objects.remove(&id);
+                        b.selection.remove(&id);
+                        b.drag_objects.remove(&id);
+                        b.drag_updated_at.remove(&id);
+                    }
+                    b.bump_scene_rev();
+                } else if let Some(objs) = parse_board_objects(&frame.data) {
                     b.objects.clear();
                     b.drag_objects.clear();
                     b.drag_updated_at.clear();
@@ -531,6 +579,7 @@ fn handle_board_frame(
             }
             send_board_savepoint_list_request(tx, board);
             send_board_users_list_request(tx, board);
+            resync_text_session(board, tx);
             true
         }
         Some("join") => {
@@ -634,6 +683,11 @@ fn handle_board_frame(
             }
             true
         }
+        Some("savepoint:restore") if frame.status != FrameStatus::Error => {
+            // Object frames converge the canvas; refresh the shelf for the new pre-restore record.
+            send_board_savepoint_list_request(tx, board);
+            true
+        }
         Some("part") => {
             if let Some(client_id) = frame.data.get("client_id").and_then(|v| v.as_str()) {
                 board.update(|b| {
@@ -705,6 +759,7 @@ fn handle_deleted_board_eject(frame: &Frame, board: leptos::prelude::RwSignal<Bo
             b.drag_updated_at.clear();
             b.cursor_updated_at.clear();
             b.join_streaming = false;
+            b.sync_seq = None;
             b.selection.clear();
             b.presence.clear();
             b.join_round_trip_ms = None;
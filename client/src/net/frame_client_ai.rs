@@ -16,9 +16,15 @@ fn is_user_visible_role(role: &str) -> bool {
     matches!(role, "assistant" | "user" | "error")
 }
 
+/// Whether `syscall` runs the agent loop and streams `ai:prompt`-shaped replies.
+#[cfg(any(test, feature = "hydrate"))]
+fn is_ai_run_syscall(syscall: &str) -> bool {
+    matches!(syscall, "ai:prompt" | "ai:preview")
+}
+
 #[cfg(any(test, feature = "hydrate"))]
 fn parse_tool_activity_message(frame: &Frame) -> Option<AiMessage> {
-    if frame.syscall != "ai:prompt" || frame.status != FrameStatus::Item {
+    if !is_ai_run_syscall(&frame.syscall) || frame.status != FrameStatus::Item {
         return None;
     }
     if frame.data.get("role").and_then(serde_json::Value::as_str) != Some("tool") {
@@ -62,6 +68,110 @@ fn parse_tool_activity_message(frame: &Frame) -> Option<AiMessage> {
     })
 }
 
+/// Provisional message ID for text streamed during one LLM round trip.
+#[cfg(any(test, feature = "hydrate"))]
+fn streamed_message_id(parent_id: &str, iteration: f64) -> String {
+    format!("{parent_id}:stream:{iteration}")
+}
+
+/// Append an `assistant_delta` item to its provisional streamed message.
+///
+/// Returns `false` for any other frame.
+#[cfg(any(test, feature = "hydrate"))]
+pub(super) fn apply_assistant_delta(ai: &mut AiState, frame: &Frame) -> bool {
+    if frame.data.get("kind").and_then(serde_json::Value::as_str) != Some("assistant_delta") {
+        return false;
+    }
+    let Some(parent_id) = frame.parent_id.as_deref() else {
+        return true;
+    };
+    let iteration = frame
+        .data
+        .get("iteration")
+        .and_then(serde_json::Value::as_f64)
+        .unwrap_or(0.0);
+    let piece = frame
+        .data
+        .get("content")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default();
+    let id = streamed_message_id(parent_id, iteration);
+    if let Some(existing) = ai.messages.iter_mut().rev().find(|msg| msg.id == id) {
+        existing.content.push_str(piece);
+    } else {
+        ai.messages.push(AiMessage {
+            id,
+            role: "assistant".to_owned(),
+            content: piece.to_owned(),
+            timestamp: 0.0,
+            mutations: None,
+        });
+    }
+    ai.loading = true;
+    true
+}
+
+/// Drop provisional streamed messages once the complete text for `parent_id` arrives.
+#[cfg(any(test, feature = "hydrate"))]
+pub(super) fn clear_streamed_messages(ai: &mut AiState, parent_id: &str) {
+    let prefix = format!("{parent_id}:stream:");
+    ai.messages.retain(|msg| !msg.id.starts_with(&prefix));
+}
+
+/// Keep the proposed changes from an `ai:preview` done frame for the ghost
+/// overlay. Error and cancel frames leave no preview behind; other syscalls
+/// are ignored.
+#[cfg(any(test, feature = "hydrate"))]
+pub(super) fn store_ai_preview(ai: &mut AiState, frame: &Frame) {
+    if frame.syscall != "ai:preview" {
+        return;
+    }
+    ai.preview = match frame.status {
+        FrameStatus::Done => super::frame_client_parse::parse_ai_preview(frame),
+        _ => None,
+    };
+}
+
+/// Drop the pending preview once an `ai:apply` or `ai:discard` for it
+/// settles.
+///
+/// Done frames only clear a matching `preview_id`, since `ai:apply` is
+/// broadcast to every client on the board. An error means the server no
+/// longer holds the preview, so it clears unconditionally. Returns `false`
+/// for any other frame.
+#[cfg(any(test, feature = "hydrate"))]
+pub(super) fn settle_ai_preview(ai: &mut AiState, frame: &Frame) -> bool {
+    if !matches!(frame.syscall.as_str(), "ai:apply" | "ai:discard") {
+        return false;
+    }
+    match frame.status {
+        FrameStatus::Done => {
+            let settled = frame
+                .data
+                .get("preview_id")
+                .and_then(serde_json::Value::as_str);
+            if ai.preview.as_ref().map(|p| p.id.as_str()) == settled {
+                ai.preview = None;
+            }
+        }
+        FrameStatus::Error => {
+            ai.preview = None;
+            let content = super::frame_error_message(frame)
+                .unwrap_or("AI preview is no longer available")
+                .to_owned();
+            ai.messages.push(AiMessage {
+                id: frame.id.clone(),
+                role: "error".to_owned(),
+                content,
+                timestamp: 0.0,
+                mutations: None,
+            });
+        }
+        _ => return false,
+    }
+    true
+}
+
 #[cfg(feature = "hydrate")]
 pub(super) fn handle_ai_frame(frame: &Frame, ai: leptos::prelude::RwSignal<AiState>) -> bool {
     use leptos::prelude::Update;
@@ -98,7 +208,15 @@ pub(super) fn handle_ai_frame(frame: &Frame, ai: leptos::prelude::RwSignal<AiSta
             }
             true
         }
-        "ai:prompt" if frame.status == FrameStatus::Done || frame.status == FrameStatus::Error => {
+        "ai:prompt" | "ai:preview"
+            if matches!(frame.status, FrameStatus::Done | FrameStatus::Error | FrameStatus::Cancel) =>
+        {
+            ai.update(|a| store_ai_preview(a, frame));
+            if frame.status != FrameStatus::Cancel
+                && let Some(parent_id) = frame.parent_id.as_deref()
+            {
+                ai.update(|a| clear_streamed_messages(a, parent_id));
+            }
             if let Some(user_msg) = super::parse_ai_prompt_user_message(frame) {
                 ai.update(|a| upsert_ai_user_message(a, user_msg));
             }
@@ -128,7 +246,17 @@ pub(super) fn handle_ai_frame(frame: &Frame, ai: leptos::prelude::RwSignal<AiSta
             }
             true
         }
-        "ai:prompt" if frame.status == FrameStatus::Item => {
+        "ai:prompt" | "ai:preview" if frame.status == FrameStatus::Item => {
+            let mut streamed = false;
+            ai.update(|a| streamed = apply_assistant_delta(a, frame));
+            if streamed {
+                return true;
+            }
+            if let Some(parent_id) = frame.parent_id.as_deref()
+                && frame.data.get("kind").and_then(serde_json::Value::as_str) == Some("assistant_text")
+            {
+                ai.update(|a| clear_streamed_messages(a, parent_id));
+            }
             if let Some(tool_msg) = parse_tool_activity_message(frame) {
                 ai.update(|a| {
                     a.messages.push(tool_msg);
@@ -6,8 +6,11 @@ fn msg(id: &str, role: &str, content: &str, timestamp: f64) -> AiMessage {
 
 #[test]
 fn upsert_ai_user_message_updates_existing_user_message_content() {
-    let mut ai =
-        AiState { messages: vec![msg("m1", "user", "old", 0.0), msg("m2", "assistant", "reply", 10.0)], loading: true };
+    let mut ai = AiState {
+        messages: vec![msg("m1", "user", "old", 0.0), msg("m2", "assistant", "reply", 10.0)],
+        loading: true,
+        preview: None,
+    };
 
     upsert_ai_user_message(&mut ai, msg("m1", "user", "new", 42.0));
 
@@ -18,7 +21,7 @@ fn upsert_ai_user_message_updates_existing_user_message_content() {
 
 #[test]
 fn upsert_ai_user_message_preserves_existing_nonzero_timestamp() {
-    let mut ai = AiState { messages: vec![msg("m1", "user", "old", 7.0)], loading: false };
+    let mut ai = AiState { messages: vec![msg("m1", "user", "old", 7.0)], loading: false, preview: None };
 
     upsert_ai_user_message(&mut ai, msg("m1", "user", "new", 99.0));
 
@@ -82,3 +85,132 @@ fn parse_tool_activity_message_summarizes_tool_result() {
     let msg_err = parse_tool_activity_message(&frame_err).expect("tool activity message");
     assert_eq!(msg_err.content, "`createSvgObject` failed");
 }
+
+fn delta_frame(iteration: i64, content: &str) -> Frame {
+    Frame {
+        id: uuid::Uuid::new_v4().to_string(),
+        parent_id: Some("req-1".to_owned()),
+        ts: 5,
+        board_id: None,
+        from: None,
+        syscall: "ai:prompt".to_owned(),
+        status: crate::net::types::FrameStatus::Item,
+        trace: None,
+        data: serde_json::json!({
+            "role": "assistant",
+            "kind": "assistant_delta",
+            "iteration": iteration,
+            "content": content
+        }),
+    }
+}
+
+#[test]
+fn apply_assistant_delta_appends_per_iteration_and_clear_removes_them() {
+    let mut ai = AiState::default();
+    assert!(apply_assistant_delta(&mut ai, &delta_frame(0, "Hel")));
+    assert!(apply_assistant_delta(&mut ai, &delta_frame(0, "lo")));
+    assert!(apply_assistant_delta(&mut ai, &delta_frame(1, "Next")));
+    assert_eq!(ai.messages.len(), 2);
+    assert_eq!(ai.messages[0].content, "Hello");
+    assert_eq!(ai.messages[1].content, "Next");
+    assert!(ai.loading);
+
+    ai.messages.push(msg("keep", "user", "hi", 1.0));
+    clear_streamed_messages(&mut ai, "req-1");
+    assert_eq!(ai.messages.len(), 1);
+    assert_eq!(ai.messages[0].id, "keep");
+}
+
+#[test]
+fn apply_assistant_delta_ignores_other_items() {
+    let mut ai = AiState::default();
+    let frame = frame_with_tool_item("tool_call", "createShape", None);
+    assert!(!apply_assistant_delta(&mut ai, &frame));
+    assert!(ai.messages.is_empty());
+}
+
+#[test]
+fn parse_tool_activity_message_accepts_preview_items() {
+    let mut frame = frame_with_tool_item("tool_call", "createShape", None);
+    frame.syscall = "ai:preview".to_owned();
+    assert!(parse_tool_activity_message(&frame).is_some());
+}
+
+fn settle_frame(syscall: &str, status: crate::net::types::FrameStatus, preview_id: &str) -> Frame {
+    Frame {
+        id: "f-settle".to_owned(),
+        parent_id: Some("req-2".to_owned()),
+        ts: 5,
+        board_id: None,
+        from: None,
+        syscall: syscall.to_owned(),
+        status,
+        trace: None,
+        data: serde_json::json!({ "preview_id": preview_id }),
+    }
+}
+
+fn with_preview(id: &str) -> AiState {
+    AiState {
+        preview: Some(crate::state::ai::AiPreview { id: id.to_owned(), ..Default::default() }),
+        ..AiState::default()
+    }
+}
+
+#[test]
+fn store_ai_preview_keeps_done_and_drops_cancelled() {
+    use crate::net::types::FrameStatus;
+
+    let mut ai = AiState::default();
+    let mut done = settle_frame("ai:preview", FrameStatus::Done, "p1");
+    done.data["ops"] = serde_json::json!([{ "op": "delete", "id": "o1" }]);
+    store_ai_preview(&mut ai, &done);
+    let preview = ai.preview.clone().expect("preview stored");
+    assert_eq!(preview.id, "p1");
+    assert_eq!(preview.deleted, vec!["o1".to_owned()]);
+
+    store_ai_preview(&mut ai, &settle_frame("ai:prompt", FrameStatus::Done, "p2"));
+    assert!(ai.preview.is_some());
+
+    store_ai_preview(&mut ai, &settle_frame("ai:preview", FrameStatus::Cancel, "p2"));
+    assert!(ai.preview.is_none());
+}
+
+#[test]
+fn settle_ai_preview_clears_only_matching_done() {
+    use crate::net::types::FrameStatus;
+
+    let mut ai = with_preview("p1");
+    assert!(settle_ai_preview(
+        &mut ai,
+        &settle_frame("ai:apply", FrameStatus::Done, "other")
+    ));
+    assert!(ai.preview.is_some());
+
+    assert!(settle_ai_preview(&mut ai, &settle_frame("ai:discard", FrameStatus::Done, "p1")));
+    assert!(ai.preview.is_none());
+    assert!(ai.messages.is_empty());
+}
+
+#[test]
+fn settle_ai_preview_error_clears_and_reports() {
+    use crate::net::types::FrameStatus;
+
+    let mut ai = with_preview("p1");
+    let mut frame = settle_frame("ai:apply", FrameStatus::Error, "p1");
+    frame.data = serde_json::json!({ "message": "preview not found" });
+    assert!(settle_ai_preview(&mut ai, &frame));
+    assert!(ai.preview.is_none());
+    assert_eq!(ai.messages.len(), 1);
+    assert_eq!(ai.messages[0].role, "error");
+    assert_eq!(ai.messages[0].content, "preview not found");
+}
+
+#[test]
+fn settle_ai_preview_ignores_other_syscalls() {
+    let mut ai = with_preview("p1");
+    let frame = settle_frame("object:batch", crate::net::types::FrameStatus::Done, "p1");
+    assert!(!settle_ai_preview(&mut ai, &frame));
+    assert!(ai.preview.is_some());
+}
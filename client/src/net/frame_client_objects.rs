@@ -23,6 +23,11 @@ pub(super) fn is_object_related_syscall(syscall: &str) -> bool {
         "object:create"
             | "object:update"
             | "object:delete"
+            | "object:batch"
+            | "history:undo"
+            | "history:redo"
+            | "ai:apply"
+            | "ai:revert"
             | "object:drag"
             | "object:drag:end"
             | "cursor:moved"
@@ -134,6 +139,12 @@ pub(super) fn apply_object_frame(frame: &Frame, board: &mut BoardState) {
                 board.bump_scene_rev();
             }
         }
+        // Undo, redo, and applied AI previews reply with the same `ops` list as a batch.
+        "object:batch" | "history:undo" | "history:redo" | "ai:apply" | "ai:revert"
+            if frame.status == FrameStatus::Done =>
+        {
+            apply_object_batch(&frame.data, board);
+        }
         "object:drag" => {
             if let Some(id) = frame.data.get("id").and_then(|v| v.as_str())
                 && let Some(existing) = board.objects.get(id as &str)
@@ -357,6 +368,50 @@ pub(super) fn upsert_presence_from_payload(board: &mut BoardState, data: &serde_
     );
 }
 
+/// Apply every entry of an `object:batch` done frame, bumping the scene revision once.
+#[cfg(any(test, feature = "hydrate"))]
+fn apply_object_batch(data: &serde_json::Value, board: &mut BoardState) {
+    use crate::net::types::BoardObject;
+    let Some(ops) = data.get("ops").and_then(|v| v.as_array()) else {
+        return;
+    };
+    let mut changed = false;
+    for op in ops {
+        let Some(id) = op.get("id").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        match op.get("op").and_then(|v| v.as_str()) {
+            Some("create") => {
+                if let Ok(obj) = serde_json::from_value::<BoardObject>(op.clone()) {
+                    board.objects.insert(obj.id.clone(), obj);
+                    changed = true;
+                }
+            }
+            Some("update") => {
+                if let Some(existing) = board.objects.get_mut(id) {
+                    merge_object_update(existing, op);
+                    board.drag_objects.remove(id);
+                    board.drag_updated_at.remove(id);
+                    changed = true;
+                } else {
+                    board.selection.remove(id);
+                }
+            }
+            Some("delete") => {
+                board.objects.remove(id);
+                board.selection.remove(id);
+                board.drag_objects.remove(id);
+                board.drag_updated_at.remove(id);
+                changed = true;
+            }
+            _ => {}
+        }
+    }
+    if changed {
+        board.bump_scene_rev();
+    }
+}
+
 /// Merge partial object updates into an existing `BoardObject`.
 #[cfg(any(test, feature = "hydrate"))]
 pub(super) fn merge_object_update(obj: &mut crate::net::types::BoardObject, data: &serde_json::Value) {
@@ -40,6 +40,8 @@ fn is_object_related_syscall_recognizes_supported_routes() {
     assert!(is_object_related_syscall("object:update"));
     assert!(is_object_related_syscall("object:drag"));
     assert!(is_object_related_syscall("cursor:moved"));
+    assert!(is_object_related_syscall("ai:apply"));
+    assert!(is_object_related_syscall("ai:revert"));
     assert!(!is_object_related_syscall("board:list"));
 }
 
@@ -113,3 +115,61 @@ fn apply_object_frame_create_done_reconciles_optimistic_local_id() {
     assert!(!board.selection.contains("local-1"));
     assert!(board.selection.contains("server-1"));
 }
+
+#[test]
+fn apply_object_frame_batch_applies_all_ops_with_one_scene_bump() {
+    let mut board = BoardState::default();
+    board.objects.insert("o1".to_owned(), obj("o1"));
+    board.objects.insert("o2".to_owned(), obj("o2"));
+    board.selection.insert("o2".to_owned());
+    let rev_before = board.scene_rev;
+
+    let f = frame(
+        "object:batch",
+        FrameStatus::Done,
+        serde_json::json!({
+            "ops": [
+                { "op": "update", "id": "o1", "x": 50.0, "version": 2 },
+                { "op": "delete", "id": "o2" },
+                {
+                    "op": "create",
+                    "id": "o3",
+                    "board_id": "b1",
+                    "kind": "ellipse",
+                    "x": 5.0,
+                    "y": 6.0,
+                    "width": 10.0,
+                    "height": 10.0,
+                    "rotation": 0.0,
+                    "z_index": 2,
+                    "props": {},
+                    "version": 1
+                }
+            ]
+        }),
+    );
+    apply_object_frame(&f, &mut board);
+
+    assert_eq!(board.objects.get("o1").map(|o| o.x), Some(50.0));
+    assert_eq!(board.objects.get("o1").map(|o| o.version), Some(2));
+    assert!(!board.objects.contains_key("o2"));
+    assert!(!board.selection.contains("o2"));
+    assert_eq!(board.objects.get("o3").map(|o| o.kind.as_str()), Some("ellipse"));
+    assert_eq!(board.scene_rev, rev_before + 1);
+}
+
+#[test]
+fn apply_object_frame_history_undo_applies_ops_like_a_batch() {
+    let mut board = BoardState::default();
+    board.objects.insert("o1".to_owned(), obj("o1"));
+
+    let f = frame(
+        "history:undo",
+        FrameStatus::Done,
+        serde_json::json!({ "ops": [{ "op": "delete", "id": "o1" }] }),
+    );
+    assert!(is_object_related_syscall(&f.syscall));
+    apply_object_frame(&f, &mut board);
+
+    assert!(!board.objects.contains_key("o1"));
+}
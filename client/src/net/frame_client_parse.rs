@@ -7,7 +7,7 @@ mod frame_client_parse_test;
 #[cfg(any(test, feature = "hydrate"))]
 use crate::net::types::Frame;
 #[cfg(any(test, feature = "hydrate"))]
-use crate::state::ai::AiMessage;
+use crate::state::ai::{AiMessage, AiPreview};
 #[cfg(any(test, feature = "hydrate"))]
 use crate::state::boards::{BoardListItem, BoardListPreviewObject};
 #[cfg(any(test, feature = "hydrate"))]
@@ -41,7 +41,11 @@ pub(super) fn parse_board_list_items(data: &serde_json::Value) -> Vec<BoardListI
                         .collect::<Vec<_>>()
                 })
                 .unwrap_or_default();
-            Some(BoardListItem { id, name, owner_id, is_public, snapshot })
+            let thumbnail_url = row
+                .get("thumbnail_url")
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_owned);
+            Some(BoardListItem { id, name, owner_id, is_public, snapshot, thumbnail_url })
         })
         .collect()
 }
@@ -102,6 +106,28 @@ pub(super) fn parse_board_object_bulk(data: &serde_json::Value) -> Vec<crate::ne
         .unwrap_or_default()
 }
 
+/// Whether a `board:join` payload carries a reconnect delta rather than a full snapshot.
+#[cfg(any(test, feature = "hydrate"))]
+pub(super) fn is_join_delta(data: &serde_json::Value) -> bool {
+    data.get("delta")
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Object IDs removed since the client's last sync, from a delta `board:join` done payload.
+#[cfg(any(test, feature = "hydrate"))]
+pub(super) fn parse_join_deleted_ids(data: &serde_json::Value) -> Vec<String> {
+    data.get("deleted")
+        .and_then(serde_json::Value::as_array)
+        .map(|ids| {
+            ids.iter()
+                .filter_map(serde_json::Value::as_str)
+                .map(str::to_owned)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 #[cfg(any(test, feature = "hydrate"))]
 pub(super) fn parse_chat_message(frame: &Frame, data: &serde_json::Value) -> Option<ChatMessage> {
     let content = pick_str(data, &["content", "message"])?.to_owned();
@@ -207,6 +233,29 @@ pub(super) fn parse_ai_prompt_user_message(frame: &Frame) -> Option<AiMessage> {
     })
 }
 
+/// Parse the proposed changes carried by an `ai:preview` done frame.
+///
+/// Returns `None` when the frame has no `preview_id` (e.g. a cancelled run).
+#[cfg(any(test, feature = "hydrate"))]
+pub(super) fn parse_ai_preview(frame: &Frame) -> Option<AiPreview> {
+    let id = frame.data.get("preview_id")?.as_str()?.to_owned();
+    let mut preview = AiPreview { id, ..AiPreview::default() };
+    let ops = frame.data.get("ops").and_then(serde_json::Value::as_array);
+    for op in ops.into_iter().flatten() {
+        match op.get("op").and_then(serde_json::Value::as_str) {
+            Some("create") => preview.created.extend(parse_board_object_item(op)),
+            Some("update") => preview.updated.extend(parse_board_object_item(op)),
+            Some("delete") => preview.deleted.extend(
+                op.get("id")
+                    .and_then(serde_json::Value::as_str)
+                    .map(str::to_owned),
+            ),
+            _ => {}
+        }
+    }
+    Some(preview)
+}
+
 #[cfg(any(test, feature = "hydrate"))]
 pub(super) fn frame_error_message(frame: &Frame) -> Option<&str> {
     pick_str(&frame.data, &["message", "error"])
@@ -71,3 +71,62 @@ fn parse_ai_message_value_accepts_integral_float_mutations_only() {
     .expect("message should parse");
     assert_eq!(fractional.mutations, None);
 }
+
+fn preview_op(op: &str, id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "op": op,
+        "id": id,
+        "board_id": "b1",
+        "kind": "rectangle",
+        "x": 10.0,
+        "y": 20.0,
+        "width": 30.0,
+        "height": 40.0,
+        "rotation": 0.0,
+        "z_index": 0,
+        "props": {},
+        "created_by": null,
+        "version": 1,
+        "group_id": null
+    })
+}
+
+#[test]
+fn parse_ai_preview_splits_ops_by_kind() {
+    let parsed = parse_ai_preview(&frame_with(serde_json::json!({
+        "preview_id": "p1",
+        "ops": [
+            preview_op("create", "o1"),
+            preview_op("update", "o2"),
+            { "op": "delete", "id": "o3" },
+            { "op": "bogus", "id": "o4" }
+        ]
+    })))
+    .expect("preview should parse");
+    assert_eq!(parsed.id, "p1");
+    assert_eq!(parsed.created.len(), 1);
+    assert_eq!(parsed.created[0].id, "o1");
+    assert_eq!(parsed.updated.len(), 1);
+    assert_eq!(parsed.updated[0].id, "o2");
+    assert_eq!(parsed.deleted, vec!["o3".to_owned()]);
+}
+
+#[test]
+fn parse_ai_preview_requires_preview_id() {
+    let cancelled = frame_with(serde_json::json!({ "ops": [preview_op("create", "o1")] }));
+    assert!(parse_ai_preview(&cancelled).is_none());
+}
+
+#[test]
+fn is_join_delta_reads_flag_and_defaults_false() {
+    assert!(is_join_delta(&serde_json::json!({ "delta": true })));
+    assert!(!is_join_delta(&serde_json::json!({ "delta": false })));
+    assert!(!is_join_delta(&serde_json::json!({ "objects": [] })));
+}
+
+#[test]
+fn parse_join_deleted_ids_skips_non_string_entries() {
+    let ids = parse_join_deleted_ids(&serde_json::json!({ "deleted": ["a", 3, "b"] }));
+    assert_eq!(ids, vec!["a".to_owned(), "b".to_owned()]);
+    assert!(parse_join_deleted_ids(&serde_json::json!({})).is_empty());
+}
@@ -155,6 +155,7 @@ fn upsert_ai_user_message_updates_existing_pending_row() {
             mutations: None,
         }],
         loading: true,
+        preview: None,
     };
 
     upsert_ai_user_message(
@@ -324,6 +325,20 @@ fn parse_board_list_items_tolerates_partial_snapshot_rows() {
     assert_eq!(items[0].snapshot.len(), 1);
 }
 
+#[test]
+fn parse_board_list_items_reads_optional_thumbnail_url() {
+    let data = serde_json::json!({
+        "boards": [
+            { "id": "b-1", "name": "Alpha", "thumbnail_url": "/api/boards/b-1/thumbnail.png" },
+            { "id": "b-2", "name": "Beta" }
+        ]
+    });
+
+    let items = parse_board_list_items(&data);
+    assert_eq!(items[0].thumbnail_url.as_deref(), Some("/api/boards/b-1/thumbnail.png"));
+    assert_eq!(items[1].thumbnail_url, None);
+}
+
 #[test]
 fn deleted_board_id_prefers_payload_then_board_id_fallback() {
     let frame_with_payload = frame(
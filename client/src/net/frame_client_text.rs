@@ -0,0 +1,161 @@
+//! Collaborative text frame handlers extracted from `frame_client`.
+
+#[cfg(test)]
+#[path = "frame_client_text_test.rs"]
+mod frame_client_text_test;
+
+#[cfg(any(test, feature = "hydrate"))]
+use crate::net::types::{Frame, FrameStatus};
+#[cfg(any(test, feature = "hydrate"))]
+use crate::state::board::BoardState;
+
+/// Read a non-negative integer that may have crossed the wire as a float.
+#[cfg(any(test, feature = "hydrate"))]
+fn data_u64(data: &serde_json::Value, key: &str) -> Option<u64> {
+    let value = data.get(key)?;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    value.as_u64().or_else(|| {
+        value
+            .as_f64()
+            .filter(|v| v.fract() == 0.0 && *v >= 0.0)
+            .map(|v| v as u64)
+    })
+}
+
+/// Apply a `text:*` frame to board state.
+///
+/// Every `text:op` done frame refreshes the object's props and version so
+/// the canvas shows peers' typing. Frames for the active editing session
+/// also advance it; the returned frame (a buffered op or a resync request)
+/// must be sent by the caller.
+#[cfg(any(test, feature = "hydrate"))]
+pub(super) fn apply_text_frame(frame: &Frame, board: &mut BoardState) -> Option<Frame> {
+    match (frame.syscall.as_str(), frame.status) {
+        ("text:state", FrameStatus::Done) => {
+            let parent_id = frame.parent_id.as_deref()?;
+            let rev = data_u64(&frame.data, "rev")?;
+            let text = frame.data.get("text").and_then(serde_json::Value::as_str)?;
+            let session = board.text_edit.as_mut()?;
+            if !session.on_state(parent_id, rev, text) {
+                return None;
+            }
+            let id = session.object_id.clone();
+            let field = session.field.clone();
+            let local = session.text.clone();
+            set_object_text(board, &id, &field, &local, None);
+        }
+        ("text:state", FrameStatus::Error) => {
+            let session = board.text_edit.as_mut()?;
+            if frame.parent_id.is_some() && frame.parent_id.as_deref() == session.state_request_id() {
+                // Leave the session unsynced; edits fall back to `object:update` on save.
+                board.text_edit = None;
+            }
+            return None;
+        }
+        ("text:op", FrameStatus::Done) => {
+            let id = frame
+                .data
+                .get("id")
+                .and_then(serde_json::Value::as_str)?
+                .to_owned();
+            let field = frame
+                .data
+                .get("field")
+                .and_then(serde_json::Value::as_str)?
+                .to_owned();
+            let rev = data_u64(&frame.data, "rev")?;
+            let server_text = frame.data.get("text").and_then(serde_json::Value::as_str)?;
+            let version = data_u64(&frame.data, "version").and_then(|v| i64::try_from(v).ok());
+
+            let mut text = server_text.to_owned();
+            if let Some(session) = board.text_edit.as_mut().filter(|s| s.is_for(&id, &field)) {
+                if let Some(parent_id) = frame.parent_id.as_deref() {
+                    session.on_ack(parent_id, rev);
+                } else {
+                    let op = frame
+                        .data
+                        .get("op")
+                        .and_then(|raw| frames::text_ops::TextOp::from_json(raw).ok())?;
+                    session.on_remote(rev, op);
+                }
+                text.clone_from(&session.text);
+            }
+            set_object_text(board, &id, &field, &text, version);
+        }
+        ("text:op", FrameStatus::Error) => {
+            let session = board.text_edit.as_mut()?;
+            if frame.parent_id.is_none() || frame.parent_id.as_deref() != session.pending_request_id() {
+                return None;
+            }
+            session.desync();
+        }
+        _ => return None,
+    }
+    next_text_frame(board)
+}
+
+/// Next outbound frame for the active editing session, if any.
+#[cfg(any(test, feature = "hydrate"))]
+pub(super) fn next_text_frame(board: &mut BoardState) -> Option<Frame> {
+    let board_id = board.board_id.clone();
+    board.text_edit.as_mut()?.next_frame(board_id)
+}
+
+#[cfg(any(test, feature = "hydrate"))]
+fn set_object_text(board: &mut BoardState, id: &str, field: &str, text: &str, version: Option<i64>) {
+    let Some(obj) = board.objects.get_mut(id) else {
+        return;
+    };
+    if !obj.props.is_object() {
+        obj.props = serde_json::json!({});
+    }
+    if let Some(props) = obj.props.as_object_mut() {
+        props.insert(field.to_owned(), serde_json::json!(text));
+    }
+    if let Some(version) = version {
+        obj.version = obj.version.max(version);
+    }
+    board.bump_scene_rev();
+}
+
+#[cfg(feature = "hydrate")]
+pub(super) fn handle_text_frame(
+    frame: &Frame,
+    board: leptos::prelude::RwSignal<BoardState>,
+    tx: &futures::channel::mpsc::UnboundedSender<Vec<u8>>,
+) -> bool {
+    use leptos::prelude::Update;
+
+    if !frame.syscall.starts_with("text:") {
+        return false;
+    }
+    let mut outbound = None;
+    board.update(|b| outbound = apply_text_frame(frame, b));
+    if let Some(next) = outbound {
+        let _ = super::send_frame(tx, &next);
+    }
+    true
+}
+
+/// Resync the active editing session after a (re)join.
+///
+/// Acknowledgements lost with a dropped socket would otherwise leave an op
+/// in flight forever.
+#[cfg(feature = "hydrate")]
+pub(super) fn resync_text_session(
+    board: leptos::prelude::RwSignal<BoardState>,
+    tx: &futures::channel::mpsc::UnboundedSender<Vec<u8>>,
+) {
+    use leptos::prelude::Update;
+
+    let mut outbound = None;
+    board.update(|b| {
+        if let Some(session) = b.text_edit.as_mut() {
+            session.desync();
+            outbound = next_text_frame(b);
+        }
+    });
+    if let Some(next) = outbound {
+        let _ = super::send_frame(tx, &next);
+    }
+}
@@ -0,0 +1,134 @@
+use super::*;
+use crate::net::types::BoardObject;
+use crate::state::text_edit::TextEditSession;
+
+fn object(id: &str, text: &str) -> BoardObject {
+    BoardObject {
+        id: id.to_owned(),
+        board_id: "b1".to_owned(),
+        kind: "sticky_note".to_owned(),
+        x: 0.0,
+        y: 0.0,
+        width: None,
+        height: None,
+        rotation: 0.0,
+        z_index: 0,
+        props: serde_json::json!({ "text": text, "color": "#fff" }),
+        created_by: None,
+        version: 1,
+        group_id: None,
+    }
+}
+
+fn frame(syscall: &str, status: FrameStatus, parent_id: Option<&str>, data: serde_json::Value) -> Frame {
+    Frame {
+        id: "f1".to_owned(),
+        parent_id: parent_id.map(str::to_owned),
+        ts: 0,
+        board_id: Some("b1".to_owned()),
+        from: None,
+        syscall: syscall.to_owned(),
+        status,
+        trace: None,
+        data,
+    }
+}
+
+fn board_with_session(text: &str) -> (BoardState, String) {
+    let mut board = BoardState { board_id: Some("b1".to_owned()), ..BoardState::default() };
+    board.objects.insert("o1".to_owned(), object("o1", text));
+    board.text_edit = Some(TextEditSession::new("o1".to_owned(), "text".to_owned(), text.to_owned()));
+    let state = next_text_frame(&mut board).expect("state request");
+    (board, state.id)
+}
+
+#[test]
+fn state_reply_syncs_session_and_flushes_nothing_without_edits() {
+    let (mut board, state_id) = board_with_session("hi");
+    let reply = frame(
+        "text:state",
+        FrameStatus::Done,
+        Some(&state_id),
+        serde_json::json!({ "id": "o1", "field": "text", "rev": 2.0, "text": "hi there" }),
+    );
+    assert!(apply_text_frame(&reply, &mut board).is_none());
+    let session = board.text_edit.as_ref().expect("session");
+    assert_eq!(session.text, "hi there");
+    assert_eq!(board.objects["o1"].props["text"], "hi there");
+}
+
+#[test]
+fn peer_op_updates_object_text_and_version() {
+    let mut board = BoardState::default();
+    board.objects.insert("o1".to_owned(), object("o1", "abc"));
+    let peer = frame(
+        "text:op",
+        FrameStatus::Done,
+        None,
+        serde_json::json!({ "id": "o1", "field": "text", "rev": 4.0, "op": [3, "d"], "text": "abcd", "version": 5.0 }),
+    );
+    assert!(apply_text_frame(&peer, &mut board).is_none());
+    assert_eq!(board.objects["o1"].props["text"], "abcd");
+    assert_eq!(board.objects["o1"].version, 5);
+}
+
+#[test]
+fn ack_releases_buffered_edits() {
+    let (mut board, state_id) = board_with_session("x");
+    let reply = frame(
+        "text:state",
+        FrameStatus::Done,
+        Some(&state_id),
+        serde_json::json!({ "rev": 0, "text": "x" }),
+    );
+    apply_text_frame(&reply, &mut board);
+
+    let session = board.text_edit.as_mut().expect("session");
+    session.local_edit("xy", 2);
+    let sent = next_text_frame(&mut board).expect("op frame");
+    board
+        .text_edit
+        .as_mut()
+        .expect("session")
+        .local_edit("xyz", 3);
+    assert!(next_text_frame(&mut board).is_none());
+
+    let ack = frame(
+        "text:op",
+        FrameStatus::Done,
+        Some(&sent.id),
+        serde_json::json!({ "id": "o1", "field": "text", "rev": 1, "op": [1, "y"], "text": "xy", "version": 2 }),
+    );
+    let next = apply_text_frame(&ack, &mut board).expect("buffered op flushed");
+    assert_eq!(next.syscall, "text:op");
+    assert_eq!(next.data["rev"], 1);
+    // The canvas shows the local text, not the server's lagging copy.
+    assert_eq!(board.objects["o1"].props["text"], "xyz");
+}
+
+#[test]
+fn rejected_op_triggers_resync_request() {
+    let (mut board, state_id) = board_with_session("x");
+    let reply = frame(
+        "text:state",
+        FrameStatus::Done,
+        Some(&state_id),
+        serde_json::json!({ "rev": 0, "text": "x" }),
+    );
+    apply_text_frame(&reply, &mut board);
+    board
+        .text_edit
+        .as_mut()
+        .expect("session")
+        .local_edit("x!", 2);
+    let sent = next_text_frame(&mut board).expect("op frame");
+
+    let error = frame(
+        "text:op",
+        FrameStatus::Error,
+        Some(&sent.id),
+        serde_json::json!({ "code": "E_TEXT_REVISION_EXPIRED" }),
+    );
+    let next = apply_text_frame(&error, &mut board).expect("resync request");
+    assert_eq!(next.syscall, "text:state");
+}
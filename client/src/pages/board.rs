@@ -35,10 +35,11 @@ use crate::components::toolbar::Toolbar;
 use crate::components::trace_view::TraceView;
 use crate::pages::board_prompt::assistant_preview_and_has_more;
 use crate::pages::board_prompt_bar::{BoardPromptBar, PromptBarStatus};
-use crate::state::ai::{AiMessage, AiState};
+use crate::state::ai::{AiMessage, AiPreview, AiState};
 use crate::state::auth::AuthState;
 use crate::state::board::BoardState;
 use crate::state::canvas_view::CanvasViewState;
+use crate::state::text_edit::{TextEditSession, char_to_utf16_index, utf16_to_char_index};
 use crate::state::ui::{RightTab, UiState, ViewMode};
 use crate::util::auth::install_unauth_redirect;
 use crate::util::frame::request_frame;
@@ -64,6 +65,22 @@ fn build_board_membership_frame(syscall: &str, board_id: String) -> crate::net::
     request_frame(syscall, Some(board_id), serde_json::json!({}))
 }
 
+fn build_preview_decision_frame(
+    syscall: &str,
+    board_id: Option<String>,
+    preview_id: String,
+) -> crate::net::types::Frame {
+    request_frame(syscall, board_id, serde_json::json!({ "preview_id": preview_id }))
+}
+
+fn build_board_join_frame(board_id: String, since_seq: Option<i64>) -> crate::net::types::Frame {
+    let data = match since_seq {
+        Some(seq) => serde_json::json!({ "since_seq": seq }),
+        None => serde_json::json!({}),
+    };
+    request_frame("board:join", Some(board_id), data)
+}
+
 fn reset_board_for_route_change(board: &mut BoardState, next_board_id: Option<String>) {
     board.board_id = next_board_id;
     board.board_name = None;
@@ -78,6 +95,7 @@ fn reset_board_for_route_change(board: &mut BoardState, next_board_id: Option<St
     board.drag_updated_at.clear();
     board.cursor_updated_at.clear();
     board.join_streaming = false;
+    board.sync_seq = None;
     board.selection.clear();
     board.presence.clear();
     board.join_round_trip_ms = None;
@@ -85,6 +103,7 @@ fn reset_board_for_route_change(board: &mut BoardState, next_board_id: Option<St
     board.pending_join_started_ms = None;
     board.pending_create_request_ids.clear();
     board.scene_rev = 0;
+    board.text_edit = None;
 }
 
 /// Board page — composes toolbar, panels, canvas placeholder, and status bar
@@ -108,10 +127,14 @@ pub fn BoardPage() -> impl IntoView {
     let prompt_preview_assistant = RwSignal::new(String::new());
     let prompt_preview_assistant_has_more = RwSignal::new(false);
     let prompt_preview_assistant_error = RwSignal::new(false);
+    let prompt_preview_first = RwSignal::new(false);
     let object_text_dialog_open = RwSignal::new(false);
     let object_text_dialog_id = RwSignal::new(None::<String>);
     let object_text_dialog_value = RwSignal::new(String::new());
     let last_object_text_dialog_seq = RwSignal::new(0_u64);
+    let object_text_dialog_caret = RwSignal::new(None::<u32>);
+    let object_text_session_target = RwSignal::new(None::<String>);
+    let last_object_text_remote_seq = RwSignal::new(0_u64);
     let help_modal_open = RwSignal::new(false);
     let restored_draft_board_id = RwSignal::new(None::<String>);
 
@@ -139,6 +162,8 @@ pub fn BoardPage() -> impl IntoView {
         // WHY: board data is board-id scoped, but websocket client identity is
         // connection-scoped and intentionally preserved.
         board.update(|b| reset_board_for_route_change(b, next_id.clone()));
+        // Previews are held per board connection; the server drops them on part.
+        ai.update(|a| a.preview = None);
         ui.update(|u| {
             u.animation_clip_object_id = None;
             u.animation_playing = false;
@@ -199,7 +224,8 @@ pub fn BoardPage() -> impl IntoView {
             return;
         }
 
-        let frame = build_board_membership_frame("board:join", board_id);
+        // Reconnects on the same board ask for a delta from the last synced sequence.
+        let frame = build_board_join_frame(board_id, state.sync_seq);
         board.update(|b| {
             b.pending_join_request_id = Some(frame.id.clone());
             #[cfg(feature = "hydrate")]
@@ -222,6 +248,7 @@ pub fn BoardPage() -> impl IntoView {
                 .get()
                 .send(&build_board_membership_frame("board:part", board_id));
         }
+        ai.update(|a| a.preview = None);
 
         board.update(|b| {
             b.board_id = None;
@@ -235,6 +262,7 @@ pub fn BoardPage() -> impl IntoView {
             b.drag_updated_at.clear();
             b.cursor_updated_at.clear();
             b.join_streaming = false;
+            b.sync_seq = None;
             b.selection.clear();
             b.presence.clear();
             b.join_round_trip_ms = None;
@@ -329,6 +357,92 @@ pub fn BoardPage() -> impl IntoView {
         }
     });
 
+    // Start a collaborative text session once per dialog open, after the
+    // board join settles. Draft text restored from storage is folded in as
+    // a local edit and rebased onto the server copy.
+    Effect::new(move || {
+        if !object_text_dialog_open.get() {
+            object_text_session_target.set(None);
+            return;
+        }
+        let Some(target_id) = object_text_dialog_id.get() else {
+            return;
+        };
+        if object_text_session_target.get_untracked().as_deref() == Some(target_id.as_str()) {
+            return;
+        }
+        let state = board.get();
+        if state.connection_status != crate::state::board::ConnectionStatus::Connected
+            || state.join_streaming
+            || state.pending_join_request_id.is_some()
+        {
+            return;
+        }
+        let Some(obj) = state.objects.get(&target_id) else {
+            return;
+        };
+        object_text_session_target.set(Some(target_id.clone()));
+        last_object_text_remote_seq.set(0);
+        if state
+            .text_edit
+            .as_ref()
+            .is_some_and(|session| session.is_for(&target_id, "text"))
+        {
+            return;
+        }
+
+        let saved = obj
+            .props
+            .get("text")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_owned();
+        let mut session = TextEditSession::new(target_id, "text".to_owned(), saved);
+        let draft = object_text_dialog_value.get_untracked();
+        session.local_edit(&draft, draft.chars().count());
+        let frame = session.next_frame(state.board_id.clone());
+        board.update(|b| b.text_edit = Some(session));
+        if let Some(frame) = frame {
+            sender.get_untracked().send(&frame);
+        }
+    });
+
+    // Mirror remote edits into the dialog, carrying the remapped caret.
+    Effect::new(move || {
+        let Some(target_id) = object_text_dialog_id.get() else {
+            return;
+        };
+        let remote = board.with(|b| {
+            b.text_edit
+                .as_ref()
+                .filter(|session| session.is_for(&target_id, "text"))
+                .map(|session| {
+                    let caret = char_to_utf16_index(&session.text, session.caret);
+                    (session.remote_seq, session.text.clone(), caret)
+                })
+        });
+        let Some((seq, text, caret)) = remote else {
+            return;
+        };
+        if seq == last_object_text_remote_seq.get_untracked() {
+            return;
+        }
+        last_object_text_remote_seq.set(seq);
+        object_text_dialog_value.set(text);
+        object_text_dialog_caret.set(Some(u32::try_from(caret).unwrap_or(u32::MAX)));
+    });
+
+    let object_text_live = Signal::derive(move || {
+        let Some(target_id) = object_text_dialog_id.get() else {
+            return false;
+        };
+        board.with(|b| {
+            b.text_edit
+                .as_ref()
+                .is_some_and(|session| session.is_for(&target_id, "text"))
+        })
+    });
+
     let send_prompt = move || {
         let text = prompt_input.get();
         if text.trim().is_empty() || ai.get().loading {
@@ -336,11 +450,20 @@ pub fn BoardPage() -> impl IntoView {
         }
 
         let prompt = text.trim().to_owned();
-        let frame = request_frame(
-            "ai:prompt",
-            board.get().board_id.clone(),
-            serde_json::json!({ "prompt": prompt }),
-        );
+        let board_id = board.get().board_id.clone();
+        // A new run replaces any preview still on screen.
+        if let Some(stale) = ai.get_untracked().preview {
+            sender
+                .get()
+                .send(&build_preview_decision_frame("ai:discard", board_id.clone(), stale.id));
+            ai.update(|a| a.preview = None);
+        }
+        let syscall = if prompt_preview_first.get_untracked() {
+            "ai:preview"
+        } else {
+            "ai:prompt"
+        };
+        let frame = request_frame(syscall, board_id, serde_json::json!({ "prompt": prompt }));
         let frame_id = frame.id.clone();
 
         prompt_status.set(PromptBarStatus::Loading);
@@ -383,6 +506,30 @@ pub fn BoardPage() -> impl IntoView {
 
     let on_prompt_submit = Callback::new(move |()| send_prompt());
 
+    let prompt_pending_preview = Signal::derive(move || ai.with(|a| a.preview.as_ref().map(AiPreview::summary)));
+    let on_preview_apply = Callback::new(move |()| {
+        let Some(preview) = ai.get_untracked().preview else {
+            return;
+        };
+        // The preview clears when the `ai:apply` broadcast comes back.
+        sender.get().send(&build_preview_decision_frame(
+            "ai:apply",
+            board.get_untracked().board_id,
+            preview.id,
+        ));
+    });
+    let on_preview_discard = Callback::new(move |()| {
+        let Some(preview) = ai.get_untracked().preview else {
+            return;
+        };
+        sender.get().send(&build_preview_decision_frame(
+            "ai:discard",
+            board.get_untracked().board_id,
+            preview.id,
+        ));
+        ai.update(|a| a.preview = None);
+    });
+
     let on_prompt_read_more = Callback::new(move |()| {
         ui.update(|u| {
             u.right_panel_expanded = true;
@@ -403,11 +550,46 @@ pub fn BoardPage() -> impl IntoView {
         }
     });
 
+    let on_object_text_input = Callback::new(move |(text, at): (String, u32)| {
+        let Some(id) = object_text_dialog_id.get_untracked() else {
+            return;
+        };
+        let mut outbound = None;
+        board.update(|b| {
+            let board_id = b.board_id.clone();
+            let Some(session) = b
+                .text_edit
+                .as_mut()
+                .filter(|session| session.is_for(&id, "text"))
+            else {
+                return;
+            };
+            let caret = utf16_to_char_index(&text, usize::try_from(at).unwrap_or(usize::MAX));
+            session.local_edit(&text, caret);
+            outbound = session.next_frame(board_id);
+            if let Some(obj) = b.objects.get_mut(&id)
+                && let Some(props) = obj.props.as_object_mut()
+            {
+                props.insert("text".to_owned(), serde_json::json!(text));
+                b.bump_scene_rev();
+            }
+        });
+        if let Some(frame) = outbound {
+            sender.get_untracked().send(&frame);
+        }
+    });
+
     let on_object_text_save = Callback::new(move |()| {
         let Some(id) = object_text_dialog_id.get() else {
             object_text_dialog_open.set(false);
             return;
         };
+        if object_text_live.get_untracked() {
+            // Edits were already sent as `text:op` while typing.
+            object_text_dialog_open.set(false);
+            object_text_dialog_id.set(None);
+            return;
+        }
         let value = object_text_dialog_value.get();
         let Some(obj) = board.get().objects.get(&id).cloned() else {
             object_text_dialog_open.set(false);
@@ -478,7 +660,11 @@ pub fn BoardPage() -> impl IntoView {
                         prompt_preview_assistant=prompt_preview_assistant
                         prompt_preview_assistant_has_more=prompt_preview_assistant_has_more
                         prompt_preview_assistant_error=prompt_preview_assistant_error
+                        prompt_preview_first=prompt_preview_first
+                        pending_preview=prompt_pending_preview
                         on_submit=on_prompt_submit
+                        on_preview_apply=on_preview_apply
+                        on_preview_discard=on_preview_discard
                         on_read_more=on_prompt_read_more
                     />
                 </div>
@@ -512,6 +698,9 @@ pub fn BoardPage() -> impl IntoView {
             view! {
                 <ObjectTextDialog
                     value=object_text_dialog_value
+                    caret=object_text_dialog_caret
+                    live=object_text_live
+                    on_input=on_object_text_input
                     on_cancel=on_object_text_cancel
                     on_save=on_object_text_save
                     on_keydown=on_object_text_keydown
@@ -1,4 +1,5 @@
-//! Board prompt input and inline preview UI.
+//! Board prompt input, inline reply preview, and apply/discard controls for
+//! a pending `ai:preview`.
 
 #[cfg(test)]
 #[path = "board_prompt_bar_test.rs"]
@@ -23,8 +24,12 @@ pub(crate) fn BoardPromptBar(
     prompt_preview_assistant: RwSignal<String>,
     prompt_preview_assistant_has_more: RwSignal<bool>,
     prompt_preview_assistant_error: RwSignal<bool>,
+    prompt_preview_first: RwSignal<bool>,
+    pending_preview: Signal<Option<String>>,
     on_submit: Callback<()>,
     on_read_more: Callback<()>,
+    on_preview_apply: Callback<()>,
+    on_preview_discard: Callback<()>,
 ) -> impl IntoView {
     let on_prompt_keydown = move |ev: leptos::ev::KeyboardEvent| {
         if ev.key() == "Enter" && !ev.shift_key() {
@@ -68,6 +73,22 @@ pub(crate) fn BoardPromptBar(
                     </span>
                 </div>
             </div>
+            <Show when=move || pending_preview.get().is_some()>
+                <div class="board-page__prompt-pending" role="group" aria-label="Proposed AI changes">
+                    <span class="board-page__prompt-pending-summary">
+                        {move || format!("Proposed: {}", pending_preview.get().unwrap_or_default())}
+                    </span>
+                    <button class="board-page__prompt-pending-action" on:click=move |_| on_preview_apply.run(())>
+                        "Apply"
+                    </button>
+                    <button
+                        class="board-page__prompt-pending-action board-page__prompt-pending-action--discard"
+                        on:click=move |_| on_preview_discard.run(())
+                    >
+                        "Discard"
+                    </button>
+                </div>
+            </Show>
             <div class="board-page__input-row">
                 <input
                     class="board-page__input-line"
@@ -96,6 +117,14 @@ pub(crate) fn BoardPromptBar(
                     }}
                 </div>
             </div>
+            <label class="board-page__prompt-mode">
+                <input
+                    type="checkbox"
+                    prop:checked=move || prompt_preview_first.get()
+                    on:change=move |ev| prompt_preview_first.set(event_target_checked(&ev))
+                />
+                "Preview changes before applying"
+            </label>
         </div>
     }
 }
@@ -32,6 +32,34 @@ fn build_board_membership_frame_sets_protocol_fields() {
     assert_eq!(frame.data, serde_json::json!({}));
 }
 
+#[test]
+fn build_board_join_frame_includes_since_seq_only_when_known() {
+    let fresh = build_board_join_frame("b-1".to_owned(), None);
+    assert_eq!(fresh.syscall, "board:join");
+    assert_eq!(fresh.data, serde_json::json!({}));
+
+    let resync = build_board_join_frame("b-1".to_owned(), Some(42));
+    assert_eq!(resync.board_id.as_deref(), Some("b-1"));
+    assert_eq!(resync.data, serde_json::json!({ "since_seq": 42 }));
+}
+
+#[test]
+fn build_preview_decision_frame_carries_preview_id() {
+    let frame = build_preview_decision_frame("ai:apply", Some("b-1".to_owned()), "p-1".to_owned());
+    assert_eq!(frame.syscall, "ai:apply");
+    assert_eq!(frame.board_id.as_deref(), Some("b-1"));
+    assert_eq!(frame.data, serde_json::json!({ "preview_id": "p-1" }));
+}
+
+#[test]
+fn reset_board_for_route_change_clears_sync_seq() {
+    let mut board = BoardState { sync_seq: Some(7), ..BoardState::default() };
+
+    reset_board_for_route_change(&mut board, Some("b-new".to_owned()));
+
+    assert!(board.sync_seq.is_none());
+}
+
 #[test]
 fn assistant_preview_shows_up_to_three_plain_paragraphs_without_more() {
     let text = "Para one.\n\nPara two.\n\nPara three.";
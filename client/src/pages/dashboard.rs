@@ -359,6 +359,7 @@ fn BoardSection(title: &'static str, items: Vec<BoardListItem>, on_delete: Callb
                                 id=b.id
                                 name=b.name
                                 snapshot=b.snapshot
+                                thumbnail_url=b.thumbnail_url
                                 on_delete=on_delete
                             />
                         }
@@ -2,12 +2,15 @@
 //!
 //! SYSTEM CONTEXT
 //! ==============
-//! Stores user-visible AI transcript + request lifecycle flags for panel UI.
+//! Stores user-visible AI transcript + request lifecycle flags for panel UI,
+//! plus the pending `ai:preview` result drawn as a ghost overlay on the canvas.
 
 #[cfg(test)]
 #[path = "ai_test.rs"]
 mod ai_test;
 
+use crate::net::types::BoardObject;
+
 /// State for the AI assistant panel.
 ///
 /// In the full Leptos implementation, fields will be `RwSignal` types
@@ -16,6 +19,50 @@ mod ai_test;
 pub struct AiState {
     pub messages: Vec<AiMessage>,
     pub loading: bool,
+    /// Changes proposed by the last `ai:preview`, awaiting apply or discard.
+    pub preview: Option<AiPreview>,
+}
+
+/// Changes proposed by an `ai:preview` request.
+///
+/// Nothing here is on the board yet; the server holds the changes under
+/// `id` until the client sends `ai:apply` or `ai:discard`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AiPreview {
+    /// Preview ID (the originating request ID).
+    pub id: String,
+    /// Objects the preview would create.
+    pub created: Vec<BoardObject>,
+    /// Existing objects as the preview would leave them.
+    pub updated: Vec<BoardObject>,
+    /// IDs of existing objects the preview would delete.
+    pub deleted: Vec<String>,
+}
+
+impl AiPreview {
+    /// True when applying the preview would change nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.updated.is_empty() && self.deleted.is_empty()
+    }
+
+    /// One-line description of the proposed changes, e.g. `"2 new, 1 changed"`.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return "No changes".to_owned();
+        }
+        [
+            (self.created.len(), "new"),
+            (self.updated.len(), "changed"),
+            (self.deleted.len(), "removed"),
+        ]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{count} {label}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
 }
 
 /// A single AI conversation message.
@@ -15,3 +15,48 @@ fn ai_state_default_not_loading() {
     let state = AiState::default();
     assert!(!state.loading);
 }
+
+// =============================================================
+// AiPreview
+// =============================================================
+
+fn preview_object(id: &str) -> BoardObject {
+    BoardObject {
+        id: id.to_owned(),
+        board_id: "b".to_owned(),
+        kind: "rectangle".to_owned(),
+        x: 0.0,
+        y: 0.0,
+        width: Some(10.0),
+        height: Some(10.0),
+        rotation: 0.0,
+        z_index: 0,
+        props: serde_json::json!({}),
+        created_by: None,
+        version: 1,
+        group_id: None,
+    }
+}
+
+#[test]
+fn ai_state_default_has_no_preview() {
+    assert!(AiState::default().preview.is_none());
+}
+
+#[test]
+fn ai_preview_summary_lists_nonzero_counts() {
+    let preview = AiPreview {
+        id: "p".to_owned(),
+        created: vec![preview_object("a"), preview_object("b")],
+        updated: Vec::new(),
+        deleted: vec!["c".to_owned()],
+    };
+    assert_eq!(preview.summary(), "2 new, 1 removed");
+}
+
+#[test]
+fn ai_preview_summary_reports_empty_preview() {
+    let preview = AiPreview { id: "p".to_owned(), ..AiPreview::default() };
+    assert!(preview.is_empty());
+    assert_eq!(preview.summary(), "No changes");
+}
@@ -12,6 +12,7 @@ mod board_test;
 use std::collections::{HashMap, HashSet};
 
 use crate::net::types::{BoardObject, Presence, Savepoint};
+use crate::state::text_edit::TextEditSession;
 
 /// Board-level state: which board is active, connection status, objects, and presence.
 #[derive(Clone, Debug, Default)]
@@ -48,6 +49,9 @@ pub struct BoardState {
     pub scene_rev: u64,
     /// True while the initial `board:join` object stream is still in flight.
     pub join_streaming: bool,
+    /// Frame sequence the local object set is known to be current through.
+    /// Sent as `since_seq` on rejoin so the server can reply with a delta.
+    pub sync_seq: Option<i64>,
     /// Access code generated for sharing, if any.
     pub generated_access_code: Option<String>,
     /// Most recent board:join round-trip latency in milliseconds.
@@ -58,6 +62,8 @@ pub struct BoardState {
     pub pending_join_started_ms: Option<f64>,
     /// Map create request frame id -> optimistic local object id for create reconciliation.
     pub pending_create_request_ids: HashMap<String, String>,
+    /// Collaborative session for the object text field being edited, if any.
+    pub text_edit: Option<TextEditSession>,
 }
 
 /// WebSocket connection status.
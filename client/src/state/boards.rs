@@ -25,6 +25,9 @@ pub struct BoardListItem {
     /// Lightweight geometry snapshot for the thumbnail preview.
     #[serde(default)]
     pub snapshot: Vec<BoardListPreviewObject>,
+    /// Server-rendered thumbnail URL; the snapshot is drawn when absent.
+    #[serde(default)]
+    pub thumbnail_url: Option<String>,
 }
 
 /// Lightweight object geometry for dashboard board previews.
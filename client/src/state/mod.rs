@@ -11,5 +11,6 @@ pub mod board;
 pub mod boards;
 pub mod canvas_view;
 pub mod chat;
+pub mod text_edit;
 pub mod trace;
 pub mod ui;
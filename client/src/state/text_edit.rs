@@ -0,0 +1,280 @@
+//! Collaborative editing session for one object text field.
+//!
+//! SYSTEM CONTEXT
+//! ==============
+//! The object text dialog edits a props field (`text`) that other clients
+//! may be typing into at the same time. The server orders operations by
+//! revision (`text:op`), and this session is the client half of that
+//! protocol: at most one operation is in flight, later keystrokes are
+//! composed into a buffer, and remote operations are transformed past both
+//! before being applied so the local text and caret stay valid.
+//!
+//! The server's done frames for our own ops and peers' broadcasts can
+//! interleave, so incoming revisions are held until every earlier revision
+//! has been applied.
+
+#[cfg(test)]
+#[path = "text_edit_test.rs"]
+mod text_edit_test;
+
+use std::collections::BTreeMap;
+
+use frames::text_ops::{TextOp, TextOpError, transform};
+
+use crate::net::types::Frame;
+use crate::util::frame::request_frame;
+
+/// Most out-of-order revisions held before giving up and resyncing.
+const MAX_EARLY_REVISIONS: usize = 64;
+
+#[derive(Clone, Debug)]
+enum Incoming {
+    /// The server accepted our in-flight operation.
+    Ack,
+    /// Another client's operation, already in server order.
+    Remote(TextOp),
+}
+
+/// Client state for a collaboratively edited text field.
+#[derive(Clone, Debug)]
+pub struct TextEditSession {
+    /// Object being edited.
+    pub object_id: String,
+    /// Props field being edited.
+    pub field: String,
+    /// Local text, including edits the server has not acknowledged.
+    pub text: String,
+    /// Caret position in `text`, in chars.
+    pub caret: usize,
+    /// Bumped whenever a remote edit or resync rewrites `text`.
+    pub remote_seq: u64,
+    /// Server revision `server_text` corresponds to; `None` until synced.
+    rev: Option<u64>,
+    /// Text at `rev` as the server has it.
+    server_text: String,
+    /// Outstanding `text:state` request ID.
+    state_request_id: Option<String>,
+    /// Operation sent to the server and awaiting acknowledgement.
+    pending: Option<(String, TextOp)>,
+    /// Local edits made while `pending` is in flight.
+    buffer: Option<TextOp>,
+    /// Revisions received ahead of `rev + 1`.
+    early: BTreeMap<u64, Incoming>,
+}
+
+impl TextEditSession {
+    /// Start a session from the text the client currently believes is saved.
+    pub fn new(object_id: String, field: String, text: String) -> Self {
+        Self {
+            object_id,
+            field,
+            caret: text.chars().count(),
+            server_text: text.clone(),
+            text,
+            remote_seq: 0,
+            rev: None,
+            state_request_id: None,
+            pending: None,
+            buffer: None,
+            early: BTreeMap::new(),
+        }
+    }
+
+    /// Whether the session tracks the given object field.
+    pub fn is_for(&self, object_id: &str, field: &str) -> bool {
+        self.object_id == object_id && self.field == field
+    }
+
+    /// ID of the in-flight `text:op` request, if any.
+    pub fn pending_request_id(&self) -> Option<&str> {
+        self.pending.as_ref().map(|(id, _)| id.as_str())
+    }
+
+    /// ID of the outstanding `text:state` request, if any.
+    pub fn state_request_id(&self) -> Option<&str> {
+        self.state_request_id.as_deref()
+    }
+
+    /// Whether the session has no revision and no resync under way.
+    pub fn needs_sync(&self) -> bool {
+        self.rev.is_none() && self.state_request_id.is_none()
+    }
+
+    /// Drop revision tracking; the next outbound frame is a `text:state`.
+    ///
+    /// Unacknowledged local edits are not lost: they remain in `text` and
+    /// are rebased onto the server text when the state reply arrives.
+    pub fn desync(&mut self) {
+        self.rev = None;
+        self.state_request_id = None;
+        self.pending = None;
+        self.buffer = None;
+        self.early.clear();
+    }
+
+    /// Record a local edit producing `new_text` with the caret at `caret`.
+    pub fn local_edit(&mut self, new_text: &str, caret: usize) {
+        let op = TextOp::from_diff(&self.text, new_text);
+        new_text.clone_into(&mut self.text);
+        self.caret = caret;
+        if op.is_noop() || self.rev.is_none() {
+            return;
+        }
+        let composed = match self.buffer.take() {
+            Some(buffer) => buffer.compose(&op),
+            None => Ok(op),
+        };
+        match composed {
+            Ok(op) => self.buffer = Some(op),
+            Err(_) => self.desync(),
+        }
+    }
+
+    /// Apply a `text:state` reply. Returns `false` if it answers another request.
+    pub fn on_state(&mut self, request_id: &str, rev: u64, server_text: &str) -> bool {
+        if self.state_request_id.as_deref() != Some(request_id) {
+            return false;
+        }
+        self.state_request_id = None;
+
+        // Rebase edits the server has not seen onto its current text.
+        let local = TextOp::from_diff(&self.server_text, &self.text);
+        let remote = TextOp::from_diff(&self.server_text, server_text);
+        let rebased = match transform(&local, &remote) {
+            Ok((local, remote)) => {
+                self.caret = remote.transform_index(self.caret);
+                local
+            }
+            Err(_) => TextOp::from_diff(server_text, &self.text),
+        };
+        match rebased.apply(server_text) {
+            Ok(text) => {
+                if text != self.text {
+                    self.remote_seq = self.remote_seq.saturating_add(1);
+                }
+                self.text = text;
+            }
+            Err(_) => return true,
+        }
+        self.caret = self.caret.min(self.text.chars().count());
+        server_text.clone_into(&mut self.server_text);
+        self.rev = Some(rev);
+        self.buffer = (!rebased.is_noop()).then_some(rebased);
+        self.drain();
+        true
+    }
+
+    /// Apply the server's acknowledgement of our in-flight operation.
+    pub fn on_ack(&mut self, request_id: &str, rev: u64) -> bool {
+        if self.pending_request_id() != Some(request_id) {
+            return false;
+        }
+        self.receive(rev, Incoming::Ack);
+        true
+    }
+
+    /// Apply another client's operation that produced revision `rev`.
+    pub fn on_remote(&mut self, rev: u64, op: TextOp) {
+        self.receive(rev, Incoming::Remote(op));
+    }
+
+    /// Build the next frame to send: a resync request or buffered edits.
+    pub fn next_frame(&mut self, board_id: Option<String>) -> Option<Frame> {
+        if self.needs_sync() {
+            let frame = request_frame(
+                "text:state",
+                board_id,
+                serde_json::json!({ "id": self.object_id, "field": self.field }),
+            );
+            self.state_request_id = Some(frame.id.clone());
+            return Some(frame);
+        }
+        let rev = self.rev?;
+        if self.pending.is_some() {
+            return None;
+        }
+        let op = self.buffer.take()?;
+        let frame = request_frame(
+            "text:op",
+            board_id,
+            serde_json::json!({
+                "id": self.object_id,
+                "field": self.field,
+                "rev": rev,
+                "op": op.to_json(),
+            }),
+        );
+        self.pending = Some((frame.id.clone(), op));
+        Some(frame)
+    }
+
+    fn receive(&mut self, rev: u64, incoming: Incoming) {
+        self.early.insert(rev, incoming);
+        self.drain();
+        if self.early.len() > MAX_EARLY_REVISIONS {
+            self.desync();
+        }
+    }
+
+    /// Apply held revisions in order until the next one is missing.
+    fn drain(&mut self) {
+        let Some(mut rev) = self.rev else {
+            return;
+        };
+        self.early = self.early.split_off(&(rev + 1));
+        while let Some(incoming) = self.early.remove(&(rev + 1)) {
+            if self.apply_incoming(incoming).is_err() {
+                self.desync();
+                return;
+            }
+            rev += 1;
+            self.rev = Some(rev);
+        }
+    }
+
+    fn apply_incoming(&mut self, incoming: Incoming) -> Result<(), TextOpError> {
+        match incoming {
+            Incoming::Ack => {
+                let Some((_, op)) = self.pending.take() else {
+                    return Err(TextOpError::Incompatible);
+                };
+                self.server_text = op.apply(&self.server_text)?;
+            }
+            Incoming::Remote(op) => {
+                let mut remote = op.clone();
+                if let Some((id, pending)) = self.pending.take() {
+                    let (pending, rest) = transform(&pending, &remote)?;
+                    self.pending = Some((id, pending));
+                    remote = rest;
+                }
+                if let Some(buffer) = self.buffer.take() {
+                    let (buffer, rest) = transform(&buffer, &remote)?;
+                    self.buffer = Some(buffer);
+                    remote = rest;
+                }
+                self.text = remote.apply(&self.text)?;
+                self.caret = remote.transform_index(self.caret);
+                self.server_text = op.apply(&self.server_text)?;
+                self.remote_seq = self.remote_seq.saturating_add(1);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Convert a UTF-16 offset (as reported by the DOM) to a char index.
+pub fn utf16_to_char_index(text: &str, utf16: usize) -> usize {
+    let mut units = 0;
+    for (index, ch) in text.chars().enumerate() {
+        if units >= utf16 {
+            return index;
+        }
+        units += ch.len_utf16();
+    }
+    text.chars().count()
+}
+
+/// Convert a char index to a UTF-16 offset for DOM selection APIs.
+pub fn char_to_utf16_index(text: &str, index: usize) -> usize {
+    text.chars().take(index).map(char::len_utf16).sum()
+}
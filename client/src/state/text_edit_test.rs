@@ -0,0 +1,96 @@
+use super::*;
+
+fn synced(text: &str, rev: u64) -> TextEditSession {
+    let mut session = TextEditSession::new("obj-1".to_owned(), "text".to_owned(), text.to_owned());
+    let frame = session.next_frame(None).expect("state request");
+    assert_eq!(frame.syscall, "text:state");
+    assert!(session.on_state(&frame.id, rev, text));
+    session
+}
+
+fn sent_op(frame: &Frame) -> TextOp {
+    TextOp::from_json(frame.data.get("op").expect("op")).expect("valid op")
+}
+
+#[test]
+fn local_edit_sends_one_op_and_buffers_the_rest() {
+    let mut session = synced("abc", 3);
+    session.local_edit("abcd", 4);
+    let first = session.next_frame(None).expect("op frame");
+    assert_eq!(first.syscall, "text:op");
+    assert_eq!(first.data.get("rev").and_then(serde_json::Value::as_u64), Some(3));
+    assert_eq!(sent_op(&first).apply("abc").unwrap(), "abcd");
+
+    session.local_edit("abcde", 5);
+    session.local_edit("abcdef", 6);
+    assert!(session.next_frame(None).is_none(), "one op in flight at a time");
+
+    assert!(session.on_ack(&first.id, 4));
+    let second = session.next_frame(None).expect("buffered op");
+    assert_eq!(second.data.get("rev").and_then(serde_json::Value::as_u64), Some(4));
+    assert_eq!(sent_op(&second).apply("abcd").unwrap(), "abcdef");
+}
+
+#[test]
+fn remote_op_transforms_past_pending_and_keeps_caret() {
+    let mut session = synced("hello", 1);
+    session.local_edit("hello!", 6);
+    let sent = session.next_frame(None).expect("op frame");
+
+    // Another client prepended text at revision 2 before our op landed.
+    session.on_remote(2, TextOp::new().insert(">> ").retain(5));
+    assert_eq!(session.text, ">> hello!");
+    assert_eq!(session.caret, 9);
+    assert_eq!(session.remote_seq, 1);
+
+    assert!(session.on_ack(&sent.id, 3));
+    session.local_edit(">> hello!?", 10);
+    let next = session.next_frame(None).expect("next op");
+    assert_eq!(next.data.get("rev").and_then(serde_json::Value::as_u64), Some(3));
+    assert_eq!(sent_op(&next).apply(">> hello!").unwrap(), ">> hello!?");
+}
+
+#[test]
+fn out_of_order_revisions_apply_in_sequence() {
+    let mut session = synced("ab", 0);
+    session.on_remote(2, TextOp::new().retain(3).insert("2"));
+    assert_eq!(session.text, "ab", "revision 2 waits for revision 1");
+    session.on_remote(1, TextOp::new().retain(2).insert("1"));
+    assert_eq!(session.text, "ab12");
+}
+
+#[test]
+fn resync_rebases_unacknowledged_edits() {
+    let mut session = synced("note", 5);
+    session.local_edit("notes", 5);
+    let _in_flight = session.next_frame(None).expect("op frame");
+
+    session.desync();
+    let state = session.next_frame(None).expect("state request");
+    assert_eq!(state.syscall, "text:state");
+    // The server lost our op and someone else rewrote the start.
+    assert!(session.on_state(&state.id, 1, "a note"));
+    assert_eq!(session.text, "a notes");
+
+    let retry = session.next_frame(None).expect("rebased op");
+    assert_eq!(retry.data.get("rev").and_then(serde_json::Value::as_u64), Some(1));
+    assert_eq!(sent_op(&retry).apply("a note").unwrap(), "a notes");
+}
+
+#[test]
+fn state_reply_for_other_request_is_ignored() {
+    let mut session = TextEditSession::new("obj-1".to_owned(), "text".to_owned(), "x".to_owned());
+    let _ = session.next_frame(None);
+    assert!(!session.on_state("other", 1, "y"));
+    assert_eq!(session.text, "x");
+}
+
+#[test]
+fn utf16_offsets_round_trip_through_char_indices() {
+    let text = "a🙂b";
+    assert_eq!(utf16_to_char_index(text, 0), 0);
+    assert_eq!(utf16_to_char_index(text, 3), 2);
+    assert_eq!(utf16_to_char_index(text, 4), 3);
+    assert_eq!(char_to_utf16_index(text, 2), 3);
+    assert_eq!(char_to_utf16_index(text, 3), 4);
+}
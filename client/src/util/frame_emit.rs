@@ -7,6 +7,10 @@
 //! - **props-only** — for visual property changes (color, border, text style).
 //! - **rotation-only** — for rotation changes; omits position/size to avoid stomping concurrent moves.
 //! - **geometry** — for position/size changes; includes props because scale metadata must travel together.
+//!
+//! Selection-wide commits collect their updates in an [`ObjectUpdateBatch`]. Two or more updates
+//! go out as a single `"object:batch"` frame so the server applies them all-or-nothing and peers
+//! see the whole change in one broadcast instead of a half-applied selection.
 
 #[cfg(test)]
 #[path = "frame_emit_test.rs"]
@@ -67,12 +71,10 @@ fn object_update_geometry_frame(
     board_id: &str,
     object_id: &str,
     version: i64,
-    x: f64,
-    y: f64,
-    width: f64,
-    height: f64,
+    geometry: Geometry,
     props: &serde_json::Value,
 ) -> Frame {
+    let Geometry { x, y, width, height } = geometry;
     Frame {
         id: uuid::Uuid::new_v4().to_string(),
         parent_id: None,
@@ -94,51 +96,117 @@ fn object_update_geometry_frame(
     }
 }
 
-/// Emit a props-only update for a single object.
-///
-/// Use this after color, border, or text-style changes — anything that mutates `props` without
-/// changing position or size.
-pub fn send_object_update_props(
-    sender: RwSignal<FrameSender>,
-    board_id: &str,
-    object_id: &str,
-    version: i64,
-    props: &serde_json::Value,
-) {
-    let frame = object_update_props_frame(board_id, object_id, version, props);
-    let _ = sender.get_untracked().send(&frame);
+/// Build an `"object:batch"` frame that wraps the payloads of individual update frames.
+fn object_batch_frame(board_id: &str, updates: &[Frame]) -> Frame {
+    let ops: Vec<serde_json::Value> = updates
+        .iter()
+        .map(|frame| {
+            let mut op = frame.data.clone();
+            if let Some(fields) = op.as_object_mut() {
+                fields.insert("op".to_owned(), serde_json::json!("update"));
+            }
+            op
+        })
+        .collect();
+    Frame {
+        id: uuid::Uuid::new_v4().to_string(),
+        parent_id: None,
+        ts: 0,
+        board_id: Some(board_id.to_owned()),
+        from: None,
+        syscall: "object:batch".to_owned(),
+        status: FrameStatus::Request,
+        trace: None,
+        data: serde_json::json!({ "ops": ops }),
+    }
 }
 
-/// Emit a rotation-only update for a single object.
-///
-/// Use this after the rotation dial is released — keeps the frame payload minimal and avoids
-/// conflicting with concurrent position updates.
-pub fn send_object_update_rotation(
-    sender: RwSignal<FrameSender>,
-    board_id: &str,
-    object_id: &str,
-    version: i64,
-    rotation: f64,
-) {
-    let frame = object_update_rotation_frame(board_id, object_id, version, rotation);
-    let _ = sender.get_untracked().send(&frame);
+/// Position and size of an object, as sent in a geometry update.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Geometry {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
 }
 
-/// Emit a geometry update (position, size, and props) for a single object.
+/// Collects object updates for a selection-wide commit and sends them as one request.
 ///
-/// Use this after a scale drag or any move/resize operation that must keep scale props in sync
-/// with the new dimensions.
-pub fn send_object_update_geometry(
-    sender: RwSignal<FrameSender>,
-    board_id: &str,
-    object_id: &str,
-    version: i64,
-    x: f64,
-    y: f64,
-    width: f64,
-    height: f64,
-    props: &serde_json::Value,
-) {
-    let frame = object_update_geometry_frame(board_id, object_id, version, x, y, width, height, props);
-    let _ = sender.get_untracked().send(&frame);
+/// A single update is sent as a plain `"object:update"` frame; two or more are wrapped in one
+/// `"object:batch"` frame so a stale version on any object rejects the whole commit.
+#[derive(Default)]
+pub struct ObjectUpdateBatch {
+    board_id: Option<String>,
+    updates: Vec<Frame>,
+}
+
+impl ObjectUpdateBatch {
+    /// Queue a props-only update. Use after color, border, or text-style changes.
+    pub fn push_props(&mut self, board_id: &str, object_id: &str, version: i64, props: &serde_json::Value) {
+        self.push(board_id, object_update_props_frame(board_id, object_id, version, props));
+    }
+
+    /// Queue a rotation-only update. Use after the rotation dial is released.
+    pub fn push_rotation(&mut self, board_id: &str, object_id: &str, version: i64, rotation: f64) {
+        self.push(board_id, object_update_rotation_frame(board_id, object_id, version, rotation));
+    }
+
+    /// Queue a geometry update (position, size, and props). Use after a scale drag or resize.
+    pub fn push_geometry(
+        &mut self,
+        board_id: &str,
+        object_id: &str,
+        version: i64,
+        geometry: Geometry,
+        props: &serde_json::Value,
+    ) {
+        self.push(
+            board_id,
+            object_update_geometry_frame(board_id, object_id, version, geometry, props),
+        );
+    }
+
+    /// Queue an update with a caller-built field map (`id`, `version`, and any changed fields).
+    pub fn push_fields(&mut self, board_id: &str, data: serde_json::Value) {
+        self.push(
+            board_id,
+            Frame {
+                id: uuid::Uuid::new_v4().to_string(),
+                parent_id: None,
+                ts: 0,
+                board_id: Some(board_id.to_owned()),
+                from: None,
+                syscall: "object:update".to_owned(),
+                status: FrameStatus::Request,
+                trace: None,
+                data,
+            },
+        );
+    }
+
+    fn push(&mut self, board_id: &str, frame: Frame) {
+        if self.board_id.is_none() {
+            self.board_id = Some(board_id.to_owned());
+        }
+        self.updates.push(frame);
+    }
+
+    /// Build the frame to send, or `None` when nothing was queued.
+    fn into_frame(mut self) -> Option<Frame> {
+        match self.updates.len() {
+            0 => None,
+            1 => self.updates.pop(),
+            _ => {
+                let board_id = self.board_id.unwrap_or_default();
+                Some(object_batch_frame(&board_id, &self.updates))
+            }
+        }
+    }
+
+    /// Send the queued updates. Does nothing when the batch is empty.
+    pub fn send(self, sender: RwSignal<FrameSender>) {
+        if let Some(frame) = self.into_frame() {
+            let _ = sender.get_untracked().send(&frame);
+        }
+    }
 }
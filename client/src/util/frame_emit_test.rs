@@ -25,7 +25,8 @@ fn object_update_rotation_frame_builds_expected_payload() {
 #[test]
 fn object_update_geometry_frame_builds_expected_payload() {
     let props = serde_json::json!({ "scale": 2.0 });
-    let frame = object_update_geometry_frame("b1", "o1", 8, 10.0, 20.0, 30.0, 40.0, &props);
+    let frame =
+        object_update_geometry_frame("b1", "o1", 8, Geometry { x: 10.0, y: 20.0, width: 30.0, height: 40.0 }, &props);
     assert_eq!(frame.syscall, "object:update");
     assert_eq!(frame.status, FrameStatus::Request);
     assert_eq!(frame.board_id.as_deref(), Some("b1"));
@@ -37,3 +38,47 @@ fn object_update_geometry_frame_builds_expected_payload() {
     assert_eq!(frame.data["height"], serde_json::json!(40.0));
     assert_eq!(frame.data["props"], props);
 }
+
+#[test]
+fn object_update_batch_sends_single_update_unwrapped() {
+    let mut batch = ObjectUpdateBatch::default();
+    batch.push_rotation("b1", "o1", 3, 45.0);
+    let frame = batch.into_frame().expect("frame");
+    assert_eq!(frame.syscall, "object:update");
+    assert_eq!(frame.data["id"], serde_json::json!("o1"));
+    assert!(frame.data.get("op").is_none());
+}
+
+#[test]
+fn object_update_batch_wraps_multiple_updates_in_one_frame() {
+    let props = serde_json::json!({ "fill": "#112233" });
+    let mut batch = ObjectUpdateBatch::default();
+    batch.push_props("b1", "o1", 3, &props);
+    batch.push_rotation("b1", "o2", 4, 90.0);
+    let frame = batch.into_frame().expect("frame");
+    assert_eq!(frame.syscall, "object:batch");
+    assert_eq!(frame.board_id.as_deref(), Some("b1"));
+    let ops = frame.data["ops"].as_array().expect("ops");
+    assert_eq!(ops.len(), 2);
+    assert_eq!(ops[0]["op"], serde_json::json!("update"));
+    assert_eq!(ops[0]["props"], props);
+    assert_eq!(ops[1]["id"], serde_json::json!("o2"));
+    assert_eq!(ops[1]["version"], serde_json::json!(4));
+}
+
+#[test]
+fn object_update_batch_push_geometry_sends_position_and_size() {
+    let props = serde_json::json!({ "scale": 2.0 });
+    let mut batch = ObjectUpdateBatch::default();
+    batch.push_geometry("b1", "o1", 5, Geometry { x: 1.0, y: 2.0, width: 30.0, height: 40.0 }, &props);
+    let frame = batch.into_frame().expect("frame");
+    assert_eq!(frame.syscall, "object:update");
+    assert_eq!(frame.data["x"], serde_json::json!(1.0));
+    assert_eq!(frame.data["height"], serde_json::json!(40.0));
+    assert_eq!(frame.data["props"], props);
+}
+
+#[test]
+fn object_update_batch_empty_sends_nothing() {
+    assert!(ObjectUpdateBatch::default().into_frame().is_none());
+}
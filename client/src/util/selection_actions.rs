@@ -7,7 +7,7 @@
 //! 2. **Apply** — on each pointer-move, compute the new target value and write it directly into
 //!    the reactive `BoardState`, keeping the canvas in sync without a server round-trip.
 //! 3. **Commit** — on pointer-up, compare the current values to the snapshotted seed values and
-//!    emit `object:update` frames only for objects that actually changed.
+//!    emit updates only for objects that actually changed, batched into one request per commit.
 //!
 //! Separating apply from commit prevents flooding the server with update frames during a drag
 //! while still delivering a single authoritative update at the end.
@@ -25,7 +25,7 @@ use crate::util::color::normalize_hex_color;
 #[cfg(feature = "hydrate")]
 use crate::util::dial_math::{BORDER_WIDTH_MAX, BORDER_WIDTH_MIN, snap_font_size_to_px};
 #[cfg(feature = "hydrate")]
-use crate::util::frame_emit::{send_object_update_geometry, send_object_update_props, send_object_update_rotation};
+use crate::util::frame_emit::{Geometry, ObjectUpdateBatch};
 #[cfg(feature = "hydrate")]
 use crate::util::object_props::{
     object_base_fill_hex, object_border_color_hex, object_border_width, object_fill_hex, object_font_size,
@@ -382,6 +382,7 @@ pub fn commit_selection_scale_updates(
         return;
     };
     let state = board.get_untracked();
+    let mut batch = ObjectUpdateBatch::default();
     for seed in &drag_state.start_items {
         let Some(obj) = state.objects.get(&seed.id) else {
             continue;
@@ -399,18 +400,20 @@ pub fn commit_selection_scale_updates(
         if !changed {
             continue;
         }
-        send_object_update_geometry(
-            sender,
+        batch.push_geometry(
             &seed.board_id,
             &seed.id,
             seed.version,
-            obj.x,
-            obj.y,
-            obj.width.unwrap_or(seed.width),
-            obj.height.unwrap_or(seed.height),
+            Geometry {
+                x: obj.x,
+                y: obj.y,
+                width: obj.width.unwrap_or(seed.width),
+                height: obj.height.unwrap_or(seed.height),
+            },
             &obj.props,
         );
     }
+    batch.send(sender);
     drag_state_signal.set(None);
 }
 
@@ -457,22 +460,20 @@ pub fn apply_group_scale_target(board: RwSignal<BoardState>, sender: RwSignal<Fr
     });
 
     let post = board.get_untracked();
+    let mut batch = ObjectUpdateBatch::default();
     for id in selected {
         let Some(obj) = post.objects.get(&id) else {
             continue;
         };
-        send_object_update_geometry(
-            sender,
+        batch.push_geometry(
             &obj.board_id,
             &obj.id,
             obj.version,
-            obj.x,
-            obj.y,
-            obj.width.unwrap_or(120.0),
-            obj.height.unwrap_or(80.0),
+            Geometry { x: obj.x, y: obj.y, width: obj.width.unwrap_or(120.0), height: obj.height.unwrap_or(80.0) },
             &obj.props,
         );
     }
+    batch.send(sender);
 }
 
 /// No-op stub used on SSR builds where the hydrate feature is absent.
@@ -543,6 +544,7 @@ pub fn commit_selection_color_updates(
         return;
     };
     let state = board.get_untracked();
+    let mut batch = ObjectUpdateBatch::default();
     for seed in &drag_state.start_items {
         let Some(obj) = state.objects.get(&seed.id) else {
             continue;
@@ -561,8 +563,9 @@ pub fn commit_selection_color_updates(
         if !changed {
             continue;
         }
-        send_object_update_props(sender, &seed.board_id, &seed.id, seed.version, &obj.props);
+        batch.push_props(&seed.board_id, &seed.id, seed.version, &obj.props);
     }
+    batch.send(sender);
     drag_state_signal.set(None);
 }
 
@@ -594,12 +597,14 @@ pub fn apply_group_base_color_target(board: RwSignal<BoardState>, sender: RwSign
     });
 
     let post = board.get_untracked();
+    let mut batch = ObjectUpdateBatch::default();
     for id in selected {
         let Some(obj) = post.objects.get(&id) else {
             continue;
         };
-        send_object_update_props(sender, &obj.board_id, &obj.id, obj.version, &obj.props);
+        batch.push_props(&obj.board_id, &obj.id, obj.version, &obj.props);
     }
+    batch.send(sender);
 }
 
 /// Reset all selected objects to the application default fill color (`#D94B4B`, no shift).
@@ -627,12 +632,14 @@ pub fn apply_group_background_defaults_target(board: RwSignal<BoardState>, sende
     });
 
     let post = board.get_untracked();
+    let mut batch = ObjectUpdateBatch::default();
     for id in selected {
         let Some(obj) = post.objects.get(&id) else {
             continue;
         };
-        send_object_update_props(sender, &obj.board_id, &obj.id, obj.version, &obj.props);
+        batch.push_props(&obj.board_id, &obj.id, obj.version, &obj.props);
     }
+    batch.send(sender);
 }
 
 /// No-op stub used on SSR builds where the hydrate feature is absent.
@@ -705,6 +712,7 @@ pub fn commit_selection_border_updates(
         return;
     };
     let state = board.get_untracked();
+    let mut batch = ObjectUpdateBatch::default();
     for seed in &drag_state.start_items {
         let Some(obj) = state.objects.get(&seed.id) else {
             continue;
@@ -715,8 +723,9 @@ pub fn commit_selection_border_updates(
         if !changed {
             continue;
         }
-        send_object_update_props(sender, &seed.board_id, &seed.id, seed.version, &obj.props);
+        batch.push_props(&seed.board_id, &seed.id, seed.version, &obj.props);
     }
+    batch.send(sender);
     drag_state_signal.set(None);
 }
 
@@ -748,12 +757,14 @@ pub fn apply_group_border_color_target(board: RwSignal<BoardState>, sender: RwSi
     });
 
     let post = board.get_untracked();
+    let mut batch = ObjectUpdateBatch::default();
     for id in selected {
         let Some(obj) = post.objects.get(&id) else {
             continue;
         };
-        send_object_update_props(sender, &obj.board_id, &obj.id, obj.version, &obj.props);
+        batch.push_props(&obj.board_id, &obj.id, obj.version, &obj.props);
     }
+    batch.send(sender);
 }
 
 /// Reset all selected objects to the application default border (`#1F1A17`, 0px width).
@@ -782,12 +793,14 @@ pub fn apply_group_border_defaults_target(board: RwSignal<BoardState>, sender: R
     });
 
     let post = board.get_untracked();
+    let mut batch = ObjectUpdateBatch::default();
     for id in selected {
         let Some(obj) = post.objects.get(&id) else {
             continue;
         };
-        send_object_update_props(sender, &obj.board_id, &obj.id, obj.version, &obj.props);
+        batch.push_props(&obj.board_id, &obj.id, obj.version, &obj.props);
     }
+    batch.send(sender);
 }
 
 /// No-op stub used on SSR builds where the hydrate feature is absent.
@@ -865,6 +878,7 @@ pub fn commit_selection_text_style_updates(
         return;
     };
     let state = board.get_untracked();
+    let mut batch = ObjectUpdateBatch::default();
     for seed in &drag_state.start_items {
         let Some(obj) = state.objects.get(&seed.id) else {
             continue;
@@ -875,8 +889,9 @@ pub fn commit_selection_text_style_updates(
         if !changed {
             continue;
         }
-        send_object_update_props(sender, &seed.board_id, &seed.id, seed.version, &obj.props);
+        batch.push_props(&seed.board_id, &seed.id, seed.version, &obj.props);
     }
+    batch.send(sender);
     drag_state_signal.set(None);
 }
 
@@ -908,12 +923,14 @@ pub fn apply_group_text_color_target(board: RwSignal<BoardState>, sender: RwSign
     });
 
     let post = board.get_untracked();
+    let mut batch = ObjectUpdateBatch::default();
     for id in selected {
         let Some(obj) = post.objects.get(&id) else {
             continue;
         };
-        send_object_update_props(sender, &obj.board_id, &obj.id, obj.version, &obj.props);
+        batch.push_props(&obj.board_id, &obj.id, obj.version, &obj.props);
     }
+    batch.send(sender);
 }
 
 /// Reset all selected objects to the default text style (`#1F1A17`, 24px).
@@ -942,12 +959,14 @@ pub fn apply_group_text_style_defaults_target(board: RwSignal<BoardState>, sende
     });
 
     let post = board.get_untracked();
+    let mut batch = ObjectUpdateBatch::default();
     for id in selected {
         let Some(obj) = post.objects.get(&id) else {
             continue;
         };
-        send_object_update_props(sender, &obj.board_id, &obj.id, obj.version, &obj.props);
+        batch.push_props(&obj.board_id, &obj.id, obj.version, &obj.props);
     }
+    batch.send(sender);
 }
 
 /// No-op stub used on SSR builds where the hydrate feature is absent.
@@ -1015,6 +1034,7 @@ pub fn commit_selection_rotation_updates(
         return;
     };
     let state = board.get_untracked();
+    let mut batch = ObjectUpdateBatch::default();
     for (id, start_rotation) in &drag_state.start_rotations {
         let Some(obj) = state.objects.get(id) else {
             continue;
@@ -1022,8 +1042,9 @@ pub fn commit_selection_rotation_updates(
         if angular_delta_fn(obj.rotation, *start_rotation) < 0.01 {
             continue;
         }
-        send_object_update_rotation(sender, &obj.board_id, &obj.id, obj.version, obj.rotation);
+        batch.push_rotation(&obj.board_id, &obj.id, obj.version, obj.rotation);
     }
+    batch.send(sender);
     drag_state_signal.set(None);
 }
 
@@ -1068,10 +1089,12 @@ pub fn apply_group_rotation_target(
     });
 
     let post = board.get_untracked();
+    let mut batch = ObjectUpdateBatch::default();
     for id in selected {
         let Some(obj) = post.objects.get(&id) else {
             continue;
         };
-        send_object_update_rotation(sender, &obj.board_id, &obj.id, obj.version, obj.rotation);
+        batch.push_rotation(&obj.board_id, &obj.id, obj.version, obj.rotation);
     }
+    batch.send(sender);
 }
@@ -4,6 +4,8 @@
 //! It intentionally keeps frame payloads flexible (`serde_json::Value`) while
 //! encoding over protobuf for compact binary transport.
 
+pub mod text_ops;
+
 use prost::Message;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
@@ -0,0 +1,455 @@
+//! Operational transform for collaborative plain-text fields.
+//!
+//! DESIGN
+//! ======
+//! A [`TextOp`] walks its base text left to right as a list of retain,
+//! insert, and delete components. The server and every client run
+//! [`transform`] with the same tie-break (the first operation's inserts land
+//! first), so concurrent edits converge no matter the order they arrive in.
+//!
+//! Lengths and positions count Unicode scalar values (`char`s), never bytes
+//! or UTF-16 units; browser callers convert at the edge.
+//!
+//! On the wire an operation is a JSON array: a positive integer retains that
+//! many chars, a negative integer deletes that many, and a string inserts it.
+
+use serde_json::Value;
+
+/// Longest text, in chars, an operation decoded from the wire may describe.
+/// Larger counts are rejected before any length arithmetic can overflow.
+pub const MAX_TEXT_LEN: usize = 1 << 20;
+
+/// Error returned by text operation parsing, application, and transforms.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TextOpError {
+    /// The operation was built for a text of a different length.
+    #[error("operation expects base length {expected}, text has {actual}")]
+    LengthMismatch { expected: usize, actual: usize },
+    /// Two operations cannot be composed or transformed together.
+    #[error("operations are not compatible")]
+    Incompatible,
+    /// The wire representation is not a valid operation.
+    #[error("malformed text operation: {0}")]
+    Malformed(String),
+}
+
+/// One step of a [`TextOp`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Component {
+    /// Keep the next `n` chars of the base text.
+    Retain(usize),
+    /// Insert a string at the current position.
+    Insert(String),
+    /// Remove the next `n` chars of the base text.
+    Delete(usize),
+}
+
+/// A sequence of components that turns a base text into a target text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextOp {
+    components: Vec<Component>,
+    base_len: usize,
+    target_len: usize,
+}
+
+fn char_len(s: &str) -> usize {
+    s.chars().count()
+}
+
+fn split_chars(s: &str, at: usize) -> (String, String) {
+    let idx = s.char_indices().nth(at).map_or(s.len(), |(i, _)| i);
+    (s[..idx].to_owned(), s[idx..].to_owned())
+}
+
+impl TextOp {
+    /// Create an empty operation (a no-op on the empty string).
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a retain of `n` chars.
+    #[must_use]
+    pub fn retain(mut self, n: usize) -> Self {
+        self.push_retain(n);
+        self
+    }
+
+    /// Append an insert of `s`.
+    #[must_use]
+    pub fn insert(mut self, s: &str) -> Self {
+        self.push_insert(s);
+        self
+    }
+
+    /// Append a delete of `n` chars.
+    #[must_use]
+    pub fn delete(mut self, n: usize) -> Self {
+        self.push_delete(n);
+        self
+    }
+
+    /// Components in order.
+    #[must_use]
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    /// Length of the text this operation applies to.
+    #[must_use]
+    pub fn base_len(&self) -> usize {
+        self.base_len
+    }
+
+    /// Length of the text this operation produces.
+    #[must_use]
+    pub fn target_len(&self) -> usize {
+        self.target_len
+    }
+
+    /// Whether applying this operation leaves the text unchanged.
+    #[must_use]
+    pub fn is_noop(&self) -> bool {
+        self.components
+            .iter()
+            .all(|c| matches!(c, Component::Retain(_)))
+    }
+
+    fn push_retain(&mut self, n: usize) {
+        if n == 0 {
+            return;
+        }
+        self.base_len += n;
+        self.target_len += n;
+        if let Some(Component::Retain(last)) = self.components.last_mut() {
+            *last += n;
+        } else {
+            self.components.push(Component::Retain(n));
+        }
+    }
+
+    fn push_insert(&mut self, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+        self.target_len += char_len(s);
+        // Keep inserts ahead of an adjacent delete so equal edits normalise to
+        // the same component list.
+        if let Some(Component::Insert(last)) = self.components.last_mut() {
+            last.push_str(s);
+            return;
+        }
+        if let Some(Component::Delete(_)) = self.components.last() {
+            let delete_at = self.components.len() - 1;
+            if delete_at > 0
+                && let Component::Insert(prev) = &mut self.components[delete_at - 1]
+            {
+                prev.push_str(s);
+                return;
+            }
+            self.components
+                .insert(delete_at, Component::Insert(s.to_owned()));
+            return;
+        }
+        self.components.push(Component::Insert(s.to_owned()));
+    }
+
+    fn push_delete(&mut self, n: usize) {
+        if n == 0 {
+            return;
+        }
+        self.base_len += n;
+        if let Some(Component::Delete(last)) = self.components.last_mut() {
+            *last += n;
+        } else {
+            self.components.push(Component::Delete(n));
+        }
+    }
+
+    /// Build the single-splice operation that turns `old` into `new`.
+    ///
+    /// Finds the longest common prefix and suffix and replaces what lies
+    /// between them, which is exactly what one keystroke or paste produces.
+    #[must_use]
+    pub fn from_diff(old: &str, new: &str) -> Self {
+        let old_chars: Vec<char> = old.chars().collect();
+        let new_chars: Vec<char> = new.chars().collect();
+        let prefix = old_chars
+            .iter()
+            .zip(&new_chars)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let max_suffix = old_chars.len().min(new_chars.len()) - prefix;
+        let suffix = old_chars
+            .iter()
+            .rev()
+            .zip(new_chars.iter().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let inserted: String = new_chars[prefix..new_chars.len() - suffix].iter().collect();
+        Self::new()
+            .retain(prefix)
+            .delete(old_chars.len() - prefix - suffix)
+            .insert(&inserted)
+            .retain(suffix)
+    }
+
+    /// Apply this operation to `text`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LengthMismatch` if `text` is not `base_len` chars long.
+    pub fn apply(&self, text: &str) -> Result<String, TextOpError> {
+        let actual = char_len(text);
+        if actual != self.base_len {
+            return Err(TextOpError::LengthMismatch {
+                expected: self.base_len,
+                actual,
+            });
+        }
+        let mut chars = text.chars();
+        let mut out = String::with_capacity(text.len());
+        for component in &self.components {
+            match component {
+                Component::Retain(n) => out.extend(chars.by_ref().take(*n)),
+                Component::Insert(s) => out.push_str(s),
+                Component::Delete(n) => {
+                    chars.by_ref().take(*n).for_each(drop);
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Combine `self` followed by `next` into one operation.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Incompatible` if `next` does not apply to the output of `self`.
+    pub fn compose(&self, next: &TextOp) -> Result<TextOp, TextOpError> {
+        if self.target_len != next.base_len {
+            return Err(TextOpError::Incompatible);
+        }
+        let mut out = TextOp::new();
+        let mut first = self.components.iter().cloned();
+        let mut second = next.components.iter().cloned();
+        let mut a = first.next();
+        let mut b = second.next();
+        loop {
+            match (a.take(), b.take()) {
+                (None, None) => break,
+                (Some(Component::Delete(n)), rest) => {
+                    out.push_delete(n);
+                    a = first.next();
+                    b = rest;
+                }
+                (rest, Some(Component::Insert(s))) => {
+                    out.push_insert(&s);
+                    a = rest;
+                    b = second.next();
+                }
+                (None, _) | (_, None) => return Err(TextOpError::Incompatible),
+                (Some(Component::Retain(n1)), Some(Component::Retain(n2))) => {
+                    let (m, rest_a, rest_b) = split_lens(n1, n2);
+                    out.push_retain(m);
+                    a = rest_a.map(Component::Retain).or_else(|| first.next());
+                    b = rest_b.map(Component::Retain).or_else(|| second.next());
+                }
+                (Some(Component::Insert(s)), Some(Component::Delete(n2))) => {
+                    let (m, _, rest_b) = split_lens(char_len(&s), n2);
+                    let (_, tail) = split_chars(&s, m);
+                    a = if tail.is_empty() {
+                        first.next()
+                    } else {
+                        Some(Component::Insert(tail))
+                    };
+                    b = rest_b.map(Component::Delete).or_else(|| second.next());
+                }
+                (Some(Component::Insert(s)), Some(Component::Retain(n2))) => {
+                    let (m, _, rest_b) = split_lens(char_len(&s), n2);
+                    let (head, tail) = split_chars(&s, m);
+                    out.push_insert(&head);
+                    a = if tail.is_empty() {
+                        first.next()
+                    } else {
+                        Some(Component::Insert(tail))
+                    };
+                    b = rest_b.map(Component::Retain).or_else(|| second.next());
+                }
+                (Some(Component::Retain(n1)), Some(Component::Delete(n2))) => {
+                    let (m, rest_a, rest_b) = split_lens(n1, n2);
+                    out.push_delete(m);
+                    a = rest_a.map(Component::Retain).or_else(|| first.next());
+                    b = rest_b.map(Component::Delete).or_else(|| second.next());
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Map a caret position in the base text to the target text.
+    ///
+    /// Inserts exactly at the caret leave it in place, so a remote user
+    /// typing at the same spot does not drag the local caret along.
+    #[must_use]
+    pub fn transform_index(&self, index: usize) -> usize {
+        let mut base = 0;
+        let mut result = index;
+        for component in &self.components {
+            if base >= index {
+                break;
+            }
+            match component {
+                Component::Retain(n) => base += n,
+                Component::Insert(s) => result += char_len(s),
+                Component::Delete(n) => {
+                    result -= (*n).min(index - base);
+                    base += n;
+                }
+            }
+        }
+        result
+    }
+
+    /// Encode as the compact JSON wire array.
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        let items = self
+            .components
+            .iter()
+            .map(|component| match component {
+                Component::Retain(n) => Value::from(*n),
+                Component::Insert(s) => Value::from(s.as_str()),
+                Component::Delete(n) => Value::from(-i64::try_from(*n).unwrap_or(i64::MAX)),
+            })
+            .collect();
+        Value::Array(items)
+    }
+
+    /// Decode the compact JSON wire array.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Malformed` if the value is not an array of non-zero integers
+    /// and non-empty strings.
+    pub fn from_json(value: &Value) -> Result<TextOp, TextOpError> {
+        let Some(items) = value.as_array() else {
+            return Err(TextOpError::Malformed("expected an array".into()));
+        };
+        let mut op = TextOp::new();
+        for item in items {
+            match item {
+                Value::String(s) if !s.is_empty() => {
+                    bounded_len(op.target_len, char_len(s))?;
+                    op.push_insert(s);
+                }
+                // Numbers cross the wire as f64; accept whole values.
+                #[allow(clippy::cast_possible_truncation)]
+                Value::Number(n) => match n
+                    .as_i64()
+                    .or_else(|| n.as_f64().filter(|v| v.fract() == 0.0).map(|v| v as i64))
+                {
+                    Some(v) if v > 0 => {
+                        let n = usize::try_from(v).unwrap_or(usize::MAX);
+                        bounded_len(op.base_len, n)?;
+                        bounded_len(op.target_len, n)?;
+                        op.push_retain(n);
+                    }
+                    Some(v) if v < 0 => {
+                        let n = usize::try_from(v.unsigned_abs()).unwrap_or(usize::MAX);
+                        bounded_len(op.base_len, n)?;
+                        op.push_delete(n);
+                    }
+                    _ => return Err(TextOpError::Malformed(format!("invalid component {n}"))),
+                },
+                other => return Err(TextOpError::Malformed(format!("invalid component {other}"))),
+            }
+        }
+        Ok(op)
+    }
+}
+
+/// `len + n`, or `Malformed` if that overflows or passes [`MAX_TEXT_LEN`].
+fn bounded_len(len: usize, n: usize) -> Result<usize, TextOpError> {
+    len.checked_add(n)
+        .filter(|&total| total <= MAX_TEXT_LEN)
+        .ok_or_else(|| {
+            TextOpError::Malformed(format!("operation longer than {MAX_TEXT_LEN} chars"))
+        })
+}
+
+/// Split two component lengths at their minimum, returning leftovers.
+fn split_lens(n1: usize, n2: usize) -> (usize, Option<usize>, Option<usize>) {
+    let m = n1.min(n2);
+    let rest = |n: usize| (n > m).then_some(n - m);
+    (m, rest(n1), rest(n2))
+}
+
+/// Transform two concurrent operations against each other.
+///
+/// Returns `(a', b')` such that applying `a` then `b'` yields the same text
+/// as applying `b` then `a'`. When both insert at the same position, `a`'s
+/// text lands first.
+///
+/// # Errors
+///
+/// Returns `Incompatible` if `a` and `b` do not share a base length.
+pub fn transform(a: &TextOp, b: &TextOp) -> Result<(TextOp, TextOp), TextOpError> {
+    if a.base_len != b.base_len {
+        return Err(TextOpError::Incompatible);
+    }
+    let mut a_prime = TextOp::new();
+    let mut b_prime = TextOp::new();
+    let mut first = a.components.iter().cloned();
+    let mut second = b.components.iter().cloned();
+    let mut cur_a = first.next();
+    let mut cur_b = second.next();
+    loop {
+        match (cur_a.take(), cur_b.take()) {
+            (None, None) => break,
+            (Some(Component::Insert(s)), rest) => {
+                b_prime.push_retain(char_len(&s));
+                a_prime.push_insert(&s);
+                cur_a = first.next();
+                cur_b = rest;
+            }
+            (rest, Some(Component::Insert(s))) => {
+                a_prime.push_retain(char_len(&s));
+                b_prime.push_insert(&s);
+                cur_a = rest;
+                cur_b = second.next();
+            }
+            (None, _) | (_, None) => return Err(TextOpError::Incompatible),
+            (Some(Component::Retain(n1)), Some(Component::Retain(n2))) => {
+                let (m, rest_a, rest_b) = split_lens(n1, n2);
+                a_prime.push_retain(m);
+                b_prime.push_retain(m);
+                cur_a = rest_a.map(Component::Retain).or_else(|| first.next());
+                cur_b = rest_b.map(Component::Retain).or_else(|| second.next());
+            }
+            (Some(Component::Delete(n1)), Some(Component::Delete(n2))) => {
+                let (_, rest_a, rest_b) = split_lens(n1, n2);
+                cur_a = rest_a.map(Component::Delete).or_else(|| first.next());
+                cur_b = rest_b.map(Component::Delete).or_else(|| second.next());
+            }
+            (Some(Component::Delete(n1)), Some(Component::Retain(n2))) => {
+                let (m, rest_a, rest_b) = split_lens(n1, n2);
+                a_prime.push_delete(m);
+                cur_a = rest_a.map(Component::Delete).or_else(|| first.next());
+                cur_b = rest_b.map(Component::Retain).or_else(|| second.next());
+            }
+            (Some(Component::Retain(n1)), Some(Component::Delete(n2))) => {
+                let (m, rest_a, rest_b) = split_lens(n1, n2);
+                b_prime.push_delete(m);
+                cur_a = rest_a.map(Component::Retain).or_else(|| first.next());
+                cur_b = rest_b.map(Component::Delete).or_else(|| second.next());
+            }
+        }
+    }
+    Ok((a_prime, b_prime))
+}
+
+#[cfg(test)]
+#[path = "text_ops_test.rs"]
+mod tests;
@@ -0,0 +1,167 @@
+use super::*;
+
+/// Small deterministic generator so convergence checks need no extra deps.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        usize::try_from(self.0 >> 33).unwrap_or(0) % bound.max(1)
+    }
+}
+
+fn random_op(rng: &mut Lcg, text: &str) -> TextOp {
+    let len = text.chars().count();
+    let mut op = TextOp::new();
+    let mut pos = 0;
+    while pos < len {
+        let n = 1 + rng.next(len - pos);
+        match rng.next(3) {
+            0 => op = op.retain(n),
+            1 => op = op.delete(n),
+            _ => op = op.insert(["a", "é", "xy", "🙂"][rng.next(4)]).retain(n),
+        }
+        pos += n;
+    }
+    if rng.next(2) == 0 {
+        op = op.insert("z");
+    }
+    op
+}
+
+#[test]
+fn apply_handles_retain_insert_delete() {
+    let op = TextOp::new().retain(2).delete(3).insert("XY").retain(1);
+    assert_eq!(op.base_len(), 6);
+    assert_eq!(op.target_len(), 5);
+    assert_eq!(op.apply("abcdef").unwrap(), "abXYf");
+}
+
+#[test]
+fn apply_rejects_wrong_base_length() {
+    let op = TextOp::new().retain(3);
+    assert_eq!(
+        op.apply("ab"),
+        Err(TextOpError::LengthMismatch {
+            expected: 3,
+            actual: 2
+        })
+    );
+}
+
+#[test]
+fn apply_counts_chars_not_bytes() {
+    let op = TextOp::new().retain(1).insert("!").retain(1);
+    assert_eq!(op.apply("é🙂").unwrap(), "é!🙂");
+}
+
+#[test]
+fn insert_after_delete_normalises_ahead_of_it() {
+    let a = TextOp::new().retain(1).delete(2).insert("x");
+    let b = TextOp::new().retain(1).insert("x").delete(2);
+    assert_eq!(a, b);
+}
+
+#[test]
+fn from_diff_builds_single_splice() {
+    let op = TextOp::from_diff("hello world", "hello brave world");
+    assert_eq!(
+        op.components(),
+        &[
+            Component::Retain(6),
+            Component::Insert("brave ".into()),
+            Component::Retain(5)
+        ]
+    );
+    assert_eq!(op.apply("hello world").unwrap(), "hello brave world");
+
+    let op = TextOp::from_diff("aaa", "aa");
+    assert_eq!(op.apply("aaa").unwrap(), "aa");
+    assert!(TextOp::from_diff("same", "same").is_noop());
+}
+
+#[test]
+fn compose_matches_sequential_apply() {
+    let mut rng = Lcg(7);
+    for _ in 0..200 {
+        let text = "the quick brown fox";
+        let a = random_op(&mut rng, text);
+        let mid = a.apply(text).unwrap();
+        let b = random_op(&mut rng, &mid);
+        let composed = a.compose(&b).unwrap();
+        assert_eq!(composed.apply(text).unwrap(), b.apply(&mid).unwrap());
+    }
+}
+
+#[test]
+fn transform_converges_for_concurrent_ops() {
+    let mut rng = Lcg(42);
+    for _ in 0..500 {
+        let text = "concurrent é🙂 text";
+        let a = random_op(&mut rng, text);
+        let b = random_op(&mut rng, text);
+        let (a_prime, b_prime) = transform(&a, &b).unwrap();
+        let left = b_prime.apply(&a.apply(text).unwrap()).unwrap();
+        let right = a_prime.apply(&b.apply(text).unwrap()).unwrap();
+        assert_eq!(left, right);
+    }
+}
+
+#[test]
+fn transform_puts_first_operation_inserts_first_on_ties() {
+    let a = TextOp::new().retain(1).insert("A").retain(1);
+    let b = TextOp::new().retain(1).insert("B").retain(1);
+    let (a_prime, _) = transform(&a, &b).unwrap();
+    assert_eq!(a_prime.apply(&b.apply("xy").unwrap()).unwrap(), "xABy");
+}
+
+#[test]
+fn transform_rejects_mismatched_bases() {
+    let a = TextOp::new().retain(2);
+    let b = TextOp::new().retain(3);
+    assert_eq!(transform(&a, &b), Err(TextOpError::Incompatible));
+}
+
+#[test]
+fn transform_index_tracks_remote_edits() {
+    let op = TextOp::new().insert("ab").retain(3).delete(2).retain(1);
+    assert_eq!(op.transform_index(0), 0);
+    assert_eq!(op.transform_index(2), 4);
+    assert_eq!(op.transform_index(4), 5);
+    assert_eq!(op.transform_index(6), 6);
+}
+
+#[test]
+fn json_round_trip() {
+    let op = TextOp::new().retain(3).insert("hi").delete(2).retain(1);
+    let json = op.to_json();
+    assert_eq!(json, serde_json::json!([3, "hi", -2, 1]));
+    assert_eq!(TextOp::from_json(&json).unwrap(), op);
+}
+
+#[test]
+fn from_json_rejects_malformed_components() {
+    assert!(TextOp::from_json(&serde_json::json!({})).is_err());
+    assert!(TextOp::from_json(&serde_json::json!([0])).is_err());
+    assert!(TextOp::from_json(&serde_json::json!([""])).is_err());
+    assert!(TextOp::from_json(&serde_json::json!([1.5])).is_err());
+}
+
+#[test]
+fn from_json_rejects_oversized_counts() {
+    let huge = 1_i64 << 62;
+    let malformed = |value: serde_json::Value| {
+        matches!(TextOp::from_json(&value), Err(TextOpError::Malformed(_)))
+    };
+    assert!(malformed(serde_json::json!([huge, huge])));
+    assert!(malformed(serde_json::json!([-huge, -huge])));
+    assert!(malformed(serde_json::json!([i64::MAX])));
+    assert!(malformed(serde_json::json!([MAX_TEXT_LEN, 1])));
+    assert!(malformed(serde_json::json!([MAX_TEXT_LEN, "x"])));
+
+    let widest = TextOp::from_json(&serde_json::json!([MAX_TEXT_LEN])).unwrap();
+    assert_eq!(widest.base_len(), MAX_TEXT_LEN);
+}
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS ai_threads (
+    id              UUID PRIMARY KEY,
+    board_id        UUID NOT NULL REFERENCES boards(id) ON DELETE CASCADE,
+    title           TEXT NOT NULL,
+    created_by      UUID,
+    created_at      BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM now()) * 1000)::BIGINT,
+    updated_at      BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM now()) * 1000)::BIGINT,
+    summary         TEXT NOT NULL DEFAULT '',
+    summarized_seq  BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_threads_board_updated ON ai_threads(board_id, updated_at DESC);
+
+CREATE TABLE IF NOT EXISTS ai_thread_messages (
+    thread_id   UUID NOT NULL REFERENCES ai_threads(id) ON DELETE CASCADE,
+    seq         BIGINT NOT NULL,
+    ts          BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM now()) * 1000)::BIGINT,
+    role        TEXT NOT NULL,
+    content     TEXT NOT NULL,
+    user_id     UUID,
+    PRIMARY KEY (thread_id, seq)
+);
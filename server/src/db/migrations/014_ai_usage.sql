@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS ai_usage (
+    id                      BIGSERIAL PRIMARY KEY,
+    ts                      BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM now()) * 1000)::BIGINT,
+    user_id                 UUID NOT NULL,
+    board_id                UUID NOT NULL,
+    trace_id                UUID,
+    model                   TEXT NOT NULL,
+    input_tokens            BIGINT NOT NULL DEFAULT 0,
+    output_tokens           BIGINT NOT NULL DEFAULT 0,
+    cache_read_tokens       BIGINT NOT NULL DEFAULT 0,
+    cache_creation_tokens   BIGINT NOT NULL DEFAULT 0,
+    cost_usd                DOUBLE PRECISION
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_usage_user_ts ON ai_usage(user_id, ts);
+CREATE INDEX IF NOT EXISTS idx_ai_usage_board_ts ON ai_usage(board_id, ts);
+
+CREATE TABLE IF NOT EXISTS ai_budgets (
+    scope           TEXT NOT NULL CHECK (scope IN ('user', 'board')),
+    scope_id        UUID NOT NULL,
+    daily_tokens    BIGINT,
+    monthly_tokens  BIGINT,
+    PRIMARY KEY (scope, scope_id)
+);
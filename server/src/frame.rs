@@ -211,6 +211,12 @@ impl Frame {
         self.reply(Status::Done, data)
     }
 
+    /// Create a cancel response carrying payload data. Terminal.
+    #[must_use]
+    pub fn cancelled_with(&self, data: Data) -> Self {
+        self.reply(Status::Cancel, data)
+    }
+
     /// Create an item response carrying payload data. Non-terminal.
     #[must_use]
     pub fn item_with(&self, data: Data) -> Self {
@@ -0,0 +1,191 @@
+//! Excalidraw (`.excalidraw`) import.
+//!
+//! Elements map by `type`: rectangles, ellipses, and diamonds keep their
+//! kind, frames become board frames titled by `name`, and text becomes text
+//! objects. Text bound inside a shape through `containerId` becomes that
+//! shape's label instead. Arrows and lines become straight connectors from
+//! their first to their last point, attached wherever `startBinding` or
+//! `endBinding` names an imported shape.
+//!
+//! Excalidraw stores `angle` in radians and points relative to the element's
+//! origin; colors are CSS strings and carry over as they are.
+
+use std::collections::{HashMap, HashSet};
+
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use super::{DEFAULT_STROKE, Geometry, Import, ImportBuilder, ImportError, Terminal, text_geometry};
+
+/// Element types imported as shapes of the same kind.
+const SHAPE_TYPES: &[&str] = &["rectangle", "ellipse", "diamond"];
+/// Excalidraw's default text size.
+const DEFAULT_FONT_SIZE: f64 = 20.0;
+
+/// Convert an Excalidraw file into board objects for `board_id`.
+///
+/// # Errors
+///
+/// Returns `InvalidFile` when the document has no `elements` array or
+/// declares a type other than `excalidraw`.
+pub fn import_excalidraw(file: &Value, board_id: Uuid, user_id: Uuid) -> Result<Import, ImportError> {
+    let invalid = || ImportError::InvalidFile("Excalidraw");
+    if file
+        .get("type")
+        .and_then(Value::as_str)
+        .is_some_and(|kind| !kind.starts_with("excalidraw"))
+    {
+        return Err(invalid());
+    }
+    let elements: Vec<&Value> = file
+        .get("elements")
+        .and_then(Value::as_array)
+        .ok_or_else(invalid)?
+        .iter()
+        .filter(|element| element.is_object() && !element["isDeleted"].as_bool().unwrap_or(false))
+        .collect();
+
+    // Text inside a shape labels the shape rather than standing alone.
+    let containers: HashSet<&str> = elements
+        .iter()
+        .filter(|element| SHAPE_TYPES.contains(&element["type"].as_str().unwrap_or("")))
+        .filter_map(|element| element["id"].as_str())
+        .collect();
+    let labels: HashMap<&str, &Value> = elements
+        .iter()
+        .filter(|element| element["type"] == "text")
+        .filter_map(|element| {
+            let container = element["containerId"].as_str()?;
+            containers
+                .contains(container)
+                .then_some((container, *element))
+        })
+        .collect();
+
+    let mut builder = ImportBuilder::new(board_id, user_id);
+    for element in elements {
+        let id = element["id"].as_str().unwrap_or("");
+        let kind = element["type"].as_str().unwrap_or("");
+        let group = outermost_group(element);
+        match kind {
+            "rectangle" | "ellipse" | "diamond" => {
+                let mut props = shape_props(element);
+                if let Some(label) = labels.get(id) {
+                    props["text"] = json!(text_of(label));
+                    props["fontSize"] = json!(font_size(label));
+                    props["textColor"] = json!(stroke_color(label));
+                }
+                builder.shape(id, kind, geometry(element), props, group);
+            }
+            "frame" | "magicframe" => {
+                let title = element["name"].as_str().unwrap_or("Frame");
+                builder.shape(id, "frame", geometry(element), json!({ "title": title }), group);
+            }
+            "text" => {
+                if element["containerId"]
+                    .as_str()
+                    .is_some_and(|container| containers.contains(container))
+                {
+                    continue;
+                }
+                let props = json!({
+                    "text": text_of(element),
+                    "fontSize": font_size(element),
+                    "textColor": stroke_color(element),
+                });
+                builder.shape(id, "text", text_geometry(geometry(element)), props, group);
+            }
+            "arrow" | "line" => connector(&mut builder, element, group),
+            _ => builder.unsupported(id, kind),
+        }
+    }
+    Ok(builder.finish())
+}
+
+/// Add an arrow or line from its first to its last point. Arrows without
+/// arrowheads import as lines, and arrows with only a start arrowhead are
+/// reversed so the board's head lands on the same end.
+fn connector(builder: &mut ImportBuilder, element: &Value, group: Option<&str>) {
+    let id = element["id"].as_str().unwrap_or("");
+    let points: Vec<(f64, f64)> = element["points"]
+        .as_array()
+        .map(|points| {
+            points
+                .iter()
+                .filter_map(|point| Some((point[0].as_f64()?, point[1].as_f64()?)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let (Some(&first), Some(&last)) = (points.first(), points.last()) else {
+        builder.unsupported(id, element["type"].as_str().unwrap_or(""));
+        return;
+    };
+
+    let Geometry { x, y, width, height, rotation } = geometry(element);
+    let center = (x + width / 2.0, y + height / 2.0);
+    let world = |(px, py): (f64, f64)| super::rotate_about((x + px, y + py), center, rotation);
+    let start = Terminal::bound(world(first), binding_target(element, "startBinding"), None);
+    let end = Terminal::bound(world(last), binding_target(element, "endBinding"), None);
+
+    let has_head = |key: &str| element[key].as_str().is_some();
+    let (kind, terminals) = if element["type"] != "arrow" {
+        ("line", [start, end])
+    } else if has_head("endArrowhead") {
+        ("arrow", [start, end])
+    } else if has_head("startArrowhead") {
+        ("arrow", [end, start])
+    } else {
+        ("line", [start, end])
+    };
+    let props = json!({
+        "stroke": stroke_color(element),
+        "strokeWidth": element["strokeWidth"].as_f64().unwrap_or(1.0),
+    });
+    builder.connector(id, kind, terminals, props, group);
+}
+
+fn geometry(element: &Value) -> Geometry {
+    Geometry {
+        x: element["x"].as_f64().unwrap_or(0.0),
+        y: element["y"].as_f64().unwrap_or(0.0),
+        width: element["width"].as_f64().unwrap_or(0.0),
+        height: element["height"].as_f64().unwrap_or(0.0),
+        rotation: element["angle"].as_f64().unwrap_or(0.0).to_degrees(),
+    }
+}
+
+fn shape_props(element: &Value) -> Value {
+    json!({
+        "fill": element["backgroundColor"].as_str().unwrap_or("transparent"),
+        "stroke": stroke_color(element),
+        "strokeWidth": element["strokeWidth"].as_f64().unwrap_or(1.0),
+    })
+}
+
+fn stroke_color(element: &Value) -> &str {
+    element["strokeColor"].as_str().unwrap_or(DEFAULT_STROKE)
+}
+
+/// The author's text, without the line breaks Excalidraw inserts to wrap it.
+fn text_of(element: &Value) -> &str {
+    element["originalText"]
+        .as_str()
+        .or_else(|| element["text"].as_str())
+        .unwrap_or("")
+}
+
+fn font_size(element: &Value) -> f64 {
+    element["fontSize"].as_f64().unwrap_or(DEFAULT_FONT_SIZE)
+}
+
+/// `groupIds` lists groups innermost first.
+fn outermost_group(element: &Value) -> Option<&str> {
+    element["groupIds"]
+        .as_array()
+        .and_then(|groups| groups.last())
+        .and_then(Value::as_str)
+}
+
+fn binding_target<'a>(element: &'a Value, key: &str) -> Option<&'a str> {
+    element[key]["elementId"].as_str()
+}
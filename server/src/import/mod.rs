@@ -0,0 +1,290 @@
+//! Whiteboard file import: Excalidraw and tldraw documents as board objects.
+//!
+//! DESIGN
+//! ======
+//! Each format module walks its file's elements in paint order and hands
+//! them to an [`ImportBuilder`], which stamps ids, z-order, and groups onto
+//! new `BoardObject`s. Connectors record which source element each end was
+//! bound to; once every element is built the builder turns those bindings
+//! into `attached` endpoints, anchored where the connector touched the
+//! shape. Bindings to elements that were not imported stay free points.
+//!
+//! Elements with no board equivalent (freehand strokes, images, embeds, ...)
+//! are listed in [`Import::unsupported`] instead of being dropped silently.
+//! Groups flatten to their outermost group, since board groups do not nest.
+
+pub mod excalidraw;
+pub mod tldraw;
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::state::BoardObject;
+
+pub use excalidraw::import_excalidraw;
+pub use tldraw::import_tldraw;
+
+/// Default stroke color when a file leaves it unset.
+const DEFAULT_STROKE: &str = "#1F1A17";
+
+/// Errors returned when a file cannot be imported at all.
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    /// The document is not a file of the expected format.
+    #[error("not a valid {0} file")]
+    InvalidFile(&'static str),
+}
+
+/// An element the importer has no board equivalent for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Unsupported {
+    /// Element id in the source file.
+    pub id: String,
+    /// Element type in the source file, e.g. `"freedraw"` or `"geo:cloud"`.
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// Result of importing one file.
+#[derive(Debug, Clone, Default)]
+pub struct Import {
+    /// New objects in paint order, with `z_index` counting up from zero.
+    pub objects: Vec<BoardObject>,
+    /// Elements that were skipped because the board cannot represent them.
+    pub unsupported: Vec<Unsupported>,
+}
+
+// =============================================================================
+// BUILDER
+// =============================================================================
+
+/// Source-space geometry of an imported shape.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Geometry {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// Clockwise rotation in degrees.
+    pub rotation: f64,
+}
+
+/// One end of an imported connector.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Terminal<'a> {
+    /// World position of the end.
+    pub point: (f64, f64),
+    /// Source id of the element the end is bound to.
+    pub bound_to: Option<&'a str>,
+    /// `ux`/`uy` anchor on the bound element, when the file stores one;
+    /// otherwise it is derived from `point`.
+    pub anchor: Option<(f64, f64)>,
+}
+
+impl<'a> Terminal<'a> {
+    pub fn free(point: (f64, f64)) -> Self {
+        Self { point, bound_to: None, anchor: None }
+    }
+
+    pub fn bound(point: (f64, f64), bound_to: Option<&'a str>, anchor: Option<(f64, f64)>) -> Self {
+        Self { point, bound_to, anchor }
+    }
+}
+
+/// A connector end waiting for its target to be imported.
+struct PendingBinding {
+    object: usize,
+    end: &'static str,
+    target: String,
+    anchor: Option<(f64, f64)>,
+}
+
+/// Collects imported objects and resolves references between them.
+pub(crate) struct ImportBuilder {
+    board_id: Uuid,
+    user_id: Uuid,
+    objects: Vec<BoardObject>,
+    unsupported: Vec<Unsupported>,
+    /// Source element id to the index of the object built from it.
+    by_source: HashMap<String, usize>,
+    groups: HashMap<String, Uuid>,
+    bindings: Vec<PendingBinding>,
+}
+
+impl ImportBuilder {
+    pub fn new(board_id: Uuid, user_id: Uuid) -> Self {
+        Self {
+            board_id,
+            user_id,
+            objects: Vec::new(),
+            unsupported: Vec::new(),
+            by_source: HashMap::new(),
+            groups: HashMap::new(),
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Add a shape drawn from source element `source_id`.
+    pub fn shape(
+        &mut self,
+        source_id: &str,
+        kind: &str,
+        geometry: Geometry,
+        props: serde_json::Value,
+        group: Option<&str>,
+    ) {
+        let object = self.object(kind, geometry, props, group);
+        self.by_source
+            .insert(source_id.to_owned(), self.objects.len());
+        self.objects.push(object);
+    }
+
+    /// Add a straight connector from `start` to `end`.
+    pub fn connector(
+        &mut self,
+        source_id: &str,
+        kind: &str,
+        [start, end]: [Terminal<'_>; 2],
+        mut props: serde_json::Value,
+        group: Option<&str>,
+    ) {
+        let (a, b) = (start.point, end.point);
+        props["a"] = serde_json::json!({ "type": "free", "x": a.0, "y": a.1 });
+        props["b"] = serde_json::json!({ "type": "free", "x": b.0, "y": b.1 });
+        let geometry = Geometry {
+            x: a.0.min(b.0),
+            y: a.1.min(b.1),
+            width: (b.0 - a.0).abs(),
+            height: (b.1 - a.1).abs(),
+            rotation: 0.0,
+        };
+        let index = self.objects.len();
+        for (key, terminal) in [("a", start), ("b", end)] {
+            if let Some(target) = terminal.bound_to {
+                self.bindings.push(PendingBinding {
+                    object: index,
+                    end: key,
+                    target: target.to_owned(),
+                    anchor: terminal.anchor,
+                });
+            }
+        }
+        self.shape(source_id, kind, geometry, props, group);
+    }
+
+    /// Record an element the board cannot represent.
+    pub fn unsupported(&mut self, source_id: &str, kind: &str) {
+        self.unsupported
+            .push(Unsupported { id: source_id.to_owned(), kind: kind.to_owned() });
+    }
+
+    /// Attach bound connector ends and return the import.
+    pub fn finish(mut self) -> Import {
+        for binding in std::mem::take(&mut self.bindings) {
+            let Some(&target) = self.by_source.get(&binding.target) else {
+                continue;
+            };
+            if target == binding.object {
+                continue;
+            }
+            let target = &self.objects[target];
+            if matches!(target.kind.as_str(), "line" | "arrow") {
+                continue;
+            }
+            let target_id = target.id;
+            let (ux, uy) = binding.anchor.map_or_else(
+                || {
+                    let endpoint = &self.objects[binding.object].props[binding.end];
+                    let x = endpoint["x"].as_f64().unwrap_or(0.0);
+                    let y = endpoint["y"].as_f64().unwrap_or(0.0);
+                    anchor_in(target, x, y)
+                },
+                |(ux, uy)| (ux.clamp(0.0, 1.0), uy.clamp(0.0, 1.0)),
+            );
+            let endpoint = &mut self.objects[binding.object].props[binding.end];
+            endpoint["type"] = serde_json::json!("attached");
+            endpoint["object_id"] = serde_json::json!(target_id);
+            endpoint["ux"] = serde_json::json!(ux);
+            endpoint["uy"] = serde_json::json!(uy);
+        }
+        Import { objects: self.objects, unsupported: self.unsupported }
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    fn object(&mut self, kind: &str, geometry: Geometry, props: serde_json::Value, group: Option<&str>) -> BoardObject {
+        let group_id = group.map(|group| {
+            *self
+                .groups
+                .entry(group.to_owned())
+                .or_insert_with(Uuid::new_v4)
+        });
+        BoardObject {
+            id: Uuid::new_v4(),
+            board_id: self.board_id,
+            kind: kind.to_owned(),
+            x: geometry.x,
+            y: geometry.y,
+            width: Some(geometry.width),
+            height: Some(geometry.height),
+            rotation: geometry.rotation,
+            z_index: self.objects.len() as i32,
+            props,
+            created_by: Some(self.user_id),
+            version: 1,
+            group_id,
+        }
+    }
+}
+
+/// Where world point `(x, y)` falls in `target`'s unrotated box, as the
+/// `ux`/`uy` fractions of an attached endpoint. Points outside the box, such
+/// as ends that stop short of a shape, clamp to its edge.
+fn anchor_in(target: &BoardObject, x: f64, y: f64) -> (f64, f64) {
+    let width = target.width.unwrap_or(0.0);
+    let height = target.height.unwrap_or(0.0);
+    let center = (target.x + width / 2.0, target.y + height / 2.0);
+    let (local_x, local_y) = rotate_about((x, y), center, -target.rotation);
+    let fraction = |offset: f64, extent: f64| {
+        if extent > 0.0 {
+            (offset / extent).clamp(0.0, 1.0)
+        } else {
+            0.5
+        }
+    };
+    (fraction(local_x - target.x, width), fraction(local_y - target.y, height))
+}
+
+/// Board text wraps this far inside an object's box, split across both sides.
+const TEXT_INSET: f64 = 12.0;
+
+/// Geometry for a text object whose source box fits its text exactly, grown
+/// by the board's text inset so lines do not rewrap or get cut off.
+pub(crate) fn text_geometry(geometry: Geometry) -> Geometry {
+    Geometry {
+        x: geometry.x - TEXT_INSET / 2.0,
+        y: geometry.y - TEXT_INSET / 2.0,
+        width: geometry.width + TEXT_INSET,
+        height: geometry.height + TEXT_INSET,
+        rotation: geometry.rotation,
+    }
+}
+
+/// Estimated height of `text` set at `font_size`, for formats that do not
+/// store one.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn text_height(text: &str, font_size: f64) -> f64 {
+    text.lines().count().max(1) as f64 * font_size * 1.25
+}
+
+/// Rotate `point` clockwise by `degrees` around `center`.
+pub(crate) fn rotate_about(point: (f64, f64), center: (f64, f64), degrees: f64) -> (f64, f64) {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (dx, dy) = (point.0 - center.0, point.1 - center.1);
+    (center.0 + dx * cos - dy * sin, center.1 + dx * sin + dy * cos)
+}
+
+#[cfg(test)]
+#[path = "mod_test.rs"]
+mod tests;
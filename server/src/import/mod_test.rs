@@ -0,0 +1,469 @@
+//! Tests for Excalidraw and tldraw import.
+
+use serde_json::json;
+use uuid::Uuid;
+
+use super::{Geometry, Import, ImportBuilder, ImportError, Terminal, anchor_in, import_excalidraw, import_tldraw};
+use crate::state::BoardObject;
+
+fn excalidraw(elements: serde_json::Value) -> Import {
+    let file = json!({ "type": "excalidraw", "version": 2, "elements": elements });
+    import_excalidraw(&file, Uuid::new_v4(), Uuid::new_v4()).unwrap()
+}
+
+fn tldraw(records: serde_json::Value) -> Import {
+    let file = json!({ "tldrawFileFormatVersion": 1, "records": records });
+    import_tldraw(&file, Uuid::new_v4(), Uuid::new_v4()).unwrap()
+}
+
+fn kinds(import: &Import) -> Vec<&str> {
+    import
+        .objects
+        .iter()
+        .map(|object| object.kind.as_str())
+        .collect()
+}
+
+fn approx(actual: f64, expected: f64) -> bool {
+    (actual - expected).abs() < 1e-9
+}
+
+fn attached_to(connector: &BoardObject, end: &str) -> Option<(Uuid, f64, f64)> {
+    let endpoint = &connector.props[end];
+    if endpoint["type"] != "attached" {
+        return None;
+    }
+    Some((
+        endpoint["object_id"].as_str()?.parse().ok()?,
+        endpoint["ux"].as_f64()?,
+        endpoint["uy"].as_f64()?,
+    ))
+}
+
+fn excalidraw_rect(id: &str, x: f64, y: f64) -> serde_json::Value {
+    json!({
+        "id": id, "type": "rectangle", "x": x, "y": y, "width": 100.0, "height": 50.0, "angle": 0.0,
+        "strokeColor": "#1e1e1e", "backgroundColor": "#a5d8ff", "strokeWidth": 2.0, "groupIds": [],
+    })
+}
+
+fn excalidraw_arrow(start: Option<&str>, end: Option<&str>, heads: (Option<&str>, Option<&str>)) -> serde_json::Value {
+    json!({
+        "id": "arrow", "type": "arrow", "x": 105.0, "y": 25.0, "width": 190.0, "height": 0.0, "angle": 0.0,
+        "strokeColor": "#e03131", "strokeWidth": 2.0, "points": [[0.0, 0.0], [90.0, 10.0], [190.0, 0.0]],
+        "startBinding": start.map(|id| json!({ "elementId": id, "focus": 0.0, "gap": 5.0 })),
+        "endBinding": end.map(|id| json!({ "elementId": id, "focus": 0.0, "gap": 5.0 })),
+        "startArrowhead": heads.0, "endArrowhead": heads.1,
+    })
+}
+
+fn tldraw_page(id: &str, index: &str) -> serde_json::Value {
+    json!({ "id": id, "typeName": "page", "name": id, "index": index })
+}
+
+fn tldraw_geo(id: &str, parent: &str, index: &str, (x, y): (f64, f64), props: serde_json::Value) -> serde_json::Value {
+    let mut base = json!({ "w": 100.0, "h": 50.0, "geo": "rectangle", "color": "black", "fill": "none", "size": "m" });
+    if let (Some(base), Some(props)) = (base.as_object_mut(), props.as_object()) {
+        base.extend(props.clone());
+    }
+    json!({
+        "id": id, "typeName": "shape", "type": "geo", "parentId": parent, "index": index,
+        "x": x, "y": y, "rotation": 0.0, "props": base,
+    })
+}
+
+// =============================================================================
+// BUILDER
+// =============================================================================
+
+#[test]
+fn anchor_in_clamps_to_the_unrotated_box() {
+    let mut import = ImportBuilder::new(Uuid::new_v4(), Uuid::new_v4());
+    let geometry = Geometry { x: 0.0, y: 0.0, width: 100.0, height: 50.0, rotation: 0.0 };
+    import.shape("a", "rectangle", geometry, json!({}), None);
+    import.shape("b", "rectangle", Geometry { rotation: 90.0, ..geometry }, json!({}), None);
+    let objects = import.finish().objects;
+
+    let (ux, uy) = anchor_in(&objects[0], 105.0, 25.0);
+    assert!(approx(ux, 1.0) && approx(uy, 0.5));
+    // The right edge of a box turned a quarter clockwise faces down.
+    let (ux, uy) = anchor_in(&objects[1], 50.0, 75.0);
+    assert!(approx(ux, 1.0) && approx(uy, 0.5));
+}
+
+#[test]
+fn finish_attaches_only_to_imported_shapes() {
+    let mut import = ImportBuilder::new(Uuid::new_v4(), Uuid::new_v4());
+    let geometry = Geometry { x: 0.0, y: 0.0, width: 100.0, height: 50.0, rotation: 0.0 };
+    let start = Terminal::bound((100.0, 25.0), Some("missing"), None);
+    let end = Terminal::bound((0.0, 0.0), Some("box"), Some((0.25, 2.0)));
+    import.connector("edge", "arrow", [start, end], json!({}), Some("g"));
+    import.shape("box", "rectangle", geometry, json!({}), Some("g"));
+    let Import { objects, unsupported } = import.finish();
+
+    assert!(unsupported.is_empty());
+    assert_eq!(objects[0].props["a"], json!({ "type": "free", "x": 100.0, "y": 25.0 }));
+    assert_eq!(attached_to(&objects[0], "b"), Some((objects[1].id, 0.25, 1.0)));
+    assert_eq!((objects[0].z_index, objects[1].z_index), (0, 1));
+    assert!(objects[0].group_id.is_some());
+    assert_eq!(objects[0].group_id, objects[1].group_id);
+}
+
+// =============================================================================
+// EXCALIDRAW
+// =============================================================================
+
+#[test]
+fn excalidraw_rejects_other_documents() {
+    let board = Uuid::new_v4();
+    for file in [
+        json!({ "elements": {} }),
+        json!({ "type": "tldraw", "elements": [] }),
+        json!([]),
+    ] {
+        assert!(matches!(
+            import_excalidraw(&file, board, board),
+            Err(ImportError::InvalidFile("Excalidraw"))
+        ));
+    }
+}
+
+#[test]
+fn excalidraw_maps_shapes_geometry_and_style() {
+    let mut ellipse = excalidraw_rect("e", 10.0, 20.0);
+    ellipse["type"] = json!("ellipse");
+    ellipse["angle"] = json!(std::f64::consts::FRAC_PI_2);
+    ellipse["backgroundColor"] = json!("transparent");
+    let mut deleted = excalidraw_rect("gone", 0.0, 0.0);
+    deleted["isDeleted"] = json!(true);
+    let frame =
+        json!({ "id": "f", "type": "frame", "x": -50.0, "y": -50.0, "width": 400.0, "height": 300.0, "name": "Plan" });
+    let diamond = json!({ "id": "d", "type": "diamond", "x": 0.0, "y": 0.0, "width": 10.0, "height": 10.0 });
+    let import = excalidraw(json!([frame, excalidraw_rect("r", 0.0, 0.0), ellipse, deleted, diamond]));
+
+    assert_eq!(kinds(&import), vec!["frame", "rectangle", "ellipse", "diamond"]);
+    let [frame, rect, ellipse, diamond] = &import.objects[..] else {
+        panic!("expected four objects");
+    };
+    assert_eq!(frame.props, json!({ "title": "Plan" }));
+    assert_eq!((frame.width, frame.height), (Some(400.0), Some(300.0)));
+    assert_eq!(
+        rect.props,
+        json!({ "fill": "#a5d8ff", "stroke": "#1e1e1e", "strokeWidth": 2.0 })
+    );
+    assert!(approx(ellipse.rotation, 90.0));
+    assert_eq!((ellipse.x, ellipse.y), (10.0, 20.0));
+    assert_eq!(ellipse.props["fill"], "transparent");
+    assert_eq!(diamond.props["stroke"], "#1F1A17");
+    assert_eq!(diamond.z_index, 3);
+    assert!(import.objects.iter().all(|object| object.version == 1));
+}
+
+#[test]
+fn excalidraw_labels_containers_and_pads_loose_text() {
+    let mut rect = excalidraw_rect("r", 0.0, 0.0);
+    rect["boundElements"] = json!([{ "id": "label", "type": "text" }]);
+    let label = json!({
+        "id": "label", "type": "text", "x": 20.0, "y": 12.0, "width": 60.0, "height": 25.0,
+        "text": "Two\nlines", "originalText": "Two lines", "fontSize": 16.0, "strokeColor": "#2f9e44",
+        "containerId": "r",
+    });
+    let loose = json!({
+        "id": "t", "type": "text", "x": 0.0, "y": 100.0, "width": 80.0, "height": 25.0,
+        "text": "Note", "fontSize": 20.0, "strokeColor": "#1e1e1e",
+    });
+    let import = excalidraw(json!([rect, label, loose]));
+
+    assert_eq!(kinds(&import), vec!["rectangle", "text"]);
+    let rect = &import.objects[0];
+    assert_eq!(rect.props["text"], "Two lines");
+    assert_eq!(rect.props["fontSize"], 16.0);
+    assert_eq!(rect.props["textColor"], "#2f9e44");
+    let text = &import.objects[1];
+    assert_eq!(text.props, json!({ "text": "Note", "fontSize": 20.0, "textColor": "#1e1e1e" }));
+    assert_eq!((text.x, text.y, text.width, text.height), (-6.0, 94.0, Some(92.0), Some(37.0)));
+}
+
+#[test]
+fn excalidraw_arrows_attach_to_bound_shapes() {
+    let import = excalidraw(json!([
+        excalidraw_rect("a", 0.0, 0.0),
+        excalidraw_rect("b", 300.0, 0.0),
+        excalidraw_arrow(Some("a"), Some("b"), (None, Some("arrow"))),
+    ]));
+
+    let arrow = &import.objects[2];
+    assert_eq!(arrow.kind, "arrow");
+    assert_eq!(attached_to(arrow, "a"), Some((import.objects[0].id, 1.0, 0.5)));
+    assert_eq!(attached_to(arrow, "b"), Some((import.objects[1].id, 0.0, 0.5)));
+    // Middle points are dropped; the free position is the last point.
+    assert_eq!(
+        (arrow.props["b"]["x"].as_f64(), arrow.props["b"]["y"].as_f64()),
+        (Some(295.0), Some(25.0))
+    );
+    assert_eq!(arrow.props["stroke"], "#e03131");
+    assert_eq!(
+        (arrow.x, arrow.y, arrow.width, arrow.height),
+        (105.0, 25.0, Some(190.0), Some(0.0))
+    );
+}
+
+#[test]
+fn excalidraw_arrowheads_pick_kind_and_direction() {
+    let reversed = excalidraw(json!([
+        excalidraw_rect("a", 0.0, 0.0),
+        excalidraw_arrow(Some("a"), None, (Some("triangle"), None)),
+    ]));
+    let arrow = &reversed.objects[1];
+    assert_eq!(arrow.kind, "arrow");
+    assert_eq!(attached_to(arrow, "b").map(|(id, ..)| id), Some(reversed.objects[0].id));
+    assert_eq!(arrow.props["a"]["type"], "free");
+
+    let plain = excalidraw(json!([excalidraw_arrow(None, None, (None, None))]));
+    assert_eq!(kinds(&plain), vec!["line"]);
+}
+
+#[test]
+fn excalidraw_reports_unsupported_elements_and_leaves_their_bindings_free() {
+    let sketch = json!({ "id": "s", "type": "freedraw", "x": 0.0, "y": 0.0, "points": [[0.0, 0.0]] });
+    let image = json!({ "id": "i", "type": "image", "x": 300.0, "y": 0.0, "width": 100.0, "height": 50.0 });
+    let import = excalidraw(json!([
+        sketch,
+        image,
+        excalidraw_arrow(Some("s"), Some("i"), (None, Some("arrow")))
+    ]));
+
+    assert_eq!(kinds(&import), vec!["arrow"]);
+    let reported: Vec<(&str, &str)> = import
+        .unsupported
+        .iter()
+        .map(|item| (item.id.as_str(), item.kind.as_str()))
+        .collect();
+    assert_eq!(reported, vec![("s", "freedraw"), ("i", "image")]);
+    assert!(attached_to(&import.objects[0], "a").is_none());
+    assert!(attached_to(&import.objects[0], "b").is_none());
+}
+
+#[test]
+fn excalidraw_groups_by_outermost_group() {
+    let mut inner = excalidraw_rect("a", 0.0, 0.0);
+    inner["groupIds"] = json!(["inner", "outer"]);
+    let mut outer = excalidraw_rect("b", 200.0, 0.0);
+    outer["groupIds"] = json!(["outer"]);
+    let loose = excalidraw_rect("c", 400.0, 0.0);
+    let import = excalidraw(json!([inner, outer, loose]));
+
+    assert!(import.objects[0].group_id.is_some());
+    assert_eq!(import.objects[0].group_id, import.objects[1].group_id);
+    assert_eq!(import.objects[2].group_id, None);
+}
+
+// =============================================================================
+// TLDRAW
+// =============================================================================
+
+#[test]
+fn tldraw_rejects_other_documents() {
+    let board = Uuid::new_v4();
+    for file in [json!({ "elements": [] }), json!({ "records": {} }), json!("tldraw")] {
+        assert!(matches!(
+            import_tldraw(&file, board, board),
+            Err(ImportError::InvalidFile("tldraw"))
+        ));
+    }
+}
+
+#[test]
+fn tldraw_maps_geo_shapes_and_palette() {
+    let rich_text = json!({
+        "type": "doc",
+        "content": [
+            { "type": "paragraph", "content": [{ "type": "text", "text": "Ship" }] },
+            { "type": "paragraph" },
+            { "type": "paragraph", "content": [{ "type": "text", "text": "it" }] },
+        ],
+    });
+    let import = tldraw(json!([
+        tldraw_page("page:one", "a1"),
+        tldraw_geo(
+            "shape:b",
+            "page:one",
+            "a2",
+            (0.0, 0.0),
+            json!({ "geo": "ellipse", "color": "blue", "fill": "semi" })
+        ),
+        tldraw_geo(
+            "shape:a",
+            "page:one",
+            "a1",
+            (10.0, 20.0),
+            json!({ "color": "red", "fill": "solid", "size": "s", "richText": rich_text })
+        ),
+        tldraw_geo("shape:c", "page:one", "a3", (0.0, 0.0), json!({ "geo": "cloud" })),
+    ]));
+
+    assert_eq!(kinds(&import), vec!["rectangle", "ellipse"]);
+    let rect = &import.objects[0];
+    assert_eq!((rect.x, rect.y, rect.width, rect.height), (10.0, 20.0, Some(100.0), Some(50.0)));
+    assert_eq!(rect.props["fill"], "#E03131");
+    assert_eq!(rect.props["stroke"], "#E03131");
+    assert_eq!(rect.props["strokeWidth"], 2.0);
+    assert_eq!(rect.props["text"], "Ship\n\nit");
+    assert_eq!(rect.props["fontSize"], 18.0);
+    assert_eq!(import.objects[1].props["fill"], "#DCE1F8");
+    assert_eq!(import.unsupported.len(), 1);
+    assert_eq!(
+        (import.unsupported[0].id.as_str(), import.unsupported[0].kind.as_str()),
+        ("shape:c", "geo:cloud")
+    );
+}
+
+#[test]
+fn tldraw_places_children_in_page_space() {
+    let frame = json!({
+        "id": "shape:frame", "typeName": "shape", "type": "frame", "parentId": "page:one", "index": "a1",
+        "x": 100.0, "y": 100.0, "rotation": 0.0, "props": { "w": 400.0, "h": 300.0, "name": "" },
+    });
+    let group = json!({
+        "id": "shape:group", "typeName": "shape", "type": "group", "parentId": "shape:frame", "index": "a2",
+        "x": 0.0, "y": 0.0, "rotation": 0.0, "props": {},
+    });
+    let mut turned = tldraw_geo(
+        "shape:turned",
+        "shape:group",
+        "a2",
+        (100.0, 0.0),
+        json!({ "w": 50.0, "h": 20.0 }),
+    );
+    turned["rotation"] = json!(std::f64::consts::FRAC_PI_2);
+    let import = tldraw(json!([
+        tldraw_page("page:one", "a1"),
+        group,
+        tldraw_geo("shape:inside", "shape:group", "a1", (10.0, 20.0), json!({})),
+        turned,
+        frame,
+    ]));
+
+    assert_eq!(kinds(&import), vec!["frame", "rectangle", "rectangle"]);
+    let [frame, inside, turned] = &import.objects[..] else {
+        panic!("expected three objects");
+    };
+    assert_eq!(frame.props, json!({ "title": "Frame" }));
+    assert_eq!(frame.group_id, None);
+    assert_eq!((inside.x, inside.y), (110.0, 120.0));
+    // Rotated a quarter turn about its top-left corner at (200, 100).
+    assert!(approx(turned.x, 165.0) && approx(turned.y, 115.0) && approx(turned.rotation, 90.0));
+    assert!(inside.group_id.is_some());
+    assert_eq!(inside.group_id, turned.group_id);
+}
+
+#[test]
+fn tldraw_arrows_attach_through_binding_records() {
+    let arrow = json!({
+        "id": "shape:arrow", "typeName": "shape", "type": "arrow", "parentId": "page:one", "index": "a3",
+        "x": 0.0, "y": 0.0, "rotation": 0.0,
+        "props": {
+            "start": { "x": 105.0, "y": 25.0 }, "end": { "x": 300.0, "y": 25.0 },
+            "arrowheadStart": "none", "arrowheadEnd": "arrow", "color": "green", "size": "m",
+            "richText": { "type": "doc", "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "calls" }] }] },
+        },
+    });
+    let binding = |terminal: &str, target: &str, precise: bool| {
+        json!({
+            "id": format!("binding:{terminal}"), "typeName": "binding", "type": "arrow",
+            "fromId": "shape:arrow", "toId": target,
+            "props": { "terminal": terminal, "normalizedAnchor": { "x": 0.0, "y": 0.25 }, "isPrecise": precise },
+        })
+    };
+    let import = tldraw(json!([
+        tldraw_page("page:one", "a1"),
+        tldraw_geo("shape:a", "page:one", "a1", (0.0, 0.0), json!({})),
+        tldraw_geo("shape:b", "page:one", "a2", (300.0, 0.0), json!({})),
+        arrow,
+        binding("start", "shape:a", false),
+        binding("end", "shape:b", true),
+    ]));
+
+    assert_eq!(kinds(&import), vec!["rectangle", "rectangle", "arrow", "text"]);
+    let arrow = &import.objects[2];
+    assert_eq!(attached_to(arrow, "a"), Some((import.objects[0].id, 1.0, 0.5)));
+    assert_eq!(attached_to(arrow, "b"), Some((import.objects[1].id, 0.0, 0.25)));
+    assert_eq!(arrow.props["stroke"], "#099268");
+    let label = &import.objects[3];
+    assert_eq!(label.props["text"], "calls");
+    assert!(approx(label.x + label.width.unwrap_or(0.0) / 2.0, 202.5));
+}
+
+#[test]
+fn tldraw_reads_bindings_stored_on_terminals() {
+    let arrow = json!({
+        "id": "shape:arrow", "typeName": "shape", "type": "arrow", "parentId": "page:one", "index": "a2",
+        "x": 0.0, "y": 0.0, "rotation": 0.0,
+        "props": {
+            "start": { "type": "binding", "boundShapeId": "shape:a", "normalizedAnchor": { "x": 0.5, "y": 0.5 }, "isPrecise": false },
+            "end": { "type": "point", "x": 400.0, "y": 0.0 },
+            "arrowheadStart": "arrow", "arrowheadEnd": "none",
+        },
+    });
+    let import = tldraw(json!([
+        tldraw_page("page:one", "a1"),
+        tldraw_geo("shape:a", "page:one", "a1", (0.0, 0.0), json!({})),
+        arrow,
+    ]));
+
+    // Only the start has a head, so the ends swap.
+    let arrow = &import.objects[1];
+    assert_eq!(arrow.kind, "arrow");
+    assert_eq!(attached_to(arrow, "b"), Some((import.objects[0].id, 0.5, 0.5)));
+    assert_eq!(arrow.props["a"], json!({ "type": "free", "x": 400.0, "y": 0.0 }));
+}
+
+#[test]
+fn tldraw_imports_first_page_notes_text_and_lines() {
+    let note = json!({
+        "id": "shape:note", "typeName": "shape", "type": "note", "parentId": "page:one", "index": "a1",
+        "x": 0.0, "y": 0.0, "rotation": 0.0, "props": { "color": "yellow", "size": "l", "text": "Idea" },
+    });
+    let text = json!({
+        "id": "shape:text", "typeName": "shape", "type": "text", "parentId": "page:one", "index": "a2",
+        "x": 0.0, "y": 300.0, "rotation": 0.0, "props": { "w": 80.0, "text": "Hello", "size": "m", "color": "violet", "scale": 2.0 },
+    });
+    let line = json!({
+        "id": "shape:line", "typeName": "shape", "type": "line", "parentId": "page:one", "index": "a3",
+        "x": 10.0, "y": 10.0, "rotation": 0.0,
+        "props": { "points": { "b": { "id": "b", "index": "a2", "x": 50.0, "y": 0.0 }, "a": { "id": "a", "index": "a1", "x": 0.0, "y": 0.0 } } },
+    });
+    let sketch = json!({
+        "id": "shape:draw", "typeName": "shape", "type": "draw", "parentId": "page:one", "index": "a4",
+        "x": 0.0, "y": 0.0, "rotation": 0.0, "props": {},
+    });
+    let elsewhere = tldraw_geo("shape:elsewhere", "page:two", "a1", (0.0, 0.0), json!({}));
+    let import = tldraw(json!([
+        tldraw_page("page:two", "a2"),
+        tldraw_page("page:one", "a1"),
+        note,
+        text,
+        line,
+        sketch,
+        elsewhere
+    ]));
+
+    assert_eq!(kinds(&import), vec!["sticky_note", "text", "line"]);
+    let [note, text, line] = &import.objects[..] else {
+        panic!("expected three objects");
+    };
+    assert_eq!((note.width, note.height), (Some(200.0), Some(200.0)));
+    assert_eq!(note.props["text"], "Idea");
+    assert_eq!(note.props["fontSize"], 36.0);
+    assert_eq!(note.props["fill"], "#F1AC4B");
+    assert_eq!(text.props, json!({ "text": "Hello", "fontSize": 48.0, "textColor": "#AE3EC9" }));
+    assert_eq!((text.width, text.height), (Some(172.0), Some(72.0)));
+    assert_eq!(line.props["a"], json!({ "type": "free", "x": 10.0, "y": 10.0 }));
+    assert_eq!(line.props["b"], json!({ "type": "free", "x": 60.0, "y": 10.0 }));
+    let reported: Vec<(&str, &str)> = import
+        .unsupported
+        .iter()
+        .map(|item| (item.id.as_str(), item.kind.as_str()))
+        .collect();
+    assert_eq!(reported, vec![("page:two", "page"), ("shape:draw", "draw")]);
+}
@@ -0,0 +1,444 @@
+//! tldraw (`.tldr`) import.
+//!
+//! A `.tldr` file is a flat list of store records. Shapes are positioned in
+//! their parent's space (the page, a frame, or a group) and rotated about
+//! their top-left corner, so the importer walks the shape tree from the first
+//! page, composing placements and painting siblings in `index` order.
+//!
+//! `geo` shapes import as rectangles, ellipses, diamonds, and stars; text,
+//! notes, frames, arrows, and lines map to their board kinds. Groups become
+//! board groups. Arrow bindings come from `binding` records, or from the
+//! terminals themselves in files written before bindings were records.
+//! Colors are tldraw's named palette, resolved to its light-theme values.
+
+use std::collections::HashMap;
+
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use super::{Geometry, Import, ImportBuilder, ImportError, Terminal, text_geometry, text_height};
+
+/// Size of a note at scale 1.
+const NOTE_SIZE: f64 = 200.0;
+/// Width given to arrow labels, which tldraw sizes to fit.
+const ARROW_LABEL_WIDTH: f64 = 160.0;
+
+/// Convert a tldraw file into board objects for `board_id`.
+///
+/// Only the first page is imported; any others are reported unsupported.
+///
+/// # Errors
+///
+/// Returns `InvalidFile` when the document has neither a `records` array nor
+/// a `store` map.
+pub fn import_tldraw(file: &Value, board_id: Uuid, user_id: Uuid) -> Result<Import, ImportError> {
+    let records: Vec<&Value> = if let Some(records) = file.get("records").and_then(Value::as_array) {
+        records.iter().collect()
+    } else if let Some(store) = file.get("store").and_then(Value::as_object) {
+        store.values().collect()
+    } else {
+        return Err(ImportError::InvalidFile("tldraw"));
+    };
+
+    let mut builder = ImportBuilder::new(board_id, user_id);
+    let mut pages: Vec<&Value> = records
+        .iter()
+        .copied()
+        .filter(|record| record["typeName"] == "page")
+        .collect();
+    pages.sort_by(|a, b| by_index(a, b));
+    for page in pages.iter().skip(1) {
+        builder.unsupported(page["id"].as_str().unwrap_or(""), "page");
+    }
+
+    let mut children: HashMap<&str, Vec<&Value>> = HashMap::new();
+    for record in records
+        .iter()
+        .filter(|record| record["typeName"] == "shape")
+    {
+        if let Some(parent) = record["parentId"].as_str() {
+            children.entry(parent).or_default().push(record);
+        }
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by(|a, b| by_index(a, b));
+    }
+
+    let mut arrow_bindings = HashMap::new();
+    for record in &records {
+        if record["typeName"] != "binding" || record["type"] != "arrow" {
+            continue;
+        }
+        let (Some(arrow), Some(terminal), Some(target)) = (
+            record["fromId"].as_str(),
+            record["props"]["terminal"].as_str(),
+            record["toId"].as_str(),
+        ) else {
+            continue;
+        };
+        arrow_bindings.insert((arrow, terminal), Binding::from_props(target, &record["props"]));
+    }
+
+    let page = pages
+        .first()
+        .and_then(|page| page["id"].as_str())
+        .or_else(|| {
+            children
+                .keys()
+                .copied()
+                .filter(|parent| parent.starts_with("page:"))
+                .min()
+        });
+    if let Some(page) = page {
+        let mut walker = Walker { builder: &mut builder, children: &children, arrow_bindings: &arrow_bindings };
+        walker.walk(page, Placement::default(), None);
+    }
+    Ok(builder.finish())
+}
+
+// =============================================================================
+// TREE WALK
+// =============================================================================
+
+/// A shape's page-space origin and rotation in radians.
+#[derive(Debug, Clone, Copy, Default)]
+struct Placement {
+    origin: (f64, f64),
+    angle: f64,
+}
+
+impl Placement {
+    /// Page position of a point in this placement's space.
+    fn apply(self, (x, y): (f64, f64)) -> (f64, f64) {
+        let (sin, cos) = self.angle.sin_cos();
+        (self.origin.0 + x * cos - y * sin, self.origin.1 + x * sin + y * cos)
+    }
+
+    /// Placement of a shape at `(x, y)` rotated by `rotation` in this space.
+    fn child(self, shape: &Value) -> Self {
+        let local = (shape["x"].as_f64().unwrap_or(0.0), shape["y"].as_f64().unwrap_or(0.0));
+        Self { origin: self.apply(local), angle: self.angle + shape["rotation"].as_f64().unwrap_or(0.0) }
+    }
+
+    /// Board geometry of a `width` × `height` box at this placement. Board
+    /// shapes rotate about their center, so the box is placed by where its
+    /// center lands.
+    fn geometry(self, width: f64, height: f64) -> Geometry {
+        let (cx, cy) = self.apply((width / 2.0, height / 2.0));
+        Geometry { x: cx - width / 2.0, y: cy - height / 2.0, width, height, rotation: self.angle.to_degrees() }
+    }
+}
+
+/// Where an arrow terminal is bound.
+#[derive(Debug, Clone, Copy)]
+struct Binding<'a> {
+    target: &'a str,
+    /// Exact anchor inside the target; `None` when tldraw aims at the
+    /// target's center and the terminal position should be used instead.
+    anchor: Option<(f64, f64)>,
+}
+
+impl<'a> Binding<'a> {
+    fn from_props(target: &'a str, props: &Value) -> Self {
+        let anchor = props["isPrecise"]
+            .as_bool()
+            .unwrap_or(false)
+            .then(|| {
+                Some((
+                    props["normalizedAnchor"]["x"].as_f64()?,
+                    props["normalizedAnchor"]["y"].as_f64()?,
+                ))
+            })
+            .flatten();
+        Self { target, anchor }
+    }
+}
+
+struct Walker<'w, 'a> {
+    builder: &'w mut ImportBuilder,
+    children: &'w HashMap<&'a str, Vec<&'a Value>>,
+    arrow_bindings: &'w HashMap<(&'a str, &'a str), Binding<'a>>,
+}
+
+impl Walker<'_, '_> {
+    /// Import the children of `parent`, then their children, in paint order.
+    fn walk(&mut self, parent: &str, placement: Placement, group: Option<&str>) {
+        let Some(shapes) = self.children.get(parent) else {
+            return;
+        };
+        for &shape in shapes {
+            let id = shape["id"].as_str().unwrap_or("");
+            let placement = placement.child(shape);
+            let group = if shape["type"] == "group" {
+                group.or(Some(id))
+            } else {
+                self.shape(shape, placement, group);
+                group
+            };
+            self.walk(id, placement, group);
+        }
+    }
+
+    fn shape(&mut self, shape: &Value, placement: Placement, group: Option<&str>) {
+        let id = shape["id"].as_str().unwrap_or("");
+        let kind = shape["type"].as_str().unwrap_or("");
+        let props = &shape["props"];
+        let scale = props["scale"].as_f64().unwrap_or(1.0);
+        let width = props["w"].as_f64().unwrap_or(0.0);
+        let height = props["h"].as_f64().unwrap_or(0.0);
+        match kind {
+            "geo" => {
+                let geo = props["geo"].as_str().unwrap_or("rectangle");
+                let Some(board_kind) = geo_kind(geo) else {
+                    self.builder.unsupported(id, &format!("geo:{geo}"));
+                    return;
+                };
+                let color = color(props["color"].as_str());
+                let mut board_props = json!({
+                    "fill": fill(color, props["fill"].as_str()),
+                    "stroke": color.solid,
+                    "strokeWidth": stroke_width(props) * scale,
+                });
+                let label = text_of(props);
+                if !label.is_empty() {
+                    board_props["text"] = json!(label);
+                    board_props["fontSize"] = json!(font_size(props) * scale);
+                    board_props["textColor"] = json!(self::color(props["labelColor"].as_str()).solid);
+                }
+                self.builder
+                    .shape(id, board_kind, placement.geometry(width, height), board_props, group);
+            }
+            "text" => {
+                let text = text_of(props);
+                let font_size = font_size(props) * scale;
+                let geometry = placement.geometry(width * scale, text_height(&text, font_size));
+                let board_props = json!({
+                    "text": text,
+                    "fontSize": font_size,
+                    "textColor": color(props["color"].as_str()).solid,
+                });
+                self.builder
+                    .shape(id, "text", text_geometry(geometry), board_props, group);
+            }
+            "note" => {
+                let color = color(props["color"].as_str());
+                let board_props = json!({
+                    "text": text_of(props),
+                    "fontSize": font_size(props) * scale,
+                    "fill": color.solid,
+                    "stroke": color.solid,
+                    "strokeWidth": 0.0,
+                });
+                let size = NOTE_SIZE * scale;
+                self.builder
+                    .shape(id, "sticky_note", placement.geometry(size, size), board_props, group);
+            }
+            "frame" => {
+                let title = props["name"]
+                    .as_str()
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or("Frame");
+                self.builder
+                    .shape(id, "frame", placement.geometry(width, height), json!({ "title": title }), group);
+            }
+            "arrow" => self.arrow(shape, placement, group),
+            "line" => self.line(shape, placement, group),
+            _ => self.builder.unsupported(id, kind),
+        }
+    }
+
+    fn arrow(&mut self, shape: &Value, placement: Placement, group: Option<&str>) {
+        let id = shape["id"].as_str().unwrap_or("");
+        let props = &shape["props"];
+        let terminal = |name: &str| {
+            let raw = &props[name];
+            let point = match (raw["x"].as_f64(), raw["y"].as_f64()) {
+                (Some(x), Some(y)) => Some(placement.apply((x, y))),
+                _ => None,
+            };
+            // Files written before bindings were records keep them on the terminal.
+            let binding = self.arrow_bindings.get(&(id, name)).copied().or_else(|| {
+                (raw["type"] == "binding")
+                    .then(|| raw["boundShapeId"].as_str())
+                    .flatten()
+                    .map(|target| Binding::from_props(target, raw))
+            });
+            match (point, binding) {
+                (Some(point), Some(binding)) => Terminal::bound(point, Some(binding.target), binding.anchor),
+                (None, Some(binding)) => {
+                    Terminal::bound(placement.origin, Some(binding.target), binding.anchor.or(Some((0.5, 0.5))))
+                }
+                (point, None) => Terminal::free(point.unwrap_or(placement.origin)),
+            }
+        };
+        let (start, end) = (terminal("start"), terminal("end"));
+
+        let has_head = |key: &str| props[key].as_str().is_some_and(|head| head != "none");
+        let (kind, terminals) = if has_head("arrowheadEnd") {
+            ("arrow", [start, end])
+        } else if has_head("arrowheadStart") {
+            ("arrow", [end, start])
+        } else {
+            ("line", [start, end])
+        };
+        let color = color(props["color"].as_str());
+        let scale = props["scale"].as_f64().unwrap_or(1.0);
+        let board_props = json!({ "stroke": color.solid, "strokeWidth": stroke_width(props) * scale });
+        self.builder
+            .connector(id, kind, terminals, board_props, group);
+
+        // Labels become loose text on the connector's midpoint, the way the
+        // board places connector labels.
+        let label = text_of(props);
+        if !label.is_empty() {
+            let font_size = font_size(props) * scale;
+            let height = text_height(&label, font_size);
+            let (mx, my) = (
+                f64::midpoint(start.point.0, end.point.0),
+                f64::midpoint(start.point.1, end.point.1),
+            );
+            let geometry = Geometry {
+                x: mx - ARROW_LABEL_WIDTH / 2.0,
+                y: my - height / 2.0,
+                width: ARROW_LABEL_WIDTH,
+                height,
+                rotation: 0.0,
+            };
+            let text_props = json!({ "text": label, "fontSize": font_size, "textColor": color.solid });
+            self.builder
+                .shape(&format!("{id}:label"), "text", text_geometry(geometry), text_props, group);
+        }
+    }
+
+    /// Lines become a straight connector from their first to last point.
+    fn line(&mut self, shape: &Value, placement: Placement, group: Option<&str>) {
+        let id = shape["id"].as_str().unwrap_or("");
+        let props = &shape["props"];
+        let mut points: Vec<&Value> = match &props["points"] {
+            Value::Object(points) => points.values().collect(),
+            Value::Array(points) => points.iter().collect(),
+            _ => Vec::new(),
+        };
+        points.sort_by(|a, b| by_index(a, b));
+        let points: Vec<(f64, f64)> = points
+            .iter()
+            .filter_map(|point| Some(placement.apply((point["x"].as_f64()?, point["y"].as_f64()?))))
+            .collect();
+        let (Some(&first), Some(&last)) = (points.first(), points.last()) else {
+            self.builder.unsupported(id, "line");
+            return;
+        };
+        let color = color(props["color"].as_str());
+        let scale = props["scale"].as_f64().unwrap_or(1.0);
+        let board_props = json!({ "stroke": color.solid, "strokeWidth": stroke_width(props) * scale });
+        self.builder
+            .connector(id, "line", [Terminal::free(first), Terminal::free(last)], board_props, group);
+    }
+}
+
+// =============================================================================
+// PROPS
+// =============================================================================
+
+fn geo_kind(geo: &str) -> Option<&'static str> {
+    match geo {
+        "rectangle" => Some("rectangle"),
+        "ellipse" => Some("ellipse"),
+        "diamond" => Some("diamond"),
+        "star" => Some("star"),
+        _ => None,
+    }
+}
+
+/// Records sort by their fractional `index` string.
+fn by_index(a: &Value, b: &Value) -> std::cmp::Ordering {
+    a["index"]
+        .as_str()
+        .unwrap_or("")
+        .cmp(b["index"].as_str().unwrap_or(""))
+}
+
+/// A palette color: the stroke value and the pale tint used by semi fills.
+#[derive(Debug, Clone, Copy)]
+struct Color {
+    solid: &'static str,
+    semi: &'static str,
+}
+
+fn color(name: Option<&str>) -> Color {
+    let (solid, semi) = match name.unwrap_or("black") {
+        "blue" => ("#4465E9", "#DCE1F8"),
+        "green" => ("#099268", "#D3E9E3"),
+        "grey" => ("#9FA8B2", "#ECEEF0"),
+        "light-blue" => ("#4BA1F1", "#DDEDFA"),
+        "light-green" => ("#4CB05E", "#DBF0E0"),
+        "light-red" => ("#F87777", "#F4DADB"),
+        "light-violet" => ("#E085F4", "#F5EAFA"),
+        "orange" => ("#E16919", "#F8E2D4"),
+        "red" => ("#E03131", "#F4DADB"),
+        "violet" => ("#AE3EC9", "#ECDCF2"),
+        "yellow" => ("#F1AC4B", "#F9F0E6"),
+        "white" => ("#FFFFFF", "#F5F5F5"),
+        _ => ("#1D1D1D", "#E8E8E8"),
+    };
+    Color { solid, semi }
+}
+
+fn fill(color: Color, style: Option<&str>) -> &'static str {
+    match style.unwrap_or("none") {
+        "solid" | "fill" => color.solid,
+        "semi" | "pattern" => color.semi,
+        _ => "transparent",
+    }
+}
+
+fn font_size(props: &Value) -> f64 {
+    match props["size"].as_str() {
+        Some("s") => 18.0,
+        Some("l") => 36.0,
+        Some("xl") => 44.0,
+        _ => 24.0,
+    }
+}
+
+fn stroke_width(props: &Value) -> f64 {
+    match props["size"].as_str() {
+        Some("s") => 2.0,
+        Some("l") => 5.0,
+        Some("xl") => 10.0,
+        _ => 3.5,
+    }
+}
+
+/// Plain text of a shape: `text` in older files, `richText` documents in
+/// newer ones with one line per block.
+fn text_of(props: &Value) -> String {
+    if let Some(text) = props["text"].as_str() {
+        return text.to_owned();
+    }
+    let mut lines = Vec::new();
+    rich_text_lines(&props["richText"], &mut lines);
+    lines.join("\n")
+}
+
+fn rich_text_lines(node: &Value, lines: &mut Vec<String>) {
+    let content = node["content"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let is_inline = |child: &Value| matches!(child["type"].as_str(), Some("text" | "hardBreak"));
+    if matches!(node["type"].as_str(), Some("paragraph" | "heading")) || content.iter().any(is_inline) {
+        let line: String = content
+            .iter()
+            .map(|child| match child["type"].as_str() {
+                Some("hardBreak") => "\n",
+                _ => child["text"].as_str().unwrap_or(""),
+            })
+            .collect();
+        lines.push(line);
+    } else {
+        for child in content {
+            rich_text_lines(child, lines);
+        }
+    }
+}
@@ -1,10 +1,19 @@
 //! Anthropic Messages API client.
 //!
 //! Ported from Prior's `kernel/src/llm/client.rs`. Thin HTTP wrapper for
-//! `/v1/messages`. Pure parsing in `parse_response` for testability.
+//! `/v1/messages`. Pure parsing in `parse_response` and `StreamState` for
+//! testability.
+//!
+//! Requests carry two prompt-cache breakpoints: one on the last tool
+//! definition and one on the static part of the system prompt (everything
+//! before [`SYSTEM_CONTEXT_HEADER`]). The per-request board context follows
+//! as an uncached system block.
 
 use super::config::LlmTimeouts;
-use super::types::{ChatResponse, ContentBlock, LlmError, Message, Tool};
+use super::failover::retry_after_secs;
+use super::sse::{SseEvent, read_events};
+use super::types::{ChatDelta, ChatResponse, ContentBlock, DeltaSink, LlmError, Message, SYSTEM_CONTEXT_HEADER, Tool};
+use serde_json::Value;
 use std::time::Duration;
 
 const API_URL: &str = "https://api.anthropic.com/v1/messages";
@@ -37,29 +46,53 @@ impl AnthropicClient {
         messages: &[Message],
         tools: Option<&[Tool]>,
     ) -> Result<ChatResponse, LlmError> {
-        let body = ApiRequest { model, max_tokens, system, messages, tools };
+        let body = ApiRequest::new(model, max_tokens, system, messages, tools, false);
+        let response = self.send(&body).await?;
+        let text = response
+            .text()
+            .await
+            .map_err(|e| LlmError::ApiRequest(e.to_string()))?;
+        parse_response(&text)
+    }
+
+    /// Stream a chat request, sending text deltas as they arrive.
+    pub async fn chat_stream(
+        &self,
+        model: &str,
+        max_tokens: u32,
+        system: &str,
+        messages: &[Message],
+        tools: Option<&[Tool]>,
+        deltas: &DeltaSink,
+    ) -> Result<ChatResponse, LlmError> {
+        let body = ApiRequest::new(model, max_tokens, system, messages, tools, true);
+        let response = self.send(&body).await?;
+        let mut stream = StreamState::default();
+        read_events(response, |event| stream.apply(&event, deltas)).await?;
+        stream.finish()
+    }
 
+    async fn send(&self, body: &ApiRequest<'_>) -> Result<reqwest::Response, LlmError> {
         let response = self
             .http
             .post(API_URL)
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", API_VERSION)
-            .json(&body)
+            .json(body)
             .send()
             .await
             .map_err(|e| LlmError::ApiRequest(e.to_string()))?;
 
         let status = response.status().as_u16();
-        let text = response
-            .text()
-            .await
-            .map_err(|e| LlmError::ApiRequest(e.to_string()))?;
-
         if status != 200 {
-            return Err(LlmError::ApiResponse { status, body: text });
+            let retry_after_secs = retry_after_secs(response.headers());
+            let text = response
+                .text()
+                .await
+                .map_err(|e| LlmError::ApiRequest(e.to_string()))?;
+            return Err(LlmError::ApiResponse { status, body: text, retry_after_secs });
         }
-
-        parse_response(&text)
+        Ok(response)
     }
 }
 
@@ -71,10 +104,69 @@ impl AnthropicClient {
 struct ApiRequest<'a> {
     model: &'a str,
     max_tokens: u32,
-    system: &'a str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    system: Vec<SystemBlock<'a>>,
     messages: &'a [Message],
     #[serde(skip_serializing_if = "Option::is_none")]
-    tools: Option<&'a [Tool]>,
+    tools: Option<Vec<ApiTool<'a>>>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+impl<'a> ApiRequest<'a> {
+    fn new(
+        model: &'a str,
+        max_tokens: u32,
+        system: &'a str,
+        messages: &'a [Message],
+        tools: Option<&'a [Tool]>,
+        stream: bool,
+    ) -> Self {
+        let (stable, context) = system
+            .find(SYSTEM_CONTEXT_HEADER)
+            .map_or((system, ""), |at| system.split_at(at));
+        let system = [(stable, Some(CacheControl::EPHEMERAL)), (context, None)]
+            .into_iter()
+            .filter(|(text, _)| !text.is_empty())
+            .map(|(text, cache_control)| SystemBlock { kind: "text", text, cache_control })
+            .collect();
+        let tools = tools.map(|tools| {
+            let last = tools.len().saturating_sub(1);
+            tools
+                .iter()
+                .enumerate()
+                .map(|(i, tool)| ApiTool { tool, cache_control: (i == last).then_some(CacheControl::EPHEMERAL) })
+                .collect()
+        });
+        Self { model, max_tokens, system, messages, tools, stream }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct SystemBlock<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    text: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheControl>,
+}
+
+#[derive(serde::Serialize)]
+struct ApiTool<'a> {
+    #[serde(flatten)]
+    tool: &'a Tool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheControl>,
+}
+
+#[derive(Clone, Copy, serde::Serialize)]
+struct CacheControl {
+    #[serde(rename = "type")]
+    kind: &'static str,
+}
+
+impl CacheControl {
+    const EPHEMERAL: Self = Self { kind: "ephemeral" };
 }
 
 #[derive(serde::Deserialize)]
@@ -86,9 +178,14 @@ struct ApiResponse {
 }
 
 #[derive(serde::Deserialize)]
+#[allow(clippy::struct_field_names)]
 struct Usage {
     input_tokens: u64,
     output_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: Option<u64>,
+    #[serde(default)]
+    cache_creation_input_tokens: Option<u64>,
 }
 
 // =============================================================================
@@ -110,9 +207,156 @@ pub(crate) fn parse_response(json: &str) -> Result<ChatResponse, LlmError> {
         stop_reason: api.stop_reason,
         input_tokens: api.usage.input_tokens,
         output_tokens: api.usage.output_tokens,
+        cache_read_tokens: api.usage.cache_read_input_tokens.unwrap_or(0),
+        cache_creation_tokens: api.usage.cache_creation_input_tokens.unwrap_or(0),
     })
 }
 
+// =============================================================================
+// STREAMING
+// =============================================================================
+
+/// A content block being assembled from stream events.
+enum PartialBlock {
+    Text(String),
+    ToolUse { id: String, name: String, json: String },
+    Skipped,
+}
+
+/// Accumulates Messages API stream events into a [`ChatResponse`].
+#[derive(Default)]
+pub(crate) struct StreamState {
+    blocks: Vec<PartialBlock>,
+    model: String,
+    stop_reason: Option<String>,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_read_tokens: u64,
+    cache_creation_tokens: u64,
+    stopped: bool,
+}
+
+impl StreamState {
+    /// Apply one event, forwarding text deltas to `deltas`.
+    pub(crate) fn apply(&mut self, event: &SseEvent, deltas: &DeltaSink) -> Result<(), LlmError> {
+        let data: Value = serde_json::from_str(&event.data).map_err(|e| LlmError::ApiParse(e.to_string()))?;
+        let kind = data.get("type").and_then(Value::as_str).unwrap_or_default();
+        match kind {
+            "message_start" => {
+                let message = data.get("message").unwrap_or(&Value::Null);
+                message
+                    .get("model")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .clone_into(&mut self.model);
+                self.input_tokens = usage(message, "input_tokens").unwrap_or(0);
+                self.output_tokens = usage(message, "output_tokens").unwrap_or(0);
+                self.cache_read_tokens = usage(message, "cache_read_input_tokens").unwrap_or(0);
+                self.cache_creation_tokens = usage(message, "cache_creation_input_tokens").unwrap_or(0);
+            }
+            "content_block_start" => {
+                let block = data.get("content_block").unwrap_or(&Value::Null);
+                let text = |key: &str| {
+                    block
+                        .get(key)
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_owned()
+                };
+                let partial = match block.get("type").and_then(Value::as_str) {
+                    Some("text") => PartialBlock::Text(text("text")),
+                    Some("tool_use") => {
+                        PartialBlock::ToolUse { id: text("id"), name: text("name"), json: String::new() }
+                    }
+                    _ => PartialBlock::Skipped,
+                };
+                self.blocks.push(partial);
+            }
+            "content_block_delta" => {
+                let delta = data.get("delta").unwrap_or(&Value::Null);
+                let fragment = |key: &str| delta.get(key).and_then(Value::as_str).unwrap_or_default();
+                let index = data
+                    .get("index")
+                    .and_then(Value::as_u64)
+                    .and_then(|i| usize::try_from(i).ok());
+                let target = match index {
+                    Some(i) => self.blocks.get_mut(i),
+                    None => self.blocks.last_mut(),
+                };
+                match target {
+                    Some(PartialBlock::Text(text)) => {
+                        let piece = fragment("text");
+                        if !piece.is_empty() {
+                            text.push_str(piece);
+                            let _ = deltas.send(ChatDelta::Text(piece.to_owned()));
+                        }
+                    }
+                    Some(PartialBlock::ToolUse { json, .. }) => json.push_str(fragment("partial_json")),
+                    Some(PartialBlock::Skipped) | None => {}
+                }
+            }
+            "message_delta" => {
+                if let Some(reason) = data
+                    .get("delta")
+                    .and_then(|d| d.get("stop_reason"))
+                    .and_then(Value::as_str)
+                {
+                    self.stop_reason = Some(reason.to_owned());
+                }
+                if let Some(tokens) = usage(&data, "output_tokens") {
+                    self.output_tokens = tokens;
+                }
+            }
+            "message_stop" => self.stopped = true,
+            "error" => {
+                let message = data
+                    .get("error")
+                    .and_then(|e| e.get("message"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("stream error");
+                return Err(LlmError::ApiStream(message.to_owned()));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Finish the stream and return the assembled response.
+    pub(crate) fn finish(self) -> Result<ChatResponse, LlmError> {
+        if !self.stopped {
+            return Err(LlmError::ApiStream("stream ended before message_stop".to_owned()));
+        }
+        let mut content = Vec::with_capacity(self.blocks.len());
+        for block in self.blocks {
+            match block {
+                PartialBlock::Text(text) => content.push(ContentBlock::Text { text }),
+                PartialBlock::ToolUse { id, name, json } => {
+                    let input = if json.trim().is_empty() {
+                        Value::Object(serde_json::Map::default())
+                    } else {
+                        serde_json::from_str(&json).map_err(|e| LlmError::ApiParse(e.to_string()))?
+                    };
+                    content.push(ContentBlock::ToolUse { id, name, input });
+                }
+                PartialBlock::Skipped => {}
+            }
+        }
+        Ok(ChatResponse {
+            content,
+            model: self.model,
+            stop_reason: self.stop_reason.unwrap_or_else(|| "end_turn".to_owned()),
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            cache_read_tokens: self.cache_read_tokens,
+            cache_creation_tokens: self.cache_creation_tokens,
+        })
+    }
+}
+
+fn usage(value: &Value, key: &str) -> Option<u64> {
+    value.get("usage")?.get(key)?.as_u64()
+}
+
 #[cfg(test)]
 #[path = "anthropic_test.rs"]
 mod tests;
@@ -91,3 +91,132 @@ fn parse_thinking_only_response_is_empty() {
     // If only thinking blocks, content should be empty after filtering.
     assert!(resp.content.is_empty());
 }
+
+fn stream_events(state: &mut StreamState, events: &[serde_json::Value], deltas: &DeltaSink) {
+    for event in events {
+        let event = SseEvent { event: None, data: event.to_string() };
+        state.apply(&event, deltas).unwrap();
+    }
+}
+
+#[test]
+fn stream_forwards_text_deltas_and_assembles_tool_input() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut state = StreamState::default();
+    stream_events(
+        &mut state,
+        &[
+            serde_json::json!({ "type": "message_start", "message": { "model": "claude-test", "usage": { "input_tokens": 12, "output_tokens": 1 } } }),
+            serde_json::json!({ "type": "content_block_start", "index": 0, "content_block": { "type": "text", "text": "" } }),
+            serde_json::json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "text_delta", "text": "Hel" } }),
+            serde_json::json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "text_delta", "text": "lo" } }),
+            serde_json::json!({ "type": "content_block_stop", "index": 0 }),
+            serde_json::json!({ "type": "content_block_start", "index": 1, "content_block": { "type": "tool_use", "id": "tu_1", "name": "createStickyNote", "input": {} } }),
+            serde_json::json!({ "type": "content_block_delta", "index": 1, "delta": { "type": "input_json_delta", "partial_json": "{\"text\": \"hi\"" } }),
+            serde_json::json!({ "type": "content_block_delta", "index": 1, "delta": { "type": "input_json_delta", "partial_json": ", \"x\": 4}" } }),
+            serde_json::json!({ "type": "content_block_stop", "index": 1 }),
+            serde_json::json!({ "type": "message_delta", "delta": { "stop_reason": "tool_use" }, "usage": { "output_tokens": 30 } }),
+            serde_json::json!({ "type": "message_stop" }),
+        ],
+        &tx,
+    );
+
+    assert_eq!(rx.try_recv().unwrap(), ChatDelta::Text("Hel".into()));
+    assert_eq!(rx.try_recv().unwrap(), ChatDelta::Text("lo".into()));
+    assert!(rx.try_recv().is_err());
+
+    let resp = state.finish().unwrap();
+    assert_eq!(resp.model, "claude-test");
+    assert_eq!(resp.stop_reason, "tool_use");
+    assert_eq!((resp.input_tokens, resp.output_tokens), (12, 30));
+    assert!(matches!(&resp.content[0], ContentBlock::Text { text } if text == "Hello"));
+    assert!(matches!(
+        &resp.content[1],
+        ContentBlock::ToolUse { id, input, .. } if id == "tu_1" && input["x"] == 4 && input["text"] == "hi"
+    ));
+}
+
+#[test]
+fn stream_error_event_and_truncated_stream_fail() {
+    let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut state = StreamState::default();
+    let error = SseEvent {
+        event: Some("error".into()),
+        data: serde_json::json!({ "type": "error", "error": { "type": "overloaded_error", "message": "Overloaded" } })
+            .to_string(),
+    };
+    assert!(matches!(state.apply(&error, &tx), Err(LlmError::ApiStream(msg)) if msg == "Overloaded"));
+    assert!(matches!(StreamState::default().finish(), Err(LlmError::ApiStream(_))));
+}
+
+#[test]
+fn parse_cache_usage() {
+    let json = serde_json::json!({
+        "content": [{ "type": "text", "text": "hi" }],
+        "model": "claude-test",
+        "stop_reason": "end_turn",
+        "usage": {
+            "input_tokens": 20,
+            "output_tokens": 5,
+            "cache_read_input_tokens": 3000,
+            "cache_creation_input_tokens": 0
+        }
+    })
+    .to_string();
+    let resp = parse_response(&json).unwrap();
+    assert_eq!((resp.cache_read_tokens, resp.cache_creation_tokens), (3000, 0));
+    assert_eq!(resp.total_tokens(), 3025);
+}
+
+#[test]
+fn stream_reads_cache_usage_from_message_start() {
+    let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut state = StreamState::default();
+    stream_events(
+        &mut state,
+        &[
+            serde_json::json!({ "type": "message_start", "message": { "model": "claude-test", "usage": { "input_tokens": 12, "output_tokens": 1, "cache_read_input_tokens": 0, "cache_creation_input_tokens": 2048 } } }),
+            serde_json::json!({ "type": "message_delta", "delta": { "stop_reason": "end_turn" }, "usage": { "output_tokens": 4 } }),
+            serde_json::json!({ "type": "message_stop" }),
+        ],
+        &tx,
+    );
+    let resp = state.finish().unwrap();
+    assert_eq!((resp.cache_read_tokens, resp.cache_creation_tokens), (0, 2048));
+    assert_eq!(resp.total_input_tokens(), 2060);
+}
+
+fn tool(name: &str) -> Tool {
+    Tool { name: name.into(), description: String::new(), input_schema: serde_json::json!({ "type": "object" }) }
+}
+
+#[test]
+fn request_marks_stable_system_prompt_and_last_tool_for_caching() {
+    let system = format!("You are a board assistant.{SYSTEM_CONTEXT_HEADER}- total_objects=0\n");
+    let tools = [tool("createStickyNote"), tool("moveObject")];
+    let body = ApiRequest::new("claude-test", 1024, &system, &[], Some(&tools), false);
+    let json = serde_json::to_value(&body).unwrap();
+
+    assert_eq!(
+        json["system"],
+        serde_json::json!([
+            { "type": "text", "text": "You are a board assistant.", "cache_control": { "type": "ephemeral" } },
+            { "type": "text", "text": format!("{SYSTEM_CONTEXT_HEADER}- total_objects=0\n") },
+        ])
+    );
+    assert!(json["tools"][0].get("cache_control").is_none());
+    assert_eq!(json["tools"][1]["name"], "moveObject");
+    assert_eq!(json["tools"][1]["cache_control"]["type"], "ephemeral");
+}
+
+#[test]
+fn request_without_context_header_caches_whole_system_prompt() {
+    let body = ApiRequest::new("claude-test", 1024, "Be brief.", &[], None, false);
+    let json = serde_json::to_value(&body).unwrap();
+    assert_eq!(json["system"].as_array().unwrap().len(), 1);
+    assert_eq!(json["system"][0]["cache_control"]["type"], "ephemeral");
+    assert!(json.get("tools").is_none());
+
+    let empty = serde_json::to_value(ApiRequest::new("claude-test", 1024, "", &[], None, false)).unwrap();
+    assert!(empty.get("system").is_none());
+}
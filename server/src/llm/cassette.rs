@@ -0,0 +1,305 @@
+//! Record/replay cassettes for deterministic LLM tests.
+//!
+//! DESIGN
+//! ======
+//! `LLM_PROVIDER=record` sends each request to the real provider and appends
+//! the exchange to a JSONL cassette; `LLM_PROVIDER=replay` answers from that
+//! file with no network access. Entries are keyed by a SHA-256 of the
+//! normalized request (system prompt, messages, and tools; model and token
+//! limits are left out so a cassette survives a model bump).
+//!
+//! Object IDs differ on every run, so normalization replaces each UUID with
+//! a placeholder numbered by first appearance. UUIDs in the recorded
+//! response that also appeared in the request get the same placeholders, and
+//! replay maps them back to the IDs of the live request. A recorded
+//! `moveObject` on the sticky created a step earlier therefore targets the
+//! sticky created in this run.
+//!
+//! Identical requests recorded more than once are replayed in recorded order;
+//! once exhausted, the last response repeats.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::types::{ChatDelta, ChatResponse, ContentBlock, DeltaSink, LlmChat, LlmError, Message, Tool};
+
+/// Whether a cassette is being written or served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+/// One line of a cassette file.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct CassetteEntry {
+    key: String,
+    request: Value,
+    response: Value,
+}
+
+/// A request rewritten with UUID placeholders, plus the UUIDs they stand for.
+struct NormalizedRequest {
+    key: String,
+    body: Value,
+    uuids: Vec<String>,
+}
+
+// =============================================================================
+// CLIENT
+// =============================================================================
+
+/// `LlmChat` that records to or replays from a cassette file.
+pub struct CassetteChat {
+    mode: CassetteMode,
+    path: PathBuf,
+    upstream: Option<Arc<dyn LlmChat>>,
+    entries: Mutex<HashMap<String, Vec<Value>>>,
+    cursors: Mutex<HashMap<String, usize>>,
+}
+
+impl CassetteChat {
+    /// Serve responses from the cassette at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LlmError::Cassette`] if the file cannot be read or parsed.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, LlmError> {
+        let path = path.into();
+        let entries = load_entries(&path)?;
+        Ok(Self {
+            mode: CassetteMode::Replay,
+            path,
+            upstream: None,
+            entries: Mutex::new(entries),
+            cursors: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Forward requests to `upstream` and append each exchange to `path`.
+    #[must_use]
+    pub fn record(path: impl Into<PathBuf>, upstream: Arc<dyn LlmChat>) -> Self {
+        Self {
+            mode: CassetteMode::Record,
+            path: path.into(),
+            upstream: Some(upstream),
+            entries: Mutex::new(HashMap::new()),
+            cursors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Mode this cassette was opened in.
+    #[must_use]
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    fn lookup(&self, request: &NormalizedRequest) -> Result<ChatResponse, LlmError> {
+        let entries = self.entries.lock().map_err(|_| poisoned())?;
+        let Some(responses) = entries.get(&request.key).filter(|r| !r.is_empty()) else {
+            return Err(LlmError::Cassette(format!(
+                "no recorded response for request {} in {}",
+                request.key,
+                self.path.display()
+            )));
+        };
+        let mut cursors = self.cursors.lock().map_err(|_| poisoned())?;
+        let cursor = cursors.entry(request.key.clone()).or_default();
+        let response = &responses[(*cursor).min(responses.len() - 1)];
+        *cursor += 1;
+
+        let text = denormalize(&response.to_string(), &request.uuids);
+        serde_json::from_str(&text).map_err(|e| LlmError::Cassette(format!("bad recorded response: {e}")))
+    }
+
+    fn store(&self, request: NormalizedRequest, response: &ChatResponse) -> Result<(), LlmError> {
+        let raw = serde_json::to_string(response).map_err(|e| LlmError::Cassette(e.to_string()))?;
+        let normalized = replace_known_uuids(&raw, &request.uuids);
+        let response: Value = serde_json::from_str(&normalized).map_err(|e| LlmError::Cassette(e.to_string()))?;
+        let entry = CassetteEntry { key: request.key, request: request.body, response };
+
+        let mut line = serde_json::to_string(&entry).map_err(|e| LlmError::Cassette(e.to_string()))?;
+        line.push('\n');
+        let mut entries = self.entries.lock().map_err(|_| poisoned())?;
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| LlmError::Cassette(e.to_string()))?;
+        }
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| LlmError::Cassette(format!("write {}: {e}", self.path.display())))?;
+        entries.entry(entry.key).or_default().push(entry.response);
+        Ok(())
+    }
+
+    fn upstream(&self) -> Result<&Arc<dyn LlmChat>, LlmError> {
+        self.upstream
+            .as_ref()
+            .ok_or_else(|| LlmError::Cassette("record mode has no upstream provider".to_owned()))
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmChat for CassetteChat {
+    async fn chat(
+        &self,
+        max_tokens: u32,
+        system: &str,
+        messages: &[Message],
+        tools: Option<&[Tool]>,
+    ) -> Result<ChatResponse, LlmError> {
+        let request = normalize_request(system, messages, tools);
+        match self.mode {
+            CassetteMode::Replay => self.lookup(&request),
+            CassetteMode::Record => {
+                let response = self
+                    .upstream()?
+                    .chat(max_tokens, system, messages, tools)
+                    .await?;
+                self.store(request, &response)?;
+                Ok(response)
+            }
+        }
+    }
+
+    async fn chat_stream(
+        &self,
+        max_tokens: u32,
+        system: &str,
+        messages: &[Message],
+        tools: Option<&[Tool]>,
+        deltas: &DeltaSink,
+    ) -> Result<ChatResponse, LlmError> {
+        let request = normalize_request(system, messages, tools);
+        match self.mode {
+            CassetteMode::Replay => {
+                let response = self.lookup(&request)?;
+                for block in &response.content {
+                    if let ContentBlock::Text { text } = block {
+                        let _ = deltas.send(ChatDelta::Text(text.clone()));
+                    }
+                }
+                Ok(response)
+            }
+            CassetteMode::Record => {
+                let response = self
+                    .upstream()?
+                    .chat_stream(max_tokens, system, messages, tools, deltas)
+                    .await?;
+                self.store(request, &response)?;
+                Ok(response)
+            }
+        }
+    }
+}
+
+fn poisoned() -> LlmError {
+    LlmError::Cassette("cassette lock poisoned".to_owned())
+}
+
+fn load_entries(path: &Path) -> Result<HashMap<String, Vec<Value>>, LlmError> {
+    let text =
+        std::fs::read_to_string(path).map_err(|e| LlmError::Cassette(format!("read {}: {e}", path.display())))?;
+    let mut entries: HashMap<String, Vec<Value>> = HashMap::new();
+    for (line_no, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: CassetteEntry = serde_json::from_str(line)
+            .map_err(|e| LlmError::Cassette(format!("{}:{}: {e}", path.display(), line_no + 1)))?;
+        entries.entry(entry.key).or_default().push(entry.response);
+    }
+    Ok(entries)
+}
+
+// =============================================================================
+// NORMALIZATION
+// =============================================================================
+
+fn normalize_request(system: &str, messages: &[Message], tools: Option<&[Tool]>) -> NormalizedRequest {
+    let raw = serde_json::json!({
+        "system": system,
+        "messages": messages,
+        "tools": tools,
+    })
+    .to_string();
+    let mut uuids = Vec::new();
+    let normalized = replace_uuids(&raw, |uuid| {
+        let index = uuids
+            .iter()
+            .position(|seen| seen == uuid)
+            .unwrap_or_else(|| {
+                uuids.push(uuid.to_owned());
+                uuids.len() - 1
+            });
+        Some(placeholder(index))
+    });
+    let key = format!("{:x}", Sha256::digest(normalized.as_bytes()));
+    let body = serde_json::from_str(&normalized).unwrap_or(Value::String(normalized));
+    NormalizedRequest { key, body, uuids }
+}
+
+fn placeholder(index: usize) -> String {
+    format!("<uuid-{index}>")
+}
+
+/// Replace UUIDs that appear in `known` with their placeholders.
+fn replace_known_uuids(text: &str, known: &[String]) -> String {
+    replace_uuids(text, |uuid| known.iter().position(|seen| seen == uuid).map(placeholder))
+}
+
+/// Map placeholders back to the UUIDs of the live request.
+fn denormalize(text: &str, uuids: &[String]) -> String {
+    let mut out = text.to_owned();
+    // Highest index first so `<uuid-1>` never clobbers part of `<uuid-12>`.
+    for (index, uuid) in uuids.iter().enumerate().rev() {
+        out = out.replace(&placeholder(index), uuid);
+    }
+    out
+}
+
+/// Rewrite every UUID in `text` with `replace`, keeping it when that returns `None`.
+fn replace_uuids(text: &str, mut replace: impl FnMut(&str) -> Option<String>) -> String {
+    const UUID_LEN: usize = 36;
+    let bytes = text.as_bytes();
+    let mut out = String::with_capacity(text.len());
+    let mut start = 0;
+    let mut i = 0;
+    while i + UUID_LEN <= bytes.len() {
+        let boundary_before = i == 0 || !bytes[i - 1].is_ascii_hexdigit();
+        let boundary_after = bytes
+            .get(i + UUID_LEN)
+            .is_none_or(|b| !b.is_ascii_hexdigit());
+        if boundary_before && boundary_after && looks_like_uuid(&bytes[i..i + UUID_LEN]) {
+            let candidate = &text[i..i + UUID_LEN];
+            if let Some(replacement) = replace(candidate) {
+                out.push_str(&text[start..i]);
+                out.push_str(&replacement);
+                start = i + UUID_LEN;
+            }
+            i += UUID_LEN;
+        } else {
+            i += 1;
+        }
+    }
+    out.push_str(&text[start..]);
+    out
+}
+
+fn looks_like_uuid(bytes: &[u8]) -> bool {
+    bytes.iter().enumerate().all(|(i, b)| match i {
+        8 | 13 | 18 | 23 => *b == b'-',
+        _ => b.is_ascii_hexdigit(),
+    })
+}
+
+#[cfg(test)]
+#[path = "cassette_test.rs"]
+mod tests;
@@ -0,0 +1,133 @@
+use super::*;
+use crate::llm::types::Content;
+
+/// Upstream that answers with the next scripted response.
+struct ScriptedLlm {
+    responses: Mutex<Vec<ChatResponse>>,
+}
+
+#[async_trait::async_trait]
+impl LlmChat for ScriptedLlm {
+    async fn chat(
+        &self,
+        _max_tokens: u32,
+        _system: &str,
+        _messages: &[Message],
+        _tools: Option<&[Tool]>,
+    ) -> Result<ChatResponse, LlmError> {
+        let mut responses = self.responses.lock().unwrap();
+        Ok(responses.remove(0))
+    }
+}
+
+fn temp_cassette() -> PathBuf {
+    std::env::temp_dir().join(format!("cassette-{}.jsonl", uuid::Uuid::new_v4()))
+}
+
+fn user(text: &str) -> Vec<Message> {
+    vec![Message { role: "user".into(), content: Content::Text(text.into()) }]
+}
+
+fn text_response(text: &str) -> ChatResponse {
+    ChatResponse {
+        content: vec![ContentBlock::Text { text: text.into() }],
+        model: "mock".into(),
+        stop_reason: "end_turn".into(),
+        input_tokens: 1,
+        output_tokens: 1,
+        cache_read_tokens: 0,
+        cache_creation_tokens: 0,
+    }
+}
+
+#[tokio::test]
+async fn replay_maps_recorded_uuids_onto_the_live_request() {
+    let path = temp_cassette();
+    let recorded_id = uuid::Uuid::new_v4().to_string();
+    let upstream = ScriptedLlm {
+        responses: Mutex::new(vec![ChatResponse {
+            content: vec![ContentBlock::ToolUse {
+                id: "tu_1".into(),
+                name: "moveObject".into(),
+                input: serde_json::json!({ "id": recorded_id, "x": 10 }),
+            }],
+            model: "mock".into(),
+            stop_reason: "tool_use".into(),
+            input_tokens: 3,
+            output_tokens: 4,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
+        }]),
+    };
+    let recorder = CassetteChat::record(&path, Arc::new(upstream));
+    recorder
+        .chat(100, "sys", &user(&format!("move {recorded_id}")), None)
+        .await
+        .unwrap();
+
+    let live_id = uuid::Uuid::new_v4().to_string();
+    let player = CassetteChat::replay(&path).unwrap();
+    let replayed = player
+        .chat(100, "sys", &user(&format!("move {live_id}")), None)
+        .await
+        .unwrap();
+    assert!(matches!(
+        &replayed.content[0],
+        ContentBlock::ToolUse { input, .. } if input["id"] == live_id.as_str()
+    ));
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[tokio::test]
+async fn replay_serves_repeated_requests_in_recorded_order() {
+    let path = temp_cassette();
+    let upstream = ScriptedLlm { responses: Mutex::new(vec![text_response("first"), text_response("second")]) };
+    let recorder = CassetteChat::record(&path, Arc::new(upstream));
+    for _ in 0..2 {
+        recorder
+            .chat(100, "sys", &user("again"), None)
+            .await
+            .unwrap();
+    }
+
+    let player = CassetteChat::replay(&path).unwrap();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut texts = Vec::new();
+    for _ in 0..3 {
+        let response = player
+            .chat_stream(100, "sys", &user("again"), None, &tx)
+            .await
+            .unwrap();
+        texts.push(response.content);
+    }
+    assert!(matches!(&texts[0][0], ContentBlock::Text { text } if text == "first"));
+    assert!(matches!(&texts[1][0], ContentBlock::Text { text } if text == "second"));
+    assert!(matches!(&texts[2][0], ContentBlock::Text { text } if text == "second"));
+    assert_eq!(rx.try_recv().unwrap(), ChatDelta::Text("first".into()));
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[tokio::test]
+async fn replay_miss_is_a_cassette_error() {
+    let path = temp_cassette();
+    std::fs::write(&path, "").unwrap();
+    let player = CassetteChat::replay(&path).unwrap();
+    let err = player
+        .chat(100, "sys", &user("never recorded"), None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, LlmError::Cassette(_)));
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn replace_uuids_only_rewrites_whole_uuids() {
+    let id = "123e4567-e89b-12d3-a456-426614174000";
+    let text = format!("a {id} b f{id} {id}");
+    let out = replace_uuids(&text, |_| Some("U".into()));
+    assert_eq!(out, format!("a U b f{id} U"));
+    assert_eq!(denormalize("<uuid-1> <uuid-0>", &["a".into(), "b".into()]), "b a");
+}
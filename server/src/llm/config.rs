@@ -1,10 +1,16 @@
 //! LLM configuration parsed from environment variables.
 
+use std::path::PathBuf;
+
+use super::cassette::CassetteMode;
 use super::types::LlmError;
 
 pub const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
 pub const DEFAULT_LLM_REQUEST_TIMEOUT_SECS: u64 = 120;
 pub const DEFAULT_LLM_CONNECT_TIMEOUT_SECS: u64 = 10;
+pub const DEFAULT_LLM_RETRY_MAX_ATTEMPTS: u32 = 3;
+pub const DEFAULT_LLM_RETRY_BASE_DELAY_MS: u64 = 500;
+pub const DEFAULT_LLM_RETRY_MAX_DELAY_MS: u64 = 10_000;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LlmProviderKind {
@@ -18,12 +24,40 @@ pub enum OpenAiApiMode {
     Responses,
 }
 
+/// Record/replay settings from `LLM_PROVIDER=record|replay`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CassetteConfig {
+    pub mode: CassetteMode,
+    pub path: PathBuf,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct LlmTimeouts {
     pub request_secs: u64,
     pub connect_secs: u64,
 }
 
+/// Retry policy applied to each provider in the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LlmRetry {
+    /// Attempts per provider before failing over, including the first.
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    /// Upper bound for backoff. A `Retry-After` longer than this fails over
+    /// instead of waiting.
+    pub max_delay_ms: u64,
+}
+
+/// A secondary provider tried after the primary fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FallbackConfig {
+    pub provider: LlmProviderKind,
+    pub api_key: String,
+    pub model: String,
+    pub openai_mode: OpenAiApiMode,
+    pub openai_base_url: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LlmConfig {
     pub provider: LlmProviderKind,
@@ -32,6 +66,11 @@ pub struct LlmConfig {
     pub openai_mode: OpenAiApiMode,
     pub openai_base_url: String,
     pub timeouts: LlmTimeouts,
+    /// Providers tried in order after the primary, sharing its timeouts.
+    pub fallbacks: Vec<FallbackConfig>,
+    pub retry: LlmRetry,
+    /// Set when requests are recorded to or replayed from a cassette.
+    pub cassette: Option<CassetteConfig>,
 }
 
 impl LlmConfig {
@@ -41,18 +80,46 @@ impl LlmConfig {
     /// - `LLM_API_KEY_ENV` (names the env var containing the key)
     ///
     /// Optional:
-    /// - `LLM_PROVIDER`: `anthropic` (default) or `openai`
+    /// - `LLM_PROVIDER`: `anthropic` (default), `openai`, `record`, or `replay`
+    /// - `LLM_CASSETTE`: cassette file; required for `record` and `replay`
+    /// - `LLM_RECORD_PROVIDER`: real provider behind `record`, `anthropic` (default) or `openai`
     /// - `LLM_MODEL`: provider default when absent
     /// - `LLM_OPENAI_MODE`: `responses` (default) or `chat_completions`
     /// - `LLM_OPENAI_BASE_URL`: default `OpenAI` API base URL
     /// - `LLM_REQUEST_TIMEOUT_SECS`: default 120
     /// - `LLM_CONNECT_TIMEOUT_SECS`: default 10
+    /// - `LLM_RETRY_MAX_ATTEMPTS`: attempts per provider, default 3
+    /// - `LLM_RETRY_BASE_DELAY_MS` / `LLM_RETRY_MAX_DELAY_MS`: backoff bounds, default 500 / 10000
+    /// - `LLM_FALLBACK_<N>_PROVIDER`, `_API_KEY_ENV`, `_MODEL`, `_OPENAI_MODE`,
+    ///   `_OPENAI_BASE_URL`: failover chain, read for N = 1, 2, ... until a
+    ///   provider is missing
+    ///
+    /// `replay` needs no API key.
     pub fn from_env() -> Result<Self, LlmError> {
-        let provider = parse_provider(std::env::var("LLM_PROVIDER").ok().as_deref())?;
+        let raw_provider = std::env::var("LLM_PROVIDER").ok();
+        let cassette = parse_cassette_mode(raw_provider.as_deref())
+            .map(|mode| {
+                let path = std::env::var("LLM_CASSETTE")
+                    .map_err(|_| LlmError::ConfigParse("LLM_CASSETTE required for record/replay".into()))?;
+                Ok::<_, LlmError>(CassetteConfig { mode, path: PathBuf::from(path) })
+            })
+            .transpose()?;
+        let provider = if cassette.is_some() {
+            parse_provider(std::env::var("LLM_RECORD_PROVIDER").ok().as_deref())?
+        } else {
+            parse_provider(raw_provider.as_deref())?
+        };
 
-        let key_var =
-            std::env::var("LLM_API_KEY_ENV").map_err(|_| LlmError::MissingApiKey { var: "LLM_API_KEY_ENV".into() })?;
-        let api_key = std::env::var(&key_var).map_err(|_| LlmError::MissingApiKey { var: key_var.clone() })?;
+        let api_key = if cassette
+            .as_ref()
+            .is_some_and(|c| c.mode == CassetteMode::Replay)
+        {
+            String::new()
+        } else {
+            let key_var = std::env::var("LLM_API_KEY_ENV")
+                .map_err(|_| LlmError::MissingApiKey { var: "LLM_API_KEY_ENV".into() })?;
+            std::env::var(&key_var).map_err(|_| LlmError::MissingApiKey { var: key_var.clone() })?
+        };
 
         let model = std::env::var("LLM_MODEL").unwrap_or_else(|_| default_model(provider).to_string());
         let openai_mode = parse_openai_mode(std::env::var("LLM_OPENAI_MODE").ok().as_deref())?;
@@ -65,10 +132,46 @@ impl LlmConfig {
             connect_secs: env_parse_u64("LLM_CONNECT_TIMEOUT_SECS", DEFAULT_LLM_CONNECT_TIMEOUT_SECS),
         };
 
-        Ok(Self { provider, api_key, model, openai_mode, openai_base_url, timeouts })
+        let retry = LlmRetry {
+            max_attempts: u32::try_from(env_parse_u64("LLM_RETRY_MAX_ATTEMPTS", DEFAULT_LLM_RETRY_MAX_ATTEMPTS.into()))
+                .unwrap_or(DEFAULT_LLM_RETRY_MAX_ATTEMPTS)
+                .max(1),
+            base_delay_ms: env_parse_u64("LLM_RETRY_BASE_DELAY_MS", DEFAULT_LLM_RETRY_BASE_DELAY_MS),
+            max_delay_ms: env_parse_u64("LLM_RETRY_MAX_DELAY_MS", DEFAULT_LLM_RETRY_MAX_DELAY_MS),
+        };
+        let mut fallbacks = Vec::new();
+        if cassette.is_none() {
+            while let Some(fallback) = parse_fallback(fallbacks.len() + 1)? {
+                fallbacks.push(fallback);
+            }
+        }
+
+        Ok(Self { provider, api_key, model, openai_mode, openai_base_url, timeouts, fallbacks, retry, cassette })
     }
 }
 
+/// Read `LLM_FALLBACK_<n>_*`, or `None` when no provider is set for `n`.
+fn parse_fallback(n: usize) -> Result<Option<FallbackConfig>, LlmError> {
+    let var = |suffix: &str| std::env::var(format!("LLM_FALLBACK_{n}_{suffix}")).ok();
+    let Some(raw_provider) = var("PROVIDER") else {
+        return Ok(None);
+    };
+    let provider = parse_provider(Some(&raw_provider))?;
+    let key_var_name = format!("LLM_FALLBACK_{n}_API_KEY_ENV");
+    let key_var = var("API_KEY_ENV").ok_or(LlmError::MissingApiKey { var: key_var_name })?;
+    let api_key = std::env::var(&key_var).map_err(|_| LlmError::MissingApiKey { var: key_var.clone() })?;
+    Ok(Some(FallbackConfig {
+        provider,
+        api_key,
+        model: var("MODEL").unwrap_or_else(|| default_model(provider).to_string()),
+        openai_mode: parse_openai_mode(var("OPENAI_MODE").as_deref())?,
+        openai_base_url: var("OPENAI_BASE_URL")
+            .unwrap_or_else(|| DEFAULT_OPENAI_BASE_URL.to_string())
+            .trim_end_matches('/')
+            .to_string(),
+    }))
+}
+
 fn env_parse_u64(key: &str, default: u64) -> u64 {
     std::env::var(key)
         .ok()
@@ -84,6 +187,14 @@ fn parse_provider(raw: Option<&str>) -> Result<LlmProviderKind, LlmError> {
     }
 }
 
+fn parse_cassette_mode(raw: Option<&str>) -> Option<CassetteMode> {
+    match raw {
+        Some("record") => Some(CassetteMode::Record),
+        Some("replay") => Some(CassetteMode::Replay),
+        _ => None,
+    }
+}
+
 fn parse_openai_mode(raw: Option<&str>) -> Result<OpenAiApiMode, LlmError> {
     match raw.unwrap_or("responses") {
         "responses" => Ok(OpenAiApiMode::Responses),
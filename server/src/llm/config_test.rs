@@ -11,6 +11,12 @@ unsafe fn clear_llm_env() {
         std::env::remove_var("LLM_OPENAI_BASE_URL");
         std::env::remove_var("LLM_REQUEST_TIMEOUT_SECS");
         std::env::remove_var("LLM_CONNECT_TIMEOUT_SECS");
+        std::env::remove_var("LLM_CASSETTE");
+        std::env::remove_var("LLM_RECORD_PROVIDER");
+        std::env::remove_var("LLM_RETRY_MAX_ATTEMPTS");
+        std::env::remove_var("LLM_FALLBACK_1_PROVIDER");
+        std::env::remove_var("LLM_FALLBACK_1_API_KEY_ENV");
+        std::env::remove_var("LLM_FALLBACK_1_MODEL");
         std::env::remove_var("ANTHROPIC_API_KEY");
         std::env::remove_var("OPENAI_API_KEY");
         std::env::remove_var("TEST_KEY");
@@ -62,6 +68,54 @@ fn from_env_parses_openai_overrides() {
     unsafe { clear_llm_env() };
 }
 
+#[test]
+fn from_env_reads_fallback_chain_and_retry() {
+    unsafe {
+        clear_llm_env();
+        std::env::set_var("LLM_API_KEY_ENV", "TEST_KEY");
+        std::env::set_var("TEST_KEY", "secret");
+        std::env::set_var("LLM_RETRY_MAX_ATTEMPTS", "5");
+        std::env::set_var("LLM_FALLBACK_1_PROVIDER", "openai");
+        std::env::set_var("LLM_FALLBACK_1_API_KEY_ENV", "OPENAI_API_KEY");
+        std::env::set_var("OPENAI_API_KEY", "sk-test");
+        std::env::set_var("LLM_FALLBACK_1_MODEL", "gpt-4o-mini");
+    }
+
+    let cfg = LlmConfig::from_env().unwrap();
+    assert_eq!(cfg.retry.max_attempts, 5);
+    assert_eq!(cfg.retry.base_delay_ms, DEFAULT_LLM_RETRY_BASE_DELAY_MS);
+    assert_eq!(
+        cfg.fallbacks,
+        vec![FallbackConfig {
+            provider: LlmProviderKind::OpenAi,
+            api_key: "sk-test".into(),
+            model: "gpt-4o-mini".into(),
+            openai_mode: OpenAiApiMode::Responses,
+            openai_base_url: DEFAULT_OPENAI_BASE_URL.into(),
+        }]
+    );
+
+    unsafe { clear_llm_env() };
+}
+
+#[test]
+fn from_env_replay_needs_cassette_but_no_api_key() {
+    unsafe {
+        clear_llm_env();
+        std::env::set_var("LLM_PROVIDER", "replay");
+    }
+    let err = LlmConfig::from_env().unwrap_err().to_string();
+    assert!(err.contains("LLM_CASSETTE"));
+
+    unsafe { std::env::set_var("LLM_CASSETTE", "tests/cassettes/ai.jsonl") };
+    let cfg = LlmConfig::from_env().unwrap();
+    let cassette = cfg.cassette.unwrap();
+    assert_eq!(cassette.mode, CassetteMode::Replay);
+    assert_eq!(cassette.path, PathBuf::from("tests/cassettes/ai.jsonl"));
+
+    unsafe { clear_llm_env() };
+}
+
 #[test]
 fn from_env_unknown_provider_errors() {
     unsafe {
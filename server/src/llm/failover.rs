@@ -0,0 +1,201 @@
+//! Provider failover chain with jittered retry.
+//!
+//! DESIGN
+//! ======
+//! `LlmClient` holds an ordered chain of providers (primary first). Each
+//! provider gets up to `LlmRetry::max_attempts` tries. Retryable errors
+//! (timeouts, 429, 5xx, 529 overloaded) back off exponentially with jitter,
+//! or wait out the provider's `Retry-After` when it sent one. Anything else,
+//! or a `Retry-After` longer than the backoff cap, moves on to the next
+//! provider. The last error surfaces once the chain is exhausted.
+//!
+//! A streamed attempt that already forwarded text is never retried: the
+//! client has shown that text and a second attempt would repeat it.
+//!
+//! Every attempt is reported on the delta sink as `ChatDelta::Attempt` so
+//! the AI service can record it as its own trace span.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use super::config::LlmRetry;
+use super::types::{ChatDelta, ChatResponse, DeltaSink, LlmAttempt, LlmChat, LlmError, Message, Tool};
+use crate::frame::ErrorCode;
+
+/// One provider/model pair in the chain.
+pub struct Backend {
+    /// Provider name reported in attempt spans, e.g. `"openai"`.
+    pub provider: &'static str,
+    pub model: String,
+    pub chat: Arc<dyn LlmChat>,
+}
+
+/// `LlmChat` that tries each backend in order, retrying transient failures.
+pub struct FailoverChat {
+    chain: Vec<Backend>,
+    retry: LlmRetry,
+}
+
+impl FailoverChat {
+    #[must_use]
+    pub fn new(chain: Vec<Backend>, retry: LlmRetry) -> Self {
+        Self { chain, retry }
+    }
+
+    async fn run(
+        &self,
+        max_tokens: u32,
+        system: &str,
+        messages: &[Message],
+        tools: Option<&[Tool]>,
+        deltas: Option<&DeltaSink>,
+    ) -> Result<ChatResponse, LlmError> {
+        let mut last_err = None;
+        for backend in &self.chain {
+            for attempt in 1..=self.retry.max_attempts {
+                let started_at = Instant::now();
+                let (result, streamed) = if let Some(sink) = deltas {
+                    stream_attempt(backend, max_tokens, system, messages, tools, sink).await
+                } else {
+                    (backend.chat.chat(max_tokens, system, messages, tools).await, false)
+                };
+                let report = |error: Option<&LlmError>, retry_in: Option<Duration>| {
+                    if let Some(sink) = deltas {
+                        let _ = sink.send(ChatDelta::Attempt(LlmAttempt {
+                            provider: backend.provider,
+                            model: backend.model.clone(),
+                            attempt,
+                            duration_ms: millis(started_at.elapsed()),
+                            error: error.map(|e| (e.error_code(), e.to_string())),
+                            retry_in_ms: retry_in.map(millis),
+                        }));
+                    }
+                };
+
+                let err = match result {
+                    Ok(response) => {
+                        report(None, None);
+                        return Ok(response);
+                    }
+                    Err(err) => err,
+                };
+                let delay = if streamed || attempt == self.retry.max_attempts {
+                    None
+                } else {
+                    retry_delay(&self.retry, &err, attempt)
+                };
+                report(Some(&err), delay);
+                warn!(
+                    provider = backend.provider,
+                    model = %backend.model,
+                    attempt,
+                    error = %err,
+                    retry_in_ms = delay.map(millis),
+                    "llm: attempt failed"
+                );
+                if streamed {
+                    return Err(err);
+                }
+                last_err = Some(err);
+                let Some(delay) = delay else {
+                    break;
+                };
+                tokio::time::sleep(delay).await;
+            }
+        }
+        Err(last_err.unwrap_or_else(|| LlmError::ConfigParse("no LLM providers configured".into())))
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmChat for FailoverChat {
+    async fn chat(
+        &self,
+        max_tokens: u32,
+        system: &str,
+        messages: &[Message],
+        tools: Option<&[Tool]>,
+    ) -> Result<ChatResponse, LlmError> {
+        self.run(max_tokens, system, messages, tools, None).await
+    }
+
+    async fn chat_stream(
+        &self,
+        max_tokens: u32,
+        system: &str,
+        messages: &[Message],
+        tools: Option<&[Tool]>,
+        deltas: &DeltaSink,
+    ) -> Result<ChatResponse, LlmError> {
+        self.run(max_tokens, system, messages, tools, Some(deltas))
+            .await
+    }
+}
+
+/// Run one streamed attempt, noting whether any text reached `sink`.
+async fn stream_attempt(
+    backend: &Backend,
+    max_tokens: u32,
+    system: &str,
+    messages: &[Message],
+    tools: Option<&[Tool]>,
+    sink: &DeltaSink,
+) -> (Result<ChatResponse, LlmError>, bool) {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let call = async move {
+        // Owning `tx` here closes the channel when the call finishes.
+        let tx = tx;
+        backend
+            .chat
+            .chat_stream(max_tokens, system, messages, tools, &tx)
+            .await
+    };
+    let forward = async {
+        let mut streamed = false;
+        while let Some(delta) = rx.recv().await {
+            streamed |= matches!(delta, ChatDelta::Text(_));
+            let _ = sink.send(delta);
+        }
+        streamed
+    };
+    tokio::join!(call, forward)
+}
+
+/// Delay before retrying after `err`, or `None` to fail over instead.
+fn retry_delay(retry: &LlmRetry, err: &LlmError, attempt: u32) -> Option<Duration> {
+    if !err.retryable() {
+        return None;
+    }
+    if let LlmError::ApiResponse { retry_after_secs: Some(secs), .. } = err {
+        let wait_ms = secs.saturating_mul(1000);
+        return (wait_ms <= retry.max_delay_ms).then(|| Duration::from_millis(wait_ms));
+    }
+    let ceiling = retry
+        .base_delay_ms
+        .saturating_mul(1 << (attempt - 1).min(20))
+        .min(retry.max_delay_ms);
+    Some(Duration::from_millis(rand::rng().random_range(ceiling / 2..=ceiling)))
+}
+
+/// Parse a delay-seconds `Retry-After` header. HTTP-date values are ignored.
+pub(crate) fn retry_after_secs(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+#[path = "failover_test.rs"]
+mod tests;
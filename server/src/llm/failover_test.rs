@@ -0,0 +1,198 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use super::*;
+use crate::llm::types::ContentBlock;
+
+/// Backend that plays back scripted results, optionally streaming text first.
+struct ScriptedLlm {
+    results: Mutex<VecDeque<Result<ChatResponse, LlmError>>>,
+    streamed_text: Option<&'static str>,
+}
+
+impl ScriptedLlm {
+    fn backend(
+        provider: &'static str,
+        results: Vec<Result<ChatResponse, LlmError>>,
+        streamed_text: Option<&'static str>,
+    ) -> Backend {
+        let chat = Arc::new(Self { results: Mutex::new(results.into()), streamed_text });
+        Backend { provider, model: format!("{provider}-model"), chat }
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmChat for ScriptedLlm {
+    async fn chat(
+        &self,
+        _max_tokens: u32,
+        _system: &str,
+        _messages: &[Message],
+        _tools: Option<&[Tool]>,
+    ) -> Result<ChatResponse, LlmError> {
+        self.results
+            .lock()
+            .unwrap()
+            .pop_front()
+            .expect("unexpected call")
+    }
+
+    async fn chat_stream(
+        &self,
+        max_tokens: u32,
+        system: &str,
+        messages: &[Message],
+        tools: Option<&[Tool]>,
+        deltas: &DeltaSink,
+    ) -> Result<ChatResponse, LlmError> {
+        if let Some(text) = self.streamed_text {
+            let _ = deltas.send(ChatDelta::Text(text.into()));
+        }
+        self.chat(max_tokens, system, messages, tools).await
+    }
+}
+
+fn fast_retry(max_attempts: u32) -> LlmRetry {
+    LlmRetry { max_attempts, base_delay_ms: 1, max_delay_ms: 5 }
+}
+
+fn text(text: &str) -> ChatResponse {
+    ChatResponse {
+        content: vec![ContentBlock::Text { text: text.into() }],
+        model: "mock".into(),
+        stop_reason: "end_turn".into(),
+        input_tokens: 1,
+        output_tokens: 1,
+        cache_read_tokens: 0,
+        cache_creation_tokens: 0,
+    }
+}
+
+fn overloaded(retry_after_secs: Option<u64>) -> Result<ChatResponse, LlmError> {
+    Err(LlmError::ApiResponse { status: 529, body: "overloaded".into(), retry_after_secs })
+}
+
+fn attempts(rx: &mut mpsc::UnboundedReceiver<ChatDelta>) -> Vec<LlmAttempt> {
+    std::iter::from_fn(|| rx.try_recv().ok())
+        .filter_map(|delta| match delta {
+            ChatDelta::Attempt(attempt) => Some(attempt),
+            ChatDelta::Text(_) => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn retries_transient_errors_then_succeeds() {
+    let chain = vec![ScriptedLlm::backend(
+        "anthropic",
+        vec![overloaded(None), Ok(text("hi"))],
+        None,
+    )];
+    let chat = FailoverChat::new(chain, fast_retry(3));
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    chat.chat_stream(10, "", &[], None, &tx).await.unwrap();
+
+    let attempts = attempts(&mut rx);
+    assert_eq!(attempts.len(), 2);
+    assert_eq!(attempts[0].attempt, 1);
+    assert_eq!(attempts[0].error.as_ref().map(|e| e.0), Some("E_API_RESPONSE"));
+    assert!(attempts[0].retry_in_ms.is_some());
+    assert_eq!(attempts[1].attempt, 2);
+    assert!(attempts[1].error.is_none());
+}
+
+#[tokio::test]
+async fn fails_over_to_next_provider_after_non_retryable_error() {
+    let unauthorized = Err(LlmError::ApiResponse { status: 401, body: "bad key".into(), retry_after_secs: None });
+    let chain = vec![
+        ScriptedLlm::backend("anthropic", vec![unauthorized], None),
+        ScriptedLlm::backend("openai", vec![Ok(text("from openai"))], None),
+    ];
+    let chat = FailoverChat::new(chain, fast_retry(3));
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    let response = chat.chat_stream(10, "", &[], None, &tx).await.unwrap();
+
+    assert!(matches!(&response.content[0], ContentBlock::Text { text } if text == "from openai"));
+    let attempts = attempts(&mut rx);
+    assert_eq!(
+        attempts
+            .iter()
+            .map(|a| (a.provider, a.model.as_str()))
+            .collect::<Vec<_>>(),
+        vec![("anthropic", "anthropic-model"), ("openai", "openai-model")]
+    );
+    assert!(attempts[0].retry_in_ms.is_none());
+}
+
+#[tokio::test]
+async fn exhausted_chain_returns_last_error() {
+    let chain = vec![
+        ScriptedLlm::backend("anthropic", vec![overloaded(None), overloaded(None)], None),
+        ScriptedLlm::backend(
+            "openai",
+            vec![
+                Err(LlmError::ApiRequest("timeout".into())),
+                Err(LlmError::ApiRequest("timeout".into())),
+            ],
+            None,
+        ),
+    ];
+    let chat = FailoverChat::new(chain, fast_retry(2));
+
+    let err = chat.chat(10, "", &[], None).await.unwrap_err();
+
+    assert!(matches!(err, LlmError::ApiRequest(_)));
+}
+
+#[tokio::test]
+async fn streamed_text_is_not_retried() {
+    let chain = vec![
+        ScriptedLlm::backend("anthropic", vec![Err(LlmError::ApiStream("dropped".into()))], Some("partial")),
+        ScriptedLlm::backend("openai", vec![], None),
+    ];
+    let chat = FailoverChat::new(chain, fast_retry(3));
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    let err = chat.chat_stream(10, "", &[], None, &tx).await.unwrap_err();
+
+    assert!(matches!(err, LlmError::ApiStream(_)));
+    assert_eq!(rx.try_recv().unwrap(), ChatDelta::Text("partial".into()));
+    assert_eq!(attempts(&mut rx).len(), 1);
+}
+
+#[test]
+fn retry_delay_honors_retry_after_within_the_cap() {
+    let retry = LlmRetry { max_attempts: 3, base_delay_ms: 100, max_delay_ms: 5_000 };
+    let soon = overloaded(Some(2)).unwrap_err();
+    let later = overloaded(Some(60)).unwrap_err();
+    let bad_request = LlmError::ApiResponse { status: 400, body: String::new(), retry_after_secs: None };
+
+    assert_eq!(retry_delay(&retry, &soon, 1), Some(Duration::from_millis(2_000)));
+    assert_eq!(retry_delay(&retry, &later, 1), None);
+    assert_eq!(retry_delay(&retry, &bad_request, 1), None);
+}
+
+#[test]
+fn retry_delay_backs_off_exponentially_with_jitter() {
+    let retry = LlmRetry { max_attempts: 5, base_delay_ms: 100, max_delay_ms: 300 };
+    let err = overloaded(None).unwrap_err();
+    for _ in 0..20 {
+        let first = retry_delay(&retry, &err, 1).unwrap().as_millis();
+        let second = retry_delay(&retry, &err, 2).unwrap().as_millis();
+        let capped = retry_delay(&retry, &err, 4).unwrap().as_millis();
+        assert!((50..=100).contains(&first));
+        assert!((100..=200).contains(&second));
+        assert!((150..=300).contains(&capped));
+    }
+}
+
+#[test]
+fn retry_after_header_parses_delay_seconds_only() {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(reqwest::header::RETRY_AFTER, "7".parse().unwrap());
+    assert_eq!(retry_after_secs(&headers), Some(7));
+    headers.insert(reqwest::header::RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+    assert_eq!(retry_after_secs(&headers), None);
+}
@@ -3,24 +3,33 @@
 //! DESIGN
 //! ======
 //! Ported from Prior's `kernel/src/llm/mod.rs`. Simplified: uses environment
-//! variables instead of config files. The `LlmClient` enum dispatches to
-//! Anthropic or `OpenAI` based on `LLM_PROVIDER`.
+//! variables instead of config files. `LlmClient` dispatches to an ordered
+//! chain of Anthropic / `OpenAI` providers with retry and failover (see
+//! `failover`), or to a record/replay cassette (see `cassette`).
 
 pub mod anthropic;
+pub mod cassette;
 pub mod config;
+pub mod failover;
 pub mod openai;
+pub mod schema;
+pub mod sse;
 pub mod tools;
 pub mod types;
 
-use config::{LlmConfig, LlmProviderKind};
+use std::sync::Arc;
+
+use cassette::{CassetteChat, CassetteMode};
+use config::{LlmConfig, LlmProviderKind, LlmTimeouts, OpenAiApiMode};
+use failover::{Backend, FailoverChat};
 pub use types::LlmChat;
-use types::{ChatResponse, LlmError, Message, Tool};
+use types::{ChatResponse, DeltaSink, LlmError, Message, Tool};
 
 // =============================================================================
 // CLIENT DISPATCH
 // =============================================================================
 
-/// Concrete LLM client that dispatches to either Anthropic or `OpenAI`.
+/// Concrete LLM client that dispatches to a provider failover chain.
 ///
 /// Configured from environment variables by [`LlmClient::from_env`].
 pub struct LlmClient {
@@ -29,6 +38,17 @@ pub struct LlmClient {
 }
 
 enum LlmProvider {
+    Chain(FailoverChat),
+    Cassette(CassetteChat),
+}
+
+/// A single HTTP provider bound to the model it should be asked for.
+struct ProviderChat {
+    http: HttpProvider,
+    model: String,
+}
+
+enum HttpProvider {
     Anthropic(anthropic::AnthropicClient),
     OpenAi(openai::OpenAiClient),
 }
@@ -41,6 +61,7 @@ impl LlmClient {
     /// - `LLM_MODEL`: model name (e.g. "claude-sonnet-4-5-20250929")
     /// - `LLM_OPENAI_MODE`: "responses" (default) or `"chat_completions"`
     /// - `LLM_OPENAI_BASE_URL`: custom base URL for OpenAI-compatible APIs
+    /// - `LLM_FALLBACK_<N>_*`: secondary providers; see [`LlmConfig::from_env`]
     ///
     /// # Errors
     ///
@@ -54,47 +75,106 @@ impl LlmClient {
     ///
     /// # Errors
     ///
-    /// Returns an error if the provider HTTP client fails to build.
-    pub fn from_config(config: LlmConfig) -> Result<Self, LlmError> {
+    /// Returns an error if a provider HTTP client fails to build.
+    pub fn from_config(mut config: LlmConfig) -> Result<Self, LlmError> {
         let model = config.model.clone();
-        let inner = match config.provider {
-            LlmProviderKind::Anthropic => {
-                LlmProvider::Anthropic(anthropic::AnthropicClient::new(config.api_key, config.timeouts)?)
-            }
-            LlmProviderKind::OpenAi => LlmProvider::OpenAi(openai::OpenAiClient::new(
-                config.api_key,
-                config.openai_mode,
-                config.openai_base_url,
+        if let Some(cassette) = config.cassette.take() {
+            let chat = match cassette.mode {
+                CassetteMode::Replay => CassetteChat::replay(cassette.path)?,
+                CassetteMode::Record => CassetteChat::record(cassette.path, Arc::new(Self::from_config(config)?)),
+            };
+            return Ok(Self { inner: LlmProvider::Cassette(chat), model });
+        }
+
+        let mut chain = vec![provider_backend(
+            config.provider,
+            config.api_key,
+            config.model,
+            config.openai_mode,
+            config.openai_base_url,
+            config.timeouts,
+        )?];
+        for fallback in config.fallbacks {
+            chain.push(provider_backend(
+                fallback.provider,
+                fallback.api_key,
+                fallback.model,
+                fallback.openai_mode,
+                fallback.openai_base_url,
                 config.timeouts,
-            )?),
-        };
-        Ok(Self { inner, model })
+            )?);
+        }
+        Ok(Self { inner: LlmProvider::Chain(FailoverChat::new(chain, config.retry)), model })
     }
 
-    /// Return the configured model name (e.g. `"claude-sonnet-4-5-20250929"`).
+    /// Return the primary model name (e.g. `"claude-sonnet-4-5-20250929"`).
     #[must_use]
     pub fn model(&self) -> &str {
         &self.model
     }
+}
 
-    async fn chat_inner(
+fn provider_backend(
+    kind: LlmProviderKind,
+    api_key: String,
+    model: String,
+    openai_mode: OpenAiApiMode,
+    openai_base_url: String,
+    timeouts: LlmTimeouts,
+) -> Result<Backend, LlmError> {
+    let (provider, http) = match kind {
+        LlmProviderKind::Anthropic => (
+            "anthropic",
+            HttpProvider::Anthropic(anthropic::AnthropicClient::new(api_key, timeouts)?),
+        ),
+        LlmProviderKind::OpenAi => (
+            "openai",
+            HttpProvider::OpenAi(openai::OpenAiClient::new(api_key, openai_mode, openai_base_url, timeouts)?),
+        ),
+    };
+    Ok(Backend { provider, model: model.clone(), chat: Arc::new(ProviderChat { http, model }) })
+}
+
+#[async_trait::async_trait]
+impl LlmChat for ProviderChat {
+    async fn chat(
         &self,
         max_tokens: u32,
         system: &str,
         messages: &[Message],
         tools: Option<&[Tool]>,
     ) -> Result<ChatResponse, LlmError> {
-        match &self.inner {
-            LlmProvider::Anthropic(c) => {
+        match &self.http {
+            HttpProvider::Anthropic(c) => {
                 c.chat(&self.model, max_tokens, system, messages, tools)
                     .await
             }
-            LlmProvider::OpenAi(c) => {
+            HttpProvider::OpenAi(c) => {
                 c.chat(&self.model, max_tokens, system, messages, tools)
                     .await
             }
         }
     }
+
+    async fn chat_stream(
+        &self,
+        max_tokens: u32,
+        system: &str,
+        messages: &[Message],
+        tools: Option<&[Tool]>,
+        deltas: &DeltaSink,
+    ) -> Result<ChatResponse, LlmError> {
+        match &self.http {
+            HttpProvider::Anthropic(c) => {
+                c.chat_stream(&self.model, max_tokens, system, messages, tools, deltas)
+                    .await
+            }
+            HttpProvider::OpenAi(c) => {
+                c.chat_stream(&self.model, max_tokens, system, messages, tools, deltas)
+                    .await
+            }
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -106,6 +186,29 @@ impl LlmChat for LlmClient {
         messages: &[Message],
         tools: Option<&[Tool]>,
     ) -> Result<ChatResponse, LlmError> {
-        self.chat_inner(max_tokens, system, messages, tools).await
+        match &self.inner {
+            LlmProvider::Chain(c) => c.chat(max_tokens, system, messages, tools).await,
+            LlmProvider::Cassette(c) => c.chat(max_tokens, system, messages, tools).await,
+        }
+    }
+
+    async fn chat_stream(
+        &self,
+        max_tokens: u32,
+        system: &str,
+        messages: &[Message],
+        tools: Option<&[Tool]>,
+        deltas: &DeltaSink,
+    ) -> Result<ChatResponse, LlmError> {
+        match &self.inner {
+            LlmProvider::Chain(c) => {
+                c.chat_stream(max_tokens, system, messages, tools, deltas)
+                    .await
+            }
+            LlmProvider::Cassette(c) => {
+                c.chat_stream(max_tokens, system, messages, tools, deltas)
+                    .await
+            }
+        }
     }
 }
@@ -8,7 +8,9 @@ use serde_json::Value;
 use std::time::Duration;
 
 use super::config::{LlmTimeouts, OpenAiApiMode};
-use super::types::{ChatResponse, Content, ContentBlock, LlmError, Message, Tool};
+use super::failover::retry_after_secs;
+use super::sse::{SseEvent, read_events};
+use super::types::{ChatDelta, ChatResponse, Content, ContentBlock, DeltaSink, LlmError, Message, Tool};
 
 pub struct OpenAiClient {
     http: reqwest::Client,
@@ -52,6 +54,45 @@ impl OpenAiClient {
         }
     }
 
+    /// Stream a chat request, sending text deltas as they arrive.
+    pub async fn chat_stream(
+        &self,
+        model: &str,
+        max_tokens: u32,
+        system: &str,
+        messages: &[Message],
+        tools: Option<&[Tool]>,
+        deltas: &DeltaSink,
+    ) -> Result<ChatResponse, LlmError> {
+        match self.mode {
+            OpenAiApiMode::ChatCompletions => {
+                let msgs = build_chat_completions_messages(system, messages);
+                let tool_defs: Option<Vec<CcToolDef<'_>>> = tools.map(|t| t.iter().map(CcToolDef::from).collect());
+                let body = CcRequest::new(model, max_tokens, &msgs, tool_defs.as_deref(), true);
+                let response = self.send("/chat/completions", &body).await?;
+                let mut stream = CcStreamState::default();
+                read_events(response, |event| stream.apply(&event, deltas)).await?;
+                stream.finish()
+            }
+            OpenAiApiMode::Responses => {
+                let input = build_responses_input(messages);
+                let tool_defs: Option<Vec<RespToolDef<'_>>> = tools.map(|t| t.iter().map(RespToolDef::from).collect());
+                let body = RespRequest {
+                    model,
+                    max_output_tokens: max_tokens,
+                    instructions: system,
+                    input: &input,
+                    tools: tool_defs.as_deref(),
+                    stream: true,
+                };
+                let response = self.send("/responses", &body).await?;
+                let mut stream = RespStreamState::default();
+                read_events(response, |event| stream.apply(&event, deltas)).await?;
+                stream.finish()
+            }
+        }
+    }
+
     async fn chat_completions(
         &self,
         model: &str,
@@ -62,7 +103,7 @@ impl OpenAiClient {
     ) -> Result<ChatResponse, LlmError> {
         let msgs = build_chat_completions_messages(system, messages);
         let tool_defs: Option<Vec<CcToolDef<'_>>> = tools.map(|t| t.iter().map(CcToolDef::from).collect());
-        let body = CcRequest { model, max_tokens, messages: &msgs, tools: tool_defs.as_deref() };
+        let body = CcRequest::new(model, max_tokens, &msgs, tool_defs.as_deref(), false);
         let text = self.send_json("/chat/completions", &body).await?;
         parse_chat_completions_response(&text)
     }
@@ -83,12 +124,21 @@ impl OpenAiClient {
             instructions: system,
             input: &input,
             tools: tool_defs.as_deref(),
+            stream: false,
         };
         let text = self.send_json("/responses", &body).await?;
         parse_responses_response(&text)
     }
 
     async fn send_json(&self, path: &str, body: &impl Serialize) -> Result<String, LlmError> {
+        self.send(path, body)
+            .await?
+            .text()
+            .await
+            .map_err(|e| LlmError::ApiRequest(e.to_string()))
+    }
+
+    async fn send(&self, path: &str, body: &impl Serialize) -> Result<reqwest::Response, LlmError> {
         let url = format!("{}{}", self.base_url, path);
         let response = self
             .http
@@ -100,14 +150,15 @@ impl OpenAiClient {
             .map_err(|e| LlmError::ApiRequest(e.to_string()))?;
 
         let status = response.status().as_u16();
-        let text = response
-            .text()
-            .await
-            .map_err(|e| LlmError::ApiRequest(e.to_string()))?;
         if status != 200 {
-            return Err(LlmError::ApiResponse { status, body: text });
+            let retry_after_secs = retry_after_secs(response.headers());
+            let text = response
+                .text()
+                .await
+                .map_err(|e| LlmError::ApiRequest(e.to_string()))?;
+            return Err(LlmError::ApiResponse { status, body: text, retry_after_secs });
         }
-        Ok(text)
+        Ok(response)
     }
 }
 
@@ -122,6 +173,29 @@ struct CcRequest<'a> {
     messages: &'a [CcMessage],
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<&'a [CcToolDef<'a>]>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<CcStreamOptions>,
+}
+
+impl<'a> CcRequest<'a> {
+    fn new(
+        model: &'a str,
+        max_tokens: u32,
+        messages: &'a [CcMessage],
+        tools: Option<&'a [CcToolDef<'a>]>,
+        stream: bool,
+    ) -> Self {
+        // Usage is only reported on a stream when asked for explicitly.
+        let stream_options = stream.then_some(CcStreamOptions { include_usage: true });
+        Self { model, max_tokens, messages, tools, stream, stream_options }
+    }
+}
+
+#[derive(Serialize)]
+struct CcStreamOptions {
+    include_usage: bool,
 }
 
 #[derive(Serialize)]
@@ -251,6 +325,8 @@ struct RespRequest<'a> {
     input: &'a [RespInputItem],
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<&'a [RespToolDef<'a>]>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
 #[derive(Serialize)]
@@ -348,6 +424,22 @@ pub(crate) fn build_responses_input(messages: &[Message]) -> Vec<RespInputItem>
 // RESPONSE PARSING
 // =============================================================================
 
+/// Read `(uncached input, cached input, output)` token counts from a usage
+/// object. The provider counts cache hits inside the input total and reports
+/// them separately under `<details_key>.cached_tokens`.
+fn usage_tokens(usage: Option<&Value>, input_key: &str, details_key: &str, output_key: &str) -> (u64, u64, u64) {
+    let count = |value: Option<&Value>| value.and_then(Value::as_u64).unwrap_or(0);
+    let input = count(usage.and_then(|u| u.get(input_key)));
+    let cached = count(
+        usage
+            .and_then(|u| u.get(details_key))
+            .and_then(|d| d.get("cached_tokens")),
+    )
+    .min(input);
+    let output = count(usage.and_then(|u| u.get(output_key)));
+    (input - cached, cached, output)
+}
+
 pub(crate) fn parse_chat_completions_response(json_text: &str) -> Result<ChatResponse, LlmError> {
     let root: Value = serde_json::from_str(json_text).map_err(|e| LlmError::ApiParse(e.to_string()))?;
     let model = root
@@ -355,16 +447,8 @@ pub(crate) fn parse_chat_completions_response(json_text: &str) -> Result<ChatRes
         .and_then(Value::as_str)
         .map(str::to_owned)
         .unwrap_or_default();
-    let prompt_tokens = root
-        .get("usage")
-        .and_then(|u| u.get("prompt_tokens"))
-        .and_then(Value::as_u64)
-        .unwrap_or(0);
-    let completion_tokens = root
-        .get("usage")
-        .and_then(|u| u.get("completion_tokens"))
-        .and_then(Value::as_u64)
-        .unwrap_or(0);
+    let (prompt_tokens, cached_tokens, completion_tokens) =
+        usage_tokens(root.get("usage"), "prompt_tokens", "prompt_tokens_details", "completion_tokens");
 
     let Some(choice) = root
         .get("choices")
@@ -420,7 +504,15 @@ pub(crate) fn parse_chat_completions_response(json_text: &str) -> Result<ChatRes
         "end_turn".to_string()
     };
 
-    Ok(ChatResponse { content, model, stop_reason, input_tokens: prompt_tokens, output_tokens: completion_tokens })
+    Ok(ChatResponse {
+        content,
+        model,
+        stop_reason,
+        input_tokens: prompt_tokens,
+        output_tokens: completion_tokens,
+        cache_read_tokens: cached_tokens,
+        cache_creation_tokens: 0,
+    })
 }
 
 pub(crate) fn parse_responses_response(json_text: &str) -> Result<ChatResponse, LlmError> {
@@ -430,16 +522,8 @@ pub(crate) fn parse_responses_response(json_text: &str) -> Result<ChatResponse,
         .and_then(Value::as_str)
         .map(str::to_owned)
         .unwrap_or_default();
-    let input_tokens = root
-        .get("usage")
-        .and_then(|u| u.get("input_tokens"))
-        .and_then(Value::as_u64)
-        .unwrap_or(0);
-    let output_tokens = root
-        .get("usage")
-        .and_then(|u| u.get("output_tokens"))
-        .and_then(Value::as_u64)
-        .unwrap_or(0);
+    let (input_tokens, cached_tokens, output_tokens) =
+        usage_tokens(root.get("usage"), "input_tokens", "input_tokens_details", "output_tokens");
 
     let mut content = Vec::new();
     if let Some(items) = root.get("output").and_then(Value::as_array) {
@@ -506,7 +590,195 @@ pub(crate) fn parse_responses_response(json_text: &str) -> Result<ChatResponse,
         "end_turn".to_string()
     };
 
-    Ok(ChatResponse { content, model, stop_reason, input_tokens, output_tokens })
+    Ok(ChatResponse {
+        content,
+        model,
+        stop_reason,
+        input_tokens,
+        output_tokens,
+        cache_read_tokens: cached_tokens,
+        cache_creation_tokens: 0,
+    })
+}
+
+// =============================================================================
+// STREAMING
+// =============================================================================
+
+/// A tool call being assembled from chat completion chunks.
+#[derive(Default)]
+struct CcPartialCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// Accumulates chat completion stream chunks into a [`ChatResponse`].
+#[derive(Default)]
+pub(crate) struct CcStreamState {
+    model: String,
+    text: String,
+    calls: Vec<CcPartialCall>,
+    finish_reason: Option<String>,
+    prompt_tokens: u64,
+    cached_tokens: u64,
+    completion_tokens: u64,
+    done: bool,
+}
+
+impl CcStreamState {
+    /// Apply one chunk, forwarding text deltas to `deltas`.
+    pub(crate) fn apply(&mut self, event: &SseEvent, deltas: &DeltaSink) -> Result<(), LlmError> {
+        if event.data.trim() == "[DONE]" {
+            self.done = true;
+            return Ok(());
+        }
+        let chunk: Value = serde_json::from_str(&event.data).map_err(|e| LlmError::ApiParse(e.to_string()))?;
+        if let Some(message) = chunk
+            .get("error")
+            .and_then(|e| e.get("message"))
+            .and_then(Value::as_str)
+        {
+            return Err(LlmError::ApiStream(message.to_owned()));
+        }
+        if let Some(model) = chunk.get("model").and_then(Value::as_str) {
+            model.clone_into(&mut self.model);
+        }
+        if let Some(usage) = chunk.get("usage").filter(|u| !u.is_null()) {
+            (self.prompt_tokens, self.cached_tokens, self.completion_tokens) =
+                usage_tokens(Some(usage), "prompt_tokens", "prompt_tokens_details", "completion_tokens");
+        }
+        let Some(choice) = chunk
+            .get("choices")
+            .and_then(Value::as_array)
+            .and_then(|arr| arr.first())
+        else {
+            return Ok(());
+        };
+        if let Some(reason) = choice.get("finish_reason").and_then(Value::as_str) {
+            self.finish_reason = Some(reason.to_owned());
+        }
+        let delta = choice.get("delta").unwrap_or(&Value::Null);
+        if let Some(piece) = delta.get("content").and_then(Value::as_str)
+            && !piece.is_empty()
+        {
+            self.text.push_str(piece);
+            let _ = deltas.send(ChatDelta::Text(piece.to_owned()));
+        }
+        for call in delta
+            .get("tool_calls")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let index = call
+                .get("index")
+                .and_then(Value::as_u64)
+                .and_then(|i| usize::try_from(i).ok())
+                .unwrap_or(self.calls.len());
+            if self.calls.len() <= index {
+                self.calls.resize_with(index + 1, CcPartialCall::default);
+            }
+            let partial = &mut self.calls[index];
+            if let Some(id) = call.get("id").and_then(Value::as_str) {
+                id.clone_into(&mut partial.id);
+            }
+            let function = call.get("function").unwrap_or(&Value::Null);
+            if let Some(name) = function.get("name").and_then(Value::as_str) {
+                partial.name.push_str(name);
+            }
+            if let Some(arguments) = function.get("arguments").and_then(Value::as_str) {
+                partial.arguments.push_str(arguments);
+            }
+        }
+        Ok(())
+    }
+
+    /// Finish the stream and return the assembled response.
+    pub(crate) fn finish(self) -> Result<ChatResponse, LlmError> {
+        if !self.done && self.finish_reason.is_none() {
+            return Err(LlmError::ApiStream("chat_completions: stream ended before finish".to_string()));
+        }
+        let mut content = Vec::new();
+        if !self.text.is_empty() {
+            content.push(ContentBlock::Text { text: self.text });
+        }
+        for call in self.calls.into_iter().filter(|call| !call.name.is_empty()) {
+            if call.id.is_empty() {
+                return Err(LlmError::ApiParse("chat_completions: tool call missing id".to_string()));
+            }
+            let input = serde_json::from_str::<Value>(&call.arguments)
+                .unwrap_or_else(|_| Value::Object(serde_json::Map::default()));
+            content.push(ContentBlock::ToolUse { id: call.id, name: call.name, input });
+        }
+        let stop_reason = if content
+            .iter()
+            .any(|block| matches!(block, ContentBlock::ToolUse { .. }))
+        {
+            "tool_use".to_string()
+        } else if self.finish_reason.as_deref() == Some("length") {
+            "max_tokens".to_string()
+        } else {
+            "end_turn".to_string()
+        };
+        Ok(ChatResponse {
+            content,
+            model: self.model,
+            stop_reason,
+            input_tokens: self.prompt_tokens,
+            output_tokens: self.completion_tokens,
+            cache_read_tokens: self.cached_tokens,
+            cache_creation_tokens: 0,
+        })
+    }
+}
+
+/// Accumulates Responses API stream events into a [`ChatResponse`].
+///
+/// Text deltas are forwarded as they arrive; the final content, including
+/// completed function calls, comes from the `response.completed` event.
+#[derive(Default)]
+pub(crate) struct RespStreamState {
+    completed: Option<ChatResponse>,
+}
+
+impl RespStreamState {
+    /// Apply one event, forwarding text deltas to `deltas`.
+    pub(crate) fn apply(&mut self, event: &SseEvent, deltas: &DeltaSink) -> Result<(), LlmError> {
+        let data: Value = serde_json::from_str(&event.data).map_err(|e| LlmError::ApiParse(e.to_string()))?;
+        match data.get("type").and_then(Value::as_str) {
+            Some("response.output_text.delta") => {
+                if let Some(piece) = data.get("delta").and_then(Value::as_str)
+                    && !piece.is_empty()
+                {
+                    let _ = deltas.send(ChatDelta::Text(piece.to_owned()));
+                }
+            }
+            Some("response.completed" | "response.incomplete") => {
+                let response = data.get("response").unwrap_or(&Value::Null);
+                self.completed = Some(parse_responses_response(&response.to_string())?);
+            }
+            Some("response.failed" | "error") => {
+                let message = data
+                    .get("response")
+                    .and_then(|r| r.get("error"))
+                    .or_else(|| data.get("error"))
+                    .and_then(|e| e.get("message"))
+                    .or_else(|| data.get("message"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("stream error");
+                return Err(LlmError::ApiStream(message.to_owned()));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Finish the stream and return the completed response.
+    pub(crate) fn finish(self) -> Result<ChatResponse, LlmError> {
+        self.completed
+            .ok_or_else(|| LlmError::ApiStream("responses: stream ended before completion".to_string()))
+    }
 }
 
 #[cfg(test)]
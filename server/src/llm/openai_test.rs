@@ -22,6 +22,19 @@ fn cc_parse_text_response() {
     assert_eq!(resp.input_tokens, 10);
 }
 
+#[test]
+fn cc_parse_splits_cached_prompt_tokens() {
+    let json = serde_json::json!({
+        "model": "gpt-4o",
+        "choices": [{ "index": 0, "message": { "role": "assistant", "content": "Hi" }, "finish_reason": "stop" }],
+        "usage": { "prompt_tokens": 1200, "completion_tokens": 5, "prompt_tokens_details": { "cached_tokens": 1024 } }
+    })
+    .to_string();
+    let resp = parse_chat_completions_response(&json).unwrap();
+    assert_eq!((resp.input_tokens, resp.cache_read_tokens, resp.output_tokens), (176, 1024, 5));
+    assert_eq!(resp.total_input_tokens(), 1200);
+}
+
 #[test]
 fn cc_parse_tool_call() {
     let json = serde_json::json!({
@@ -130,6 +143,18 @@ fn resp_parse_text_response() {
     assert_eq!(resp.stop_reason, "end_turn");
 }
 
+#[test]
+fn resp_parse_splits_cached_input_tokens() {
+    let json = serde_json::json!({
+        "model": "gpt-4o",
+        "output": [{ "type": "message", "content": [{ "type": "output_text", "text": "Done!" }] }],
+        "usage": { "input_tokens": 2048, "output_tokens": 8, "input_tokens_details": { "cached_tokens": 2000 } }
+    })
+    .to_string();
+    let resp = parse_responses_response(&json).unwrap();
+    assert_eq!((resp.input_tokens, resp.cache_read_tokens, resp.output_tokens), (48, 2000, 8));
+}
+
 #[test]
 fn resp_parse_function_call() {
     let json = serde_json::json!({
@@ -351,3 +376,95 @@ fn build_resp_input_text_block_becomes_message() {
     assert_eq!(json["type"], "message");
     assert_eq!(json["content"][0]["text"], "response text");
 }
+
+// ===== streaming =====
+
+fn data_event(data: serde_json::Value) -> SseEvent {
+    SseEvent { event: None, data: data.to_string() }
+}
+
+#[test]
+fn cc_stream_forwards_text_and_assembles_tool_calls() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut state = CcStreamState::default();
+    let chunks = [
+        serde_json::json!({ "model": "gpt-4o", "choices": [{ "index": 0, "delta": { "role": "assistant", "content": "Sure" } }] }),
+        serde_json::json!({ "choices": [{ "index": 0, "delta": { "tool_calls": [{ "index": 0, "id": "call_1", "function": { "name": "createShape", "arguments": "{\"ty" } }] } }] }),
+        serde_json::json!({ "choices": [{ "index": 0, "delta": { "tool_calls": [{ "index": 0, "function": { "arguments": "pe\":\"rectangle\"}" } }] } }] }),
+        serde_json::json!({ "choices": [{ "index": 0, "delta": {}, "finish_reason": "tool_calls" }] }),
+        serde_json::json!({ "choices": [], "usage": { "prompt_tokens": 9, "completion_tokens": 4, "prompt_tokens_details": { "cached_tokens": 6 } } }),
+    ];
+    for chunk in chunks {
+        state.apply(&data_event(chunk), &tx).unwrap();
+    }
+    state
+        .apply(&SseEvent { event: None, data: "[DONE]".into() }, &tx)
+        .unwrap();
+
+    assert_eq!(rx.try_recv().unwrap(), ChatDelta::Text("Sure".into()));
+    let resp = state.finish().unwrap();
+    assert_eq!(resp.model, "gpt-4o");
+    assert_eq!(resp.stop_reason, "tool_use");
+    assert_eq!((resp.input_tokens, resp.cache_read_tokens, resp.output_tokens), (3, 6, 4));
+    assert!(matches!(
+        &resp.content[1],
+        ContentBlock::ToolUse { id, name, input } if id == "call_1" && name == "createShape" && input["type"] == "rectangle"
+    ));
+}
+
+#[test]
+fn cc_stream_without_finish_is_an_error() {
+    let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut state = CcStreamState::default();
+    state
+        .apply(
+            &data_event(serde_json::json!({ "choices": [{ "index": 0, "delta": { "content": "cut" } }] })),
+            &tx,
+        )
+        .unwrap();
+    assert!(matches!(state.finish(), Err(LlmError::ApiStream(_))));
+}
+
+#[test]
+fn resp_stream_forwards_text_and_uses_completed_response() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut state = RespStreamState::default();
+    state
+        .apply(
+            &data_event(serde_json::json!({ "type": "response.output_text.delta", "delta": "Hi" })),
+            &tx,
+        )
+        .unwrap();
+    state
+        .apply(
+            &data_event(serde_json::json!({
+                "type": "response.completed",
+                "response": {
+                    "model": "gpt-4.1",
+                    "output": [
+                        { "type": "message", "content": [{ "type": "output_text", "text": "Hi" }] },
+                        { "type": "function_call", "call_id": "fc_1", "name": "moveObject", "arguments": "{\"x\":1}" }
+                    ],
+                    "usage": { "input_tokens": 7, "output_tokens": 3 }
+                }
+            })),
+            &tx,
+        )
+        .unwrap();
+
+    assert_eq!(rx.try_recv().unwrap(), ChatDelta::Text("Hi".into()));
+    let resp = state.finish().unwrap();
+    assert_eq!(resp.stop_reason, "tool_use");
+    assert_eq!(resp.content.len(), 2);
+}
+
+#[test]
+fn resp_stream_failed_event_is_an_error() {
+    let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut state = RespStreamState::default();
+    let failed = data_event(serde_json::json!({
+        "type": "response.failed",
+        "response": { "error": { "message": "server overloaded" } }
+    }));
+    assert!(matches!(state.apply(&failed, &tx), Err(LlmError::ApiStream(msg)) if msg == "server overloaded"));
+}
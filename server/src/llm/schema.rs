@@ -0,0 +1,150 @@
+//! Tool input validation — checks LLM tool calls against their declared schema.
+//!
+//! DESIGN
+//! ======
+//! Implements the JSON-Schema subset the board tools actually declare:
+//! `type`, `properties`, `required`, `enum`, `items`, `format: "uuid"`, and
+//! numeric/array bounds. Unknown keywords are ignored rather than rejected so
+//! a richer schema never blocks a tool call. Every violation is collected with
+//! its path so the model can fix all of them in one retry.
+
+use serde_json::Value;
+
+/// One schema violation at `path` (e.g. `input.objectIds[0]`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Validate `input` against `schema`, returning every violation found.
+#[must_use]
+pub fn validate(schema: &Value, input: &Value) -> Vec<SchemaViolation> {
+    let mut violations = Vec::new();
+    validate_at(schema, input, "input", &mut violations);
+    violations
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str, violations: &mut Vec<SchemaViolation>) {
+    let mut violate = |message: String| violations.push(SchemaViolation { path: path.to_owned(), message });
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| matches_type(t, value)) {
+            violate(format!("expected {}, got {}", types.join(" or "), type_name(value)));
+            // Nested checks on a value of the wrong type only add noise.
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            let options = allowed
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            violate(format!("must be one of {options}"));
+        }
+    }
+
+    if schema.get("format").and_then(Value::as_str) == Some("uuid") {
+        if let Some(s) = value.as_str() {
+            if uuid::Uuid::parse_str(s).is_err() {
+                violate(format!("{s:?} is not a valid UUID"));
+            }
+        }
+    }
+
+    if let Some(n) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+            if n < min {
+                violate(format!("must be >= {min}"));
+            }
+        }
+        if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+            if n > max {
+                violate(format!("must be <= {max}"));
+            }
+        }
+    }
+
+    if let Some(map) = value.as_object() {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for key in required.iter().filter_map(Value::as_str) {
+                if map.get(key).is_none_or(Value::is_null) {
+                    violations.push(SchemaViolation { path: format!("{path}.{key}"), message: "is required".into() });
+                }
+            }
+        }
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            for (key, property_schema) in properties {
+                // Explicit nulls read as "not provided", matching how the executors treat them.
+                if let Some(property) = map.get(key).filter(|v| !v.is_null()) {
+                    validate_at(property_schema, property, &format!("{path}.{key}"), violations);
+                }
+            }
+        }
+    } else if let Some(items) = value.as_array() {
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+            if (items.len() as u64) < min {
+                violations.push(SchemaViolation {
+                    path: path.to_owned(),
+                    message: format!("must have at least {min} item(s)"),
+                });
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+            if (items.len() as u64) > max {
+                violations.push(SchemaViolation {
+                    path: path.to_owned(),
+                    message: format!("must have at most {max} item(s)"),
+                });
+            }
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                validate_at(item_schema, item, &format!("{path}[{i}]"), violations);
+            }
+        }
+    }
+}
+
+fn matches_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        // Unknown type names are not ours to enforce.
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+#[path = "schema_test.rs"]
+mod tests;
@@ -0,0 +1,105 @@
+use serde_json::json;
+
+use super::*;
+
+fn messages(schema: &Value, input: &Value) -> Vec<String> {
+    validate(schema, input)
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn sticky_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "text": { "type": "string" },
+            "x": { "type": "number" },
+            "limit": { "type": "integer", "minimum": 1, "maximum": 50 },
+            "kind": { "type": "string", "enum": ["sticky_note", "frame"] },
+            "objectIds": { "type": "array", "items": { "type": "string", "format": "uuid" } }
+        },
+        "required": ["text"]
+    })
+}
+
+#[test]
+fn valid_input_has_no_violations() {
+    let input = json!({
+        "text": "hi",
+        "x": 1.5,
+        "limit": 10,
+        "kind": "frame",
+        "objectIds": ["6f1c0d0e-3a1b-4b8e-9a55-9a0e8f3b7c21"]
+    });
+    assert!(validate(&sticky_schema(), &input).is_empty());
+}
+
+#[test]
+fn missing_required_field_is_reported() {
+    assert_eq!(messages(&sticky_schema(), &json!({})), vec!["input.text: is required"]);
+}
+
+#[test]
+fn null_required_field_counts_as_missing() {
+    assert_eq!(
+        messages(&sticky_schema(), &json!({ "text": null })),
+        vec!["input.text: is required"]
+    );
+}
+
+#[test]
+fn wrong_type_names_expected_and_actual() {
+    let got = messages(&sticky_schema(), &json!({ "text": "hi", "x": "10" }));
+    assert_eq!(got, vec!["input.x: expected number, got string"]);
+}
+
+#[test]
+fn integer_rejects_fractions_but_accepts_whole_floats() {
+    let got = messages(&sticky_schema(), &json!({ "text": "hi", "limit": 2.5 }));
+    assert_eq!(got, vec!["input.limit: expected integer, got number"]);
+    assert!(validate(&sticky_schema(), &json!({ "text": "hi", "limit": 3.0 })).is_empty());
+}
+
+#[test]
+fn numeric_bounds_are_enforced() {
+    let got = messages(&sticky_schema(), &json!({ "text": "hi", "limit": 0 }));
+    assert_eq!(got, vec!["input.limit: must be >= 1"]);
+}
+
+#[test]
+fn enum_lists_allowed_values() {
+    let got = messages(&sticky_schema(), &json!({ "text": "hi", "kind": "circle" }));
+    assert_eq!(got, vec![r#"input.kind: must be one of "sticky_note", "frame""#]);
+}
+
+#[test]
+fn array_items_are_checked_with_index_paths() {
+    let got = messages(&sticky_schema(), &json!({ "text": "hi", "objectIds": ["not-a-uuid", 7] }));
+    assert_eq!(
+        got,
+        vec![
+            r#"input.objectIds[0]: "not-a-uuid" is not a valid UUID"#,
+            "input.objectIds[1]: expected string, got number",
+        ]
+    );
+}
+
+#[test]
+fn all_violations_are_collected() {
+    let got = messages(&sticky_schema(), &json!({ "x": true, "kind": "circle" }));
+    assert_eq!(got.len(), 3);
+}
+
+#[test]
+fn unknown_properties_are_allowed() {
+    assert!(validate(&sticky_schema(), &json!({ "text": "hi", "extra": 1 })).is_empty());
+}
+
+#[test]
+fn non_object_input_reports_root_type() {
+    assert_eq!(
+        messages(&sticky_schema(), &json!("hi")),
+        vec!["input: expected object, got string"]
+    );
+}
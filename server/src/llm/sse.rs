@@ -0,0 +1,113 @@
+//! Server-sent events decoder for streamed LLM responses.
+//!
+//! Both providers stream `text/event-stream` bodies. Network chunks can split
+//! a line, or a multi-byte character, anywhere, so bytes are buffered until a
+//! full line arrives and events are emitted once their blank-line terminator
+//! is seen.
+
+use super::types::LlmError;
+
+/// One decoded server-sent event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The `event:` field, if the server sent one.
+    pub event: Option<String>,
+    /// The `data:` lines joined with newlines.
+    pub data: String,
+}
+
+/// Incremental decoder fed with raw response chunks.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buf: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    /// Feed one chunk and return every event it completed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LlmError::ApiStream`] if a line is not valid UTF-8.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Vec<SseEvent>, LlmError> {
+        self.buf.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(pos) = self.buf.iter().position(|b| *b == b'\n') {
+            let raw = self.buf.drain(..=pos).collect::<Vec<_>>();
+            let line = std::str::from_utf8(&raw)
+                .map_err(|e| LlmError::ApiStream(e.to_string()))?
+                .trim_end_matches(['\n', '\r']);
+            if let Some(event) = self.line(line) {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+
+    /// Flush an event left unterminated when the body ends.
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        if !self.buf.is_empty() {
+            let rest = std::mem::take(&mut self.buf);
+            let line = String::from_utf8_lossy(&rest)
+                .trim_end_matches('\r')
+                .to_owned();
+            if let Some(event) = self.line(&line) {
+                return Some(event);
+            }
+        }
+        self.line("")
+    }
+
+    fn line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            if self.data.is_empty() {
+                self.event = None;
+                return None;
+            }
+            let data = std::mem::take(&mut self.data).join("\n");
+            return Some(SseEvent { event: self.event.take(), data });
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => self.event = Some(value.to_owned()),
+            "data" => self.data.push(value.to_owned()),
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Read a streamed HTTP body and hand each event to `on_event`.
+///
+/// # Errors
+///
+/// Returns [`LlmError::ApiRequest`] if reading the body fails, or whatever
+/// `on_event` returns.
+pub(crate) async fn read_events(
+    mut response: reqwest::Response,
+    mut on_event: impl FnMut(SseEvent) -> Result<(), LlmError>,
+) -> Result<(), LlmError> {
+    let mut decoder = SseDecoder::default();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| LlmError::ApiRequest(e.to_string()))?
+    {
+        for event in decoder.push(&chunk)? {
+            on_event(event)?;
+        }
+    }
+    if let Some(event) = decoder.finish() {
+        on_event(event)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "sse_test.rs"]
+mod tests;
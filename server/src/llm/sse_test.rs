@@ -0,0 +1,40 @@
+use super::*;
+
+#[test]
+fn decodes_events_split_across_chunks() {
+    let mut decoder = SseDecoder::default();
+    assert!(decoder.push(b"event: ping\nda").unwrap().is_empty());
+    let events = decoder
+        .push(b"ta: {}\n\ndata: one\r\ndata: two\n\n")
+        .unwrap();
+    assert_eq!(
+        events,
+        vec![
+            SseEvent { event: Some("ping".into()), data: "{}".into() },
+            SseEvent { event: None, data: "one\ntwo".into() },
+        ]
+    );
+}
+
+#[test]
+fn keeps_multibyte_characters_split_between_chunks() {
+    let mut decoder = SseDecoder::default();
+    let bytes = "data: héllo\n\n".as_bytes();
+    let (head, tail) = bytes.split_at(8);
+    assert!(decoder.push(head).unwrap().is_empty());
+    let events = decoder.push(tail).unwrap();
+    assert_eq!(events[0].data, "héllo");
+}
+
+#[test]
+fn ignores_comments_and_flushes_trailing_event() {
+    let mut decoder = SseDecoder::default();
+    assert!(
+        decoder
+            .push(b": keep-alive\n\ndata: [DONE]")
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(decoder.finish(), Some(SseEvent { event: None, data: "[DONE]".into() }));
+    assert_eq!(decoder.finish(), None);
+}
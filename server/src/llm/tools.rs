@@ -7,8 +7,15 @@
 //! Definitions are provider-agnostic and converted by adapters, keeping the
 //! command surface stable even when LLM backend implementations change.
 
+use std::sync::OnceLock;
+
+use super::schema::{self, SchemaViolation};
 use super::types::Tool;
 
+/// Tools that only read the board. These are the only tools `ai:ask` offers
+/// and the only ones the executor runs in read-only mode.
+pub const READ_ONLY_TOOL_NAMES: &[&str] = &["getBoardState", "searchBoard", "summarizeFrame", "exportMermaid"];
+
 /// Build the set of tools available to the `CollabBoard` AI agent.
 ///
 /// Returns the standard board tools.
@@ -17,6 +24,41 @@ pub fn gauntlet_week_1_tools() -> Vec<Tool> {
     board_tools()
 }
 
+/// Build the read-only subset of the board tools for viewer Q&A.
+#[must_use]
+pub fn read_only_tools() -> Vec<Tool> {
+    board_tools()
+        .into_iter()
+        .filter(|tool| is_read_only_tool(&tool.name))
+        .collect()
+}
+
+/// Whether `name` is a tool that cannot change the board.
+#[must_use]
+pub fn is_read_only_tool(name: &str) -> bool {
+    READ_ONLY_TOOL_NAMES.contains(&name)
+}
+
+/// Validate a tool call's input against that tool's declared `input_schema`.
+///
+/// Unknown tool names pass; the executor reports those itself.
+///
+/// # Errors
+///
+/// Returns every schema violation when the input does not conform.
+pub fn validate_tool_input(name: &str, input: &serde_json::Value) -> Result<(), Vec<SchemaViolation>> {
+    static TOOLS: OnceLock<Vec<Tool>> = OnceLock::new();
+    let Some(tool) = TOOLS
+        .get_or_init(board_tools)
+        .iter()
+        .find(|tool| tool.name == name)
+    else {
+        return Ok(());
+    };
+    let violations = schema::validate(&tool.input_schema, input);
+    if violations.is_empty() { Ok(()) } else { Err(violations) }
+}
+
 #[must_use]
 #[allow(clippy::too_many_lines)]
 pub(crate) fn board_tools() -> Vec<Tool> {
@@ -328,16 +370,24 @@ pub(crate) fn board_tools() -> Vec<Tool> {
         },
         Tool {
             name: "createMermaidDiagram".into(),
-            description: "Parse Mermaid sequence diagram syntax and render it as native board objects (rectangles, \
-                          arrows, text, connectors, frames). Supports participants, messages (solid/dashed/open/cross \
-                          arrows), notes, activation bars, and control flow blocks (loop, alt, opt, par, critical, \
-                          break). Use for directed-path requests such as user journey maps, flow charts, process \
-                          flows, state transitions, and step-by-step pipelines."
+            description: "Parse Mermaid flowchart, state, class, or sequence diagram syntax and render it as native \
+                          board objects (shapes, connectors, text, frames). Flowcharts (`flowchart TD|LR|BT|RL` or \
+                          `graph`) support rect/round/diamond/circle nodes, solid/dotted/thick links with labels, and \
+                          subgraphs, laid out in layers. State diagrams (`stateDiagram-v2`) support states, `[*]` \
+                          start/end, composite states, choice/fork states, and labeled transitions. Class diagrams \
+                          (`classDiagram`) support classes with members and annotations, and inheritance, composition, \
+                          aggregation, and association relations with labels and cardinalities. Sequence diagrams \
+                          support participants, messages, notes, activation bars, and control flow blocks. Use \
+                          flowcharts for user journey maps, flow charts, process flows, and step-by-step pipelines; \
+                          state diagrams for lifecycles and state machines; class diagrams for data models."
                 .into(),
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
-                    "mermaid": { "type": "string", "description": "Mermaid sequenceDiagram syntax" },
+                    "mermaid": {
+                        "type": "string",
+                        "description": "Mermaid flowchart, stateDiagram-v2, classDiagram, or sequenceDiagram syntax"
+                    },
                     "x": {
                         "type": "number",
                         "description": "Optional X origin in world coordinates (board-space)"
@@ -405,6 +455,55 @@ pub(crate) fn board_tools() -> Vec<Tool> {
                 "properties": {}
             }),
         },
+        Tool {
+            name: "searchBoard".into(),
+            description: "Find objects whose text, title, or label contains the query (case-insensitive). \
+                          Returns each match with its position and the frame it sits in."
+                .into(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Text to search for" },
+                    "kind": { "type": "string", "description": "Only match objects of this kind (e.g. sticky_note, frame)" },
+                    "limit": { "type": "integer", "description": "Maximum matches to return (default 20, max 50)" }
+                },
+                "required": ["query"]
+            }),
+        },
+        Tool {
+            name: "summarizeFrame".into(),
+            description: "Collect the text content of a frame in reading order (top to bottom, left to right), \
+                          with counts by kind and the connections between its objects. Identify the frame by \
+                          frameId or by title. Use this to answer questions about what a frame contains."
+                .into(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "frameId": { "type": "string", "description": "ID of the frame to summarize" },
+                    "title": { "type": "string", "description": "Frame title to look up (case-insensitive) when the ID is unknown" }
+                }
+            }),
+        },
+        Tool {
+            name: "exportMermaid".into(),
+            description: "Export board shapes and the connectors attached between them as Mermaid `flowchart` \
+                          text, with labels taken from object text and frames as subgraphs. Scope the export to \
+                          a frame with frameId or to a selection with objectIds; omit both for the whole board. \
+                          Use this when the user wants a diagram as text for docs, issues, or PR descriptions."
+                .into(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "frameId": { "type": "string", "format": "uuid", "description": "Export only this frame's contents" },
+                    "objectIds": {
+                        "type": "array",
+                        "items": { "type": "string", "format": "uuid" },
+                        "minItems": 1,
+                        "description": "Export only these objects and the connectors between them"
+                    }
+                }
+            }),
+        },
     ]
 }
 
@@ -25,6 +25,9 @@ fn gauntlet_tools_match_board_tools() {
     assert!(names.contains(&"createMermaidDiagram"));
     assert!(names.contains(&"createAnimationClip"));
     assert!(names.contains(&"getBoardState"));
+    assert!(names.contains(&"searchBoard"));
+    assert!(names.contains(&"summarizeFrame"));
+    assert!(names.contains(&"exportMermaid"));
 }
 
 #[test]
@@ -41,9 +44,9 @@ fn schema_shape_is_object() {
 }
 
 #[test]
-fn board_tools_returns_all_nineteen_tools() {
+fn board_tools_returns_all_twenty_two_tools() {
     let tools = board_tools();
-    assert_eq!(tools.len(), 19);
+    assert_eq!(tools.len(), 22);
 }
 
 #[test]
@@ -304,3 +307,43 @@ fn create_mermaid_diagram_requires_mermaid() {
     assert!(props.get("y").is_some());
     assert!(props.get("scale").is_some());
 }
+
+#[test]
+fn read_only_tools_are_exactly_the_read_only_names() {
+    let names: Vec<String> = read_only_tools().into_iter().map(|t| t.name).collect();
+    assert_eq!(names.len(), READ_ONLY_TOOL_NAMES.len());
+    for name in READ_ONLY_TOOL_NAMES {
+        assert!(names.iter().any(|n| n == name), "missing {name}");
+    }
+}
+
+#[test]
+fn mutating_tools_are_not_read_only() {
+    for name in ["createStickyNote", "deleteObject", "moveObject", "updateText", "swot"] {
+        assert!(!is_read_only_tool(name), "{name} should not be read-only");
+    }
+    assert!(is_read_only_tool("getBoardState"));
+}
+
+#[test]
+fn search_board_requires_query() {
+    let tools = board_tools();
+    let tool = tools.iter().find(|t| t.name == "searchBoard").unwrap();
+    assert_eq!(tool.input_schema["required"], serde_json::json!(["query"]));
+}
+
+#[test]
+fn validate_tool_input_accepts_conforming_input() {
+    assert!(validate_tool_input("createStickyNote", &serde_json::json!({ "text": "hi", "x": 1 })).is_ok());
+}
+
+#[test]
+fn validate_tool_input_reports_violations() {
+    let violations = validate_tool_input("moveObject", &serde_json::json!({ "x": 1 })).unwrap_err();
+    assert!(violations.iter().any(|v| v.path == "input.objectId"));
+}
+
+#[test]
+fn validate_tool_input_ignores_unknown_tools() {
+    assert!(validate_tool_input("nonexistent_tool", &serde_json::json!(null)).is_ok());
+}
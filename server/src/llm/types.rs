@@ -25,8 +25,14 @@ pub enum LlmError {
     ApiRequest(String),
 
     /// The LLM provider returned a non-success HTTP status.
+    ///
+    /// `retry_after_secs` carries the provider's `Retry-After` header, if any.
     #[error("API response error: status {status}")]
-    ApiResponse { status: u16, body: String },
+    ApiResponse {
+        status: u16,
+        body: String,
+        retry_after_secs: Option<u64>,
+    },
 
     /// The LLM provider response body could not be deserialized.
     #[error("API response parse failed: {0}")]
@@ -35,6 +41,14 @@ pub enum LlmError {
     /// The underlying HTTP client could not be constructed.
     #[error("HTTP client build failed: {0}")]
     HttpClientBuild(String),
+
+    /// A streamed response reported an error or ended before completing.
+    #[error("API stream failed: {0}")]
+    ApiStream(String),
+
+    /// A record/replay cassette could not be read, written, or matched.
+    #[error("cassette error: {0}")]
+    Cassette(String),
 }
 
 impl crate::frame::ErrorCode for LlmError {
@@ -46,11 +60,16 @@ impl crate::frame::ErrorCode for LlmError {
             Self::ApiResponse { .. } => "E_API_RESPONSE",
             Self::ApiParse(_) => "E_API_PARSE",
             Self::HttpClientBuild(_) => "E_HTTP_CLIENT_BUILD",
+            Self::ApiStream(_) => "E_API_STREAM",
+            Self::Cassette(_) => "E_LLM_CASSETTE",
         }
     }
 
     fn retryable(&self) -> bool {
-        matches!(self, Self::ApiRequest(_) | Self::ApiResponse { status: 429 | 500..=599, .. })
+        matches!(
+            self,
+            Self::ApiRequest(_) | Self::ApiStream(_) | Self::ApiResponse { status: 429 | 500..=599, .. }
+        )
     }
 }
 
@@ -108,6 +127,16 @@ pub enum Content {
     Blocks(Vec<ContentBlock>),
 }
 
+// =============================================================================
+// SYSTEM PROMPT
+// =============================================================================
+
+/// Header that starts the per-request board context in a system prompt.
+///
+/// Everything before it is identical across requests, so providers with
+/// explicit prompt caching place their cache breakpoint there.
+pub const SYSTEM_CONTEXT_HEADER: &str = "\n\nBoard context summary:\n";
+
 // =============================================================================
 // TOOL DEFINITION
 // =============================================================================
@@ -137,10 +166,98 @@ pub struct ChatResponse {
     pub content: Vec<ContentBlock>,
     pub model: String,
     pub stop_reason: String,
+    /// Uncached input tokens.
     pub input_tokens: u64,
     pub output_tokens: u64,
+    /// Input tokens served from the provider's prompt cache.
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    /// Input tokens written to the provider's prompt cache.
+    #[serde(default)]
+    pub cache_creation_tokens: u64,
 }
 
+impl ChatResponse {
+    /// All input tokens processed for this call, cached or not.
+    #[must_use]
+    pub fn total_input_tokens(&self) -> u64 {
+        self.input_tokens + self.cache_read_tokens + self.cache_creation_tokens
+    }
+
+    /// All tokens processed for this call.
+    #[must_use]
+    pub fn total_tokens(&self) -> u64 {
+        self.total_input_tokens() + self.output_tokens
+    }
+
+    /// Tokens charged against the rate-limit budget.
+    ///
+    /// Cache reads count at 10%, matching their price, so prompt caching
+    /// stretches the budget as well as lowering cost.
+    #[must_use]
+    pub fn billable_tokens(&self) -> u64 {
+        self.input_tokens + self.cache_read_tokens.div_ceil(10) + self.cache_creation_tokens + self.output_tokens
+    }
+
+    /// Estimated USD cost of this call, or `None` for models without a
+    /// known price.
+    ///
+    /// Cache reads bill at 10% of the input rate and cache writes at 125%.
+    #[must_use]
+    pub fn cost_usd(&self) -> Option<f64> {
+        let (input_rate, output_rate) = price_per_mtok(&self.model)?;
+        #[allow(clippy::cast_precision_loss)]
+        let tokens = |n: u64| n as f64 / 1_000_000.0;
+        Some(
+            tokens(self.input_tokens) * input_rate
+                + tokens(self.cache_read_tokens) * input_rate * 0.1
+                + tokens(self.cache_creation_tokens) * input_rate * 1.25
+                + tokens(self.output_tokens) * output_rate,
+        )
+    }
+}
+
+/// Input/output USD price per million tokens, by model family.
+fn price_per_mtok(model: &str) -> Option<(f64, f64)> {
+    if model.contains("haiku") {
+        Some((1.0, 5.0))
+    } else if model.contains("sonnet") {
+        Some((3.0, 15.0))
+    } else if model.contains("opus") {
+        Some((5.0, 25.0))
+    } else {
+        None
+    }
+}
+
+/// Incremental output surfaced while a streamed response is in flight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatDelta {
+    /// A fragment of assistant text, in arrival order.
+    Text(String),
+    /// One finished attempt against a provider in the failover chain.
+    Attempt(LlmAttempt),
+}
+
+/// Outcome of a single provider attempt, reported for tracing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LlmAttempt {
+    /// Provider name, e.g. `"anthropic"`.
+    pub provider: &'static str,
+    pub model: String,
+    /// 1-based attempt number against this provider.
+    pub attempt: u32,
+    pub duration_ms: u64,
+    /// Error code and message when the attempt failed.
+    pub error: Option<(&'static str, String)>,
+    /// Delay before retrying the same provider; `None` when the chain
+    /// moved on or stopped.
+    pub retry_in_ms: Option<u64>,
+}
+
+/// Receiving end for [`ChatDelta`]s from [`LlmChat::chat_stream`].
+pub type DeltaSink = tokio::sync::mpsc::UnboundedSender<ChatDelta>;
+
 // =============================================================================
 // LLM CHAT TRAIT
 // =============================================================================
@@ -161,6 +278,34 @@ pub trait LlmChat: Send + Sync {
         messages: &[Message],
         tools: Option<&[Tool]>,
     ) -> Result<ChatResponse, LlmError>;
+
+    /// Streaming variant of [`LlmChat::chat`].
+    ///
+    /// Text is sent to `deltas` as it arrives; the returned response holds
+    /// the complete content, including fully assembled tool-use blocks. The
+    /// default implementation makes a one-shot `chat` call and reports each
+    /// text block as a single delta.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`LlmError`] under the same conditions as `chat`, or
+    /// [`LlmError::ApiStream`] if the stream fails part way.
+    async fn chat_stream(
+        &self,
+        max_tokens: u32,
+        system: &str,
+        messages: &[Message],
+        tools: Option<&[Tool]>,
+        deltas: &DeltaSink,
+    ) -> Result<ChatResponse, LlmError> {
+        let response = self.chat(max_tokens, system, messages, tools).await?;
+        for block in &response.content {
+            if let ContentBlock::Text { text } = block {
+                let _ = deltas.send(ChatDelta::Text(text.clone()));
+            }
+        }
+        Ok(response)
+    }
 }
 
 #[cfg(test)]
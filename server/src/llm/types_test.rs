@@ -25,7 +25,7 @@ fn error_code_api_request() {
 
 #[test]
 fn error_code_api_response() {
-    let err = LlmError::ApiResponse { status: 500, body: "oops".into() };
+    let err = LlmError::ApiResponse { status: 500, body: "oops".into(), retry_after_secs: None };
     assert_eq!(err.error_code(), "E_API_RESPONSE");
 }
 
@@ -53,31 +53,31 @@ fn retryable_api_request() {
 
 #[test]
 fn retryable_api_response_429() {
-    let err = LlmError::ApiResponse { status: 429, body: "rate limited".into() };
+    let err = LlmError::ApiResponse { status: 429, body: "rate limited".into(), retry_after_secs: None };
     assert!(err.retryable());
 }
 
 #[test]
 fn retryable_api_response_500() {
-    let err = LlmError::ApiResponse { status: 500, body: "internal".into() };
+    let err = LlmError::ApiResponse { status: 500, body: "internal".into(), retry_after_secs: None };
     assert!(err.retryable());
 }
 
 #[test]
 fn retryable_api_response_503() {
-    let err = LlmError::ApiResponse { status: 503, body: "unavailable".into() };
+    let err = LlmError::ApiResponse { status: 503, body: "unavailable".into(), retry_after_secs: None };
     assert!(err.retryable());
 }
 
 #[test]
 fn not_retryable_api_response_400() {
-    let err = LlmError::ApiResponse { status: 400, body: "bad request".into() };
+    let err = LlmError::ApiResponse { status: 400, body: "bad request".into(), retry_after_secs: None };
     assert!(!err.retryable());
 }
 
 #[test]
 fn not_retryable_api_response_401() {
-    let err = LlmError::ApiResponse { status: 401, body: "unauthorized".into() };
+    let err = LlmError::ApiResponse { status: 401, body: "unauthorized".into(), retry_after_secs: None };
     assert!(!err.retryable());
 }
 
@@ -274,6 +274,8 @@ fn chat_response_round_trip() {
         stop_reason: "end_turn".into(),
         input_tokens: 100,
         output_tokens: 50,
+        cache_read_tokens: 0,
+        cache_creation_tokens: 0,
     };
     let json = serde_json::to_string(&resp).unwrap();
     let restored: ChatResponse = serde_json::from_str(&json).unwrap();
@@ -292,8 +294,54 @@ fn chat_response_empty_content() {
         stop_reason: "end_turn".into(),
         input_tokens: 0,
         output_tokens: 0,
+        cache_read_tokens: 0,
+        cache_creation_tokens: 0,
     };
     let json = serde_json::to_string(&resp).unwrap();
     let restored: ChatResponse = serde_json::from_str(&json).unwrap();
     assert!(restored.content.is_empty());
 }
+
+#[test]
+fn chat_response_cache_tokens_default_and_count_toward_totals() {
+    let json =
+        r#"{"content":[],"model":"claude-sonnet-4-5","stop_reason":"end_turn","input_tokens":10,"output_tokens":5}"#;
+    let mut resp: ChatResponse = serde_json::from_str(json).unwrap();
+    assert_eq!((resp.cache_read_tokens, resp.cache_creation_tokens), (0, 0));
+
+    resp.cache_read_tokens = 1000;
+    resp.cache_creation_tokens = 200;
+    assert_eq!(resp.total_input_tokens(), 1210);
+    assert_eq!(resp.total_tokens(), 1215);
+}
+
+#[test]
+fn chat_response_billable_tokens_discount_cache_reads() {
+    let json =
+        r#"{"content":[],"model":"claude-sonnet-4-5","stop_reason":"end_turn","input_tokens":10,"output_tokens":5}"#;
+    let mut resp: ChatResponse = serde_json::from_str(json).unwrap();
+    assert_eq!(resp.billable_tokens(), 15);
+
+    resp.cache_read_tokens = 1001;
+    resp.cache_creation_tokens = 200;
+    assert_eq!(resp.billable_tokens(), 10 + 101 + 200 + 5);
+    assert!(resp.billable_tokens() < resp.total_tokens());
+}
+
+#[test]
+fn chat_response_cost_discounts_cache_reads() {
+    let resp = ChatResponse {
+        content: vec![],
+        model: "claude-sonnet-4-5".into(),
+        stop_reason: "end_turn".into(),
+        input_tokens: 1_000_000,
+        output_tokens: 0,
+        cache_read_tokens: 1_000_000,
+        cache_creation_tokens: 1_000_000,
+    };
+    // $3 uncached + $0.30 cache read + $3.75 cache write.
+    assert!((resp.cost_usd().unwrap() - 7.05).abs() < 1e-9);
+
+    let unknown = ChatResponse { model: "gpt-test".into(), ..resp };
+    assert!(unknown.cost_usd().is_none());
+}
@@ -14,6 +14,7 @@
 
 mod db;
 mod frame;
+mod import;
 mod llm;
 mod mermaid;
 mod rate_limit;
@@ -186,6 +187,9 @@ async fn run() -> Result<(), String> {
     }
 
     let mut app_state = state::AppState::new(pool, llm, github);
+    if let Err(e) = services::usage::load_budgets(&app_state).await {
+        tracing::warn!(error = %e, "failed to load AI budgets — only env defaults apply");
+    }
     app_state.frame_persist_tx = Some(services::persistence::spawn_frame_persistence_worker(app_state.pool.clone()));
 
     // Spawn background persistence task.
@@ -1,4 +1,13 @@
-//! AST types for Mermaid sequence diagrams.
+//! AST types for Mermaid sequence, flowchart, state, and class diagrams.
+
+/// Any diagram the parser understands, chosen by its header line.
+#[derive(Debug, Clone)]
+pub enum Diagram {
+    Sequence(SequenceDiagram),
+    Flowchart(Flowchart),
+    State(StateDiagram),
+    Class(ClassDiagram),
+}
 
 /// A parsed Mermaid sequence diagram.
 #[derive(Debug, Clone)]
@@ -91,3 +100,199 @@ pub enum BlockKind {
     Critical,
     Break,
 }
+
+// =============================================================================
+// FLOWCHART
+// =============================================================================
+
+/// A parsed Mermaid `flowchart` / `graph` diagram.
+#[derive(Debug, Clone)]
+pub struct Flowchart {
+    pub direction: FlowDirection,
+    /// Nodes in order of first appearance.
+    pub nodes: Vec<FlowNode>,
+    pub edges: Vec<FlowEdge>,
+    /// Subgraphs in order of appearance; parents precede their children.
+    pub subgraphs: Vec<Subgraph>,
+}
+
+/// Direction in which flowchart, state, and class diagram layers advance.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FlowDirection {
+    /// `TD` / `TB`
+    #[default]
+    TopDown,
+    /// `BT`
+    BottomUp,
+    /// `LR`
+    LeftRight,
+    /// `RL`
+    RightLeft,
+}
+
+/// A flowchart node.
+#[derive(Debug, Clone)]
+pub struct FlowNode {
+    pub id: String,
+    pub label: String,
+    pub shape: NodeShape,
+    /// Innermost subgraph that last mentioned the node.
+    pub subgraph: Option<String>,
+}
+
+/// Node shape, from the brackets around its label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeShape {
+    /// `A[text]`
+    Rect,
+    /// `A(text)` or `A([text])`
+    Round,
+    /// `A{text}`
+    Diamond,
+    /// `A((text))`
+    Circle,
+}
+
+/// A link between two flowchart nodes.
+#[derive(Debug, Clone)]
+pub struct FlowEdge {
+    pub from: String,
+    pub to: String,
+    pub label: Option<String>,
+    pub line: LineStyle,
+    /// `-->` style links end in an arrowhead; `---` style links do not.
+    pub arrowhead: bool,
+}
+
+/// Stroke style for flowchart links.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineStyle {
+    /// `-->` / `---`
+    Solid,
+    /// `-.->` / `-.-`
+    Dotted,
+    /// `==>` / `===`
+    Thick,
+}
+
+/// A `subgraph ... end` group.
+#[derive(Debug, Clone)]
+pub struct Subgraph {
+    pub id: String,
+    pub title: String,
+    pub parent: Option<String>,
+}
+
+// =============================================================================
+// STATE DIAGRAM
+// =============================================================================
+
+/// A parsed Mermaid `stateDiagram` / `stateDiagram-v2` diagram.
+#[derive(Debug, Clone)]
+pub struct StateDiagram {
+    pub direction: FlowDirection,
+    /// States in order of first appearance, including pseudo-states and
+    /// composite states.
+    pub states: Vec<State>,
+    pub transitions: Vec<Transition>,
+}
+
+/// A state, pseudo-state, or composite state.
+#[derive(Debug, Clone)]
+pub struct State {
+    /// Unique id. Each `[*]` gets a synthetic id per enclosing composite.
+    pub id: String,
+    pub label: String,
+    pub kind: StateKind,
+    /// Innermost composite state containing this one.
+    pub parent: Option<String>,
+}
+
+/// What a state draws as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateKind {
+    Simple,
+    /// `state X { ... }`; drawn as a frame around its children.
+    Composite,
+    /// `[*]` as a transition source.
+    Start,
+    /// `[*]` as a transition target.
+    End,
+    /// `state X <<choice>>`
+    Choice,
+    /// `state X <<fork>>` or `<<join>>`
+    Fork,
+}
+
+/// A `From --> To : label` transition.
+#[derive(Debug, Clone)]
+pub struct Transition {
+    pub from: String,
+    pub to: String,
+    pub label: Option<String>,
+}
+
+// =============================================================================
+// CLASS DIAGRAM
+// =============================================================================
+
+/// A parsed Mermaid `classDiagram` diagram.
+#[derive(Debug, Clone)]
+pub struct ClassDiagram {
+    pub direction: FlowDirection,
+    /// Classes in order of first appearance.
+    pub classes: Vec<Class>,
+    pub relations: Vec<Relation>,
+}
+
+/// A class box.
+#[derive(Debug, Clone)]
+pub struct Class {
+    pub id: String,
+    /// Display name; generics written `Name~T~` read as `Name<T>`.
+    pub label: String,
+    /// Annotation such as `interface` from `<<interface>>`.
+    pub annotation: Option<String>,
+    pub attributes: Vec<String>,
+    pub methods: Vec<String>,
+}
+
+/// A relation between two classes, e.g. `Animal <|-- Duck : extends`.
+///
+/// `from` is the left-hand class as written and `to` the right-hand one.
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub from: String,
+    pub to: String,
+    pub kind: RelationKind,
+    /// Which end carries the kind's marker (`<|`, `*`, `o`, `>`).
+    pub marker_at: MarkerEnd,
+    /// `..` links instead of `--`.
+    pub dashed: bool,
+    pub label: Option<String>,
+    pub from_cardinality: Option<String>,
+    pub to_cardinality: Option<String>,
+}
+
+/// UML relation kind, from the link's end markers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelationKind {
+    /// `<|--`, or `<|..` (realization) when dashed.
+    Inheritance,
+    /// `*--`
+    Composition,
+    /// `o--`
+    Aggregation,
+    /// `-->`, or `..>` (dependency) when dashed.
+    Association,
+    /// `--` or `..` with no markers.
+    Link,
+}
+
+/// Which end of a relation carries its marker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerEnd {
+    From,
+    To,
+    Neither,
+}
@@ -0,0 +1,309 @@
+//! Flowchart export: converts board shapes and attached connectors back into
+//! Mermaid `flowchart` text.
+//!
+//! Shapes become nodes labeled by their text, frames become (nested)
+//! subgraphs, and `line` / `arrow` objects whose two ends are attached to
+//! exported nodes become links. A loose text object sitting on a connector's
+//! midpoint — how the layout engine places edge labels — becomes that link's
+//! label; other text is exported only when a connector attaches to it.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use uuid::Uuid;
+
+/// Kinds exported as nodes whenever they are in scope.
+const NODE_KINDS: &[&str] = &["rectangle", "ellipse", "diamond", "star", "sticky_note"];
+/// Text props read for labels, in priority order.
+const LABEL_PROPS: &[&str] = &["text", "label", "title"];
+/// How far a loose text's center may sit from a connector's midpoint and
+/// still label it.
+const LABEL_SNAP_DISTANCE: f64 = 40.0;
+const INDENT: &str = "    ";
+
+/// The fields of a board object the exporter reads.
+#[derive(Debug, Clone, Copy)]
+pub struct ExportObject<'a> {
+    pub id: Uuid,
+    pub kind: &'a str,
+    pub x: f64,
+    pub y: f64,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+    pub props: &'a serde_json::Value,
+}
+
+impl ExportObject<'_> {
+    fn center(&self) -> (f64, f64) {
+        (
+            self.x + self.width.unwrap_or(0.0) / 2.0,
+            self.y + self.height.unwrap_or(0.0) / 2.0,
+        )
+    }
+
+    fn area(&self) -> f64 {
+        self.width.unwrap_or(0.0) * self.height.unwrap_or(0.0)
+    }
+
+    fn contains(&self, (px, py): (f64, f64)) -> bool {
+        let (Some(w), Some(h)) = (self.width, self.height) else {
+            return false;
+        };
+        px >= self.x && px <= self.x + w && py >= self.y && py <= self.y + h
+    }
+
+    fn label(&self) -> Option<&str> {
+        LABEL_PROPS
+            .iter()
+            .filter_map(|key| self.props.get(*key).and_then(serde_json::Value::as_str))
+            .map(str::trim)
+            .find(|text| !text.is_empty())
+    }
+
+    /// Attached object id and point of connector end `key` (`a` or `b`).
+    fn endpoint(&self, key: &str) -> (Option<Uuid>, Option<(f64, f64)>) {
+        let end = self.props.get(key);
+        let object_id = end
+            .and_then(|e| e.get("object_id"))
+            .and_then(serde_json::Value::as_str)
+            .and_then(|s| s.parse().ok());
+        let point = end.and_then(|e| Some((e.get("x")?.as_f64()?, e.get("y")?.as_f64()?)));
+        (object_id, point)
+    }
+}
+
+/// Which objects to export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportScope {
+    /// Every object on the board.
+    Board,
+    /// Only these objects, plus connectors joining them.
+    Objects(HashSet<Uuid>),
+    /// Everything whose center lies inside this frame, excluding the frame.
+    Frame(Uuid),
+}
+
+/// Export the objects in `scope` as a Mermaid flowchart.
+///
+/// The direction is `LR` when links run mostly horizontally and `TD`
+/// otherwise. Node and subgraph ids (`n1`, `f1`, ...) follow reading order.
+///
+/// # Errors
+///
+/// Returns a descriptive error string when the scope names a frame that is
+/// not on the board.
+pub fn export_flowchart(objects: &[ExportObject<'_>], scope: &ExportScope) -> Result<String, String> {
+    let in_scope: Vec<&ExportObject<'_>> = match scope {
+        ExportScope::Board => objects.iter().collect(),
+        ExportScope::Objects(ids) => objects.iter().filter(|o| ids.contains(&o.id)).collect(),
+        ExportScope::Frame(frame_id) => {
+            let frame = objects
+                .iter()
+                .find(|o| o.id == *frame_id && o.kind == "frame")
+                .ok_or_else(|| format!("frame {frame_id} not found"))?;
+            objects
+                .iter()
+                .filter(|o| o.id != frame.id && frame.contains(o.center()))
+                .collect()
+        }
+    };
+
+    let mut candidates: Vec<&ExportObject<'_>> = in_scope
+        .iter()
+        .copied()
+        .filter(|o| NODE_KINDS.contains(&o.kind) || o.kind == "text")
+        .collect();
+    candidates.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    let candidate_ids: HashSet<Uuid> = candidates.iter().map(|o| o.id).collect();
+
+    let connectors: Vec<(&ExportObject<'_>, Uuid, Uuid)> = objects
+        .iter()
+        .filter(|o| o.kind == "line" || o.kind == "arrow")
+        .filter_map(|o| {
+            let (Some(from), _) = o.endpoint("a") else { return None };
+            let (Some(to), _) = o.endpoint("b") else { return None };
+            (from != to && candidate_ids.contains(&from) && candidate_ids.contains(&to)).then_some((o, from, to))
+        })
+        .collect();
+    let attached: HashSet<Uuid> = connectors
+        .iter()
+        .flat_map(|&(_, from, to)| [from, to])
+        .collect();
+
+    // Text joins the graph only through a connector; loose text may label one.
+    let (nodes, loose_text): (Vec<&ExportObject<'_>>, Vec<_>) = candidates
+        .into_iter()
+        .partition(|o| o.kind != "text" || attached.contains(&o.id));
+    let node_order: HashMap<Uuid, usize> = nodes.iter().enumerate().map(|(i, o)| (o.id, i)).collect();
+    let node_ids: HashMap<Uuid, String> = node_order
+        .iter()
+        .map(|(&id, i)| (id, format!("n{}", i + 1)))
+        .collect();
+    let mut connectors = connectors;
+    connectors.sort_by_key(|&(o, from, to)| (node_order[&from], node_order[&to], o.id));
+
+    let mut frames: Vec<&ExportObject<'_>> = in_scope
+        .iter()
+        .copied()
+        .filter(|o| o.kind == "frame")
+        .collect();
+    frames.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    let frame_ids: HashMap<Uuid, String> = frames
+        .iter()
+        .enumerate()
+        .map(|(i, f)| (f.id, format!("f{}", i + 1)))
+        .collect();
+    let enclosing = |obj: &ExportObject<'_>| {
+        frames
+            .iter()
+            .filter(|f| f.id != obj.id && f.area() > obj.area() && f.contains(obj.center()))
+            .min_by(|a, b| a.area().total_cmp(&b.area()))
+            .map(|f| f.id)
+    };
+
+    let mut children: HashMap<Option<Uuid>, Vec<Line<'_>>> = HashMap::new();
+    for frame in &frames {
+        children
+            .entry(enclosing(frame))
+            .or_default()
+            .push(Line::Subgraph(frame));
+    }
+    for node in &nodes {
+        children
+            .entry(enclosing(node))
+            .or_default()
+            .push(Line::Node(node));
+    }
+
+    let mut out = format!("flowchart {}\n", direction(&connectors, &nodes));
+    write_members(&mut out, None, &children, &node_ids, &frame_ids, 1);
+
+    let mut used_labels = HashSet::new();
+    for &(connector, from, to) in &connectors {
+        let label = connector.label().map(ToOwned::to_owned).or_else(|| {
+            let text = snapped_label(connector, &loose_text, &used_labels)?;
+            used_labels.insert(text.id);
+            text.label().map(ToOwned::to_owned)
+        });
+        let label = label
+            .map(|l| format!("|\"{}\"|", escape(&l)))
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "{INDENT}{} {}{label} {}",
+            node_ids[&from],
+            link_operator(connector),
+            node_ids[&to]
+        );
+    }
+    Ok(out)
+}
+
+/// A line in a subgraph body.
+enum Line<'a> {
+    Node(&'a ExportObject<'a>),
+    Subgraph(&'a ExportObject<'a>),
+}
+
+/// Write the nodes and subgraphs directly inside `parent`, recursing into
+/// nested subgraphs.
+fn write_members(
+    out: &mut String,
+    parent: Option<Uuid>,
+    children: &HashMap<Option<Uuid>, Vec<Line<'_>>>,
+    node_ids: &HashMap<Uuid, String>,
+    frame_ids: &HashMap<Uuid, String>,
+    depth: usize,
+) {
+    let indent = INDENT.repeat(depth);
+    for line in children.get(&parent).into_iter().flatten() {
+        match line {
+            Line::Node(node) => {
+                let label = escape(node.label().unwrap_or(""));
+                let (open, close) = node_brackets(node);
+                let _ = writeln!(out, "{indent}{}{open}\"{label}\"{close}", node_ids[&node.id]);
+            }
+            Line::Subgraph(frame) => {
+                let title = escape(frame.label().unwrap_or("Frame"));
+                let _ = writeln!(out, "{indent}subgraph {} [\"{title}\"]", frame_ids[&frame.id]);
+                write_members(out, Some(frame.id), children, node_ids, frame_ids, depth + 1);
+                let _ = writeln!(out, "{indent}end");
+            }
+        }
+    }
+}
+
+fn node_brackets(node: &ExportObject<'_>) -> (&'static str, &'static str) {
+    match node.kind {
+        "diamond" => ("{", "}"),
+        "ellipse" => match (node.width, node.height) {
+            (Some(w), Some(h)) if (w - h).abs() < 1.0 => ("((", "))"),
+            _ => ("(", ")"),
+        },
+        _ => ("[", "]"),
+    }
+}
+
+fn link_operator(connector: &ExportObject<'_>) -> &'static str {
+    let arrow = connector.kind == "arrow";
+    let dashed = connector
+        .props
+        .get("dashPattern")
+        .is_some_and(|p| !p.is_null());
+    let thick = connector
+        .props
+        .get("strokeWidth")
+        .and_then(serde_json::Value::as_f64)
+        .is_some_and(|w| w >= 4.0);
+    match (arrow, dashed, thick) {
+        (true, true, _) => "-.->",
+        (false, true, _) => "-.-",
+        (true, false, true) => "==>",
+        (false, false, true) => "===",
+        (true, false, false) => "-->",
+        (false, false, false) => "---",
+    }
+}
+
+/// The unused loose text closest to the connector's midpoint, if near enough.
+fn snapped_label<'a>(
+    connector: &ExportObject<'_>,
+    loose_text: &[&'a ExportObject<'a>],
+    used: &HashSet<Uuid>,
+) -> Option<&'a ExportObject<'a>> {
+    let (mx, my) = match (connector.endpoint("a").1, connector.endpoint("b").1) {
+        (Some((ax, ay)), Some((bx, by))) => (ax.midpoint(bx), ay.midpoint(by)),
+        _ => connector.center(),
+    };
+    loose_text
+        .iter()
+        .copied()
+        .filter(|t| !used.contains(&t.id) && t.label().is_some())
+        .map(|t| {
+            let (tx, ty) = t.center();
+            (t, (tx - mx).hypot(ty - my))
+        })
+        .filter(|(_, distance)| *distance <= LABEL_SNAP_DISTANCE)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(t, _)| t)
+}
+
+/// `LR` when links run mostly horizontally, `TD` otherwise.
+fn direction(connectors: &[(&ExportObject<'_>, Uuid, Uuid)], nodes: &[&ExportObject<'_>]) -> &'static str {
+    let centers: HashMap<Uuid, (f64, f64)> = nodes.iter().map(|n| (n.id, n.center())).collect();
+    let (across, down) = connectors
+        .iter()
+        .filter_map(|(_, from, to)| Some((centers.get(from)?, centers.get(to)?)))
+        .fold((0.0, 0.0), |(across, down), ((fx, fy), (tx, ty))| {
+            (across + (tx - fx).abs(), down + (ty - fy).abs())
+        });
+    if across > down { "LR" } else { "TD" }
+}
+
+/// Quote-safe label text for Mermaid.
+fn escape(label: &str) -> String {
+    label
+        .replace('"', "#quot;")
+        .replace('|', "#124;")
+        .replace('\n', "<br/>")
+}
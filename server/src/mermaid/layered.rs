@@ -0,0 +1,399 @@
+//! Sugiyama-style layered layout shared by flowcharts, state diagrams, and
+//! class diagrams.
+//!
+//! Breaks cycles, assigns layers by longest path, pads long edges with dummy
+//! vertices, reduces crossings with barycenter sweeps (keeping top-level
+//! groups contiguous), then places layers along the flow direction. Groups
+//! are boxed around their members and nested groups.
+
+use std::collections::HashMap;
+
+use super::ast::FlowDirection;
+
+const LAYER_GAP: f64 = 80.0;
+const NODE_GAP: f64 = 40.0;
+const DUMMY_SIZE: f64 = 20.0;
+const GROUP_PADDING: f64 = 30.0;
+const GROUP_TITLE_H: f64 = 30.0;
+const ORDER_SWEEPS: usize = 8;
+
+/// A graph to lay out. Nodes and groups are referred to by index.
+pub struct LayeredGraph {
+    pub direction: FlowDirection,
+    /// `(width, height)` of each node.
+    pub sizes: Vec<(f64, f64)>,
+    /// Directed links between nodes; self-links are ignored.
+    pub links: Vec<(usize, usize)>,
+    /// Parent of each group. Parents must precede their children.
+    pub group_parents: Vec<Option<usize>>,
+    /// Innermost group of each node.
+    pub node_groups: Vec<Option<usize>>,
+}
+
+/// Final boxes, offset to the origin and scaled.
+pub struct GraphLayout {
+    pub nodes: Vec<NodeBox>,
+    /// Box around each group, or `None` for a group with no members.
+    pub groups: Vec<Option<NodeBox>>,
+}
+
+/// An axis-aligned box.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeBox {
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+impl NodeBox {
+    #[must_use]
+    pub fn center(&self) -> (f64, f64) {
+        (self.x + self.w / 2.0, self.y + self.h / 2.0)
+    }
+
+    /// Point at unit coordinates `(ux, uy)` within the box.
+    #[must_use]
+    pub fn at(&self, (ux, uy): (f64, f64)) -> (f64, f64) {
+        (self.x + ux * self.w, self.y + uy * self.h)
+    }
+
+    fn union(self, other: Self) -> Self {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Self { x, y, w: (self.x + self.w).max(other.x + other.w) - x, h: (self.y + self.h).max(other.y + other.h) - y }
+    }
+}
+
+/// A vertex in the layered graph: a real node or a dummy on a long edge.
+struct LayerVertex {
+    node: Option<usize>,
+    layer: usize,
+    /// Top-level group used to keep clusters contiguous within a layer.
+    cluster: Option<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+}
+
+/// Lay out `graph` with its top-left corner at `(origin_x, origin_y)`.
+#[must_use]
+pub fn layout(graph: &LayeredGraph, origin_x: f64, origin_y: f64, scale: f64) -> GraphLayout {
+    let node_count = graph.sizes.len();
+    let links: Vec<(usize, usize)> = graph
+        .links
+        .iter()
+        .copied()
+        .filter(|(from, to)| from != to && *from < node_count && *to < node_count)
+        .collect();
+    let acyclic = break_cycles(node_count, &links);
+    let layers = assign_layers(node_count, &acyclic);
+    let clusters = top_level_clusters(graph);
+    let mut vertices = build_layer_graph(&layers, &acyclic, &clusters);
+    let order = order_layers(&mut vertices);
+    let mut nodes = place_nodes(graph, &vertices, &order);
+    let mut groups = group_boxes(graph, &nodes);
+
+    let placed = || nodes.iter().chain(groups.iter().flatten());
+    let min_x = placed().map(|b| b.x).fold(f64::INFINITY, f64::min);
+    let min_y = placed().map(|b| b.y).fold(f64::INFINITY, f64::min);
+    for b in nodes.iter_mut().chain(groups.iter_mut().flatten()) {
+        b.x = origin_x + (b.x - min_x) * scale;
+        b.y = origin_y + (b.y - min_y) * scale;
+        b.w *= scale;
+        b.h *= scale;
+    }
+    GraphLayout { nodes, groups }
+}
+
+/// Unit anchors `(from, to)` on the facing sides of two boxes along the flow
+/// axis.
+#[must_use]
+pub fn facing_anchors(from: NodeBox, to: NodeBox, direction: FlowDirection) -> ((f64, f64), (f64, f64)) {
+    let ((fx, fy), (tx, ty)) = (from.center(), to.center());
+    if matches!(direction, FlowDirection::LeftRight | FlowDirection::RightLeft) {
+        if tx >= fx {
+            ((1.0, 0.5), (0.0, 0.5))
+        } else {
+            ((0.0, 0.5), (1.0, 0.5))
+        }
+    } else if ty >= fy {
+        ((0.5, 1.0), (0.5, 0.0))
+    } else {
+        ((0.5, 0.0), (0.5, 1.0))
+    }
+}
+
+/// Orient every link so the graph is acyclic, reversing DFS back edges.
+fn break_cycles(node_count: usize, links: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut out: Vec<Vec<usize>> = vec![Vec::new(); node_count];
+    for &(from, to) in links {
+        out[from].push(to);
+    }
+    // 0 = unvisited, 1 = on the DFS stack, 2 = done.
+    let mut state = vec![0_u8; node_count];
+    let mut back_edges = Vec::new();
+    for root in 0..node_count {
+        if state[root] != 0 {
+            continue;
+        }
+        let mut stack = vec![(root, 0_usize)];
+        state[root] = 1;
+        while let Some((node, next)) = stack.pop() {
+            if let Some(&to) = out[node].get(next) {
+                stack.push((node, next + 1));
+                match state[to] {
+                    0 => {
+                        state[to] = 1;
+                        stack.push((to, 0));
+                    }
+                    1 => back_edges.push((node, to)),
+                    _ => {}
+                }
+            } else {
+                state[node] = 2;
+            }
+        }
+    }
+    links
+        .iter()
+        .map(|&(from, to)| {
+            if back_edges.contains(&(from, to)) {
+                (to, from)
+            } else {
+                (from, to)
+            }
+        })
+        .collect()
+}
+
+/// Longest-path layering: every node sits one layer below its deepest parent.
+fn assign_layers(node_count: usize, acyclic: &[(usize, usize)]) -> Vec<usize> {
+    let mut indegree = vec![0_usize; node_count];
+    let mut out: Vec<Vec<usize>> = vec![Vec::new(); node_count];
+    for &(from, to) in acyclic {
+        out[from].push(to);
+        indegree[to] += 1;
+    }
+    let mut layers = vec![0_usize; node_count];
+    let mut ready: Vec<usize> = (0..node_count)
+        .filter(|&n| indegree[n] == 0)
+        .rev()
+        .collect();
+    while let Some(node) = ready.pop() {
+        for &to in &out[node] {
+            layers[to] = layers[to].max(layers[node] + 1);
+            indegree[to] -= 1;
+            if indegree[to] == 0 {
+                ready.push(to);
+            }
+        }
+    }
+    layers
+}
+
+/// Map each node to its outermost enclosing group.
+///
+/// Parents precede their children, so the walk only follows links to
+/// earlier groups; a malformed graph whose parent links loop stops instead
+/// of spinning forever.
+fn top_level_clusters(graph: &LayeredGraph) -> Vec<Option<usize>> {
+    graph
+        .node_groups
+        .iter()
+        .map(|group| {
+            let mut current = (*group)?;
+            while let Some(&Some(parent)) = graph.group_parents.get(current)
+                && parent < current
+            {
+                current = parent;
+            }
+            Some(current)
+        })
+        .collect()
+}
+
+/// Build the layered graph, splitting edges that span several layers into
+/// chains of dummy vertices.
+fn build_layer_graph(layers: &[usize], acyclic: &[(usize, usize)], clusters: &[Option<usize>]) -> Vec<LayerVertex> {
+    let mut vertices: Vec<LayerVertex> = layers
+        .iter()
+        .enumerate()
+        .map(|(node, &layer)| LayerVertex {
+            node: Some(node),
+            layer,
+            cluster: clusters.get(node).copied().flatten(),
+            up: Vec::new(),
+            down: Vec::new(),
+        })
+        .collect();
+    for &(from, to) in acyclic {
+        let cluster = if vertices[from].cluster == vertices[to].cluster {
+            vertices[from].cluster
+        } else {
+            None
+        };
+        let mut prev = from;
+        for layer in layers[from] + 1..layers[to] {
+            vertices.push(LayerVertex { node: None, layer, cluster, up: Vec::new(), down: Vec::new() });
+            let dummy = vertices.len() - 1;
+            vertices[prev].down.push(dummy);
+            vertices[dummy].up.push(prev);
+            prev = dummy;
+        }
+        vertices[prev].down.push(to);
+        vertices[to].up.push(prev);
+    }
+    vertices
+}
+
+/// Order vertices within each layer with alternating barycenter sweeps,
+/// keeping each top-level group contiguous.
+fn order_layers(vertices: &mut [LayerVertex]) -> Vec<Vec<usize>> {
+    let layer_count = vertices.iter().map(|v| v.layer).max().map_or(0, |m| m + 1);
+    let mut order: Vec<Vec<usize>> = vec![Vec::new(); layer_count];
+    for (i, v) in vertices.iter().enumerate() {
+        order[v.layer].push(i);
+    }
+    let mut position = vec![0.0_f64; vertices.len()];
+    let sync_positions = |order: &[Vec<usize>], position: &mut [f64]| {
+        for layer in order {
+            for (pos, &v) in layer.iter().enumerate() {
+                #[allow(clippy::cast_precision_loss)]
+                let pos = pos as f64;
+                position[v] = pos;
+            }
+        }
+    };
+    sync_positions(&order, &mut position);
+
+    for sweep in 0..ORDER_SWEEPS {
+        let downward = sweep % 2 == 0;
+        let layer_indices: Vec<usize> = if downward {
+            (1..layer_count).collect()
+        } else {
+            (0..layer_count.saturating_sub(1)).rev().collect()
+        };
+        for layer in layer_indices {
+            let barycenter = |v: usize| {
+                let neighbours = if downward { &vertices[v].up } else { &vertices[v].down };
+                if neighbours.is_empty() {
+                    position[v]
+                } else {
+                    #[allow(clippy::cast_precision_loss)]
+                    let count = neighbours.len() as f64;
+                    neighbours.iter().map(|&n| position[n]).sum::<f64>() / count
+                }
+            };
+            let keys: HashMap<usize, f64> = order[layer].iter().map(|&v| (v, barycenter(v))).collect();
+            let mut cluster_keys: HashMap<usize, (f64, f64)> = HashMap::new();
+            for &v in &order[layer] {
+                if let Some(cluster) = vertices[v].cluster {
+                    let entry = cluster_keys.entry(cluster).or_insert((0.0, 0.0));
+                    entry.0 += keys[&v];
+                    entry.1 += 1.0;
+                }
+            }
+            let group_key = |v: usize| {
+                vertices[v]
+                    .cluster
+                    .and_then(|c| cluster_keys.get(&c))
+                    .map_or(keys[&v], |(sum, count)| sum / count)
+            };
+            order[layer].sort_by(|&a, &b| {
+                group_key(a)
+                    .total_cmp(&group_key(b))
+                    .then(vertices[a].cluster.cmp(&vertices[b].cluster))
+                    .then(keys[&a].total_cmp(&keys[&b]))
+            });
+            sync_positions(&order, &mut position);
+        }
+    }
+    order
+}
+
+/// Place layers along the flow direction and vertices across it, centering
+/// each layer on the widest one.
+fn place_nodes(graph: &LayeredGraph, vertices: &[LayerVertex], order: &[Vec<usize>]) -> Vec<NodeBox> {
+    let horizontal = matches!(graph.direction, FlowDirection::LeftRight | FlowDirection::RightLeft);
+    // (along the flow, across the flow) extents.
+    let extent = |v: &LayerVertex| match v.node {
+        Some(n) => {
+            let (w, h) = graph.sizes[n];
+            if horizontal { (w, h) } else { (h, w) }
+        }
+        None => (0.0, DUMMY_SIZE),
+    };
+
+    let layer_depth: Vec<f64> = order
+        .iter()
+        .map(|layer| {
+            layer
+                .iter()
+                .map(|&v| extent(&vertices[v]).0)
+                .fold(0.0, f64::max)
+        })
+        .collect();
+    let layer_span = |layer: &[usize]| {
+        let sizes: f64 = layer.iter().map(|&v| extent(&vertices[v]).1).sum();
+        #[allow(clippy::cast_precision_loss)]
+        let gaps = layer.len().saturating_sub(1) as f64 * NODE_GAP;
+        sizes + gaps
+    };
+    let widest = order.iter().map(|l| layer_span(l)).fold(0.0, f64::max);
+
+    let mut boxes = vec![NodeBox { x: 0.0, y: 0.0, w: 0.0, h: 0.0 }; graph.sizes.len()];
+    let mut along = 0.0;
+    for (layer, depth) in order.iter().zip(&layer_depth) {
+        let mut across = (widest - layer_span(layer)) / 2.0;
+        for &v in layer {
+            let (_, cross) = extent(&vertices[v]);
+            if let Some(n) = vertices[v].node {
+                let (w, h) = graph.sizes[n];
+                let center_along = along + depth / 2.0;
+                let center_across = across + cross / 2.0;
+                let (cx, cy) = match graph.direction {
+                    FlowDirection::TopDown => (center_across, center_along),
+                    FlowDirection::BottomUp => (center_across, -center_along),
+                    FlowDirection::LeftRight => (center_along, center_across),
+                    FlowDirection::RightLeft => (-center_along, center_across),
+                };
+                boxes[n] = NodeBox { x: cx - w / 2.0, y: cy - h / 2.0, w, h };
+            }
+            across += cross + NODE_GAP;
+        }
+        along += depth + LAYER_GAP;
+    }
+    boxes
+}
+
+/// Box every group around its nodes and nested groups, leaving room for a
+/// title. Groups with no members get no box.
+fn group_boxes(graph: &LayeredGraph, nodes: &[NodeBox]) -> Vec<Option<NodeBox>> {
+    let mut boxes: Vec<Option<NodeBox>> = vec![None; graph.group_parents.len()];
+    // Parents precede children, so walking in reverse finishes each child
+    // before its parent needs it.
+    for group in (0..graph.group_parents.len()).rev() {
+        let members = graph
+            .node_groups
+            .iter()
+            .zip(nodes)
+            .filter(|(g, _)| **g == Some(group))
+            .map(|(_, b)| *b);
+        let children = graph
+            .group_parents
+            .iter()
+            .enumerate()
+            .filter(|(_, parent)| **parent == Some(group))
+            .filter_map(|(child, _)| boxes[child]);
+        boxes[group] = members
+            .chain(children)
+            .reduce(NodeBox::union)
+            .map(|inner| NodeBox {
+                x: inner.x - GROUP_PADDING,
+                y: inner.y - GROUP_PADDING - GROUP_TITLE_H,
+                w: inner.w + 2.0 * GROUP_PADDING,
+                h: inner.h + 2.0 * GROUP_PADDING + GROUP_TITLE_H,
+            });
+    }
+    boxes
+}
@@ -1,6 +1,17 @@
-//! Layout engine: converts a parsed sequence diagram AST into board object descriptors.
+//! Layout engine: converts parsed Mermaid ASTs into board object descriptors.
+//!
+//! Sequence diagrams use a fixed column-per-participant grid. Flowcharts,
+//! state diagrams, and class diagrams go through the shared layered layout
+//! in `layered`; their connectors name the node ids they join so the caller
+//! can attach them to the created objects.
 
-use super::ast::{ArrowStyle, Block, BlockKind, Event, Note, NotePosition, SequenceDiagram};
+use std::collections::HashMap;
+
+use super::ast::{
+    ArrowStyle, Block, BlockKind, Class, ClassDiagram, Diagram, Event, FlowDirection, FlowNode, Flowchart, LineStyle,
+    MarkerEnd, NodeShape, Note, NotePosition, Relation, RelationKind, SequenceDiagram, State, StateDiagram, StateKind,
+};
+use super::layered::{self, LayeredGraph, NodeBox};
 
 // Layout constants (in logical pixels, before scale).
 const PARTICIPANT_BOX_W: f64 = 120.0;
@@ -22,13 +33,30 @@ pub struct ObjectDescriptor {
     pub width: f64,
     pub height: f64,
     pub props: serde_json::Value,
+    /// Diagram node this object draws, so connectors can bind to it.
+    pub node_id: Option<String>,
+    /// Node ids `(from, to)` a connector joins; its `a`/`b` props hold the anchors.
+    pub endpoints: Option<(String, String)>,
+}
+
+/// Convert any parsed diagram into a list of board object descriptors.
+///
+/// Objects are positioned starting from `(origin_x, origin_y)` and scaled by `scale`.
+#[must_use]
+pub fn render_to_objects(diagram: &Diagram, origin_x: f64, origin_y: f64, scale: f64) -> Vec<ObjectDescriptor> {
+    match diagram {
+        Diagram::Sequence(sequence) => render_sequence(sequence, origin_x, origin_y, scale),
+        Diagram::Flowchart(flowchart) => render_flowchart(flowchart, origin_x, origin_y, scale),
+        Diagram::State(state) => render_state_diagram(state, origin_x, origin_y, scale),
+        Diagram::Class(class) => render_class_diagram(class, origin_x, origin_y, scale),
+    }
 }
 
 /// Convert a parsed sequence diagram into a list of board object descriptors.
 ///
 /// Objects are positioned starting from `(origin_x, origin_y)` and scaled by `scale`.
 #[must_use]
-pub fn render_to_objects(diagram: &SequenceDiagram, origin_x: f64, origin_y: f64, scale: f64) -> Vec<ObjectDescriptor> {
+pub fn render_sequence(diagram: &SequenceDiagram, origin_x: f64, origin_y: f64, scale: f64) -> Vec<ObjectDescriptor> {
     let mut objects = Vec::new();
     let participant_count = diagram.participants.len();
     if participant_count == 0 {
@@ -235,6 +263,8 @@ fn render_note(
             "fontSize": 14,
             "textColor": "#1F1A17"
         }),
+        node_id: None,
+        endpoints: None,
     });
 }
 
@@ -299,6 +329,8 @@ fn render_block(
             "stroke": "#78909C",
             "strokeWidth": 1
         }),
+        node_id: None,
+        endpoints: None,
     });
 }
 
@@ -319,6 +351,8 @@ fn make_rect(x: f64, y: f64, w: f64, h: f64, label: &str, fill: &str, stroke: &s
             "fontSize": 14,
             "textColor": "#1F1A17"
         }),
+        node_id: None,
+        endpoints: None,
     }
 }
 
@@ -336,6 +370,8 @@ fn make_lifeline(cx: f64, top: f64, bottom: f64) -> ObjectDescriptor {
             "strokeWidth": 1,
             "dashPattern": LIFELINE_DASH_PATTERN
         }),
+        node_id: None,
+        endpoints: None,
     }
 }
 
@@ -353,7 +389,7 @@ fn make_message_arrow(kind: &str, from_x: f64, to_x: f64, y: f64, is_dashed: boo
             .as_object_mut()
             .map(|m| m.insert("dashPattern".into(), serde_json::json!(LIFELINE_DASH_PATTERN)));
     }
-    ObjectDescriptor { kind: kind.into(), x, y: y - 1.0, width: w, height: 2.0, props }
+    ObjectDescriptor { kind: kind.into(), x, y: y - 1.0, width: w, height: 2.0, props, node_id: None, endpoints: None }
 }
 
 fn make_text(x: f64, y: f64, w: f64, font_size: f64, text: &str) -> ObjectDescriptor {
@@ -368,6 +404,8 @@ fn make_text(x: f64, y: f64, w: f64, font_size: f64, text: &str) -> ObjectDescri
             "fontSize": font_size,
             "textColor": "#1F1A17"
         }),
+        node_id: None,
+        endpoints: None,
     }
 }
 
@@ -383,6 +421,8 @@ fn make_activation(cx: f64, y: f64, height: f64) -> ObjectDescriptor {
             "stroke": "#1565C0",
             "strokeWidth": 1
         }),
+        node_id: None,
+        endpoints: None,
     }
 }
 
@@ -399,7 +439,7 @@ fn make_block_separator(x: f64, y: f64, w: f64, label: &str) -> ObjectDescriptor
             .as_object_mut()
             .map(|m| m.insert("text".into(), serde_json::json!(format!("[{label}]"))));
     }
-    ObjectDescriptor { kind: "line".into(), x, y, width: w, height: 0.0, props }
+    ObjectDescriptor { kind: "line".into(), x, y, width: w, height: 0.0, props, node_id: None, endpoints: None }
 }
 
 /// Count the number of "rows" consumed by a list of events (for lifeline sizing).
@@ -419,3 +459,600 @@ fn count_event_rows(events: &[Event]) -> usize {
     }
     rows
 }
+
+// ---- layered diagrams ----
+
+const NODE_MIN_W: f64 = 120.0;
+const NODE_H: f64 = 50.0;
+const CHAR_W: f64 = 8.0;
+const LABEL_PADDING: f64 = 32.0;
+const CONNECTOR_LABEL_FONT: f64 = 14.0;
+const CONNECTOR_STROKE: &str = "#455A64";
+
+/// How a bound connector is drawn.
+#[derive(Clone, Copy)]
+struct ConnectorStyle {
+    arrowhead: bool,
+    dashed: bool,
+    stroke_width: u32,
+}
+
+impl ConnectorStyle {
+    const ARROW: Self = Self { arrowhead: true, dashed: false, stroke_width: 2 };
+}
+
+/// Width of a box wide enough for its longest label line.
+fn label_width(label: &str, min_w: f64) -> f64 {
+    let longest = label.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+    #[allow(clippy::cast_precision_loss)]
+    let text_w = longest as f64 * CHAR_W + LABEL_PADDING;
+    text_w.max(min_w)
+}
+
+/// Connect the facing sides of two boxes along the flow axis. The connector
+/// names both node ids so the caller can attach its ends.
+fn make_bound_connector(
+    (from_id, from): (&str, NodeBox),
+    (to_id, to): (&str, NodeBox),
+    direction: FlowDirection,
+    style: ConnectorStyle,
+) -> ObjectDescriptor {
+    let (from_anchor, to_anchor) = layered::facing_anchors(from, to, direction);
+    let (ax, ay) = from.at(from_anchor);
+    let (bx, by) = to.at(to_anchor);
+    let kind = if style.arrowhead { "arrow" } else { "line" };
+
+    let mut props = serde_json::json!({
+        "a": { "ux": from_anchor.0, "uy": from_anchor.1, "x": ax, "y": ay },
+        "b": { "ux": to_anchor.0, "uy": to_anchor.1, "x": bx, "y": by },
+        "style": kind,
+        "stroke": CONNECTOR_STROKE,
+        "strokeWidth": style.stroke_width
+    });
+    if style.dashed {
+        props
+            .as_object_mut()
+            .map(|m| m.insert("dashPattern".into(), serde_json::json!(LIFELINE_DASH_PATTERN)));
+    }
+    ObjectDescriptor {
+        kind: kind.into(),
+        x: ax.min(bx),
+        y: ay.min(by),
+        width: (bx - ax).abs().max(1.0),
+        height: (by - ay).abs(),
+        props,
+        node_id: None,
+        endpoints: Some((from_id.to_owned(), to_id.to_owned())),
+    }
+}
+
+/// Text centered on the point `t` of the way along a connector.
+fn make_connector_label(connector: &ObjectDescriptor, t: f64, label: &str) -> ObjectDescriptor {
+    let point = |end: &str| {
+        let p = &connector.props[end];
+        (p["x"].as_f64().unwrap_or(0.0), p["y"].as_f64().unwrap_or(0.0))
+    };
+    let ((ax, ay), (bx, by)) = (point("a"), point("b"));
+    let (cx, cy) = (ax + (bx - ax) * t, ay + (by - ay) * t);
+    #[allow(clippy::cast_precision_loss)]
+    let label_w = (label.chars().count() as f64 * CHAR_W).max(40.0);
+    make_text(
+        cx - label_w / 2.0,
+        cy - CONNECTOR_LABEL_FONT / 2.0,
+        label_w,
+        CONNECTOR_LABEL_FONT,
+        label,
+    )
+}
+
+fn make_group_frame(title: &str, b: NodeBox, node_id: Option<&str>) -> ObjectDescriptor {
+    ObjectDescriptor {
+        kind: "frame".into(),
+        x: b.x,
+        y: b.y,
+        width: b.w,
+        height: b.h,
+        props: serde_json::json!({
+            "title": title,
+            "stroke": "#78909C",
+            "strokeWidth": 1
+        }),
+        node_id: node_id.map(ToOwned::to_owned),
+        endpoints: None,
+    }
+}
+
+fn make_labeled_shape(kind: &str, b: NodeBox, id: &str, props: serde_json::Value) -> ObjectDescriptor {
+    ObjectDescriptor {
+        kind: kind.into(),
+        x: b.x,
+        y: b.y,
+        width: b.w,
+        height: b.h,
+        props,
+        node_id: Some(id.to_owned()),
+        endpoints: None,
+    }
+}
+
+// ---- flowchart ----
+
+const FLOW_DIAMOND_EXTRA: f64 = 40.0;
+const FLOW_CIRCLE_MIN_D: f64 = 80.0;
+
+/// Convert a parsed flowchart into node shapes, bound connectors, edge labels,
+/// and subgraph frames.
+///
+/// Frames come first (outermost first) so they sit beneath their nodes, then
+/// nodes, then connectors. Objects are positioned from `(origin_x, origin_y)`
+/// and scaled by `scale`.
+#[must_use]
+pub fn render_flowchart(chart: &Flowchart, origin_x: f64, origin_y: f64, scale: f64) -> Vec<ObjectDescriptor> {
+    if chart.nodes.is_empty() {
+        return Vec::new();
+    }
+    let index: HashMap<&str, usize> = chart
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.id.as_str(), i))
+        .collect();
+    let group_index: HashMap<&str, usize> = chart
+        .subgraphs
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.id.as_str(), i))
+        .collect();
+    let graph = LayeredGraph {
+        direction: chart.direction,
+        sizes: chart.nodes.iter().map(flow_node_size).collect(),
+        links: chart
+            .edges
+            .iter()
+            .filter_map(|e| Some((*index.get(e.from.as_str())?, *index.get(e.to.as_str())?)))
+            .collect(),
+        group_parents: chart
+            .subgraphs
+            .iter()
+            .map(|s| {
+                s.parent
+                    .as_deref()
+                    .and_then(|p| group_index.get(p).copied())
+            })
+            .collect(),
+        node_groups: chart
+            .nodes
+            .iter()
+            .map(|n| {
+                n.subgraph
+                    .as_deref()
+                    .and_then(|s| group_index.get(s).copied())
+            })
+            .collect(),
+    };
+    let placed = layered::layout(&graph, origin_x, origin_y, scale);
+
+    let mut objects = Vec::new();
+    for (subgraph, b) in chart.subgraphs.iter().zip(&placed.groups) {
+        if let Some(b) = b {
+            objects.push(make_group_frame(&subgraph.title, *b, None));
+        }
+    }
+    for (node, b) in chart.nodes.iter().zip(&placed.nodes) {
+        objects.push(make_flow_node(node, *b));
+    }
+    let mut labels = Vec::new();
+    for edge in &chart.edges {
+        let (Some(&from), Some(&to)) = (index.get(edge.from.as_str()), index.get(edge.to.as_str())) else {
+            continue;
+        };
+        if from == to {
+            continue;
+        }
+        let style = ConnectorStyle {
+            arrowhead: edge.arrowhead,
+            dashed: edge.line == LineStyle::Dotted,
+            stroke_width: if edge.line == LineStyle::Thick { 4 } else { 2 },
+        };
+        let connector = make_bound_connector(
+            (&edge.from, placed.nodes[from]),
+            (&edge.to, placed.nodes[to]),
+            chart.direction,
+            style,
+        );
+        if let Some(label) = &edge.label {
+            labels.push(make_connector_label(&connector, 0.5, label));
+        }
+        objects.push(connector);
+    }
+    objects.extend(labels);
+    objects
+}
+
+/// Size a node from its label and shape.
+fn flow_node_size(node: &FlowNode) -> (f64, f64) {
+    let text_w = label_width(&node.label, 0.0);
+    match node.shape {
+        NodeShape::Rect | NodeShape::Round => (text_w.max(NODE_MIN_W), NODE_H),
+        NodeShape::Diamond => (text_w.max(NODE_MIN_W) + FLOW_DIAMOND_EXTRA, NODE_H + FLOW_DIAMOND_EXTRA),
+        NodeShape::Circle => {
+            let d = text_w.max(FLOW_CIRCLE_MIN_D);
+            (d, d)
+        }
+    }
+}
+
+fn make_flow_node(node: &FlowNode, b: NodeBox) -> ObjectDescriptor {
+    // The board has no rounded rectangle, so round nodes render as ellipses.
+    let (kind, fill, stroke) = match node.shape {
+        NodeShape::Rect => ("rectangle", "#E3F2FD", "#1565C0"),
+        NodeShape::Round | NodeShape::Circle => ("ellipse", "#E8F5E9", "#2E7D32"),
+        NodeShape::Diamond => ("diamond", "#FFF3E0", "#EF6C00"),
+    };
+    make_labeled_shape(
+        kind,
+        b,
+        &node.id,
+        serde_json::json!({
+            "text": node.label,
+            "fill": fill,
+            "stroke": stroke,
+            "strokeWidth": 2,
+            "fontSize": 14,
+            "textColor": "#1F1A17"
+        }),
+    )
+}
+
+// ---- state diagram ----
+
+const STATE_LINE_H: f64 = 20.0;
+const STATE_START_D: f64 = 24.0;
+const STATE_END_D: f64 = 28.0;
+const STATE_CHOICE_D: f64 = 40.0;
+const STATE_FORK_LONG: f64 = 80.0;
+const STATE_FORK_SHORT: f64 = 8.0;
+const STATE_PSEUDO_FILL: &str = "#1F1A17";
+
+/// Convert a parsed state diagram into state shapes, composite-state frames,
+/// and bound transition arrows with labels.
+///
+/// Composite states with children become frames carrying their state id, so
+/// transitions into or out of a composite attach to the frame itself. A
+/// composite with no children draws as an ordinary state.
+#[must_use]
+pub fn render_state_diagram(diagram: &StateDiagram, origin_x: f64, origin_y: f64, scale: f64) -> Vec<ObjectDescriptor> {
+    let has_children = |id: &str| {
+        diagram
+            .states
+            .iter()
+            .any(|s| s.parent.as_deref() == Some(id))
+    };
+    let (groups, nodes): (Vec<_>, Vec<_>) = diagram
+        .states
+        .iter()
+        .partition(|s| s.kind == StateKind::Composite && has_children(&s.id));
+    if nodes.is_empty() {
+        return Vec::new();
+    }
+    let node_index: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.id.as_str(), i))
+        .collect();
+    let group_index: HashMap<&str, usize> = groups
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.id.as_str(), i))
+        .collect();
+    let parent_group = |parent: Option<&String>| parent.and_then(|p| group_index.get(p.as_str()).copied());
+
+    let graph = LayeredGraph {
+        direction: diagram.direction,
+        sizes: nodes
+            .iter()
+            .map(|s| state_size(s, diagram.direction))
+            .collect(),
+        links: state_links(diagram, &nodes, &node_index),
+        group_parents: groups
+            .iter()
+            .map(|s| parent_group(s.parent.as_ref()))
+            .collect(),
+        node_groups: nodes
+            .iter()
+            .map(|s| parent_group(s.parent.as_ref()))
+            .collect(),
+    };
+    let placed = layered::layout(&graph, origin_x, origin_y, scale);
+    let box_of = |id: &str| {
+        node_index
+            .get(id)
+            .map(|&i| placed.nodes[i])
+            .or_else(|| placed.groups[*group_index.get(id)?])
+    };
+
+    let mut objects = Vec::new();
+    for (state, b) in groups.iter().zip(&placed.groups) {
+        if let Some(b) = b {
+            objects.push(make_group_frame(&state.label, *b, Some(&state.id)));
+        }
+    }
+    for (state, b) in nodes.iter().zip(&placed.nodes) {
+        objects.push(make_state(state, *b));
+    }
+    let mut labels = Vec::new();
+    for transition in &diagram.transitions {
+        let (Some(from), Some(to)) = (box_of(&transition.from), box_of(&transition.to)) else {
+            continue;
+        };
+        if transition.from == transition.to {
+            continue;
+        }
+        let connector = make_bound_connector(
+            (&transition.from, from),
+            (&transition.to, to),
+            diagram.direction,
+            ConnectorStyle::ARROW,
+        );
+        if let Some(label) = &transition.label {
+            labels.push(make_connector_label(&connector, 0.5, label));
+        }
+        objects.push(connector);
+    }
+    objects.extend(labels);
+    objects
+}
+
+/// Layering links for transitions, which may start or end at a composite.
+///
+/// Transitions into a composite are layered toward its entry states and
+/// transitions out of it from its exit states, so the whole frame sits
+/// between its neighbours.
+fn state_links(diagram: &StateDiagram, nodes: &[&State], node_index: &HashMap<&str, usize>) -> Vec<(usize, usize)> {
+    let parent_of: HashMap<&str, Option<&str>> = diagram
+        .states
+        .iter()
+        .map(|s| (s.id.as_str(), s.parent.as_deref()))
+        .collect();
+    let is_within = |state: &str, composite: &str| {
+        let mut current = parent_of.get(state).copied().flatten();
+        while let Some(parent) = current {
+            if parent == composite {
+                return true;
+            }
+            current = parent_of.get(parent).copied().flatten();
+        }
+        false
+    };
+    let members = |id: &str| -> Vec<usize> {
+        match node_index.get(id) {
+            Some(&i) => vec![i],
+            None => (0..nodes.len())
+                .filter(|&i| is_within(&nodes[i].id, id))
+                .collect(),
+        }
+    };
+    let direct_links: Vec<(usize, usize)> = diagram
+        .transitions
+        .iter()
+        .filter_map(|t| Some((*node_index.get(t.from.as_str())?, *node_index.get(t.to.as_str())?)))
+        .collect();
+    let boundary = |id: &str, exits: bool| {
+        let inside = members(id);
+        let edge: Vec<usize> = inside
+            .iter()
+            .copied()
+            .filter(|&i| {
+                !direct_links.iter().any(|&(from, to)| {
+                    if exits {
+                        from == i && inside.contains(&to)
+                    } else {
+                        to == i && inside.contains(&from)
+                    }
+                })
+            })
+            .collect();
+        if edge.is_empty() {
+            inside.into_iter().take(1).collect()
+        } else {
+            edge
+        }
+    };
+    let mut links = Vec::new();
+    for transition in &diagram.transitions {
+        let targets = boundary(&transition.to, false);
+        for from in boundary(&transition.from, true) {
+            links.extend(targets.iter().map(|&to| (from, to)));
+        }
+    }
+    links
+}
+
+fn state_size(state: &State, direction: FlowDirection) -> (f64, f64) {
+    match state.kind {
+        StateKind::Start => (STATE_START_D, STATE_START_D),
+        StateKind::End => (STATE_END_D, STATE_END_D),
+        StateKind::Choice => (STATE_CHOICE_D, STATE_CHOICE_D),
+        // The bar lies across the flow.
+        StateKind::Fork => {
+            if matches!(direction, FlowDirection::LeftRight | FlowDirection::RightLeft) {
+                (STATE_FORK_SHORT, STATE_FORK_LONG)
+            } else {
+                (STATE_FORK_LONG, STATE_FORK_SHORT)
+            }
+        }
+        StateKind::Simple | StateKind::Composite => {
+            #[allow(clippy::cast_precision_loss)]
+            let lines = state.label.lines().count().max(1) as f64;
+            (label_width(&state.label, NODE_MIN_W), NODE_H + (lines - 1.0) * STATE_LINE_H)
+        }
+    }
+}
+
+fn make_state(state: &State, b: NodeBox) -> ObjectDescriptor {
+    let props = match state.kind {
+        StateKind::Start | StateKind::Fork => serde_json::json!({
+            "fill": STATE_PSEUDO_FILL,
+            "stroke": STATE_PSEUDO_FILL,
+            "strokeWidth": 1
+        }),
+        // A filled disc with a heavy ring reads as the UML bullseye.
+        StateKind::End => serde_json::json!({
+            "fill": STATE_PSEUDO_FILL,
+            "stroke": "#9E9E9E",
+            "strokeWidth": 4
+        }),
+        StateKind::Choice => serde_json::json!({
+            "fill": "#FFF3E0",
+            "stroke": "#EF6C00",
+            "strokeWidth": 2
+        }),
+        StateKind::Simple | StateKind::Composite => serde_json::json!({
+            "text": state.label,
+            "fill": "#E8EAF6",
+            "stroke": "#3949AB",
+            "strokeWidth": 2,
+            "fontSize": 14,
+            "textColor": "#1F1A17"
+        }),
+    };
+    let kind = match state.kind {
+        StateKind::Start | StateKind::End => "ellipse",
+        StateKind::Choice => "diamond",
+        StateKind::Fork | StateKind::Simple | StateKind::Composite => "rectangle",
+    };
+    make_labeled_shape(kind, b, &state.id, props)
+}
+
+// ---- class diagram ----
+
+const CLASS_MIN_W: f64 = 140.0;
+const CLASS_LINE_H: f64 = 18.0;
+const CLASS_PADDING_H: f64 = 24.0;
+const CARDINALITY_OFFSET: f64 = 0.15;
+
+/// Convert a parsed class diagram into class boxes and bound relation
+/// connectors with labels and cardinalities.
+///
+/// Each class is one rectangle listing its annotation, name, attributes, and
+/// methods. Connectors are oriented so the relation's marker end is the
+/// connector's `b` end, and carry a `relation` prop naming the UML relation.
+#[must_use]
+pub fn render_class_diagram(diagram: &ClassDiagram, origin_x: f64, origin_y: f64, scale: f64) -> Vec<ObjectDescriptor> {
+    if diagram.classes.is_empty() {
+        return Vec::new();
+    }
+    let index: HashMap<&str, usize> = diagram
+        .classes
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.id.as_str(), i))
+        .collect();
+    let texts: Vec<String> = diagram.classes.iter().map(class_text).collect();
+    let graph = LayeredGraph {
+        direction: diagram.direction,
+        sizes: texts.iter().map(|t| class_size(t)).collect(),
+        links: diagram
+            .relations
+            .iter()
+            .filter_map(|r| Some((*index.get(r.from.as_str())?, *index.get(r.to.as_str())?)))
+            .collect(),
+        group_parents: Vec::new(),
+        node_groups: vec![None; diagram.classes.len()],
+    };
+    let placed = layered::layout(&graph, origin_x, origin_y, scale);
+
+    let mut objects: Vec<ObjectDescriptor> = diagram
+        .classes
+        .iter()
+        .zip(&texts)
+        .zip(&placed.nodes)
+        .map(|((class, text), b)| {
+            make_labeled_shape(
+                "rectangle",
+                *b,
+                &class.id,
+                serde_json::json!({
+                    "text": text,
+                    "fill": "#FFFDE7",
+                    "stroke": "#5D4037",
+                    "strokeWidth": 2,
+                    "fontSize": 14,
+                    "textColor": "#1F1A17"
+                }),
+            )
+        })
+        .collect();
+    let mut labels = Vec::new();
+    for relation in &diagram.relations {
+        let (Some(&left), Some(&right)) = (index.get(relation.from.as_str()), index.get(relation.to.as_str())) else {
+            continue;
+        };
+        if left == right {
+            continue;
+        }
+        let left_end = (relation.from.as_str(), placed.nodes[left], relation.from_cardinality.as_deref());
+        let right_end = (relation.to.as_str(), placed.nodes[right], relation.to_cardinality.as_deref());
+        // The marker belongs on the `b` end.
+        let (a, b) = if relation.marker_at == MarkerEnd::From {
+            (right_end, left_end)
+        } else {
+            (left_end, right_end)
+        };
+        let arrowhead = relation.marker_at != MarkerEnd::Neither
+            && matches!(relation.kind, RelationKind::Inheritance | RelationKind::Association);
+        let style = ConnectorStyle { arrowhead, dashed: relation.dashed, stroke_width: 2 };
+        let mut connector = make_bound_connector((a.0, a.1), (b.0, b.1), diagram.direction, style);
+        connector
+            .props
+            .as_object_mut()
+            .map(|m| m.insert("relation".into(), serde_json::json!(relation_name(relation))));
+
+        if let Some(label) = &relation.label {
+            labels.push(make_connector_label(&connector, 0.5, label));
+        }
+        if let Some(cardinality) = a.2 {
+            labels.push(make_connector_label(&connector, CARDINALITY_OFFSET, cardinality));
+        }
+        if let Some(cardinality) = b.2 {
+            labels.push(make_connector_label(&connector, 1.0 - CARDINALITY_OFFSET, cardinality));
+        }
+        objects.push(connector);
+    }
+    objects.extend(labels);
+    objects
+}
+
+/// Class box text: annotation, name, then attribute and method sections
+/// separated by blank lines.
+fn class_text(class: &Class) -> String {
+    let mut header = Vec::new();
+    if let Some(annotation) = &class.annotation {
+        header.push(format!("«{annotation}»"));
+    }
+    header.push(class.label.clone());
+    [header, class.attributes.clone(), class.methods.clone()]
+        .iter()
+        .filter(|section| !section.is_empty())
+        .map(|section| section.join("\n"))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn class_size(text: &str) -> (f64, f64) {
+    #[allow(clippy::cast_precision_loss)]
+    let lines = text.lines().count().max(1) as f64;
+    (label_width(text, CLASS_MIN_W), lines * CLASS_LINE_H + CLASS_PADDING_H)
+}
+
+fn relation_name(relation: &Relation) -> &'static str {
+    match (relation.kind, relation.dashed) {
+        (RelationKind::Inheritance, false) => "inheritance",
+        (RelationKind::Inheritance, true) => "realization",
+        (RelationKind::Composition, _) => "composition",
+        (RelationKind::Aggregation, _) => "aggregation",
+        (RelationKind::Association, false) => "association",
+        (RelationKind::Association, true) => "dependency",
+        (RelationKind::Link, _) => "link",
+    }
+}
@@ -1,15 +1,22 @@
-//! Mermaid sequence diagram parser and layout engine.
+//! Mermaid diagram parser and layout engine.
 //!
-//! Parses Mermaid `sequenceDiagram` syntax into an AST and lays out the diagram
-//! as a collection of board object descriptors (rectangles, arrows, text, frames)
-//! ready for creation via the AI tool system.
+//! Parses Mermaid `sequenceDiagram`, `flowchart` / `graph`, `stateDiagram`,
+//! and `classDiagram` syntax into an AST and lays out the diagram as a
+//! collection of board object descriptors (shapes, arrows, text, frames)
+//! ready for creation via the AI tool system. Connectors in the layered
+//! diagrams name the node ids they join so the caller can bind them to the
+//! created shapes. Going the other way, board shapes and attached connectors
+//! export back to `flowchart` text.
 
 pub mod ast;
+pub mod export;
+pub mod layered;
 pub mod layout;
 pub mod parse;
 
+pub use export::{ExportObject, ExportScope, export_flowchart};
 pub use layout::render_to_objects;
-pub use parse::parse;
+pub use parse::parse_diagram;
 
 #[cfg(test)]
 #[path = "mod_test.rs"]
@@ -1,7 +1,12 @@
-//! Tests for the Mermaid sequence diagram parser and layout engine.
+//! Tests for the Mermaid parser and layout engine.
 
-use super::ast::{ArrowStyle, BlockKind, Event, NotePosition};
-use super::{parse, render_to_objects};
+use super::ast::{
+    ArrowStyle, BlockKind, Diagram, Event, FlowDirection, LineStyle, MarkerEnd, NodeShape, NotePosition, RelationKind,
+    StateKind,
+};
+use super::layout::{ObjectDescriptor, render_class_diagram, render_flowchart, render_sequence, render_state_diagram};
+use super::parse::{parse, parse_class_diagram, parse_flowchart, parse_state_diagram};
+use super::{ExportObject, ExportScope, export_flowchart, parse_diagram, render_to_objects};
 
 // =============================================================================
 // PARSER TESTS
@@ -281,7 +286,7 @@ fn layout_two_participants_three_messages() {
         Alice->>Bob: Third
     ";
     let diagram = parse(input).unwrap();
-    let objects = render_to_objects(&diagram, 0.0, 0.0, 1.0);
+    let objects = render_sequence(&diagram, 0.0, 0.0, 1.0);
 
     // Expected objects:
     // 2 top participant boxes + 2 lifelines + 2 bottom participant boxes = 6
@@ -310,7 +315,7 @@ fn layout_two_participants_three_messages() {
 fn layout_empty_diagram() {
     let input = "sequenceDiagram";
     let diagram = parse(input).unwrap();
-    let objects = render_to_objects(&diagram, 0.0, 0.0, 1.0);
+    let objects = render_sequence(&diagram, 0.0, 0.0, 1.0);
     assert!(objects.is_empty());
 }
 
@@ -323,9 +328,9 @@ fn layout_with_offset_and_scale() {
         Alice->>Bob: Hello
     ";
     let diagram = parse(input).unwrap();
-    let objects_default = render_to_objects(&diagram, 0.0, 0.0, 1.0);
-    let objects_offset = render_to_objects(&diagram, 100.0, 200.0, 1.0);
-    let objects_scaled = render_to_objects(&diagram, 0.0, 0.0, 2.0);
+    let objects_default = render_sequence(&diagram, 0.0, 0.0, 1.0);
+    let objects_offset = render_sequence(&diagram, 100.0, 200.0, 1.0);
+    let objects_scaled = render_sequence(&diagram, 0.0, 0.0, 2.0);
 
     assert_eq!(objects_default.len(), objects_offset.len());
     assert_eq!(objects_default.len(), objects_scaled.len());
@@ -357,7 +362,7 @@ fn layout_with_activation() {
         deactivate Bob
     ";
     let diagram = parse(input).unwrap();
-    let objects = render_to_objects(&diagram, 0.0, 0.0, 1.0);
+    let objects = render_sequence(&diagram, 0.0, 0.0, 1.0);
 
     // Check that activation rectangle exists (thin rectangle on Bob's lifeline).
     let rects: Vec<_> = objects.iter().filter(|o| o.kind == "rectangle").collect();
@@ -374,7 +379,7 @@ fn layout_with_block_creates_frame() {
         end
     ";
     let diagram = parse(input).unwrap();
-    let objects = render_to_objects(&diagram, 0.0, 0.0, 1.0);
+    let objects = render_sequence(&diagram, 0.0, 0.0, 1.0);
 
     let frames: Vec<_> = objects.iter().filter(|o| o.kind == "frame").collect();
     assert_eq!(frames.len(), 1);
@@ -394,7 +399,7 @@ fn layout_with_note() {
         Note right of Alice: Important
     ";
     let diagram = parse(input).unwrap();
-    let objects = render_to_objects(&diagram, 0.0, 0.0, 1.0);
+    let objects = render_sequence(&diagram, 0.0, 0.0, 1.0);
 
     let notes: Vec<_> = objects.iter().filter(|o| o.kind == "sticky_note").collect();
     assert_eq!(notes.len(), 1);
@@ -427,7 +432,7 @@ fn end_to_end_full_diagram() {
     assert_eq!(diagram.participants.len(), 3);
     assert_eq!(diagram.events.len(), 9); // 4 messages + 2 activate + 2 deactivate + 1 note
 
-    let objects = render_to_objects(&diagram, 50.0, 100.0, 1.5);
+    let objects = render_sequence(&diagram, 50.0, 100.0, 1.5);
     assert!(!objects.is_empty());
 
     // Verify all positions are offset.
@@ -442,3 +447,840 @@ fn end_to_end_full_diagram() {
     assert!(kinds.contains(&"text"));
     assert!(kinds.contains(&"sticky_note"));
 }
+
+// =============================================================================
+// FLOWCHART PARSER TESTS
+// =============================================================================
+
+#[test]
+fn parse_diagram_dispatches_on_header() {
+    assert!(matches!(parse_diagram("flowchart LR\nA --> B").unwrap(), Diagram::Flowchart(_)));
+    assert!(matches!(parse_diagram("graph TD\nA --> B").unwrap(), Diagram::Flowchart(_)));
+    assert!(matches!(
+        parse_diagram("sequenceDiagram\nA->>B: hi").unwrap(),
+        Diagram::Sequence(_)
+    ));
+    assert!(matches!(parse_diagram("A->>B: hi").unwrap(), Diagram::Sequence(_)));
+    assert!(matches!(
+        parse_diagram("stateDiagram-v2\n[*] --> A").unwrap(),
+        Diagram::State(_)
+    ));
+    assert!(matches!(parse_diagram("classDiagram\nA <|-- B").unwrap(), Diagram::Class(_)));
+}
+
+#[test]
+fn parse_flowchart_directions() {
+    assert_eq!(parse_flowchart("flowchart TD").unwrap().direction, FlowDirection::TopDown);
+    assert_eq!(parse_flowchart("graph TB").unwrap().direction, FlowDirection::TopDown);
+    assert_eq!(parse_flowchart("flowchart LR").unwrap().direction, FlowDirection::LeftRight);
+    assert_eq!(parse_flowchart("flowchart RL").unwrap().direction, FlowDirection::RightLeft);
+    assert_eq!(parse_flowchart("graph BT").unwrap().direction, FlowDirection::BottomUp);
+    assert_eq!(parse_flowchart("flowchart").unwrap().direction, FlowDirection::TopDown);
+    assert!(parse_flowchart("flowchart XY").is_err());
+}
+
+#[test]
+fn parse_flowchart_node_shapes() {
+    let input = r"
+        flowchart TD
+        A[Start] --> B(Work) --> C{Done?} --> D((End))
+        E([Stadium])
+    ";
+    let chart = parse_flowchart(input).unwrap();
+    let shapes: Vec<_> = chart
+        .nodes
+        .iter()
+        .map(|n| (n.id.as_str(), n.label.as_str(), n.shape))
+        .collect();
+    assert_eq!(
+        shapes,
+        vec![
+            ("A", "Start", NodeShape::Rect),
+            ("B", "Work", NodeShape::Round),
+            ("C", "Done?", NodeShape::Diamond),
+            ("D", "End", NodeShape::Circle),
+            ("E", "Stadium", NodeShape::Round),
+        ]
+    );
+    assert_eq!(chart.edges.len(), 3);
+}
+
+#[test]
+fn parse_flowchart_bare_ids_default_to_rect_labeled_by_id() {
+    let chart = parse_flowchart("graph LR\nA --> B;B --> C").unwrap();
+    assert_eq!(chart.nodes.len(), 3);
+    assert_eq!(chart.nodes[2].label, "C");
+    assert_eq!(chart.nodes[2].shape, NodeShape::Rect);
+    assert_eq!(chart.edges.len(), 2);
+}
+
+#[test]
+fn parse_flowchart_later_shape_relabels_node() {
+    let chart = parse_flowchart("flowchart TD\nA --> B\nB{Check}").unwrap();
+    assert_eq!(chart.nodes[1].label, "Check");
+    assert_eq!(chart.nodes[1].shape, NodeShape::Diamond);
+}
+
+#[test]
+fn parse_flowchart_quoted_label_may_contain_brackets() {
+    let chart = parse_flowchart(
+        r#"flowchart TD
+A["Array [0]"] --> B"#,
+    )
+    .unwrap();
+    assert_eq!(chart.nodes[0].label, "Array [0]");
+}
+
+#[test]
+fn parse_flowchart_link_styles_and_labels() {
+    let input = r"
+        flowchart LR
+        A -->|yes| B
+        A -- no --> C
+        B --- C
+        C -.-> D
+        D -. maybe .-> E
+        E ==> F
+        F == go ==> G
+        G === H
+    ";
+    let chart = parse_flowchart(input).unwrap();
+    let edges: Vec<_> = chart
+        .edges
+        .iter()
+        .map(|e| (e.from.as_str(), e.to.as_str(), e.label.as_deref(), e.line, e.arrowhead))
+        .collect();
+    assert_eq!(
+        edges,
+        vec![
+            ("A", "B", Some("yes"), LineStyle::Solid, true),
+            ("A", "C", Some("no"), LineStyle::Solid, true),
+            ("B", "C", None, LineStyle::Solid, false),
+            ("C", "D", None, LineStyle::Dotted, true),
+            ("D", "E", Some("maybe"), LineStyle::Dotted, true),
+            ("E", "F", None, LineStyle::Thick, true),
+            ("F", "G", Some("go"), LineStyle::Thick, true),
+            ("G", "H", None, LineStyle::Thick, false),
+        ]
+    );
+}
+
+#[test]
+fn parse_flowchart_ampersand_fans_out() {
+    let chart = parse_flowchart("flowchart TD\nA & B --> C & D").unwrap();
+    assert_eq!(chart.edges.len(), 4);
+}
+
+#[test]
+fn parse_flowchart_subgraphs_nest_and_own_new_nodes() {
+    let input = r"
+        flowchart TD
+        Start --> A
+        subgraph outer [Backend]
+            A --> B
+            subgraph inner
+                C
+            end
+        end
+        B --> C
+    ";
+    let chart = parse_flowchart(input).unwrap();
+    assert_eq!(chart.subgraphs.len(), 2);
+    assert_eq!(chart.subgraphs[0].id, "outer");
+    assert_eq!(chart.subgraphs[0].title, "Backend");
+    assert_eq!(chart.subgraphs[1].id, "inner");
+    assert_eq!(chart.subgraphs[1].parent.as_deref(), Some("outer"));
+
+    let subgraph_of = |id: &str| {
+        chart
+            .nodes
+            .iter()
+            .find(|n| n.id == id)
+            .and_then(|n| n.subgraph.clone())
+    };
+    // Mentioning a node inside a subgraph moves it there; a mention after
+    // `end` does not move it back out.
+    assert_eq!(subgraph_of("Start"), None);
+    assert_eq!(subgraph_of("A").as_deref(), Some("outer"));
+    assert_eq!(subgraph_of("B").as_deref(), Some("outer"));
+    assert_eq!(subgraph_of("C").as_deref(), Some("inner"));
+}
+
+#[test]
+fn parse_flowchart_ignores_styling_and_comments() {
+    let input = r"
+        flowchart TD
+        %% a comment
+        classDef hot fill:#f00
+        A --> B
+        class A hot
+        style B fill:#0f0
+    ";
+    let chart = parse_flowchart(input).unwrap();
+    assert_eq!(chart.nodes.len(), 2);
+    assert_eq!(chart.edges.len(), 1);
+}
+
+#[test]
+fn parse_flowchart_errors() {
+    assert!(parse_flowchart("flowchart TD\nsubgraph one\nA").is_err());
+    assert!(parse_flowchart("flowchart TD\nend").is_err());
+    assert!(parse_flowchart("flowchart TD\nA[open --> B").is_err());
+    assert!(parse_flowchart("flowchart TD\nA -->").is_err());
+    assert!(parse_flowchart("flowchart TD\nsubgraph A\nsubgraph A\nx\nend\nend").is_err());
+    assert!(parse_flowchart("flowchart TD\nsubgraph A\nend\nsubgraph A [Again]\nend").is_err());
+}
+
+// =============================================================================
+// FLOWCHART LAYOUT TESTS
+// =============================================================================
+
+fn node<'a>(objects: &'a [ObjectDescriptor], id: &str) -> &'a ObjectDescriptor {
+    objects
+        .iter()
+        .find(|o| o.node_id.as_deref() == Some(id))
+        .unwrap_or_else(|| panic!("node {id} not rendered"))
+}
+
+#[test]
+fn layout_flowchart_kinds_and_bound_connectors() {
+    let input = r"
+        flowchart TD
+        A[Start] --> B{Ok?}
+        B -->|yes| C((Done))
+        B -.- D(Retry)
+    ";
+    let objects = render_flowchart(&parse_flowchart(input).unwrap(), 0.0, 0.0, 1.0);
+
+    assert_eq!(node(&objects, "A").kind, "rectangle");
+    assert_eq!(node(&objects, "B").kind, "diamond");
+    assert_eq!(node(&objects, "C").kind, "ellipse");
+    assert!((node(&objects, "C").width - node(&objects, "C").height).abs() < 1e-9);
+    assert_eq!(node(&objects, "A").props["text"], "Start");
+
+    let connectors: Vec<_> = objects.iter().filter(|o| o.endpoints.is_some()).collect();
+    assert_eq!(connectors.len(), 3);
+    assert_eq!(connectors[0].kind, "arrow");
+    assert_eq!(connectors[0].endpoints, Some(("A".into(), "B".into())));
+    let dotted = connectors
+        .iter()
+        .find(|c| c.endpoints == Some(("B".into(), "D".into())))
+        .unwrap();
+    assert_eq!(dotted.kind, "line");
+    assert!(dotted.props.get("dashPattern").is_some());
+
+    let labels: Vec<_> = objects.iter().filter(|o| o.kind == "text").collect();
+    assert_eq!(labels.len(), 1);
+    assert_eq!(labels[0].props["text"], "yes");
+}
+
+#[test]
+fn layout_flowchart_layers_follow_direction() {
+    let chain = "A --> B\nB --> C";
+    let td = render_flowchart(&parse_flowchart(&format!("flowchart TD\n{chain}")).unwrap(), 0.0, 0.0, 1.0);
+    assert!(node(&td, "A").y < node(&td, "B").y);
+    assert!(node(&td, "B").y < node(&td, "C").y);
+    assert!((node(&td, "A").x - node(&td, "C").x).abs() < 1e-9);
+
+    let lr = render_flowchart(&parse_flowchart(&format!("flowchart LR\n{chain}")).unwrap(), 0.0, 0.0, 1.0);
+    assert!(node(&lr, "A").x < node(&lr, "B").x);
+    assert!(node(&lr, "B").x < node(&lr, "C").x);
+
+    let bt = render_flowchart(&parse_flowchart(&format!("flowchart BT\n{chain}")).unwrap(), 0.0, 0.0, 1.0);
+    assert!(node(&bt, "A").y > node(&bt, "C").y);
+
+    let rl = render_flowchart(&parse_flowchart(&format!("flowchart RL\n{chain}")).unwrap(), 0.0, 0.0, 1.0);
+    assert!(node(&rl, "A").x > node(&rl, "C").x);
+}
+
+#[test]
+fn layout_flowchart_uses_longest_path_layers() {
+    // C is reached directly from A and through B; it belongs below B.
+    let chart = parse_flowchart("flowchart TD\nA --> B --> C\nA --> C").unwrap();
+    let objects = render_flowchart(&chart, 0.0, 0.0, 1.0);
+    assert!(node(&objects, "B").y < node(&objects, "C").y);
+}
+
+#[test]
+fn layout_flowchart_handles_cycles() {
+    let chart = parse_flowchart("flowchart TD\nA --> B --> C --> A\nC --> C").unwrap();
+    let objects = render_flowchart(&chart, 0.0, 0.0, 1.0);
+    assert!(node(&objects, "A").y < node(&objects, "B").y);
+    assert!(node(&objects, "B").y < node(&objects, "C").y);
+    // The self-loop has no drawable connector; the back edge is still drawn.
+    let connectors = objects.iter().filter(|o| o.endpoints.is_some()).count();
+    assert_eq!(connectors, 3);
+}
+
+#[test]
+fn layout_flowchart_barycenter_removes_avoidable_crossing() {
+    // Declared so that a naive first-appearance order crosses A->D and B->C.
+    let chart = parse_flowchart("flowchart TD\nA --> D\nB --> C\nA & B\nC\nD").unwrap();
+    let objects = render_flowchart(&chart, 0.0, 0.0, 1.0);
+    let a_left_of_b = node(&objects, "A").x < node(&objects, "B").x;
+    let d_left_of_c = node(&objects, "D").x < node(&objects, "C").x;
+    assert_eq!(a_left_of_b, d_left_of_c);
+}
+
+#[test]
+fn layout_flowchart_nodes_do_not_overlap() {
+    let chart = parse_flowchart("flowchart TD\nA --> B & C & D\nB & C & D --> E").unwrap();
+    let objects = render_flowchart(&chart, 0.0, 0.0, 1.0);
+    let nodes: Vec<_> = objects.iter().filter(|o| o.node_id.is_some()).collect();
+    for (i, a) in nodes.iter().enumerate() {
+        for b in &nodes[i + 1..] {
+            let apart = a.x + a.width <= b.x || b.x + b.width <= a.x || a.y + a.height <= b.y || b.y + b.height <= a.y;
+            assert!(apart, "{:?} overlaps {:?}", a.node_id, b.node_id);
+        }
+    }
+}
+
+#[test]
+fn layout_flowchart_frames_enclose_subgraph_nodes() {
+    let input = r"
+        flowchart LR
+        Client --> API
+        subgraph backend [Backend]
+            API --> DB
+            subgraph storage [Storage]
+                DB
+            end
+        end
+    ";
+    let objects = render_flowchart(&parse_flowchart(input).unwrap(), 0.0, 0.0, 1.0);
+    let frames: Vec<_> = objects.iter().filter(|o| o.kind == "frame").collect();
+    assert_eq!(frames.len(), 2);
+    // Outer frames are emitted first so they sit beneath inner ones.
+    assert_eq!(frames[0].props["title"], "Backend");
+    assert_eq!(frames[1].props["title"], "Storage");
+    assert_eq!(objects[0].kind, "frame");
+
+    let inside = |frame: &ObjectDescriptor, obj: &ObjectDescriptor| {
+        obj.x >= frame.x
+            && obj.y >= frame.y
+            && obj.x + obj.width <= frame.x + frame.width
+            && obj.y + obj.height <= frame.y + frame.height
+    };
+    assert!(inside(frames[0], node(&objects, "API")));
+    assert!(inside(frames[0], node(&objects, "DB")));
+    assert!(inside(frames[0], frames[1]));
+    assert!(inside(frames[1], node(&objects, "DB")));
+    assert!(!inside(frames[0], node(&objects, "Client")));
+}
+
+#[test]
+fn layout_flowchart_survives_self_parented_subgraph() {
+    // Built by hand: the parser rejects the repeated id that produces this.
+    let mut chart = parse_flowchart("flowchart TD\nsubgraph A\nsubgraph B\nx\nend\nend").unwrap();
+    chart.subgraphs[1].id = "A".to_owned();
+    chart.subgraphs[1].parent = Some("A".to_owned());
+    chart.nodes[0].subgraph = Some("A".to_owned());
+    let objects = render_flowchart(&chart, 0.0, 0.0, 1.0);
+    assert!(objects.iter().any(|o| o.node_id.as_deref() == Some("x")));
+}
+
+#[test]
+fn layout_flowchart_connector_anchors_face_each_other() {
+    let objects = render_flowchart(&parse_flowchart("flowchart TD\nA --> B").unwrap(), 0.0, 0.0, 1.0);
+    let arrow = objects.iter().find(|o| o.endpoints.is_some()).unwrap();
+    assert_eq!(arrow.props["a"]["uy"], 1.0);
+    assert_eq!(arrow.props["b"]["uy"], 0.0);
+    let a = node(&objects, "A");
+    assert_eq!(arrow.props["a"]["y"], a.y + a.height);
+}
+
+#[test]
+fn layout_flowchart_offset_and_scale() {
+    let chart = parse_flowchart("flowchart TD\nA --> B").unwrap();
+    let base = render_flowchart(&chart, 0.0, 0.0, 1.0);
+    let moved = render_flowchart(&chart, 100.0, 50.0, 2.0);
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+    assert!(close(node(&base, "A").x, 0.0));
+    assert!(close(node(&base, "A").y, 0.0));
+    assert!(close(node(&moved, "A").x, 100.0));
+    assert!(close(node(&moved, "A").y, 50.0));
+    assert!(close(node(&moved, "B").width, node(&base, "B").width * 2.0));
+    assert!(close(node(&moved, "B").y, 50.0 + node(&base, "B").y * 2.0));
+}
+
+#[test]
+fn render_to_objects_renders_flowcharts_and_empty_charts() {
+    let diagram = parse_diagram("flowchart TD\nA --> B").unwrap();
+    assert_eq!(render_to_objects(&diagram, 0.0, 0.0, 1.0).len(), 3);
+    let empty = parse_diagram("flowchart TD").unwrap();
+    assert!(render_to_objects(&empty, 0.0, 0.0, 1.0).is_empty());
+}
+
+// =============================================================================
+// STATE DIAGRAM TESTS
+// =============================================================================
+
+#[test]
+fn parse_state_transitions_and_pseudo_states() {
+    let input = r"
+        stateDiagram-v2
+        [*] --> Still
+        Still --> Moving : push
+        Moving --> Still
+        Moving --> [*]
+    ";
+    let diagram = parse_state_diagram(input).unwrap();
+    let ids: Vec<&str> = diagram.states.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids, vec!["[*]start", "Still", "Moving", "[*]end"]);
+    assert_eq!(diagram.states[0].kind, StateKind::Start);
+    assert_eq!(diagram.states[3].kind, StateKind::End);
+    assert_eq!(diagram.transitions.len(), 4);
+    assert_eq!(diagram.transitions[1].label.as_deref(), Some("push"));
+    assert_eq!(diagram.transitions[2].label, None);
+}
+
+#[test]
+fn parse_state_composites_scope_pseudo_states() {
+    let input = r"
+        stateDiagram-v2
+        [*] --> Active
+        state Active {
+            [*] --> Idle
+            Idle --> Busy
+        }
+        Active --> [*]
+    ";
+    let diagram = parse_state_diagram(input).unwrap();
+    let state = |id: &str| diagram.states.iter().find(|s| s.id == id).unwrap();
+    assert_eq!(state("Active").kind, StateKind::Composite);
+    assert_eq!(state("Active").parent, None);
+    assert_eq!(state("Idle").parent.as_deref(), Some("Active"));
+    assert_eq!(state("[*]start:Active").parent.as_deref(), Some("Active"));
+    assert!(diagram.states.iter().any(|s| s.id == "[*]start"));
+    assert!(diagram.states.iter().any(|s| s.id == "[*]end"));
+}
+
+#[test]
+fn parse_state_declarations_and_descriptions() {
+    let input = r#"
+        stateDiagram
+        direction LR
+        state "Waiting for input" as Wait
+        state Check <<choice>>
+        state Split <<fork>>
+        Run : Running
+        Run : fast
+        note right of Run : ignored
+        note left of Wait
+            multi-line note
+        end note
+        Wait --> Check
+    "#;
+    let diagram = parse_state_diagram(input).unwrap();
+    let state = |id: &str| diagram.states.iter().find(|s| s.id == id).unwrap();
+    assert_eq!(diagram.direction, FlowDirection::LeftRight);
+    assert_eq!(state("Wait").label, "Waiting for input");
+    assert_eq!(state("Check").kind, StateKind::Choice);
+    assert_eq!(state("Split").kind, StateKind::Fork);
+    assert_eq!(state("Run").label, "Running\nfast");
+    assert_eq!(diagram.states.len(), 4);
+    assert_eq!(diagram.transitions.len(), 1);
+}
+
+#[test]
+fn parse_state_errors() {
+    assert!(parse_state_diagram("stateDiagram\nstate A {\nB --> C").is_err());
+    assert!(parse_state_diagram("stateDiagram\n}").is_err());
+    assert!(parse_state_diagram("stateDiagram\nA -->").is_err());
+    assert!(parse_state_diagram("stateDiagram\ndirection XY").is_err());
+}
+
+#[test]
+fn layout_state_kinds_and_bound_transitions() {
+    let input = r"
+        stateDiagram-v2
+        [*] --> Idle
+        Idle --> Check : poll
+        state Check <<choice>>
+        Check --> Idle
+        Check --> [*]
+    ";
+    let objects = render_state_diagram(&parse_state_diagram(input).unwrap(), 0.0, 0.0, 1.0);
+    assert_eq!(node(&objects, "[*]start").kind, "ellipse");
+    assert_eq!(node(&objects, "[*]end").kind, "ellipse");
+    assert_eq!(node(&objects, "Idle").kind, "rectangle");
+    assert_eq!(node(&objects, "Idle").props["text"], "Idle");
+    assert_eq!(node(&objects, "Check").kind, "diamond");
+
+    let arrows: Vec<_> = objects.iter().filter(|o| o.endpoints.is_some()).collect();
+    assert_eq!(arrows.len(), 4);
+    assert!(arrows.iter().all(|a| a.kind == "arrow"));
+    assert_eq!(arrows[0].endpoints, Some(("[*]start".into(), "Idle".into())));
+    assert!(node(&objects, "[*]start").y < node(&objects, "Idle").y);
+
+    let labels: Vec<_> = objects.iter().filter(|o| o.kind == "text").collect();
+    assert_eq!(labels.len(), 1);
+    assert_eq!(labels[0].props["text"], "poll");
+}
+
+#[test]
+fn layout_state_composite_is_a_bindable_frame_around_children() {
+    let input = r"
+        stateDiagram-v2
+        [*] --> Active
+        state Active {
+            [*] --> Idle
+            Idle --> Busy
+        }
+        Active --> Done
+    ";
+    let objects = render_state_diagram(&parse_state_diagram(input).unwrap(), 0.0, 0.0, 1.0);
+    let frame = node(&objects, "Active");
+    assert_eq!(frame.kind, "frame");
+    assert_eq!(frame.props["title"], "Active");
+    assert_eq!(objects[0].node_id.as_deref(), Some("Active"));
+    for child in ["[*]start:Active", "Idle", "Busy"] {
+        let b = node(&objects, child);
+        assert!(b.x >= frame.x && b.x + b.width <= frame.x + frame.width, "{child} x");
+        assert!(b.y >= frame.y && b.y + b.height <= frame.y + frame.height, "{child} y");
+    }
+    let done = node(&objects, "Done");
+    assert!(done.y >= frame.y + frame.height);
+    assert!(
+        objects
+            .iter()
+            .any(|o| o.endpoints == Some(("Active".into(), "Done".into())))
+    );
+}
+
+#[test]
+fn layout_state_fork_bar_lies_across_the_flow() {
+    let td = render_state_diagram(
+        &parse_state_diagram("stateDiagram\nstate F <<fork>>\nA --> F").unwrap(),
+        0.0,
+        0.0,
+        1.0,
+    );
+    assert!(node(&td, "F").width > node(&td, "F").height);
+    let lr = render_state_diagram(
+        &parse_state_diagram("stateDiagram\ndirection LR\nstate F <<fork>>\nA --> F").unwrap(),
+        0.0,
+        0.0,
+        1.0,
+    );
+    assert!(node(&lr, "F").height > node(&lr, "F").width);
+}
+
+// =============================================================================
+// CLASS DIAGRAM TESTS
+// =============================================================================
+
+#[test]
+fn parse_class_members_annotations_and_generics() {
+    let input = r"
+        classDiagram
+        class Animal {
+            <<abstract>>
+            +String name
+            +int age
+            +makeSound() void
+        }
+        class List~T~
+        <<interface>> Shape
+        Shape : +area() double
+        Animal : +isMammal() bool
+    ";
+    let diagram = parse_class_diagram(input).unwrap();
+    let class = |id: &str| diagram.classes.iter().find(|c| c.id == id).unwrap();
+    assert_eq!(class("Animal").annotation.as_deref(), Some("abstract"));
+    assert_eq!(class("Animal").attributes, vec!["+String name", "+int age"]);
+    assert_eq!(class("Animal").methods, vec!["+makeSound() void", "+isMammal() bool"]);
+    assert_eq!(class("List").label, "List<T>");
+    assert_eq!(class("Shape").annotation.as_deref(), Some("interface"));
+    assert_eq!(class("Shape").methods, vec!["+area() double"]);
+}
+
+#[test]
+fn parse_class_relation_kinds_and_markers() {
+    let input = r#"
+        classDiagram
+        Animal <|-- Duck
+        Car *-- Wheel
+        Pond o-- Duck
+        Duck --> Pond : swims in
+        Shape <|.. Circle
+        Client ..> Service
+        A -- B
+        Zoo "1" --> "*" Animal : houses
+    "#;
+    let diagram = parse_class_diagram(input).unwrap();
+    let r = &diagram.relations;
+    assert_eq!(r.len(), 8);
+    assert_eq!(
+        (r[0].kind, r[0].marker_at, r[0].dashed),
+        (RelationKind::Inheritance, MarkerEnd::From, false)
+    );
+    assert_eq!((r[1].kind, r[1].marker_at), (RelationKind::Composition, MarkerEnd::From));
+    assert_eq!((r[2].kind, r[2].marker_at), (RelationKind::Aggregation, MarkerEnd::From));
+    assert_eq!((r[3].kind, r[3].marker_at), (RelationKind::Association, MarkerEnd::To));
+    assert_eq!(r[3].label.as_deref(), Some("swims in"));
+    assert_eq!((r[4].kind, r[4].dashed), (RelationKind::Inheritance, true));
+    assert_eq!(
+        (r[5].kind, r[5].marker_at, r[5].dashed),
+        (RelationKind::Association, MarkerEnd::To, true)
+    );
+    assert_eq!((r[6].kind, r[6].marker_at), (RelationKind::Link, MarkerEnd::Neither));
+    assert_eq!(r[7].from_cardinality.as_deref(), Some("1"));
+    assert_eq!(r[7].to_cardinality.as_deref(), Some("*"));
+    assert_eq!((r[7].from.as_str(), r[7].to.as_str()), ("Zoo", "Animal"));
+}
+
+#[test]
+fn parse_class_errors() {
+    assert!(parse_class_diagram("classDiagram\nclass A {\n+x").is_err());
+    assert!(parse_class_diagram("classDiagram\n}").is_err());
+    assert!(parse_class_diagram("classDiagram\n<|-- B").is_err());
+    assert!(parse_class_diagram("classDiagram\ndirection XY").is_err());
+}
+
+#[test]
+fn layout_class_boxes_list_members() {
+    let input = r"
+        classDiagram
+        class Animal {
+            <<abstract>>
+            +String name
+            +eat() void
+        }
+        class Empty
+    ";
+    let objects = render_class_diagram(&parse_class_diagram(input).unwrap(), 0.0, 0.0, 1.0);
+    let animal = node(&objects, "Animal");
+    assert_eq!(animal.kind, "rectangle");
+    assert_eq!(animal.props["text"], "«abstract»\nAnimal\n\n+String name\n\n+eat() void");
+    assert_eq!(node(&objects, "Empty").props["text"], "Empty");
+    assert!(animal.height > node(&objects, "Empty").height);
+}
+
+#[test]
+fn layout_class_connectors_put_markers_on_the_b_end() {
+    let input = r#"
+        classDiagram
+        Animal <|-- Duck
+        Duck --> Pond
+        Car *-- Wheel
+        Zoo "1" --> "*" Animal : houses
+    "#;
+    let objects = render_class_diagram(&parse_class_diagram(input).unwrap(), 0.0, 0.0, 1.0);
+    let connector = |relation: &str| {
+        objects
+            .iter()
+            .find(|o| o.props.get("relation").and_then(|r| r.as_str()) == Some(relation))
+            .unwrap()
+    };
+    let inheritance = connector("inheritance");
+    assert_eq!(inheritance.kind, "arrow");
+    assert_eq!(inheritance.endpoints, Some(("Duck".into(), "Animal".into())));
+    // Layering still follows the text: the parent sits above the child.
+    assert!(node(&objects, "Animal").y < node(&objects, "Duck").y);
+
+    let composition = connector("composition");
+    assert_eq!(composition.kind, "line");
+    assert_eq!(composition.endpoints, Some(("Wheel".into(), "Car".into())));
+
+    let association = objects
+        .iter()
+        .find(|o| o.endpoints == Some(("Duck".into(), "Pond".into())))
+        .unwrap();
+    assert_eq!(association.kind, "arrow");
+    assert_eq!(association.props["relation"], "association");
+
+    let texts: Vec<&str> = objects
+        .iter()
+        .filter(|o| o.kind == "text")
+        .filter_map(|o| o.props["text"].as_str())
+        .collect();
+    assert_eq!(texts, vec!["houses", "1", "*"]);
+}
+
+#[test]
+fn layout_class_dashed_relations_have_dash_pattern() {
+    let objects = render_to_objects(&parse_diagram("classDiagram\nShape <|.. Circle").unwrap(), 0.0, 0.0, 1.0);
+    let connector = objects.iter().find(|o| o.endpoints.is_some()).unwrap();
+    assert_eq!(connector.props["relation"], "realization");
+    assert!(connector.props.get("dashPattern").is_some());
+}
+
+// =============================================================================
+// FLOWCHART EXPORT TESTS
+// =============================================================================
+
+/// Board objects as the Mermaid executor would create them: one id per
+/// descriptor, with connector ends attached to the nodes they name.
+fn board_objects(descriptors: &[ObjectDescriptor]) -> Vec<(uuid::Uuid, ObjectDescriptor)> {
+    let ids: Vec<uuid::Uuid> = descriptors.iter().map(|_| uuid::Uuid::new_v4()).collect();
+    let by_node: std::collections::HashMap<&str, uuid::Uuid> = descriptors
+        .iter()
+        .zip(&ids)
+        .filter_map(|(d, id)| Some((d.node_id.as_deref()?, *id)))
+        .collect();
+    descriptors
+        .iter()
+        .zip(ids)
+        .map(|(d, id)| {
+            let mut d = d.clone();
+            if let Some((from, to)) = d.endpoints.clone() {
+                d.props["a"]["type"] = serde_json::json!("attached");
+                d.props["a"]["object_id"] = serde_json::json!(by_node[from.as_str()].to_string());
+                d.props["b"]["type"] = serde_json::json!("attached");
+                d.props["b"]["object_id"] = serde_json::json!(by_node[to.as_str()].to_string());
+            }
+            (id, d)
+        })
+        .collect()
+}
+
+fn export_view(objects: &[(uuid::Uuid, ObjectDescriptor)]) -> Vec<ExportObject<'_>> {
+    objects
+        .iter()
+        .map(|(id, d)| ExportObject {
+            id: *id,
+            kind: &d.kind,
+            x: d.x,
+            y: d.y,
+            width: (d.width > 0.0).then_some(d.width),
+            height: (d.height > 0.0).then_some(d.height),
+            props: &d.props,
+        })
+        .collect()
+}
+
+fn find_id(objects: &[(uuid::Uuid, ObjectDescriptor)], pred: impl Fn(&ObjectDescriptor) -> bool) -> uuid::Uuid {
+    objects
+        .iter()
+        .find(|(_, d)| pred(d))
+        .map(|(id, _)| *id)
+        .unwrap()
+}
+
+#[test]
+fn export_round_trips_rendered_flowchart() {
+    let input = r"
+        flowchart LR
+        A[Start] --> B{Ok?}
+        B -->|yes| C((Done))
+        B -.- D(Retry)
+        subgraph g [Group]
+        C
+        end
+    ";
+    let objects = board_objects(&render_flowchart(&parse_flowchart(input).unwrap(), 0.0, 0.0, 1.0));
+    let text = export_flowchart(&export_view(&objects), &ExportScope::Board).unwrap();
+    let chart = parse_flowchart(&text).unwrap();
+
+    assert_eq!(chart.direction, FlowDirection::LeftRight);
+    let label = |id: &str| {
+        chart
+            .nodes
+            .iter()
+            .find(|n| n.id == id)
+            .unwrap()
+            .label
+            .as_str()
+    };
+    let edges: Vec<(&str, &str, Option<&str>, LineStyle, bool)> = chart
+        .edges
+        .iter()
+        .map(|e| (label(&e.from), label(&e.to), e.label.as_deref(), e.line, e.arrowhead))
+        .collect();
+    assert_eq!(edges.len(), 3);
+    assert!(edges.contains(&("Start", "Ok?", None, LineStyle::Solid, true)));
+    assert!(edges.contains(&("Ok?", "Done", Some("yes"), LineStyle::Solid, true)));
+    assert!(edges.contains(&("Ok?", "Retry", None, LineStyle::Dotted, false)));
+
+    let shape = |l: &str| chart.nodes.iter().find(|n| n.label == l).unwrap().shape;
+    assert_eq!(shape("Ok?"), NodeShape::Diamond);
+    assert_eq!(shape("Done"), NodeShape::Circle);
+    assert_eq!(shape("Retry"), NodeShape::Round);
+    assert_eq!(chart.subgraphs.len(), 1);
+    assert_eq!(chart.subgraphs[0].title, "Group");
+    let done = chart.nodes.iter().find(|n| n.label == "Done").unwrap();
+    assert_eq!(done.subgraph.as_deref(), Some(chart.subgraphs[0].id.as_str()));
+    // The edge label text is consumed, not exported as a node.
+    assert_eq!(chart.nodes.len(), 4);
+}
+
+#[test]
+fn export_selection_keeps_connectors_between_selected_shapes() {
+    let objects = board_objects(&render_flowchart(
+        &parse_flowchart("flowchart TD\nA[One] --> B[Two]\nB --> C[Three]").unwrap(),
+        0.0,
+        0.0,
+        1.0,
+    ));
+    let selected = ["One", "Two"]
+        .iter()
+        .map(|l| find_id(&objects, |d| d.props["text"] == *l && d.kind != "text"))
+        .collect();
+    let text = export_flowchart(&export_view(&objects), &ExportScope::Objects(selected)).unwrap();
+    assert_eq!(text, "flowchart TD\n    n1[\"One\"]\n    n2[\"Two\"]\n    n1 --> n2\n");
+}
+
+#[test]
+fn export_frame_scope_and_missing_frame() {
+    let input = "flowchart TD\nX[Outside] --> A\nsubgraph g [Inner]\nA[In] --> B[Also in]\nend";
+    let objects = board_objects(&render_flowchart(&parse_flowchart(input).unwrap(), 0.0, 0.0, 1.0));
+    let frame = find_id(&objects, |d| d.kind == "frame");
+    let text = export_flowchart(&export_view(&objects), &ExportScope::Frame(frame)).unwrap();
+    assert!(text.contains("\"In\"") && text.contains("\"Also in\""));
+    assert!(!text.contains("Outside"));
+    assert!(!text.contains("subgraph"));
+    assert!(text.ends_with("n1 --> n2\n"));
+
+    let missing = uuid::Uuid::new_v4();
+    assert_eq!(
+        export_flowchart(&export_view(&objects), &ExportScope::Frame(missing)),
+        Err(format!("frame {missing} not found"))
+    );
+}
+
+#[test]
+fn export_link_styles_and_escaping() {
+    let shape = |text: &str, x: f64| ObjectDescriptor {
+        kind: "rectangle".into(),
+        x,
+        y: 0.0,
+        width: 100.0,
+        height: 50.0,
+        props: serde_json::json!({ "text": text }),
+        node_id: Some(text.into()),
+        endpoints: None,
+    };
+    let link = |kind: &str, to: &str, props: serde_json::Value| ObjectDescriptor {
+        kind: kind.into(),
+        x: 0.0,
+        y: 0.0,
+        width: 1.0,
+        height: 1.0,
+        props: serde_json::json!({ "a": {}, "b": {} })
+            .as_object()
+            .unwrap()
+            .clone()
+            .into_iter()
+            .chain(props.as_object().unwrap().clone())
+            .collect(),
+        node_id: None,
+        endpoints: Some(("say \"hi\"".into(), to.into())),
+    };
+    let objects = board_objects(&[
+        shape("say \"hi\"", 0.0),
+        shape("b", 200.0),
+        shape("c", 400.0),
+        shape("d", 600.0),
+        link("arrow", "b", serde_json::json!({ "strokeWidth": 4 })),
+        link("line", "c", serde_json::json!({ "text": "a|b" })),
+        link("arrow", "d", serde_json::json!({ "dashPattern": "8,4" })),
+    ]);
+    let text = export_flowchart(&export_view(&objects), &ExportScope::Board).unwrap();
+    assert!(text.contains("n1[\"say #quot;hi#quot;\"]"));
+    assert!(text.contains("n1 ==> n2"));
+    assert!(text.contains("n1 ---|\"a#124;b\"| n3"));
+    assert!(text.contains("n1 -.-> n4"));
+    // Links run left to right.
+    assert!(text.starts_with("flowchart LR\n"));
+}
@@ -1,9 +1,37 @@
-//! Recursive descent parser for Mermaid sequence diagram syntax.
+//! Recursive descent parser for Mermaid sequence, flowchart, state, and class
+//! diagram syntax.
 
 use super::ast::{
-    ArrowStyle, Block, BlockKind, BlockSection, Event, Message, Note, NotePosition, Participant, SequenceDiagram,
+    ArrowStyle, Block, BlockKind, BlockSection, Class, ClassDiagram, Diagram, Event, FlowDirection, FlowEdge, FlowNode,
+    Flowchart, LineStyle, MarkerEnd, Message, NodeShape, Note, NotePosition, Participant, Relation, RelationKind,
+    SequenceDiagram, State, StateDiagram, StateKind, Subgraph, Transition,
 };
 
+/// Parse Mermaid text of any supported kind, dispatching on its header line.
+///
+/// Text without a `flowchart` / `graph`, `stateDiagram`, or `classDiagram`
+/// header is parsed as a sequence diagram.
+///
+/// # Errors
+///
+/// Returns a descriptive error string if parsing fails.
+pub fn parse_diagram(input: &str) -> Result<Diagram, String> {
+    let header = input
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with("%%"))
+        .unwrap_or("");
+    if is_flowchart_header(header) {
+        parse_flowchart(input).map(Diagram::Flowchart)
+    } else if is_state_header(header) {
+        parse_state_diagram(input).map(Diagram::State)
+    } else if is_class_header(header) {
+        parse_class_diagram(input).map(Diagram::Class)
+    } else {
+        parse(input).map(Diagram::Sequence)
+    }
+}
+
 /// Parse Mermaid sequence diagram text into an AST.
 ///
 /// Accepts text with or without the `sequenceDiagram` header line.
@@ -303,3 +331,757 @@ fn ensure_participant(id: &str, participants: &mut Vec<Participant>, participant
         participants.push(Participant { id: id.to_owned(), label: id.to_owned() });
     }
 }
+
+// =============================================================================
+// FLOWCHART
+// =============================================================================
+
+/// Node shapes keyed by their opening and closing brackets, longest first.
+const NODE_SHAPES: &[(&str, &str, NodeShape)] = &[
+    ("((", "))", NodeShape::Circle),
+    ("([", "])", NodeShape::Round),
+    ("(", ")", NodeShape::Round),
+    ("{", "}", NodeShape::Diamond),
+    ("[", "]", NodeShape::Rect),
+];
+
+/// Statements that only style the chart and carry no structure.
+const IGNORED_FLOWCHART_KEYWORDS: &[&str] = &["direction", "classdef", "class", "style", "linkstyle", "click"];
+
+/// Parse Mermaid `flowchart` / `graph` text into an AST.
+///
+/// Accepts text with or without the header line; the direction defaults to
+/// top-down.
+///
+/// # Errors
+///
+/// Returns a descriptive error string for unknown directions, unterminated
+/// node shapes, links without a target, and unbalanced `subgraph` / `end`.
+pub fn parse_flowchart(input: &str) -> Result<Flowchart, String> {
+    let mut lines = input
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with("%%"))
+        .peekable();
+
+    let mut direction = FlowDirection::TopDown;
+    if let Some(header) = lines.next_if(|l| is_flowchart_header(l)) {
+        if let Some(dir) = header.split_whitespace().nth(1) {
+            let dir = dir.trim_end_matches(';');
+            direction = parse_direction(dir).ok_or_else(|| format!("unknown flowchart direction: {dir}"))?;
+        }
+    }
+
+    let mut builder = FlowchartBuilder::default();
+    for line in lines {
+        for statement in split_statements(line) {
+            builder.statement(statement)?;
+        }
+    }
+    if let Some(open) = builder.open.last() {
+        return Err(format!("subgraph {open} is missing `end`"));
+    }
+
+    Ok(Flowchart { direction, nodes: builder.nodes, edges: builder.edges, subgraphs: builder.subgraphs })
+}
+
+fn is_flowchart_header(line: &str) -> bool {
+    let keyword = line.split_whitespace().next().unwrap_or("");
+    keyword.eq_ignore_ascii_case("flowchart") || keyword.eq_ignore_ascii_case("graph")
+}
+
+fn parse_direction(dir: &str) -> Option<FlowDirection> {
+    match dir.to_ascii_uppercase().as_str() {
+        "TD" | "TB" => Some(FlowDirection::TopDown),
+        "BT" => Some(FlowDirection::BottomUp),
+        "LR" => Some(FlowDirection::LeftRight),
+        "RL" => Some(FlowDirection::RightLeft),
+        _ => None,
+    }
+}
+
+#[derive(Default)]
+struct FlowchartBuilder {
+    nodes: Vec<FlowNode>,
+    edges: Vec<FlowEdge>,
+    subgraphs: Vec<Subgraph>,
+    /// Ids of the subgraphs currently open, innermost last.
+    open: Vec<String>,
+}
+
+/// A parsed link operator such as `-->`, `-.->|label|` or `== label ==>`.
+struct Link {
+    line: LineStyle,
+    arrowhead: bool,
+    label: Option<String>,
+}
+
+impl FlowchartBuilder {
+    fn statement(&mut self, statement: &str) -> Result<(), String> {
+        let keyword = statement
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match keyword.as_str() {
+            "subgraph" => self.open_subgraph(statement["subgraph".len()..].trim()),
+            "end" => self
+                .open
+                .pop()
+                .map(|_| ())
+                .ok_or_else(|| "`end` without an open subgraph".to_owned()),
+            kw if IGNORED_FLOWCHART_KEYWORDS.contains(&kw) => Ok(()),
+            _ => self.chain(statement),
+        }
+    }
+
+    /// Open a subgraph from a header like `id [Title]`, `"Title"` or `Title`.
+    ///
+    /// Ids must be unique: nodes and nested subgraphs refer to their
+    /// subgraph by id, so a repeated id could make a subgraph its own parent.
+    fn open_subgraph(&mut self, rest: &str) -> Result<(), String> {
+        let (id, title) = match rest.find('[') {
+            Some(open) if rest.ends_with(']') => (
+                rest[..open].trim().to_owned(),
+                unquote(&rest[open + 1..rest.len() - 1]).to_owned(),
+            ),
+            _ => (unquote(rest).to_owned(), unquote(rest).to_owned()),
+        };
+        let id = if id.is_empty() {
+            format!("subgraph{}", self.subgraphs.len() + 1)
+        } else {
+            id
+        };
+        if self.subgraphs.iter().any(|subgraph| subgraph.id == id) {
+            return Err(format!("duplicate subgraph id `{id}`"));
+        }
+        self.subgraphs
+            .push(Subgraph { id: id.clone(), title, parent: self.open.last().cloned() });
+        self.open.push(id);
+        Ok(())
+    }
+
+    /// Parse a node chain like `A[Start] --> B{Ok?} -->|yes| C & D`.
+    ///
+    /// Lines that do not start with a node are skipped.
+    fn chain(&mut self, statement: &str) -> Result<(), String> {
+        let (mut sources, mut rest) = self.node_group(statement)?;
+        if sources.is_empty() {
+            return Ok(());
+        }
+        while let Some((link, after_link)) = parse_link(rest.trim_start()) {
+            let (targets, after_targets) = self.node_group(after_link.trim_start())?;
+            if targets.is_empty() {
+                return Err(format!("link without a target node: {statement}"));
+            }
+            for from in &sources {
+                for to in &targets {
+                    self.edges.push(FlowEdge {
+                        from: from.clone(),
+                        to: to.clone(),
+                        label: link.label.clone(),
+                        line: link.line,
+                        arrowhead: link.arrowhead,
+                    });
+                }
+            }
+            sources = targets;
+            rest = after_targets;
+        }
+        Ok(())
+    }
+
+    /// Parse `A`, or `A & B & C`, declaring each node.
+    fn node_group<'a>(&mut self, text: &'a str) -> Result<(Vec<String>, &'a str), String> {
+        let mut ids = Vec::new();
+        let mut rest = text;
+        loop {
+            let Some((id, after)) = self.node_ref(rest.trim_start())? else {
+                break;
+            };
+            ids.push(id);
+            rest = after;
+            match rest.trim_start().strip_prefix('&') {
+                Some(after_amp) => rest = after_amp,
+                None => break,
+            }
+        }
+        Ok((ids, rest))
+    }
+
+    /// Parse one node reference with an optional shape, e.g. `B{Is it?}`.
+    fn node_ref<'a>(&mut self, text: &'a str) -> Result<Option<(String, &'a str)>, String> {
+        let id_len = text
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(text.len());
+        if id_len == 0 {
+            return Ok(None);
+        }
+        let id = &text[..id_len];
+        let mut rest = &text[id_len..];
+
+        let mut declared = None;
+        if let Some(&(open, close, shape)) = NODE_SHAPES
+            .iter()
+            .find(|(open, _, _)| rest.starts_with(open))
+        {
+            let body = &rest[open.len()..];
+            // A quoted label may contain the closing bracket.
+            let search_from = body
+                .strip_prefix('"')
+                .and_then(|quoted| quoted.find('"'))
+                .map_or(0, |q| q + 2);
+            let end = body[search_from..]
+                .find(close)
+                .map(|i| i + search_from)
+                .ok_or_else(|| format!("unterminated shape for node {id}: {text}"))?;
+            declared = Some((unquote(&body[..end]).to_owned(), shape));
+            rest = &body[end + close.len()..];
+        }
+
+        self.declare(id, declared);
+        Ok(Some((id.to_owned(), rest)))
+    }
+
+    /// Add a node on first mention. A later mention with a shape relabels it,
+    /// and a mention inside a subgraph moves it there, as Mermaid does.
+    fn declare(&mut self, id: &str, declared: Option<(String, NodeShape)>) {
+        if let Some(node) = self.nodes.iter_mut().find(|n| n.id == id) {
+            if let Some((label, shape)) = declared {
+                node.label = label;
+                node.shape = shape;
+            }
+            if let Some(subgraph) = self.open.last() {
+                node.subgraph = Some(subgraph.clone());
+            }
+            return;
+        }
+        let (label, shape) = declared.unwrap_or_else(|| (id.to_owned(), NodeShape::Rect));
+        self.nodes
+            .push(FlowNode { id: id.to_owned(), label, shape, subgraph: self.open.last().cloned() });
+    }
+}
+
+/// Parse a link operator and its optional label from the start of `text`.
+fn parse_link(text: &str) -> Option<(Link, &str)> {
+    let (line, mut label, rest) = if let Some(after) = text.strip_prefix("-.") {
+        if let Some(rest) = after.trim_start_matches('.').strip_prefix('-') {
+            (LineStyle::Dotted, None, rest)
+        } else {
+            // `-. label .->`
+            let end = after.find(".-")?;
+            (LineStyle::Dotted, Some(after[..end].trim()), &after[end + 2..])
+        }
+    } else if text.starts_with("--") {
+        let (label, rest) = parse_run_link(text, '-')?;
+        (LineStyle::Solid, label, rest)
+    } else if text.starts_with("==") {
+        let (label, rest) = parse_run_link(text, '=')?;
+        (LineStyle::Thick, label, rest)
+    } else {
+        return None;
+    };
+
+    let (arrowhead, mut rest) = match rest.strip_prefix('>') {
+        Some(after) => (true, after),
+        None => (false, rest),
+    };
+    if let Some(piped) = rest.trim_start().strip_prefix('|') {
+        let end = piped.find('|')?;
+        label = Some(piped[..end].trim());
+        rest = &piped[end + 1..];
+    }
+
+    let label = label
+        .map(unquote)
+        .filter(|l| !l.is_empty())
+        .map(ToOwned::to_owned);
+    Some((Link { line, arrowhead, label }, rest))
+}
+
+/// Parse `-->`, `---`, or the labeled `-- label -->` form for `ch` of `-`/`=`,
+/// leaving any trailing `>` for the caller.
+fn parse_run_link(text: &str, ch: char) -> Option<(Option<&str>, &str)> {
+    let after = text.trim_start_matches(ch);
+    let run = text.len() - after.len();
+    if after.starts_with('>') || run >= 3 {
+        return Some((None, after));
+    }
+    let closing = [ch, ch].iter().collect::<String>();
+    let end = after.find(&closing)?;
+    Some((Some(after[..end].trim()), after[end..].trim_start_matches(ch)))
+}
+
+/// Split a line on `;` separators outside brackets and quotes.
+fn split_statements(line: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut depth = 0_i32;
+    let mut in_quote = false;
+    let mut start = 0;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quote = !in_quote,
+            '[' | '(' | '{' if !in_quote => depth += 1,
+            ']' | ')' | '}' if !in_quote => depth -= 1,
+            ';' if !in_quote && depth <= 0 => {
+                statements.push(line[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    statements.push(line[start..].trim());
+    statements.retain(|s| !s.is_empty());
+    statements
+}
+
+fn unquote(text: &str) -> &str {
+    let text = text.trim();
+    text.strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .unwrap_or(text)
+}
+
+// =============================================================================
+// STATE DIAGRAM
+// =============================================================================
+
+/// Statements that only style the diagram and carry no structure.
+const IGNORED_STATE_KEYWORDS: &[&str] = &["classdef", "class", "style", "hide", "scale"];
+
+/// Parse Mermaid `stateDiagram` / `stateDiagram-v2` text into an AST.
+///
+/// Accepts text with or without the header line. Notes and concurrency
+/// separators (`--`) are skipped.
+///
+/// # Errors
+///
+/// Returns a descriptive error string for unknown directions, transitions
+/// without a target, and unbalanced composite-state braces.
+pub fn parse_state_diagram(input: &str) -> Result<StateDiagram, String> {
+    let mut lines = input
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with("%%"))
+        .peekable();
+    lines.next_if(|l| is_state_header(l));
+
+    let mut builder = StateBuilder::default();
+    let mut in_note = false;
+    for line in lines {
+        if in_note {
+            in_note = !line.eq_ignore_ascii_case("end note");
+            continue;
+        }
+        for statement in line.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+            in_note = builder.statement(statement)?;
+        }
+    }
+    if let Some(open) = builder.open.last() {
+        return Err(format!("composite state {open} is missing `}}`"));
+    }
+
+    Ok(StateDiagram { direction: builder.direction, states: builder.states, transitions: builder.transitions })
+}
+
+fn is_state_header(line: &str) -> bool {
+    let keyword = line.split_whitespace().next().unwrap_or("");
+    keyword.eq_ignore_ascii_case("stateDiagram") || keyword.eq_ignore_ascii_case("stateDiagram-v2")
+}
+
+#[derive(Default)]
+struct StateBuilder {
+    direction: FlowDirection,
+    states: Vec<State>,
+    transitions: Vec<Transition>,
+    /// Ids of the composite states currently open, innermost last.
+    open: Vec<String>,
+}
+
+impl StateBuilder {
+    /// Apply one statement. Returns `true` when it opens a multi-line note.
+    fn statement(&mut self, statement: &str) -> Result<bool, String> {
+        let keyword = statement
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match keyword.as_str() {
+            "}" => {
+                self.open
+                    .pop()
+                    .ok_or_else(|| "`}` without an open composite state".to_owned())?;
+            }
+            "--" => {}
+            "note" => return Ok(!statement.contains(':')),
+            "direction" => {
+                // Only the top-level direction affects the layout.
+                let dir = statement["direction".len()..].trim();
+                let direction =
+                    parse_direction(dir).ok_or_else(|| format!("unknown state diagram direction: {dir}"))?;
+                if self.open.is_empty() {
+                    self.direction = direction;
+                }
+            }
+            "state" => self.declare_state(statement["state".len()..].trim())?,
+            kw if IGNORED_STATE_KEYWORDS.contains(&kw) => {}
+            _ => self.transition_or_description(statement)?,
+        }
+        Ok(false)
+    }
+
+    /// Parse `X`, `X {`, `"Description" as X`, or `X <<choice>>`.
+    fn declare_state(&mut self, rest: &str) -> Result<(), String> {
+        let (rest, opens) = match rest.strip_suffix('{') {
+            Some(before) => (before.trim(), true),
+            None => (rest, false),
+        };
+        let (id, label, marker) = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted
+                .find('"')
+                .ok_or_else(|| format!("unterminated state description: {rest}"))?;
+            let id = quoted[end + 1..]
+                .trim()
+                .strip_prefix("as ")
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .ok_or_else(|| format!("state description without `as <id>`: {rest}"))?;
+            (id, Some(&quoted[..end]), "")
+        } else {
+            let (id, marker) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            if id.is_empty() {
+                return Err("`state` without an id".to_owned());
+            }
+            (id, None, marker.trim())
+        };
+
+        let kind = match marker.to_ascii_lowercase().as_str() {
+            "<<choice>>" => StateKind::Choice,
+            "<<fork>>" | "<<join>>" => StateKind::Fork,
+            _ if opens => StateKind::Composite,
+            _ => StateKind::Simple,
+        };
+        let state = self.ensure(id, kind);
+        if kind != StateKind::Simple {
+            state.kind = kind;
+        }
+        if let Some(label) = label {
+            label.clone_into(&mut state.label);
+        }
+        if opens {
+            self.open.push(id.to_owned());
+        }
+        Ok(())
+    }
+
+    /// Parse `A --> B : label` or `A : description`; a bare id declares it.
+    fn transition_or_description(&mut self, statement: &str) -> Result<(), String> {
+        if let Some((from, rest)) = statement.split_once("-->") {
+            let (to, label) = match rest.split_once(':') {
+                Some((to, label)) => (to.trim(), Some(label.trim()).filter(|l| !l.is_empty())),
+                None => (rest.trim(), None),
+            };
+            if to.is_empty() {
+                return Err(format!("transition without a target state: {statement}"));
+            }
+            let from = self.endpoint(from.trim(), StateKind::Start);
+            let to = self.endpoint(to, StateKind::End);
+            self.transitions
+                .push(Transition { from, to, label: label.map(ToOwned::to_owned) });
+        } else if let Some((id, description)) = statement.split_once(':') {
+            let state = self.ensure(id.trim(), StateKind::Simple);
+            // Repeated descriptions stack, as Mermaid renders them.
+            if state.label == state.id {
+                description.trim().clone_into(&mut state.label);
+            } else {
+                state.label.push('\n');
+                state.label.push_str(description.trim());
+            }
+        } else {
+            self.ensure(statement, StateKind::Simple);
+        }
+        Ok(())
+    }
+
+    /// Resolve a transition end, giving each `[*]` a start or end id scoped
+    /// to the enclosing composite state.
+    fn endpoint(&mut self, id: &str, pseudo: StateKind) -> String {
+        if id != "[*]" {
+            return self.ensure(id, StateKind::Simple).id.clone();
+        }
+        let role = if pseudo == StateKind::Start { "start" } else { "end" };
+        let id = match self.open.last() {
+            Some(scope) => format!("[*]{role}:{scope}"),
+            None => format!("[*]{role}"),
+        };
+        let state = self.ensure(&id, pseudo);
+        state.label.clear();
+        id
+    }
+
+    /// Add a state on first mention inside the innermost open composite.
+    fn ensure(&mut self, id: &str, kind: StateKind) -> &mut State {
+        let index = if let Some(i) = self.states.iter().position(|s| s.id == id) {
+            i
+        } else {
+            self.states.push(State {
+                id: id.to_owned(),
+                label: id.to_owned(),
+                kind,
+                parent: self.open.last().cloned(),
+            });
+            self.states.len() - 1
+        };
+        &mut self.states[index]
+    }
+}
+
+// =============================================================================
+// CLASS DIAGRAM
+// =============================================================================
+
+/// Statements that only style or link the diagram and carry no structure.
+const IGNORED_CLASS_KEYWORDS: &[&str] = &["note", "classdef", "style", "cssclass", "callback", "click", "link"];
+
+/// Parse Mermaid `classDiagram` text into an AST.
+///
+/// Accepts text with or without the header line. Relations must separate the
+/// link operator from the class names with whitespace, e.g. `A <|-- B`.
+///
+/// # Errors
+///
+/// Returns a descriptive error string for unknown directions, relations
+/// missing a class, and unbalanced class-body braces.
+pub fn parse_class_diagram(input: &str) -> Result<ClassDiagram, String> {
+    let mut lines = input
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with("%%"))
+        .peekable();
+    lines.next_if(|l| is_class_header(l));
+
+    let mut builder = ClassBuilder::default();
+    for line in lines {
+        builder.statement(line)?;
+    }
+    if let Some(open) = builder.open {
+        return Err(format!("class {open} is missing `}}`"));
+    }
+
+    Ok(ClassDiagram { direction: builder.direction, classes: builder.classes, relations: builder.relations })
+}
+
+fn is_class_header(line: &str) -> bool {
+    let keyword = line.split_whitespace().next().unwrap_or("");
+    keyword.eq_ignore_ascii_case("classDiagram") || keyword.eq_ignore_ascii_case("classDiagram-v2")
+}
+
+#[derive(Default)]
+struct ClassBuilder {
+    direction: FlowDirection,
+    classes: Vec<Class>,
+    relations: Vec<Relation>,
+    /// Class whose `{ ... }` member block is open.
+    open: Option<String>,
+}
+
+impl ClassBuilder {
+    fn statement(&mut self, statement: &str) -> Result<(), String> {
+        if let Some(open) = self.open.clone() {
+            if statement == "}" {
+                self.open = None;
+            } else if let Some(annotation) = parse_annotation(statement) {
+                self.ensure(&open).annotation = Some(annotation.to_owned());
+            } else {
+                self.add_member(&open, statement);
+            }
+            return Ok(());
+        }
+
+        let keyword = statement
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match keyword.as_str() {
+            "direction" => {
+                let dir = statement["direction".len()..].trim();
+                self.direction =
+                    parse_direction(dir).ok_or_else(|| format!("unknown class diagram direction: {dir}"))?;
+            }
+            "class" => self.declare_class(statement["class".len()..].trim())?,
+            "}" => return Err("`}` without an open class".to_owned()),
+            kw if IGNORED_CLASS_KEYWORDS.contains(&kw) => {}
+            _ if statement.starts_with("<<") => {
+                // `<<interface>> Shape`
+                let end = statement
+                    .find(">>")
+                    .ok_or_else(|| format!("unterminated annotation: {statement}"))?;
+                let id = class_id(statement[end + 2..].trim());
+                if id.is_empty() {
+                    return Err(format!("annotation without a class: {statement}"));
+                }
+                self.ensure(id).annotation = Some(statement[2..end].trim().to_owned());
+            }
+            _ => self.relation_or_member(statement)?,
+        }
+        Ok(())
+    }
+
+    /// Parse `Name`, `Name~T~`, or `Name {` opening a member block.
+    fn declare_class(&mut self, rest: &str) -> Result<(), String> {
+        let (rest, opens) = match rest.strip_suffix('{') {
+            Some(before) => (before.trim(), true),
+            None => (rest, false),
+        };
+        let name = rest.split_whitespace().next().unwrap_or("");
+        let id = class_id(name);
+        if id.is_empty() {
+            return Err("`class` without a name".to_owned());
+        }
+        let label = class_label(name);
+        self.ensure(id).label = label;
+        if opens {
+            self.open = Some(id.to_owned());
+        }
+        Ok(())
+    }
+
+    /// Parse a relation such as `A "1" *-- "many" B : owns`, or a member line
+    /// such as `A : +name String`.
+    fn relation_or_member(&mut self, statement: &str) -> Result<(), String> {
+        let (body, label) = match statement.split_once(':') {
+            Some((body, label)) => (body.trim(), Some(label.trim()).filter(|l| !l.is_empty())),
+            None => (statement, None),
+        };
+        let tokens = split_quoted_words(body);
+        let Some(op) = tokens.iter().position(|t| parse_relation_op(t).is_some()) else {
+            match (tokens.as_slice(), label) {
+                ([name], Some(member)) => {
+                    let id = class_id(name);
+                    self.ensure(id);
+                    self.add_member(id, member);
+                }
+                ([name], None) => {
+                    self.ensure(class_id(name));
+                }
+                _ => {}
+            }
+            return Ok(());
+        };
+        let (kind, marker_at, dashed) =
+            parse_relation_op(tokens[op]).unwrap_or((RelationKind::Link, MarkerEnd::Neither, false));
+        let (from, from_cardinality) = match &tokens[..op] {
+            [name] => (*name, None),
+            [name, cardinality] => (*name, Some(unquote(cardinality).to_owned())),
+            _ => return Err(format!("relation without a source class: {statement}")),
+        };
+        let (to, to_cardinality) = match &tokens[op + 1..] {
+            [name] => (*name, None),
+            [cardinality, name] => (*name, Some(unquote(cardinality).to_owned())),
+            _ => return Err(format!("relation without a target class: {statement}")),
+        };
+        let (from, to) = (class_id(from).to_owned(), class_id(to).to_owned());
+        self.ensure(&from);
+        self.ensure(&to);
+        self.relations.push(Relation {
+            from,
+            to,
+            kind,
+            marker_at,
+            dashed,
+            label: label.map(ToOwned::to_owned),
+            from_cardinality,
+            to_cardinality,
+        });
+        Ok(())
+    }
+
+    /// Methods are members with a parameter list; the rest are attributes.
+    fn add_member(&mut self, id: &str, member: &str) {
+        let member = member.trim().replace('~', "");
+        let class = self.ensure(id);
+        if member.contains('(') {
+            class.methods.push(member);
+        } else {
+            class.attributes.push(member);
+        }
+    }
+
+    fn ensure(&mut self, id: &str) -> &mut Class {
+        let index = if let Some(i) = self.classes.iter().position(|c| c.id == id) {
+            i
+        } else {
+            self.classes.push(Class {
+                id: id.to_owned(),
+                label: id.to_owned(),
+                annotation: None,
+                attributes: Vec::new(),
+                methods: Vec::new(),
+            });
+            self.classes.len() - 1
+        };
+        &mut self.classes[index]
+    }
+}
+
+/// Parse a relation operator like `<|--`, `*--`, `..>`, or `--`.
+fn parse_relation_op(token: &str) -> Option<(RelationKind, MarkerEnd, bool)> {
+    let (start, dashed) = match (token.find("--"), token.find("..")) {
+        (Some(i), _) => (i, false),
+        (None, Some(i)) => (i, true),
+        (None, None) => return None,
+    };
+    let (left, right) = (&token[..start], &token[start + 2..]);
+    let marker_kind = |marker: &str| match marker {
+        "<|" | "|>" => Some(RelationKind::Inheritance),
+        "*" => Some(RelationKind::Composition),
+        "o" => Some(RelationKind::Aggregation),
+        "<" | ">" => Some(RelationKind::Association),
+        _ => None,
+    };
+    match (left, right) {
+        ("", "") => Some((RelationKind::Link, MarkerEnd::Neither, dashed)),
+        (marker, "") => Some((marker_kind(marker)?, MarkerEnd::From, dashed)),
+        ("", marker) => Some((marker_kind(marker)?, MarkerEnd::To, dashed)),
+        // Two-way relations keep the left-hand marker.
+        (marker, _) => Some((marker_kind(marker)?, MarkerEnd::From, dashed)),
+    }
+}
+
+/// `<<interface>>` on its own line inside a class body.
+fn parse_annotation(statement: &str) -> Option<&str> {
+    statement
+        .strip_prefix("<<")
+        .and_then(|s| s.strip_suffix(">>"))
+        .map(str::trim)
+}
+
+/// Class id without its generic parameter: `List~T~` is `List`.
+fn class_id(name: &str) -> &str {
+    name.split('~').next().unwrap_or(name).trim()
+}
+
+/// Display name with generics in angle brackets: `List~T~` is `List<T>`.
+fn class_label(name: &str) -> String {
+    match name.split_once('~') {
+        Some((id, generic)) => format!("{id}<{}>", generic.trim_end_matches('~')),
+        None => name.to_owned(),
+    }
+}
+
+/// Split on whitespace, keeping `"quoted words"` together.
+fn split_quoted_words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        let end = if let Some(quoted) = rest.strip_prefix('"') {
+            quoted.find('"').map_or(rest.len(), |i| i + 2)
+        } else {
+            rest.find(char::is_whitespace).unwrap_or(rest.len())
+        };
+        words.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    words
+}
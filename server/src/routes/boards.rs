@@ -1,14 +1,22 @@
 //! Board member management routes.
 
-use axum::extract::{Path, State};
-use axum::http::StatusCode;
-use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use std::collections::{HashMap, HashSet};
+
+use axum::extract::{Path, Query, State};
+use axum::http::header::{CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Json, Response};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::import::{self, Import, ImportError, Unsupported};
+use crate::mermaid::{ExportObject, ExportScope, export_flowchart};
 use crate::routes::auth::AuthUser;
 use crate::services::board::{self, BoardMemberRow, BoardRole};
+use crate::services::pdf;
+use crate::services::persistence;
+use crate::services::raster::{self, RasterError, RasterOptions};
+use crate::services::scene::{self, SceneError};
 use crate::state::{AppState, BoardObject};
 
 #[derive(Serialize)]
@@ -349,7 +357,7 @@ pub async fn create_object_rest(
         }
     }
 
-    broadcast_object_frame(&state, board_id, "object:create", object_to_data(&object)).await;
+    broadcast_object_frame(&state, board_id, auth.user.id, "object:create", object_to_data(&object)).await;
     Ok((StatusCode::CREATED, Json(object)))
 }
 
@@ -453,10 +461,12 @@ pub async fn patch_object(
         if let Some(board_state) = boards.get_mut(&board_id) {
             board_state.objects.insert(object.id, object.clone());
             board_state.dirty.remove(&object.id);
+            // REST patches are unversioned overwrites; restart field tracking.
+            board_state.field_versions.remove(&object.id);
         }
     }
 
-    broadcast_object_frame(&state, board_id, "object:update", object_to_data(&object)).await;
+    broadcast_object_frame(&state, board_id, auth.user.id, "object:update", object_to_data(&object)).await;
     Ok(Json(object))
 }
 
@@ -485,12 +495,13 @@ pub async fn delete_object_rest(
         if let Some(board_state) = boards.get_mut(&board_id) {
             board_state.objects.remove(&object_id);
             board_state.dirty.remove(&object_id);
+            board_state.field_versions.remove(&object_id);
         }
     }
 
     let mut data = crate::frame::Data::new();
-    data.insert("object_id".into(), serde_json::json!(object_id));
-    broadcast_object_frame(&state, board_id, "object:delete", data).await;
+    data.insert("id".into(), serde_json::json!(object_id));
+    broadcast_object_frame(&state, board_id, auth.user.id, "object:delete", data).await;
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
@@ -501,18 +512,28 @@ fn object_to_data(object: &BoardObject) -> crate::frame::Data {
     }
 }
 
-async fn broadcast_object_frame(state: &AppState, board_id: Uuid, syscall: &str, data: crate::frame::Data) {
+/// Record a REST mutation in the frame log and announce it to connected
+/// clients. Logging it lets a client rejoining with `since_seq` pick up the
+/// change in its delta.
+async fn broadcast_object_frame(
+    state: &AppState,
+    board_id: Uuid,
+    user_id: Uuid,
+    syscall: &str,
+    data: crate::frame::Data,
+) {
     let frame = crate::frame::Frame {
         id: Uuid::new_v4(),
         parent_id: None,
         ts: now_ms_i64(),
         board_id: Some(board_id),
-        from: None,
+        from: Some(user_id.to_string()),
         syscall: syscall.to_owned(),
         status: crate::frame::Status::Done,
         trace: None,
         data,
     };
+    persistence::enqueue_frame(state, &frame);
     board::broadcast(state, board_id, &frame, None).await;
 }
 
@@ -537,6 +558,36 @@ async fn next_z_index(state: &AppState, board_id: Uuid) -> Result<i32, sqlx::Err
     Ok(max_z.unwrap_or(-1) + 1)
 }
 
+async fn board_is_empty(state: &AppState, board_id: Uuid) -> Result<bool, sqlx::Error> {
+    {
+        let boards = state.boards.read().await;
+        if let Some(board_state) = boards.get(&board_id) {
+            return Ok(board_state.objects.is_empty());
+        }
+    }
+
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM board_objects WHERE board_id = $1)")
+        .bind(board_id)
+        .fetch_one(&state.pool)
+        .await?;
+    Ok(!exists)
+}
+
+/// Which of `ids` already belong to an object on any board, saved or live.
+async fn existing_object_ids(state: &AppState, ids: &[Uuid]) -> Result<HashSet<Uuid>, sqlx::Error> {
+    let mut taken: HashSet<Uuid> = sqlx::query_scalar::<_, Uuid>("SELECT id FROM board_objects WHERE id = ANY($1)")
+        .bind(ids)
+        .fetch_all(&state.pool)
+        .await?
+        .into_iter()
+        .collect();
+    let boards = state.boards.read().await;
+    for board_state in boards.values() {
+        taken.extend(ids.iter().filter(|id| board_state.objects.contains_key(id)));
+    }
+    Ok(taken)
+}
+
 async fn load_objects_from_db(pool: &sqlx::PgPool, board_id: Uuid) -> Result<Vec<BoardObject>, sqlx::Error> {
     let rows = sqlx::query_as::<
         _,
@@ -660,12 +711,25 @@ struct BoardExportObjectLine {
 #[derive(Deserialize)]
 pub struct ImportJsonlBody {
     pub jsonl: String,
+    /// Keep the file's object ids when the board is empty. Ids already in
+    /// use elsewhere are still replaced.
+    #[serde(default)]
+    pub keep_ids: bool,
 }
 
 #[derive(Serialize)]
 pub struct ImportJsonlResponse {
     pub imported: usize,
     pub skipped: usize,
+    /// Whether the file's object ids were kept rather than replaced.
+    pub kept_ids: bool,
+}
+
+#[derive(Serialize)]
+pub struct ImportFileResponse {
+    pub imported: usize,
+    /// Elements the board has no equivalent for, which were not imported.
+    pub unsupported: Vec<Unsupported>,
 }
 
 /// `GET /api/boards/:id/export.jsonl` — download board snapshot as NDJSON/JSONL.
@@ -717,6 +781,221 @@ pub async fn export_jsonl(
         .into_response())
 }
 
+#[derive(Deserialize)]
+pub struct ExportScopeQuery {
+    /// Export only this frame.
+    pub frame: Option<Uuid>,
+    /// Comma-separated object IDs to export.
+    pub ids: Option<String>,
+}
+
+/// `GET /api/boards/:id/export.mmd` — download shapes and attached connectors
+/// as a Mermaid flowchart, optionally scoped to a frame or a selection.
+pub async fn export_mermaid(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(board_id): Path<Uuid>,
+    Query(query): Query<ExportScopeQuery>,
+) -> Result<Response, StatusCode> {
+    let scope = parse_export_scope(&query).ok_or(StatusCode::BAD_REQUEST)?;
+    let mut objects = scene::load_board_objects(&state, board_id, auth.user.id)
+        .await
+        .map_err(board_error_to_status)?;
+    let text = board_mermaid(&mut objects, &scope).map_err(|_| StatusCode::NOT_FOUND)?;
+    let filename = format!("board-{board_id}.mmd");
+
+    Ok((
+        [
+            (CONTENT_TYPE, "text/vnd.mermaid; charset=utf-8"),
+            (CONTENT_DISPOSITION, &format!("attachment; filename=\"{filename}\"")),
+        ],
+        text,
+    )
+        .into_response())
+}
+
+/// Render objects as a Mermaid flowchart. Live boards come back in map
+/// order, so objects are first put in the database's `z_index, id` order to
+/// keep the output stable.
+fn board_mermaid(objects: &mut [BoardObject], scope: &ExportScope) -> Result<String, String> {
+    objects.sort_by(|a, b| a.z_index.cmp(&b.z_index).then(a.id.cmp(&b.id)));
+    let view: Vec<ExportObject<'_>> = objects
+        .iter()
+        .map(|object| ExportObject {
+            id: object.id,
+            kind: &object.kind,
+            x: object.x,
+            y: object.y,
+            width: object.width,
+            height: object.height,
+            props: &object.props,
+        })
+        .collect();
+    export_flowchart(&view, scope)
+}
+
+/// Scope for an export query, or `None` when both a frame and IDs are given
+/// or an ID is malformed.
+pub(crate) fn parse_export_scope(query: &ExportScopeQuery) -> Option<ExportScope> {
+    match (query.frame, query.ids.as_deref()) {
+        (Some(_), Some(_)) => None,
+        (Some(frame), None) => Some(ExportScope::Frame(frame)),
+        (None, Some(ids)) => ids
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(|id| id.parse::<Uuid>().ok())
+            .collect::<Option<_>>()
+            .map(ExportScope::Objects),
+        (None, None) => Some(ExportScope::Board),
+    }
+}
+
+/// `GET /api/boards/:id/export.svg` — download the board, one frame, or a
+/// selection as an SVG document.
+pub async fn export_svg(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(board_id): Path<Uuid>,
+    Query(query): Query<ExportScopeQuery>,
+) -> Result<Response, StatusCode> {
+    let scope = parse_export_scope(&query).ok_or(StatusCode::BAD_REQUEST)?;
+    let objects = scene::load_board_objects(&state, board_id, auth.user.id)
+        .await
+        .map_err(board_error_to_status)?;
+    let scene = tokio::task::spawn_blocking(move || scene::build_scene(&objects, &scope))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(scene_error_to_status)?;
+    let filename = format!("board-{board_id}.svg");
+
+    Ok((
+        [
+            (CONTENT_TYPE, "image/svg+xml; charset=utf-8"),
+            (CONTENT_DISPOSITION, &format!("attachment; filename=\"{filename}\"")),
+        ],
+        scene.svg,
+    )
+        .into_response())
+}
+
+/// `GET /api/boards/:id/export.pdf` — download the board, one frame, or a
+/// selection as a single-page vector PDF.
+pub async fn export_pdf(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(board_id): Path<Uuid>,
+    Query(query): Query<ExportScopeQuery>,
+) -> Result<Response, StatusCode> {
+    let scope = parse_export_scope(&query).ok_or(StatusCode::BAD_REQUEST)?;
+    let objects = scene::load_board_objects(&state, board_id, auth.user.id)
+        .await
+        .map_err(board_error_to_status)?;
+    let pdf = tokio::task::spawn_blocking(move || pdf::render_pdf(&objects, &scope))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(scene_error_to_status)?;
+    let filename = format!("board-{board_id}.pdf");
+
+    Ok((
+        [
+            (CONTENT_TYPE, "application/pdf"),
+            (CONTENT_DISPOSITION, &format!("attachment; filename=\"{filename}\"")),
+        ],
+        pdf,
+    )
+        .into_response())
+}
+
+#[derive(Deserialize)]
+pub struct ExportPngQuery {
+    /// Pixels per world unit (default 1).
+    pub scale: Option<f64>,
+    /// Crop the image to this frame.
+    pub frame: Option<Uuid>,
+    /// Comma-separated object IDs to render.
+    pub ids: Option<String>,
+}
+
+/// `GET /api/boards/:id/export.png` — render the board, one frame, or a
+/// selection to a PNG image.
+pub async fn export_png(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(board_id): Path<Uuid>,
+    Query(query): Query<ExportPngQuery>,
+) -> Result<Response, StatusCode> {
+    let scope =
+        parse_export_scope(&ExportScopeQuery { frame: query.frame, ids: query.ids }).ok_or(StatusCode::BAD_REQUEST)?;
+    let options = RasterOptions { scale: query.scale.unwrap_or(1.0), scope };
+    let objects = scene::load_board_objects(&state, board_id, auth.user.id)
+        .await
+        .map_err(board_error_to_status)?;
+    let png = tokio::task::spawn_blocking(move || raster::render_png(&objects, &options))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(raster_error_to_status)?;
+    let filename = format!("board-{board_id}.png");
+
+    Ok((
+        [
+            (CONTENT_TYPE, "image/png"),
+            (CONTENT_DISPOSITION, &format!("attachment; filename=\"{filename}\"")),
+        ],
+        png,
+    )
+        .into_response())
+}
+
+/// `GET /api/boards/:id/thumbnail.png` — dashboard thumbnail, served from
+/// the render cache and revalidated by `ETag`.
+pub async fn thumbnail_png(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(board_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let objects = scene::load_board_objects(&state, board_id, auth.user.id)
+        .await
+        .map_err(board_error_to_status)?;
+    let thumbnail = raster::cached_thumbnail(&state, board_id, objects)
+        .await
+        .map_err(raster_error_to_status)?;
+    let etag = format!("\"{:016x}\"", thumbnail.fingerprint);
+    if headers
+        .get(IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        == Some(etag.as_str())
+    {
+        return Ok((StatusCode::NOT_MODIFIED, [(ETAG, etag)]).into_response());
+    }
+
+    Ok((
+        [
+            (CONTENT_TYPE, "image/png".to_owned()),
+            (CACHE_CONTROL, "private, no-cache".to_owned()),
+            (ETAG, etag),
+        ],
+        thumbnail.png,
+    )
+        .into_response())
+}
+
+pub(crate) fn raster_error_to_status(err: RasterError) -> StatusCode {
+    match err {
+        RasterError::InvalidScale(_) => StatusCode::BAD_REQUEST,
+        RasterError::Scene(err) => scene_error_to_status(err),
+        RasterError::Render(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+pub(crate) fn scene_error_to_status(err: SceneError) -> StatusCode {
+    match err {
+        SceneError::FrameNotFound(_) | SceneError::NothingSelected => StatusCode::NOT_FOUND,
+        SceneError::Invalid(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 fn now_ms_i64() -> i64 {
     let Ok(duration) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) else {
         return 0;
@@ -789,9 +1068,14 @@ pub(crate) fn parse_import_object_line(
         .get("group_id")
         .and_then(serde_json::Value::as_str)
         .and_then(|s| Uuid::parse_str(s).ok());
+    let id = map
+        .get("id")
+        .and_then(serde_json::Value::as_str)
+        .and_then(|s| Uuid::parse_str(s).ok())
+        .unwrap_or_else(Uuid::new_v4);
 
     Ok(Some(crate::state::BoardObject {
-        id: Uuid::new_v4(),
+        id,
         board_id,
         kind,
         x,
@@ -807,6 +1091,90 @@ pub(crate) fn parse_import_object_line(
     }))
 }
 
+/// Give imported objects ids for this board and rewrite the references
+/// between them: connector attachments, animation clip targets, and groups.
+///
+/// With `keep_ids`, each object keeps its file id unless it is in `taken` or
+/// repeats an earlier line; otherwise every object and group gets a fresh id.
+/// References to ids outside the file are left as they are.
+pub(crate) fn remap_import_ids(objects: &mut [BoardObject], keep_ids: bool, taken: &HashSet<Uuid>) {
+    let mut ids = HashMap::new();
+    let mut assigned = HashSet::new();
+    for object in objects.iter_mut() {
+        let old = object.id;
+        let new = if keep_ids && !taken.contains(&old) && !assigned.contains(&old) {
+            old
+        } else {
+            Uuid::new_v4()
+        };
+        ids.entry(old).or_insert(new);
+        assigned.insert(new);
+        object.id = new;
+    }
+
+    let mut groups = HashMap::new();
+    for object in objects.iter_mut() {
+        object.group_id = object.group_id.map(|group| {
+            *groups
+                .entry(group)
+                .or_insert_with(|| if keep_ids { group } else { Uuid::new_v4() })
+        });
+        remap_connector_ends(&mut object.props, &ids);
+        remap_animation_targets(&mut object.props, &ids);
+    }
+}
+
+fn remap_connector_ends(props: &mut serde_json::Value, ids: &HashMap<Uuid, Uuid>) {
+    for end in ["a", "b"] {
+        if let Some(object_id) = props.get_mut(end).and_then(|end| end.get_mut("object_id")) {
+            remap_id(object_id, ids);
+        }
+    }
+}
+
+/// Rewrite an animation clip's scope and event targets, plus attachments on
+/// connectors the clip creates.
+fn remap_animation_targets(props: &mut serde_json::Value, ids: &HashMap<Uuid, Uuid>) {
+    let Some(animation) = props.get_mut("animation") else {
+        return;
+    };
+    if let Some(scope) = animation
+        .get_mut("scopeObjectIds")
+        .and_then(serde_json::Value::as_array_mut)
+    {
+        for id in scope {
+            remap_id(id, ids);
+        }
+    }
+    let Some(events) = animation
+        .get_mut("events")
+        .and_then(serde_json::Value::as_array_mut)
+    else {
+        return;
+    };
+    for event in events {
+        if let Some(target) = event.get_mut("targetId") {
+            remap_id(target, ids);
+        }
+        if let Some(props) = event
+            .get_mut("object")
+            .and_then(|object| object.get_mut("props"))
+        {
+            remap_connector_ends(props, ids);
+        }
+    }
+}
+
+fn remap_id(value: &mut serde_json::Value, ids: &HashMap<Uuid, Uuid>) {
+    if let Some(new) = value
+        .as_str()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .and_then(|id| ids.get(&id))
+    {
+        *value = serde_json::Value::String(new.to_string());
+    }
+}
+
 #[cfg(test)]
 #[path = "boards_test.rs"]
 mod tests;
@@ -837,41 +1205,104 @@ pub async fn import_jsonl(
     }
 
     if objects.is_empty() {
-        return Ok(Json(ImportJsonlResponse { imported: 0, skipped }));
+        return Ok(Json(ImportJsonlResponse { imported: 0, skipped, kept_ids: false }));
     }
 
-    board::flush_objects(&state.pool, &objects)
+    let kept_ids = body.keep_ids
+        && board_is_empty(&state, board_id)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let taken = if kept_ids {
+        let ids: Vec<Uuid> = objects.iter().map(|object| object.id).collect();
+        existing_object_ids(&state, &ids)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    } else {
+        HashSet::new()
+    };
+    remap_import_ids(&mut objects, kept_ids, &taken);
+    store_imported_objects(&state, board_id, auth.user.id, &objects).await?;
+
+    Ok(Json(ImportJsonlResponse { imported: objects.len(), skipped, kept_ids }))
+}
+
+/// `POST /api/boards/:id/import/excalidraw` — import an `.excalidraw` file.
+pub async fn import_excalidraw(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(board_id): Path<Uuid>,
+    Json(file): Json<serde_json::Value>,
+) -> Result<Json<ImportFileResponse>, StatusCode> {
+    import_file(&state, &auth, board_id, |user_id| {
+        import::import_excalidraw(&file, board_id, user_id)
+    })
+    .await
+}
+
+/// `POST /api/boards/:id/import/tldraw` — import a `.tldr` file.
+pub async fn import_tldraw(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(board_id): Path<Uuid>,
+    Json(file): Json<serde_json::Value>,
+) -> Result<Json<ImportFileResponse>, StatusCode> {
+    import_file(&state, &auth, board_id, |user_id| {
+        import::import_tldraw(&file, board_id, user_id)
+    })
+    .await
+}
+
+/// Convert a whiteboard file and add its objects above everything on the
+/// board.
+async fn import_file(
+    state: &AppState,
+    auth: &AuthUser,
+    board_id: Uuid,
+    convert: impl FnOnce(Uuid) -> Result<Import, ImportError>,
+) -> Result<Json<ImportFileResponse>, StatusCode> {
+    board::ensure_board_permission(&state.pool, board_id, auth.user.id, board::BoardPermission::Edit)
+        .await
+        .map_err(board_error_to_status)?;
+
+    let Import { mut objects, unsupported } = convert(auth.user.id).map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !objects.is_empty() {
+        let base = next_z_index(state, board_id)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        for object in &mut objects {
+            object.z_index = object.z_index.saturating_add(base);
+        }
+        store_imported_objects(state, board_id, auth.user.id, &objects).await?;
+    }
+
+    Ok(Json(ImportFileResponse { imported: objects.len(), unsupported }))
+}
+
+/// Persist newly imported objects, add them to the live board, and log and
+/// announce each as an `object:create` frame.
+async fn store_imported_objects(
+    state: &AppState,
+    board_id: Uuid,
+    user_id: Uuid,
+    objects: &[BoardObject],
+) -> Result<(), StatusCode> {
+    board::flush_objects(&state.pool, objects)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     {
         let mut boards = state.boards.write().await;
         if let Some(board_state) = boards.get_mut(&board_id) {
-            for object in &objects {
+            for object in objects {
                 board_state.objects.insert(object.id, object.clone());
                 board_state.dirty.remove(&object.id);
             }
         }
     }
 
-    for object in &objects {
-        let data = match serde_json::to_value(object) {
-            Ok(serde_json::Value::Object(map)) => map.into_iter().collect(),
-            _ => continue,
-        };
-        let frame = crate::frame::Frame {
-            id: Uuid::new_v4(),
-            parent_id: None,
-            ts: now_ms_i64(),
-            board_id: Some(board_id),
-            from: None,
-            syscall: "object:create".to_owned(),
-            status: crate::frame::Status::Done,
-            trace: None,
-            data,
-        };
-        board::broadcast(&state, board_id, &frame, None).await;
+    for object in objects {
+        broadcast_object_frame(state, board_id, user_id, "object:create", object_to_data(object)).await;
     }
 
-    Ok(Json(ImportJsonlResponse { imported: objects.len(), skipped }))
+    Ok(())
 }
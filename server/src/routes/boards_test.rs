@@ -125,3 +125,142 @@ fn parse_import_assigns_new_uuid() {
         .unwrap();
     assert_ne!(r1.id, r2.id);
 }
+
+#[test]
+fn parse_import_keeps_line_id() {
+    let id = Uuid::new_v4();
+    let line = format!(r#"{{"type":"object","id":"{id}","kind":"rectangle"}}"#);
+    let result = parse_import_object_line(&line, Uuid::nil(), Uuid::nil())
+        .unwrap()
+        .unwrap();
+    assert_eq!(result.id, id);
+}
+
+fn import_round_trip_objects() -> (Vec<BoardObject>, [Uuid; 4]) {
+    let (a, b, group, outside) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let lines = [
+        format!(r#"{{"type":"object","id":"{a}","kind":"rectangle","group_id":"{group}"}}"#),
+        format!(r#"{{"type":"object","id":"{b}","kind":"ellipse","group_id":"{group}"}}"#),
+        format!(
+            r#"{{"type":"object","id":"{}","kind":"arrow","props":{{"a":{{"type":"attached","object_id":"{a}","ux":1,"uy":0.5}},"b":{{"type":"attached","object_id":"{outside}","ux":0,"uy":0.5}}}}}}"#,
+            Uuid::new_v4()
+        ),
+        format!(
+            r#"{{"type":"object","id":"{}","kind":"rectangle","props":{{"animation":{{"scopeObjectIds":["{a}","{b}"],"events":[{{"tMs":0,"op":"update","targetId":"{b}","patch":{{}}}},{{"tMs":10,"op":"create","object":{{"id":"clip-1","kind":"line","props":{{"a":{{"type":"attached","object_id":"{b}"}}}}}}}},{{"tMs":20,"op":"delete","targetId":"clip-1"}}]}}}}}}"#,
+            Uuid::new_v4()
+        ),
+    ];
+    let objects = lines
+        .iter()
+        .map(|line| {
+            parse_import_object_line(line, Uuid::nil(), Uuid::nil())
+                .unwrap()
+                .unwrap()
+        })
+        .collect();
+    (objects, [a, b, group, outside])
+}
+
+#[test]
+fn remap_import_ids_rewrites_references_to_new_ids() {
+    let (mut objects, [a, b, group, outside]) = import_round_trip_objects();
+    let original: Vec<Uuid> = objects.iter().map(|object| object.id).collect();
+    remap_import_ids(&mut objects, false, &HashSet::new());
+
+    for (object, old) in objects.iter().zip(&original) {
+        assert_ne!(object.id, *old);
+    }
+    let (new_a, new_b) = (objects[0].id.to_string(), objects[1].id.to_string());
+    assert!(objects[0].group_id.is_some());
+    assert_ne!(objects[0].group_id, Some(group));
+    assert_eq!(objects[0].group_id, objects[1].group_id);
+    assert_eq!(objects[2].group_id, None);
+
+    let arrow = &objects[2].props;
+    assert_eq!(arrow["a"]["object_id"], new_a.as_str());
+    assert_eq!(arrow["b"]["object_id"], outside.to_string().as_str());
+
+    let animation = &objects[3].props["animation"];
+    assert_eq!(animation["scopeObjectIds"], serde_json::json!([new_a, new_b]));
+    assert_eq!(animation["events"][0]["targetId"], new_b.as_str());
+    assert_eq!(animation["events"][1]["object"]["id"], "clip-1");
+    assert_eq!(animation["events"][1]["object"]["props"]["a"]["object_id"], new_b.as_str());
+    assert_eq!(animation["events"][2]["targetId"], "clip-1");
+    assert!(
+        !objects
+            .iter()
+            .any(|object| object.id == a || object.id == b)
+    );
+}
+
+#[test]
+fn remap_import_ids_keeps_free_ids_when_asked() {
+    let (mut objects, [a, b, group, _]) = import_round_trip_objects();
+    let taken = HashSet::from([b]);
+    remap_import_ids(&mut objects, true, &taken);
+
+    assert_eq!(objects[0].id, a);
+    assert_ne!(objects[1].id, b);
+    assert_eq!(objects[0].group_id, Some(group));
+    assert_eq!(objects[1].group_id, Some(group));
+    let new_b = objects[1].id.to_string();
+    assert_eq!(objects[3].props["animation"]["events"][0]["targetId"], new_b.as_str());
+}
+
+#[test]
+fn remap_import_ids_separates_repeated_ids() {
+    let id = Uuid::new_v4();
+    let line = format!(r#"{{"type":"object","id":"{id}","kind":"rectangle"}}"#);
+    let mut objects: Vec<BoardObject> = (0..2)
+        .map(|_| {
+            parse_import_object_line(&line, Uuid::nil(), Uuid::nil())
+                .unwrap()
+                .unwrap()
+        })
+        .collect();
+    remap_import_ids(&mut objects, true, &HashSet::new());
+    assert_eq!(objects[0].id, id);
+    assert_ne!(objects[1].id, id);
+}
+
+#[test]
+fn parse_export_scope_defaults_to_board() {
+    let query = ExportScopeQuery { frame: None, ids: None };
+    assert_eq!(parse_export_scope(&query), Some(ExportScope::Board));
+}
+
+#[test]
+fn parse_export_scope_reads_frame_or_ids() {
+    let frame = Uuid::new_v4();
+    let query = ExportScopeQuery { frame: Some(frame), ids: None };
+    assert_eq!(parse_export_scope(&query), Some(ExportScope::Frame(frame)));
+
+    let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+    let query = ExportScopeQuery { frame: None, ids: Some(format!("{a}, {b},")) };
+    assert_eq!(
+        parse_export_scope(&query),
+        Some(ExportScope::Objects([a, b].into_iter().collect()))
+    );
+}
+
+#[test]
+fn parse_export_scope_rejects_bad_ids_and_both_scopes() {
+    let query = ExportScopeQuery { frame: None, ids: Some("not-a-uuid".into()) };
+    assert_eq!(parse_export_scope(&query), None);
+    let query = ExportScopeQuery { frame: Some(Uuid::new_v4()), ids: Some(Uuid::new_v4().to_string()) };
+    assert_eq!(parse_export_scope(&query), None);
+}
+
+#[test]
+fn board_mermaid_orders_live_objects_like_the_database() {
+    let mut low = crate::state::test_helpers::dummy_object();
+    low.props = serde_json::json!({ "text": "low" });
+    let mut high = crate::state::test_helpers::dummy_object();
+    high.z_index = 1;
+    high.props = serde_json::json!({ "text": "high" });
+
+    let text = board_mermaid(&mut [high.clone(), low.clone()], &ExportScope::Board).unwrap();
+
+    assert_eq!(text, board_mermaid(&mut [low, high], &ExportScope::Board).unwrap());
+    assert!(text.find("low").unwrap() < text.find("high").unwrap());
+}
@@ -8,6 +8,7 @@
 
 pub mod auth;
 pub mod boards;
+pub mod usage;
 pub mod users;
 pub mod ws;
 
@@ -17,7 +18,7 @@ use axum::Router;
 use axum::extract::Path;
 use axum::http::StatusCode;
 use axum::response::Redirect;
-use axum::routing::{get, patch, post};
+use axum::routing::{get, patch, post, put};
 use leptos::prelude::*;
 use leptos_axum::{LeptosRoutes, generate_route_list};
 use tower_http::cors::{Any, CorsLayer};
@@ -67,12 +68,21 @@ fn api_routes(state: AppState) -> Router {
                 .delete(boards::delete_object_rest),
         )
         .route("/api/boards/{id}/import.jsonl", post(boards::import_jsonl))
+        .route("/api/boards/{id}/import/excalidraw", post(boards::import_excalidraw))
+        .route("/api/boards/{id}/import/tldraw", post(boards::import_tldraw))
         .route("/api/boards/{id}/export.jsonl", get(boards::export_jsonl))
+        .route("/api/boards/{id}/export.mmd", get(boards::export_mermaid))
+        .route("/api/boards/{id}/export.svg", get(boards::export_svg))
+        .route("/api/boards/{id}/export.pdf", get(boards::export_pdf))
+        .route("/api/boards/{id}/export.png", get(boards::export_png))
+        .route("/api/boards/{id}/thumbnail.png", get(boards::thumbnail_png))
         .route(
             "/api/boards/{id}/members/{user_id}",
             patch(boards::update_member).delete(boards::delete_member),
         )
         .route("/api/users/{id}/profile", get(users::user_profile))
+        .route("/api/ai/usage", get(usage::usage_report))
+        .route("/api/ai/budgets", put(usage::set_budget))
         .route("/api/ws", get(ws::handle_ws))
         .route("/healthz", get(healthz))
         .layer(cors)
@@ -0,0 +1,91 @@
+//! AI usage report and budget routes.
+//!
+//! DESIGN
+//! ======
+//! Reports read the durable `ai_usage` ledger, so they survive restarts and
+//! cover every process. Budget writes can only tighten the operator's env
+//! defaults; board budgets need board admin rights and user budgets can only
+//! be set by that user.
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::Json;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::boards::board_error_to_status;
+use crate::services::board::{self, BoardPermission};
+use crate::services::usage::{self, BudgetLimits, BudgetScope, ReportScope, UsageReport};
+use crate::state::AppState;
+
+const DEFAULT_REPORT_DAYS: i64 = 30;
+const MAX_REPORT_DAYS: i64 = 366;
+
+#[derive(Deserialize)]
+pub struct UsageQuery {
+    /// Report on this board instead of the caller's own usage.
+    pub board_id: Option<Uuid>,
+    pub days: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct BudgetBody {
+    pub scope: BudgetScope,
+    pub scope_id: Uuid,
+    #[serde(flatten)]
+    pub limits: BudgetLimits,
+}
+
+/// `GET /api/ai/usage` — AI usage report for the caller or a board.
+pub async fn usage_report(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<UsageReport>, StatusCode> {
+    let scope = match query.board_id {
+        Some(board_id) => {
+            board::ensure_board_permission(&state.pool, board_id, auth.user.id, BoardPermission::View)
+                .await
+                .map_err(board_error_to_status)?;
+            ReportScope::Board(board_id)
+        }
+        None => ReportScope::User(auth.user.id),
+    };
+    let days = query
+        .days
+        .unwrap_or(DEFAULT_REPORT_DAYS)
+        .clamp(1, MAX_REPORT_DAYS);
+    let report = usage::usage_report(&state, scope, days)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(report))
+}
+
+/// `PUT /api/ai/budgets` — set or clear a user or board token budget.
+pub async fn set_budget(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<BudgetBody>,
+) -> Result<Json<BudgetLimits>, StatusCode> {
+    if [body.limits.daily_tokens, body.limits.monthly_tokens]
+        .iter()
+        .flatten()
+        .any(|limit| *limit <= 0)
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match body.scope {
+        BudgetScope::User if body.scope_id != auth.user.id => return Err(StatusCode::FORBIDDEN),
+        BudgetScope::User => {}
+        BudgetScope::Board => {
+            board::ensure_board_permission(&state.pool, body.scope_id, auth.user.id, BoardPermission::Admin)
+                .await
+                .map_err(board_error_to_status)?;
+        }
+    }
+    usage::set_budget(&state, body.scope, body.scope_id, body.limits)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(usage::effective_limits(&state, body.scope, body.scope_id).await))
+}
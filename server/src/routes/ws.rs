@@ -22,17 +22,23 @@ use axum::extract::{Query, State};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
-use crate::frame::{Data, Frame};
+use crate::frame::{Data, Frame, Status};
+use crate::llm::LlmChat;
 use crate::services;
 use crate::state::AppState;
 
 const DEFAULT_WS_CLIENT_CHANNEL_CAPACITY: usize = 256;
 const JOIN_BULK_CHUNK_SIZE: usize = 256;
+/// Most distinct changed objects a reconnect delta may carry before falling back to a full snapshot.
+const JOIN_DELTA_MAX_OBJECTS: usize = 1024;
+/// Most operations a single `object:batch` request may carry.
+const OBJECT_BATCH_MAX_OPS: usize = 1000;
 
 fn ws_client_channel_capacity() -> usize {
     std::env::var("WS_CLIENT_CHANNEL_CAPACITY")
@@ -58,6 +64,8 @@ enum Outcome {
     Reply(Data),
     /// Stream one or more non-terminal payloads, then a terminal done payload, to sender.
     ReplyStream { items: Vec<Data>, done: Data },
+    /// Stream non-terminal payloads, then a terminal cancel payload, to sender.
+    ReplyStreamCancelled { items: Vec<Data>, data: Data },
     /// Send empty done to sender only.
     Done,
     /// Reply to sender with one payload, broadcast different data to peers.
@@ -156,21 +164,36 @@ async fn run_ws(mut socket: WebSocket, state: AppState, user_id: Uuid) {
                 let Some(msg) = msg else { break };
                 let Ok(msg) = msg else { break };
                 match msg {
-                    Message::Binary(bytes) => {
-                        dispatch_frame(
-                            &state,
-                            &mut socket,
-                            &mut current_board,
-                            &mut trace_enabled,
-                            client_id,
-                            user_id,
-                            &user_name,
-                            &user_color,
-                            &client_tx,
-                            &bytes,
-                        )
-                        .await;
-                    }
+                    Message::Binary(bytes) => match decode_inbound(client_id, &bytes) {
+                        Ok(req) if is_ai_run_request(&req) => {
+                            // Run prompts off the read loop so a cancel frame can reach them.
+                            spawn_detached_frame(
+                                &state,
+                                current_board,
+                                client_id,
+                                user_id,
+                                &user_name,
+                                &user_color,
+                                &client_tx,
+                                req,
+                            );
+                        }
+                        inbound => {
+                            dispatch_frame(
+                                &state,
+                                &mut socket,
+                                &mut current_board,
+                                &mut trace_enabled,
+                                client_id,
+                                user_id,
+                                &user_name,
+                                &user_color,
+                                &client_tx,
+                                inbound,
+                            )
+                            .await;
+                        }
+                    },
                     Message::Close(_) => break,
                     _ => {}
                 }
@@ -205,6 +228,8 @@ async fn run_ws(mut socket: WebSocket, state: AppState, user_id: Uuid) {
         let mut sessions = state.ai_session_messages.write().await;
         sessions.retain(|(session_client_id, _board_id), _| *session_client_id != client_id);
     }
+    services::ai::cancel_client_prompts(&state, client_id).await;
+    services::ai::discard_client_previews(&state, client_id).await;
     info!(%client_id, "ws: client disconnected");
 }
 
@@ -212,7 +237,8 @@ async fn run_ws(mut socket: WebSocket, state: AppState, user_id: Uuid) {
 // FRAME DISPATCH
 // =============================================================================
 
-/// Parse an incoming binary frame, dispatch to handler, apply outcome.
+/// Dispatch a decoded inbound frame to its handler and send the replies, or
+/// send back the error for a frame that failed to decode.
 async fn dispatch_frame(
     state: &AppState,
     socket: &mut WebSocket,
@@ -223,31 +249,107 @@ async fn dispatch_frame(
     user_name: &str,
     user_color: &str,
     client_tx: &mpsc::Sender<Frame>,
-    bytes: &[u8],
+    inbound: Result<Frame, String>,
 ) {
-    let sender_frames = process_inbound_bytes(
-        state,
-        current_board,
-        trace_enabled,
-        client_id,
-        user_id,
-        user_name,
-        user_color,
-        client_tx,
-        bytes,
-    )
-    .await;
+    let sender_frames = match inbound {
+        Ok(req) => {
+            process_inbound_frame(
+                state,
+                current_board,
+                trace_enabled,
+                client_id,
+                user_id,
+                user_name,
+                user_color,
+                client_tx,
+                req,
+            )
+            .await
+        }
+        Err(message) => vec![gateway_error(state, message)],
+    };
     for frame in sender_frames {
         let outbound = frame_for_client(&frame, *trace_enabled);
         let _ = send_frame(socket, &outbound).await;
     }
 }
 
-/// Parse and process one inbound binary frame and return frames for the sender.
+/// Whether an inbound frame is a request that runs the AI tool loop.
+fn is_ai_run_request(req: &Frame) -> bool {
+    is_ai_run_syscall(&req.syscall) && req.status == Status::Request
+}
+
+/// Whether `syscall` runs the AI tool loop and streams `item` frames back.
+fn is_ai_run_syscall(syscall: &str) -> bool {
+    matches!(syscall, "ai:prompt" | "ai:preview" | "ai:ask")
+}
+
+/// Process an inbound frame on its own task, replying through `client_tx`.
+///
+/// Replies pass through the socket loop's outbound channel, which applies
+/// the connection's trace filter at send time.
+#[allow(clippy::too_many_arguments)]
+fn spawn_detached_frame(
+    state: &AppState,
+    current_board: Option<Uuid>,
+    client_id: Uuid,
+    user_id: Uuid,
+    user_name: &str,
+    user_color: &str,
+    client_tx: &mpsc::Sender<Frame>,
+    req: Frame,
+) {
+    let state = state.clone();
+    let user_name = user_name.to_owned();
+    let user_color = user_color.to_owned();
+    let client_tx = client_tx.clone();
+    tokio::spawn(async move {
+        let mut current_board = current_board;
+        let mut trace_enabled = false;
+        let sender_frames = process_inbound_frame(
+            &state,
+            &mut current_board,
+            &mut trace_enabled,
+            client_id,
+            user_id,
+            &user_name,
+            &user_color,
+            &client_tx,
+            req,
+        )
+        .await;
+        for frame in sender_frames {
+            if client_tx.send(frame).await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Decode one inbound binary frame, or describe why it is malformed.
+fn decode_inbound(client_id: Uuid, bytes: &[u8]) -> Result<Frame, String> {
+    let inbound = frames::decode_frame(bytes).map_err(|e| {
+        warn!(%client_id, error = %e, "ws: invalid inbound frame");
+        format!("invalid frame: {e}")
+    })?;
+    Frame::try_from(inbound).map_err(|e| {
+        warn!(%client_id, error = %e, "ws: inbound frame conversion failed");
+        format!("invalid frame: {e}")
+    })
+}
+
+/// Persist and return a `gateway:error` frame for the sender.
+fn gateway_error(state: &AppState, message: String) -> Frame {
+    let err = Frame::request("gateway:error", Data::new()).with_data("message", message);
+    services::persistence::enqueue_frame(state, &err);
+    err
+}
+
+/// Process one decoded inbound frame and return frames for the sender.
 ///
 /// This keeps the websocket transport concerns separate from frame handling,
 /// so tests can exercise frame dispatch and AI broadcast behavior end-to-end.
-async fn process_inbound_bytes(
+async fn process_inbound_frame(
     state: &AppState,
     current_board: &mut Option<Uuid>,
     trace_enabled: &mut bool,
@@ -256,27 +358,8 @@ async fn process_inbound_bytes(
     user_name: &str,
     user_color: &str,
     client_tx: &mpsc::Sender<Frame>,
-    bytes: &[u8],
+    mut req: Frame,
 ) -> Vec<Frame> {
-    let inbound = match frames::decode_frame(bytes) {
-        Ok(frame) => frame,
-        Err(e) => {
-            warn!(%client_id, error = %e, "ws: invalid inbound frame");
-            let err = Frame::request("gateway:error", Data::new()).with_data("message", format!("invalid frame: {e}"));
-            services::persistence::enqueue_frame(state, &err);
-            return vec![err];
-        }
-    };
-    let mut req = match Frame::try_from(inbound) {
-        Ok(r) => r,
-        Err(e) => {
-            warn!(%client_id, error = %e, "ws: inbound frame conversion failed");
-            let err = Frame::request("gateway:error", Data::new()).with_data("message", format!("invalid frame: {e}"));
-            services::persistence::enqueue_frame(state, &err);
-            return vec![err];
-        }
-    };
-
     // Stamp the authenticated user_id as `from`.
     req.from = Some(user_id.to_string());
 
@@ -289,6 +372,10 @@ async fn process_inbound_bytes(
         services::persistence::enqueue_frame(state, &req);
     }
 
+    if req.status == Status::Cancel {
+        return handle_cancel(state, client_id, &req).await;
+    }
+
     // Dispatch to handler — returns Outcome or error Frame.
     let result = match prefix {
         "board" => handle_board(state, current_board, client_id, user_id, user_name, user_color, client_tx, &req).await,
@@ -298,6 +385,8 @@ async fn process_inbound_bytes(
         "ai" => handle_ai(state, *current_board, client_id, &req).await,
         "trace" => handle_trace(trace_enabled, &req),
         "tool" => handle_tool(state, *current_board, client_id, &req).await,
+        "text" => handle_text(state, *current_board, client_id, &req).await,
+        "history" => handle_history(state, *current_board, client_id, user_id, &req).await,
         _ => Err(req.error(format!("unknown prefix: {prefix}"))),
     };
 
@@ -338,13 +427,13 @@ async fn process_inbound_bytes(
             let mut sender_frames = Vec::with_capacity(items.len() + 1);
             for data in items {
                 let (data, trace) = split_trace_from_data(data);
-                let mut item_frame = if req.syscall == "ai:prompt" {
+                let mut item_frame = if is_ai_run_syscall(&req.syscall) {
                     req.item_with(data)
                 } else {
                     req.bulk_with(data)
                 };
                 item_frame.trace = trace;
-                if req.syscall == "ai:prompt" {
+                if is_ai_run_syscall(&req.syscall) {
                     services::persistence::enqueue_frame(state, &item_frame);
                 }
                 sender_frames.push(item_frame);
@@ -356,6 +445,22 @@ async fn process_inbound_bytes(
             sender_frames.push(done_frame);
             sender_frames
         }
+        Ok(Outcome::ReplyStreamCancelled { items, data }) => {
+            let mut sender_frames = Vec::with_capacity(items.len() + 1);
+            for data in items {
+                let (data, trace) = split_trace_from_data(data);
+                let mut item_frame = req.item_with(data);
+                item_frame.trace = trace;
+                services::persistence::enqueue_frame(state, &item_frame);
+                sender_frames.push(item_frame);
+            }
+            let (data, trace) = split_trace_from_data(data);
+            let mut cancel_frame = req.cancelled_with(data);
+            cancel_frame.trace = trace;
+            services::persistence::enqueue_frame(state, &cancel_frame);
+            sender_frames.push(cancel_frame);
+            sender_frames
+        }
         Ok(Outcome::Done) => {
             let sender_frame = req.done();
             services::persistence::enqueue_frame(state, &sender_frame);
@@ -434,6 +539,7 @@ async fn handle_board(
         "visibility:set" => handle_board_visibility_set(state, *current_board, user_id, req).await,
         "savepoint:create" => handle_board_savepoint_create(state, *current_board, user_id, req).await,
         "savepoint:list" => handle_board_savepoint_list(state, *current_board, user_id, req).await,
+        "savepoint:restore" => handle_board_savepoint_restore(state, *current_board, user_id, req).await,
         "access:generate" => handle_board_access_generate(state, *current_board, user_id, req).await,
         "access:redeem" => handle_board_access_redeem(state, user_id, req).await,
         _ => Err(req.error(format!("unknown board op: {op}"))),
@@ -490,23 +596,64 @@ async fn handle_board_join(
         services::board::part_board(state, old_board, client_id).await;
     }
 
+    // Read the sequence before snapshotting: every frame at or below it was
+    // applied in memory first, so the snapshot is current through `sync_seq`.
+    let since_seq = req
+        .data
+        .get("since_seq")
+        .and_then(serde_json::Value::as_i64);
+    let sync_seq = services::board::latest_frame_seq(&state.pool, board_id)
+        .await
+        .ok();
+
     match services::board::join_board(state, board_id, user_id, user_name, user_color, client_id, client_tx.clone())
         .await
     {
         Ok(objects) => {
             *current_board = Some(board_id);
 
+            let touched = match (since_seq, sync_seq) {
+                (Some(since), Some(latest)) => services::board::touched_object_ids_since(
+                    &state.pool,
+                    board_id,
+                    since,
+                    latest,
+                    JOIN_DELTA_MAX_OBJECTS,
+                )
+                .await
+                .unwrap_or_else(|e| {
+                    warn!(%board_id, error = %e, "board:join delta query failed; sending full snapshot");
+                    None
+                }),
+                _ => None,
+            };
+            let (objects, deleted) = match services::board::plan_join_sync(objects, touched) {
+                services::board::JoinSync::Full(objects) => (objects, None),
+                services::board::JoinSync::Delta { upserts, deleted } => (upserts, Some(deleted)),
+            };
+            let is_delta = deleted.is_some();
+
             let object_rows = objects.iter().map(object_to_data).collect::<Vec<_>>();
             let item_payloads = object_rows
                 .chunks(JOIN_BULK_CHUNK_SIZE)
                 .map(|chunk| {
                     let mut data = Data::new();
                     data.insert("objects".into(), serde_json::json!(chunk));
+                    if is_delta {
+                        data.insert("delta".into(), serde_json::json!(true));
+                    }
                     data
                 })
                 .collect::<Vec<_>>();
             let mut done = Data::new();
             done.insert("count".into(), serde_json::json!(object_rows.len()));
+            if let Some(seq) = sync_seq {
+                done.insert("seq".into(), serde_json::json!(seq));
+            }
+            if let Some(deleted) = deleted {
+                done.insert("delta".into(), serde_json::json!(true));
+                done.insert("deleted".into(), serde_json::json!(deleted));
+            }
             if let Ok(Some((name, is_public))) =
                 sqlx::query_as::<_, (String, bool)>("SELECT name, is_public FROM boards WHERE id = $1")
                     .bind(board_id)
@@ -681,6 +828,7 @@ async fn handle_board_list(state: &AppState, user_id: Uuid, req: &Frame) -> Resu
                         "owner_id": b.owner_id,
                         "is_public": b.is_public,
                         "snapshot": previews.remove(&b.id).unwrap_or_default(),
+                        "thumbnail_url": format!("/api/boards/{}/thumbnail.png", b.id),
                     })
                 })
                 .collect();
@@ -738,6 +886,7 @@ async fn handle_board_delete(state: &AppState, user_id: Uuid, req: &Frame) -> Re
                 let mut boards = state.boards.write().await;
                 boards.remove(&board_id);
             }
+            state.thumbnails.write().await.remove(&board_id);
 
             for tx in recipients {
                 let _ = tx.try_send(notify.clone());
@@ -822,6 +971,79 @@ async fn handle_board_savepoint_list(
     }
 }
 
+async fn handle_board_savepoint_restore(
+    state: &AppState,
+    current_board: Option<Uuid>,
+    user_id: Uuid,
+    req: &Frame,
+) -> Result<Outcome, Frame> {
+    let Some(board_id) = board_id_from_frame(req, current_board) else {
+        return Err(req.error("board_id required"));
+    };
+    let Some(savepoint_id) = req
+        .data
+        .get("savepoint_id")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse::<Uuid>().ok())
+    else {
+        return Err(req.error("savepoint_id required"));
+    };
+
+    match services::savepoint::restore_savepoint(state, board_id, user_id, savepoint_id).await {
+        Ok(result) => {
+            broadcast_restore_changes(state, board_id, user_id, req.id, &result.diff).await;
+
+            let mut reply = Data::new();
+            reply.insert("savepoint_id".into(), serde_json::json!(savepoint_id));
+            reply.insert("created".into(), serde_json::json!(result.diff.created.len()));
+            reply.insert("updated".into(), serde_json::json!(result.diff.updated.len()));
+            reply.insert("deleted".into(), serde_json::json!(result.diff.deleted.len()));
+            let mut broadcast = Data::new();
+            broadcast.insert("savepoint_id".into(), serde_json::json!(savepoint_id));
+            broadcast.insert("pre_restore_savepoint_id".into(), serde_json::json!(result.pre_restore.id));
+            reply.insert(
+                "pre_restore".into(),
+                services::savepoint::savepoint_row_to_json(result.pre_restore),
+            );
+            Ok(Outcome::ReplyAndBroadcast { reply, broadcast })
+        }
+        Err(e) => Err(req.error_from(&e)),
+    }
+}
+
+/// Broadcast the object frames that move every client onto a restored snapshot.
+async fn broadcast_restore_changes(
+    state: &AppState,
+    board_id: Uuid,
+    user_id: Uuid,
+    parent_id: Uuid,
+    diff: &services::savepoint::RestoreDiff,
+) {
+    let deletes = diff.deleted.iter().map(|id| {
+        let mut data = Data::new();
+        data.insert("id".into(), serde_json::json!(id));
+        ("object:delete", data)
+    });
+    let creates = diff
+        .created
+        .iter()
+        .map(|obj| ("object:create", object_to_data(obj)));
+    let updates = diff
+        .updated
+        .iter()
+        .map(|obj| ("object:update", object_to_data(obj)));
+
+    for (syscall, data) in deletes.chain(creates).chain(updates) {
+        let mut frame = Frame::request(syscall, data)
+            .with_board_id(board_id)
+            .with_from(user_id.to_string());
+        frame.parent_id = Some(parent_id);
+        frame.status = crate::frame::Status::Done;
+        services::persistence::enqueue_frame(state, &frame);
+        services::board::broadcast(state, board_id, &frame, None).await;
+    }
+}
+
 // =============================================================================
 // ACCESS CODE HANDLERS
 // =============================================================================
@@ -1004,7 +1226,11 @@ async fn handle_object(
             )
             .await
             {
-                Ok(obj) => Ok(Outcome::Broadcast(object_to_data(&obj))),
+                Ok(obj) => {
+                    let changes = vec![services::history::HistoryChange::Created(obj.clone())];
+                    services::history::record(state, board_id, user_id, changes).await;
+                    Ok(Outcome::Broadcast(object_to_data(&obj)))
+                }
                 Err(e) => Err(req.error_from(&e)),
             }
         }
@@ -1032,8 +1258,14 @@ async fn handle_object(
                 .and_then(|v| i32::try_from(v).ok())
                 .unwrap_or(0);
 
+            let before = services::history::objects_before(state, board_id, &[object_id]).await;
             match services::object::update_object(state, board_id, object_id, &req.data, version).await {
-                Ok(obj) => Ok(Outcome::Broadcast(object_to_data(&obj))),
+                Ok(obj) => {
+                    let applied = [services::object::BatchChange::Updated(obj.clone())];
+                    let changes = services::history::changes_from_batch(before, &applied);
+                    services::history::record(state, board_id, user_id, changes).await;
+                    Ok(Outcome::Broadcast(object_to_data(&obj)))
+                }
                 Err(e) => Err(req.error_from(&e)),
             }
         }
@@ -1047,8 +1279,12 @@ async fn handle_object(
                 return Err(req.error("id required"));
             };
 
+            let before = services::history::objects_before(state, board_id, &[object_id]).await;
             match services::object::delete_object(state, board_id, object_id).await {
                 Ok(()) => {
+                    let applied = [services::object::BatchChange::Deleted(object_id)];
+                    let changes = services::history::changes_from_batch(before, &applied);
+                    services::history::record(state, board_id, user_id, changes).await;
                     let mut data = Data::new();
                     data.insert("id".into(), serde_json::json!(object_id));
                     Ok(Outcome::Broadcast(data))
@@ -1056,6 +1292,7 @@ async fn handle_object(
                 Err(e) => Err(req.error_from(&e)),
             }
         }
+        "batch" => handle_object_batch(state, board_id, user_id, req).await,
         "drag" => {
             let Some(object_id) = req
                 .data
@@ -1092,6 +1329,270 @@ async fn handle_object(
     }
 }
 
+/// Apply an `object:batch` request atomically and broadcast one combined frame.
+///
+/// `data.ops` is an ordered array of `{ "op": "create" | "update" | "delete", ... }`
+/// entries using the same fields as the single-object syscalls. The done frame
+/// carries `ops` in the same order, each entry holding the resulting object (or
+/// just the ID for deletes), so clients can apply the whole batch in one pass.
+async fn handle_object_batch(state: &AppState, board_id: Uuid, user_id: Uuid, req: &Frame) -> Result<Outcome, Frame> {
+    let Some(raw_ops) = req.data.get("ops").and_then(serde_json::Value::as_array) else {
+        return Err(req.error("ops required"));
+    };
+    if raw_ops.is_empty() {
+        return Err(req.error("ops required"));
+    }
+    if raw_ops.len() > OBJECT_BATCH_MAX_OPS {
+        return Err(req.error(format!("too many ops: max {OBJECT_BATCH_MAX_OPS}")));
+    }
+
+    let mut ops = Vec::with_capacity(raw_ops.len());
+    for (index, raw) in raw_ops.iter().enumerate() {
+        let op = parse_batch_op(raw).map_err(|message| req.error(format!("ops[{index}]: {message}")))?;
+        ops.push(op);
+    }
+
+    let touched = ops
+        .iter()
+        .filter_map(|op| match op {
+            services::object::BatchOp::Update { id, .. } | services::object::BatchOp::Delete { id } => Some(*id),
+            services::object::BatchOp::Create { .. } => None,
+        })
+        .collect::<Vec<_>>();
+    let before = services::history::objects_before(state, board_id, &touched).await;
+
+    let changes = services::object::apply_batch(state, board_id, &ops, Some(user_id))
+        .await
+        .map_err(|e| req.error_from(&e))?;
+
+    let recorded = services::history::changes_from_batch(before, &changes);
+    services::history::record(state, board_id, user_id, recorded).await;
+
+    Ok(Outcome::Broadcast(batch_changes_to_data(&changes)))
+}
+
+/// Encode applied batch changes as the `ops` payload shared by `object:batch`
+/// and `history:*` done frames.
+fn batch_changes_to_data(changes: &[services::object::BatchChange]) -> Data {
+    let results: Vec<serde_json::Value> = changes
+        .iter()
+        .map(|change| {
+            let (op, mut data) = match change {
+                services::object::BatchChange::Created(obj) => ("create", object_to_data(obj)),
+                services::object::BatchChange::Updated(obj) => ("update", object_to_data(obj)),
+                services::object::BatchChange::Deleted(id) => {
+                    let mut data = Data::new();
+                    data.insert("id".into(), serde_json::json!(id));
+                    ("delete", data)
+                }
+            };
+            data.insert("op".into(), serde_json::json!(op));
+            serde_json::json!(data)
+        })
+        .collect();
+
+    let mut data = Data::new();
+    data.insert("ops".into(), serde_json::json!(results));
+    data
+}
+
+/// Parse one entry of an `object:batch` `ops` array.
+fn parse_batch_op(raw: &serde_json::Value) -> Result<services::object::BatchOp, String> {
+    let Some(fields) = raw.as_object() else {
+        return Err("op must be an object".into());
+    };
+    let op = fields
+        .get("op")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default();
+    let parse_id = || {
+        fields
+            .get("id")
+            .and_then(serde_json::Value::as_str)
+            .and_then(|s| s.parse::<Uuid>().ok())
+            .ok_or_else(|| "id required".to_string())
+    };
+
+    match op {
+        "create" => Ok(services::object::BatchOp::Create {
+            kind: fields
+                .get("kind")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("sticky_note")
+                .to_string(),
+            x: fields
+                .get("x")
+                .and_then(serde_json::Value::as_f64)
+                .unwrap_or(0.0),
+            y: fields
+                .get("y")
+                .and_then(serde_json::Value::as_f64)
+                .unwrap_or(0.0),
+            width: fields.get("width").and_then(serde_json::Value::as_f64),
+            height: fields.get("height").and_then(serde_json::Value::as_f64),
+            rotation: fields
+                .get("rotation")
+                .and_then(serde_json::Value::as_f64)
+                .unwrap_or(0.0),
+            props: fields
+                .get("props")
+                .cloned()
+                .unwrap_or(serde_json::json!({})),
+            group_id: fields
+                .get("group_id")
+                .and_then(serde_json::Value::as_str)
+                .and_then(|s| Uuid::parse_str(s).ok()),
+        }),
+        "update" => {
+            let id = parse_id()?;
+            let version = fields
+                .get("version")
+                .and_then(|value| {
+                    value.as_i64().or_else(|| {
+                        #[allow(clippy::cast_possible_truncation)]
+                        value
+                            .as_f64()
+                            .filter(|v| v.fract() == 0.0)
+                            .map(|v| v as i64)
+                    })
+                })
+                .and_then(|v| i32::try_from(v).ok())
+                .unwrap_or(0);
+            let updates: Data = fields
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            Ok(services::object::BatchOp::Update { id, version, updates })
+        }
+        "delete" => Ok(services::object::BatchOp::Delete { id: parse_id()? }),
+        other => Err(format!("unknown batch op: {other}")),
+    }
+}
+
+// =============================================================================
+// HISTORY HANDLERS
+// =============================================================================
+
+/// Undo or redo the requesting user's own edits on the current board.
+///
+/// The done frame uses the `object:batch` `ops` shape so every client applies
+/// the reverted objects with the same code path.
+async fn handle_history(
+    state: &AppState,
+    current_board: Option<Uuid>,
+    client_id: Uuid,
+    user_id: Uuid,
+    req: &Frame,
+) -> Result<Outcome, Frame> {
+    let Some(board_id) = current_board else {
+        return Err(req.error("must join a board first"));
+    };
+    if !services::board::client_has_permission(state, board_id, client_id, services::board::BoardPermission::Edit).await
+    {
+        return Err(req.error("forbidden"));
+    }
+
+    let op = req.syscall.split_once(':').map_or("", |(_, op)| op);
+    let result = match op {
+        "undo" => services::history::undo(state, board_id, user_id).await,
+        "redo" => services::history::redo(state, board_id, user_id).await,
+        _ => return Err(req.error(format!("unknown history op: {op}"))),
+    };
+    let changes = result.map_err(|e| req.error_from(&e))?;
+    Ok(Outcome::Broadcast(batch_changes_to_data(&changes)))
+}
+
+// =============================================================================
+// TEXT HANDLERS
+// =============================================================================
+
+/// Collaborative text editing for object text props.
+///
+/// `text:state` returns the current revision and text of a field so an editor
+/// can start tracking it. `text:op` submits an operation against a revision;
+/// the transformed operation is broadcast with the new revision, the full
+/// resulting text, and the bumped object version.
+async fn handle_text(
+    state: &AppState,
+    current_board: Option<Uuid>,
+    client_id: Uuid,
+    req: &Frame,
+) -> Result<Outcome, Frame> {
+    let Some(board_id) = current_board else {
+        return Err(req.error("must join a board first"));
+    };
+    let Some(object_id) = req
+        .data
+        .get("id")
+        .and_then(serde_json::Value::as_str)
+        .and_then(|s| s.parse::<Uuid>().ok())
+    else {
+        return Err(req.error("id required"));
+    };
+    let field = req
+        .data
+        .get("field")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("text");
+
+    let op = req.syscall.split_once(':').map_or("", |(_, op)| op);
+    match op {
+        "state" => {
+            let (revision, text) = services::text::text_state(state, board_id, object_id, field)
+                .await
+                .map_err(|e| req.error_from(&e))?;
+            let mut data = Data::new();
+            data.insert("id".into(), serde_json::json!(object_id));
+            data.insert("field".into(), serde_json::json!(field));
+            data.insert("rev".into(), serde_json::json!(revision));
+            data.insert("text".into(), serde_json::json!(text));
+            Ok(Outcome::Reply(data))
+        }
+        "op" => {
+            if !services::board::client_has_permission(
+                state,
+                board_id,
+                client_id,
+                services::board::BoardPermission::Edit,
+            )
+            .await
+            {
+                return Err(req.error("forbidden"));
+            }
+            let Some(base_rev) = req.data.get("rev").and_then(|value| {
+                value.as_u64().or_else(|| {
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    value
+                        .as_f64()
+                        .filter(|v| v.fract() == 0.0 && *v >= 0.0)
+                        .map(|v| v as u64)
+                })
+            }) else {
+                return Err(req.error("rev required"));
+            };
+            let Some(raw_op) = req.data.get("op") else {
+                return Err(req.error("op required"));
+            };
+            let text_op = frames::text_ops::TextOp::from_json(raw_op)
+                .map_err(|e| req.error_from(&services::text::TextError::from(e)))?;
+
+            let edit = services::text::apply_text_op(state, board_id, object_id, field, base_rev, text_op)
+                .await
+                .map_err(|e| req.error_from(&e))?;
+
+            let mut data = Data::new();
+            data.insert("id".into(), serde_json::json!(object_id));
+            data.insert("field".into(), serde_json::json!(field));
+            data.insert("rev".into(), serde_json::json!(edit.rev));
+            data.insert("op".into(), edit.op.to_json());
+            data.insert("text".into(), serde_json::json!(edit.text));
+            data.insert("version".into(), serde_json::json!(edit.object.version));
+            Ok(Outcome::Broadcast(data))
+        }
+        _ => Err(req.error(format!("unknown text op: {op}"))),
+    }
+}
+
 // =============================================================================
 // CURSOR HANDLER
 // =============================================================================
@@ -1262,6 +1763,25 @@ fn allow_external_tool_syscalls() -> bool {
         .is_some_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
 }
 
+/// Summarize applied AI mutations as `{op, id}` entries.
+fn ai_mutations_to_data(mutations: &[services::ai::AiMutation]) -> Vec<serde_json::Value> {
+    mutations
+        .iter()
+        .map(|mutation| match mutation {
+            services::ai::AiMutation::Created(obj) => serde_json::json!({ "op": "create", "id": obj.id }),
+            services::ai::AiMutation::Updated(obj) => serde_json::json!({ "op": "update", "id": obj.id }),
+            services::ai::AiMutation::Deleted(id) => serde_json::json!({ "op": "delete", "id": id }),
+        })
+        .collect()
+}
+
+/// Persist and broadcast one `object:*` frame per AI mutation.
+///
+/// With a `trace_id`, each frame is tagged as an `object.mutation` span. The
+/// first update or delete of each object in `before` also carries that
+/// object's prior state as `trace.before`, which `ai:revert` restores. The
+/// same effects are kept in the in-memory prompt log, which `ai:revert`
+/// prefers over the lossy frame log.
 async fn broadcast_ai_mutations(
     state: &AppState,
     board_id: Uuid,
@@ -1269,8 +1789,16 @@ async fn broadcast_ai_mutations(
     parent_id: Option<Uuid>,
     trace_id: Option<Uuid>,
     mutations: &[services::ai::AiMutation],
+    before: &HashMap<Uuid, crate::state::BoardObject>,
 ) {
+    if let Some(trace_id) = trace_id {
+        services::revert::remember_prompt(state, board_id, trace_id, mutations, before).await;
+    }
+    let mut seen = std::collections::HashSet::new();
     for mutation in mutations {
+        let prior = before
+            .get(&mutation.object_id())
+            .filter(|_| seen.insert(mutation.object_id()));
         let (syscall, data) = match mutation {
             services::ai::AiMutation::Created(obj) => ("object:create", object_to_data(obj)),
             services::ai::AiMutation::Updated(obj) => ("object:update", object_to_data(obj)),
@@ -1292,6 +1820,9 @@ async fn broadcast_ai_mutations(
                 "kind": "object.mutation",
                 "label": syscall
             }));
+            if let (Some(trace), Some(prior)) = (frame.trace.as_mut(), prior) {
+                trace["before"] = serde_json::json!(prior);
+            }
         }
         frame.status = crate::frame::Status::Done;
         services::persistence::enqueue_frame(state, &frame);
@@ -1319,7 +1850,7 @@ async fn handle_tool(
         return Err(req.error("missing authenticated user id"));
     };
 
-    match services::tool_syscall::dispatch_tool_frame(state, board_id, req).await {
+    match services::tool_syscall::dispatch_tool_frame(state, board_id, services::ai::ToolAccess::Full, req).await {
         Ok(outcome) => {
             let trace_id = req
                 .trace
@@ -1328,7 +1859,16 @@ async fn handle_tool(
                 .and_then(|trace| trace.get("trace_id"))
                 .and_then(serde_json::Value::as_str)
                 .and_then(|id| id.parse::<Uuid>().ok());
-            broadcast_ai_mutations(state, board_id, user_id, Some(req.id), trace_id, &outcome.mutations).await;
+            broadcast_ai_mutations(
+                state,
+                board_id,
+                user_id,
+                Some(req.id),
+                trace_id,
+                &outcome.mutations,
+                &outcome.before,
+            )
+            .await;
             Ok(Outcome::Reply(outcome.done_data))
         }
         Err(err) => Err(req.error_from(&err)),
@@ -1351,6 +1891,24 @@ fn handle_trace(trace_enabled: &mut bool, req: &Frame) -> Result<Outcome, Frame>
     }
 }
 
+/// Handle a `cancel` frame aimed at a running request.
+///
+/// The cancelled request sends its own terminal frame, so a successful cancel
+/// produces no reply here.
+async fn handle_cancel(state: &AppState, client_id: Uuid, req: &Frame) -> Vec<Frame> {
+    let Some(target_id) = req.parent_id else {
+        let err = req.error("parent_id required");
+        services::persistence::enqueue_frame(state, &err);
+        return vec![err];
+    };
+    if services::ai::cancel_prompt(state, client_id, target_id).await {
+        return Vec::new();
+    }
+    let err = req.error("no running request to cancel");
+    services::persistence::enqueue_frame(state, &err);
+    vec![err]
+}
+
 async fn handle_ai(
     state: &AppState,
     current_board: Option<Uuid>,
@@ -1367,12 +1925,17 @@ async fn handle_ai(
     let Some(user_id) = req.from.as_deref().and_then(|s| s.parse::<Uuid>().ok()) else {
         return Err(req.error("missing authenticated user id"));
     };
-    if !services::board::client_has_permission(state, board_id, client_id, services::board::BoardPermission::Edit).await
-    {
+    let op = req.syscall.split_once(':').map_or("", |(_, op)| op);
+    // Asking is read-only, so viewers may do it; everything else edits the board.
+    let required = if op == "ask" {
+        services::board::BoardPermission::View
+    } else {
+        services::board::BoardPermission::Edit
+    };
+    if !services::board::client_has_permission(state, board_id, client_id, required).await {
         return Err(req.error("forbidden"));
     }
 
-    let op = req.syscall.split_once(':').map_or("", |(_, op)| op);
     match op {
         "prompt" => {
             let prompt_started_at = Instant::now();
@@ -1391,6 +1954,7 @@ async fn handle_ai(
             if prompt.is_empty() {
                 return Err(req.error("prompt required"));
             }
+            let thread_id = parse_thread_id(req);
 
             match services::ai::handle_prompt_with_parent(
                 state,
@@ -1401,17 +1965,37 @@ async fn handle_ai(
                 prompt,
                 grid_context.as_deref(),
                 Some(req.id),
+                thread_id,
             )
             .await
             {
                 Ok(result) => {
-                    broadcast_ai_mutations(state, board_id, user_id, Some(req.id), Some(req.id), &result.mutations)
-                        .await;
+                    broadcast_ai_mutations(
+                        state,
+                        board_id,
+                        user_id,
+                        Some(req.id),
+                        Some(req.id),
+                        &result.mutations,
+                        &result.before,
+                    )
+                    .await;
+
+                    if result.cancelled {
+                        let mut data = Data::new();
+                        data.insert("prompt".into(), serde_json::json!(prompt));
+                        data.insert("turn_over".into(), serde_json::json!(true));
+                        data.insert("mutations".into(), serde_json::json!(ai_mutations_to_data(&result.mutations)));
+                        return Ok(Outcome::ReplyStreamCancelled { items: result.items, data });
+                    }
 
                     let mut done = Data::new();
                     done.insert("prompt".into(), serde_json::json!(prompt));
                     done.insert("turn_over".into(), serde_json::json!(true));
                     done.insert("mutations".into(), serde_json::json!(result.mutations.len()));
+                    if let Some(thread_id) = thread_id {
+                        done.insert("thread_id".into(), serde_json::json!(thread_id));
+                    }
                     done.insert(
                         "trace".into(),
                         serde_json::json!({
@@ -1424,7 +2008,8 @@ async fn handle_ai(
                             "total_duration_ms": result.trace.total_duration_ms,
                             "total_llm_duration_ms": result.trace.total_llm_duration_ms,
                             "total_tool_duration_ms": result.trace.total_tool_duration_ms,
-                            "overhead_duration_ms": result.trace.overhead_duration_ms
+                            "overhead_duration_ms": result.trace.overhead_duration_ms,
+                            "tool_validation_failures": result.trace.tool_validation_failures
                         }),
                     );
                     Ok(Outcome::ReplyStream { items: result.items, done })
@@ -1444,11 +2029,198 @@ async fn handle_ai(
                 }
             }
         }
-        "history" => ai_history(state, board_id, user_id, req).await,
+        "preview" => ai_preview(state, llm, board_id, client_id, user_id, req).await,
+        "ask" => ai_ask(state, llm, board_id, client_id, user_id, req).await,
+        "apply" => {
+            let Some(preview_id) = parse_preview_id(req) else {
+                return Err(req.error("preview_id required"));
+            };
+            let changes = services::ai::apply_preview(state, board_id, client_id, user_id, preview_id)
+                .await
+                .map_err(|e| req.error_from(&e))?;
+            let mut data = batch_changes_to_data(&changes);
+            data.insert("preview_id".into(), serde_json::json!(preview_id));
+            Ok(Outcome::Broadcast(data))
+        }
+        "discard" => {
+            let Some(preview_id) = parse_preview_id(req) else {
+                return Err(req.error("preview_id required"));
+            };
+            services::ai::discard_preview(state, board_id, client_id, preview_id)
+                .await
+                .map_err(|e| req.error_from(&e))?;
+            let mut data = Data::new();
+            data.insert("preview_id".into(), serde_json::json!(preview_id));
+            Ok(Outcome::Reply(data))
+        }
+        "revert" => {
+            let Some(trace_id) = req
+                .data
+                .get("trace_id")
+                .and_then(|v| v.as_str())
+                .and_then(|s| s.parse::<Uuid>().ok())
+            else {
+                return Err(req.error("trace_id required"));
+            };
+            let result = services::revert::revert_prompt(state, board_id, user_id, trace_id)
+                .await
+                .map_err(|e| req.error_from(&e))?;
+            let conflicts = result
+                .conflicts
+                .iter()
+                .map(|(id, reason)| serde_json::json!({ "id": id, "reason": reason.as_str() }))
+                .collect::<Vec<_>>();
+            let mut data = batch_changes_to_data(&result.changes);
+            data.insert("trace_id".into(), serde_json::json!(trace_id));
+            data.insert("conflicts".into(), serde_json::json!(conflicts));
+            if let Some(mismatch) = result.mismatch {
+                data.insert(
+                    "mismatch".into(),
+                    serde_json::json!({ "expected": mismatch.expected, "recorded": mismatch.recorded }),
+                );
+            }
+            Ok(Outcome::Broadcast(data))
+        }
+        "thread:list" => {
+            let threads = services::ai_thread::list_threads(&state.pool, board_id)
+                .await
+                .map_err(|e| req.error_from(&e))?;
+            let mut data = Data::new();
+            data.insert("threads".into(), serde_json::json!(threads));
+            Ok(Outcome::Reply(data))
+        }
+        "thread:create" => {
+            let title = req.data.get("title").and_then(|v| v.as_str());
+            let thread = services::ai_thread::create_thread(&state.pool, board_id, user_id, title)
+                .await
+                .map_err(|e| req.error_from(&e))?;
+            let mut data = Data::new();
+            data.insert("thread".into(), serde_json::json!(thread));
+            Ok(Outcome::Broadcast(data))
+        }
+        "history" => match parse_thread_id(req) {
+            Some(thread_id) => ai_thread_history(state, board_id, thread_id, req).await,
+            None => ai_history(state, board_id, user_id, req).await,
+        },
         _ => Err(req.error(format!("unknown ai op: {op}"))),
     }
 }
 
+/// Run an `ai:preview` request and reply with its proposed changes.
+///
+/// The done frame carries `preview_id` (the request ID) and `ops` in the
+/// `object:batch` reply format. Nothing is broadcast until `ai:apply`.
+async fn ai_preview(
+    state: &AppState,
+    llm: &Arc<dyn LlmChat>,
+    board_id: Uuid,
+    client_id: Uuid,
+    user_id: Uuid,
+    req: &Frame,
+) -> Result<Outcome, Frame> {
+    let prompt = req
+        .data
+        .get("prompt")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    if prompt.is_empty() {
+        return Err(req.error("prompt required"));
+    }
+    let grid_context = req.data.get("grid_context").and_then(|v| v.as_str());
+
+    let preview = services::ai::handle_preview(state, llm, board_id, client_id, user_id, prompt, grid_context, req.id)
+        .await
+        .map_err(|e| {
+            let mut err = req.error_from(&e);
+            err.data.insert("prompt".into(), serde_json::json!(prompt));
+            err
+        })?;
+
+    let mut data = batch_changes_to_data(&preview.changes);
+    data.insert("prompt".into(), serde_json::json!(prompt));
+    data.insert("turn_over".into(), serde_json::json!(true));
+    if preview.prompt.cancelled {
+        return Ok(Outcome::ReplyStreamCancelled { items: preview.prompt.items, data });
+    }
+    data.insert("preview_id".into(), serde_json::json!(req.id));
+    Ok(Outcome::ReplyStream { items: preview.prompt.items, done: data })
+}
+
+/// Run an `ai:ask` request and stream back the answer.
+///
+/// Only read-only tools run, so there are never mutations to broadcast.
+async fn ai_ask(
+    state: &AppState,
+    llm: &Arc<dyn LlmChat>,
+    board_id: Uuid,
+    client_id: Uuid,
+    user_id: Uuid,
+    req: &Frame,
+) -> Result<Outcome, Frame> {
+    let prompt = req
+        .data
+        .get("prompt")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    if prompt.is_empty() {
+        return Err(req.error("prompt required"));
+    }
+    let grid_context = req.data.get("grid_context").and_then(|v| v.as_str());
+
+    let result = services::ai::handle_ask(state, llm, board_id, client_id, user_id, prompt, grid_context, req.id)
+        .await
+        .map_err(|e| {
+            let mut err = req.error_from(&e);
+            err.data.insert("prompt".into(), serde_json::json!(prompt));
+            err
+        })?;
+
+    let mut data = Data::new();
+    data.insert("prompt".into(), serde_json::json!(prompt));
+    data.insert("turn_over".into(), serde_json::json!(true));
+    if result.cancelled {
+        return Ok(Outcome::ReplyStreamCancelled { items: result.items, data });
+    }
+    Ok(Outcome::ReplyStream { items: result.items, done: data })
+}
+
+fn parse_preview_id(req: &Frame) -> Option<Uuid> {
+    req.data
+        .get("preview_id")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse::<Uuid>().ok())
+}
+
+fn parse_thread_id(req: &Frame) -> Option<Uuid> {
+    req.data
+        .get("thread_id")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse::<Uuid>().ok())
+}
+
+/// Reply with a thread's full message log in the `ai:history` message format.
+async fn ai_thread_history(state: &AppState, board_id: Uuid, thread_id: Uuid, req: &Frame) -> Result<Outcome, Frame> {
+    let rows = services::ai_thread::thread_messages(&state.pool, board_id, thread_id)
+        .await
+        .map_err(|e| req.error_from(&e))?;
+    let messages = rows
+        .into_iter()
+        .map(|row| {
+            serde_json::json!({
+                "seq": row.seq,
+                "ts": row.ts,
+                "role": row.role,
+                "text": row.content,
+                "user_id": row.user_id,
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut data = Data::new();
+    data.insert("thread_id".into(), serde_json::json!(thread_id));
+    data.insert("messages".into(), serde_json::json!(messages));
+    Ok(Outcome::Reply(data))
+}
+
 async fn ai_history(state: &AppState, board_id: Uuid, user_id: Uuid, req: &Frame) -> Result<Outcome, Frame> {
     let rows = match sqlx::query_as::<
         _,
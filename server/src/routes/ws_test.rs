@@ -35,6 +35,8 @@ impl LlmChat for MockLlm {
                 stop_reason: "end_turn".into(),
                 input_tokens: 0,
                 output_tokens: 0,
+                cache_read_tokens: 0,
+                cache_creation_tokens: 0,
             })
         } else {
             Ok(responses.remove(0))
@@ -130,7 +132,7 @@ async fn process_inbound_bytes(
         .unwrap_or("#8a8178");
     let mut trace_enabled = false;
 
-    super::process_inbound_bytes(
+    process_traced_bytes(
         state,
         current_board,
         &mut trace_enabled,
@@ -144,6 +146,46 @@ async fn process_inbound_bytes(
     .await
 }
 
+/// Decode `bytes` and process the frame the way the socket loop does.
+#[allow(clippy::too_many_arguments)]
+async fn process_traced_bytes(
+    state: &AppState,
+    current_board: &mut Option<Uuid>,
+    trace_enabled: &mut bool,
+    client_id: Uuid,
+    user_id: Uuid,
+    user_name: &str,
+    user_color: &str,
+    client_tx: &mpsc::Sender<Frame>,
+    bytes: &[u8],
+) -> Vec<Frame> {
+    match super::decode_inbound(client_id, bytes) {
+        Ok(req) => {
+            super::process_inbound_frame(
+                state,
+                current_board,
+                trace_enabled,
+                client_id,
+                user_id,
+                user_name,
+                user_color,
+                client_tx,
+                req,
+            )
+            .await
+        }
+        Err(message) => vec![super::gateway_error(state, message)],
+    }
+}
+
+#[test]
+fn is_ai_run_request_matches_only_ai_run_requests() {
+    let prompt = Frame::request("ai:prompt", Data::new());
+    assert!(super::is_ai_run_request(&prompt));
+    assert!(!super::is_ai_run_request(&prompt.done()));
+    assert!(!super::is_ai_run_request(&Frame::request("ai:revert", Data::new())));
+}
+
 #[test]
 fn frame_for_client_strips_trace_when_disabled() {
     let mut frame = Frame::request("ai:prompt", Data::new());
@@ -170,7 +212,7 @@ async fn trace_config_toggles_per_connection_trace_flag() {
     enable_data.insert("enabled".into(), json!(true));
     let enable_bytes = request_bytes(Uuid::new_v4(), "trace:config", enable_data);
 
-    let enable_reply = super::process_inbound_bytes(
+    let enable_reply = process_traced_bytes(
         &state,
         &mut current_board,
         &mut trace_enabled,
@@ -199,7 +241,7 @@ async fn trace_config_toggles_per_connection_trace_flag() {
     disable_data.insert("enabled".into(), json!(false));
     let disable_bytes = request_bytes(Uuid::new_v4(), "trace:config", disable_data);
 
-    let disable_reply = super::process_inbound_bytes(
+    let disable_reply = process_traced_bytes(
         &state,
         &mut current_board,
         &mut trace_enabled,
@@ -249,6 +291,27 @@ async fn integration_pool() -> sqlx::PgPool {
     pool
 }
 
+/// Connect one editing client for `user_id` to a board already in memory.
+async fn register_client(state: &AppState, board_id: Uuid, user_id: Uuid, tx: &mpsc::Sender<Frame>) -> Uuid {
+    let client_id = Uuid::new_v4();
+    let mut boards = state.boards.write().await;
+    let board = boards
+        .get_mut(&board_id)
+        .expect("board should exist in memory");
+    board.clients.insert(client_id, tx.clone());
+    board.users.insert(
+        client_id,
+        crate::state::ConnectedClient {
+            user_id,
+            user_name: "solo".to_owned(),
+            user_color: "#aaa".to_owned(),
+            can_edit: true,
+            can_admin: true,
+        },
+    );
+    client_id
+}
+
 async fn register_two_clients(
     state: &AppState,
     board_id: Uuid,
@@ -346,6 +409,32 @@ async fn board_unknown_op_returns_error() {
     );
 }
 
+#[tokio::test]
+async fn board_savepoint_restore_requires_savepoint_id() {
+    let state = test_helpers::test_app_state();
+    let (client_tx, _client_rx) = mpsc::channel(8);
+    let mut current_board = None;
+
+    let mut data = Data::new();
+    data.insert("savepoint_id".into(), json!("not-a-uuid"));
+    let text = request_bytes(Uuid::new_v4(), "board:savepoint:restore", data);
+
+    let reply =
+        process_inbound_bytes(&state, &mut current_board, Uuid::new_v4(), Uuid::new_v4(), &client_tx, &text).await;
+
+    assert_eq!(reply.len(), 1);
+    assert_eq!(reply[0].syscall, "board:savepoint:restore");
+    assert_eq!(reply[0].status, Status::Error);
+    assert!(
+        reply[0]
+            .data
+            .get("message")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .contains("savepoint_id required")
+    );
+}
+
 #[tokio::test]
 async fn chat_message_requires_joined_board() {
     let state = test_helpers::test_app_state();
@@ -1151,6 +1240,217 @@ async fn multi_user_stale_update_is_rejected_and_not_broadcast() {
     assert_eq!(obj_after.version, 3);
 }
 
+#[tokio::test]
+async fn object_batch_broadcasts_single_combined_frame() {
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let (client_a_id, client_a_tx, _client_a_rx, _client_b_id, _client_b_tx, mut client_b_rx) =
+        register_two_clients(&state, board_id).await;
+    let mut current_board = Some(board_id);
+
+    let mut data = Data::new();
+    data.insert(
+        "ops".into(),
+        json!([
+            { "op": "update", "id": obj_id, "version": 1, "x": 640.0 },
+            { "op": "create", "kind": "rectangle", "x": 10.0, "y": 20.0 },
+        ]),
+    );
+    let req = request_bytes(board_id, "object:batch", data);
+    let reply =
+        process_inbound_bytes(&state, &mut current_board, client_a_id, Uuid::new_v4(), &client_a_tx, &req).await;
+
+    assert_eq!(reply.len(), 1);
+    assert_eq!(reply[0].status, Status::Done);
+    let ops = reply[0]
+        .data
+        .get("ops")
+        .and_then(serde_json::Value::as_array)
+        .expect("ops array");
+    assert_eq!(ops.len(), 2);
+    assert_eq!(ops[0].get("op").and_then(|v| v.as_str()), Some("update"));
+    assert_eq!(ops[0].get("version").and_then(serde_json::Value::as_i64), Some(2));
+    assert_eq!(ops[1].get("op").and_then(|v| v.as_str()), Some("create"));
+
+    let peer_seen = recv_board_broadcast(&mut client_b_rx).await;
+    assert_eq!(peer_seen.syscall, "object:batch");
+    assert_eq!(peer_seen.data.get("ops"), reply[0].data.get("ops"));
+    assert_no_board_broadcast(&mut client_b_rx).await;
+
+    let boards = state.boards.read().await;
+    let board = boards.get(&board_id).expect("board should exist");
+    assert_eq!(board.objects.len(), 2);
+    let moved = board.objects.get(&obj_id).expect("object should exist");
+    assert!((moved.x - 640.0).abs() < f64::EPSILON);
+}
+
+#[tokio::test]
+async fn object_batch_stale_op_rejects_batch_without_broadcast() {
+    let mut obj = test_helpers::dummy_object();
+    obj.version = 4;
+    let obj_id = obj.id;
+
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let (client_a_id, client_a_tx, _client_a_rx, _client_b_id, _client_b_tx, mut client_b_rx) =
+        register_two_clients(&state, board_id).await;
+    let mut current_board = Some(board_id);
+
+    let mut data = Data::new();
+    data.insert(
+        "ops".into(),
+        json!([
+            { "op": "create", "kind": "rectangle" },
+            { "op": "update", "id": obj_id, "version": 1, "x": 640.0 },
+        ]),
+    );
+    let req = request_bytes(board_id, "object:batch", data);
+    let reply =
+        process_inbound_bytes(&state, &mut current_board, client_a_id, Uuid::new_v4(), &client_a_tx, &req).await;
+
+    assert_eq!(reply.len(), 1);
+    assert_eq!(reply[0].status, Status::Error);
+    assert_eq!(reply[0].data.get("code").and_then(|v| v.as_str()), Some("E_STALE_UPDATE"));
+    assert_no_board_broadcast(&mut client_b_rx).await;
+
+    let boards = state.boards.read().await;
+    let board = boards.get(&board_id).expect("board should exist");
+    assert_eq!(board.objects.len(), 1);
+    assert_eq!(
+        board
+            .objects
+            .get(&obj_id)
+            .expect("object should exist")
+            .version,
+        4
+    );
+}
+
+#[tokio::test]
+async fn object_batch_reports_index_of_malformed_op() {
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let (client_a_id, client_a_tx, _client_a_rx, _client_b_id, _client_b_tx, _client_b_rx) =
+        register_two_clients(&state, board_id).await;
+    let mut current_board = Some(board_id);
+
+    let mut data = Data::new();
+    data.insert("ops".into(), json!([{ "op": "create" }, { "op": "delete" }]));
+    let req = request_bytes(board_id, "object:batch", data);
+    let reply =
+        process_inbound_bytes(&state, &mut current_board, client_a_id, Uuid::new_v4(), &client_a_tx, &req).await;
+
+    assert_eq!(reply.len(), 1);
+    assert_eq!(reply[0].status, Status::Error);
+    assert_eq!(
+        reply[0].data.get("message").and_then(|v| v.as_str()),
+        Some("ops[1]: id required")
+    );
+}
+
+#[tokio::test]
+async fn text_op_broadcasts_transformed_op_and_text() {
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let (client_a_id, client_a_tx, _client_a_rx, _client_b_id, _client_b_tx, mut client_b_rx) =
+        register_two_clients(&state, board_id).await;
+    let mut current_board = Some(board_id);
+
+    let mut data = Data::new();
+    data.insert("id".into(), json!(obj_id));
+    data.insert("field".into(), json!("text"));
+    let req = request_bytes(board_id, "text:state", data);
+    let reply =
+        process_inbound_bytes(&state, &mut current_board, client_a_id, Uuid::new_v4(), &client_a_tx, &req).await;
+    assert_eq!(reply[0].status, Status::Done);
+    assert_eq!(
+        reply[0]
+            .data
+            .get("text")
+            .and_then(serde_json::Value::as_str),
+        Some("test")
+    );
+    let base_rev = reply[0]
+        .data
+        .get("rev")
+        .and_then(serde_json::Value::as_u64)
+        .expect("rev");
+
+    let mut data = Data::new();
+    data.insert("id".into(), json!(obj_id));
+    data.insert("field".into(), json!("text"));
+    data.insert("rev".into(), json!(base_rev));
+    data.insert("op".into(), json!([4, "ing"]));
+    let req = request_bytes(board_id, "text:op", data);
+    let reply =
+        process_inbound_bytes(&state, &mut current_board, client_a_id, Uuid::new_v4(), &client_a_tx, &req).await;
+
+    assert_eq!(reply.len(), 1);
+    assert_eq!(reply[0].status, Status::Done);
+    assert_eq!(
+        reply[0]
+            .data
+            .get("text")
+            .and_then(serde_json::Value::as_str),
+        Some("testing")
+    );
+    assert_eq!(reply[0].data.get("rev").and_then(serde_json::Value::as_u64), Some(base_rev + 1));
+    assert_eq!(
+        reply[0]
+            .data
+            .get("version")
+            .and_then(serde_json::Value::as_i64),
+        Some(2)
+    );
+
+    let peer_seen = recv_board_broadcast(&mut client_b_rx).await;
+    assert_eq!(peer_seen.syscall, "text:op");
+    assert_eq!(peer_seen.data.get("op"), Some(&json!([4, "ing"])));
+
+    let boards = state.boards.read().await;
+    let board = boards.get(&board_id).expect("board should exist");
+    let obj = board.objects.get(&obj_id).expect("object should exist");
+    assert_eq!(obj.props.get("text").and_then(serde_json::Value::as_str), Some("testing"));
+}
+
+#[tokio::test]
+async fn text_op_rejects_unsupported_field_without_broadcast() {
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let (client_a_id, client_a_tx, _client_a_rx, _client_b_id, _client_b_tx, mut client_b_rx) =
+        register_two_clients(&state, board_id).await;
+    let mut current_board = Some(board_id);
+
+    let mut data = Data::new();
+    data.insert("id".into(), json!(obj_id));
+    data.insert("field".into(), json!("color"));
+    data.insert("rev".into(), json!(0));
+    data.insert("op".into(), json!([7, "!"]));
+    let req = request_bytes(board_id, "text:op", data);
+    let reply =
+        process_inbound_bytes(&state, &mut current_board, client_a_id, Uuid::new_v4(), &client_a_tx, &req).await;
+
+    assert_eq!(reply.len(), 1);
+    assert_eq!(reply[0].status, Status::Error);
+    assert_eq!(
+        reply[0]
+            .data
+            .get("code")
+            .and_then(serde_json::Value::as_str),
+        Some("E_INVALID_TEXT_FIELD")
+    );
+    assert_no_board_broadcast(&mut client_b_rx).await;
+}
+
 #[tokio::test]
 async fn ai_prompt_create_sticky_broadcasts_mutation_and_replies_with_text() {
     let llm: Arc<dyn LlmChat> = Arc::new(MockLlm::new(vec![
@@ -1164,6 +1464,8 @@ async fn ai_prompt_create_sticky_broadcasts_mutation_and_replies_with_text() {
             stop_reason: "tool_use".into(),
             input_tokens: 25,
             output_tokens: 30,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
         ChatResponse {
             content: vec![ContentBlock::Text { text: "Created a sticky.".into() }],
@@ -1171,6 +1473,8 @@ async fn ai_prompt_create_sticky_broadcasts_mutation_and_replies_with_text() {
             stop_reason: "end_turn".into(),
             input_tokens: 10,
             output_tokens: 6,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
     ]));
     let state = test_helpers::test_app_state_with_llm(llm);
@@ -1237,6 +1541,7 @@ async fn ai_prompt_create_sticky_broadcasts_mutation_and_replies_with_text() {
             .and_then(serde_json::Value::as_i64)
             .is_some()
     );
+    assert_eq!(trace.get("tool_validation_failures"), Some(&serde_json::json!(0)));
     let assistant_item = sender_frames
         .iter()
         .find(|f| f.status == Status::Item && f.data.get("role").and_then(|v| v.as_str()) == Some("assistant"))
@@ -1279,6 +1584,115 @@ async fn ai_prompt_create_sticky_broadcasts_mutation_and_replies_with_text() {
     assert_eq!(created.props.get("text").and_then(|v| v.as_str()), Some("hello from ai"));
 }
 
+/// Returns one tool call, then never answers again.
+struct StallingLlm {
+    calls: Mutex<usize>,
+}
+
+#[async_trait::async_trait]
+impl LlmChat for StallingLlm {
+    async fn chat(
+        &self,
+        _max_tokens: u32,
+        _system: &str,
+        _messages: &[Message],
+        _tools: Option<&[Tool]>,
+    ) -> Result<ChatResponse, LlmError> {
+        let first = {
+            let mut calls = self.calls.lock().expect("mock mutex should lock");
+            *calls += 1;
+            *calls == 1
+        };
+        if !first {
+            std::future::pending::<()>().await;
+        }
+        Ok(ChatResponse {
+            content: vec![ContentBlock::ToolUse {
+                id: "tool_1".into(),
+                name: "createStickyNote".into(),
+                input: json!({ "text": "partial", "x": 10, "y": 10 }),
+            }],
+            model: "mock".into(),
+            stop_reason: "tool_use".into(),
+            input_tokens: 5,
+            output_tokens: 5,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
+        })
+    }
+}
+
+#[tokio::test]
+async fn ai_prompt_cancel_stops_prompt_and_lists_applied_mutations() {
+    let state = test_helpers::test_app_state_with_llm(Arc::new(StallingLlm { calls: Mutex::new(0) }));
+    let board_id = test_helpers::seed_board(&state).await;
+    let (sender_client_id, sender_tx, _sender_rx, _peer_client_id, _peer_tx, _peer_rx) =
+        register_two_clients(&state, board_id).await;
+    let user_id = Uuid::new_v4();
+
+    let mut data = Data::new();
+    data.insert("prompt".into(), json!("make stickies forever"));
+    let prompt = Frame::request("ai:prompt", data).with_board_id(board_id);
+    let prompt_id = prompt.id;
+    let prompt_bytes = frames::encode_frame(&frames::Frame::from(&prompt));
+
+    let running = tokio::spawn({
+        let state = state.clone();
+        let sender_tx = sender_tx.clone();
+        async move {
+            let mut current_board = Some(board_id);
+            process_inbound_bytes(&state, &mut current_board, sender_client_id, user_id, &sender_tx, &prompt_bytes)
+                .await
+        }
+    });
+
+    // Wait for the first tool call to land so the prompt is stalled on its second LLM call.
+    timeout(Duration::from_secs(2), async {
+        while state.boards.read().await[&board_id].objects.is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("first tool call should apply");
+
+    let mut current_board = Some(board_id);
+    let mut data = Data::new();
+    data.insert("prompt".into(), json!("and another"));
+    let second_bytes = request_bytes(board_id, "ai:prompt", data);
+    let second =
+        process_inbound_bytes(&state, &mut current_board, sender_client_id, user_id, &sender_tx, &second_bytes).await;
+    let rejected = second.last().expect("second prompt reply");
+    assert_eq!(rejected.status, Status::Error);
+    assert_eq!(rejected.data["code"], "E_AI_BUSY");
+    assert_eq!(state.ai_prompt_cancels.read().await.len(), 1);
+
+    let cancel_bytes = frames::encode_frame(&frames::Frame::from(&Frame::cancel(prompt_id)));
+    let cancel_reply =
+        process_inbound_bytes(&state, &mut current_board, sender_client_id, user_id, &sender_tx, &cancel_bytes).await;
+    assert!(cancel_reply.is_empty(), "the prompt sends the terminal frame");
+
+    let prompt_frames = timeout(Duration::from_secs(2), running)
+        .await
+        .expect("cancelled prompt should finish")
+        .expect("prompt task should not panic");
+    let terminal = prompt_frames.last().expect("terminal frame");
+    assert_eq!(terminal.status, Status::Cancel);
+    assert_eq!(terminal.parent_id, Some(prompt_id));
+    let mutations = terminal
+        .data
+        .get("mutations")
+        .and_then(serde_json::Value::as_array)
+        .expect("applied mutations");
+    assert_eq!(mutations.len(), 1);
+    assert_eq!(mutations[0]["op"], "create");
+    assert!(state.ai_prompt_cancels.read().await.is_empty());
+
+    let late_cancel =
+        process_inbound_bytes(&state, &mut current_board, sender_client_id, user_id, &sender_tx, &cancel_bytes).await;
+    assert_eq!(late_cancel.len(), 1);
+    assert_eq!(late_cancel[0].status, Status::Error);
+}
+
 #[tokio::test]
 async fn ai_prompt_resize_sticky_broadcasts_update_and_replies_with_text() {
     let mut sticky = test_helpers::dummy_object();
@@ -1299,6 +1713,8 @@ async fn ai_prompt_resize_sticky_broadcasts_update_and_replies_with_text() {
             stop_reason: "tool_use".into(),
             input_tokens: 20,
             output_tokens: 28,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
         ChatResponse {
             content: vec![ContentBlock::Text { text: "Resized sticky 4.".into() }],
@@ -1306,6 +1722,8 @@ async fn ai_prompt_resize_sticky_broadcasts_update_and_replies_with_text() {
             stop_reason: "end_turn".into(),
             input_tokens: 8,
             output_tokens: 5,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
     ]));
     let state = test_helpers::test_app_state_with_llm(llm);
@@ -1395,6 +1813,8 @@ async fn ai_prompt_multi_tool_single_turn_broadcasts_all_mutations_and_replies_w
             stop_reason: "tool_use".into(),
             input_tokens: 30,
             output_tokens: 40,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
         ChatResponse {
             content: vec![ContentBlock::Text { text: "Added two stickies.".into() }],
@@ -1402,6 +1822,8 @@ async fn ai_prompt_multi_tool_single_turn_broadcasts_all_mutations_and_replies_w
             stop_reason: "end_turn".into(),
             input_tokens: 10,
             output_tokens: 8,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
     ]));
     let state = test_helpers::test_app_state_with_llm(llm);
@@ -1497,6 +1919,8 @@ async fn ai_prompt_sequence_multi_tool_text_then_multi_tool_text() {
             stop_reason: "tool_use".into(),
             input_tokens: 28,
             output_tokens: 32,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
         ChatResponse {
             content: vec![ContentBlock::Text { text: "First batch complete.".into() }],
@@ -1504,6 +1928,8 @@ async fn ai_prompt_sequence_multi_tool_text_then_multi_tool_text() {
             stop_reason: "end_turn".into(),
             input_tokens: 9,
             output_tokens: 7,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
         ChatResponse {
             content: vec![
@@ -1522,6 +1948,8 @@ async fn ai_prompt_sequence_multi_tool_text_then_multi_tool_text() {
             stop_reason: "tool_use".into(),
             input_tokens: 30,
             output_tokens: 34,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
         ChatResponse {
             content: vec![ContentBlock::Text { text: "Second batch complete.".into() }],
@@ -1529,6 +1957,8 @@ async fn ai_prompt_sequence_multi_tool_text_then_multi_tool_text() {
             stop_reason: "end_turn".into(),
             input_tokens: 9,
             output_tokens: 7,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
     ]));
     let state = test_helpers::test_app_state_with_llm(llm);
@@ -1639,3 +2069,212 @@ async fn ai_prompt_sequence_multi_tool_text_then_multi_tool_text() {
     let board = boards.get(&board_id).expect("board should exist");
     assert_eq!(board.objects.len(), 4);
 }
+
+#[tokio::test]
+async fn history_undo_survives_last_client_leaving_and_rejoining() {
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let (client_tx, _client_rx) = mpsc::channel(64);
+    let user_id = Uuid::new_v4();
+    let client_id = register_client(&state, board_id, user_id, &client_tx).await;
+    let mut current_board = Some(board_id);
+
+    let mut data = Data::new();
+    data.insert("id".into(), json!(obj_id));
+    data.insert("version".into(), json!(1));
+    data.insert("x".into(), json!(640.0));
+    let req = request_bytes(board_id, "object:update", data);
+    process_inbound_bytes(&state, &mut current_board, client_id, user_id, &client_tx, &req).await;
+
+    // Stand in for the persistence flush so the last part evicts the board.
+    let flushed = {
+        let mut boards = state.boards.write().await;
+        let board = boards.get_mut(&board_id).expect("board should exist");
+        board.dirty.clear();
+        board.objects.clone()
+    };
+    services::board::part_board(&state, board_id, client_id).await;
+    assert!(!state.boards.read().await.contains_key(&board_id));
+
+    // Rejoin on a fresh connection; the board rehydrates from what was flushed.
+    let mut board_state = crate::state::BoardState::new();
+    board_state.objects = flushed;
+    state.boards.write().await.insert(board_id, board_state);
+    let rejoined_id = register_client(&state, board_id, user_id, &client_tx).await;
+
+    let req = request_bytes(board_id, "history:undo", Data::new());
+    let reply = process_inbound_bytes(&state, &mut current_board, rejoined_id, user_id, &client_tx, &req).await;
+
+    assert_eq!(reply.len(), 1);
+    assert_eq!(reply[0].status, Status::Done);
+    let boards = state.boards.read().await;
+    let restored = &boards[&board_id].objects[&obj_id];
+    assert!((restored.x - 100.0).abs() < f64::EPSILON);
+}
+
+#[tokio::test]
+async fn history_undo_reverts_senders_update_and_broadcasts_ops() {
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let (client_a_id, client_a_tx, _client_a_rx, _client_b_id, _client_b_tx, mut client_b_rx) =
+        register_two_clients(&state, board_id).await;
+    let mut current_board = Some(board_id);
+    let user_id = Uuid::new_v4();
+
+    let mut data = Data::new();
+    data.insert("id".into(), json!(obj_id));
+    data.insert("version".into(), json!(1));
+    data.insert("x".into(), json!(640.0));
+    let req = request_bytes(board_id, "object:update", data);
+    process_inbound_bytes(&state, &mut current_board, client_a_id, user_id, &client_a_tx, &req).await;
+    let _ = recv_board_broadcast(&mut client_b_rx).await;
+
+    let req = request_bytes(board_id, "history:undo", Data::new());
+    let reply = process_inbound_bytes(&state, &mut current_board, client_a_id, user_id, &client_a_tx, &req).await;
+
+    assert_eq!(reply.len(), 1);
+    assert_eq!(reply[0].status, Status::Done);
+    let ops = reply[0]
+        .data
+        .get("ops")
+        .and_then(serde_json::Value::as_array)
+        .expect("ops array");
+    assert_eq!(ops.len(), 1);
+    assert_eq!(ops[0].get("op").and_then(serde_json::Value::as_str), Some("update"));
+    assert_eq!(ops[0].get("x").and_then(serde_json::Value::as_f64), Some(100.0));
+
+    let peer_seen = recv_board_broadcast(&mut client_b_rx).await;
+    assert_eq!(peer_seen.syscall, "history:undo");
+
+    let req = request_bytes(board_id, "history:undo", Data::new());
+    let reply = process_inbound_bytes(&state, &mut current_board, client_a_id, user_id, &client_a_tx, &req).await;
+    assert_eq!(reply[0].status, Status::Error);
+    assert_eq!(
+        reply[0]
+            .data
+            .get("code")
+            .and_then(serde_json::Value::as_str),
+        Some("E_NOTHING_TO_UNDO")
+    );
+}
+
+#[tokio::test]
+async fn ai_preview_replies_without_broadcast_then_apply_broadcasts_ops() {
+    let llm: Arc<dyn LlmChat> = Arc::new(MockLlm::new(vec![ChatResponse {
+        content: vec![ContentBlock::ToolUse {
+            id: "tool_1".into(),
+            name: "createStickyNote".into(),
+            input: json!({ "text": "proposed", "x": 220, "y": 180 }),
+        }],
+        model: "mock".into(),
+        stop_reason: "tool_use".into(),
+        input_tokens: 0,
+        output_tokens: 0,
+        cache_read_tokens: 0,
+        cache_creation_tokens: 0,
+    }]));
+    let state = test_helpers::test_app_state_with_llm(llm);
+    let board_id = test_helpers::seed_board(&state).await;
+    let (sender_client_id, sender_tx, _sender_rx, _peer_client_id, _peer_tx, mut peer_rx) =
+        register_two_clients(&state, board_id).await;
+    let user_id = Uuid::new_v4();
+    let mut current_board = Some(board_id);
+
+    let mut data = Data::new();
+    data.insert("prompt".into(), json!("add a sticky"));
+    let preview_frames = process_inbound_bytes(
+        &state,
+        &mut current_board,
+        sender_client_id,
+        user_id,
+        &sender_tx,
+        &request_bytes(board_id, "ai:preview", data),
+    )
+    .await;
+    let done = preview_frames
+        .iter()
+        .find(|f| f.status == Status::Done)
+        .expect("preview done frame");
+    assert_eq!(done.syscall, "ai:preview");
+    let preview_id = done.data["preview_id"]
+        .as_str()
+        .expect("preview_id")
+        .to_owned();
+    let ops = done.data["ops"].as_array().expect("ops");
+    assert_eq!(ops.len(), 1);
+    assert_eq!(ops[0]["op"], "create");
+    assert_eq!(ops[0]["props"]["text"], "proposed");
+    assert_no_board_broadcast(&mut peer_rx).await;
+    assert!(state.boards.read().await[&board_id].objects.is_empty());
+
+    let mut data = Data::new();
+    data.insert("preview_id".into(), json!(preview_id));
+    let apply_frames = process_inbound_bytes(
+        &state,
+        &mut current_board,
+        sender_client_id,
+        user_id,
+        &sender_tx,
+        &request_bytes(board_id, "ai:apply", data),
+    )
+    .await;
+    assert_eq!(apply_frames.len(), 1);
+    assert_eq!(apply_frames[0].status, Status::Done);
+    assert_eq!(apply_frames[0].data["ops"][0]["id"], ops[0]["id"]);
+
+    let peer = recv_board_broadcast(&mut peer_rx).await;
+    assert_eq!(peer.syscall, "ai:apply");
+    assert_eq!(peer.data["ops"][0]["op"], "create");
+    // Reconnect deltas read the same `ops` from the persisted frame.
+    assert!(services::board::OPS_FRAME_SYSCALLS.contains(&peer.syscall.as_str()));
+    assert_eq!(state.boards.read().await[&board_id].objects.len(), 1);
+}
+
+#[tokio::test]
+async fn ai_ask_replies_without_mutating_or_broadcasting() {
+    let llm: Arc<dyn LlmChat> = Arc::new(MockLlm::new(vec![ChatResponse {
+        content: vec![ContentBlock::ToolUse {
+            id: "tool_1".into(),
+            name: "createStickyNote".into(),
+            input: json!({ "text": "sneaky", "x": 220, "y": 180 }),
+        }],
+        model: "mock".into(),
+        stop_reason: "tool_use".into(),
+        input_tokens: 0,
+        output_tokens: 0,
+        cache_read_tokens: 0,
+        cache_creation_tokens: 0,
+    }]));
+    let state = test_helpers::test_app_state_with_llm(llm);
+    let board_id = test_helpers::seed_board(&state).await;
+    let (sender_client_id, sender_tx, _sender_rx, _peer_client_id, _peer_tx, mut peer_rx) =
+        register_two_clients(&state, board_id).await;
+    let mut current_board = Some(board_id);
+
+    let mut data = Data::new();
+    data.insert("prompt".into(), json!("what decisions were made?"));
+    let frames = process_inbound_bytes(
+        &state,
+        &mut current_board,
+        sender_client_id,
+        Uuid::new_v4(),
+        &sender_tx,
+        &request_bytes(board_id, "ai:ask", data),
+    )
+    .await;
+
+    let done = frames
+        .iter()
+        .find(|f| f.status == Status::Done)
+        .expect("ask done frame");
+    assert_eq!(done.syscall, "ai:ask");
+    assert!(!done.data.contains_key("mutations"));
+    assert_no_board_broadcast(&mut peer_rx).await;
+    assert!(state.boards.read().await[&board_id].objects.is_empty());
+}
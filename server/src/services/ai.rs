@@ -6,24 +6,42 @@
 //! the LLM with `CollabBoard` tools, executes returned tool calls as object
 //! mutations, and broadcasts results to board peers.
 //!
+//! `ai:preview` runs the same loop against a scratch copy of the board and
+//! keeps the net changes aside; `ai:apply` later commits them in one step
+//! (refusing if any touched object changed meanwhile) and `ai:discard` drops
+//! them.
+//!
+//! `ai:ask` answers questions for viewers. It offers the model only the
+//! read-only tools, and the executor itself refuses any mutating tool in that
+//! mode, so a viewer's prompt cannot change the board whatever the model asks.
+//!
+//! Tool inputs are validated against the tool's declared schema before
+//! dispatch. Violations go back to the model as `tool_result` errors so it can
+//! correct the call, and are counted in the trace summary.
+//!
 //! Tool names match the G4 Week 1 spec exactly (issue #19):
 //! createStickyNote, createShape, createFrame, createConnector,
 //! createSvgObject, updateSvgContent, importSvg, exportSelectionToSvg, deleteObject,
-//! moveObject, resizeObject, updateText, changeColor, swot, createAnimationClip, getBoardState.
+//! moveObject, resizeObject, updateText, changeColor, swot, createAnimationClip, getBoardState,
+//! searchBoard, summarizeFrame, exportMermaid.
 
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
 use std::sync::{Arc, OnceLock};
 use std::time::Instant;
 
 use serde_json::json;
+use tokio::sync::{RwLock, mpsc, watch};
 use tracing::{info, warn};
 use uuid::Uuid;
 
-use crate::frame::{Data, Frame};
+use crate::frame::{Data, FRAME_CODE, Frame, Status};
 use crate::llm::LlmChat;
-use crate::llm::tools::gauntlet_week_1_tools;
-use crate::llm::types::{Content, ContentBlock, Message};
-use crate::state::{AppState, BoardObject, ClientViewport};
+use crate::llm::tools::{gauntlet_week_1_tools, is_read_only_tool, read_only_tools, validate_tool_input};
+use crate::llm::types::{ChatDelta, Content, ContentBlock, LlmAttempt, Message, SYSTEM_CONTEXT_HEADER};
+use crate::services::object::BatchChange;
+use crate::services::savepoint::same_object_state;
+use crate::state::{AppState, BoardObject, BoardState, ClientViewport};
 
 const DEFAULT_AI_MAX_TOOL_ITERATIONS: usize = 10;
 const DEFAULT_AI_MAX_TOKENS: u32 = 4096;
@@ -32,6 +50,10 @@ const MAX_SESSION_CONVERSATION_MESSAGES: usize = 3;
 const MAX_SESSION_MESSAGE_CHARS: usize = 600;
 const MAX_SESSION_TOTAL_CHARS: usize = 3_000;
 const MAX_SVG_BYTES: usize = 200_000;
+const THREAD_SUMMARY_HEADER: &str = "\n\nEarlier conversation summary:\n";
+const READ_ONLY_MODE_PROMPT: &str = "\n\nRead-only mode: the user can view but not edit this board. Answer their \
+     question from the board's contents using getBoardState, searchBoard, summarizeFrame, and exportMermaid. Do \
+     not offer to create, move, or change anything.\n";
 const BASE_SYSTEM_PROMPT: &str = include_str!("../llm/system.md");
 
 fn env_parse<T>(key: &str, default: T) -> T
@@ -130,6 +152,20 @@ pub enum AiError {
     RateLimited(String),
     #[error("invalid tool syscall: {0}")]
     InvalidToolSyscall(String),
+    #[error("preview not found: {0}")]
+    PreviewNotFound(Uuid),
+    #[error("preview is out of date: object {0} changed since it was generated")]
+    PreviewConflict(Uuid),
+    #[error("thread error: {0}")]
+    Thread(#[from] super::ai_thread::ThreadError),
+    #[error("{0}")]
+    Usage(#[from] super::usage::UsageError),
+    #[error("tool {0} is not available in read-only mode")]
+    ToolNotPermitted(String),
+    #[error("invalid input for {tool}: {details}")]
+    InvalidToolInput { tool: String, details: String },
+    #[error("another AI request is still running for this client")]
+    Busy,
 }
 
 impl crate::frame::ErrorCode for AiError {
@@ -141,6 +177,13 @@ impl crate::frame::ErrorCode for AiError {
             Self::ObjectError(_) => "E_OBJECT_ERROR",
             Self::RateLimited(_) => "E_RATE_LIMITED",
             Self::InvalidToolSyscall(_) => "E_INVALID_TOOL_SYSCALL",
+            Self::PreviewNotFound(_) => "E_PREVIEW_NOT_FOUND",
+            Self::PreviewConflict(_) => "E_PREVIEW_CONFLICT",
+            Self::Thread(e) => e.error_code(),
+            Self::Usage(e) => e.error_code(),
+            Self::ToolNotPermitted(_) => "E_TOOL_NOT_PERMITTED",
+            Self::InvalidToolInput { .. } => "E_INVALID_TOOL_INPUT",
+            Self::Busy => "E_AI_BUSY",
         }
     }
 
@@ -159,9 +202,55 @@ impl From<crate::rate_limit::RateLimitError> for AiError {
 #[derive(Debug)]
 pub struct AiResult {
     pub mutations: Vec<AiMutation>,
+    /// State at prompt start of each pre-existing object the prompt touched.
+    pub before: HashMap<Uuid, BoardObject>,
     pub text: Option<String>,
     pub items: Vec<Data>,
     pub trace: AiTraceSummary,
+    /// The prompt was cancelled before the LLM finished its turn.
+    pub cancelled: bool,
+}
+
+/// Cancel handle for one running prompt, keyed by its request frame ID.
+#[derive(Debug)]
+pub struct PromptCancel {
+    client_id: Uuid,
+    signal: watch::Sender<bool>,
+}
+
+/// Which tools a prompt may run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolAccess {
+    /// Every board tool.
+    Full,
+    /// Only tools that cannot change the board.
+    ReadOnly,
+}
+
+/// The kind of AI request driving the tool loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PromptMode {
+    Prompt,
+    Preview,
+    Ask,
+}
+
+impl PromptMode {
+    /// Syscall used for streamed text deltas.
+    fn syscall(self) -> &'static str {
+        match self {
+            Self::Prompt => "ai:prompt",
+            Self::Preview => "ai:preview",
+            Self::Ask => "ai:ask",
+        }
+    }
+
+    fn tool_access(self) -> ToolAccess {
+        match self {
+            Self::Prompt | Self::Preview => ToolAccess::Full,
+            Self::Ask => ToolAccess::ReadOnly,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -171,6 +260,35 @@ pub enum AiMutation {
     Deleted(Uuid),
 }
 
+impl AiMutation {
+    /// ID of the object this mutation touched.
+    #[must_use]
+    pub fn object_id(&self) -> Uuid {
+        match self {
+            Self::Created(obj) | Self::Updated(obj) => obj.id,
+            Self::Deleted(id) => *id,
+        }
+    }
+}
+
+/// Net board changes proposed by an `ai:preview`, held until applied or discarded.
+#[derive(Debug)]
+pub struct AiPreview {
+    board_id: Uuid,
+    client_id: Uuid,
+    /// State of each updated or deleted object when the preview ran.
+    base: HashMap<Uuid, BoardObject>,
+    /// One change per touched object, in the order the AI first touched it.
+    changes: Vec<BatchChange>,
+}
+
+/// Result of an AI preview: the prompt run plus its net proposed changes.
+#[derive(Debug)]
+pub struct AiPreviewResult {
+    pub prompt: AiResult,
+    pub changes: Vec<BatchChange>,
+}
+
 #[derive(Debug, Clone, Copy)]
 #[allow(clippy::struct_field_names)]
 pub struct AiTraceSummary {
@@ -178,6 +296,8 @@ pub struct AiTraceSummary {
     pub total_llm_duration_ms: i64,
     pub total_tool_duration_ms: i64,
     pub overhead_duration_ms: i64,
+    /// Tool calls rejected because their input failed schema validation.
+    pub tool_validation_failures: usize,
 }
 
 // =============================================================================
@@ -193,9 +313,19 @@ pub async fn handle_prompt(
     prompt: &str,
     grid_context: Option<&str>,
 ) -> Result<AiResult, AiError> {
-    handle_prompt_with_parent(state, llm, board_id, client_id, user_id, prompt, grid_context, None).await
+    handle_prompt_with_parent(state, llm, board_id, client_id, user_id, prompt, grid_context, None, None).await
 }
 
+/// Run a prompt on behalf of the `ai:prompt` request `parent_frame_id`.
+///
+/// While it runs, [`cancel_prompt`] with the same request ID stops it before
+/// the next LLM round trip or tool call; the result then has `cancelled` set
+/// and lists only the mutations applied so far.
+///
+/// With a `thread_id`, conversation context comes from that persisted board
+/// thread instead of the websocket session, and the completed turn is
+/// appended to it.
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_prompt_with_parent(
     state: &AppState,
     llm: &Arc<dyn LlmChat>,
@@ -205,6 +335,149 @@ pub async fn handle_prompt_with_parent(
     prompt: &str,
     grid_context: Option<&str>,
     parent_frame_id: Option<Uuid>,
+    thread_id: Option<Uuid>,
+) -> Result<AiResult, AiError> {
+    run_cancellable(
+        state,
+        llm,
+        board_id,
+        client_id,
+        user_id,
+        prompt,
+        grid_context,
+        parent_frame_id,
+        thread_id,
+        PromptMode::Prompt,
+    )
+    .await
+}
+
+/// Answer a question about the board without changing it.
+///
+/// Runs the tool loop with only the read-only tools, for users with view
+/// access. It is cancellable under `request_id` like any prompt.
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_ask(
+    state: &AppState,
+    llm: &Arc<dyn LlmChat>,
+    board_id: Uuid,
+    client_id: Uuid,
+    user_id: Uuid,
+    prompt: &str,
+    grid_context: Option<&str>,
+    request_id: Uuid,
+) -> Result<AiResult, AiError> {
+    run_cancellable(
+        state,
+        llm,
+        board_id,
+        client_id,
+        user_id,
+        prompt,
+        grid_context,
+        Some(request_id),
+        None,
+        PromptMode::Ask,
+    )
+    .await
+}
+
+/// Run a prompt while registered for cancellation under `parent_frame_id`.
+///
+/// Fails with `Busy` while the same client already has a run in progress.
+///
+/// Text deltas are streamed to the client as items of the mode's syscall.
+#[allow(clippy::too_many_arguments)]
+async fn run_cancellable(
+    state: &AppState,
+    llm: &Arc<dyn LlmChat>,
+    board_id: Uuid,
+    client_id: Uuid,
+    user_id: Uuid,
+    prompt: &str,
+    grid_context: Option<&str>,
+    parent_frame_id: Option<Uuid>,
+    thread_id: Option<Uuid>,
+    mode: PromptMode,
+) -> Result<AiResult, AiError> {
+    let (signal, mut cancel) = watch::channel(false);
+    {
+        // One tool loop per client: runs are spawned off the socket loop, so
+        // without this a client could start any number against the board.
+        let mut running = state.ai_prompt_cancels.write().await;
+        if running.values().any(|entry| entry.client_id == client_id) {
+            return Err(AiError::Busy);
+        }
+        if let Some(request_id) = parent_frame_id {
+            running.insert(request_id, PromptCancel { client_id, signal });
+        }
+    }
+    let result = run_prompt(
+        state,
+        llm,
+        board_id,
+        client_id,
+        user_id,
+        prompt,
+        grid_context,
+        parent_frame_id,
+        thread_id,
+        mode,
+        &mut cancel,
+    )
+    .await;
+    if let Some(request_id) = parent_frame_id {
+        state.ai_prompt_cancels.write().await.remove(&request_id);
+    }
+    result
+}
+
+/// Signal the running prompt started by `request_id` to stop.
+///
+/// Only the client that sent the prompt may cancel it. Returns `false` when
+/// no such prompt is running.
+pub async fn cancel_prompt(state: &AppState, client_id: Uuid, request_id: Uuid) -> bool {
+    let running = state.ai_prompt_cancels.read().await;
+    match running.get(&request_id) {
+        Some(entry) if entry.client_id == client_id => {
+            entry.signal.send_replace(true);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Cancel every prompt a client still has running, e.g. when it disconnects.
+pub async fn cancel_client_prompts(state: &AppState, client_id: Uuid) {
+    let running = state.ai_prompt_cancels.read().await;
+    for entry in running
+        .values()
+        .filter(|entry| entry.client_id == client_id)
+    {
+        entry.signal.send_replace(true);
+    }
+}
+
+/// Resolve once the prompt is cancelled; never resolves otherwise.
+async fn cancelled(cancel: &mut watch::Receiver<bool>) {
+    if cancel.wait_for(|flag| *flag).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_prompt(
+    state: &AppState,
+    llm: &Arc<dyn LlmChat>,
+    board_id: Uuid,
+    client_id: Uuid,
+    user_id: Uuid,
+    prompt: &str,
+    grid_context: Option<&str>,
+    parent_frame_id: Option<Uuid>,
+    thread_id: Option<Uuid>,
+    mode: PromptMode,
+    cancel: &mut watch::Receiver<bool>,
 ) -> Result<AiResult, AiError> {
     info!(%board_id, %client_id, prompt_len = prompt.len(), "ai: prompt received");
     let root_started_at = Instant::now();
@@ -226,18 +499,32 @@ pub async fn handle_prompt_with_parent(
         )
     };
 
-    let system = build_system_prompt(&board_snapshot, grid_context, viewport_snapshot.as_ref());
-    let tools = gauntlet_week_1_tools();
+    let mut system = build_system_prompt(&board_snapshot, grid_context, viewport_snapshot.as_ref());
+    let tools = match mode.tool_access() {
+        ToolAccess::Full => gauntlet_week_1_tools(),
+        ToolAccess::ReadOnly => {
+            system.push_str(READ_ONLY_MODE_PROMPT);
+            read_only_tools()
+        }
+    };
     let session_key = (client_id, board_id);
-    let session_memory_enabled = ai_enable_session_memory();
-    let prior_session_messages = if session_memory_enabled {
+    let session_memory_enabled = thread_id.is_none() && ai_enable_session_memory();
+    let prior_session_messages = if let Some(thread_id) = thread_id {
+        let context = super::ai_thread::load_context(&state.pool, board_id, thread_id).await?;
+        if !context.summary.is_empty() {
+            // Appended after the context header so the cached prompt prefix is unchanged.
+            system.push_str(THREAD_SUMMARY_HEADER);
+            system.push_str(&context.summary);
+        }
+        context.messages
+    } else if session_memory_enabled {
         load_session_messages(state, session_key).await
     } else {
         Vec::new()
     };
 
-    // Keep persisted context scoped to the active websocket session.
-    // Refreshing reconnects and clears this memory.
+    // Without a thread, context is scoped to the active websocket session;
+    // refreshing reconnects and clears this memory.
     let prompt_message =
         Message { role: "user".into(), content: Content::Text(format!("<user_input>{prompt}</user_input>")) };
     let mut base_messages = prior_session_messages;
@@ -249,10 +536,32 @@ pub async fn handle_prompt_with_parent(
     let mut final_text: Option<String> = None;
     let mut total_llm_duration_ms: i64 = 0;
     let mut total_tool_duration_ms: i64 = 0;
+    let mut tool_validation_failures: usize = 0;
     let token_reservation = u64::from(max_tokens);
     let trace_id = trace_id_for_prompt(parent_frame_id);
+    let mut was_cancelled = false;
+    // Text deltas go straight to the requesting socket; they are not persisted.
+    let delta_client = match parent_frame_id {
+        Some(_) => state.ws_clients.read().await.get(&client_id).cloned(),
+        None => None,
+    };
 
-    for iteration in 0..max_tool_iterations {
+    'iterations: for iteration in 0..max_tool_iterations {
+        if *cancel.borrow() {
+            was_cancelled = true;
+            break;
+        }
+        // Durable per-user/per-board budgets gate the in-memory reservation below.
+        if let Err(err) = super::usage::check_budgets(state, user_id, board_id).await {
+            let item = budget_stop_item(iteration, err)?;
+            warn!(%board_id, iteration, "ai: budget exhausted mid-prompt; stopping early");
+            final_text = item
+                .get("content")
+                .and_then(|v| v.as_str())
+                .map(str::to_owned);
+            stream_items.push(item);
+            break;
+        }
         let mut llm_req = Frame::request("ai:llm_request", Data::new())
             .with_board_id(board_id)
             .with_from(user_id.to_string());
@@ -282,10 +591,50 @@ pub async fn handle_prompt_with_parent(
         state
             .rate_limiter
             .reserve_token_budget(client_id, token_reservation)?;
-        let response = match llm
-            .chat(max_tokens, &system, &llm_messages, Some(&tools))
-            .await
-        {
+        let (delta_tx, mut delta_rx) = mpsc::unbounded_channel();
+        let chat = llm.chat_stream(max_tokens, &system, &llm_messages, Some(&tools), &delta_tx);
+        tokio::pin!(chat);
+        let outcome = loop {
+            tokio::select! {
+                outcome = &mut chat => break Some(outcome),
+                Some(delta) = delta_rx.recv() => match delta {
+                    ChatDelta::Text(text) => {
+                        forward_delta(delta_client.as_ref(), mode.syscall(), board_id, parent_frame_id, iteration, text).await;
+                    }
+                    ChatDelta::Attempt(attempt) => {
+                        record_attempt_span(state, &llm_req, trace_id, root_started_at, iteration, &attempt);
+                    }
+                },
+                () = cancelled(cancel) => break None,
+            }
+        };
+        while let Ok(delta) = delta_rx.try_recv() {
+            match delta {
+                ChatDelta::Text(text) => {
+                    forward_delta(
+                        delta_client.as_ref(),
+                        mode.syscall(),
+                        board_id,
+                        parent_frame_id,
+                        iteration,
+                        text,
+                    )
+                    .await;
+                }
+                ChatDelta::Attempt(attempt) => {
+                    record_attempt_span(state, &llm_req, trace_id, root_started_at, iteration, &attempt);
+                }
+            }
+        }
+        let Some(outcome) = outcome else {
+            super::persistence::enqueue_frame(state, &llm_req.cancelled_with(Data::new()));
+            state
+                .rate_limiter
+                .release_reserved_tokens(client_id, token_reservation);
+            was_cancelled = true;
+            break;
+        };
+        let response = match outcome {
             Ok(response) => response,
             Err(err) => {
                 let duration_ms = elapsed_ms(llm_started_at);
@@ -313,7 +662,7 @@ pub async fn handle_prompt_with_parent(
         };
         let duration_ms = elapsed_ms(llm_started_at);
         total_llm_duration_ms = total_llm_duration_ms.saturating_add(duration_ms);
-        let total_tokens = response.input_tokens + response.output_tokens;
+        let total_tokens = response.total_tokens();
 
         let llm_done_data = Data::new();
         let mut llm_trace = trace_meta_with_timing(
@@ -331,7 +680,12 @@ pub async fn handle_prompt_with_parent(
         llm_trace.insert("iteration".into(), json!(iteration));
         llm_trace.insert("input_tokens".into(), json!(response.input_tokens));
         llm_trace.insert("output_tokens".into(), json!(response.output_tokens));
+        llm_trace.insert("cache_read_tokens".into(), json!(response.cache_read_tokens));
+        llm_trace.insert("cache_creation_tokens".into(), json!(response.cache_creation_tokens));
         llm_trace.insert("tokens".into(), json!(total_tokens));
+        if let Some(cost_usd) = response.cost_usd() {
+            llm_trace.insert("cost_usd".into(), json!(cost_usd));
+        }
         llm_trace.insert("stop_reason".into(), json!(response.stop_reason.clone()));
         let mut llm_done = llm_req.done_with(llm_done_data);
         llm_done.trace = Some(serde_json::Value::Object(llm_trace));
@@ -342,13 +696,19 @@ pub async fn handle_prompt_with_parent(
             stop_reason = %response.stop_reason,
             input_tokens = response.input_tokens,
             output_tokens = response.output_tokens,
+            cache_read_tokens = response.cache_read_tokens,
+            cache_creation_tokens = response.cache_creation_tokens,
             "ai: LLM response"
         );
 
         // Record token usage for budget tracking.
         state
             .rate_limiter
-            .record_tokens(client_id, total_tokens, token_reservation);
+            .record_tokens(client_id, response.billable_tokens(), token_reservation);
+        super::usage::record_usage(
+            state,
+            super::usage::UsageRecord::from_response(user_id, board_id, Some(trace_id), &response),
+        );
 
         // Collect text blocks.
         let text_parts: Vec<&str> = response
@@ -387,6 +747,10 @@ pub async fn handle_prompt_with_parent(
         // Execute each tool call and collect results.
         let mut tool_results = Vec::new();
         for (tool_id, tool_name, input) in &tool_calls {
+            if *cancel.borrow() {
+                was_cancelled = true;
+                break 'iterations;
+            }
             let mut start_item = Data::new();
             start_item.insert("role".into(), json!("tool"));
             start_item.insert("kind".into(), json!("tool_call"));
@@ -400,6 +764,7 @@ pub async fn handle_prompt_with_parent(
                 state,
                 board_id,
                 user_id,
+                mode.tool_access(),
                 iteration,
                 tool_id,
                 tool_name,
@@ -418,6 +783,9 @@ pub async fn handle_prompt_with_parent(
                 }
                 Err(e) => {
                     warn!(iteration, tool = %tool_name, error = %e, "ai: tool error");
+                    if matches!(e, AiError::InvalidToolInput { .. }) {
+                        tool_validation_failures += 1;
+                    }
                     (e.to_string(), Some(true))
                 }
             };
@@ -461,7 +829,7 @@ pub async fn handle_prompt_with_parent(
     // Guarantee the client always receives a response payload by synthesizing
     // fallback text when the LLM returned none (e.g. thinking-only or
     // mutations-only responses).
-    if final_text.is_none() {
+    if final_text.is_none() && !was_cancelled {
         let synthesized = if all_mutations.is_empty() {
             "Done.".into()
         } else {
@@ -482,7 +850,16 @@ pub async fn handle_prompt_with_parent(
         "ai: prompt complete"
     );
 
-    if session_memory_enabled && let Some(text) = final_text.clone() {
+    if was_cancelled {
+        info!(%board_id, mutations = all_mutations.len(), "ai: prompt cancelled");
+    } else if let (Some(thread_id), Some(text)) = (thread_id, final_text.as_deref()) {
+        append_thread_turn(state, llm, thread_id, user_id, board_id, prompt, text).await;
+    } else if session_memory_enabled
+        && mode != PromptMode::Preview
+        && let Some(text) = final_text.clone()
+    {
+        // A preview may still be discarded, so it reads the conversation but
+        // never joins it.
         append_session_messages(state, session_key, prompt_message, text).await;
     }
 
@@ -490,23 +867,132 @@ pub async fn handle_prompt_with_parent(
     let overhead_duration_ms = total_duration_ms
         .saturating_sub(total_llm_duration_ms)
         .saturating_sub(total_tool_duration_ms);
+    let touched = all_mutations
+        .iter()
+        .map(AiMutation::object_id)
+        .collect::<HashSet<_>>();
+    let before = board_snapshot
+        .into_iter()
+        .filter(|obj| touched.contains(&obj.id))
+        .map(|obj| (obj.id, obj))
+        .collect();
     Ok(AiResult {
         mutations: all_mutations,
+        before,
         text: final_text,
         items: stream_items,
+        cancelled: was_cancelled,
         trace: AiTraceSummary {
             total_duration_ms,
             total_llm_duration_ms,
             total_tool_duration_ms,
             overhead_duration_ms,
+            tool_validation_failures,
         },
     })
 }
 
+/// Turn a failed budget check at the top of a tool round into the item that
+/// ends the prompt early.
+///
+/// Earlier rounds' mutations are already applied, so running out of budget
+/// after the first LLM call stops the loop with a partial result, like a
+/// cancellation. Before any call, and for database errors, the prompt fails.
+fn budget_stop_item(iteration: usize, err: super::usage::UsageError) -> Result<Data, AiError> {
+    if iteration == 0 || !matches!(err, super::usage::UsageError::BudgetExceeded { .. }) {
+        return Err(err.into());
+    }
+    let mut item = Data::new();
+    item.insert("role".into(), json!("assistant"));
+    item.insert("kind".into(), json!("budget_exceeded"));
+    item.insert("content".into(), json!(format!("Stopped early: {err}.")));
+    Ok(item)
+}
+
+/// Send one streamed text delta to the client as a `syscall` item.
+///
+/// Clients append `assistant_delta` items to a provisional message that the
+/// complete `assistant_text` item replaces when the prompt finishes.
+async fn forward_delta(
+    client: Option<&mpsc::Sender<Frame>>,
+    syscall: &str,
+    board_id: Uuid,
+    parent_frame_id: Option<Uuid>,
+    iteration: usize,
+    text: String,
+) {
+    let (Some(client), Some(parent_id)) = (client, parent_frame_id) else {
+        return;
+    };
+    let mut data = Data::new();
+    data.insert("role".into(), json!("assistant"));
+    data.insert("kind".into(), json!("assistant_delta"));
+    data.insert("iteration".into(), json!(iteration));
+    data.insert("content".into(), json!(text));
+    let mut frame = Frame::request(syscall, data).with_board_id(board_id);
+    frame.parent_id = Some(parent_id);
+    frame.status = Status::Item;
+    let _ = client.send(frame).await;
+}
+
+/// Persist one provider attempt as an `ai:llm_attempt` span under `llm_req`.
+///
+/// Attempts are reported once they finish, so the request frame is
+/// backdated by the attempt's duration.
+fn record_attempt_span(
+    state: &AppState,
+    llm_req: &Frame,
+    trace_id: Uuid,
+    root_started_at: Instant,
+    iteration: usize,
+    attempt: &LlmAttempt,
+) {
+    let duration_ms = i64::try_from(attempt.duration_ms).unwrap_or(i64::MAX);
+    let mut req = Frame::request("ai:llm_attempt", Data::new());
+    req.parent_id = Some(llm_req.id);
+    req.board_id = llm_req.board_id;
+    req.from.clone_from(&llm_req.from);
+    req.ts = req.ts.saturating_sub(duration_ms);
+
+    let mut trace = trace_meta_with_timing(
+        trace_id,
+        req.id,
+        Some(llm_req.id),
+        "ai.llm_attempt",
+        Some(&attempt.model),
+        root_started_at,
+        None,
+    )
+    .as_object()
+    .cloned()
+    .unwrap_or_default();
+    trace.insert("iteration".into(), json!(iteration));
+    trace.insert("provider".into(), json!(attempt.provider));
+    trace.insert("attempt".into(), json!(attempt.attempt));
+    req.trace = Some(serde_json::Value::Object(trace.clone()));
+    super::persistence::enqueue_frame(state, &req);
+
+    trace.insert("duration_ms".into(), json!(duration_ms));
+    if let Some(retry_in_ms) = attempt.retry_in_ms {
+        trace.insert("retry_in_ms".into(), json!(retry_in_ms));
+    }
+    let mut done = match &attempt.error {
+        Some((code, message)) => {
+            let mut err = req.error(message.clone());
+            err.data.insert(FRAME_CODE.into(), json!(code));
+            err
+        }
+        None => req.done_with(Data::new()),
+    };
+    done.trace = Some(serde_json::Value::Object(trace));
+    super::persistence::enqueue_frame(state, &done);
+}
+
 async fn execute_tool_via_syscall(
     state: &AppState,
     board_id: Uuid,
     user_id: Uuid,
+    access: ToolAccess,
     iteration: usize,
     tool_use_id: &str,
     tool_name: &str,
@@ -543,7 +1029,7 @@ async fn execute_tool_via_syscall(
     req.trace = Some(serde_json::Value::Object(req_trace));
     super::persistence::enqueue_frame(state, &req);
 
-    match super::tool_syscall::dispatch_tool_frame(state, board_id, &req).await {
+    match super::tool_syscall::dispatch_tool_frame(state, board_id, access, &req).await {
         Ok(outcome) => {
             let duration_ms = elapsed_ms(tool_started_at);
             *total_tool_duration_ms = total_tool_duration_ms.saturating_add(duration_ms);
@@ -592,6 +1078,30 @@ async fn execute_tool_via_syscall(
     }
 }
 
+/// Persist a completed turn to its thread, then fold older turns into the
+/// thread summary in the background.
+async fn append_thread_turn(
+    state: &AppState,
+    llm: &Arc<dyn LlmChat>,
+    thread_id: Uuid,
+    user_id: Uuid,
+    board_id: Uuid,
+    prompt: &str,
+    text: &str,
+) {
+    if let Err(e) = super::ai_thread::append_turn(&state.pool, thread_id, user_id, prompt, text).await {
+        warn!(error = %e, %thread_id, "ai: failed to append thread turn");
+        return;
+    }
+    let state = state.clone();
+    let llm = llm.clone();
+    tokio::spawn(async move {
+        if let Err(e) = super::ai_thread::compact_thread(&state, &llm, thread_id, user_id, board_id).await {
+            warn!(error = %e, %thread_id, "ai: thread summary update failed");
+        }
+    });
+}
+
 async fn load_session_messages(state: &AppState, session_key: (Uuid, Uuid)) -> Vec<Message> {
     state
         .ai_session_messages
@@ -661,6 +1171,254 @@ fn session_messages_total_chars(messages: &[Message]) -> usize {
         .sum()
 }
 
+// =============================================================================
+// PREVIEW
+// =============================================================================
+
+/// Run a prompt against a scratch copy of the board without changing it.
+///
+/// The tool loop is the same as for [`handle_prompt_with_parent`], but tools
+/// see a private copy of the board's objects, so nothing is persisted or
+/// broadcast. Unless the run is cancelled, its net changes are kept under
+/// `request_id` for [`apply_preview`] or [`discard_preview`].
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_preview(
+    state: &AppState,
+    llm: &Arc<dyn LlmChat>,
+    board_id: Uuid,
+    client_id: Uuid,
+    user_id: Uuid,
+    prompt: &str,
+    grid_context: Option<&str>,
+    request_id: Uuid,
+) -> Result<AiPreviewResult, AiError> {
+    let mut scratch_board = BoardState::new();
+    {
+        let boards = state.boards.read().await;
+        let board = boards
+            .get(&board_id)
+            .ok_or(AiError::BoardNotLoaded(board_id))?;
+        scratch_board.objects.clone_from(&board.objects);
+        scratch_board
+            .field_versions
+            .clone_from(&board.field_versions);
+        scratch_board.viewports.clone_from(&board.viewports);
+    }
+    scratch_board.scratch = true;
+    let before = scratch_board.objects.clone();
+    // No persistence sender: the run's `ai:llm_request` and `tool:*` frames
+    // describe changes that never happened, so they stay out of the frame log.
+    let scratch = AppState {
+        boards: Arc::new(RwLock::new(HashMap::from([(board_id, scratch_board)]))),
+        frame_persist_tx: None,
+        ..state.clone()
+    };
+
+    let result = run_cancellable(
+        &scratch,
+        llm,
+        board_id,
+        client_id,
+        user_id,
+        prompt,
+        grid_context,
+        Some(request_id),
+        None,
+        PromptMode::Preview,
+    )
+    .await?;
+    let changes = {
+        let boards = scratch.boards.read().await;
+        boards
+            .get(&board_id)
+            .map(|board| net_changes(&before, &board.objects, &result.mutations))
+            .unwrap_or_default()
+    };
+
+    if !result.cancelled {
+        let base = changes
+            .iter()
+            .filter_map(|change| match change {
+                BatchChange::Updated(BoardObject { id, .. }) | BatchChange::Deleted(id) => {
+                    before.get(id).map(|obj| (*id, obj.clone()))
+                }
+                BatchChange::Created(_) => None,
+            })
+            .collect();
+        let preview = AiPreview { board_id, client_id, base, changes: changes.clone() };
+        state.ai_previews.write().await.insert(request_id, preview);
+    }
+    Ok(AiPreviewResult { prompt: result, changes })
+}
+
+/// Collapse the AI's mutations into one change per touched object.
+///
+/// Objects the AI created and then deleted, or edited back to their original
+/// state, drop out entirely.
+pub(crate) fn net_changes(
+    before: &HashMap<Uuid, BoardObject>,
+    after: &HashMap<Uuid, BoardObject>,
+    mutations: &[AiMutation],
+) -> Vec<BatchChange> {
+    let mut seen = HashSet::new();
+    let mut changes = Vec::new();
+    for mutation in mutations {
+        let id = mutation.object_id();
+        if !seen.insert(id) {
+            continue;
+        }
+        match (before.get(&id), after.get(&id)) {
+            (None, Some(obj)) => changes.push(BatchChange::Created(obj.clone())),
+            (Some(old), Some(obj)) if !same_object_state(old, obj) => changes.push(BatchChange::Updated(obj.clone())),
+            (Some(_), None) => changes.push(BatchChange::Deleted(id)),
+            _ => {}
+        }
+    }
+    changes
+}
+
+/// Commit a stored preview to the live board in one step.
+///
+/// Every object the preview updates or deletes must still be in the state
+/// the preview saw; otherwise nothing is applied and the preview is dropped.
+/// Updated objects take a version one past the live version, so in-flight
+/// edits based on the pre-apply state are rejected as stale. The applied
+/// changes are recorded as a single undo entry for `user_id`.
+///
+/// # Errors
+///
+/// Returns `PreviewNotFound` if `client_id` holds no such preview for the
+/// board, `PreviewConflict` if the board moved on, `BoardNotLoaded`, or an
+/// object error if removing deleted rows from Postgres fails.
+pub async fn apply_preview(
+    state: &AppState,
+    board_id: Uuid,
+    client_id: Uuid,
+    user_id: Uuid,
+    preview_id: Uuid,
+) -> Result<Vec<BatchChange>, AiError> {
+    let preview = take_preview(state, board_id, client_id, preview_id).await?;
+
+    let (before, changes) = {
+        let mut boards = state.boards.write().await;
+        let board = boards
+            .get_mut(&board_id)
+            .ok_or(AiError::BoardNotLoaded(board_id))?;
+
+        let mut before = HashMap::with_capacity(preview.base.len());
+        for change in &preview.changes {
+            let (id, unchanged) = match change {
+                BatchChange::Created(obj) => (obj.id, !board.objects.contains_key(&obj.id)),
+                BatchChange::Updated(BoardObject { id, .. }) | BatchChange::Deleted(id) => {
+                    let live = board.objects.get(id);
+                    let unchanged = live
+                        .zip(preview.base.get(id))
+                        .is_some_and(|(live, base)| same_object_state(live, base));
+                    if let Some(live) = live {
+                        before.insert(*id, live.clone());
+                    }
+                    (*id, unchanged)
+                }
+            };
+            if !unchanged {
+                return Err(AiError::PreviewConflict(id));
+            }
+        }
+
+        let mut changes = Vec::with_capacity(preview.changes.len());
+        for change in preview.changes {
+            match change {
+                BatchChange::Created(obj) => {
+                    board.dirty.insert(obj.id);
+                    board.objects.insert(obj.id, obj.clone());
+                    changes.push(BatchChange::Created(obj));
+                }
+                BatchChange::Updated(mut obj) => {
+                    obj.version = before
+                        .get(&obj.id)
+                        .map_or(obj.version, |live| live.version + 1);
+                    // Applied objects are replaced wholesale, so per-field history restarts.
+                    board.field_versions.remove(&obj.id);
+                    board.dirty.insert(obj.id);
+                    board.objects.insert(obj.id, obj.clone());
+                    changes.push(BatchChange::Updated(obj));
+                }
+                BatchChange::Deleted(id) => {
+                    board.objects.remove(&id);
+                    board.dirty.remove(&id);
+                    board.field_versions.remove(&id);
+                    board.text_logs.remove(&id);
+                    changes.push(BatchChange::Deleted(id));
+                }
+            }
+        }
+        (before, changes)
+    };
+
+    let deleted = changes
+        .iter()
+        .filter_map(|change| match change {
+            BatchChange::Deleted(id) => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if !deleted.is_empty() {
+        sqlx::query("DELETE FROM board_objects WHERE board_id = $1 AND id = ANY($2)")
+            .bind(board_id)
+            .bind(&deleted)
+            .execute(&state.pool)
+            .await
+            .map_err(super::object::ObjectError::from)?;
+    }
+
+    let recorded = super::history::changes_from_batch(before, &changes);
+    super::history::record(state, board_id, user_id, recorded).await;
+    Ok(changes)
+}
+
+/// Drop a stored preview without applying it.
+///
+/// # Errors
+///
+/// Returns `PreviewNotFound` if `client_id` holds no such preview for the board.
+pub async fn discard_preview(
+    state: &AppState,
+    board_id: Uuid,
+    client_id: Uuid,
+    preview_id: Uuid,
+) -> Result<(), AiError> {
+    take_preview(state, board_id, client_id, preview_id)
+        .await
+        .map(drop)
+}
+
+/// Drop every preview a client still holds, e.g. when it disconnects.
+pub async fn discard_client_previews(state: &AppState, client_id: Uuid) {
+    state
+        .ai_previews
+        .write()
+        .await
+        .retain(|_, preview| preview.client_id != client_id);
+}
+
+async fn take_preview(
+    state: &AppState,
+    board_id: Uuid,
+    client_id: Uuid,
+    preview_id: Uuid,
+) -> Result<AiPreview, AiError> {
+    let mut previews = state.ai_previews.write().await;
+    let owned = previews
+        .get(&preview_id)
+        .is_some_and(|preview| preview.board_id == board_id && preview.client_id == client_id);
+    if !owned {
+        return Err(AiError::PreviewNotFound(preview_id));
+    }
+    previews
+        .remove(&preview_id)
+        .ok_or(AiError::PreviewNotFound(preview_id))
+}
+
 // =============================================================================
 // SYSTEM PROMPT
 // =============================================================================
@@ -671,7 +1429,7 @@ pub(crate) fn build_system_prompt(
     viewport: Option<&ClientViewport>,
 ) -> String {
     let mut prompt = String::from(BASE_SYSTEM_PROMPT.trim_end());
-    prompt.push_str("\n\nBoard context summary:\n");
+    prompt.push_str(SYSTEM_CONTEXT_HEADER);
     let _ = writeln!(prompt, "- total_objects={}", objects.len());
     if objects.is_empty() {
         prompt.push_str("- board_state=empty\n");
@@ -788,6 +1546,30 @@ pub(crate) async fn execute_tool(
     input: &serde_json::Value,
     mutations: &mut Vec<AiMutation>,
 ) -> Result<String, AiError> {
+    execute_tool_with_access(state, board_id, ToolAccess::Full, tool_name, input, mutations).await
+}
+
+/// Execute a tool, refusing anything but read-only tools under
+/// [`ToolAccess::ReadOnly`] before it can touch the board.
+pub(crate) async fn execute_tool_with_access(
+    state: &AppState,
+    board_id: Uuid,
+    access: ToolAccess,
+    tool_name: &str,
+    input: &serde_json::Value,
+    mutations: &mut Vec<AiMutation>,
+) -> Result<String, AiError> {
+    if access == ToolAccess::ReadOnly && !is_read_only_tool(tool_name) {
+        return Err(AiError::ToolNotPermitted(tool_name.to_owned()));
+    }
+    if let Err(violations) = validate_tool_input(tool_name, input) {
+        let details = violations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(AiError::InvalidToolInput { tool: tool_name.to_owned(), details });
+    }
     match tool_name {
         "createStickyNote" => execute_create_sticky_note(state, board_id, input, mutations).await,
         "createShape" => execute_create_shape(state, board_id, input, mutations).await,
@@ -808,6 +1590,9 @@ pub(crate) async fn execute_tool(
         "createMermaidDiagram" => execute_create_mermaid_diagram(state, board_id, input, mutations).await,
         "createAnimationClip" => execute_create_animation_clip(state, board_id, input, mutations).await,
         "getBoardState" => execute_get_board_state(state, board_id).await,
+        "searchBoard" => execute_search_board(state, board_id, input).await,
+        "summarizeFrame" => execute_summarize_frame(state, board_id, input).await,
+        "exportMermaid" => execute_export_mermaid(state, board_id, input).await,
         _ => Ok(format!("unknown tool: {tool_name}")),
     }
 }
@@ -2067,7 +2852,7 @@ async fn execute_create_mermaid_diagram(
         .unwrap_or(1.0)
         .clamp(0.5, 3.0);
 
-    let diagram = match crate::mermaid::parse(mermaid_text) {
+    let diagram = match crate::mermaid::parse_diagram(mermaid_text) {
         Ok(d) => d,
         Err(e) => return Ok(format!("error: failed to parse mermaid diagram: {e}")),
     };
@@ -2077,9 +2862,15 @@ async fn execute_create_mermaid_diagram(
         return Ok("error: mermaid diagram produced no objects".into());
     }
 
+    // Connectors follow their nodes and frames, so node ids resolve by then.
+    let mut node_objects: HashMap<&str, Uuid> = HashMap::new();
     let mut created_count = 0_usize;
     for desc in &descriptors {
-        let props = desc.props.clone();
+        let mut props = desc.props.clone();
+        if let Some((from, to)) = &desc.endpoints {
+            bind_connector_endpoint(&mut props, "a", node_objects.get(from.as_str()).copied());
+            bind_connector_endpoint(&mut props, "b", node_objects.get(to.as_str()).copied());
+        }
         let w = if desc.width > 0.0 { Some(desc.width) } else { None };
         let h = if desc.height > 0.0 { Some(desc.height) } else { None };
 
@@ -2088,7 +2879,7 @@ async fn execute_create_mermaid_diagram(
         {
             Ok(obj) => {
                 // Ensure dimensions are persisted for shapes that need them.
-                let obj = if (desc.kind == "frame" || desc.kind == "rectangle" || desc.kind == "text")
+                let obj = if matches!(desc.kind.as_str(), "frame" | "rectangle" | "ellipse" | "diamond" | "text")
                     && (desc.width > 0.0 || desc.height > 0.0)
                 {
                     let mut data = Data::new();
@@ -2104,6 +2895,9 @@ async fn execute_create_mermaid_diagram(
                 } else {
                     obj
                 };
+                if let Some(node_id) = &desc.node_id {
+                    node_objects.insert(node_id.as_str(), obj.id);
+                }
                 mutations.push(AiMutation::Created(obj));
                 created_count += 1;
             }
@@ -2113,15 +2907,45 @@ async fn execute_create_mermaid_diagram(
         }
     }
 
-    let participant_count = diagram.participants.len();
-    let message_count = diagram
-        .events
-        .iter()
-        .filter(|e| matches!(e, crate::mermaid::ast::Event::Message(_)))
-        .count();
-    Ok(format!(
-        "created {created_count} objects from Mermaid diagram ({participant_count} participants, {message_count} messages)"
-    ))
+    match &diagram {
+        crate::mermaid::ast::Diagram::Sequence(sequence) => {
+            let participant_count = sequence.participants.len();
+            let message_count = sequence
+                .events
+                .iter()
+                .filter(|e| matches!(e, crate::mermaid::ast::Event::Message(_)))
+                .count();
+            Ok(format!(
+                "created {created_count} objects from Mermaid diagram ({participant_count} participants, {message_count} messages)"
+            ))
+        }
+        crate::mermaid::ast::Diagram::Flowchart(flowchart) => Ok(format!(
+            "created {created_count} objects from Mermaid flowchart ({} nodes, {} edges, {} subgraphs)",
+            flowchart.nodes.len(),
+            flowchart.edges.len(),
+            flowchart.subgraphs.len()
+        )),
+        crate::mermaid::ast::Diagram::State(states) => Ok(format!(
+            "created {created_count} objects from Mermaid state diagram ({} states, {} transitions)",
+            states.states.len(),
+            states.transitions.len()
+        )),
+        crate::mermaid::ast::Diagram::Class(classes) => Ok(format!(
+            "created {created_count} objects from Mermaid class diagram ({} classes, {} relations)",
+            classes.classes.len(),
+            classes.relations.len()
+        )),
+    }
+}
+
+/// Attach connector endpoint `end` (`a` or `b`) to a created object, keeping
+/// the anchor the layout chose. Unresolved endpoints stay free points.
+fn bind_connector_endpoint(props: &mut serde_json::Value, end: &str, object_id: Option<Uuid>) {
+    let (Some(object_id), Some(endpoint)) = (object_id, props.get_mut(end).and_then(|v| v.as_object_mut())) else {
+        return;
+    };
+    endpoint.insert("type".into(), json!("attached"));
+    endpoint.insert("object_id".into(), json!(object_id));
 }
 
 async fn execute_create_animation_clip(
@@ -2543,6 +3367,238 @@ async fn execute_get_board_state(state: &AppState, board_id: Uuid) -> Result<Str
     Ok(json!({ "objects": objects, "count": objects.len() }).to_string())
 }
 
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 50;
+/// Text-bearing props, in display priority order.
+const TEXT_PROPS: &[&str] = &["title", "text", "label"];
+
+fn object_texts(obj: &BoardObject) -> Vec<&str> {
+    TEXT_PROPS
+        .iter()
+        .filter_map(|key| obj.props.get(*key).and_then(serde_json::Value::as_str))
+        .filter(|text| !text.trim().is_empty())
+        .collect()
+}
+
+fn object_contains_point(obj: &BoardObject, x: f64, y: f64) -> bool {
+    let (Some(w), Some(h)) = (obj.width, obj.height) else {
+        return false;
+    };
+    x >= obj.x && x <= obj.x + w && y >= obj.y && y <= obj.y + h
+}
+
+fn object_center_point(obj: &BoardObject) -> (f64, f64) {
+    (obj.x + obj.width.unwrap_or(0.0) / 2.0, obj.y + obj.height.unwrap_or(0.0) / 2.0)
+}
+
+/// The smallest frame containing the center of `obj`, if any.
+fn enclosing_frame<'a>(obj: &BoardObject, frames: &[&'a BoardObject]) -> Option<&'a BoardObject> {
+    let (cx, cy) = object_center_point(obj);
+    frames
+        .iter()
+        .filter(|frame| frame.id != obj.id && object_contains_point(frame, cx, cy))
+        .min_by(|a, b| {
+            let area = |f: &BoardObject| f.width.unwrap_or(0.0) * f.height.unwrap_or(0.0);
+            area(a).total_cmp(&area(b))
+        })
+        .copied()
+}
+
+async fn execute_search_board(state: &AppState, board_id: Uuid, input: &serde_json::Value) -> Result<String, AiError> {
+    let query = input
+        .get("query")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("")
+        .trim()
+        .to_lowercase();
+    if query.is_empty() {
+        return Ok("error: query is required".into());
+    }
+    let kind = input.get("kind").and_then(serde_json::Value::as_str);
+    let limit = input
+        .get("limit")
+        .and_then(serde_json::Value::as_u64)
+        .and_then(|v| usize::try_from(v).ok())
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+
+    let boards = state.boards.read().await;
+    let Some(board) = boards.get(&board_id) else {
+        return Ok("error: board not loaded".into());
+    };
+    let frames = board
+        .objects
+        .values()
+        .filter(|obj| obj.kind == "frame")
+        .collect::<Vec<_>>();
+    let mut matches = board
+        .objects
+        .values()
+        .filter(|obj| kind.is_none_or(|kind| obj.kind == kind))
+        .filter(|obj| {
+            object_texts(obj)
+                .iter()
+                .any(|text| text.to_lowercase().contains(&query))
+        })
+        .collect::<Vec<_>>();
+    matches.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    let total = matches.len();
+    let results = matches
+        .into_iter()
+        .take(limit)
+        .map(|obj| {
+            let frame = enclosing_frame(obj, &frames);
+            json!({
+                "id": obj.id,
+                "kind": obj.kind,
+                "x": obj.x,
+                "y": obj.y,
+                "text": object_texts(obj),
+                "frameId": frame.map(|f| f.id),
+                "frameTitle": frame.and_then(|f| f.props.get("title")).and_then(serde_json::Value::as_str),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "matches": results, "count": total, "truncated": total > limit }).to_string())
+}
+
+async fn execute_summarize_frame(
+    state: &AppState,
+    board_id: Uuid,
+    input: &serde_json::Value,
+) -> Result<String, AiError> {
+    let frame_id = input
+        .get("frameId")
+        .and_then(serde_json::Value::as_str)
+        .and_then(|s| s.parse::<Uuid>().ok());
+    let title = input
+        .get("title")
+        .and_then(serde_json::Value::as_str)
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty());
+    if frame_id.is_none() && title.is_none() {
+        return Ok("error: frameId or title is required".into());
+    }
+
+    let boards = state.boards.read().await;
+    let Some(board) = boards.get(&board_id) else {
+        return Ok("error: board not loaded".into());
+    };
+    let frames = board
+        .objects
+        .values()
+        .filter(|obj| obj.kind == "frame")
+        .collect::<Vec<_>>();
+    let frame = match (frame_id, title.as_deref()) {
+        (Some(id), _) => frames.iter().find(|f| f.id == id).copied(),
+        (None, Some(title)) => frames
+            .iter()
+            .find(|f| {
+                f.props
+                    .get("title")
+                    .and_then(serde_json::Value::as_str)
+                    .is_some_and(|t| t.trim().to_lowercase() == title)
+            })
+            .copied(),
+        (None, None) => None,
+    };
+    let Some(frame) = frame else {
+        return Ok("error: frame not found".into());
+    };
+
+    let mut members = board
+        .objects
+        .values()
+        .filter(|obj| enclosing_frame(obj, &frames).is_some_and(|f| f.id == frame.id))
+        .collect::<Vec<_>>();
+    members.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    let member_ids = members.iter().map(|obj| obj.id).collect::<HashSet<_>>();
+
+    let mut kind_counts = std::collections::BTreeMap::<&str, usize>::new();
+    for obj in &members {
+        *kind_counts.entry(obj.kind.as_str()).or_default() += 1;
+    }
+    let items = members
+        .iter()
+        .filter_map(|obj| {
+            let texts = object_texts(obj);
+            (!texts.is_empty()).then(|| json!({ "id": obj.id, "kind": obj.kind, "text": texts.join(" — ") }))
+        })
+        .collect::<Vec<_>>();
+    let endpoint = |obj: &BoardObject, key: &str| {
+        obj.props
+            .get(key)
+            .and_then(|v| v.get("object_id"))
+            .and_then(serde_json::Value::as_str)
+            .and_then(|s| s.parse::<Uuid>().ok())
+    };
+    let connections = board
+        .objects
+        .values()
+        .filter(|obj| obj.kind == "arrow" || obj.kind == "line")
+        .filter_map(|edge| {
+            let from = endpoint(edge, "a")?;
+            let to = endpoint(edge, "b")?;
+            (member_ids.contains(&from) && member_ids.contains(&to)).then(|| json!({ "from": from, "to": to }))
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "frameId": frame.id,
+        "title": frame.props.get("title"),
+        "kindCounts": kind_counts,
+        "items": items,
+        "connections": connections,
+    })
+    .to_string())
+}
+
+async fn execute_export_mermaid(
+    state: &AppState,
+    board_id: Uuid,
+    input: &serde_json::Value,
+) -> Result<String, AiError> {
+    let frame_id = input
+        .get("frameId")
+        .and_then(serde_json::Value::as_str)
+        .and_then(|s| s.parse::<Uuid>().ok());
+    let object_ids = input
+        .get("objectIds")
+        .and_then(serde_json::Value::as_array)
+        .map(|ids| {
+            ids.iter()
+                .filter_map(serde_json::Value::as_str)
+                .filter_map(|s| s.parse::<Uuid>().ok())
+                .collect::<HashSet<_>>()
+        });
+    let scope = match (frame_id, object_ids) {
+        (Some(_), Some(_)) => return Ok("error: pass frameId or objectIds, not both".into()),
+        (Some(frame_id), None) => crate::mermaid::ExportScope::Frame(frame_id),
+        (None, Some(ids)) => crate::mermaid::ExportScope::Objects(ids),
+        (None, None) => crate::mermaid::ExportScope::Board,
+    };
+
+    let boards = state.boards.read().await;
+    let Some(board) = boards.get(&board_id) else {
+        return Ok("error: board not loaded".into());
+    };
+    let objects = board
+        .objects
+        .values()
+        .map(|obj| crate::mermaid::ExportObject {
+            id: obj.id,
+            kind: &obj.kind,
+            x: obj.x,
+            y: obj.y,
+            width: obj.width,
+            height: obj.height,
+            props: &obj.props,
+        })
+        .collect::<Vec<_>>();
+    Ok(crate::mermaid::export_flowchart(&objects, &scope).unwrap_or_else(|e| format!("error: {e}")))
+}
+
 fn canonical_kind(kind: &str) -> Option<String> {
     match kind.trim().to_ascii_lowercase().as_str() {
         "rectangle" => Some("rectangle".to_owned()),
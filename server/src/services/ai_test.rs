@@ -1,4 +1,5 @@
 use super::*;
+use crate::llm::cassette::CassetteChat;
 use crate::llm::types::{ChatResponse, ContentBlock, LlmChat, LlmError, Message, Tool};
 use crate::state::test_helpers;
 use std::sync::Mutex;
@@ -34,6 +35,8 @@ impl LlmChat for MockLlm {
                 stop_reason: "end_turn".into(),
                 input_tokens: 0,
                 output_tokens: 0,
+                cache_read_tokens: 0,
+                cache_creation_tokens: 0,
             })
         } else {
             Ok(responses.remove(0))
@@ -598,10 +601,13 @@ async fn tool_update_text_rejects_head_field() {
     let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
     let mut mutations = Vec::new();
     let input = json!({ "objectId": obj_id.to_string(), "field": "head", "newText": "New head" });
-    let result = execute_tool(&state, board_id, "updateText", &input, &mut mutations)
+    let err = execute_tool(&state, board_id, "updateText", &input, &mut mutations)
         .await
-        .unwrap();
-    assert_eq!(result, "error: field must be one of text/title");
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"invalid input for updateText: input.field: must be one of "text", "title""#
+    );
     assert!(mutations.is_empty());
 }
 
@@ -729,6 +735,218 @@ async fn tool_get_board_state_with_objects() {
     assert!(mutations.is_empty());
 }
 
+// =========================================================================
+// execute_tool — read-only tools
+// =========================================================================
+
+fn text_object(kind: &str, x: f64, y: f64, props: serde_json::Value) -> BoardObject {
+    let mut obj = test_helpers::dummy_object();
+    obj.kind = kind.into();
+    obj.x = x;
+    obj.y = y;
+    obj.width = Some(100.0);
+    obj.height = Some(100.0);
+    obj.props = props;
+    obj
+}
+
+fn retro_board() -> (BoardObject, BoardObject, BoardObject, BoardObject) {
+    let mut frame = text_object("frame", 0.0, 0.0, json!({ "title": "Retro" }));
+    frame.width = Some(600.0);
+    frame.height = Some(400.0);
+    let first = text_object("sticky_note", 50.0, 50.0, json!({ "text": "Ship weekly demos" }));
+    let second = text_object("sticky_note", 50.0, 200.0, json!({ "text": "Decided: move standup to 10am" }));
+    let outside = text_object("sticky_note", 900.0, 50.0, json!({ "text": "Standup notes backlog" }));
+    (frame, first, second, outside)
+}
+
+#[tokio::test]
+async fn read_only_access_refuses_mutating_tool() {
+    let state = test_helpers::test_app_state();
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let mut mutations = Vec::new();
+
+    let err = execute_tool_with_access(
+        &state,
+        board_id,
+        ToolAccess::ReadOnly,
+        "deleteObject",
+        &json!({ "objectId": obj_id }),
+        &mut mutations,
+    )
+    .await
+    .unwrap_err();
+
+    assert!(matches!(err, AiError::ToolNotPermitted(ref name) if name == "deleteObject"));
+    assert!(mutations.is_empty());
+    assert!(
+        state.boards.read().await[&board_id]
+            .objects
+            .contains_key(&obj_id)
+    );
+}
+
+#[tokio::test]
+async fn read_only_access_refuses_unknown_tool() {
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let mut mutations = Vec::new();
+    let result = execute_tool_with_access(
+        &state,
+        board_id,
+        ToolAccess::ReadOnly,
+        "nonexistent_tool",
+        &json!({}),
+        &mut mutations,
+    )
+    .await;
+    assert!(matches!(result, Err(AiError::ToolNotPermitted(_))));
+}
+
+#[tokio::test]
+async fn tool_search_board_matches_text_case_insensitively_with_frame() {
+    let state = test_helpers::test_app_state();
+    let (frame, first, second, outside) = retro_board();
+    let (frame_id, second_id, outside_id) = (frame.id, second.id, outside.id);
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![frame, first, second, outside]).await;
+    let mut mutations = Vec::new();
+
+    let result = execute_tool_with_access(
+        &state,
+        board_id,
+        ToolAccess::ReadOnly,
+        "searchBoard",
+        &json!({ "query": "STANDUP" }),
+        &mut mutations,
+    )
+    .await
+    .unwrap();
+
+    let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+    assert_eq!(parsed["count"], 2);
+    let matches = parsed["matches"].as_array().unwrap();
+    let inside = matches
+        .iter()
+        .find(|m| m["id"] == json!(second_id))
+        .unwrap();
+    assert_eq!(inside["frameId"], json!(frame_id));
+    assert_eq!(inside["frameTitle"], "Retro");
+    let loose = matches
+        .iter()
+        .find(|m| m["id"] == json!(outside_id))
+        .unwrap();
+    assert!(loose["frameId"].is_null());
+    assert!(mutations.is_empty());
+}
+
+#[tokio::test]
+async fn tool_search_board_requires_query() {
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let mut mutations = Vec::new();
+    let err = execute_tool(&state, board_id, "searchBoard", &json!({}), &mut mutations)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AiError::InvalidToolInput { ref details, .. } if details == "input.query: is required"));
+}
+
+#[tokio::test]
+async fn tool_summarize_frame_by_title_lists_contents_in_reading_order() {
+    let state = test_helpers::test_app_state();
+    let (frame, first, second, outside) = retro_board();
+    let (first_id, second_id) = (first.id, second.id);
+    let mut arrow = text_object(
+        "arrow",
+        60.0,
+        60.0,
+        json!({ "a": { "object_id": first_id }, "b": { "object_id": second_id } }),
+    );
+    arrow.width = None;
+    arrow.height = None;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![frame, second, outside, first, arrow]).await;
+    let mut mutations = Vec::new();
+
+    let result = execute_tool(&state, board_id, "summarizeFrame", &json!({ "title": "retro" }), &mut mutations)
+        .await
+        .unwrap();
+
+    let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+    assert_eq!(parsed["title"], "Retro");
+    let items = parsed["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["text"], "Ship weekly demos");
+    assert_eq!(items[1]["text"], "Decided: move standup to 10am");
+    assert_eq!(parsed["kindCounts"]["sticky_note"], 2);
+    assert_eq!(parsed["connections"], json!([{ "from": first_id, "to": second_id }]));
+}
+
+#[tokio::test]
+async fn tool_summarize_frame_reports_missing_frame() {
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let mut mutations = Vec::new();
+    let result = execute_tool(&state, board_id, "summarizeFrame", &json!({ "title": "nope" }), &mut mutations)
+        .await
+        .unwrap();
+    assert_eq!(result, "error: frame not found");
+}
+
+#[tokio::test]
+async fn tool_export_mermaid_scopes_to_frame() {
+    let state = test_helpers::test_app_state();
+    let (frame, first, second, outside) = retro_board();
+    let frame_id = frame.id;
+    let arrow = text_object(
+        "arrow",
+        60.0,
+        60.0,
+        json!({ "a": { "type": "attached", "object_id": first.id }, "b": { "type": "attached", "object_id": second.id } }),
+    );
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![frame, second, outside, first, arrow]).await;
+    let mut mutations = Vec::new();
+
+    let result = execute_tool(
+        &state,
+        board_id,
+        "exportMermaid",
+        &json!({ "frameId": frame_id }),
+        &mut mutations,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        result,
+        "flowchart TD\n    n1[\"Ship weekly demos\"]\n    n2[\"Decided: move standup to 10am\"]\n    n1 --> n2\n"
+    );
+    assert!(mutations.is_empty());
+
+    let whole = execute_tool(&state, board_id, "exportMermaid", &json!({}), &mut mutations)
+        .await
+        .unwrap();
+    assert!(whole.contains("subgraph f1 [\"Retro\"]"));
+    assert!(whole.contains("Standup notes backlog"));
+}
+
+#[tokio::test]
+async fn tool_export_mermaid_rejects_conflicting_scopes_and_unknown_frames() {
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let mut mutations = Vec::new();
+    let id = Uuid::new_v4();
+    let both = json!({ "frameId": id, "objectIds": [id] });
+    let result = execute_tool(&state, board_id, "exportMermaid", &both, &mut mutations)
+        .await
+        .unwrap();
+    assert_eq!(result, "error: pass frameId or objectIds, not both");
+
+    let result = execute_tool(&state, board_id, "exportMermaid", &json!({ "frameId": id }), &mut mutations)
+        .await
+        .unwrap();
+    assert_eq!(result, format!("error: frame {id} not found"));
+}
+
 // =========================================================================
 // execute_tool — swot
 // =========================================================================
@@ -767,6 +985,139 @@ async fn tool_swot_creates_template() {
     assert!(labels.contains(&"Threats"));
 }
 
+// =========================================================================
+// execute_tool — createMermaidDiagram
+// =========================================================================
+
+#[tokio::test]
+async fn tool_mermaid_flowchart_binds_connectors_to_created_nodes() {
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let mut mutations = Vec::new();
+    let input =
+        json!({ "mermaid": "flowchart TD\nA[Start] --> B{Ok?}\nsubgraph g [Group]\nB -->|yes| C((Done))\nend" });
+    let result = execute_tool(&state, board_id, "createMermaidDiagram", &input, &mut mutations)
+        .await
+        .unwrap();
+    assert_eq!(
+        result,
+        "created 7 objects from Mermaid flowchart (3 nodes, 2 edges, 1 subgraphs)"
+    );
+
+    let created: Vec<&crate::state::BoardObject> = mutations
+        .iter()
+        .filter_map(|m| match m {
+            AiMutation::Created(obj) => Some(obj),
+            _ => None,
+        })
+        .collect();
+    let by_text = |text: &str| {
+        created
+            .iter()
+            .find(|obj| obj.kind != "text" && obj.props.get("text") == Some(&json!(text)))
+            .map(|obj| obj.id)
+            .unwrap()
+    };
+    let arrows: Vec<_> = created.iter().filter(|obj| obj.kind == "arrow").collect();
+    assert_eq!(arrows.len(), 2);
+    assert_eq!(arrows[0].props["a"]["type"], "attached");
+    assert_eq!(arrows[0].props["a"]["object_id"], json!(by_text("Start")));
+    assert_eq!(arrows[0].props["b"]["object_id"], json!(by_text("Ok?")));
+    assert_eq!(arrows[1].props["b"]["object_id"], json!(by_text("Done")));
+    assert_eq!(created.iter().filter(|obj| obj.kind == "frame").count(), 1);
+}
+
+#[tokio::test]
+async fn tool_mermaid_state_diagram_binds_transitions_to_states_and_composites() {
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let mut mutations = Vec::new();
+    let input = json!({
+        "mermaid": "stateDiagram-v2\n[*] --> Active\nstate Active {\n[*] --> Idle\n}\nActive --> Done : finish"
+    });
+    let result = execute_tool(&state, board_id, "createMermaidDiagram", &input, &mut mutations)
+        .await
+        .unwrap();
+    assert_eq!(result, "created 9 objects from Mermaid state diagram (5 states, 3 transitions)");
+
+    let created: Vec<&crate::state::BoardObject> = mutations
+        .iter()
+        .filter_map(|m| match m {
+            AiMutation::Created(obj) => Some(obj),
+            _ => None,
+        })
+        .collect();
+    let frame = created.iter().find(|obj| obj.kind == "frame").unwrap();
+    let done = created
+        .iter()
+        .find(|obj| obj.kind == "rectangle" && obj.props.get("text") == Some(&json!("Done")))
+        .unwrap();
+    let arrows: Vec<_> = created.iter().filter(|obj| obj.kind == "arrow").collect();
+    assert_eq!(arrows.len(), 3);
+    assert!(
+        arrows
+            .iter()
+            .all(|a| a.props["a"]["type"] == "attached" && a.props["b"]["type"] == "attached")
+    );
+    assert_eq!(arrows[0].props["b"]["object_id"], json!(frame.id));
+    assert_eq!(arrows[2].props["a"]["object_id"], json!(frame.id));
+    assert_eq!(arrows[2].props["b"]["object_id"], json!(done.id));
+}
+
+#[tokio::test]
+async fn tool_mermaid_class_diagram_binds_relations_to_classes() {
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let mut mutations = Vec::new();
+    let input = json!({
+        "mermaid": "classDiagram\nclass Animal {\n+String name\n+eat() void\n}\nAnimal <|-- Duck\nCar *-- Wheel"
+    });
+    let result = execute_tool(&state, board_id, "createMermaidDiagram", &input, &mut mutations)
+        .await
+        .unwrap();
+    assert_eq!(result, "created 6 objects from Mermaid class diagram (4 classes, 2 relations)");
+
+    let created: Vec<&crate::state::BoardObject> = mutations
+        .iter()
+        .filter_map(|m| match m {
+            AiMutation::Created(obj) => Some(obj),
+            _ => None,
+        })
+        .collect();
+    let class_id = |name: &str| {
+        created
+            .iter()
+            .find(|obj| {
+                obj.kind == "rectangle"
+                    && obj.props["text"]
+                        .as_str()
+                        .is_some_and(|t| t.lines().next() == Some(name))
+            })
+            .map(|obj| obj.id)
+            .unwrap()
+    };
+    let inheritance = created.iter().find(|obj| obj.kind == "arrow").unwrap();
+    assert_eq!(inheritance.props["relation"], "inheritance");
+    assert_eq!(inheritance.props["a"]["object_id"], json!(class_id("Duck")));
+    assert_eq!(inheritance.props["b"]["object_id"], json!(class_id("Animal")));
+    let composition = created.iter().find(|obj| obj.kind == "line").unwrap();
+    assert_eq!(composition.props["a"]["type"], "attached");
+    assert_eq!(composition.props["b"]["object_id"], json!(class_id("Car")));
+}
+
+#[tokio::test]
+async fn tool_mermaid_reports_flowchart_parse_errors() {
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let mut mutations = Vec::new();
+    let input = json!({ "mermaid": "flowchart TD\nsubgraph g\nA --> B" });
+    let result = execute_tool(&state, board_id, "createMermaidDiagram", &input, &mut mutations)
+        .await
+        .unwrap();
+    assert_eq!(result, "error: failed to parse mermaid diagram: subgraph g is missing `end`");
+    assert!(mutations.is_empty());
+}
+
 // =========================================================================
 // execute_tool — createAnimationClip
 // =========================================================================
@@ -981,6 +1332,8 @@ async fn handle_prompt_text_only() {
         stop_reason: "end_turn".into(),
         input_tokens: 10,
         output_tokens: 5,
+        cache_read_tokens: 0,
+        cache_creation_tokens: 0,
     }]));
     let client_id = Uuid::new_v4();
     let user_id = Uuid::new_v4();
@@ -991,6 +1344,47 @@ async fn handle_prompt_text_only() {
     assert!(result.mutations.is_empty());
 }
 
+#[tokio::test]
+async fn handle_prompt_streams_text_deltas_to_requesting_client() {
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let mock = Arc::new(MockLlm::new(vec![ChatResponse {
+        content: vec![ContentBlock::Text { text: "Streaming hello".into() }],
+        model: "mock".into(),
+        stop_reason: "end_turn".into(),
+        input_tokens: 10,
+        output_tokens: 5,
+        cache_read_tokens: 0,
+        cache_creation_tokens: 0,
+    }]));
+    let client_id = Uuid::new_v4();
+    let (client_tx, mut client_rx) = tokio::sync::mpsc::channel(8);
+    state.ws_clients.write().await.insert(client_id, client_tx);
+    let request_id = Uuid::new_v4();
+
+    let result = handle_prompt_with_parent(
+        &state,
+        &(mock as Arc<dyn LlmChat>),
+        board_id,
+        client_id,
+        Uuid::new_v4(),
+        "hello",
+        None,
+        Some(request_id),
+        None,
+    )
+    .await
+    .unwrap();
+
+    let delta = client_rx.try_recv().expect("delta frame");
+    assert_eq!(delta.syscall, "ai:prompt");
+    assert_eq!(delta.status, Status::Item);
+    assert_eq!(delta.parent_id, Some(request_id));
+    assert_eq!(delta.data["kind"], "assistant_delta");
+    assert_eq!(delta.data["content"], "Streaming hello");
+    assert_eq!(result.text.as_deref(), Some("Streaming hello"));
+}
+
 #[tokio::test]
 async fn handle_prompt_with_tool_call() {
     let state = test_helpers::test_app_state();
@@ -1007,6 +1401,8 @@ async fn handle_prompt_with_tool_call() {
             stop_reason: "tool_use".into(),
             input_tokens: 10,
             output_tokens: 20,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
         // Second response: done
         ChatResponse {
@@ -1015,6 +1411,8 @@ async fn handle_prompt_with_tool_call() {
             stop_reason: "end_turn".into(),
             input_tokens: 30,
             output_tokens: 5,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
     ]));
     let result = handle_prompt(
@@ -1033,6 +1431,63 @@ async fn handle_prompt_with_tool_call() {
     assert_eq!(result.text.as_deref(), Some("Created a note"));
 }
 
+#[tokio::test]
+async fn handle_prompt_tool_loop_replays_from_cassette() {
+    let path = std::env::temp_dir().join(format!("ai-cassette-{}.jsonl", Uuid::new_v4()));
+    let mock = Arc::new(MockLlm::new(vec![
+        ChatResponse {
+            content: vec![ContentBlock::ToolUse {
+                id: "tu_1".into(),
+                name: "createStickyNote".into(),
+                input: json!({ "text": "hello", "x": 100, "y": 100 }),
+            }],
+            model: "mock".into(),
+            stop_reason: "tool_use".into(),
+            input_tokens: 10,
+            output_tokens: 20,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
+        },
+        ChatResponse {
+            content: vec![ContentBlock::Text { text: "Created a note".into() }],
+            model: "mock".into(),
+            stop_reason: "end_turn".into(),
+            input_tokens: 30,
+            output_tokens: 5,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
+        },
+    ]));
+
+    // Record against one board, then replay the same prompt on a fresh one.
+    let recorder: Arc<dyn LlmChat> = Arc::new(CassetteChat::record(&path, mock));
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    handle_prompt(
+        &state,
+        &recorder,
+        board_id,
+        Uuid::new_v4(),
+        Uuid::new_v4(),
+        "create a note",
+        None,
+    )
+    .await
+    .unwrap();
+
+    let player: Arc<dyn LlmChat> = Arc::new(CassetteChat::replay(&path).unwrap());
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let result = handle_prompt(&state, &player, board_id, Uuid::new_v4(), Uuid::new_v4(), "create a note", None)
+        .await
+        .unwrap();
+
+    assert_eq!(result.mutations.len(), 1);
+    assert!(matches!(&result.mutations[0], AiMutation::Created(_)));
+    assert_eq!(result.text.as_deref(), Some("Created a note"));
+    let _ = std::fs::remove_file(path);
+}
+
 #[tokio::test]
 async fn handle_prompt_board_not_loaded() {
     let state = test_helpers::test_app_state();
@@ -1087,6 +1542,8 @@ async fn user_message_wrapped_in_xml_tags() {
                 stop_reason: "end_turn".into(),
                 input_tokens: 5,
                 output_tokens: 2,
+                cache_read_tokens: 0,
+                cache_creation_tokens: 0,
             })
         }
     }
@@ -1132,6 +1589,8 @@ async fn handle_prompt_rate_limited() {
             stop_reason: "end_turn".into(),
             input_tokens: 1,
             output_tokens: 1,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         }]));
         let _ = handle_prompt(&state, &(mock as Arc<dyn LlmChat>), board_id, client_id, client_id, "hi", None).await;
     }
@@ -1142,6 +1601,37 @@ async fn handle_prompt_rate_limited() {
     assert!(matches!(result.unwrap_err(), AiError::RateLimited(_)));
 }
 
+// =========================================================================
+// Budgets
+// =========================================================================
+
+fn budget_exceeded() -> crate::services::usage::UsageError {
+    crate::services::usage::UsageError::BudgetExceeded { scope: "user", period: "daily", limit: 100, used: 120 }
+}
+
+#[test]
+fn budget_stop_fails_before_any_llm_call() {
+    let err = budget_stop_item(0, budget_exceeded()).unwrap_err();
+    assert!(matches!(err, AiError::Usage(_)));
+}
+
+#[test]
+fn budget_stop_ends_later_rounds_with_partial_result_item() {
+    let item = budget_stop_item(2, budget_exceeded()).unwrap();
+    assert_eq!(item.get("kind"), Some(&json!("budget_exceeded")));
+    assert_eq!(item.get("role"), Some(&json!("assistant")));
+    assert_eq!(
+        item.get("content"),
+        Some(&json!("Stopped early: user daily AI token budget exceeded (120/100 tokens)."))
+    );
+}
+
+#[test]
+fn budget_stop_still_fails_on_database_errors() {
+    let err = budget_stop_item(1, sqlx::Error::PoolTimedOut.into()).unwrap_err();
+    assert!(matches!(err, AiError::Usage(_)));
+}
+
 // =========================================================================
 // Thinking-only response
 // =========================================================================
@@ -1157,6 +1647,8 @@ async fn handle_prompt_thinking_only_still_returns_text() {
         stop_reason: "end_turn".into(),
         input_tokens: 10,
         output_tokens: 5,
+        cache_read_tokens: 0,
+        cache_creation_tokens: 0,
     }]));
     let result = handle_prompt(
         &state,
@@ -1196,6 +1688,8 @@ async fn handle_prompt_mutations_only_returns_text() {
             stop_reason: "tool_use".into(),
             input_tokens: 10,
             output_tokens: 20,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
         // Second response: done with no text
         ChatResponse {
@@ -1204,6 +1698,8 @@ async fn handle_prompt_mutations_only_returns_text() {
             stop_reason: "end_turn".into(),
             input_tokens: 30,
             output_tokens: 5,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
     ]));
     let result = handle_prompt(
@@ -1257,6 +1753,8 @@ async fn handle_prompt_tool_context_keeps_only_latest_round() {
                     stop_reason: "tool_use".into(),
                     input_tokens: 10,
                     output_tokens: 20,
+                    cache_read_tokens: 0,
+                    cache_creation_tokens: 0,
                 },
                 1 => ChatResponse {
                     content: vec![ContentBlock::ToolUse {
@@ -1268,6 +1766,8 @@ async fn handle_prompt_tool_context_keeps_only_latest_round() {
                     stop_reason: "tool_use".into(),
                     input_tokens: 12,
                     output_tokens: 18,
+                    cache_read_tokens: 0,
+                    cache_creation_tokens: 0,
                 },
                 _ => ChatResponse {
                     content: vec![ContentBlock::Text { text: "done".into() }],
@@ -1275,6 +1775,8 @@ async fn handle_prompt_tool_context_keeps_only_latest_round() {
                     stop_reason: "end_turn".into(),
                     input_tokens: 16,
                     output_tokens: 8,
+                    cache_read_tokens: 0,
+                    cache_creation_tokens: 0,
                 },
             };
             *calls += 1;
@@ -1310,6 +1812,8 @@ async fn handle_prompt_persists_trace_envelope_for_llm_and_tool_spans() {
             stop_reason: "tool_use".into(),
             input_tokens: 11,
             output_tokens: 17,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
         ChatResponse {
             content: vec![ContentBlock::Text { text: "done".into() }],
@@ -1317,6 +1821,8 @@ async fn handle_prompt_persists_trace_envelope_for_llm_and_tool_spans() {
             stop_reason: "end_turn".into(),
             input_tokens: 5,
             output_tokens: 6,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
         },
     ]));
     let mut state = test_helpers::test_app_state();
@@ -1334,6 +1840,7 @@ async fn handle_prompt_persists_trace_envelope_for_llm_and_tool_spans() {
         "create a sticky",
         None,
         Some(root),
+        None,
     )
     .await
     .expect("prompt should succeed");
@@ -1421,6 +1928,8 @@ async fn handle_prompt_reuses_session_history_by_default() {
                 stop_reason: "end_turn".into(),
                 input_tokens: 5,
                 output_tokens: 3,
+                cache_read_tokens: 0,
+                cache_creation_tokens: 0,
             })
         }
     }
@@ -1473,3 +1982,392 @@ async fn session_history_is_trimmed_by_char_budget() {
     assert!(total_chars <= MAX_SESSION_TOTAL_CHARS + 32);
     assert!(stored.len() >= 2);
 }
+
+// =========================================================================
+// preview / apply / discard
+// =========================================================================
+
+fn tool_call(id: &str, name: &str, input: serde_json::Value) -> ContentBlock {
+    ContentBlock::ToolUse { id: id.into(), name: name.into(), input }
+}
+
+fn tool_turn(calls: Vec<ContentBlock>) -> ChatResponse {
+    ChatResponse {
+        content: calls,
+        model: "mock".into(),
+        stop_reason: "tool_use".into(),
+        input_tokens: 0,
+        output_tokens: 0,
+        cache_read_tokens: 0,
+        cache_creation_tokens: 0,
+    }
+}
+
+async fn run_preview(
+    state: &AppState,
+    board_id: Uuid,
+    client_id: Uuid,
+    calls: Vec<ContentBlock>,
+) -> (Uuid, AiPreviewResult) {
+    let llm: Arc<dyn LlmChat> = Arc::new(MockLlm::new(vec![tool_turn(calls)]));
+    let request_id = Uuid::new_v4();
+    let preview = handle_preview(state, &llm, board_id, client_id, Uuid::new_v4(), "tidy up", None, request_id)
+        .await
+        .unwrap();
+    (request_id, preview)
+}
+
+#[tokio::test]
+async fn preview_leaves_live_board_untouched() {
+    let state = test_helpers::test_app_state();
+    let kept = test_helpers::dummy_object();
+    let doomed = test_helpers::dummy_object();
+    let (kept_id, doomed_id) = (kept.id, doomed.id);
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![kept, doomed]).await;
+    let client_id = Uuid::new_v4();
+
+    let (request_id, preview) = run_preview(
+        &state,
+        board_id,
+        client_id,
+        vec![
+            tool_call(
+                "tu_1",
+                "moveObject",
+                json!({ "objectId": kept_id.to_string(), "x": 300, "y": 400 }),
+            ),
+            tool_call("tu_2", "deleteObject", json!({ "objectId": doomed_id.to_string() })),
+            tool_call("tu_3", "createStickyNote", json!({ "text": "new", "x": 900, "y": 900 })),
+        ],
+    )
+    .await;
+
+    assert!(!preview.prompt.cancelled);
+    assert_eq!(preview.changes.len(), 3);
+    assert!(
+        matches!(&preview.changes[0], BatchChange::Updated(obj) if obj.id == kept_id && (obj.x - 300.0).abs() < f64::EPSILON)
+    );
+    assert!(matches!(&preview.changes[1], BatchChange::Deleted(id) if *id == doomed_id));
+    assert!(matches!(&preview.changes[2], BatchChange::Created(obj) if obj.kind == "sticky_note"));
+
+    let boards = state.boards.read().await;
+    let board = &boards[&board_id];
+    assert_eq!(board.objects.len(), 2);
+    assert!((board.objects[&kept_id].x - 100.0).abs() < f64::EPSILON);
+    assert!(board.dirty.is_empty());
+    drop(boards);
+    assert!(state.ai_previews.read().await.contains_key(&request_id));
+
+    discard_preview(&state, board_id, client_id, request_id)
+        .await
+        .unwrap();
+    assert!(state.ai_previews.read().await.is_empty());
+}
+
+#[tokio::test]
+async fn preview_persists_no_frames() {
+    let mut state = test_helpers::test_app_state();
+    let (persist_tx, mut persist_rx) = tokio::sync::mpsc::channel::<crate::frame::Frame>(128);
+    state.frame_persist_tx = Some(persist_tx);
+    let board_id = test_helpers::seed_board(&state).await;
+
+    let (_, preview) = run_preview(
+        &state,
+        board_id,
+        Uuid::new_v4(),
+        vec![tool_call(
+            "tu_1",
+            "createStickyNote",
+            json!({ "text": "maybe", "x": 0, "y": 0 }),
+        )],
+    )
+    .await;
+
+    assert_eq!(preview.changes.len(), 1);
+    assert!(persist_rx.try_recv().is_err(), "preview frames must not reach the frame log");
+}
+
+#[tokio::test]
+async fn discarded_preview_stays_out_of_session_memory() {
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let client_id = Uuid::new_v4();
+
+    let (request_id, _) = run_preview(
+        &state,
+        board_id,
+        client_id,
+        vec![tool_call(
+            "tu_1",
+            "createStickyNote",
+            json!({ "text": "maybe", "x": 0, "y": 0 }),
+        )],
+    )
+    .await;
+    discard_preview(&state, board_id, client_id, request_id)
+        .await
+        .unwrap();
+    assert!(
+        load_session_messages(&state, (client_id, board_id))
+            .await
+            .is_empty()
+    );
+
+    let llm: Arc<dyn LlmChat> = Arc::new(MockLlm::new(Vec::new()));
+    handle_prompt(&state, &llm, board_id, client_id, Uuid::new_v4(), "what changed?", None)
+        .await
+        .unwrap();
+    let session = load_session_messages(&state, (client_id, board_id)).await;
+    assert_eq!(session.len(), 2, "only the real prompt and its reply are remembered");
+    assert!(matches!(&session[0].content, crate::llm::types::Content::Text(text) if text.contains("what changed?")));
+}
+
+#[tokio::test]
+async fn apply_preview_commits_changes_and_records_history() {
+    let state = test_helpers::test_app_state();
+    let mut obj = test_helpers::dummy_object();
+    obj.version = 2;
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let client_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+
+    let (request_id, preview) = run_preview(
+        &state,
+        board_id,
+        client_id,
+        vec![
+            tool_call(
+                "tu_1",
+                "moveObject",
+                json!({ "objectId": obj_id.to_string(), "x": 300, "y": 400 }),
+            ),
+            tool_call("tu_2", "createStickyNote", json!({ "text": "new", "x": 900, "y": 900 })),
+        ],
+    )
+    .await;
+    let BatchChange::Created(created) = &preview.changes[1] else {
+        panic!("expected created sticky");
+    };
+
+    let changes = apply_preview(&state, board_id, client_id, user_id, request_id)
+        .await
+        .unwrap();
+    assert_eq!(changes.len(), 2);
+
+    let boards = state.boards.read().await;
+    let board = &boards[&board_id];
+    let moved = &board.objects[&obj_id];
+    assert!((moved.x - 300.0).abs() < f64::EPSILON);
+    assert_eq!(moved.version, 3);
+    assert!(board.objects.contains_key(&created.id));
+    assert!(board.dirty.contains(&obj_id) && board.dirty.contains(&created.id));
+    drop(boards);
+
+    let histories = state.histories.read().await;
+    assert_eq!(histories[&(board_id, user_id)].undo_len(), 1);
+    drop(histories);
+    assert!(matches!(
+        apply_preview(&state, board_id, client_id, user_id, request_id).await,
+        Err(AiError::PreviewNotFound(id)) if id == request_id
+    ));
+}
+
+#[tokio::test]
+async fn apply_preview_rejects_when_board_moved_on() {
+    let state = test_helpers::test_app_state();
+    let mut obj = test_helpers::dummy_object();
+    obj.version = 2;
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let client_id = Uuid::new_v4();
+
+    let (request_id, _) = run_preview(
+        &state,
+        board_id,
+        client_id,
+        vec![tool_call(
+            "tu_1",
+            "moveObject",
+            json!({ "objectId": obj_id.to_string(), "x": 300, "y": 400 }),
+        )],
+    )
+    .await;
+
+    let mut updates = Data::new();
+    updates.insert("x".into(), json!(50.0));
+    crate::services::object::update_object(&state, board_id, obj_id, &updates, 2)
+        .await
+        .unwrap();
+
+    let err = apply_preview(&state, board_id, client_id, Uuid::new_v4(), request_id)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AiError::PreviewConflict(id) if id == obj_id));
+    let boards = state.boards.read().await;
+    assert!((boards[&board_id].objects[&obj_id].x - 50.0).abs() < f64::EPSILON);
+    drop(boards);
+    assert!(state.ai_previews.read().await.is_empty());
+}
+
+#[tokio::test]
+async fn preview_is_owned_by_requesting_client() {
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let client_id = Uuid::new_v4();
+    let (request_id, _) = run_preview(
+        &state,
+        board_id,
+        client_id,
+        vec![tool_call("tu_1", "createStickyNote", json!({ "text": "new" }))],
+    )
+    .await;
+
+    let other = Uuid::new_v4();
+    assert!(matches!(
+        discard_preview(&state, board_id, other, request_id).await,
+        Err(AiError::PreviewNotFound(_))
+    ));
+    discard_client_previews(&state, client_id).await;
+    assert!(state.ai_previews.read().await.is_empty());
+}
+
+#[test]
+fn net_changes_collapses_repeated_and_transient_mutations() {
+    let original = test_helpers::dummy_object();
+    let before = HashMap::from([(original.id, original.clone())]);
+    let mut moved = original.clone();
+    moved.x += 10.0;
+    let mut moved_back = moved.clone();
+    moved_back.x = original.x;
+    let transient = test_helpers::dummy_object();
+    let created = test_helpers::dummy_object();
+    let after = HashMap::from([(original.id, moved_back.clone()), (created.id, created.clone())]);
+
+    let changes = net_changes(
+        &before,
+        &after,
+        &[
+            AiMutation::Updated(moved),
+            AiMutation::Created(transient.clone()),
+            AiMutation::Created(created.clone()),
+            AiMutation::Updated(moved_back),
+            AiMutation::Deleted(transient.id),
+        ],
+    );
+    assert_eq!(changes.len(), 1);
+    assert!(matches!(&changes[0], BatchChange::Created(obj) if obj.id == created.id));
+}
+
+// =========================================================================
+// handle_ask
+// =========================================================================
+
+struct ToolRecordingLlm {
+    inner: MockLlm,
+    offered: Mutex<Vec<Vec<String>>>,
+}
+
+#[async_trait::async_trait]
+impl LlmChat for ToolRecordingLlm {
+    async fn chat(
+        &self,
+        max_tokens: u32,
+        system: &str,
+        messages: &[Message],
+        tools: Option<&[Tool]>,
+    ) -> Result<ChatResponse, LlmError> {
+        let names = tools
+            .unwrap_or_default()
+            .iter()
+            .map(|t| t.name.clone())
+            .collect();
+        self.offered.lock().unwrap().push(names);
+        self.inner.chat(max_tokens, system, messages, tools).await
+    }
+}
+
+#[tokio::test]
+async fn ask_offers_only_read_only_tools_and_refuses_mutations() {
+    let state = test_helpers::test_app_state();
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let recorder = Arc::new(ToolRecordingLlm {
+        inner: MockLlm::new(vec![tool_turn(vec![tool_call(
+            "t1",
+            "deleteObject",
+            json!({ "objectId": obj_id }),
+        )])]),
+        offered: Mutex::new(Vec::new()),
+    });
+    let llm: Arc<dyn LlmChat> = recorder.clone();
+
+    let result = handle_ask(
+        &state,
+        &llm,
+        board_id,
+        Uuid::new_v4(),
+        Uuid::new_v4(),
+        "what's here?",
+        None,
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap();
+
+    assert!(result.mutations.is_empty());
+    assert!(
+        state.boards.read().await[&board_id]
+            .objects
+            .contains_key(&obj_id)
+    );
+    let refused = result
+        .items
+        .iter()
+        .find(|item| item.get("kind") == Some(&json!("tool_result")))
+        .expect("tool result item");
+    assert_eq!(refused.get("is_error"), Some(&json!(true)));
+    for offered in recorder.offered.lock().unwrap().iter() {
+        assert!(
+            offered
+                .iter()
+                .all(|name| crate::llm::tools::is_read_only_tool(name)),
+            "{offered:?}"
+        );
+        assert!(offered.iter().any(|name| name == "searchBoard"));
+    }
+}
+
+// =========================================================================
+// tool input validation
+// =========================================================================
+
+#[tokio::test]
+async fn invalid_tool_input_is_returned_to_model_and_counted() {
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let llm: Arc<dyn LlmChat> = Arc::new(MockLlm::new(vec![
+        tool_turn(vec![tool_call("t1", "createStickyNote", json!({ "x": "left" }))]),
+        tool_turn(vec![tool_call("t2", "createStickyNote", json!({ "text": "fixed", "x": 10 }))]),
+    ]));
+
+    let result = handle_prompt(&state, &llm, board_id, Uuid::new_v4(), Uuid::new_v4(), "add a note", None)
+        .await
+        .unwrap();
+
+    assert_eq!(result.trace.tool_validation_failures, 1);
+    assert_eq!(result.mutations.len(), 1);
+    let rejected = result
+        .items
+        .iter()
+        .find(|item| item.get("tool_use_id") == Some(&json!("t1")) && item.get("kind") == Some(&json!("tool_result")))
+        .expect("rejected tool result");
+    assert_eq!(rejected.get("is_error"), Some(&json!(true)));
+    assert_eq!(
+        rejected.get("content"),
+        Some(&json!(
+            "invalid input for createStickyNote: input.text: is required; input.x: expected number, got string"
+        ))
+    );
+}
@@ -0,0 +1,452 @@
+//! AI thread service — persisted, named AI conversations per board.
+//!
+//! DESIGN
+//! ======
+//! A board can hold any number of threads. A prompt sent with a `thread_id`
+//! loads that thread's summary and recent turns as conversation context and
+//! appends its own turn when it completes, so a team can continue a
+//! conversation across reconnects and deploys.
+//!
+//! Threads are never hard-truncated. Once the unsummarized tail grows past
+//! `MAX_THREAD_CONTEXT_MESSAGES`, the older turns are folded into a running
+//! summary by the LLM (or a clipped transcript if that call fails). Later
+//! prompts see the summary plus the most recent turns, while the full
+//! message log stays in `ai_thread_messages` for `ai:history`.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::usage::{self, UsageRecord};
+use crate::llm::LlmChat;
+use crate::llm::types::{Content, ContentBlock, Message};
+use crate::state::AppState;
+
+/// Unsummarized messages kept as context before older turns are folded.
+const MAX_THREAD_CONTEXT_MESSAGES: usize = 12;
+/// Most recent messages left verbatim after a fold.
+const THREAD_KEEP_RECENT_MESSAGES: usize = 6;
+const MAX_THREAD_MESSAGE_CHARS: usize = 2_000;
+const MAX_THREAD_SUMMARY_CHARS: usize = 4_000;
+const THREAD_SUMMARY_MAX_TOKENS: u32 = 1024;
+const DEFAULT_THREAD_TITLE: &str = "New thread";
+const THREAD_SUMMARY_SYSTEM_PROMPT: &str = "You maintain the running summary of a conversation between a team and \
+     the AI assistant on a collaborative whiteboard. Merge the previous summary with the new turns into one concise \
+     summary. Keep decisions, requests, names, and what was built or changed on the board. Reply with the summary \
+     only.";
+
+/// Errors returned by AI thread operations.
+#[derive(Debug, thiserror::Error)]
+pub enum ThreadError {
+    /// No thread with the given ID exists on the board.
+    #[error("thread not found: {0}")]
+    ThreadNotFound(Uuid),
+    /// A Postgres query failed.
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl crate::frame::ErrorCode for ThreadError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::ThreadNotFound(_) => "E_THREAD_NOT_FOUND",
+            Self::Database(_) => "E_DATABASE",
+        }
+    }
+}
+
+/// A named AI conversation on a board.
+#[derive(Debug, Clone, Serialize)]
+pub struct AiThread {
+    pub id: Uuid,
+    pub board_id: Uuid,
+    pub title: String,
+    pub created_by: Option<Uuid>,
+    /// Creation timestamp in milliseconds since the Unix epoch.
+    pub created_at: i64,
+    /// Timestamp of the last appended turn in milliseconds since the Unix epoch.
+    pub updated_at: i64,
+}
+
+/// One stored thread message.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadMessage {
+    pub seq: i64,
+    pub ts: i64,
+    /// `"user"` or `"assistant"`.
+    pub role: String,
+    /// Raw prompt or reply text.
+    pub content: String,
+    /// Author of user messages.
+    pub user_id: Option<Uuid>,
+}
+
+type ThreadRow = (Uuid, Uuid, String, Option<Uuid>, i64, i64);
+type MessageRow = (i64, i64, String, String, Option<Uuid>);
+
+fn thread_from_row((id, board_id, title, created_by, created_at, updated_at): ThreadRow) -> AiThread {
+    AiThread { id, board_id, title, created_by, created_at, updated_at }
+}
+
+fn message_from_row((seq, ts, role, content, user_id): MessageRow) -> ThreadMessage {
+    ThreadMessage { seq, ts, role, content, user_id }
+}
+
+/// Conversation context loaded for a prompt.
+#[derive(Debug, Clone, Default)]
+pub struct ThreadContext {
+    /// Running summary of turns no longer sent verbatim; empty if none.
+    pub summary: String,
+    /// Recent turns, oldest first, ready to precede the new prompt.
+    pub messages: Vec<Message>,
+}
+
+// =============================================================================
+// THREADS
+// =============================================================================
+
+/// Create a thread on a board. Blank titles fall back to a default.
+///
+/// # Errors
+///
+/// Returns a database error if the insert fails.
+pub async fn create_thread(
+    pool: &PgPool,
+    board_id: Uuid,
+    user_id: Uuid,
+    title: Option<&str>,
+) -> Result<AiThread, ThreadError> {
+    let title = title
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .unwrap_or(DEFAULT_THREAD_TITLE);
+    let row = sqlx::query_as::<_, ThreadRow>(
+        "INSERT INTO ai_threads (id, board_id, title, created_by)
+         VALUES ($1, $2, $3, $4)
+         RETURNING id, board_id, title, created_by, created_at, updated_at",
+    )
+    .bind(Uuid::new_v4())
+    .bind(board_id)
+    .bind(title)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+    let thread = thread_from_row(row);
+    info!(%board_id, thread_id = %thread.id, "ai: thread created");
+    Ok(thread)
+}
+
+/// List a board's threads, most recently active first.
+///
+/// # Errors
+///
+/// Returns a database error if the query fails.
+pub async fn list_threads(pool: &PgPool, board_id: Uuid) -> Result<Vec<AiThread>, ThreadError> {
+    let rows = sqlx::query_as::<_, ThreadRow>(
+        "SELECT id, board_id, title, created_by, created_at, updated_at
+         FROM ai_threads
+         WHERE board_id = $1
+         ORDER BY updated_at DESC",
+    )
+    .bind(board_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(thread_from_row).collect())
+}
+
+/// Full message log of a thread, oldest first.
+///
+/// # Errors
+///
+/// Returns `ThreadNotFound` if the thread is not on the board, or a database
+/// error if a query fails.
+pub async fn thread_messages(
+    pool: &PgPool,
+    board_id: Uuid,
+    thread_id: Uuid,
+) -> Result<Vec<ThreadMessage>, ThreadError> {
+    ensure_thread(pool, board_id, thread_id).await?;
+    let rows = sqlx::query_as::<_, MessageRow>(
+        "SELECT seq, ts, role, content, user_id
+         FROM ai_thread_messages
+         WHERE thread_id = $1
+         ORDER BY seq ASC",
+    )
+    .bind(thread_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(message_from_row).collect())
+}
+
+async fn ensure_thread(pool: &PgPool, board_id: Uuid, thread_id: Uuid) -> Result<(), ThreadError> {
+    let found = sqlx::query_scalar::<_, i32>("SELECT 1 FROM ai_threads WHERE id = $1 AND board_id = $2")
+        .bind(thread_id)
+        .bind(board_id)
+        .fetch_optional(pool)
+        .await?;
+    found
+        .map(|_| ())
+        .ok_or(ThreadError::ThreadNotFound(thread_id))
+}
+
+// =============================================================================
+// CONTEXT
+// =============================================================================
+
+/// Load the summary and unsummarized turns of a thread for a new prompt.
+///
+/// # Errors
+///
+/// Returns `ThreadNotFound` if the thread is not on the board, or a database
+/// error if a query fails.
+pub async fn load_context(pool: &PgPool, board_id: Uuid, thread_id: Uuid) -> Result<ThreadContext, ThreadError> {
+    let (summary, summarized_seq) = sqlx::query_as::<_, (String, i64)>(
+        "SELECT summary, summarized_seq FROM ai_threads WHERE id = $1 AND board_id = $2",
+    )
+    .bind(thread_id)
+    .bind(board_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(ThreadError::ThreadNotFound(thread_id))?;
+    let rows = unsummarized_messages(pool, thread_id, summarized_seq).await?;
+    Ok(ThreadContext { summary, messages: context_messages(&rows) })
+}
+
+async fn unsummarized_messages(
+    pool: &PgPool,
+    thread_id: Uuid,
+    summarized_seq: i64,
+) -> Result<Vec<ThreadMessage>, ThreadError> {
+    let rows = sqlx::query_as::<_, MessageRow>(
+        "SELECT seq, ts, role, content, user_id
+         FROM ai_thread_messages
+         WHERE thread_id = $1 AND seq > $2
+         ORDER BY seq ASC",
+    )
+    .bind(thread_id)
+    .bind(summarized_seq)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(message_from_row).collect())
+}
+
+/// Convert stored messages into LLM messages, wrapping user prompts the same
+/// way live prompts are wrapped.
+#[must_use]
+pub fn context_messages(rows: &[ThreadMessage]) -> Vec<Message> {
+    rows.iter()
+        .map(|row| {
+            let text = clip(&row.content, MAX_THREAD_MESSAGE_CHARS);
+            let text = if row.role == "user" {
+                format!("<user_input>{text}</user_input>")
+            } else {
+                text
+            };
+            Message { role: row.role.clone(), content: Content::Text(text) }
+        })
+        .collect()
+}
+
+/// Append a completed prompt and its reply to a thread.
+///
+/// # Errors
+///
+/// Returns `ThreadNotFound` if the thread no longer exists, or a database
+/// error if a query fails.
+pub async fn append_turn(
+    pool: &PgPool,
+    thread_id: Uuid,
+    user_id: Uuid,
+    prompt: &str,
+    reply: &str,
+) -> Result<(), ThreadError> {
+    let mut tx = pool.begin().await?;
+    // Lock the thread row so concurrent prompts take consecutive sequence numbers.
+    sqlx::query_scalar::<_, i32>("SELECT 1 FROM ai_threads WHERE id = $1 FOR UPDATE")
+        .bind(thread_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ThreadError::ThreadNotFound(thread_id))?;
+    let last_seq =
+        sqlx::query_scalar::<_, i64>("SELECT COALESCE(MAX(seq), 0) FROM ai_thread_messages WHERE thread_id = $1")
+            .bind(thread_id)
+            .fetch_one(&mut *tx)
+            .await?;
+    sqlx::query(
+        "INSERT INTO ai_thread_messages (thread_id, seq, role, content, user_id)
+         VALUES ($1, $2, 'user', $3, $4), ($1, $5, 'assistant', $6, NULL)",
+    )
+    .bind(thread_id)
+    .bind(last_seq + 1)
+    .bind(prompt)
+    .bind(user_id)
+    .bind(last_seq + 2)
+    .bind(reply)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("UPDATE ai_threads SET updated_at = (EXTRACT(EPOCH FROM now()) * 1000)::BIGINT WHERE id = $1")
+        .bind(thread_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+// =============================================================================
+// SUMMARIZATION
+// =============================================================================
+
+/// Number of leading unsummarized messages to fold into the summary.
+///
+/// Always even so user/assistant pairs stay together.
+#[must_use]
+pub fn fold_count(unsummarized: usize) -> usize {
+    if unsummarized <= MAX_THREAD_CONTEXT_MESSAGES {
+        return 0;
+    }
+    let fold = unsummarized - THREAD_KEEP_RECENT_MESSAGES;
+    fold - fold % 2
+}
+
+/// Fold older turns into the thread summary once the tail is too long.
+///
+/// Runs after a turn is appended. The update only lands if no other
+/// compaction moved the summary in the meantime. The summary call is
+/// charged to `user_id` and `board_id` like any other LLM call.
+///
+/// # Errors
+///
+/// Returns a database error if a query fails.
+pub async fn compact_thread(
+    state: &AppState,
+    llm: &Arc<dyn LlmChat>,
+    thread_id: Uuid,
+    user_id: Uuid,
+    board_id: Uuid,
+) -> Result<(), ThreadError> {
+    let pool = &state.pool;
+    let Some((summary, summarized_seq)) =
+        sqlx::query_as::<_, (String, i64)>("SELECT summary, summarized_seq FROM ai_threads WHERE id = $1")
+            .bind(thread_id)
+            .fetch_optional(pool)
+            .await?
+    else {
+        return Ok(());
+    };
+    let rows = unsummarized_messages(pool, thread_id, summarized_seq).await?;
+    let fold = fold_count(rows.len());
+    if fold == 0 {
+        return Ok(());
+    }
+    let folded = &rows[..fold];
+    let next_summary = summarize(state, llm, user_id, board_id, &summary, folded).await;
+    let through_seq = folded.last().map_or(summarized_seq, |row| row.seq);
+    sqlx::query("UPDATE ai_threads SET summary = $1, summarized_seq = $2 WHERE id = $3 AND summarized_seq = $4")
+        .bind(&next_summary)
+        .bind(through_seq)
+        .bind(thread_id)
+        .bind(summarized_seq)
+        .execute(pool)
+        .await?;
+    info!(%thread_id, folded = fold, through_seq, "ai: thread summarized");
+    Ok(())
+}
+
+/// Merge `previous` and the folded turns into a new summary.
+///
+/// Falls back to the transcript without calling the LLM when the user
+/// or board is over budget.
+pub async fn summarize(
+    state: &AppState,
+    llm: &Arc<dyn LlmChat>,
+    user_id: Uuid,
+    board_id: Uuid,
+    previous: &str,
+    folded: &[ThreadMessage],
+) -> String {
+    if let Err(e) = usage::check_budgets(state, user_id, board_id).await {
+        warn!(error = %e, %board_id, "ai: thread summary skipped; using transcript");
+        return fallback_summary(previous, folded);
+    }
+    let mut request = String::new();
+    if !previous.is_empty() {
+        request.push_str("Previous summary:\n");
+        request.push_str(previous);
+        request.push_str("\n\n");
+    }
+    request.push_str("New turns:\n");
+    request.push_str(&transcript(folded));
+    let messages = [Message { role: "user".into(), content: Content::Text(request) }];
+
+    match llm
+        .chat(THREAD_SUMMARY_MAX_TOKENS, THREAD_SUMMARY_SYSTEM_PROMPT, &messages, None)
+        .await
+    {
+        Ok(response) => {
+            usage::record_usage(state, UsageRecord::from_response(user_id, board_id, None, &response));
+            let text = response
+                .content
+                .iter()
+                .filter_map(|block| match block {
+                    ContentBlock::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<String>();
+            let text = text.trim();
+            if text.is_empty() {
+                fallback_summary(previous, folded)
+            } else {
+                clip(text, MAX_THREAD_SUMMARY_CHARS)
+            }
+        }
+        Err(e) => {
+            warn!(error = %e, "ai: thread summary failed; using transcript");
+            fallback_summary(previous, folded)
+        }
+    }
+}
+
+/// Summary built without the LLM: the previous summary plus a clipped
+/// transcript, keeping the most recent text when over budget.
+#[must_use]
+pub fn fallback_summary(previous: &str, folded: &[ThreadMessage]) -> String {
+    let mut summary = previous.to_owned();
+    if !summary.is_empty() {
+        summary.push('\n');
+    }
+    summary.push_str(&transcript(folded));
+    let len = summary.chars().count();
+    if len <= MAX_THREAD_SUMMARY_CHARS {
+        return summary;
+    }
+    summary
+        .chars()
+        .skip(len - MAX_THREAD_SUMMARY_CHARS)
+        .collect()
+}
+
+fn transcript(rows: &[ThreadMessage]) -> String {
+    rows.iter()
+        .map(|row| {
+            let speaker = if row.role == "user" { "User" } else { "Assistant" };
+            format!("{speaker}: {}", clip(&row.content, MAX_THREAD_MESSAGE_CHARS))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn clip(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_owned();
+    }
+    let mut clipped = text.chars().take(max_chars).collect::<String>();
+    clipped.push('…');
+    clipped
+}
+
+#[cfg(test)]
+#[path = "ai_thread_test.rs"]
+mod tests;
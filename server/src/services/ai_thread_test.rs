@@ -0,0 +1,120 @@
+use super::*;
+use crate::llm::types::{ChatResponse, LlmError, Tool};
+use crate::state::test_helpers;
+
+fn message(seq: i64, role: &str, content: &str) -> ThreadMessage {
+    ThreadMessage { seq, ts: 0, role: role.to_owned(), content: content.to_owned(), user_id: None }
+}
+
+fn turns(pairs: usize) -> Vec<ThreadMessage> {
+    (0..pairs)
+        .flat_map(|i| {
+            let seq = i64::try_from(i * 2).unwrap();
+            [
+                message(seq + 1, "user", &format!("ask {i}")),
+                message(seq + 2, "assistant", &format!("done {i}")),
+            ]
+        })
+        .collect()
+}
+
+struct FixedLlm(Result<String, ()>);
+
+#[async_trait::async_trait]
+impl LlmChat for FixedLlm {
+    async fn chat(
+        &self,
+        _max_tokens: u32,
+        _system: &str,
+        _messages: &[Message],
+        _tools: Option<&[Tool]>,
+    ) -> Result<ChatResponse, LlmError> {
+        match &self.0 {
+            Ok(text) => Ok(ChatResponse {
+                content: vec![ContentBlock::Text { text: text.clone() }],
+                model: "mock".into(),
+                stop_reason: "end_turn".into(),
+                input_tokens: 0,
+                output_tokens: 0,
+                cache_read_tokens: 0,
+                cache_creation_tokens: 0,
+            }),
+            Err(()) => Err(LlmError::ApiRequest("down".into())),
+        }
+    }
+}
+
+// =============================================================================
+// fold_count
+// =============================================================================
+
+#[test]
+fn fold_count_keeps_short_threads_verbatim() {
+    assert_eq!(fold_count(0), 0);
+    assert_eq!(fold_count(MAX_THREAD_CONTEXT_MESSAGES), 0);
+}
+
+#[test]
+fn fold_count_folds_whole_pairs_down_to_recent_tail() {
+    assert_eq!(fold_count(14), 14 - THREAD_KEEP_RECENT_MESSAGES);
+    assert_eq!(fold_count(15) % 2, 0);
+    assert!(15 - fold_count(15) >= THREAD_KEEP_RECENT_MESSAGES);
+}
+
+// =============================================================================
+// context
+// =============================================================================
+
+#[test]
+fn context_messages_wrap_user_prompts_only() {
+    let messages = context_messages(&turns(1));
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].role, "user");
+    assert!(matches!(&messages[0].content, Content::Text(t) if t == "<user_input>ask 0</user_input>"));
+    assert_eq!(messages[1].role, "assistant");
+    assert!(matches!(&messages[1].content, Content::Text(t) if t == "done 0"));
+}
+
+#[test]
+fn context_messages_clip_long_content() {
+    let long = "x".repeat(MAX_THREAD_MESSAGE_CHARS + 50);
+    let messages = context_messages(&[message(1, "assistant", &long)]);
+    let Content::Text(text) = &messages[0].content else {
+        panic!("expected text content");
+    };
+    assert_eq!(text.chars().count(), MAX_THREAD_MESSAGE_CHARS + 1);
+}
+
+// =============================================================================
+// summarization
+// =============================================================================
+
+#[test]
+fn fallback_summary_appends_transcript_to_previous_summary() {
+    let summary = fallback_summary("Earlier: built a kanban board.", &turns(1));
+    assert_eq!(summary, "Earlier: built a kanban board.\nUser: ask 0\nAssistant: done 0");
+}
+
+#[test]
+fn fallback_summary_keeps_most_recent_text_when_over_budget() {
+    let previous = "p".repeat(MAX_THREAD_SUMMARY_CHARS);
+    let summary = fallback_summary(&previous, &turns(1));
+    assert_eq!(summary.chars().count(), MAX_THREAD_SUMMARY_CHARS);
+    assert!(summary.ends_with("Assistant: done 0"));
+}
+
+#[tokio::test]
+async fn summarize_uses_llm_reply() {
+    let llm: Arc<dyn LlmChat> = Arc::new(FixedLlm(Ok("  Team planned a retro board.  ".into())));
+    let state = test_helpers::test_app_state();
+    let summary = summarize(&state, &llm, Uuid::new_v4(), Uuid::new_v4(), "", &turns(2)).await;
+    assert_eq!(summary, "Team planned a retro board.");
+}
+
+#[tokio::test]
+async fn summarize_falls_back_when_llm_fails() {
+    let llm: Arc<dyn LlmChat> = Arc::new(FixedLlm(Err(())));
+    let state = test_helpers::test_app_state();
+    let summary = summarize(&state, &llm, Uuid::new_v4(), Uuid::new_v4(), "", &turns(1)).await;
+    assert_eq!(summary, "User: ask 0\nAssistant: done 0");
+}
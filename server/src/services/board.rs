@@ -154,6 +154,20 @@ pub struct BoardPreviewObject {
     pub z_index: i32,
 }
 
+/// How a joining client should be brought up to date.
+#[derive(Debug, Clone)]
+pub enum JoinSync {
+    /// Stream every live object; the client replaces its local set.
+    Full(Vec<BoardObject>),
+    /// Stream only objects touched since the client's last known frame sequence.
+    Delta {
+        /// Current state of objects created or updated since the sequence.
+        upserts: Vec<BoardObject>,
+        /// Objects deleted since the sequence.
+        deleted: Vec<Uuid>,
+    },
+}
+
 /// Full object record included in JSONL board exports.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct BoardExportObject {
@@ -724,6 +738,107 @@ pub async fn join_board(
     Ok(objects)
 }
 
+/// Latest persisted frame sequence number for a board, or 0 if none.
+///
+/// # Errors
+///
+/// Returns a database error if the query fails.
+pub async fn latest_frame_seq(pool: &PgPool, board_id: Uuid) -> Result<i64, sqlx::Error> {
+    let seq: Option<i64> = sqlx::query_scalar("SELECT MAX(seq) FROM frames WHERE board_id = $1")
+        .bind(board_id)
+        .fetch_one(pool)
+        .await?;
+    Ok(seq.unwrap_or(0))
+}
+
+/// Syscalls whose done frames carry a batch-style `ops` array of changes.
+pub const OPS_FRAME_SYSCALLS: &[&str] = &["object:batch", "history:undo", "history:redo", "ai:apply", "ai:revert"];
+
+/// IDs of objects created, updated, or deleted after `since_seq`.
+///
+/// Includes every object named in the `ops` array of frames listed in
+/// [`OPS_FRAME_SYSCALLS`].
+///
+/// Returns `None` when the gap is too large to be worth a delta: either more
+/// than `limit` distinct objects changed, or `since_seq` is ahead of the
+/// persisted log (the client's sequence came from a different history).
+///
+/// # Errors
+///
+/// Returns a database error if the query fails.
+pub async fn touched_object_ids_since(
+    pool: &PgPool,
+    board_id: Uuid,
+    since_seq: i64,
+    latest_seq: i64,
+    limit: usize,
+) -> Result<Option<Vec<Uuid>>, sqlx::Error> {
+    if since_seq < 0 || since_seq > latest_seq {
+        return Ok(None);
+    }
+
+    let rows = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT DISTINCT id FROM (
+             SELECT data->>'id' AS id
+             FROM frames
+             WHERE board_id = $1
+               AND seq > $2
+               AND status = 'done'
+               AND syscall IN ('object:create', 'object:update', 'object:delete', 'text:op')
+             UNION ALL
+             SELECT op->>'id' AS id
+             FROM frames, jsonb_array_elements(frames.data->'ops') AS op
+             WHERE board_id = $1
+               AND seq > $2
+               AND status = 'done'
+               AND syscall = ANY($4)
+         ) touched
+         LIMIT $3",
+    )
+    .bind(board_id)
+    .bind(since_seq)
+    .bind(i64::try_from(limit.saturating_add(1)).unwrap_or(i64::MAX))
+    .bind(OPS_FRAME_SYSCALLS)
+    .fetch_all(pool)
+    .await?;
+
+    if rows.len() > limit {
+        return Ok(None);
+    }
+    Ok(Some(
+        rows.into_iter()
+            .flatten()
+            .filter_map(|id| id.parse::<Uuid>().ok())
+            .collect(),
+    ))
+}
+
+/// Decide between a full snapshot and a delta for a joining client.
+///
+/// `touched` is the set of object IDs changed since the client's sequence, or
+/// `None` when no usable delta exists. Touched IDs still live are sent as
+/// upserts; the rest are reported as deleted.
+#[must_use]
+pub fn plan_join_sync(objects: Vec<BoardObject>, touched: Option<Vec<Uuid>>) -> JoinSync {
+    let Some(touched) = touched else {
+        return JoinSync::Full(objects);
+    };
+
+    let mut live = objects
+        .into_iter()
+        .map(|obj| (obj.id, obj))
+        .collect::<HashMap<_, _>>();
+    let mut upserts = Vec::new();
+    let mut deleted = Vec::new();
+    for id in touched {
+        match live.remove(&id) {
+            Some(obj) => upserts.push(obj),
+            None => deleted.push(id),
+        }
+    }
+    JoinSync::Delta { upserts, deleted }
+}
+
 /// Leave a board. Removes the client sender. If last client, flushes
 /// dirty objects and evicts the board state from memory.
 pub async fn part_board(state: &AppState, board_id: Uuid, client_id: Uuid) {
@@ -199,6 +199,51 @@ async fn broadcast_empty_board_is_noop() {
     broadcast(&state, board_id, &frame, None).await;
 }
 
+#[test]
+fn plan_join_sync_without_touched_ids_sends_full_snapshot() {
+    let objects = vec![test_helpers::dummy_object(), test_helpers::dummy_object()];
+
+    match plan_join_sync(objects, None) {
+        JoinSync::Full(objects) => assert_eq!(objects.len(), 2),
+        JoinSync::Delta { .. } => panic!("expected full snapshot"),
+    }
+}
+
+#[test]
+fn plan_join_sync_splits_touched_ids_into_upserts_and_deletes() {
+    let untouched = test_helpers::dummy_object();
+    let edited = test_helpers::dummy_object();
+    let edited_id = edited.id;
+    let deleted_id = Uuid::new_v4();
+
+    match plan_join_sync(vec![untouched, edited], Some(vec![edited_id, deleted_id])) {
+        JoinSync::Delta { upserts, deleted } => {
+            assert_eq!(upserts.len(), 1);
+            assert_eq!(upserts[0].id, edited_id);
+            assert_eq!(deleted, vec![deleted_id]);
+        }
+        JoinSync::Full(_) => panic!("expected delta"),
+    }
+}
+
+#[test]
+fn plan_join_sync_with_no_changes_is_empty_delta() {
+    match plan_join_sync(vec![test_helpers::dummy_object()], Some(Vec::new())) {
+        JoinSync::Delta { upserts, deleted } => {
+            assert!(upserts.is_empty());
+            assert!(deleted.is_empty());
+        }
+        JoinSync::Full(_) => panic!("expected delta"),
+    }
+}
+
+#[test]
+fn ops_frame_syscalls_include_ai_apply_and_revert() {
+    // Both reply with batch-style `ops`, so reconnect deltas must expand them.
+    assert!(OPS_FRAME_SYSCALLS.contains(&"ai:apply"));
+    assert!(OPS_FRAME_SYSCALLS.contains(&"ai:revert"));
+}
+
 #[test]
 fn board_error_code_variants() {
     use crate::frame::ErrorCode;
@@ -372,3 +417,46 @@ async fn part_board_flushes_dirty_object_to_database_on_last_client() {
     assert_eq!(persisted.4, Some(120.0));
     assert_eq!(persisted.5, 2);
 }
+
+#[cfg(feature = "live-db-tests")]
+#[tokio::test]
+#[ignore = "requires TEST_DATABASE_URL/live Postgres"]
+async fn touched_object_ids_since_expands_ops_of_every_batch_syscall() {
+    let pool = integration_pool().await;
+    let owner_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO users (id, name) VALUES ($1, 'Delta Owner')")
+        .bind(owner_id)
+        .execute(&pool)
+        .await
+        .expect("user insert should succeed");
+    let board = create_board(&pool, "Delta Board", owner_id)
+        .await
+        .expect("create_board should succeed");
+    let since = latest_frame_seq(&pool, board.id)
+        .await
+        .expect("latest_frame_seq should succeed");
+
+    let mut expected = Vec::new();
+    for syscall in OPS_FRAME_SYSCALLS {
+        let id = Uuid::new_v4();
+        expected.push(id);
+        let mut data = Data::new();
+        data.insert("ops".into(), serde_json::json!([{ "op": "update", "id": id }]));
+        let mut frame = Frame::request(*syscall, data).with_board_id(board.id);
+        frame.status = crate::frame::Status::Done;
+        crate::services::persistence::persist_frame(&pool, &frame)
+            .await
+            .expect("persist_frame should succeed");
+    }
+
+    let latest = latest_frame_seq(&pool, board.id)
+        .await
+        .expect("latest_frame_seq should succeed");
+    let mut touched = touched_object_ids_since(&pool, board.id, since, latest, 100)
+        .await
+        .expect("touched_object_ids_since should succeed")
+        .expect("delta should be usable");
+    touched.sort();
+    expected.sort();
+    assert_eq!(touched, expected);
+}
@@ -0,0 +1,418 @@
+//! History service — per-user undo and redo of object edits.
+//!
+//! DESIGN
+//! ======
+//! Every object create, update, delete, and batch a user makes is recorded
+//! as one history entry holding the object states before and after. Entries
+//! live on `AppState` keyed by `(board_id, user_id)`, so they outlive the
+//! websocket connection and the board's in-memory session. The persistence
+//! task sweeps out histories idle for longer than [`HISTORY_IDLE_TTL`] on
+//! boards nobody has loaded, which keeps the map bounded.
+//!
+//! Undo applies the inverse of the newest entry field by field. A field is
+//! only reverted while it still holds the value the user's action left
+//! behind; if someone else has changed it since, that field is skipped so an
+//! undo never overwrites a newer edit. The same rule governs deleting an
+//! object the user created (skipped if anyone touched it) and recreating one
+//! they deleted (skipped if it already exists). Whatever the undo actually
+//! changed becomes a redo entry, and redo runs the same machinery in the
+//! other direction. An entry that conflicts entirely is discarded and the
+//! next one is tried.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+use crate::services::object::BatchChange;
+use crate::services::savepoint::same_object_state;
+use crate::state::{AppState, BoardObject, BoardState, FieldVersions};
+
+/// Most undo entries kept per user and board.
+pub const HISTORY_LIMIT: usize = 100;
+/// How long an untouched history outlives its board's in-memory session.
+pub const HISTORY_IDLE_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// Errors returned by history service operations.
+#[derive(Debug, thiserror::Error)]
+pub enum HistoryError {
+    /// The user has no undoable edits on this board.
+    #[error("nothing to undo")]
+    NothingToUndo,
+    /// The user has no redoable edits on this board.
+    #[error("nothing to redo")]
+    NothingToRedo,
+    /// The board has not been loaded into memory yet.
+    #[error("board not loaded: {0}")]
+    BoardNotLoaded(Uuid),
+    /// A Postgres query failed.
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl crate::frame::ErrorCode for HistoryError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::NothingToUndo => "E_NOTHING_TO_UNDO",
+            Self::NothingToRedo => "E_NOTHING_TO_REDO",
+            Self::BoardNotLoaded(_) => "E_BOARD_NOT_LOADED",
+            Self::Database(_) => "E_DATABASE",
+        }
+    }
+}
+
+/// One object change within a history entry.
+#[derive(Debug, Clone)]
+pub enum HistoryChange {
+    /// The object was created; undo deletes it.
+    Created(BoardObject),
+    /// The object was edited; undo restores the edited fields.
+    Updated { before: BoardObject, after: BoardObject },
+    /// The object was deleted; undo recreates it with the same ID.
+    Deleted(BoardObject),
+}
+
+/// Undo and redo stacks for one user on one board.
+#[derive(Debug, Clone)]
+pub struct UserHistory {
+    undo: Vec<Vec<HistoryChange>>,
+    redo: Vec<Vec<HistoryChange>>,
+    /// Last record, undo, or redo; drives the idle sweep.
+    touched_at: Instant,
+}
+
+impl Default for UserHistory {
+    fn default() -> Self {
+        Self { undo: Vec::new(), redo: Vec::new(), touched_at: Instant::now() }
+    }
+}
+
+impl UserHistory {
+    /// Number of entries available to undo.
+    #[must_use]
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Number of entries available to redo.
+    #[must_use]
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Undo,
+    Redo,
+}
+
+// =============================================================================
+// RECORD
+// =============================================================================
+
+/// Snapshot the current state of objects an edit is about to touch.
+pub async fn objects_before(state: &AppState, board_id: Uuid, ids: &[Uuid]) -> HashMap<Uuid, BoardObject> {
+    let boards = state.boards.read().await;
+    let Some(board) = boards.get(&board_id) else {
+        return HashMap::new();
+    };
+    ids.iter()
+        .filter_map(|id| board.objects.get(id).map(|obj| (*id, obj.clone())))
+        .collect()
+}
+
+/// Pair applied changes with the object states they replaced.
+///
+/// `before` holds the pre-edit state of every updated or deleted object;
+/// repeated changes to one object chain through the intermediate states.
+#[must_use]
+pub fn changes_from_batch(mut before: HashMap<Uuid, BoardObject>, changes: &[BatchChange]) -> Vec<HistoryChange> {
+    let mut recorded = Vec::with_capacity(changes.len());
+    for change in changes {
+        match change {
+            BatchChange::Created(obj) => {
+                before.insert(obj.id, obj.clone());
+                recorded.push(HistoryChange::Created(obj.clone()));
+            }
+            BatchChange::Updated(obj) => {
+                if let Some(prev) = before.insert(obj.id, obj.clone()) {
+                    recorded.push(HistoryChange::Updated { before: prev, after: obj.clone() });
+                }
+            }
+            BatchChange::Deleted(id) => {
+                if let Some(prev) = before.remove(id) {
+                    recorded.push(HistoryChange::Deleted(prev));
+                }
+            }
+        }
+    }
+    recorded
+}
+
+/// Record a user's edit as a new undo entry and clear their redo stack.
+pub async fn record(state: &AppState, board_id: Uuid, user_id: Uuid, changes: Vec<HistoryChange>) {
+    if changes.is_empty() {
+        return;
+    }
+    let mut histories = state.histories.write().await;
+    let history = histories.entry((board_id, user_id)).or_default();
+    history.touched_at = Instant::now();
+    history.undo.push(changes);
+    if history.undo.len() > HISTORY_LIMIT {
+        let excess = history.undo.len() - HISTORY_LIMIT;
+        history.undo.drain(..excess);
+    }
+    history.redo.clear();
+}
+
+/// Drop histories untouched since `now - HISTORY_IDLE_TTL` whose board is
+/// not loaded. Histories on loaded boards are kept however old they are.
+pub async fn sweep_idle(state: &AppState, now: Instant) {
+    let loaded = state
+        .boards
+        .read()
+        .await
+        .keys()
+        .copied()
+        .collect::<HashSet<_>>();
+    let mut histories = state.histories.write().await;
+    let before = histories.len();
+    histories.retain(|(board_id, _), history| {
+        loaded.contains(board_id) || now.saturating_duration_since(history.touched_at) < HISTORY_IDLE_TTL
+    });
+    let dropped = before - histories.len();
+    if dropped > 0 {
+        tracing::info!(dropped, remaining = histories.len(), "history: swept idle undo histories");
+    }
+}
+
+// =============================================================================
+// UNDO / REDO
+// =============================================================================
+
+/// Revert the user's newest edit that still applies.
+///
+/// # Errors
+///
+/// Returns `NothingToUndo` when no entry applies, `BoardNotLoaded` if the
+/// board is not in memory, or `Database` if removing rows fails.
+pub async fn undo(state: &AppState, board_id: Uuid, user_id: Uuid) -> Result<Vec<BatchChange>, HistoryError> {
+    step(state, board_id, user_id, Direction::Undo).await
+}
+
+/// Reapply the user's most recently undone edit that still applies.
+///
+/// # Errors
+///
+/// Returns `NothingToRedo` when no entry applies, `BoardNotLoaded` if the
+/// board is not in memory, or `Database` if removing rows fails.
+pub async fn redo(state: &AppState, board_id: Uuid, user_id: Uuid) -> Result<Vec<BatchChange>, HistoryError> {
+    step(state, board_id, user_id, Direction::Redo).await
+}
+
+async fn step(
+    state: &AppState,
+    board_id: Uuid,
+    user_id: Uuid,
+    direction: Direction,
+) -> Result<Vec<BatchChange>, HistoryError> {
+    let key = (board_id, user_id);
+    loop {
+        let entry = {
+            let mut histories = state.histories.write().await;
+            histories.get_mut(&key).and_then(|history| {
+                history.touched_at = Instant::now();
+                match direction {
+                    Direction::Undo => history.undo.pop(),
+                    Direction::Redo => history.redo.pop(),
+                }
+            })
+        };
+        let Some(entry) = entry else {
+            return Err(match direction {
+                Direction::Undo => HistoryError::NothingToUndo,
+                Direction::Redo => HistoryError::NothingToRedo,
+            });
+        };
+
+        let applied = {
+            let mut boards = state.boards.write().await;
+            boards
+                .get_mut(&board_id)
+                .map(|board| apply_inverse(board, &entry))
+        };
+        let Some((changes, inverse)) = applied else {
+            // Put the entry back so it is not lost while the board is unloaded.
+            let mut histories = state.histories.write().await;
+            let history = histories.entry(key).or_default();
+            match direction {
+                Direction::Undo => history.undo.push(entry),
+                Direction::Redo => history.redo.push(entry),
+            }
+            return Err(HistoryError::BoardNotLoaded(board_id));
+        };
+        if changes.is_empty() {
+            continue;
+        }
+
+        let deleted = changes
+            .iter()
+            .filter_map(|change| match change {
+                BatchChange::Deleted(id) => Some(*id),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if !deleted.is_empty() {
+            sqlx::query("DELETE FROM board_objects WHERE board_id = $1 AND id = ANY($2)")
+                .bind(board_id)
+                .bind(&deleted)
+                .execute(&state.pool)
+                .await?;
+        }
+
+        let mut histories = state.histories.write().await;
+        let history = histories.entry(key).or_default();
+        match direction {
+            Direction::Undo => history.redo.push(inverse),
+            Direction::Redo => history.undo.push(inverse),
+        }
+        return Ok(changes);
+    }
+}
+
+/// Apply the inverse of `entry`, newest change first, skipping conflicts.
+///
+/// Returns the applied changes for broadcast and the entry that reverses them.
+fn apply_inverse(board: &mut BoardState, entry: &[HistoryChange]) -> (Vec<BatchChange>, Vec<HistoryChange>) {
+    let mut changes = Vec::new();
+    let mut inverse = Vec::new();
+
+    for change in entry.iter().rev() {
+        match change {
+            HistoryChange::Created(created) => {
+                let Some(current) = board.objects.get(&created.id) else {
+                    continue;
+                };
+                if !same_object_state(current, created) {
+                    continue;
+                }
+                let removed = current.clone();
+                board.objects.remove(&created.id);
+                board.dirty.remove(&created.id);
+                board.field_versions.remove(&created.id);
+                board.text_logs.remove(&created.id);
+                changes.push(BatchChange::Deleted(created.id));
+                inverse.push(HistoryChange::Deleted(removed));
+            }
+            HistoryChange::Updated { before, after } => {
+                let Some(current) = board.objects.get_mut(&after.id) else {
+                    continue;
+                };
+                let previous = current.clone();
+                let fields = revert_fields(current, after, before);
+                if fields.is_empty() {
+                    continue;
+                }
+                current.version += 1;
+                let versions = board
+                    .field_versions
+                    .entry(after.id)
+                    .or_insert_with(|| FieldVersions::starting_at(previous.version));
+                for field in fields {
+                    versions.fields.insert(field, current.version);
+                }
+                board.dirty.insert(after.id);
+                changes.push(BatchChange::Updated(current.clone()));
+                inverse.push(HistoryChange::Updated { before: previous, after: current.clone() });
+            }
+            HistoryChange::Deleted(deleted) => {
+                if board.objects.contains_key(&deleted.id) {
+                    continue;
+                }
+                let mut restored = deleted.clone();
+                restored.version += 1;
+                board.objects.insert(restored.id, restored.clone());
+                board.dirty.insert(restored.id);
+                changes.push(BatchChange::Created(restored.clone()));
+                inverse.push(HistoryChange::Created(restored));
+            }
+        }
+    }
+
+    // The inverse entry is undone newest-first too, so keep application order.
+    inverse.reverse();
+    (changes, inverse)
+}
+
+/// Move fields that differ between `from` and `to` back to `to`.
+///
+/// Only fields still equal to `from` in `current` are written. Returns the
+/// version-tracking names of the fields that changed.
+fn revert_fields(current: &mut BoardObject, from: &BoardObject, to: &BoardObject) -> Vec<String> {
+    let mut fields = Vec::new();
+
+    let geometry = |obj: &BoardObject| {
+        (
+            obj.x.to_bits(),
+            obj.y.to_bits(),
+            obj.width.map(f64::to_bits),
+            obj.height.map(f64::to_bits),
+        )
+    };
+    if geometry(from) != geometry(to) && geometry(current) == geometry(from) {
+        current.x = to.x;
+        current.y = to.y;
+        current.width = to.width;
+        current.height = to.height;
+        fields.push("geometry".to_owned());
+    }
+    if from.rotation.to_bits() != to.rotation.to_bits() && current.rotation.to_bits() == from.rotation.to_bits() {
+        current.rotation = to.rotation;
+        fields.push("rotation".to_owned());
+    }
+    if from.z_index != to.z_index && current.z_index == from.z_index {
+        current.z_index = to.z_index;
+        fields.push("z_index".to_owned());
+    }
+    if from.group_id != to.group_id && current.group_id == from.group_id {
+        current.group_id = to.group_id;
+        fields.push("group_id".to_owned());
+    }
+
+    match (from.props.as_object(), to.props.as_object(), current.props.as_object_mut()) {
+        (Some(from_props), Some(to_props), Some(current_props)) => {
+            let mut keys = from_props.keys().chain(to_props.keys()).collect::<Vec<_>>();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let was = from_props.get(key);
+                let target = to_props.get(key);
+                if was == target || current_props.get(key) != was {
+                    continue;
+                }
+                match target {
+                    Some(value) => current_props.insert(key.clone(), value.clone()),
+                    None => current_props.remove(key),
+                };
+                fields.push(format!("props.{key}"));
+            }
+        }
+        _ => {
+            if from.props != to.props && current.props == from.props {
+                current.props = to.props.clone();
+                fields.push("props".to_owned());
+            }
+        }
+    }
+
+    fields
+}
+
+#[cfg(test)]
+#[path = "history_test.rs"]
+mod tests;
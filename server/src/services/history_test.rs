@@ -0,0 +1,195 @@
+use super::*;
+use crate::services::object::{BatchOp, apply_batch};
+use crate::state::test_helpers;
+
+async fn update(state: &AppState, board_id: Uuid, user_id: Uuid, id: Uuid, version: i32, fields: serde_json::Value) {
+    let mut updates: crate::frame::Data = fields
+        .as_object()
+        .map(|map| map.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default();
+    updates.insert("id".into(), serde_json::json!(id));
+    let before = objects_before(state, board_id, &[id]).await;
+    let changes = apply_batch(state, board_id, &[BatchOp::Update { id, version, updates }], None)
+        .await
+        .expect("update should apply");
+    record(state, board_id, user_id, changes_from_batch(before, &changes)).await;
+}
+
+async fn object(state: &AppState, board_id: Uuid, id: Uuid) -> Option<BoardObject> {
+    let boards = state.boards.read().await;
+    boards.get(&board_id)?.objects.get(&id).cloned()
+}
+
+#[tokio::test]
+async fn undo_reverts_own_update_and_redo_reapplies_it() {
+    let state = test_helpers::test_app_state();
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let user_id = Uuid::new_v4();
+
+    update(&state, board_id, user_id, obj_id, 1, serde_json::json!({ "x": 500.0 })).await;
+
+    let undone = undo(&state, board_id, user_id).await.expect("undo");
+    assert_eq!(undone.len(), 1);
+    let after_undo = object(&state, board_id, obj_id).await.expect("object");
+    assert!((after_undo.x - 100.0).abs() < f64::EPSILON);
+    assert_eq!(after_undo.version, 3);
+
+    redo(&state, board_id, user_id).await.expect("redo");
+    let after_redo = object(&state, board_id, obj_id).await.expect("object");
+    assert!((after_redo.x - 500.0).abs() < f64::EPSILON);
+
+    assert!(matches!(
+        redo(&state, board_id, user_id).await,
+        Err(HistoryError::NothingToRedo)
+    ));
+}
+
+#[tokio::test]
+async fn undo_skips_fields_changed_by_someone_else() {
+    let state = test_helpers::test_app_state();
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let alice = Uuid::new_v4();
+    let bob = Uuid::new_v4();
+
+    update(
+        &state,
+        board_id,
+        alice,
+        obj_id,
+        1,
+        serde_json::json!({ "x": 500.0, "props": { "text": "alice" } }),
+    )
+    .await;
+    update(
+        &state,
+        board_id,
+        bob,
+        obj_id,
+        2,
+        serde_json::json!({ "props": { "text": "bob" } }),
+    )
+    .await;
+
+    undo(&state, board_id, alice).await.expect("undo");
+    let current = object(&state, board_id, obj_id).await.expect("object");
+    assert!((current.x - 100.0).abs() < f64::EPSILON, "alice's move is reverted");
+    assert_eq!(current.props["text"], "bob", "bob's newer text survives");
+}
+
+#[tokio::test]
+async fn undo_only_touches_the_requesting_users_history() {
+    let state = test_helpers::test_app_state();
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let alice = Uuid::new_v4();
+    let bob = Uuid::new_v4();
+
+    update(&state, board_id, bob, obj_id, 1, serde_json::json!({ "rotation": 45.0 })).await;
+
+    assert!(matches!(undo(&state, board_id, alice).await, Err(HistoryError::NothingToUndo)));
+    let current = object(&state, board_id, obj_id).await.expect("object");
+    assert!((current.rotation - 45.0).abs() < f64::EPSILON);
+}
+
+#[tokio::test]
+async fn undo_of_delete_recreates_object_with_same_id() {
+    let state = test_helpers::test_app_state();
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let user_id = Uuid::new_v4();
+
+    // Remove from memory directly; `delete_object` would touch Postgres.
+    let before = objects_before(&state, board_id, &[obj_id]).await;
+    state
+        .boards
+        .write()
+        .await
+        .get_mut(&board_id)
+        .expect("board")
+        .objects
+        .remove(&obj_id);
+    record(
+        &state,
+        board_id,
+        user_id,
+        changes_from_batch(before, &[BatchChange::Deleted(obj_id)]),
+    )
+    .await;
+
+    let changes = undo(&state, board_id, user_id).await.expect("undo");
+    assert!(matches!(&changes[..], [BatchChange::Created(obj)] if obj.id == obj_id));
+    assert!(object(&state, board_id, obj_id).await.is_some());
+}
+
+#[tokio::test]
+async fn fully_conflicting_entry_is_discarded_for_the_next_one() {
+    let state = test_helpers::test_app_state();
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let alice = Uuid::new_v4();
+    let bob = Uuid::new_v4();
+
+    update(&state, board_id, alice, obj_id, 1, serde_json::json!({ "rotation": 10.0 })).await;
+    update(&state, board_id, alice, obj_id, 2, serde_json::json!({ "x": 300.0 })).await;
+    update(&state, board_id, bob, obj_id, 3, serde_json::json!({ "x": 900.0 })).await;
+
+    undo(&state, board_id, alice).await.expect("undo");
+    let current = object(&state, board_id, obj_id).await.expect("object");
+    assert!((current.x - 900.0).abs() < f64::EPSILON);
+    assert!(current.rotation.abs() < f64::EPSILON, "older rotation entry was undone instead");
+
+    let histories = state.histories.read().await;
+    let history = histories.get(&(board_id, alice)).expect("history");
+    assert_eq!(history.undo_len(), 0);
+    assert_eq!(history.redo_len(), 1);
+}
+
+#[tokio::test]
+async fn new_edit_clears_redo_stack() {
+    let state = test_helpers::test_app_state();
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+    let user_id = Uuid::new_v4();
+
+    update(&state, board_id, user_id, obj_id, 1, serde_json::json!({ "x": 1.0 })).await;
+    undo(&state, board_id, user_id).await.expect("undo");
+    update(&state, board_id, user_id, obj_id, 3, serde_json::json!({ "y": 2.0 })).await;
+
+    assert!(matches!(
+        redo(&state, board_id, user_id).await,
+        Err(HistoryError::NothingToRedo)
+    ));
+}
+
+#[tokio::test]
+async fn sweep_idle_drops_only_stale_histories_of_unloaded_boards() {
+    let state = test_helpers::test_app_state();
+    let loaded = test_helpers::seed_board(&state).await;
+    let unloaded = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    for board_id in [loaded, unloaded] {
+        record(
+            &state,
+            board_id,
+            user_id,
+            vec![HistoryChange::Created(test_helpers::dummy_object())],
+        )
+        .await;
+    }
+
+    sweep_idle(&state, Instant::now()).await;
+    assert_eq!(state.histories.read().await.len(), 2, "fresh histories survive eviction");
+
+    sweep_idle(&state, Instant::now() + HISTORY_IDLE_TTL + Duration::from_secs(1)).await;
+    let histories = state.histories.read().await;
+    assert!(histories.contains_key(&(loaded, user_id)));
+    assert!(!histories.contains_key(&(unloaded, user_id)));
+}
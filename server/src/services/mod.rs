@@ -6,11 +6,19 @@
 //! handlers can stay focused on protocol translation and auth plumbing.
 
 pub mod ai;
+pub mod ai_thread;
 pub mod auth;
 pub mod board;
 pub mod email_auth;
+pub mod history;
 pub mod object;
+pub mod pdf;
 pub mod persistence;
+pub mod raster;
+pub mod revert;
 pub mod savepoint;
+pub mod scene;
 pub mod session;
+pub mod text;
 pub mod tool_syscall;
+pub mod usage;
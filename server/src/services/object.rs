@@ -4,13 +4,28 @@
 //! ======
 //! Object mutations update in-memory state immediately, mark the object
 //! as dirty for debounced persistence, and return the updated object for
-//! broadcast. LWW conflict resolution: incoming version must be >= current
-//! version, otherwise the update is rejected as stale.
+//! broadcast.
+//!
+//! LWW conflict resolution is per field rather than per object. Each field
+//! (geometry, rotation, z-index, group, and every props key) remembers the
+//! object version that last wrote it. An update only writes fields whose
+//! value actually changes, and only if its base version is at least that
+//! field's version; stale fields are dropped so concurrent edits to
+//! different fields both survive. The update is rejected as stale only when
+//! every field it would change is stale. Props merge per key, and a `null`
+//! value deletes the key.
+//!
+//! Batches apply a list of creates, updates, and deletes under a single
+//! `boards` write lock. Every operation is validated against a staged copy
+//! first, so one stale version or missing object rejects the whole batch
+//! and nothing is applied.
+
+use std::collections::HashMap;
 
 use uuid::Uuid;
 
 use crate::frame::Data;
-use crate::state::{AppState, BoardObject};
+use crate::state::{AppState, BoardObject, FieldVersions};
 
 // =============================================================================
 // TYPES
@@ -33,6 +48,34 @@ pub enum ObjectError {
     Database(#[from] sqlx::Error),
 }
 
+/// One operation inside an `object:batch` request.
+#[derive(Debug, Clone)]
+pub enum BatchOp {
+    /// Create a new object; the server assigns the ID and z-index.
+    Create {
+        kind: String,
+        x: f64,
+        y: f64,
+        width: Option<f64>,
+        height: Option<f64>,
+        rotation: f64,
+        props: serde_json::Value,
+        group_id: Option<Uuid>,
+    },
+    /// Update an existing object using the same field map as `update_object`.
+    Update { id: Uuid, version: i32, updates: Data },
+    /// Delete an existing object.
+    Delete { id: Uuid },
+}
+
+/// Outcome of one applied batch operation, reported in request order.
+#[derive(Debug, Clone)]
+pub enum BatchChange {
+    Created(BoardObject),
+    Updated(BoardObject),
+    Deleted(Uuid),
+}
+
 impl crate::frame::ErrorCode for ObjectError {
     fn error_code(&self) -> &'static str {
         match self {
@@ -101,11 +144,13 @@ pub async fn create_object(
 // UPDATE
 // =============================================================================
 
-/// Update an existing object with LWW conflict resolution.
+/// Update an existing object with field-level LWW conflict resolution.
 ///
 /// # Errors
 ///
-/// Returns `StaleUpdate` if `incoming_version < current.version`.
+/// Returns `StaleUpdate` if `incoming_version` predates field tracking for the
+/// object, or if every field the update would change was written by a newer
+/// version.
 pub async fn update_object(
     state: &AppState,
     board_id: Uuid,
@@ -121,28 +166,133 @@ pub async fn update_object(
         .objects
         .get_mut(&object_id)
         .ok_or(ObjectError::NotFound(object_id))?;
+    let versions = board
+        .field_versions
+        .entry(object_id)
+        .or_insert_with(|| FieldVersions::starting_at(obj.version));
+
+    apply_update(obj, versions, updates, incoming_version)?;
+    board.dirty.insert(object_id);
 
-    // LWW: reject stale updates.
-    if incoming_version < obj.version {
-        return Err(ObjectError::StaleUpdate { incoming: incoming_version, current: obj.version });
+    Ok(obj.clone())
+}
+
+/// Apply an update map to an object in place with field-level LWW.
+///
+/// Bumps `version` and records the new version against every written field.
+/// Leaves the object untouched when the update is rejected.
+fn apply_update(
+    obj: &mut BoardObject,
+    versions: &mut FieldVersions,
+    updates: &Data,
+    incoming_version: i32,
+) -> Result<(), ObjectError> {
+    let stale = || ObjectError::StaleUpdate { incoming: incoming_version, current: obj.version };
+    if incoming_version < versions.floor {
+        return Err(stale());
     }
 
-    // Apply updates from data map.
-    if let Some(x) = updates.get("x").and_then(serde_json::Value::as_f64) {
-        obj.x = x;
+    let writes = plan_field_writes(obj, versions, updates, incoming_version);
+    if writes.fields.is_empty() && writes.stale {
+        return Err(stale());
     }
-    if let Some(y) = updates.get("y").and_then(serde_json::Value::as_f64) {
+
+    let next_version = obj.version + 1;
+    for field in &writes.fields {
+        versions.fields.insert(field.clone(), next_version);
+    }
+    if let Some((x, y, width, height)) = writes.geometry {
+        obj.x = x;
         obj.y = y;
+        obj.width = width;
+        obj.height = height;
+    }
+    if let Some(rotation) = writes.rotation {
+        obj.rotation = rotation;
+    }
+    if let Some(z_index) = writes.z_index {
+        obj.z_index = z_index;
     }
-    if let Some(w) = updates.get("width").and_then(serde_json::Value::as_f64) {
-        obj.width = Some(w);
+    if let Some(group_id) = writes.group_id {
+        obj.group_id = group_id;
     }
-    if let Some(h) = updates.get("height").and_then(serde_json::Value::as_f64) {
-        obj.height = Some(h);
+    if let Some(props) = writes.props {
+        obj.props = props;
     }
-    if let Some(r) = updates.get("rotation").and_then(serde_json::Value::as_f64) {
-        obj.rotation = r;
+    obj.version = next_version;
+    Ok(())
+}
+
+/// Field values an update is allowed to write, computed before mutating.
+#[derive(Default)]
+#[allow(clippy::option_option)]
+struct FieldWrites {
+    /// Version-tracking names of the fields being written.
+    fields: Vec<String>,
+    /// At least one changed field was skipped because a newer version wrote it.
+    stale: bool,
+    geometry: Option<(f64, f64, Option<f64>, Option<f64>)>,
+    rotation: Option<f64>,
+    z_index: Option<i32>,
+    group_id: Option<Option<Uuid>>,
+    props: Option<serde_json::Value>,
+}
+
+impl FieldWrites {
+    /// Admit a changed field if `incoming_version` is not older than its last write.
+    fn admit(&mut self, versions: &FieldVersions, field: &str, incoming_version: i32) -> bool {
+        if incoming_version >= versions.version_of(field) {
+            self.fields.push(field.to_string());
+            true
+        } else {
+            self.stale = true;
+            false
+        }
     }
+}
+
+fn same_f64(a: f64, b: f64) -> bool {
+    a.to_bits() == b.to_bits()
+}
+
+fn same_opt_f64(a: Option<f64>, b: Option<f64>) -> bool {
+    a.map(f64::to_bits) == b.map(f64::to_bits)
+}
+
+/// Decide which recognised fields of an update map change the object and may be written.
+fn plan_field_writes(
+    obj: &BoardObject,
+    versions: &FieldVersions,
+    updates: &Data,
+    incoming_version: i32,
+) -> FieldWrites {
+    let mut writes = FieldWrites::default();
+
+    let x = updates.get("x").and_then(serde_json::Value::as_f64);
+    let y = updates.get("y").and_then(serde_json::Value::as_f64);
+    let width = updates.get("width").and_then(serde_json::Value::as_f64);
+    let height = updates.get("height").and_then(serde_json::Value::as_f64);
+    let geometry = (
+        x.unwrap_or(obj.x),
+        y.unwrap_or(obj.y),
+        width.or(obj.width),
+        height.or(obj.height),
+    );
+    let geometry_changed = !same_f64(geometry.0, obj.x)
+        || !same_f64(geometry.1, obj.y)
+        || !same_opt_f64(geometry.2, obj.width)
+        || !same_opt_f64(geometry.3, obj.height);
+    if geometry_changed && writes.admit(versions, "geometry", incoming_version) {
+        writes.geometry = Some(geometry);
+    }
+
+    if let Some(r) = updates.get("rotation").and_then(serde_json::Value::as_f64)
+        && !same_f64(r, obj.rotation)
+        && writes.admit(versions, "rotation", incoming_version)
+    {
+        writes.rotation = Some(r);
+    }
+
     #[allow(clippy::cast_possible_truncation)]
     if let Some(z) = updates.get("z_index").and_then(|value| {
         value.as_i64().or_else(|| {
@@ -153,24 +303,63 @@ pub async fn update_object(
         })
     }) {
         #[allow(clippy::cast_possible_truncation)]
-        {
-            obj.z_index = z as i32;
+        let z = z as i32;
+        if z != obj.z_index && writes.admit(versions, "z_index", incoming_version) {
+            writes.z_index = Some(z);
         }
     }
-    if let Some(p) = updates.get("props") {
-        obj.props = p.clone();
-    }
+
     if updates.get("group_id").is_some() {
-        obj.group_id = updates
+        let group_id = updates
             .get("group_id")
             .and_then(serde_json::Value::as_str)
             .and_then(|s| Uuid::parse_str(s).ok());
+        if group_id != obj.group_id && writes.admit(versions, "group_id", incoming_version) {
+            writes.group_id = Some(group_id);
+        }
     }
 
-    obj.version += 1;
-    board.dirty.insert(object_id);
+    if let Some(incoming) = updates.get("props") {
+        writes.props = plan_props_merge(obj, versions, incoming, incoming_version, &mut writes);
+    }
 
-    Ok(obj.clone())
+    writes
+}
+
+/// Merge incoming props per key, treating `null` as a delete.
+///
+/// Returns the merged props when at least one key is written. A non-object
+/// payload replaces props wholesale under the single `"props"` field.
+fn plan_props_merge(
+    obj: &BoardObject,
+    versions: &FieldVersions,
+    incoming: &serde_json::Value,
+    incoming_version: i32,
+    writes: &mut FieldWrites,
+) -> Option<serde_json::Value> {
+    let Some(incoming) = incoming.as_object() else {
+        return (*incoming != obj.props && writes.admit(versions, "props", incoming_version)).then(|| incoming.clone());
+    };
+
+    let mut merged = obj.props.as_object().cloned().unwrap_or_default();
+    let mut wrote = false;
+    for (key, value) in incoming {
+        let changed = if value.is_null() {
+            merged.contains_key(key)
+        } else {
+            merged.get(key) != Some(value)
+        };
+        if !changed || !writes.admit(versions, &format!("props.{key}"), incoming_version) {
+            continue;
+        }
+        if value.is_null() {
+            merged.remove(key);
+        } else {
+            merged.insert(key.clone(), value.clone());
+        }
+        wrote = true;
+    }
+    wrote.then_some(serde_json::Value::Object(merged))
 }
 
 // =============================================================================
@@ -192,6 +381,11 @@ pub async fn delete_object(state: &AppState, board_id: Uuid, object_id: Uuid) ->
         return Err(ObjectError::NotFound(object_id));
     }
     board.dirty.remove(&object_id);
+    board.field_versions.remove(&object_id);
+    board.text_logs.remove(&object_id);
+    if board.scratch {
+        return Ok(());
+    }
 
     // Delete from Postgres immediately (not deferred).
     sqlx::query("DELETE FROM board_objects WHERE id = $1")
@@ -202,6 +396,121 @@ pub async fn delete_object(state: &AppState, board_id: Uuid, object_id: Uuid) ->
     Ok(())
 }
 
+// =============================================================================
+// BATCH
+// =============================================================================
+
+/// Apply a batch of creates, updates, and deletes atomically.
+///
+/// All operations run against a staged overlay of the touched objects while
+/// the `boards` write lock is held. If any operation fails its LWW check or
+/// targets a missing object, the error is returned and the board is left
+/// untouched. Later operations see the effects of earlier ones, so changing
+/// the same field twice in one batch requires the bumped version the second
+/// time.
+///
+/// # Errors
+///
+/// Returns `BoardNotLoaded`, `NotFound`, or `StaleUpdate` without applying
+/// anything, or `Database` if removing deleted rows from Postgres fails.
+pub async fn apply_batch(
+    state: &AppState,
+    board_id: Uuid,
+    ops: &[BatchOp],
+    created_by: Option<Uuid>,
+) -> Result<Vec<BatchChange>, ObjectError> {
+    let mut boards = state.boards.write().await;
+    let board = boards
+        .get_mut(&board_id)
+        .ok_or(ObjectError::BoardNotLoaded(board_id))?;
+
+    // Stage every operation first; `None` marks a staged delete.
+    let mut staged: HashMap<Uuid, Option<(BoardObject, FieldVersions)>> = HashMap::new();
+    let mut changes = Vec::with_capacity(ops.len());
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    let mut next_z_index = board.objects.len() as i32;
+
+    for op in ops {
+        match op {
+            BatchOp::Create { kind, x, y, width, height, rotation, props, group_id } => {
+                let obj = BoardObject {
+                    id: Uuid::new_v4(),
+                    board_id,
+                    kind: kind.clone(),
+                    x: *x,
+                    y: *y,
+                    width: *width,
+                    height: *height,
+                    rotation: *rotation,
+                    z_index: next_z_index,
+                    props: props.clone(),
+                    created_by,
+                    version: 1,
+                    group_id: *group_id,
+                };
+                next_z_index += 1;
+                staged.insert(obj.id, Some((obj.clone(), FieldVersions::starting_at(obj.version))));
+                changes.push(BatchChange::Created(obj));
+            }
+            BatchOp::Update { id, version, updates } => {
+                let current = match staged.get(id) {
+                    Some(entry) => entry.clone(),
+                    None => board.objects.get(id).map(|obj| {
+                        let versions = board
+                            .field_versions
+                            .get(id)
+                            .cloned()
+                            .unwrap_or_else(|| FieldVersions::starting_at(obj.version));
+                        (obj.clone(), versions)
+                    }),
+                };
+                let (mut obj, mut versions) = current.ok_or(ObjectError::NotFound(*id))?;
+                apply_update(&mut obj, &mut versions, updates, *version)?;
+                staged.insert(*id, Some((obj.clone(), versions)));
+                changes.push(BatchChange::Updated(obj));
+            }
+            BatchOp::Delete { id } => {
+                let exists = match staged.get(id) {
+                    Some(entry) => entry.is_some(),
+                    None => board.objects.contains_key(id),
+                };
+                if !exists {
+                    return Err(ObjectError::NotFound(*id));
+                }
+                staged.insert(*id, None);
+                changes.push(BatchChange::Deleted(*id));
+            }
+        }
+    }
+
+    // Commit the staged overlay.
+    let mut deleted = Vec::new();
+    for (id, entry) in staged {
+        if let Some((obj, versions)) = entry {
+            board.dirty.insert(id);
+            board.objects.insert(id, obj);
+            board.field_versions.insert(id, versions);
+        } else {
+            board.objects.remove(&id);
+            board.dirty.remove(&id);
+            board.field_versions.remove(&id);
+            board.text_logs.remove(&id);
+            deleted.push(id);
+        }
+    }
+
+    // Deletes go to Postgres immediately, matching `delete_object`.
+    if !deleted.is_empty() && !board.scratch {
+        sqlx::query("DELETE FROM board_objects WHERE board_id = $1 AND id = ANY($2)")
+            .bind(board_id)
+            .bind(&deleted)
+            .execute(&state.pool)
+            .await?;
+    }
+
+    Ok(changes)
+}
+
 #[cfg(test)]
 #[path = "object_test.rs"]
 mod tests;
@@ -420,7 +420,7 @@ async fn update_object_group_id_null_clears() {
 }
 
 #[tokio::test]
-async fn update_object_props_replaces_entirely() {
+async fn update_object_props_merges_per_key() {
     let state = test_helpers::test_app_state();
     let board_id = test_helpers::seed_board(&state).await;
     let obj = create_object(
@@ -445,12 +445,107 @@ async fn update_object_props_replaces_entirely() {
         .await
         .unwrap();
     assert_eq!(updated.props.get("text").unwrap().as_str().unwrap(), "new");
-    assert!(
-        updated.props.get("color").is_none(),
-        "old key 'color' should be gone after full props replace"
+    assert_eq!(
+        updated.props.get("color").unwrap().as_str().unwrap(),
+        "#FF0000",
+        "keys absent from the update should be kept"
     );
 }
 
+#[tokio::test]
+async fn update_object_props_null_deletes_key() {
+    let state = test_helpers::test_app_state();
+    let mut obj = test_helpers::dummy_object();
+    obj.props = serde_json::json!({"text": "hi", "color": "#FF0000"});
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+
+    let mut data = Data::new();
+    data.insert("props".into(), serde_json::json!({"color": null}));
+    let updated = update_object(&state, board_id, obj_id, &data, 1)
+        .await
+        .unwrap();
+    assert!(updated.props.get("color").is_none());
+    assert_eq!(updated.props.get("text").unwrap().as_str().unwrap(), "hi");
+}
+
+#[tokio::test]
+async fn update_object_concurrent_edits_to_different_props_both_survive() {
+    let state = test_helpers::test_app_state();
+    let mut obj = test_helpers::dummy_object();
+    obj.props = serde_json::json!({"text": "old", "fill": "#FFFFFF"});
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+
+    // Both users start from version 1 and send their whole props blob.
+    let mut fill_edit = Data::new();
+    fill_edit.insert("props".into(), serde_json::json!({"text": "old", "fill": "#000000"}));
+    let mut text_edit = Data::new();
+    text_edit.insert("props".into(), serde_json::json!({"text": "new", "fill": "#FFFFFF"}));
+
+    update_object(&state, board_id, obj_id, &fill_edit, 1)
+        .await
+        .unwrap();
+    let merged = update_object(&state, board_id, obj_id, &text_edit, 1)
+        .await
+        .unwrap();
+
+    assert_eq!(merged.version, 3);
+    assert_eq!(merged.props.get("fill").unwrap().as_str().unwrap(), "#000000");
+    assert_eq!(merged.props.get("text").unwrap().as_str().unwrap(), "new");
+}
+
+#[tokio::test]
+async fn update_object_concurrent_edits_to_same_prop_reject_loser() {
+    let state = test_helpers::test_app_state();
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+
+    let mut first = Data::new();
+    first.insert("props".into(), serde_json::json!({"color": "#111111"}));
+    let mut second = Data::new();
+    second.insert("props".into(), serde_json::json!({"color": "#222222"}));
+
+    update_object(&state, board_id, obj_id, &first, 1)
+        .await
+        .unwrap();
+    let result = update_object(&state, board_id, obj_id, &second, 1).await;
+    assert!(matches!(
+        result.unwrap_err(),
+        ObjectError::StaleUpdate { incoming: 1, current: 2 }
+    ));
+
+    let boards = state.boards.read().await;
+    let after = boards.get(&board_id).unwrap().objects.get(&obj_id).unwrap();
+    assert_eq!(after.props.get("color").unwrap().as_str().unwrap(), "#111111");
+}
+
+#[tokio::test]
+async fn update_object_geometry_and_props_edits_both_survive() {
+    let state = test_helpers::test_app_state();
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+
+    let mut moved = Data::new();
+    moved.insert("x".into(), serde_json::json!(500.0));
+    let mut recolored = Data::new();
+    recolored.insert("x".into(), serde_json::json!(100.0));
+    recolored.insert("props".into(), serde_json::json!({"color": "#00FF00"}));
+
+    update_object(&state, board_id, obj_id, &moved, 1)
+        .await
+        .unwrap();
+    let updated = update_object(&state, board_id, obj_id, &recolored, 1)
+        .await
+        .unwrap();
+
+    // The stale geometry is dropped; the fresh color is applied.
+    assert!((updated.x - 500.0).abs() < f64::EPSILON);
+    assert_eq!(updated.props.get("color").unwrap().as_str().unwrap(), "#00FF00");
+}
+
 #[tokio::test]
 async fn update_object_sequential_version_increments() {
     let state = test_helpers::test_app_state();
@@ -509,3 +604,122 @@ async fn delete_object_removes_from_memory() {
     .unwrap();
     let _ = delete_object(&state, board_id, obj.id).await;
 }
+
+fn batch_create(kind: &str, x: f64) -> BatchOp {
+    BatchOp::Create {
+        kind: kind.into(),
+        x,
+        y: 0.0,
+        width: None,
+        height: None,
+        rotation: 0.0,
+        props: serde_json::json!({}),
+        group_id: None,
+    }
+}
+
+fn batch_move(id: Uuid, version: i32, x: f64) -> BatchOp {
+    let mut updates = Data::new();
+    updates.insert("x".into(), serde_json::json!(x));
+    BatchOp::Update { id, version, updates }
+}
+
+#[tokio::test]
+async fn apply_batch_creates_and_updates_in_order() {
+    let state = test_helpers::test_app_state();
+    let existing = test_helpers::dummy_object();
+    let existing_id = existing.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![existing]).await;
+
+    let ops = vec![
+        batch_create("rectangle", 10.0),
+        batch_move(existing_id, 1, 300.0),
+        batch_create("ellipse", 20.0),
+    ];
+    let changes = apply_batch(&state, board_id, &ops, None).await.unwrap();
+    assert_eq!(changes.len(), 3);
+
+    let BatchChange::Created(first) = &changes[0] else {
+        panic!("expected create");
+    };
+    let BatchChange::Updated(moved) = &changes[1] else {
+        panic!("expected update");
+    };
+    let BatchChange::Created(second) = &changes[2] else {
+        panic!("expected create");
+    };
+    assert_eq!(first.z_index, 1);
+    assert_eq!(second.z_index, 2);
+    assert_eq!(moved.version, 2);
+    assert!((moved.x - 300.0).abs() < f64::EPSILON);
+
+    let boards = state.boards.read().await;
+    let board = boards.get(&board_id).unwrap();
+    assert_eq!(board.objects.len(), 3);
+    assert!(board.dirty.contains(&first.id));
+    assert!(board.dirty.contains(&second.id));
+    assert!(board.dirty.contains(&existing_id));
+}
+
+#[tokio::test]
+async fn apply_batch_stale_version_rejects_whole_batch() {
+    let state = test_helpers::test_app_state();
+    let fresh = test_helpers::dummy_object();
+    let mut ahead = test_helpers::dummy_object();
+    ahead.version = 5;
+    let (fresh_id, ahead_id) = (fresh.id, ahead.id);
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![fresh, ahead]).await;
+
+    let ops = vec![
+        batch_create("rectangle", 0.0),
+        batch_move(fresh_id, 1, 300.0),
+        batch_move(ahead_id, 2, 400.0),
+    ];
+    let result = apply_batch(&state, board_id, &ops, None).await;
+    assert!(matches!(result, Err(ObjectError::StaleUpdate { incoming: 2, current: 5 })));
+
+    let boards = state.boards.read().await;
+    let board = boards.get(&board_id).unwrap();
+    assert_eq!(board.objects.len(), 2);
+    assert!(board.dirty.is_empty());
+    let fresh_after = board.objects.get(&fresh_id).unwrap();
+    assert!((fresh_after.x - 100.0).abs() < f64::EPSILON);
+    assert_eq!(fresh_after.version, 1);
+}
+
+#[tokio::test]
+async fn apply_batch_sees_earlier_ops_in_same_batch() {
+    let state = test_helpers::test_app_state();
+    let obj = test_helpers::dummy_object();
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![obj]).await;
+
+    // Second update must carry the version bumped by the first.
+    let repeated_version = vec![batch_move(obj_id, 1, 10.0), batch_move(obj_id, 1, 20.0)];
+    assert!(matches!(
+        apply_batch(&state, board_id, &repeated_version, None).await,
+        Err(ObjectError::StaleUpdate { incoming: 1, current: 2 })
+    ));
+
+    let chained = vec![batch_move(obj_id, 1, 10.0), batch_move(obj_id, 2, 20.0)];
+    let changes = apply_batch(&state, board_id, &chained, None).await.unwrap();
+    let BatchChange::Updated(last) = &changes[1] else {
+        panic!("expected update");
+    };
+    assert_eq!(last.version, 3);
+    assert!((last.x - 20.0).abs() < f64::EPSILON);
+}
+
+#[tokio::test]
+async fn apply_batch_missing_delete_target_rejects_whole_batch() {
+    let state = test_helpers::test_app_state();
+    let board_id = test_helpers::seed_board(&state).await;
+    let missing = Uuid::new_v4();
+
+    let ops = vec![batch_create("rectangle", 0.0), BatchOp::Delete { id: missing }];
+    let result = apply_batch(&state, board_id, &ops, None).await;
+    assert!(matches!(result, Err(ObjectError::NotFound(id)) if id == missing));
+
+    let boards = state.boards.read().await;
+    assert!(boards.get(&board_id).unwrap().objects.is_empty());
+}
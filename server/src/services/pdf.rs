@@ -0,0 +1,251 @@
+//! PDF service — vector board export for print.
+//!
+//! DESIGN
+//! ======
+//! Boards are built as SVG scenes (see `scene`) and parsed with `usvg`, which
+//! resolves styles, turns every shape into a path, and lays text out as glyph
+//! outlines with the host's fonts. Walking that tree writes a single page
+//! sized to the scene at 96 world units per inch, so shapes, strokes, and
+//! text stay vector paths at any zoom and no fonts need embedding.
+//!
+//! The writer covers what scenes produce: solid fills and strokes, group
+//! opacity, and nested SVG images. Gradients in user SVG are painted with
+//! their first stop; clip paths, masks, filters, and raster images are
+//! skipped.
+
+use pdf_writer::types::{LineCapStyle, LineJoinStyle};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref};
+use resvg::tiny_skia::PathSegment;
+use resvg::usvg;
+
+use crate::mermaid::ExportScope;
+use crate::services::scene::{self, SceneError};
+use crate::state::BoardObject;
+
+/// PDF points per world unit: 72 points per inch over 96 CSS pixels.
+const POINTS_PER_UNIT: f32 = 0.75;
+
+/// Render a board, one frame of it, or a selection as a one-page PDF.
+///
+/// # Errors
+///
+/// Returns `FrameNotFound` or `NothingSelected` when the scope does not match
+/// the board, or `Invalid` if the scene cannot be parsed.
+pub fn render_pdf(objects: &[BoardObject], scope: &ExportScope) -> Result<Vec<u8>, SceneError> {
+    let tree = scene::build_scene(objects, scope)?.tree()?;
+    Ok(write_pdf(&tree))
+}
+
+fn write_pdf(tree: &usvg::Tree) -> Vec<u8> {
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let page_id = Ref::new(3);
+    let content_id = Ref::new(4);
+    let width = tree.size().width() * POINTS_PER_UNIT;
+    let height = tree.size().height() * POINTS_PER_UNIT;
+
+    let mut page = PageWriter { content: Content::new(), alphas: Vec::new() };
+    // PDF's origin is the bottom-left corner; flip to SVG's top-left.
+    page.content
+        .transform([POINTS_PER_UNIT, 0.0, 0.0, -POINTS_PER_UNIT, 0.0, height]);
+    page.group(tree.root(), 1.0);
+    let PageWriter { content, alphas } = page;
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id).kids([page_id]).count(1);
+    let mut page_dict = pdf.page(page_id);
+    page_dict
+        .media_box(Rect::new(0.0, 0.0, width, height))
+        .parent(page_tree_id)
+        .contents(content_id);
+    let mut resources = page_dict.resources();
+    let mut states = resources.ext_g_states();
+    for (idx, (fill, stroke)) in alphas.iter().enumerate() {
+        states
+            .insert(Name(alpha_state_name(idx).as_bytes()))
+            .dict()
+            .pair(Name(b"ca"), *fill)
+            .pair(Name(b"CA"), *stroke);
+    }
+    states.finish();
+    resources.finish();
+    page_dict.finish();
+    pdf.stream(content_id, &content.finish());
+    pdf.finish()
+}
+
+fn alpha_state_name(idx: usize) -> String {
+    format!("A{idx}")
+}
+
+struct PageWriter {
+    content: Content,
+    /// Distinct `(fill, stroke)` alpha pairs, one graphics state each.
+    alphas: Vec<(f32, f32)>,
+}
+
+impl PageWriter {
+    /// Write a group's children under its transform. Group opacity is folded
+    /// into each painted path, which matches `resvg` wherever children do not
+    /// overlap.
+    fn group(&mut self, group: &usvg::Group, opacity: f32) {
+        let opacity = opacity * group.opacity().get();
+        let ts = group.transform();
+        self.content.save_state();
+        if !ts.is_identity() {
+            self.content
+                .transform([ts.sx, ts.ky, ts.kx, ts.sy, ts.tx, ts.ty]);
+        }
+        for node in group.children() {
+            match node {
+                usvg::Node::Group(child) => self.group(child, opacity),
+                usvg::Node::Path(path) => self.path(path, opacity),
+                usvg::Node::Text(text) => self.group(text.flattened(), opacity),
+                usvg::Node::Image(image) => {
+                    if let (true, usvg::ImageKind::SVG(tree)) = (image.is_visible(), image.kind()) {
+                        self.group(tree.root(), opacity);
+                    }
+                }
+            }
+        }
+        self.content.restore_state();
+    }
+
+    fn path(&mut self, path: &usvg::Path, opacity: f32) {
+        if !path.is_visible() {
+            return;
+        }
+        let fill = path
+            .fill()
+            .and_then(|fill| Some((fill, solid_color(fill.paint())?)));
+        let stroke = path
+            .stroke()
+            .and_then(|stroke| Some((stroke, solid_color(stroke.paint())?)));
+        let paint_fill = |writer: &mut Self| {
+            if let Some((fill, (color, alpha))) = fill {
+                writer.set_alpha(opacity * fill.opacity().get() * alpha, 1.0);
+                writer.content.set_fill_rgb(
+                    f32::from(color.red) / 255.0,
+                    f32::from(color.green) / 255.0,
+                    f32::from(color.blue) / 255.0,
+                );
+                writer.segments(path.data());
+                match fill.rule() {
+                    usvg::FillRule::NonZero => writer.content.fill_nonzero(),
+                    usvg::FillRule::EvenOdd => writer.content.fill_even_odd(),
+                };
+            }
+        };
+        let paint_stroke = |writer: &mut Self| {
+            if let Some((stroke, (color, alpha))) = stroke {
+                writer.set_alpha(1.0, opacity * stroke.opacity().get() * alpha);
+                writer.content.set_stroke_rgb(
+                    f32::from(color.red) / 255.0,
+                    f32::from(color.green) / 255.0,
+                    f32::from(color.blue) / 255.0,
+                );
+                writer.content.set_line_width(stroke.width().get());
+                writer.content.set_line_cap(match stroke.linecap() {
+                    usvg::LineCap::Butt => LineCapStyle::ButtCap,
+                    usvg::LineCap::Round => LineCapStyle::RoundCap,
+                    usvg::LineCap::Square => LineCapStyle::ProjectingSquareCap,
+                });
+                writer.content.set_line_join(match stroke.linejoin() {
+                    usvg::LineJoin::Miter | usvg::LineJoin::MiterClip => LineJoinStyle::MiterJoin,
+                    usvg::LineJoin::Round => LineJoinStyle::RoundJoin,
+                    usvg::LineJoin::Bevel => LineJoinStyle::BevelJoin,
+                });
+                writer.content.set_miter_limit(stroke.miterlimit().get());
+                writer
+                    .content
+                    .set_dash_pattern(stroke.dasharray().unwrap_or_default().iter().copied(), stroke.dashoffset());
+                writer.segments(path.data());
+                writer.content.stroke();
+            }
+        };
+        match path.paint_order() {
+            usvg::PaintOrder::FillAndStroke => {
+                paint_fill(self);
+                paint_stroke(self);
+            }
+            usvg::PaintOrder::StrokeAndFill => {
+                paint_stroke(self);
+                paint_fill(self);
+            }
+        }
+    }
+
+    /// Select the graphics state for these alphas, adding it on first use.
+    fn set_alpha(&mut self, fill: f32, stroke: f32) {
+        let idx = self
+            .alphas
+            .iter()
+            .position(|&pair| pair == (fill, stroke))
+            .unwrap_or_else(|| {
+                self.alphas.push((fill, stroke));
+                self.alphas.len() - 1
+            });
+        self.content
+            .set_parameters(Name(alpha_state_name(idx).as_bytes()));
+    }
+
+    /// Append path construction operators. PDF has no quadratic curves, so
+    /// they are raised to cubics.
+    fn segments(&mut self, data: &resvg::tiny_skia::Path) {
+        let (mut last_x, mut last_y) = (0.0, 0.0);
+        let (mut start_x, mut start_y) = (0.0, 0.0);
+        for segment in data.segments() {
+            match segment {
+                PathSegment::MoveTo(p) => {
+                    self.content.move_to(p.x, p.y);
+                    (last_x, last_y) = (p.x, p.y);
+                    (start_x, start_y) = (p.x, p.y);
+                }
+                PathSegment::LineTo(p) => {
+                    self.content.line_to(p.x, p.y);
+                    (last_x, last_y) = (p.x, p.y);
+                }
+                PathSegment::QuadTo(c, p) => {
+                    self.content.cubic_to(
+                        last_x + (c.x - last_x) * 2.0 / 3.0,
+                        last_y + (c.y - last_y) * 2.0 / 3.0,
+                        p.x + (c.x - p.x) * 2.0 / 3.0,
+                        p.y + (c.y - p.y) * 2.0 / 3.0,
+                        p.x,
+                        p.y,
+                    );
+                    (last_x, last_y) = (p.x, p.y);
+                }
+                PathSegment::CubicTo(c1, c2, p) => {
+                    self.content.cubic_to(c1.x, c1.y, c2.x, c2.y, p.x, p.y);
+                    (last_x, last_y) = (p.x, p.y);
+                }
+                PathSegment::Close => {
+                    self.content.close_path();
+                    (last_x, last_y) = (start_x, start_y);
+                }
+            }
+        }
+    }
+}
+
+/// The color and alpha to paint with. Gradients fall back to their first
+/// stop; patterns are not supported.
+fn solid_color(paint: &usvg::Paint) -> Option<(usvg::Color, f32)> {
+    let first_stop = |stops: &[usvg::Stop]| {
+        stops
+            .first()
+            .map(|stop| (stop.color(), stop.opacity().get()))
+    };
+    match paint {
+        usvg::Paint::Color(color) => Some((*color, 1.0)),
+        usvg::Paint::LinearGradient(gradient) => first_stop(gradient.stops()),
+        usvg::Paint::RadialGradient(gradient) => first_stop(gradient.stops()),
+        usvg::Paint::Pattern(_) => None,
+    }
+}
+
+#[cfg(test)]
+#[path = "pdf_test.rs"]
+mod tests;
@@ -0,0 +1,80 @@
+use super::*;
+use crate::state::test_helpers;
+
+fn shape(kind: &str, x: f64, y: f64, width: f64, height: f64, props: serde_json::Value) -> BoardObject {
+    let mut obj = test_helpers::dummy_object();
+    obj.kind = kind.to_owned();
+    obj.x = x;
+    obj.y = y;
+    obj.width = Some(width);
+    obj.height = Some(height);
+    obj.props = props;
+    obj
+}
+
+fn text_of(pdf: &[u8]) -> String {
+    String::from_utf8_lossy(pdf).into_owned()
+}
+
+#[test]
+fn render_pdf_sizes_page_to_scene_in_points() {
+    let rect = shape("rectangle", 0.0, 0.0, 100.0, 50.0, serde_json::json!({ "fill": "#0000FF" }));
+    let pdf = render_pdf(&[rect], &ExportScope::Board).unwrap();
+    assert!(pdf.starts_with(b"%PDF-"));
+    // 148 x 98 world units with padding, at 0.75pt per unit.
+    assert!(text_of(&pdf).contains("/MediaBox [0 0 111 73.5]"));
+}
+
+#[test]
+fn render_pdf_paints_shapes_as_vector_paths() {
+    let rect = shape(
+        "rectangle",
+        0.0,
+        0.0,
+        100.0,
+        50.0,
+        serde_json::json!({ "fill": "#0000FF", "stroke": "#FF0000", "strokeWidth": 3.0 }),
+    );
+    let text = text_of(&render_pdf(&[rect], &ExportScope::Board).unwrap());
+    assert!(text.contains("0 0 1 rg"));
+    assert!(text.contains("1 0 0 RG"));
+    assert!(text.contains("3 w"));
+}
+
+#[test]
+fn render_pdf_folds_translucent_colors_into_graphics_states() {
+    let rect = shape(
+        "rectangle",
+        0.0,
+        0.0,
+        100.0,
+        50.0,
+        serde_json::json!({ "fill": "rgba(0, 0, 255, 0.2)" }),
+    );
+    let text = text_of(&render_pdf(&[rect], &ExportScope::Board).unwrap());
+    assert!(text.contains("/ExtGState"));
+    assert!(text.contains("/ca 0.2"));
+}
+
+#[test]
+fn render_pdf_draws_nested_svg_content() {
+    let svg = shape(
+        "svg",
+        0.0,
+        0.0,
+        100.0,
+        100.0,
+        serde_json::json!({ "svg": r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><rect width="10" height="10" fill="#00FF00"/></svg>"## }),
+    );
+    let text = text_of(&render_pdf(&[svg], &ExportScope::Board).unwrap());
+    assert!(text.contains("0 1 0 rg"));
+}
+
+#[test]
+fn render_pdf_reports_missing_frame() {
+    let rect = shape("rectangle", 0.0, 0.0, 100.0, 50.0, serde_json::json!({}));
+    assert!(matches!(
+        render_pdf(&[rect], &ExportScope::Frame(uuid::Uuid::new_v4())),
+        Err(SceneError::FrameNotFound(_))
+    ));
+}
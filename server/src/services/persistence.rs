@@ -3,7 +3,8 @@
 //! DESIGN
 //! ======
 //! A background task flushes dirty objects, then sleeps 100ms before
-//! the next cycle. Frames use a bounded queue + batched async writer so
+//! the next cycle. Once a minute it also sweeps idle undo histories and
+//! prompt logs. Frames use a bounded queue + batched async writer so
 //! websocket handling never blocks on Postgres I/O.
 //!
 //! ERROR HANDLING
@@ -12,7 +13,7 @@
 //! durability over duplicate flush attempts: repeated upserts are acceptable,
 //! silent data loss is not.
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use sqlx::PgPool;
 use tokio::task::JoinHandle;
@@ -29,6 +30,8 @@ const DEFAULT_FRAME_PERSIST_FLUSH_MS: u64 = 5;
 const DEFAULT_FRAME_PERSIST_RETRIES: usize = 2;
 const DEFAULT_FRAME_PERSIST_RETRY_BASE_MS: u64 = 20;
 const DEFAULT_OBJECT_FLUSH_INTERVAL_MS: u64 = 100;
+/// How often the persistence task sweeps idle undo histories and prompt logs.
+const HISTORY_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Tuning knobs for the frame persistence worker, loaded from environment variables.
 #[derive(Clone, Copy)]
@@ -72,8 +75,14 @@ pub fn spawn_persistence_task(state: AppState) -> JoinHandle<()> {
     let flush_interval_ms = env_parse("OBJECT_FLUSH_INTERVAL_MS", DEFAULT_OBJECT_FLUSH_INTERVAL_MS);
     info!(flush_interval_ms, "object persistence flush configured");
     tokio::spawn(async move {
+        let mut last_history_sweep = Instant::now();
         loop {
             flush_all_dirty(&state).await;
+            if last_history_sweep.elapsed() >= HISTORY_SWEEP_INTERVAL {
+                last_history_sweep = Instant::now();
+                super::history::sweep_idle(&state, last_history_sweep).await;
+                super::revert::sweep_idle(&state, last_history_sweep).await;
+            }
             tokio::time::sleep(Duration::from_millis(flush_interval_ms)).await;
         }
     })
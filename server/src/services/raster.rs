@@ -0,0 +1,166 @@
+//! Raster service — headless board rendering for PNG export and thumbnails.
+//!
+//! DESIGN
+//! ======
+//! Boards are built as SVG scenes (see `scene`), which mirror the browser's
+//! `canvas::render` output, and drawn onto a `tiny-skia` pixmap by `resvg`.
+//! PNG exports share the scene's scoping: the whole board, one frame, or a
+//! selection.
+//!
+//! Dashboard thumbnails are cached per board together with a fingerprint of
+//! the object versions they were drawn from, so an unchanged board is never
+//! rendered twice.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use axum::body::Bytes;
+use resvg::tiny_skia::{Pixmap, Transform};
+use uuid::Uuid;
+
+use crate::mermaid::ExportScope;
+use crate::services::scene::{self, Scene, SceneError};
+use crate::state::{AppState, BoardObject, BoardThumbnail};
+
+/// Smallest accepted export scale.
+pub const MIN_SCALE: f64 = 0.1;
+/// Largest accepted export scale.
+pub const MAX_SCALE: f64 = 4.0;
+/// Longest image side in pixels; larger exports are scaled down to fit.
+pub const MAX_DIMENSION: u32 = 4096;
+/// Thumbnail bounding box in pixels.
+pub const THUMBNAIL_WIDTH: u32 = 320;
+pub const THUMBNAIL_HEIGHT: u32 = 200;
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// Errors returned by raster service operations.
+#[derive(Debug, thiserror::Error)]
+pub enum RasterError {
+    /// The requested scale is outside `MIN_SCALE..=MAX_SCALE`.
+    #[error("scale must be between {MIN_SCALE} and {MAX_SCALE}: {0}")]
+    InvalidScale(f64),
+    /// The scene could not be built for the requested scope.
+    #[error(transparent)]
+    Scene(#[from] SceneError),
+    /// Rendering or PNG encoding failed.
+    #[error("render failed: {0}")]
+    Render(String),
+}
+
+/// What to render and at which resolution.
+#[derive(Debug, Clone)]
+pub struct RasterOptions {
+    /// Pixels per world unit.
+    pub scale: f64,
+    /// Which part of the board to render.
+    pub scope: ExportScope,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        Self { scale: 1.0, scope: ExportScope::Board }
+    }
+}
+
+/// Order-independent fingerprint of object IDs and versions.
+///
+/// Any edit bumps an object's version, so an unchanged fingerprint means an
+/// unchanged picture.
+#[must_use]
+pub fn fingerprint(objects: &[BoardObject]) -> u64 {
+    let mut keys: Vec<(Uuid, i32)> = objects.iter().map(|obj| (obj.id, obj.version)).collect();
+    keys.sort_unstable();
+    let mut hasher = DefaultHasher::new();
+    keys.hash(&mut hasher);
+    hasher.finish()
+}
+
+// =============================================================================
+// RENDERING
+// =============================================================================
+
+/// Render a board, one frame of it, or a selection to PNG bytes.
+///
+/// Scales that would exceed `MAX_DIMENSION` pixels on either side are
+/// reduced to fit.
+///
+/// # Errors
+///
+/// Returns `InvalidScale` for a scale outside `MIN_SCALE..=MAX_SCALE`,
+/// `Scene` when the scope does not match the board, or `Render` if
+/// rendering or encoding fails.
+pub fn render_png(objects: &[BoardObject], options: &RasterOptions) -> Result<Vec<u8>, RasterError> {
+    if !(MIN_SCALE..=MAX_SCALE).contains(&options.scale) {
+        return Err(RasterError::InvalidScale(options.scale));
+    }
+    let scene = scene::build_scene(objects, &options.scope)?;
+    let max = f64::from(MAX_DIMENSION);
+    let scale = options.scale.min(max / scene.width).min(max / scene.height);
+    rasterize(&scene, scale)
+}
+
+/// Render a whole board scaled to fit the thumbnail box.
+///
+/// # Errors
+///
+/// Returns `Render` if rendering or encoding fails.
+pub fn render_thumbnail(objects: &[BoardObject]) -> Result<Vec<u8>, RasterError> {
+    let scene = scene::build_scene(objects, &ExportScope::Board)?;
+    let scale = (f64::from(THUMBNAIL_WIDTH) / scene.width).min(f64::from(THUMBNAIL_HEIGHT) / scene.height);
+    rasterize(&scene, scale)
+}
+
+/// Return the board's thumbnail, rendering it only when the objects changed
+/// since the cached one was drawn.
+///
+/// # Errors
+///
+/// Returns `Render` if rendering fails.
+pub async fn cached_thumbnail(
+    state: &AppState,
+    board_id: Uuid,
+    objects: Vec<BoardObject>,
+) -> Result<BoardThumbnail, RasterError> {
+    let fingerprint = fingerprint(&objects);
+    if let Some(cached) = state.thumbnails.read().await.get(&board_id) {
+        if cached.fingerprint == fingerprint {
+            return Ok(cached.clone());
+        }
+    }
+
+    let png = tokio::task::spawn_blocking(move || render_thumbnail(&objects))
+        .await
+        .map_err(|e| RasterError::Render(e.to_string()))??;
+    let thumbnail = BoardThumbnail { fingerprint, png: Bytes::from(png) };
+    state
+        .thumbnails
+        .write()
+        .await
+        .insert(board_id, thumbnail.clone());
+    Ok(thumbnail)
+}
+
+/// Draw the scene at `scale` pixels per world unit. The pixel size rounds up
+/// and the scene is stretched by that fraction of a pixel to fill it.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn rasterize(scene: &Scene, scale: f64) -> Result<Vec<u8>, RasterError> {
+    let tree = scene.tree()?;
+    let pixels = |extent: f64| ((extent * scale).ceil() as u32).clamp(1, MAX_DIMENSION);
+    let (width, height) = (pixels(scene.width), pixels(scene.height));
+    let mut pixmap = Pixmap::new(width, height).ok_or_else(|| RasterError::Render("invalid image size".to_owned()))?;
+    let transform = Transform::from_scale(
+        (f64::from(width) / scene.width) as f32,
+        (f64::from(height) / scene.height) as f32,
+    );
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+    pixmap
+        .encode_png()
+        .map_err(|e| RasterError::Render(e.to_string()))
+}
+
+#[cfg(test)]
+#[path = "raster_test.rs"]
+mod tests;
@@ -0,0 +1,241 @@
+use super::*;
+use crate::state::test_helpers;
+
+fn shape(kind: &str, x: f64, y: f64, width: f64, height: f64, props: serde_json::Value) -> BoardObject {
+    let mut obj = test_helpers::dummy_object();
+    obj.kind = kind.to_owned();
+    obj.x = x;
+    obj.y = y;
+    obj.width = Some(width);
+    obj.height = Some(height);
+    obj.props = props;
+    obj
+}
+
+fn decode(png: &[u8]) -> Pixmap {
+    Pixmap::decode_png(png).expect("valid png")
+}
+
+fn rgb_at(pixmap: &Pixmap, x: u32, y: u32) -> (u8, u8, u8) {
+    let pixel = pixmap.pixel(x, y).expect("pixel in bounds");
+    (pixel.red(), pixel.green(), pixel.blue())
+}
+
+// =============================================================================
+// fingerprint
+// =============================================================================
+
+#[test]
+fn fingerprint_ignores_order_and_tracks_versions() {
+    let a = test_helpers::dummy_object();
+    let b = test_helpers::dummy_object();
+    let forward = fingerprint(&[a.clone(), b.clone()]);
+    assert_eq!(forward, fingerprint(&[b.clone(), a.clone()]));
+
+    let mut edited = a;
+    edited.version += 1;
+    assert_ne!(forward, fingerprint(&[edited, b]));
+}
+
+// =============================================================================
+// render_png
+// =============================================================================
+
+#[test]
+fn render_png_pads_content_bounds() {
+    let rect = shape("rectangle", 0.0, 0.0, 100.0, 50.0, serde_json::json!({ "fill": "#0000FF" }));
+    let image = decode(&render_png(&[rect], &RasterOptions::default()).unwrap());
+    assert_eq!((image.width(), image.height()), (148, 98));
+    assert_eq!(rgb_at(&image, 74, 49), (0, 0, 255));
+    assert_eq!(rgb_at(&image, 2, 2), (0xF6, 0xF1, 0xE7));
+}
+
+#[test]
+fn render_png_applies_scale() {
+    let rect = shape("rectangle", 0.0, 0.0, 100.0, 50.0, serde_json::json!({}));
+    let options = RasterOptions { scale: 2.0, scope: ExportScope::Board };
+    let image = decode(&render_png(&[rect], &options).unwrap());
+    assert_eq!((image.width(), image.height()), (296, 196));
+}
+
+#[test]
+fn render_png_rejects_out_of_range_scale() {
+    for scale in [0.0, -1.0, 10.0, f64::NAN] {
+        let options = RasterOptions { scale, scope: ExportScope::Board };
+        assert!(matches!(render_png(&[], &options), Err(RasterError::InvalidScale(_))));
+    }
+}
+
+#[test]
+fn render_png_shrinks_oversized_boards_to_fit() {
+    let wide = shape("rectangle", 0.0, 0.0, 20_000.0, 100.0, serde_json::json!({}));
+    let options = RasterOptions { scale: 1.0, scope: ExportScope::Board };
+    let image = decode(&render_png(&[wide], &options).unwrap());
+    assert_eq!(image.width(), MAX_DIMENSION);
+    assert!(image.height() < 100);
+}
+
+#[test]
+fn render_png_crops_to_frame() {
+    let frame = shape("frame", 100.0, 100.0, 200.0, 150.0, serde_json::json!({ "title": "Plan" }));
+    let mut inside = shape("ellipse", 150.0, 150.0, 100.0, 80.0, serde_json::json!({ "fill": "#00FF00" }));
+    inside.z_index = 1;
+    let outside = shape("rectangle", 1000.0, 1000.0, 50.0, 50.0, serde_json::json!({}));
+    let options = RasterOptions { scale: 1.0, scope: ExportScope::Frame(frame.id) };
+    let image = decode(&render_png(&[frame, inside, outside], &options).unwrap());
+    assert_eq!((image.width(), image.height()), (200, 150));
+    assert_eq!(rgb_at(&image, 100, 90), (0, 255, 0));
+}
+
+#[test]
+fn render_png_reports_missing_frame() {
+    let rect = shape("rectangle", 0.0, 0.0, 100.0, 50.0, serde_json::json!({}));
+    let not_a_frame = RasterOptions { scale: 1.0, scope: ExportScope::Frame(rect.id) };
+    assert!(matches!(
+        render_png(std::slice::from_ref(&rect), &not_a_frame),
+        Err(RasterError::Scene(SceneError::FrameNotFound(id))) if id == rect.id
+    ));
+
+    let missing = RasterOptions { scale: 1.0, scope: ExportScope::Frame(Uuid::new_v4()) };
+    assert!(matches!(
+        render_png(&[rect], &missing),
+        Err(RasterError::Scene(SceneError::FrameNotFound(_)))
+    ));
+}
+
+#[test]
+fn render_png_draws_in_z_order() {
+    let mut top = shape("rectangle", 0.0, 0.0, 100.0, 100.0, serde_json::json!({ "fill": "#FF0000" }));
+    top.z_index = 2;
+    let mut bottom = shape("rectangle", 0.0, 0.0, 100.0, 100.0, serde_json::json!({ "fill": "#0000FF" }));
+    bottom.z_index = 1;
+    let image = decode(&render_png(&[top, bottom], &RasterOptions::default()).unwrap());
+    assert_eq!(rgb_at(&image, 74, 74), (255, 0, 0));
+}
+
+#[test]
+fn render_png_draws_attached_connectors() {
+    let a = shape("rectangle", 0.0, 0.0, 40.0, 40.0, serde_json::json!({ "fill": "#FFFFFF" }));
+    let b = shape("rectangle", 200.0, 0.0, 40.0, 40.0, serde_json::json!({ "fill": "#FFFFFF" }));
+    let mut arrow = test_helpers::dummy_object();
+    arrow.kind = "arrow".into();
+    arrow.props = serde_json::json!({
+        "a": { "type": "attached", "object_id": a.id.to_string(), "ux": 1.0, "uy": 0.5, "x": 0.0, "y": 0.0 },
+        "b": { "type": "attached", "object_id": b.id.to_string(), "ux": 0.0, "uy": 0.5, "x": 0.0, "y": 0.0 },
+        "stroke": "#000000",
+        "strokeWidth": 4.0,
+    });
+    let image = decode(&render_png(&[a, b, arrow], &RasterOptions::default()).unwrap());
+    // Midpoint of the connector between the two shapes, offset by padding.
+    assert_eq!(rgb_at(&image, 24 + 120, 24 + 20), (0, 0, 0));
+}
+
+#[test]
+fn render_png_draws_inline_svg() {
+    let svg = shape(
+        "svg",
+        0.0,
+        0.0,
+        100.0,
+        100.0,
+        serde_json::json!({ "svg": r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><rect width="10" height="10" fill="#00FF00"/></svg>"## }),
+    );
+    let image = decode(&render_png(&[svg], &RasterOptions::default()).unwrap());
+    assert_eq!(rgb_at(&image, 74, 74), (0, 255, 0));
+}
+
+#[test]
+fn render_png_ignores_svg_images_outside_data_urls() {
+    let dir = std::env::temp_dir().join(format!("raster-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let red = Pixmap::new(4, 4)
+        .map(|mut pixmap| {
+            pixmap.fill(resvg::tiny_skia::Color::from_rgba8(255, 0, 0, 255));
+            pixmap.encode_png().unwrap()
+        })
+        .unwrap();
+    let file = dir.join("secret.png");
+    std::fs::write(&file, red).unwrap();
+
+    let markup = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><image href="{}" width="10" height="10"/></svg>"#,
+        file.display()
+    );
+    let svg = shape("svg", 0.0, 0.0, 100.0, 100.0, serde_json::json!({ "svg": markup }));
+    let image = decode(&render_png(&[svg], &RasterOptions::default()).unwrap());
+    assert_ne!(rgb_at(&image, 74, 74), (255, 0, 0));
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn render_png_falls_back_to_placeholder_for_bad_svg() {
+    let svg = shape(
+        "svg",
+        0.0,
+        0.0,
+        100.0,
+        100.0,
+        serde_json::json!({ "svg": "not svg", "fill": "#0000FF" }),
+    );
+    let image = decode(&render_png(&[svg], &RasterOptions::default()).unwrap());
+    assert_eq!(rgb_at(&image, 30, 30), (0, 0, 255));
+}
+
+#[test]
+fn render_png_crops_to_selection() {
+    let selected = shape("rectangle", 0.0, 0.0, 100.0, 50.0, serde_json::json!({ "fill": "#0000FF" }));
+    let other = shape("rectangle", 500.0, 500.0, 100.0, 50.0, serde_json::json!({}));
+    let options = RasterOptions { scale: 1.0, scope: ExportScope::Objects(std::iter::once(selected.id).collect()) };
+    let image = decode(&render_png(&[selected, other], &options).unwrap());
+    assert_eq!((image.width(), image.height()), (148, 98));
+    assert_eq!(rgb_at(&image, 74, 49), (0, 0, 255));
+}
+
+// =============================================================================
+// thumbnails
+// =============================================================================
+
+#[test]
+fn render_thumbnail_fits_thumbnail_box() {
+    let rect = shape("rectangle", 0.0, 0.0, 1000.0, 200.0, serde_json::json!({}));
+    let image = decode(&render_thumbnail(&[rect]).unwrap());
+    assert_eq!(image.width(), THUMBNAIL_WIDTH);
+    assert!(image.height() <= THUMBNAIL_HEIGHT);
+}
+
+#[test]
+fn render_thumbnail_of_empty_board_is_blank() {
+    let image = decode(&render_thumbnail(&[]).unwrap());
+    assert_eq!((image.width(), image.height()), (THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT));
+}
+
+#[tokio::test]
+async fn cached_thumbnail_rerenders_only_after_edits() {
+    let state = test_helpers::test_app_state();
+    let board_id = Uuid::new_v4();
+    let mut rect = shape("rectangle", 0.0, 0.0, 100.0, 50.0, serde_json::json!({}));
+
+    let first = cached_thumbnail(&state, board_id, vec![rect.clone()])
+        .await
+        .unwrap();
+    let again = cached_thumbnail(&state, board_id, vec![rect.clone()])
+        .await
+        .unwrap();
+    assert_eq!(first.fingerprint, again.fingerprint);
+    assert_eq!(first.png.as_ptr(), again.png.as_ptr());
+
+    rect.version += 1;
+    let edited = cached_thumbnail(&state, board_id, vec![rect])
+        .await
+        .unwrap();
+    assert_ne!(edited.fingerprint, first.fingerprint);
+    assert_eq!(
+        state
+            .thumbnails
+            .read()
+            .await
+            .get(&board_id)
+            .map(|cached| cached.fingerprint),
+        Some(edited.fingerprint)
+    );
+}
@@ -0,0 +1,445 @@
+//! Revert service — take back everything one AI prompt changed.
+//!
+//! DESIGN
+//! ======
+//! Every AI mutation is persisted as an `object:*` frame whose trace carries
+//! the prompt's `trace_id`, and the first update or delete of an object also
+//! carries its prior state under `trace.before`. Reverting a prompt loads
+//! those frames, collapses them into each object's state before and after the
+//! prompt, and moves the objects back under a single board write lock.
+//!
+//! Frame persistence is batched and drops frames when its queue is full, so
+//! the same effects are also folded into an in-memory log on `AppState` as
+//! the mutations are broadcast, keyed by trace. Revert reads that log first
+//! and only falls back to the frame log once the entry has been swept (or the
+//! server restarted). On that path the frame count is checked against the
+//! mutation count the prompt reported, and any shortfall is returned so the
+//! caller can say the revert may be partial.
+//!
+//! An object is only reverted while it still holds the state the prompt left
+//! behind. If anyone has changed, deleted, or recreated it since, it is
+//! skipped and reported as a conflict, so a revert never overwrites a newer
+//! edit. The applied changes become one undo entry for the reverting user.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+use crate::services::ai::AiMutation;
+use crate::services::object::BatchChange;
+use crate::services::savepoint::same_object_state;
+use crate::state::{AppState, BoardObject, BoardState};
+
+/// How long an untouched prompt log outlives its board's in-memory session.
+pub const PROMPT_LOG_IDLE_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// Errors returned by revert service operations.
+#[derive(Debug, thiserror::Error)]
+pub enum RevertError {
+    /// No AI mutations were recorded for the trace on this board.
+    #[error("no AI mutations recorded for trace {0}")]
+    TraceNotFound(Uuid),
+    /// The board has not been loaded into memory yet.
+    #[error("board not loaded: {0}")]
+    BoardNotLoaded(Uuid),
+    /// A Postgres query failed.
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl crate::frame::ErrorCode for RevertError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::TraceNotFound(_) => "E_TRACE_NOT_FOUND",
+            Self::BoardNotLoaded(_) => "E_BOARD_NOT_LOADED",
+            Self::Database(_) => "E_DATABASE",
+        }
+    }
+}
+
+/// Net effect of a prompt on one object.
+#[derive(Debug, Clone)]
+pub struct PromptEffect {
+    pub id: Uuid,
+    /// Whether the prompt created the object.
+    pub created: bool,
+    /// State before the prompt; `None` if created or not recorded.
+    pub before: Option<BoardObject>,
+    /// State the prompt left behind; `None` if the prompt deleted the object.
+    pub after: Option<BoardObject>,
+}
+
+/// Why an object could not be reverted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictReason {
+    /// The object was edited after the prompt.
+    Modified,
+    /// The object the prompt updated has since been deleted.
+    Deleted,
+    /// The object the prompt deleted exists again.
+    Recreated,
+    /// The prompt's record lacks the object's prior state.
+    Unrecorded,
+}
+
+impl ConflictReason {
+    /// Wire name used in `ai:revert` replies.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Modified => "modified",
+            Self::Deleted => "deleted",
+            Self::Recreated => "recreated",
+            Self::Unrecorded => "unrecorded",
+        }
+    }
+}
+
+/// Mutation frames found for a prompt versus the mutations it reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameMismatch {
+    /// Mutations the prompt's `ai:prompt` done frame reported.
+    pub expected: usize,
+    /// Mutation frames actually persisted under the trace.
+    pub recorded: usize,
+}
+
+/// Outcome of reverting a prompt.
+#[derive(Debug, Default)]
+pub struct RevertResult {
+    /// Changes applied to the live board, in the order the prompt first touched each object.
+    pub changes: Vec<BatchChange>,
+    /// Objects left alone, with the reason.
+    pub conflicts: Vec<(Uuid, ConflictReason)>,
+    /// Set when the effects came from an incomplete frame log.
+    pub mismatch: Option<FrameMismatch>,
+}
+
+/// In-memory record of everything one AI trace changed on a board.
+#[derive(Debug)]
+pub struct PromptLog {
+    pub board_id: Uuid,
+    /// Net effect per object, in the order the trace first touched each one.
+    pub effects: Vec<PromptEffect>,
+    /// Mutations folded in so far.
+    pub mutations: usize,
+    /// Last time mutations were folded in; drives the idle sweep.
+    pub touched_at: Instant,
+    index: HashMap<Uuid, usize>,
+}
+
+impl PromptLog {
+    fn new(board_id: Uuid) -> Self {
+        Self { board_id, effects: Vec::new(), mutations: 0, touched_at: Instant::now(), index: HashMap::new() }
+    }
+
+    /// Fold one mutation into the object's effect. The first touch fixes
+    /// `created` and `before`; every touch replaces `after`.
+    fn touch(
+        &mut self,
+        id: Uuid,
+        created: bool,
+        before: impl FnOnce() -> Option<BoardObject>,
+        after: Option<BoardObject>,
+    ) {
+        let effects = &mut self.effects;
+        let slot = *self.index.entry(id).or_insert_with(|| {
+            let before = if created { None } else { before() };
+            effects.push(PromptEffect { id, created, before, after: None });
+            effects.len() - 1
+        });
+        self.effects[slot].after = after;
+        self.mutations += 1;
+    }
+}
+
+// =============================================================================
+// PROMPT LOG
+// =============================================================================
+
+/// Fold a batch of AI mutations made under `trace_id` into its prompt log.
+///
+/// `before` holds the prior state of objects the batch touched. A trace may
+/// arrive in several batches (one per `tool:*` frame); later batches extend
+/// the same log and never overwrite an object's first recorded state.
+pub async fn remember_prompt(
+    state: &AppState,
+    board_id: Uuid,
+    trace_id: Uuid,
+    mutations: &[AiMutation],
+    before: &HashMap<Uuid, BoardObject>,
+) {
+    if mutations.is_empty() {
+        return;
+    }
+    let mut logs = state.prompt_logs.write().await;
+    let log = logs
+        .entry(trace_id)
+        .or_insert_with(|| PromptLog::new(board_id));
+    if log.board_id != board_id {
+        return;
+    }
+    for mutation in mutations {
+        let id = mutation.object_id();
+        let prior = || before.get(&id).cloned();
+        match mutation {
+            AiMutation::Created(obj) => log.touch(id, true, prior, Some(obj.clone())),
+            AiMutation::Updated(obj) => log.touch(id, false, prior, Some(obj.clone())),
+            AiMutation::Deleted(_) => log.touch(id, false, prior, None),
+        }
+    }
+    log.touched_at = Instant::now();
+}
+
+/// Drop prompt logs untouched since `now - PROMPT_LOG_IDLE_TTL` whose board
+/// is not loaded. Logs on loaded boards are kept however old they are.
+pub async fn sweep_idle(state: &AppState, now: Instant) {
+    let loaded = state
+        .boards
+        .read()
+        .await
+        .keys()
+        .copied()
+        .collect::<HashSet<_>>();
+    let mut logs = state.prompt_logs.write().await;
+    let before = logs.len();
+    logs.retain(|_, log| {
+        loaded.contains(&log.board_id) || now.saturating_duration_since(log.touched_at) < PROMPT_LOG_IDLE_TTL
+    });
+    let dropped = before - logs.len();
+    if dropped > 0 {
+        tracing::info!(dropped, remaining = logs.len(), "revert: swept idle prompt logs");
+    }
+}
+
+// =============================================================================
+// REVERT
+// =============================================================================
+
+/// Revert every object change the AI made under `trace_id`.
+///
+/// Effects come from the in-memory prompt log when it still holds the trace,
+/// otherwise from the persisted mutation frames.
+///
+/// # Errors
+///
+/// Returns `TraceNotFound` if no mutations are recorded for the trace,
+/// `BoardNotLoaded` if the board is not in memory, or `Database` if loading
+/// frames or removing rows fails.
+pub async fn revert_prompt(
+    state: &AppState,
+    board_id: Uuid,
+    user_id: Uuid,
+    trace_id: Uuid,
+) -> Result<RevertResult, RevertError> {
+    let remembered = state
+        .prompt_logs
+        .read()
+        .await
+        .get(&trace_id)
+        .filter(|log| log.board_id == board_id)
+        .map(|log| log.effects.clone());
+    let (effects, mismatch) = match remembered {
+        Some(effects) => (effects, None),
+        None => load_frame_effects(state, board_id, trace_id).await?,
+    };
+    if effects.is_empty() {
+        return Err(RevertError::TraceNotFound(trace_id));
+    }
+
+    let (before, result) = {
+        let mut boards = state.boards.write().await;
+        let board = boards
+            .get_mut(&board_id)
+            .ok_or(RevertError::BoardNotLoaded(board_id))?;
+        let before = effects
+            .iter()
+            .filter_map(|effect| {
+                board
+                    .objects
+                    .get(&effect.id)
+                    .map(|obj| (effect.id, obj.clone()))
+            })
+            .collect::<HashMap<_, _>>();
+        (before, revert_effects(board, &effects))
+    };
+
+    let deleted = result
+        .changes
+        .iter()
+        .filter_map(|change| match change {
+            BatchChange::Deleted(id) => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if !deleted.is_empty() {
+        sqlx::query("DELETE FROM board_objects WHERE board_id = $1 AND id = ANY($2)")
+            .bind(board_id)
+            .bind(&deleted)
+            .execute(&state.pool)
+            .await?;
+    }
+
+    let recorded = super::history::changes_from_batch(before, &result.changes);
+    super::history::record(state, board_id, user_id, recorded).await;
+    Ok(RevertResult { mismatch, ..result })
+}
+
+/// Load a trace's effects from the frame log, and compare the number of
+/// mutation frames with the count its `ai:prompt` done frame reported.
+async fn load_frame_effects(
+    state: &AppState,
+    board_id: Uuid,
+    trace_id: Uuid,
+) -> Result<(Vec<PromptEffect>, Option<FrameMismatch>), RevertError> {
+    let rows = sqlx::query_as::<_, (String, serde_json::Value, Option<serde_json::Value>)>(
+        "SELECT syscall, data, trace
+         FROM frames
+         WHERE board_id = $1
+           AND status = 'done'
+           AND trace->>'trace_id' = $2
+           AND trace->>'kind' = 'object.mutation'
+         ORDER BY seq ASC",
+    )
+    .bind(board_id)
+    .bind(trace_id.to_string())
+    .fetch_all(&state.pool)
+    .await?;
+    let expected = sqlx::query_scalar::<_, Option<i64>>(
+        "SELECT (data->>'mutations')::bigint
+         FROM frames
+         WHERE board_id = $1
+           AND parent_id = $2
+           AND syscall = 'ai:prompt'
+           AND status = 'done'
+         LIMIT 1",
+    )
+    .bind(board_id)
+    .bind(trace_id)
+    .fetch_optional(&state.pool)
+    .await?
+    .flatten();
+    let mismatch = frame_mismatch(expected, rows.len());
+    if let Some(mismatch) = mismatch {
+        tracing::warn!(
+            %trace_id,
+            expected = mismatch.expected,
+            recorded = mismatch.recorded,
+            "revert: frame log is missing mutations for prompt"
+        );
+    }
+    Ok((collapse_mutation_frames(&rows), mismatch))
+}
+
+/// Compare a prompt's reported mutation count with the frames recorded for it.
+/// An unknown count (no done frame, or a `tool:*` trace) never mismatches.
+#[must_use]
+pub fn frame_mismatch(expected: Option<i64>, recorded: usize) -> Option<FrameMismatch> {
+    let expected = usize::try_from(expected?).ok()?;
+    (expected != recorded).then_some(FrameMismatch { expected, recorded })
+}
+
+/// Collapse persisted `(syscall, data, trace)` mutation rows, oldest first,
+/// into one effect per object.
+#[must_use]
+pub fn collapse_mutation_frames(rows: &[(String, serde_json::Value, Option<serde_json::Value>)]) -> Vec<PromptEffect> {
+    let mut log = PromptLog::new(Uuid::nil());
+    for (syscall, data, trace) in rows {
+        let Some(id) = data
+            .get("id")
+            .and_then(serde_json::Value::as_str)
+            .and_then(|s| s.parse::<Uuid>().ok())
+        else {
+            continue;
+        };
+        let after = match syscall.as_str() {
+            "object:create" | "object:update" => {
+                let Ok(obj) = serde_json::from_value::<BoardObject>(data.clone()) else {
+                    continue;
+                };
+                Some(obj)
+            }
+            "object:delete" => None,
+            _ => continue,
+        };
+        let before = || {
+            trace
+                .as_ref()
+                .and_then(|trace| trace.get("before"))
+                .and_then(|before| serde_json::from_value::<BoardObject>(before.clone()).ok())
+        };
+        log.touch(id, syscall == "object:create", before, after);
+    }
+    log.effects
+}
+
+/// Move each object back to its pre-prompt state, skipping conflicts.
+pub fn revert_effects(board: &mut BoardState, effects: &[PromptEffect]) -> RevertResult {
+    let mut result = RevertResult::default();
+    for effect in effects {
+        let live = board.objects.get(&effect.id);
+        if !effect.created && effect.before.is_none() {
+            result
+                .conflicts
+                .push((effect.id, ConflictReason::Unrecorded));
+            continue;
+        }
+        match (&effect.before, &effect.after) {
+            (None, None) => {}
+            // Created by the prompt: delete it unless someone has touched it.
+            (None, Some(after)) => match live {
+                None => {}
+                Some(live) if !same_object_state(live, after) => {
+                    result.conflicts.push((effect.id, ConflictReason::Modified));
+                }
+                Some(_) => {
+                    board.objects.remove(&effect.id);
+                    board.dirty.remove(&effect.id);
+                    board.field_versions.remove(&effect.id);
+                    board.text_logs.remove(&effect.id);
+                    result.changes.push(BatchChange::Deleted(effect.id));
+                }
+            },
+            // Updated by the prompt: restore the prior state.
+            (Some(before), Some(after)) => match live {
+                None => result.conflicts.push((effect.id, ConflictReason::Deleted)),
+                Some(live) if !same_object_state(live, after) => {
+                    result.conflicts.push((effect.id, ConflictReason::Modified));
+                }
+                Some(live) => {
+                    let mut restored = before.clone();
+                    restored.version = live.version + 1;
+                    // Restored objects are replaced wholesale, so per-field history restarts.
+                    board.field_versions.remove(&effect.id);
+                    board.dirty.insert(effect.id);
+                    board.objects.insert(effect.id, restored.clone());
+                    result.changes.push(BatchChange::Updated(restored));
+                }
+            },
+            // Deleted by the prompt: recreate it with the same ID.
+            (Some(before), None) => {
+                if live.is_some() {
+                    result
+                        .conflicts
+                        .push((effect.id, ConflictReason::Recreated));
+                    continue;
+                }
+                let mut restored = before.clone();
+                restored.version += 1;
+                board.dirty.insert(effect.id);
+                board.objects.insert(effect.id, restored.clone());
+                result.changes.push(BatchChange::Created(restored));
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+#[path = "revert_test.rs"]
+mod tests;
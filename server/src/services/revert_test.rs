@@ -0,0 +1,271 @@
+use super::*;
+use crate::state::test_helpers::{self, dummy_object};
+use serde_json::json;
+
+fn row(
+    syscall: &str,
+    data: serde_json::Value,
+    trace: serde_json::Value,
+) -> (String, serde_json::Value, Option<serde_json::Value>) {
+    (syscall.to_owned(), data, Some(trace))
+}
+
+fn moved(obj: &BoardObject, x: f64) -> BoardObject {
+    let mut next = obj.clone();
+    next.x = x;
+    next.version += 1;
+    next
+}
+
+// =============================================================================
+// collapse_mutation_frames
+// =============================================================================
+
+#[test]
+fn collapse_keeps_first_before_and_last_after() {
+    let obj = dummy_object();
+    let first = moved(&obj, 10.0);
+    let second = moved(&first, 20.0);
+    let rows = vec![
+        row("object:update", json!(first), json!({ "before": obj })),
+        row("object:update", json!(second), json!({ "before": first })),
+    ];
+
+    let effects = collapse_mutation_frames(&rows);
+
+    assert_eq!(effects.len(), 1);
+    assert!(!effects[0].created);
+    assert!(same_object_state(effects[0].before.as_ref().unwrap(), &obj));
+    assert!(same_object_state(effects[0].after.as_ref().unwrap(), &second));
+}
+
+#[test]
+fn collapse_tracks_created_and_deleted_objects_in_order() {
+    let created = dummy_object();
+    let deleted = dummy_object();
+    let rows = vec![
+        row("object:create", json!(created), json!({})),
+        row("object:delete", json!({ "id": deleted.id }), json!({ "before": deleted })),
+    ];
+
+    let effects = collapse_mutation_frames(&rows);
+
+    assert_eq!(effects.len(), 2);
+    assert_eq!(effects[0].id, created.id);
+    assert!(effects[0].created);
+    assert!(effects[0].before.is_none());
+    assert!(effects[0].after.is_some());
+    assert_eq!(effects[1].id, deleted.id);
+    assert!(effects[1].before.is_some());
+    assert!(effects[1].after.is_none());
+}
+
+#[test]
+fn collapse_skips_rows_without_object_id() {
+    let rows = vec![row("object:update", json!({ "x": 1.0 }), json!({}))];
+    assert!(collapse_mutation_frames(&rows).is_empty());
+}
+
+// =============================================================================
+// revert_effects
+// =============================================================================
+
+fn effect(id: Uuid, created: bool, before: Option<&BoardObject>, after: Option<&BoardObject>) -> PromptEffect {
+    PromptEffect { id, created, before: before.cloned(), after: after.cloned() }
+}
+
+#[test]
+fn revert_deletes_created_restores_updated_and_recreates_deleted() {
+    let created = dummy_object();
+    let original = dummy_object();
+    let updated = moved(&original, 50.0);
+    let deleted = dummy_object();
+    let mut board = BoardState::new();
+    board.objects.insert(created.id, created.clone());
+    board.objects.insert(updated.id, updated.clone());
+
+    let result = revert_effects(
+        &mut board,
+        &[
+            effect(created.id, true, None, Some(&created)),
+            effect(original.id, false, Some(&original), Some(&updated)),
+            effect(deleted.id, false, Some(&deleted), None),
+        ],
+    );
+
+    assert!(result.conflicts.is_empty());
+    assert_eq!(result.changes.len(), 3);
+    assert!(!board.objects.contains_key(&created.id));
+    let restored = &board.objects[&original.id];
+    assert!(same_object_state(restored, &original));
+    assert_eq!(restored.version, updated.version + 1);
+    assert!(board.objects.contains_key(&deleted.id));
+    assert_eq!(board.objects[&deleted.id].version, deleted.version + 1);
+    assert!(board.dirty.contains(&original.id));
+    assert!(board.dirty.contains(&deleted.id));
+}
+
+#[test]
+fn revert_skips_objects_edited_since_the_prompt() {
+    let created = dummy_object();
+    let original = dummy_object();
+    let updated = moved(&original, 50.0);
+    let mut board = BoardState::new();
+    board.objects.insert(created.id, moved(&created, 5.0));
+    board.objects.insert(original.id, moved(&updated, 75.0));
+
+    let result = revert_effects(
+        &mut board,
+        &[
+            effect(created.id, true, None, Some(&created)),
+            effect(original.id, false, Some(&original), Some(&updated)),
+        ],
+    );
+
+    assert!(result.changes.is_empty());
+    assert_eq!(
+        result.conflicts,
+        vec![
+            (created.id, ConflictReason::Modified),
+            (original.id, ConflictReason::Modified)
+        ]
+    );
+    assert!(board.objects.contains_key(&created.id));
+    assert!((board.objects[&original.id].x - 75.0).abs() < f64::EPSILON);
+    assert!(board.dirty.is_empty());
+}
+
+#[test]
+fn revert_reports_deleted_recreated_and_unrecorded_conflicts() {
+    let original = dummy_object();
+    let updated = moved(&original, 50.0);
+    let deleted = dummy_object();
+    let unrecorded = dummy_object();
+    let mut board = BoardState::new();
+    board.objects.insert(deleted.id, deleted.clone());
+    board.objects.insert(unrecorded.id, unrecorded.clone());
+
+    let result = revert_effects(
+        &mut board,
+        &[
+            effect(original.id, false, Some(&original), Some(&updated)),
+            effect(deleted.id, false, Some(&deleted), None),
+            effect(unrecorded.id, false, None, Some(&unrecorded)),
+        ],
+    );
+
+    assert!(result.changes.is_empty());
+    assert_eq!(
+        result.conflicts,
+        vec![
+            (original.id, ConflictReason::Deleted),
+            (deleted.id, ConflictReason::Recreated),
+            (unrecorded.id, ConflictReason::Unrecorded),
+        ]
+    );
+    assert!(!board.objects.contains_key(&original.id));
+}
+
+#[test]
+fn revert_ignores_created_object_already_removed() {
+    let created = dummy_object();
+    let mut board = BoardState::new();
+
+    let result = revert_effects(&mut board, &[effect(created.id, true, None, Some(&created))]);
+
+    assert!(result.changes.is_empty());
+    assert!(result.conflicts.is_empty());
+}
+
+#[test]
+fn frame_mismatch_flags_missing_frames_only_when_count_is_known() {
+    assert_eq!(frame_mismatch(Some(3), 2), Some(FrameMismatch { expected: 3, recorded: 2 }));
+    assert_eq!(frame_mismatch(Some(2), 2), None);
+    assert_eq!(frame_mismatch(None, 0), None);
+}
+
+// =============================================================================
+// prompt log
+// =============================================================================
+
+#[tokio::test]
+async fn remember_prompt_extends_trace_across_batches() {
+    let state = test_helpers::test_app_state();
+    let board_id = Uuid::new_v4();
+    let trace_id = Uuid::new_v4();
+    let original = dummy_object();
+    let first = moved(&original, 10.0);
+    let second = moved(&first, 20.0);
+    let created = dummy_object();
+
+    remember_prompt(
+        &state,
+        board_id,
+        trace_id,
+        &[AiMutation::Updated(first.clone()), AiMutation::Created(created.clone())],
+        &HashMap::from([(original.id, original.clone())]),
+    )
+    .await;
+    remember_prompt(
+        &state,
+        board_id,
+        trace_id,
+        &[AiMutation::Updated(second.clone())],
+        &HashMap::from([(first.id, first.clone())]),
+    )
+    .await;
+
+    let logs = state.prompt_logs.read().await;
+    let log = &logs[&trace_id];
+    assert_eq!(log.mutations, 3);
+    assert_eq!(log.effects.len(), 2);
+    assert!(same_object_state(log.effects[0].before.as_ref().unwrap(), &original));
+    assert!(same_object_state(log.effects[0].after.as_ref().unwrap(), &second));
+    assert!(log.effects[1].created);
+    assert!(log.effects[1].before.is_none());
+}
+
+#[tokio::test]
+async fn revert_prompt_uses_prompt_log_without_persisted_frames() {
+    let state = test_helpers::test_app_state();
+    let original = dummy_object();
+    let updated = moved(&original, 50.0);
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![updated.clone()]).await;
+    let trace_id = Uuid::new_v4();
+    remember_prompt(
+        &state,
+        board_id,
+        trace_id,
+        &[AiMutation::Updated(updated.clone())],
+        &HashMap::from([(original.id, original.clone())]),
+    )
+    .await;
+
+    let result = revert_prompt(&state, board_id, Uuid::new_v4(), trace_id)
+        .await
+        .unwrap();
+
+    assert_eq!(result.changes.len(), 1);
+    assert!(result.conflicts.is_empty());
+    assert!(result.mismatch.is_none());
+    let boards = state.boards.read().await;
+    assert!(same_object_state(&boards[&board_id].objects[&original.id], &original));
+}
+
+#[tokio::test]
+async fn sweep_idle_drops_only_stale_logs_of_unloaded_boards() {
+    let state = test_helpers::test_app_state();
+    let loaded = test_helpers::seed_board(&state).await;
+    let (loaded_trace, unloaded_trace) = (Uuid::new_v4(), Uuid::new_v4());
+    let created = [AiMutation::Created(dummy_object())];
+    remember_prompt(&state, loaded, loaded_trace, &created, &HashMap::new()).await;
+    remember_prompt(&state, Uuid::new_v4(), unloaded_trace, &created, &HashMap::new()).await;
+
+    sweep_idle(&state, Instant::now()).await;
+    assert_eq!(state.prompt_logs.read().await.len(), 2);
+
+    sweep_idle(&state, Instant::now() + PROMPT_LOG_IDLE_TTL + Duration::from_secs(1)).await;
+    let logs = state.prompt_logs.read().await;
+    assert!(logs.contains_key(&loaded_trace));
+    assert!(!logs.contains_key(&unloaded_trace));
+}
@@ -5,7 +5,12 @@
 //! Savepoints store a full board snapshot and the current global frame sequence
 //! for the board. This gives fast "rewind from checkpoint + replay tail" later
 //! without turning every operation into a heavyweight snapshot write.
+//!
+//! Restoring a savepoint swaps the live object set under a single board write
+//! lock and records an auto savepoint of the pre-restore state, so a restore
+//! is itself undoable from the rewind shelf.
 
+use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
@@ -23,6 +28,15 @@ pub enum SavepointError {
     /// No board with the given ID was found, or the caller lacks access.
     #[error("board not found or not accessible: {0}")]
     BoardNotFound(Uuid),
+    /// No savepoint with the given ID exists on the board.
+    #[error("savepoint not found: {0}")]
+    SavepointNotFound(Uuid),
+    /// The stored snapshot could not be decoded into board objects.
+    #[error("invalid savepoint snapshot: {0}")]
+    InvalidSnapshot(String),
+    /// The board kept changing while a restore was trying to apply.
+    #[error("board changed during restore, try again: {0}")]
+    Busy(Uuid),
     /// A Postgres query failed.
     #[error("database error: {0}")]
     Database(#[from] sqlx::Error),
@@ -32,9 +46,16 @@ impl crate::frame::ErrorCode for SavepointError {
     fn error_code(&self) -> &'static str {
         match self {
             Self::BoardNotFound(_) => "E_BOARD_NOT_FOUND",
+            Self::SavepointNotFound(_) => "E_SAVEPOINT_NOT_FOUND",
+            Self::InvalidSnapshot(_) => "E_INVALID_SNAPSHOT",
+            Self::Busy(_) => "E_RESTORE_BUSY",
             Self::Database(_) => "E_DATABASE",
         }
     }
+
+    fn retryable(&self) -> bool {
+        matches!(self, Self::Busy(_))
+    }
 }
 
 /// A savepoint record as stored in and retrieved from the database.
@@ -60,6 +81,36 @@ pub struct SavepointRow {
     pub snapshot: serde_json::Value,
 }
 
+/// Object-level changes produced by restoring a savepoint.
+#[derive(Debug, Clone, Default)]
+pub struct RestoreDiff {
+    /// Objects present in the snapshot but missing from the live board.
+    pub created: Vec<BoardObject>,
+    /// Objects present in both whose fields differ from the snapshot.
+    pub updated: Vec<BoardObject>,
+    /// Live objects absent from the snapshot.
+    pub deleted: Vec<Uuid>,
+}
+
+impl RestoreDiff {
+    /// Whether the restore changes nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.updated.is_empty() && self.deleted.is_empty()
+    }
+}
+
+/// Result of a savepoint restore.
+#[derive(Debug, Clone)]
+pub struct RestoreResult {
+    /// The savepoint that was restored.
+    pub restored: SavepointRow,
+    /// Auto savepoint capturing the board state immediately before the restore.
+    pub pre_restore: SavepointRow,
+    /// Changes applied to the live board.
+    pub diff: RestoreDiff,
+}
+
 fn now_ms() -> i64 {
     let Ok(dur) = SystemTime::now().duration_since(UNIX_EPOCH) else {
         return 0;
@@ -134,14 +185,6 @@ async fn snapshot_objects(state: &AppState, board_id: Uuid) -> Result<Vec<BoardO
     Ok(objects)
 }
 
-async fn current_board_seq(pool: &PgPool, board_id: Uuid) -> Result<i64, SavepointError> {
-    let seq: Option<i64> = sqlx::query_scalar("SELECT MAX(seq) FROM frames WHERE board_id = $1")
-        .bind(board_id)
-        .fetch_one(pool)
-        .await?;
-    Ok(seq.unwrap_or(0))
-}
-
 pub async fn create_savepoint(
     state: &AppState,
     board_id: Uuid,
@@ -152,8 +195,20 @@ pub async fn create_savepoint(
 ) -> Result<SavepointRow, SavepointError> {
     ensure_board_access(&state.pool, board_id, user_id).await?;
     let objects = snapshot_objects(state, board_id).await?;
+    insert_savepoint(&state.pool, board_id, user_id, &objects, label, is_auto, reason).await
+}
+
+async fn insert_savepoint(
+    pool: &PgPool,
+    board_id: Uuid,
+    user_id: Uuid,
+    objects: &[BoardObject],
+    label: Option<&str>,
+    is_auto: bool,
+    reason: &str,
+) -> Result<SavepointRow, SavepointError> {
     let snapshot = serde_json::to_value(objects).unwrap_or_else(|_| serde_json::json!([]));
-    let seq = current_board_seq(&state.pool, board_id).await?;
+    let seq = board::latest_frame_seq(pool, board_id).await?;
 
     let row = SavepointRow {
         id: Uuid::new_v4(),
@@ -180,7 +235,7 @@ pub async fn create_savepoint(
     .bind(&row.reason)
     .bind(&row.label)
     .bind(&row.snapshot)
-    .execute(&state.pool)
+    .execute(pool)
     .await?;
 
     Ok(row)
@@ -264,6 +319,229 @@ pub async fn maybe_create_auto_savepoint(
     Ok(Some(row))
 }
 
+async fn fetch_savepoint(pool: &PgPool, board_id: Uuid, savepoint_id: Uuid) -> Result<SavepointRow, SavepointError> {
+    let row = sqlx::query_as::<
+        _,
+        (
+            Uuid,
+            Uuid,
+            i64,
+            i64,
+            Option<Uuid>,
+            bool,
+            String,
+            Option<String>,
+            serde_json::Value,
+        ),
+    >(
+        "SELECT id, board_id, seq, ts, created_by, is_auto, reason, label, snapshot
+         FROM board_savepoints
+         WHERE id = $1 AND board_id = $2",
+    )
+    .bind(savepoint_id)
+    .bind(board_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some((id, board_id, seq, ts, created_by, is_auto, reason, label, snapshot)) = row else {
+        return Err(SavepointError::SavepointNotFound(savepoint_id));
+    };
+    Ok(SavepointRow { id, board_id, seq, ts, created_by, is_auto, reason, label, snapshot })
+}
+
+/// Decode a stored snapshot into board objects re-homed onto `board_id`.
+///
+/// # Errors
+///
+/// Returns `InvalidSnapshot` if the JSON is not an array of board objects.
+pub fn decode_snapshot(board_id: Uuid, snapshot: &serde_json::Value) -> Result<Vec<BoardObject>, SavepointError> {
+    let mut objects = serde_json::from_value::<Vec<BoardObject>>(snapshot.clone())
+        .map_err(|e| SavepointError::InvalidSnapshot(e.to_string()))?;
+    for obj in &mut objects {
+        obj.board_id = board_id;
+    }
+    Ok(objects)
+}
+
+/// Whether two objects have the same visible state, ignoring version.
+#[must_use]
+pub fn same_object_state(a: &BoardObject, b: &BoardObject) -> bool {
+    a.kind == b.kind
+        && a.x.to_bits() == b.x.to_bits()
+        && a.y.to_bits() == b.y.to_bits()
+        && a.width.map(f64::to_bits) == b.width.map(f64::to_bits)
+        && a.height.map(f64::to_bits) == b.height.map(f64::to_bits)
+        && a.rotation.to_bits() == b.rotation.to_bits()
+        && a.z_index == b.z_index
+        && a.props == b.props
+        && a.group_id == b.group_id
+}
+
+/// Compute the object changes needed to turn `current` into `snapshot`.
+///
+/// Updated objects take a version one past the live version so in-flight
+/// edits based on the pre-restore state are rejected as stale.
+#[must_use]
+pub fn diff_restore(current: &HashMap<Uuid, BoardObject>, snapshot: Vec<BoardObject>) -> RestoreDiff {
+    let mut diff = RestoreDiff::default();
+    let mut restored_ids = std::collections::HashSet::with_capacity(snapshot.len());
+
+    for mut obj in snapshot {
+        restored_ids.insert(obj.id);
+        match current.get(&obj.id) {
+            None => diff.created.push(obj),
+            Some(live) if !same_object_state(live, &obj) => {
+                obj.version = live.version + 1;
+                diff.updated.push(obj);
+            }
+            Some(_) => {}
+        }
+    }
+
+    diff.deleted = current
+        .keys()
+        .filter(|id| !restored_ids.contains(id))
+        .copied()
+        .collect();
+    diff.deleted.sort_unstable();
+    diff
+}
+
+/// Restores attempted before giving up on a board that keeps changing
+/// between the pre-restore snapshot and the swap.
+const RESTORE_ATTEMPTS: usize = 3;
+
+/// Restore a board to the state captured by a savepoint.
+///
+/// An auto savepoint of the pre-restore state is recorded first, so a failed
+/// write leaves the board untouched. The live object set is then swapped
+/// under one board write lock, removed objects are deleted from Postgres,
+/// and restored objects are persisted (via the dirty set when the board is
+/// live, directly otherwise).
+///
+/// # Errors
+///
+/// Returns `BoardNotFound` on access failure, `SavepointNotFound` if the
+/// savepoint does not belong to the board, `Busy` if the board kept changing
+/// during the restore, or a database error.
+pub async fn restore_savepoint(
+    state: &AppState,
+    board_id: Uuid,
+    user_id: Uuid,
+    savepoint_id: Uuid,
+) -> Result<RestoreResult, SavepointError> {
+    ensure_board_access(&state.pool, board_id, user_id).await?;
+    let restored = fetch_savepoint(&state.pool, board_id, savepoint_id).await?;
+    let snapshot = decode_snapshot(board_id, &restored.snapshot)?;
+
+    for _ in 0..RESTORE_ATTEMPTS {
+        // PHASE: RECORD PRE-RESTORE STATE
+        // WHY: the undo point must exist before the board changes, and a
+        // database failure here must leave the board as it was.
+        let before = snapshot_objects(state, board_id).await?;
+        let pre_restore =
+            insert_savepoint(&state.pool, board_id, user_id, &before, Some("Before restore"), true, "restore").await?;
+
+        // PHASE: SWAP LIVE OBJECTS
+        // WHY: re-check under the write lock that nothing changed since the
+        // snapshot, so the pre-restore savepoint is exactly what the swap
+        // replaces.
+        let swapped = {
+            let mut boards = state.boards.write().await;
+            match boards.get_mut(&board_id) {
+                Some(board_state) if unchanged_since(&board_state.objects, &before) => {
+                    let diff = diff_restore(&board_state.objects, snapshot.clone());
+                    for id in &diff.deleted {
+                        board_state.objects.remove(id);
+                        board_state.dirty.remove(id);
+                        board_state.field_versions.remove(id);
+                    }
+                    // Restored objects are replaced wholesale, so per-field history restarts.
+                    for obj in diff.created.iter().chain(diff.updated.iter()) {
+                        board_state.dirty.insert(obj.id);
+                        board_state.field_versions.remove(&obj.id);
+                        board_state.objects.insert(obj.id, obj.clone());
+                    }
+                    Swap::Live(diff)
+                }
+                Some(_) => Swap::Changed,
+                None => Swap::NotLoaded,
+            }
+        };
+
+        let diff = match swapped {
+            Swap::Live(diff) => {
+                // The live board already changed and must be broadcast, so a
+                // failed delete is logged rather than returned.
+                if let Err(e) = delete_objects(&state.pool, board_id, &diff.deleted).await {
+                    tracing::error!(%board_id, error = %e, "failed to delete objects removed by restore");
+                }
+                diff
+            }
+            Swap::NotLoaded => {
+                // Board is not loaded: diff against Postgres and write through.
+                let current = before.iter().map(|obj| (obj.id, obj.clone())).collect();
+                let diff = diff_restore(&current, snapshot);
+                let changed = diff
+                    .created
+                    .iter()
+                    .chain(diff.updated.iter())
+                    .cloned()
+                    .collect::<Vec<_>>();
+                board::flush_objects(&state.pool, &changed).await?;
+                delete_objects(&state.pool, board_id, &diff.deleted).await?;
+                diff
+            }
+            Swap::Changed => {
+                // An edit landed after the snapshot; drop the stale undo point and retry.
+                if let Err(e) = sqlx::query("DELETE FROM board_savepoints WHERE id = $1")
+                    .bind(pre_restore.id)
+                    .execute(&state.pool)
+                    .await
+                {
+                    tracing::warn!(%board_id, error = %e, "failed to drop stale pre-restore savepoint");
+                }
+                continue;
+            }
+        };
+        return Ok(RestoreResult { restored, pre_restore, diff });
+    }
+
+    Err(SavepointError::Busy(board_id))
+}
+
+/// Outcome of the locked phase of a restore attempt.
+enum Swap {
+    /// The live board was swapped onto the snapshot.
+    Live(RestoreDiff),
+    /// The board is not loaded; Postgres is the live copy.
+    NotLoaded,
+    /// The board changed after the pre-restore snapshot.
+    Changed,
+}
+
+/// Whether `current` still holds exactly the objects captured in `before`.
+fn unchanged_since(current: &HashMap<Uuid, BoardObject>, before: &[BoardObject]) -> bool {
+    current.len() == before.len()
+        && before.iter().all(|obj| {
+            current
+                .get(&obj.id)
+                .is_some_and(|live| live.version == obj.version && same_object_state(live, obj))
+        })
+}
+
+async fn delete_objects(pool: &PgPool, board_id: Uuid, ids: &[Uuid]) -> Result<(), sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    sqlx::query("DELETE FROM board_objects WHERE board_id = $1 AND id = ANY($2)")
+        .bind(board_id)
+        .bind(ids)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
 #[cfg(test)]
 #[path = "savepoint_test.rs"]
 mod tests;
@@ -78,3 +78,112 @@ fn savepoint_rows_to_json_maps_all_rows() {
     assert_eq!(result[0]["seq"], 1);
     assert_eq!(result[1]["seq"], 2);
 }
+
+fn object_at(x: f64, version: i32) -> BoardObject {
+    BoardObject {
+        id: Uuid::new_v4(),
+        board_id: Uuid::nil(),
+        kind: "rectangle".to_owned(),
+        x,
+        y: 0.0,
+        width: Some(10.0),
+        height: Some(10.0),
+        rotation: 0.0,
+        z_index: 0,
+        props: serde_json::json!({}),
+        created_by: None,
+        version,
+        group_id: None,
+    }
+}
+
+#[test]
+fn diff_restore_classifies_created_updated_and_deleted() {
+    let kept = object_at(0.0, 1);
+    let mut moved = object_at(10.0, 4);
+    let removed = object_at(20.0, 1);
+    let recreated = object_at(30.0, 2);
+
+    let current: HashMap<Uuid, BoardObject> = [kept.clone(), moved.clone(), removed.clone()]
+        .into_iter()
+        .map(|obj| (obj.id, obj))
+        .collect();
+    moved.x = 99.0;
+    moved.version = 2;
+
+    let diff = diff_restore(&current, vec![kept, moved.clone(), recreated.clone()]);
+
+    assert_eq!(diff.created.len(), 1);
+    assert_eq!(diff.created[0].id, recreated.id);
+    assert_eq!(diff.created[0].version, 2);
+    assert_eq!(diff.updated.len(), 1);
+    assert_eq!(diff.updated[0].id, moved.id);
+    assert!((diff.updated[0].x - 99.0).abs() < f64::EPSILON);
+    assert_eq!(diff.updated[0].version, 5, "restored version should supersede the live version");
+    assert_eq!(diff.deleted, vec![removed.id]);
+}
+
+#[test]
+fn diff_restore_of_identical_snapshot_is_empty() {
+    let a = object_at(0.0, 3);
+    let current: HashMap<Uuid, BoardObject> = std::iter::once((a.id, a.clone())).collect();
+
+    let diff = diff_restore(&current, vec![a]);
+
+    assert!(diff.is_empty());
+}
+
+#[test]
+fn unchanged_since_detects_edits_additions_and_removals() {
+    let a = object_at(0.0, 1);
+    let b = object_at(10.0, 1);
+    let before = vec![a.clone(), b.clone()];
+    let current: HashMap<Uuid, BoardObject> = before.iter().map(|obj| (obj.id, obj.clone())).collect();
+    assert!(unchanged_since(&current, &before));
+
+    let mut edited = current.clone();
+    edited.insert(a.id, BoardObject { version: 2, ..a.clone() });
+    assert!(!unchanged_since(&edited, &before));
+
+    let mut moved = current.clone();
+    moved.insert(b.id, BoardObject { x: 11.0, ..b.clone() });
+    assert!(!unchanged_since(&moved, &before));
+
+    let mut grown = current.clone();
+    let c = object_at(20.0, 1);
+    grown.insert(c.id, c);
+    assert!(!unchanged_since(&grown, &before));
+
+    let mut shrunk = current;
+    shrunk.remove(&b.id);
+    assert!(!unchanged_since(&shrunk, &before));
+}
+
+#[test]
+fn busy_restore_is_retryable() {
+    use crate::frame::ErrorCode;
+
+    let err = SavepointError::Busy(Uuid::nil());
+    assert_eq!(err.error_code(), "E_RESTORE_BUSY");
+    assert!(err.retryable());
+    assert!(!SavepointError::SavepointNotFound(Uuid::nil()).retryable());
+}
+
+#[test]
+fn decode_snapshot_rehomes_objects_onto_board() {
+    let board_id = Uuid::new_v4();
+    let obj = object_at(5.0, 1);
+    let snapshot = serde_json::to_value(vec![obj.clone()]).expect("snapshot should serialize");
+
+    let objects = decode_snapshot(board_id, &snapshot).expect("snapshot should decode");
+
+    assert_eq!(objects.len(), 1);
+    assert_eq!(objects[0].id, obj.id);
+    assert_eq!(objects[0].board_id, board_id);
+}
+
+#[test]
+fn decode_snapshot_rejects_non_array() {
+    let err = decode_snapshot(Uuid::nil(), &serde_json::json!({"kind": "rectangle"})).expect_err("should fail");
+    assert!(matches!(err, SavepointError::InvalidSnapshot(_)));
+}
@@ -0,0 +1,696 @@
+//! Scene service — boards as standalone SVG documents for export.
+//!
+//! DESIGN
+//! ======
+//! Every visual export starts here. Objects are converted into the `canvas`
+//! crate's document model and written in the browser renderer's
+//! `(z_index, id)` order as SVG elements that mirror their `canvas::render`
+//! counterparts: shapes fill, stroke, and center their wrapped label; frames
+//! draw a header band and title; connectors resolve attached endpoints
+//! against the whole board, even when only part of it is exported; inline SVG
+//! is embedded as an image so its ids and styles cannot leak into the rest of
+//! the scene, falling back to the same placeholder the browser shows.
+//!
+//! Labels are wrapped here with the host's sans-serif font metrics, so line
+//! breaks match the canvas whichever program opens the file. On a host
+//! without fonts an average glyph width stands in.
+//!
+//! `raster` renders scenes to PNG and `pdf` converts them to PDF.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::sync::{Arc, OnceLock};
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use canvas::consts::STAR_INNER_RATIO;
+use canvas::doc::{BoardObject as CanvasObject, DocStore, ObjectKind, Props, WorldBounds, object_world_bounds};
+use canvas::hit;
+use canvas::render::{
+    ARROW_ANGLE, ARROW_SIZE, ATTACHED_ANCHOR_RADIUS_WORLD, FRAME_BODY_FILL, FRAME_HEADER_FILL, FRAME_TITLE_COLOR,
+    fit_text_with_ellipsis, wrap_text_lines,
+};
+use resvg::usvg::{self, fontdb};
+use uuid::Uuid;
+
+use crate::mermaid::ExportScope;
+use crate::services::board::{self, BoardError, BoardPermission};
+use crate::state::{AppState, BoardObject};
+
+/// World-space margin around whole-board and selection exports.
+const PADDING: f64 = 24.0;
+/// Scene extent in world units when the board is empty.
+const EMPTY_WIDTH: f64 = 320.0;
+const EMPTY_HEIGHT: f64 = 200.0;
+const BACKGROUND: &str = "#F6F1E7";
+/// Glyph width, in ems, assumed when the host has no fonts to measure with.
+const FALLBACK_GLYPH_WIDTH: f64 = 0.55;
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// Errors returned by scene service operations.
+#[derive(Debug, thiserror::Error)]
+pub enum SceneError {
+    /// The requested frame is not on the board.
+    #[error("frame not found: {0}")]
+    FrameNotFound(Uuid),
+    /// None of the requested object IDs are on the board.
+    #[error("no selected objects on the board")]
+    NothingSelected,
+    /// The generated document could not be parsed for rendering.
+    #[error("invalid scene: {0}")]
+    Invalid(String),
+}
+
+/// A board, or part of one, as an SVG document.
+#[derive(Debug, Clone)]
+pub struct Scene {
+    /// Standalone SVG markup with one user unit per world unit.
+    pub svg: String,
+    /// Document size in world units.
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Scene {
+    /// Parse the document with the system fonts for rendering.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Invalid` if the markup does not parse.
+    pub fn tree(&self) -> Result<usvg::Tree, SceneError> {
+        usvg::Tree::from_str(&self.svg, &svg_options()).map_err(|e| SceneError::Invalid(e.to_string()))
+    }
+}
+
+// =============================================================================
+// LOADING
+// =============================================================================
+
+/// Load every object on a board for export.
+///
+/// Prefers the live in-memory board, which may hold edits not yet flushed to
+/// Postgres, and falls back to the persisted objects.
+///
+/// # Errors
+///
+/// Returns `Forbidden`/`NotFound` when the user cannot view the board, or a
+/// database error if a query fails.
+pub async fn load_board_objects(
+    state: &AppState,
+    board_id: Uuid,
+    user_id: Uuid,
+) -> Result<Vec<BoardObject>, BoardError> {
+    let live = {
+        let boards = state.boards.read().await;
+        boards
+            .get(&board_id)
+            .map(|board| board.objects.values().cloned().collect::<Vec<_>>())
+    };
+    if let Some(objects) = live {
+        board::ensure_board_permission(&state.pool, board_id, user_id, BoardPermission::View).await?;
+        return Ok(objects);
+    }
+
+    let rows = board::list_board_export_objects(&state.pool, board_id, user_id).await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| BoardObject {
+            id: row.id,
+            board_id: row.board_id,
+            kind: row.kind,
+            x: row.x,
+            y: row.y,
+            width: row.width,
+            height: row.height,
+            rotation: row.rotation,
+            z_index: row.z_index,
+            props: row.props,
+            created_by: row.created_by,
+            version: row.version,
+            group_id: row.group_id,
+        })
+        .collect())
+}
+
+/// Convert a server object into the `canvas` document model.
+///
+/// Kinds the canvas has no renderer for (such as `sticky_note`) draw as
+/// rectangles, and missing sizes fall back to the client's defaults.
+#[must_use]
+pub fn canvas_object(obj: &BoardObject) -> CanvasObject {
+    CanvasObject {
+        id: obj.id,
+        board_id: obj.board_id,
+        kind: canvas_kind(&obj.kind),
+        x: obj.x,
+        y: obj.y,
+        width: obj.width.unwrap_or(120.0).max(1.0),
+        height: obj.height.unwrap_or(80.0).max(1.0),
+        rotation: obj.rotation,
+        z_index: i64::from(obj.z_index),
+        props: obj.props.clone(),
+        created_by: obj.created_by,
+        version: i64::from(obj.version),
+        group_id: obj.group_id,
+    }
+}
+
+/// The canvas kind used to draw a server object kind.
+#[must_use]
+pub fn canvas_kind(kind: &str) -> ObjectKind {
+    match kind {
+        "text" => ObjectKind::Text,
+        "frame" => ObjectKind::Frame,
+        "ellipse" => ObjectKind::Ellipse,
+        "diamond" => ObjectKind::Diamond,
+        "star" => ObjectKind::Star,
+        "line" => ObjectKind::Line,
+        "arrow" => ObjectKind::Arrow,
+        "svg" => ObjectKind::Svg,
+        _ => ObjectKind::Rect,
+    }
+}
+
+// =============================================================================
+// SCENES
+// =============================================================================
+
+/// Build the SVG scene for `scope`.
+///
+/// A whole board or a selection is padded around its content; a frame scope
+/// crops to the frame's exact bounds and includes everything overlapping it.
+/// A selection also takes the connectors attached to two selected objects.
+///
+/// # Errors
+///
+/// Returns `FrameNotFound` when the scope names something that is not a frame
+/// on the board, or `NothingSelected` when none of the scoped IDs exist.
+pub fn build_scene(objects: &[BoardObject], scope: &ExportScope) -> Result<Scene, SceneError> {
+    let mut doc = DocStore::new();
+    doc.load_snapshot(objects.iter().map(canvas_object).collect());
+
+    let (drawn, bounds) = match scope {
+        ExportScope::Board => {
+            let drawn = doc.sorted_objects();
+            let bounds = content_bounds(&drawn, &doc);
+            (drawn, bounds)
+        }
+        ExportScope::Frame(frame_id) => {
+            let frame = doc
+                .get(frame_id)
+                .filter(|obj| obj.kind == ObjectKind::Frame)
+                .ok_or(SceneError::FrameNotFound(*frame_id))?;
+            let bounds = object_world_bounds(frame);
+            (doc.sorted_objects_in_bounds(bounds), bounds)
+        }
+        ExportScope::Objects(ids) => {
+            let drawn: Vec<&CanvasObject> = doc
+                .sorted_objects()
+                .into_iter()
+                .filter(|obj| ids.contains(&obj.id) || joins(obj, ids))
+                .collect();
+            if drawn.is_empty() {
+                return Err(SceneError::NothingSelected);
+            }
+            let bounds = content_bounds(&drawn, &doc);
+            (drawn, bounds)
+        }
+    };
+
+    let (width, height) = (width_of(bounds), height_of(bounds));
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="{} {} {width} {height}">"#,
+        bounds.min_x, bounds.min_y,
+    );
+    let _ = write!(
+        svg,
+        r#"<rect x="{}" y="{}" width="{width}" height="{height}" fill="{BACKGROUND}"/>"#,
+        bounds.min_x, bounds.min_y,
+    );
+    for obj in drawn {
+        write_object(&mut svg, obj, &doc);
+    }
+    svg.push_str("</svg>");
+    Ok(Scene { svg, width, height })
+}
+
+/// Whether `obj` is a connector with both ends attached to objects in `ids`.
+fn joins(obj: &CanvasObject, ids: &HashSet<Uuid>) -> bool {
+    matches!(obj.kind, ObjectKind::Line | ObjectKind::Arrow)
+        && ["a", "b"].into_iter().all(|key| {
+            obj.props
+                .get(key)
+                .and_then(|end| end.get("object_id"))
+                .and_then(serde_json::Value::as_str)
+                .and_then(|id| id.parse::<Uuid>().ok())
+                .is_some_and(|id| ids.contains(&id))
+        })
+}
+
+/// Padded bounds of `objects`, or a blank canvas when there are none.
+fn content_bounds(objects: &[&CanvasObject], doc: &DocStore) -> WorldBounds {
+    objects
+        .iter()
+        .map(|obj| drawn_bounds(obj, doc))
+        .reduce(|a, b| WorldBounds {
+            min_x: a.min_x.min(b.min_x),
+            min_y: a.min_y.min(b.min_y),
+            max_x: a.max_x.max(b.max_x),
+            max_y: a.max_y.max(b.max_y),
+        })
+        .map_or(
+            WorldBounds { min_x: 0.0, min_y: 0.0, max_x: EMPTY_WIDTH, max_y: EMPTY_HEIGHT },
+            |bounds| bounds.expand(PADDING),
+        )
+}
+
+/// World bounds as drawn: connectors span their resolved endpoints, which
+/// may have moved with the objects they are attached to.
+fn drawn_bounds(obj: &CanvasObject, doc: &DocStore) -> WorldBounds {
+    match (hit::edge_endpoint_a_resolved(obj, doc), hit::edge_endpoint_b_resolved(obj, doc)) {
+        (Some(a), Some(b)) if matches!(obj.kind, ObjectKind::Line | ObjectKind::Arrow) => {
+            WorldBounds { min_x: a.x.min(b.x), min_y: a.y.min(b.y), max_x: a.x.max(b.x), max_y: a.y.max(b.y) }
+        }
+        _ => object_world_bounds(obj),
+    }
+}
+
+fn width_of(bounds: WorldBounds) -> f64 {
+    (bounds.max_x - bounds.min_x).max(1.0)
+}
+
+fn height_of(bounds: WorldBounds) -> f64 {
+    (bounds.max_y - bounds.min_y).max(1.0)
+}
+
+// =============================================================================
+// OBJECT WRITERS
+// =============================================================================
+
+/// Write one object as a `<g>` tagged with its ID. Connectors are written in
+/// world space; everything else in its centered, rotated local space.
+fn write_object(out: &mut String, obj: &CanvasObject, doc: &DocStore) {
+    let props = Props::new(&obj.props);
+    if matches!(obj.kind, ObjectKind::Line | ObjectKind::Arrow) {
+        let _ = write!(out, r#"<g data-object-id="{}">"#, obj.id);
+        write_edge(out, obj, doc, &props);
+        out.push_str("</g>");
+        return;
+    }
+
+    let (w, h) = (obj.width, obj.height);
+    let _ = write!(
+        out,
+        r#"<g data-object-id="{}" transform="translate({} {}) rotate({})">"#,
+        obj.id,
+        obj.x + w / 2.0,
+        obj.y + h / 2.0,
+        obj.rotation,
+    );
+    match obj.kind {
+        ObjectKind::Rect => write_shape(out, obj, &props, &rect_element(w, h)),
+        ObjectKind::Ellipse => {
+            let element = format!(r#"<ellipse rx="{}" ry="{}""#, w / 2.0, h / 2.0);
+            write_shape(out, obj, &props, &element);
+        }
+        ObjectKind::Diamond => {
+            let (hw, hh) = (w / 2.0, h / 2.0);
+            let element = polygon_element(&[(0.0, -hh), (hw, 0.0), (0.0, hh), (-hw, 0.0)]);
+            write_shape(out, obj, &props, &element);
+        }
+        ObjectKind::Star => write_shape(out, obj, &props, &polygon_element(&star_points(w, h))),
+        ObjectKind::Text => write_text(out, obj, &props),
+        ObjectKind::Frame => write_frame(out, obj, &props),
+        ObjectKind::Svg => write_svg(out, obj, &props),
+        ObjectKind::Line | ObjectKind::Arrow => {}
+    }
+    out.push_str("</g>");
+}
+
+/// Fill and stroke an open shape element, then draw the label over it.
+fn write_shape(out: &mut String, obj: &CanvasObject, props: &Props<'_>, element: &str) {
+    let _ = write!(
+        out,
+        "{element}{}{}/>",
+        paint("fill", props.fill()),
+        stroke(props.stroke(), line_width(props))
+    );
+    write_text(out, obj, props);
+}
+
+fn write_frame(out: &mut String, obj: &CanvasObject, props: &Props<'_>) {
+    let (w, h) = (obj.width, obj.height);
+    let title_h = (h * 0.14).clamp(18.0, 28.0);
+    let _ = write!(
+        out,
+        "{}{}{}/>",
+        rect_element(w, h),
+        paint("fill", FRAME_BODY_FILL),
+        stroke(props.stroke(), line_width(props))
+    );
+    let _ = write!(
+        out,
+        r#"<rect x="{}" y="{}" width="{w}" height="{title_h}"{}/>"#,
+        -w / 2.0,
+        -h / 2.0,
+        paint("fill", FRAME_HEADER_FILL)
+    );
+
+    let title = obj
+        .props
+        .get("title")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("Frame");
+    let font_size = (title_h * 0.45).clamp(10.0, 14.0);
+    let line = TextLine { text: title.to_owned(), x: -w / 2.0 + 8.0, y: -h / 2.0 + title_h / 2.0 };
+    write_lines(out, &[line], font_size, FRAME_TITLE_COLOR, "start");
+}
+
+/// Head near the top, wrapped body centered, foot near the bottom.
+fn write_text(out: &mut String, obj: &CanvasObject, props: &Props<'_>) {
+    let (head, text, foot) = (props.head(), props.text(), props.foot());
+    if head.is_empty() && text.is_empty() && foot.is_empty() {
+        return;
+    }
+    let font_size = props
+        .font_size()
+        .unwrap_or_else(|| (obj.height / 6.0).clamp(12.0, 24.0))
+        .clamp(8.0, 96.0);
+    let measure = |s: &str| text_width(s, font_size);
+    let lines = layout_text(obj.width, obj.height, font_size, (head, text, foot), &measure);
+    write_lines(out, &lines, font_size, props.text_color(), "middle");
+}
+
+/// Embed inline SVG as a data-URL image stretched over the object, or draw
+/// the browser's placeholder for markup that does not parse.
+fn write_svg(out: &mut String, obj: &CanvasObject, props: &Props<'_>) {
+    let (w, h) = (obj.width, obj.height);
+    let markup = obj
+        .props
+        .get("svg")
+        .and_then(serde_json::Value::as_str)
+        .filter(|svg| usvg::Tree::from_str(svg, &svg_options()).is_ok());
+    if let Some(markup) = markup {
+        let _ = write!(
+            out,
+            r#"<image x="{}" y="{}" width="{w}" height="{h}" preserveAspectRatio="none" href="data:image/svg+xml;base64,{}"/>"#,
+            -w / 2.0,
+            -h / 2.0,
+            BASE64.encode(markup)
+        );
+        return;
+    }
+
+    let _ = write!(
+        out,
+        "{}{}{}/>",
+        rect_element(w, h),
+        paint("fill", props.fill()),
+        stroke(props.stroke(), line_width(props))
+    );
+    let label = obj
+        .props
+        .get("title")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("SVG");
+    let font_size = (h / 5.0).clamp(10.0, 20.0);
+    let line = TextLine { text: label.to_owned(), x: 0.0, y: 0.0 };
+    write_lines(out, &[line], font_size, props.text_color(), "middle");
+}
+
+fn write_edge(out: &mut String, obj: &CanvasObject, doc: &DocStore, props: &Props<'_>) {
+    let (Some(a), Some(b)) = (hit::edge_endpoint_a_resolved(obj, doc), hit::edge_endpoint_b_resolved(obj, doc)) else {
+        return;
+    };
+    let color = props.stroke();
+    let _ = write!(
+        out,
+        r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{}/>"#,
+        a.x,
+        a.y,
+        b.x,
+        b.y,
+        stroke(color, line_width(props))
+    );
+
+    if obj.kind == ObjectKind::Arrow {
+        let angle = (b.y - a.y).atan2(b.x - a.x);
+        let head = polygon_element(&[
+            (b.x, b.y),
+            (
+                b.x - ARROW_SIZE * (angle - ARROW_ANGLE).cos(),
+                b.y - ARROW_SIZE * (angle - ARROW_ANGLE).sin(),
+            ),
+            (
+                b.x - ARROW_SIZE * (angle + ARROW_ANGLE).cos(),
+                b.y - ARROW_SIZE * (angle + ARROW_ANGLE).sin(),
+            ),
+        ]);
+        let _ = write!(out, "{head}{}/>", paint("fill", color));
+    }
+
+    // Attachment markers so snapped endpoints are visible.
+    for (point, key) in [(a, "a"), (b, "b")] {
+        if endpoint_is_attached(obj, key) {
+            let _ = write!(
+                out,
+                r##"<circle cx="{}" cy="{}" r="{ATTACHED_ANCHOR_RADIUS_WORLD}" fill="#ffffff"/>"##,
+                point.x, point.y
+            );
+        }
+    }
+}
+
+/// Pre-positioned lines of text in the object's local space.
+fn write_lines(out: &mut String, lines: &[TextLine], font_size: f64, color: &str, anchor: &str) {
+    if lines.is_empty() {
+        return;
+    }
+    let _ = write!(
+        out,
+        r#"<text font-family="sans-serif" font-size="{font_size}"{} text-anchor="{anchor}" dominant-baseline="central">"#,
+        paint("fill", color)
+    );
+    for line in lines {
+        let _ = write!(
+            out,
+            r#"<tspan x="{}" y="{}">{}</tspan>"#,
+            line.x,
+            line.y,
+            escape_xml(&line.text)
+        );
+    }
+    out.push_str("</text>");
+}
+
+/// `fill` or `stroke` attributes for a CSS color. Colors SVG cannot express
+/// are left unpainted.
+fn paint(attr: &str, raw: &str) -> String {
+    let Ok(color) = raw.trim().parse::<svgtypes::Color>() else {
+        return format!(r#" {attr}="none""#);
+    };
+    let hex = format!("#{:02x}{:02x}{:02x}", color.red, color.green, color.blue);
+    if color.alpha == u8::MAX {
+        format!(r#" {attr}="{hex}""#)
+    } else {
+        format!(r#" {attr}="{hex}" {attr}-opacity="{}""#, f64::from(color.alpha) / 255.0)
+    }
+}
+
+fn stroke(raw: &str, width: f64) -> String {
+    format!(r#"{} stroke-width="{width}""#, paint("stroke", raw))
+}
+
+/// Canvas ignores a zero `lineWidth`, so the browser strokes at its 1px
+/// default when `strokeWidth` is unset.
+fn line_width(props: &Props<'_>) -> f64 {
+    let width = props.stroke_width();
+    if width > 0.0 { width } else { 1.0 }
+}
+
+fn endpoint_is_attached(obj: &CanvasObject, key: &str) -> bool {
+    obj.props
+        .get(key)
+        .and_then(|v| v.get("type"))
+        .and_then(serde_json::Value::as_str)
+        == Some("attached")
+}
+
+/// An unclosed `<rect` centered on the origin.
+fn rect_element(width: f64, height: f64) -> String {
+    format!(
+        r#"<rect x="{}" y="{}" width="{width}" height="{height}""#,
+        -width / 2.0,
+        -height / 2.0
+    )
+}
+
+/// An unclosed `<polygon` through `points`.
+fn polygon_element(points: &[(f64, f64)]) -> String {
+    let points: Vec<String> = points.iter().map(|(x, y)| format!("{x},{y}")).collect();
+    format!(r#"<polygon points="{}""#, points.join(" "))
+}
+
+/// Ten alternating outer and inner vertices, starting at the top.
+fn star_points(width: f64, height: f64) -> Vec<(f64, f64)> {
+    let (half_w, half_h) = (width / 2.0, height / 2.0);
+    let step = std::f64::consts::PI / 5.0;
+    (0..10)
+        .map(|i| {
+            let angle = step.mul_add(f64::from(i), -std::f64::consts::FRAC_PI_2);
+            let ratio = if i % 2 == 0 { 1.0 } else { STAR_INNER_RATIO };
+            (half_w * ratio * angle.cos(), half_h * ratio * angle.sin())
+        })
+        .collect()
+}
+
+fn escape_xml(raw: &str) -> String {
+    raw.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// =============================================================================
+// TEXT LAYOUT
+// =============================================================================
+
+/// One line of text positioned in an object's centered local space.
+#[derive(Debug, Clone, PartialEq)]
+struct TextLine {
+    text: String,
+    x: f64,
+    y: f64,
+}
+
+/// Position head, body, and foot lines the way `canvas::render` does: the
+/// body wraps to the object's width minus padding and is truncated with an
+/// ellipsis when it would overflow the height.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn layout_text(
+    width: f64,
+    height: f64,
+    font_size: f64,
+    (head, text, foot): (&str, &str, &str),
+    mut measure: impl FnMut(&str) -> f64,
+) -> Vec<TextLine> {
+    let mut out = Vec::new();
+    let max_w = (width - 12.0).max(1.0);
+    let hh = height / 2.0;
+    if !head.is_empty() {
+        out.push(TextLine { text: fit_text_with_ellipsis(head, max_w, &mut measure), x: 0.0, y: -hh + font_size });
+    }
+    if !text.is_empty() {
+        let line_height = (font_size * 1.25).max(12.0);
+        let max_lines = ((height / line_height).floor() as usize).max(1);
+        let mut lines = wrap_text_lines(text, max_w, &mut measure);
+        if lines.len() > max_lines {
+            lines.truncate(max_lines);
+            if let Some(last) = lines.last_mut() {
+                *last = fit_text_with_ellipsis(last, max_w, &mut measure);
+            }
+        }
+        let start_y = -line_height * (lines.len().saturating_sub(1) as f64) * 0.5;
+        for (idx, line) in lines.into_iter().enumerate() {
+            out.push(TextLine { text: line, x: 0.0, y: start_y + idx as f64 * line_height });
+        }
+    }
+    if !foot.is_empty() {
+        out.push(TextLine { text: fit_text_with_ellipsis(foot, max_w, &mut measure), x: 0.0, y: hh - font_size });
+    }
+    out
+}
+
+// =============================================================================
+// FONTS
+// =============================================================================
+
+struct Fonts {
+    db: Arc<fontdb::Database>,
+    /// Face used for sans-serif text, if the host has any fonts.
+    sans: Option<fontdb::ID>,
+}
+
+/// System fonts, loaded once per process.
+fn fonts() -> &'static Fonts {
+    static FONTS: OnceLock<Fonts> = OnceLock::new();
+    FONTS.get_or_init(|| {
+        let mut db = fontdb::Database::new();
+        db.load_system_fonts();
+        let query = |db: &fontdb::Database| {
+            db.query(&fontdb::Query { families: &[fontdb::Family::SansSerif], ..fontdb::Query::default() })
+        };
+        // The default sans-serif family is Arial; use whatever the host has.
+        if query(&db).is_none() {
+            let fallback = ["DejaVu Sans", "Liberation Sans", "Noto Sans"]
+                .into_iter()
+                .find(|name| {
+                    db.faces()
+                        .any(|face| face.families.iter().any(|(family, _)| family == name))
+                })
+                .map(str::to_owned)
+                .or_else(|| {
+                    db.faces()
+                        .next()
+                        .and_then(|face| face.families.first())
+                        .map(|(family, _)| family.clone())
+                });
+            if let Some(family) = fallback {
+                db.set_sans_serif_family(family);
+            }
+        }
+        let sans = query(&db);
+        Fonts { db: Arc::new(db), sans }
+    })
+}
+
+/// Parse options for board SVG. Image hrefs resolve only from data URLs so
+/// user markup cannot read files from the server's disk.
+#[must_use]
+pub fn svg_options() -> usvg::Options<'static> {
+    usvg::Options {
+        fontdb: Arc::clone(&fonts().db),
+        image_href_resolver: usvg::ImageHrefResolver {
+            resolve_data: usvg::ImageHrefResolver::default_data_resolver(),
+            resolve_string: Box::new(|_, _| None),
+        },
+        ..usvg::Options::default()
+    }
+}
+
+/// Advance width of `text` in the sans-serif face, without kerning.
+/// Characters the face lacks count as half an em.
+#[allow(clippy::cast_precision_loss)]
+fn text_width(text: &str, font_size: f64) -> f64 {
+    let fallback = || text.chars().count() as f64 * FALLBACK_GLYPH_WIDTH * font_size;
+    let Some(id) = fonts().sans else {
+        return fallback();
+    };
+    fonts()
+        .db
+        .with_face_data(id, |data, index| {
+            let face = ttf_parser::Face::parse(data, index).ok()?;
+            let units: f64 = text
+                .chars()
+                .map(|ch| {
+                    face.glyph_index(ch)
+                        .and_then(|glyph| face.glyph_hor_advance(glyph))
+                        .map_or(f64::from(face.units_per_em()) / 2.0, f64::from)
+                })
+                .sum();
+            Some(units / f64::from(face.units_per_em()) * font_size)
+        })
+        .flatten()
+        .unwrap_or_else(fallback)
+}
+
+#[cfg(test)]
+#[path = "scene_test.rs"]
+mod tests;
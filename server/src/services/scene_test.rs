@@ -0,0 +1,239 @@
+use super::*;
+use crate::state::test_helpers;
+
+fn shape(kind: &str, x: f64, y: f64, width: f64, height: f64, props: serde_json::Value) -> BoardObject {
+    let mut obj = test_helpers::dummy_object();
+    obj.kind = kind.to_owned();
+    obj.x = x;
+    obj.y = y;
+    obj.width = Some(width);
+    obj.height = Some(height);
+    obj.props = props;
+    obj
+}
+
+fn connector(kind: &str, from: &BoardObject, to: &BoardObject) -> BoardObject {
+    let mut obj = test_helpers::dummy_object();
+    obj.kind = kind.to_owned();
+    obj.props = serde_json::json!({
+        "a": { "type": "attached", "object_id": from.id.to_string(), "ux": 1.0, "uy": 0.5, "x": 0.0, "y": 0.0 },
+        "b": { "type": "attached", "object_id": to.id.to_string(), "ux": 0.0, "uy": 0.5, "x": 0.0, "y": 0.0 },
+        "stroke": "#000000",
+    });
+    obj
+}
+
+/// Fixed-width measure: every character is 10 units wide.
+fn mono(text: &str) -> f64 {
+    text.chars().map(|_| 10.0).sum()
+}
+
+// =============================================================================
+// conversion
+// =============================================================================
+
+#[test]
+fn canvas_kind_draws_unknown_kinds_as_rects() {
+    assert_eq!(canvas_kind("rectangle"), ObjectKind::Rect);
+    assert_eq!(canvas_kind("sticky_note"), ObjectKind::Rect);
+    assert_eq!(canvas_kind("ellipse"), ObjectKind::Ellipse);
+    assert_eq!(canvas_kind("arrow"), ObjectKind::Arrow);
+    assert_eq!(canvas_kind("svg"), ObjectKind::Svg);
+}
+
+#[test]
+fn canvas_object_fills_in_default_size() {
+    let obj = test_helpers::dummy_object();
+    let converted = canvas_object(&obj);
+    assert_eq!(converted.id, obj.id);
+    assert_eq!(converted.kind, ObjectKind::Rect);
+    assert!((converted.width - 120.0).abs() < f64::EPSILON);
+    assert!((converted.height - 80.0).abs() < f64::EPSILON);
+}
+
+// =============================================================================
+// build_scene
+// =============================================================================
+
+#[test]
+fn build_scene_pads_whole_board() {
+    let rect = shape("rectangle", 0.0, 0.0, 100.0, 50.0, serde_json::json!({}));
+    let scene = build_scene(std::slice::from_ref(&rect), &ExportScope::Board).unwrap();
+    assert!((scene.width - 148.0).abs() < f64::EPSILON);
+    assert!((scene.height - 98.0).abs() < f64::EPSILON);
+    assert!(
+        scene.svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="148" height="98" viewBox="-24 -24 148 98">"#
+        )
+    );
+    assert!(
+        scene
+            .svg
+            .contains(&format!(r#"data-object-id="{}""#, rect.id))
+    );
+    assert!(scene.tree().is_ok());
+}
+
+#[test]
+fn build_scene_of_empty_board_is_blank() {
+    let scene = build_scene(&[], &ExportScope::Board).unwrap();
+    assert!((scene.width - 320.0).abs() < f64::EPSILON);
+    assert!((scene.height - 200.0).abs() < f64::EPSILON);
+    assert!(!scene.svg.contains("data-object-id"));
+}
+
+#[test]
+fn build_scene_writes_geometry_rotation_and_paint() {
+    let mut rect = shape(
+        "ellipse",
+        10.0,
+        20.0,
+        100.0,
+        50.0,
+        serde_json::json!({ "fill": "rgba(255, 0, 0, 0.2)", "stroke": "#00F", "strokeWidth": 3.0 }),
+    );
+    rect.rotation = 45.0;
+    let scene = build_scene(&[rect], &ExportScope::Board).unwrap();
+    assert!(
+        scene
+            .svg
+            .contains(r#"transform="translate(60 45) rotate(45)""#)
+    );
+    assert!(scene.svg.contains(
+        r##"<ellipse rx="50" ry="25" fill="#ff0000" fill-opacity="0.2" stroke="#0000ff" stroke-width="3"/>"##
+    ));
+}
+
+#[test]
+fn build_scene_wraps_and_escapes_labels() {
+    let rect = shape(
+        "rectangle",
+        0.0,
+        0.0,
+        80.0,
+        200.0,
+        serde_json::json!({ "text": "one two three four five six <&>", "fontSize": 14.0 }),
+    );
+    let scene = build_scene(&[rect], &ExportScope::Board).unwrap();
+    assert!(scene.svg.matches("<tspan").count() > 1);
+    assert!(scene.svg.contains("&lt;&amp;&gt;"));
+}
+
+#[test]
+fn build_scene_crops_to_frame_and_keeps_overlapping_objects() {
+    let frame = shape("frame", 100.0, 100.0, 200.0, 150.0, serde_json::json!({ "title": "Plan" }));
+    let inside = shape("rectangle", 280.0, 120.0, 100.0, 50.0, serde_json::json!({}));
+    let outside = shape("rectangle", 1000.0, 1000.0, 50.0, 50.0, serde_json::json!({}));
+    let scope = ExportScope::Frame(frame.id);
+    let scene = build_scene(&[frame.clone(), inside.clone(), outside.clone()], &scope).unwrap();
+    assert!(scene.svg.contains(r#"viewBox="100 100 200 150""#));
+    assert!(scene.svg.contains(&frame.id.to_string()));
+    assert!(scene.svg.contains(&inside.id.to_string()));
+    assert!(!scene.svg.contains(&outside.id.to_string()));
+    assert!(scene.svg.contains(">Plan</tspan>"));
+}
+
+#[test]
+fn build_scene_reports_missing_frame() {
+    let rect = shape("rectangle", 0.0, 0.0, 100.0, 50.0, serde_json::json!({}));
+    assert!(matches!(
+        build_scene(std::slice::from_ref(&rect), &ExportScope::Frame(rect.id)),
+        Err(SceneError::FrameNotFound(id)) if id == rect.id
+    ));
+}
+
+#[test]
+fn build_scene_selection_takes_connectors_between_selected_objects() {
+    let a = shape("rectangle", 0.0, 0.0, 40.0, 40.0, serde_json::json!({}));
+    let b = shape("rectangle", 200.0, 0.0, 40.0, 40.0, serde_json::json!({}));
+    let c = shape("rectangle", 0.0, 300.0, 40.0, 40.0, serde_json::json!({}));
+    let joined = connector("arrow", &a, &b);
+    let dangling = connector("line", &a, &c);
+    let objects = [a.clone(), b.clone(), c.clone(), joined.clone(), dangling.clone()];
+
+    let scope = ExportScope::Objects([a.id, b.id].into_iter().collect());
+    let scene = build_scene(&objects, &scope).unwrap();
+    assert!(scene.svg.contains(&joined.id.to_string()));
+    assert!(!scene.svg.contains(&dangling.id.to_string()));
+    assert!(!scene.svg.contains(&c.id.to_string()));
+    // Attached ends resolve to the shapes' facing edges.
+    assert!(
+        scene
+            .svg
+            .contains(r#"<line x1="40" y1="20" x2="200" y2="20""#)
+    );
+    assert!(scene.svg.contains("<polygon points=\"200,20 "));
+    assert!(scene.svg.contains("<circle "));
+    assert!((scene.width - 288.0).abs() < f64::EPSILON);
+}
+
+#[test]
+fn build_scene_resolves_connectors_against_unselected_objects() {
+    let a = shape("rectangle", 0.0, 0.0, 40.0, 40.0, serde_json::json!({}));
+    let b = shape("rectangle", 200.0, 0.0, 40.0, 40.0, serde_json::json!({}));
+    let joined = connector("line", &a, &b);
+    let scope = ExportScope::Objects(std::iter::once(joined.id).collect());
+    let scene = build_scene(&[a.clone(), b, joined], &scope).unwrap();
+    assert!(!scene.svg.contains(&a.id.to_string()));
+    assert!(
+        scene
+            .svg
+            .contains(r#"<line x1="40" y1="20" x2="200" y2="20""#)
+    );
+}
+
+#[test]
+fn build_scene_reports_empty_selection() {
+    let rect = shape("rectangle", 0.0, 0.0, 100.0, 50.0, serde_json::json!({}));
+    let scope = ExportScope::Objects(std::iter::once(Uuid::new_v4()).collect());
+    assert!(matches!(build_scene(&[rect], &scope), Err(SceneError::NothingSelected)));
+}
+
+#[test]
+fn build_scene_embeds_inline_svg_as_isolated_image() {
+    let markup = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><rect id="a" width="10" height="10" fill="#00FF00"/></svg>"##;
+    let svg = shape("svg", 0.0, 0.0, 100.0, 50.0, serde_json::json!({ "svg": markup }));
+    let scene = build_scene(&[svg], &ExportScope::Board).unwrap();
+    let expected = format!(
+        r#"<image x="-50" y="-25" width="100" height="50" preserveAspectRatio="none" href="data:image/svg+xml;base64,{}"/>"#,
+        BASE64.encode(markup)
+    );
+    assert!(scene.svg.contains(&expected));
+    assert!(!scene.svg.contains(r#"id="a""#));
+}
+
+#[test]
+fn build_scene_draws_placeholder_for_bad_svg() {
+    let svg = shape(
+        "svg",
+        0.0,
+        0.0,
+        100.0,
+        50.0,
+        serde_json::json!({ "svg": "not svg", "title": "Logo" }),
+    );
+    let scene = build_scene(&[svg], &ExportScope::Board).unwrap();
+    assert!(!scene.svg.contains("<image"));
+    assert!(scene.svg.contains(">Logo</tspan>"));
+}
+
+// =============================================================================
+// text layout
+// =============================================================================
+
+#[test]
+fn layout_text_centers_body_and_pins_head_and_foot() {
+    let lines = layout_text(120.0, 100.0, 10.0, ("Head", "aaa bbb", "Foot"), &mono);
+    let texts: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+    assert_eq!(texts, vec!["Head", "aaa bbb", "Foot"]);
+    assert!((lines[0].y + 40.0).abs() < f64::EPSILON);
+    assert!(lines[1].y.abs() < f64::EPSILON);
+    assert!((lines[2].y - 40.0).abs() < f64::EPSILON);
+}
+
+#[test]
+fn layout_text_drops_lines_past_the_height() {
+    let lines = layout_text(60.0, 30.0, 10.0, ("", "aaaa bbbb cccc", ""), &mono);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1].text, "bbbb");
+}
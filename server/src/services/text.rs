@@ -0,0 +1,239 @@
+//! Text service — collaborative editing of object text props.
+//!
+//! DESIGN
+//! ======
+//! Each editable text field (`text`, `head`, `foot` in an object's props)
+//! carries a revision counter and a bounded log of the operations that
+//! produced recent revisions. A client submits an operation against the
+//! revision it last saw; the service transforms it past every later
+//! operation, applies it to the live props, and returns the transformed
+//! operation with the new revision for broadcast. Because every replica
+//! transforms with the same tie-break, concurrent typists converge instead
+//! of overwriting each other.
+//!
+//! If a field is rewritten outside this service (an `object:update`, a
+//! savepoint restore), the log no longer matches the live text. The log is
+//! then reset to a fresh revision so clients holding an older revision are
+//! told to resync rather than having operations land on the wrong text.
+
+use frames::text_ops::{TextOp, TextOpError, transform};
+use uuid::Uuid;
+
+use crate::state::{AppState, BoardObject, FieldVersions, TextLog};
+
+/// Props fields that support collaborative editing.
+pub const TEXT_FIELDS: [&str; 3] = ["text", "head", "foot"];
+
+/// Most operations kept per field; older revisions must resync.
+const TEXT_LOG_CAPACITY: usize = 256;
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// Errors returned by text service operations.
+#[derive(Debug, thiserror::Error)]
+pub enum TextError {
+    /// No object with the given ID exists on the board.
+    #[error("object not found: {0}")]
+    NotFound(Uuid),
+    /// The board has not been loaded into memory yet.
+    #[error("board not loaded: {0}")]
+    BoardNotLoaded(Uuid),
+    /// The field is not one of [`TEXT_FIELDS`].
+    #[error("field does not support collaborative editing: {0}")]
+    InvalidField(String),
+    /// The client claims a revision the server has not reached.
+    #[error("text revision {incoming} is ahead of current {current}")]
+    RevisionAhead { incoming: u64, current: u64 },
+    /// The client's revision has been trimmed from the log or reset.
+    #[error("text revision {incoming} is older than {oldest}; resync required")]
+    RevisionExpired { incoming: u64, oldest: u64 },
+    /// The operation is malformed or does not fit the text.
+    #[error("invalid text operation: {0}")]
+    InvalidOp(#[from] TextOpError),
+}
+
+impl crate::frame::ErrorCode for TextError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "E_OBJECT_NOT_FOUND",
+            Self::BoardNotLoaded(_) => "E_BOARD_NOT_LOADED",
+            Self::InvalidField(_) => "E_INVALID_TEXT_FIELD",
+            Self::RevisionAhead { .. } => "E_TEXT_REVISION_AHEAD",
+            Self::RevisionExpired { .. } => "E_TEXT_REVISION_EXPIRED",
+            Self::InvalidOp(_) => "E_INVALID_TEXT_OP",
+        }
+    }
+}
+
+/// Result of applying a text operation.
+#[derive(Debug, Clone)]
+pub struct TextEdit {
+    /// The operation as applied, transformed past concurrent edits.
+    pub op: TextOp,
+    /// Revision reached by this operation.
+    pub rev: u64,
+    /// Field text after the operation.
+    pub text: String,
+    /// Object after the edit, with its bumped version.
+    pub object: BoardObject,
+}
+
+// =============================================================================
+// HELPERS
+// =============================================================================
+
+fn ensure_text_field(field: &str) -> Result<(), TextError> {
+    if TEXT_FIELDS.contains(&field) {
+        Ok(())
+    } else {
+        Err(TextError::InvalidField(field.to_owned()))
+    }
+}
+
+fn field_text(obj: &BoardObject, field: &str) -> String {
+    obj.props
+        .get(field)
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default()
+        .to_owned()
+}
+
+/// Reset the log to a fresh revision if the live text changed elsewhere.
+fn sync_log(log: &mut TextLog, current: &str) {
+    if log.text != current {
+        log.rev += 1;
+        log.history.clear();
+        current.clone_into(&mut log.text);
+    }
+}
+
+/// Oldest revision an incoming operation may be based on.
+fn oldest_rev(log: &TextLog) -> u64 {
+    log.rev - log.history.len() as u64
+}
+
+// =============================================================================
+// STATE
+// =============================================================================
+
+/// Current revision and text of a field, for a client opening an editor.
+///
+/// # Errors
+///
+/// Returns `InvalidField`, `BoardNotLoaded`, or `NotFound`.
+pub async fn text_state(
+    state: &AppState,
+    board_id: Uuid,
+    object_id: Uuid,
+    field: &str,
+) -> Result<(u64, String), TextError> {
+    ensure_text_field(field)?;
+    let mut boards = state.boards.write().await;
+    let board = boards
+        .get_mut(&board_id)
+        .ok_or(TextError::BoardNotLoaded(board_id))?;
+    let obj = board
+        .objects
+        .get(&object_id)
+        .ok_or(TextError::NotFound(object_id))?;
+    let current = field_text(obj, field);
+    let log = board
+        .text_logs
+        .entry(object_id)
+        .or_default()
+        .entry(field.to_owned())
+        .or_default();
+    sync_log(log, &current);
+    Ok((log.rev, current))
+}
+
+// =============================================================================
+// APPLY
+// =============================================================================
+
+/// Apply an operation based on revision `rev` to an object's text field.
+///
+/// The operation is transformed past every operation applied after `rev`,
+/// then written to the live props. The object version bumps and the field
+/// is recorded in its field-level versions so a concurrent `object:update`
+/// built from an older version cannot overwrite the merged text.
+///
+/// # Errors
+///
+/// Returns `RevisionAhead` or `RevisionExpired` when `rev` is outside the
+/// log, `InvalidOp` when the operation does not fit the text, and the usual
+/// lookup errors.
+pub async fn apply_text_op(
+    state: &AppState,
+    board_id: Uuid,
+    object_id: Uuid,
+    field: &str,
+    rev: u64,
+    op: TextOp,
+) -> Result<TextEdit, TextError> {
+    ensure_text_field(field)?;
+    let mut boards = state.boards.write().await;
+    let board = boards
+        .get_mut(&board_id)
+        .ok_or(TextError::BoardNotLoaded(board_id))?;
+    let obj = board
+        .objects
+        .get_mut(&object_id)
+        .ok_or(TextError::NotFound(object_id))?;
+    let current = field_text(obj, field);
+    let log = board
+        .text_logs
+        .entry(object_id)
+        .or_default()
+        .entry(field.to_owned())
+        .or_default();
+    sync_log(log, &current);
+
+    if rev > log.rev {
+        return Err(TextError::RevisionAhead { incoming: rev, current: log.rev });
+    }
+    let oldest = oldest_rev(log);
+    if rev < oldest {
+        return Err(TextError::RevisionExpired { incoming: rev, oldest });
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    let skip = (rev - oldest) as usize;
+    let mut op = op;
+    for concurrent in log.history.iter().skip(skip) {
+        op = transform(&op, concurrent)?.0;
+    }
+    let text = op.apply(&current)?;
+
+    // PHASE: WRITE LIVE PROPS
+    if !obj.props.is_object() {
+        obj.props = serde_json::json!({});
+    }
+    if let Some(props) = obj.props.as_object_mut() {
+        props.insert(field.to_owned(), serde_json::json!(text));
+    }
+    board
+        .field_versions
+        .entry(object_id)
+        .or_insert_with(|| FieldVersions::starting_at(obj.version))
+        .fields
+        .insert(format!("props.{field}"), obj.version + 1);
+    obj.version += 1;
+    board.dirty.insert(object_id);
+
+    // PHASE: ADVANCE LOG
+    log.history.push_back(op.clone());
+    if log.history.len() > TEXT_LOG_CAPACITY {
+        log.history.pop_front();
+    }
+    log.rev += 1;
+    text.clone_into(&mut log.text);
+
+    Ok(TextEdit { op, rev: log.rev, text, object: obj.clone() })
+}
+
+#[cfg(test)]
+#[path = "text_test.rs"]
+mod tests;
@@ -0,0 +1,119 @@
+use super::*;
+use crate::state::test_helpers;
+
+async fn seed_sticky(state: &AppState, text: &str) -> (Uuid, Uuid) {
+    let mut obj = test_helpers::dummy_object();
+    obj.props = serde_json::json!({ "text": text, "color": "#FFEB3B" });
+    let obj_id = obj.id;
+    let board_id = test_helpers::seed_board_with_objects(state, vec![obj]).await;
+    (board_id, obj_id)
+}
+
+#[tokio::test]
+async fn text_state_reports_current_text_and_revision() {
+    let state = test_helpers::test_app_state();
+    let (board_id, obj_id) = seed_sticky(&state, "hello").await;
+
+    let (rev, text) = text_state(&state, board_id, obj_id, "text").await.unwrap();
+    assert_eq!(text, "hello");
+    let (again, _) = text_state(&state, board_id, obj_id, "text").await.unwrap();
+    assert_eq!(rev, again);
+}
+
+#[tokio::test]
+async fn text_state_rejects_non_text_field() {
+    let state = test_helpers::test_app_state();
+    let (board_id, obj_id) = seed_sticky(&state, "hello").await;
+
+    let result = text_state(&state, board_id, obj_id, "color").await;
+    assert!(matches!(result, Err(TextError::InvalidField(field)) if field == "color"));
+}
+
+#[tokio::test]
+async fn apply_text_op_writes_props_and_bumps_versions() {
+    let state = test_helpers::test_app_state();
+    let (board_id, obj_id) = seed_sticky(&state, "hello").await;
+    let (rev, _) = text_state(&state, board_id, obj_id, "text").await.unwrap();
+
+    let op = TextOp::new().retain(5).insert(" world");
+    let edit = apply_text_op(&state, board_id, obj_id, "text", rev, op)
+        .await
+        .unwrap();
+
+    assert_eq!(edit.rev, rev + 1);
+    assert_eq!(edit.text, "hello world");
+    assert_eq!(edit.object.version, 2);
+    assert_eq!(edit.object.props["color"], "#FFEB3B");
+
+    let boards = state.boards.read().await;
+    let board = boards.get(&board_id).unwrap();
+    assert_eq!(board.objects[&obj_id].props["text"], "hello world");
+    assert!(board.dirty.contains(&obj_id));
+    assert_eq!(board.field_versions[&obj_id].version_of("props.text"), 2);
+}
+
+#[tokio::test]
+async fn concurrent_text_ops_both_survive() {
+    let state = test_helpers::test_app_state();
+    let (board_id, obj_id) = seed_sticky(&state, "ac").await;
+    let (rev, _) = text_state(&state, board_id, obj_id, "text").await.unwrap();
+
+    // Both typists edit from the same revision.
+    let first = TextOp::new().insert("<").retain(2);
+    let second = TextOp::new().retain(1).insert("b").retain(1);
+    apply_text_op(&state, board_id, obj_id, "text", rev, first)
+        .await
+        .unwrap();
+    let edit = apply_text_op(&state, board_id, obj_id, "text", rev, second)
+        .await
+        .unwrap();
+
+    assert_eq!(edit.text, "<abc");
+    assert_eq!(edit.rev, rev + 2);
+    assert_eq!(edit.op, TextOp::new().retain(2).insert("b").retain(1));
+}
+
+#[tokio::test]
+async fn apply_text_op_rejects_revision_ahead() {
+    let state = test_helpers::test_app_state();
+    let (board_id, obj_id) = seed_sticky(&state, "x").await;
+    let (rev, _) = text_state(&state, board_id, obj_id, "text").await.unwrap();
+
+    let result = apply_text_op(&state, board_id, obj_id, "text", rev + 5, TextOp::new().retain(1)).await;
+    assert!(matches!(result, Err(TextError::RevisionAhead { .. })));
+}
+
+#[tokio::test]
+async fn external_rewrite_expires_old_revisions() {
+    let state = test_helpers::test_app_state();
+    let (board_id, obj_id) = seed_sticky(&state, "old").await;
+    let (rev, _) = text_state(&state, board_id, obj_id, "text").await.unwrap();
+
+    {
+        let mut boards = state.boards.write().await;
+        let obj = boards
+            .get_mut(&board_id)
+            .unwrap()
+            .objects
+            .get_mut(&obj_id)
+            .unwrap();
+        obj.props["text"] = serde_json::json!("rewritten");
+    }
+
+    let result = apply_text_op(&state, board_id, obj_id, "text", rev, TextOp::new().retain(3).insert("!")).await;
+    assert!(matches!(result, Err(TextError::RevisionExpired { .. })));
+
+    let (fresh, text) = text_state(&state, board_id, obj_id, "text").await.unwrap();
+    assert!(fresh > rev);
+    assert_eq!(text, "rewritten");
+}
+
+#[tokio::test]
+async fn apply_text_op_rejects_op_that_does_not_fit() {
+    let state = test_helpers::test_app_state();
+    let (board_id, obj_id) = seed_sticky(&state, "abc").await;
+    let (rev, _) = text_state(&state, board_id, obj_id, "text").await.unwrap();
+
+    let result = apply_text_op(&state, board_id, obj_id, "text", rev, TextOp::new().retain(10)).await;
+    assert!(matches!(result, Err(TextError::InvalidOp(_))));
+}
@@ -4,13 +4,15 @@
 //! This module strips the prefix and routes the operation to the AI tool-execution layer,
 //! then assembles the result into the `done` frame payload that is returned to the caller.
 
+use std::collections::{HashMap, HashSet};
+
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::frame::{Data, Frame};
-use crate::state::AppState;
+use crate::state::{AppState, BoardObject};
 
-use super::ai::{AiError, AiMutation};
+use super::ai::{AiError, AiMutation, ToolAccess};
 
 /// The result of executing a single tool syscall.
 ///
@@ -25,6 +27,8 @@ pub(crate) struct ToolSyscallResult {
     pub done_data: Data,
     /// Board object mutations produced by the tool (creates, updates, deletes).
     pub mutations: Vec<AiMutation>,
+    /// Pre-tool state of each existing object the tool updated or deleted, keyed by ID.
+    pub before: HashMap<Uuid, BoardObject>,
 }
 
 /// Parse a `tool:<name>` syscall frame, execute the named tool, and return a [`ToolSyscallResult`].
@@ -32,14 +36,17 @@ pub(crate) struct ToolSyscallResult {
 /// The `syscall` field of `req` must have the form `"tool:<operation>"`. The operation name is
 /// extracted, the `"input"` field of `req.data` is forwarded to the AI tool executor, and the
 /// resulting content and mutations are packaged for the caller to broadcast and acknowledge.
+/// Under [`ToolAccess::ReadOnly`] only read-only tools run.
 ///
 /// # Errors
 ///
 /// Returns [`AiError::InvalidToolSyscall`] when the syscall string is missing or empty after
-/// stripping the `"tool:"` prefix. Propagates any error returned by the underlying tool executor.
+/// stripping the `"tool:"` prefix, and [`AiError::ToolNotPermitted`] for a mutating tool under
+/// read-only access. Propagates any error returned by the underlying tool executor.
 pub(crate) async fn dispatch_tool_frame(
     state: &AppState,
     board_id: Uuid,
+    access: ToolAccess,
     req: &Frame,
 ) -> Result<ToolSyscallResult, AiError> {
     let tool_name = req.syscall.split_once(':').map_or("", |(_, op)| op).trim();
@@ -53,8 +60,20 @@ pub(crate) async fn dispatch_tool_frame(
         .cloned()
         .unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new()));
 
+    // Tools only update or delete objects named in their input, so snapshot
+    // just those rather than the whole board.
+    let mut referenced = Vec::new();
+    collect_object_ids(&input, &mut referenced);
+    let mut before = super::history::objects_before(state, board_id, &referenced).await;
+
     let mut mutations = Vec::new();
-    let content = super::ai::execute_tool(state, board_id, tool_name, &input, &mut mutations).await?;
+    let content =
+        super::ai::execute_tool_with_access(state, board_id, access, tool_name, &input, &mut mutations).await?;
+    let touched = mutations
+        .iter()
+        .map(AiMutation::object_id)
+        .collect::<HashSet<_>>();
+    before.retain(|id, _| touched.contains(id));
 
     let mut done_data = Data::new();
     if let Some(tool_use_id) = req.data.get("tool_use_id") {
@@ -63,5 +82,23 @@ pub(crate) async fn dispatch_tool_frame(
     done_data.insert("content".into(), json!(content));
     done_data.insert("mutations".into(), json!(mutations.len()));
 
-    Ok(ToolSyscallResult { content, done_data, mutations })
+    Ok(ToolSyscallResult { content, done_data, mutations, before })
+}
+
+/// Collect every string in a tool input that parses as a UUID.
+fn collect_object_ids(value: &serde_json::Value, ids: &mut Vec<Uuid>) {
+    match value {
+        serde_json::Value::String(s) => {
+            if let Ok(id) = s.parse::<Uuid>() {
+                ids.push(id);
+            }
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|item| collect_object_ids(item, ids)),
+        serde_json::Value::Object(map) => map.values().for_each(|item| collect_object_ids(item, ids)),
+        _ => {}
+    }
 }
+
+#[cfg(test)]
+#[path = "tool_syscall_test.rs"]
+mod tests;
@@ -0,0 +1,46 @@
+use super::*;
+use crate::state::test_helpers;
+
+fn tool_frame(board_id: Uuid, tool: &str, input: serde_json::Value) -> Frame {
+    let mut data = Data::new();
+    data.insert("input".into(), input);
+    Frame::request(format!("tool:{tool}"), data).with_board_id(board_id)
+}
+
+#[test]
+fn collect_object_ids_walks_nested_input() {
+    let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+    let mut ids = Vec::new();
+    collect_object_ids(
+        &json!({ "objectId": a.to_string(), "scope": [b.to_string(), "not-a-uuid"], "x": 4 }),
+        &mut ids,
+    );
+    ids.sort();
+    let mut expected = vec![a, b];
+    expected.sort();
+    assert_eq!(ids, expected);
+}
+
+#[tokio::test]
+async fn dispatch_returns_prior_state_of_mutated_objects_only() {
+    let state = test_helpers::test_app_state();
+    let moved = test_helpers::dummy_object();
+    let bystander = test_helpers::dummy_object();
+    let (moved_id, bystander_id) = (moved.id, bystander.id);
+    let board_id = test_helpers::seed_board_with_objects(&state, vec![moved, bystander]).await;
+
+    let req = tool_frame(
+        board_id,
+        "moveObject",
+        json!({ "objectId": moved_id.to_string(), "x": 500.0, "y": 600.0 }),
+    );
+    let outcome = dispatch_tool_frame(&state, board_id, ToolAccess::Full, &req)
+        .await
+        .unwrap();
+
+    assert_eq!(outcome.mutations.len(), 1);
+    assert_eq!(outcome.before.len(), 1);
+    let prior = &outcome.before[&moved_id];
+    assert!((prior.x - 100.0).abs() < f64::EPSILON);
+    assert!(!outcome.before.contains_key(&bystander_id));
+}
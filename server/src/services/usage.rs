@@ -0,0 +1,536 @@
+//! AI usage service — durable token ledger and per-user/per-board budgets.
+//!
+//! DESIGN
+//! ======
+//! Every LLM call made for a prompt appends a row to `ai_usage` with the
+//! user, board, model, token counts, and estimated cost. Inserts run off the
+//! request path; a failed insert is logged rather than failing the prompt.
+//!
+//! Budgets cap total tokens per user and per board over the current UTC day
+//! and month. Operator defaults come from env vars; `ai_budgets` rows
+//! (cached in `AppState`) can only tighten them. Budgets are checked against
+//! the ledger before each LLM call reserves from the in-memory
+//! `RateLimiter`, and the check does no I/O when no limit applies.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::llm::types::ChatResponse;
+use crate::state::AppState;
+
+const MS_PER_DAY: i64 = 86_400_000;
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// Errors returned by usage and budget operations.
+#[derive(Debug, thiserror::Error)]
+pub enum UsageError {
+    /// A token budget for the current period is used up.
+    #[error("{scope} {period} AI token budget exceeded ({used}/{limit} tokens)")]
+    BudgetExceeded {
+        scope: &'static str,
+        period: &'static str,
+        limit: i64,
+        used: i64,
+    },
+    /// A Postgres query failed.
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl crate::frame::ErrorCode for UsageError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::BudgetExceeded { .. } => "E_BUDGET_EXCEEDED",
+            Self::Database(_) => "E_DATABASE",
+        }
+    }
+}
+
+/// What a budget applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    User,
+    Board,
+}
+
+impl BudgetScope {
+    /// Name stored in `ai_budgets.scope`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Board => "board",
+        }
+    }
+
+    /// Parse a stored or requested scope name.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "user" => Some(Self::User),
+            "board" => Some(Self::Board),
+            _ => None,
+        }
+    }
+}
+
+/// Token limits for one user or board; `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BudgetLimits {
+    pub daily_tokens: Option<i64>,
+    pub monthly_tokens: Option<i64>,
+}
+
+impl BudgetLimits {
+    /// Whether neither period is capped.
+    #[must_use]
+    pub fn is_unlimited(&self) -> bool {
+        self.daily_tokens.is_none() && self.monthly_tokens.is_none()
+    }
+
+    /// The stricter limit of `self` and `other` for each period.
+    #[must_use]
+    pub fn stricter(self, other: Self) -> Self {
+        fn min(a: Option<i64>, b: Option<i64>) -> Option<i64> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            }
+        }
+        Self {
+            daily_tokens: min(self.daily_tokens, other.daily_tokens),
+            monthly_tokens: min(self.monthly_tokens, other.monthly_tokens),
+        }
+    }
+}
+
+/// Tokens spent in the current UTC day and month.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PeriodUsage {
+    pub daily_tokens: i64,
+    pub monthly_tokens: i64,
+}
+
+/// One LLM call to add to the ledger.
+#[derive(Debug, Clone)]
+pub struct UsageRecord {
+    pub user_id: Uuid,
+    pub board_id: Uuid,
+    pub trace_id: Option<Uuid>,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_creation_tokens: u64,
+    /// Estimated cost; `None` for models without known pricing.
+    pub cost_usd: Option<f64>,
+}
+
+impl UsageRecord {
+    /// Build a ledger row from a provider response.
+    #[must_use]
+    pub fn from_response(user_id: Uuid, board_id: Uuid, trace_id: Option<Uuid>, response: &ChatResponse) -> Self {
+        Self {
+            user_id,
+            board_id,
+            trace_id,
+            model: response.model.clone(),
+            input_tokens: response.input_tokens,
+            output_tokens: response.output_tokens,
+            cache_read_tokens: response.cache_read_tokens,
+            cache_creation_tokens: response.cache_creation_tokens,
+            cost_usd: response.cost_usd(),
+        }
+    }
+}
+
+// =============================================================================
+// LEDGER
+// =============================================================================
+
+/// Append an LLM call to `ai_usage` in the background.
+pub fn record_usage(state: &AppState, record: UsageRecord) {
+    let pool = state.pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = insert_usage(&pool, &record).await {
+            warn!(error = %e, user_id = %record.user_id, board_id = %record.board_id, "usage: failed to record LLM call");
+        }
+    });
+}
+
+async fn insert_usage(pool: &PgPool, record: &UsageRecord) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO ai_usage
+            (user_id, board_id, trace_id, model, input_tokens, output_tokens,
+             cache_read_tokens, cache_creation_tokens, cost_usd)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+    )
+    .bind(record.user_id)
+    .bind(record.board_id)
+    .bind(record.trace_id)
+    .bind(&record.model)
+    .bind(to_i64(record.input_tokens))
+    .bind(to_i64(record.output_tokens))
+    .bind(to_i64(record.cache_read_tokens))
+    .bind(to_i64(record.cache_creation_tokens))
+    .bind(record.cost_usd)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn to_i64(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+// =============================================================================
+// BUDGETS
+// =============================================================================
+
+fn env_limit(key: &str) -> Option<i64> {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+}
+
+/// Operator default limits for a scope, from `AI_{USER,BOARD}_{DAILY,MONTHLY}_TOKEN_BUDGET`.
+fn default_limits(scope: BudgetScope) -> BudgetLimits {
+    static USER: OnceLock<BudgetLimits> = OnceLock::new();
+    static BOARD: OnceLock<BudgetLimits> = OnceLock::new();
+    match scope {
+        BudgetScope::User => *USER.get_or_init(|| BudgetLimits {
+            daily_tokens: env_limit("AI_USER_DAILY_TOKEN_BUDGET"),
+            monthly_tokens: env_limit("AI_USER_MONTHLY_TOKEN_BUDGET"),
+        }),
+        BudgetScope::Board => *BOARD.get_or_init(|| BudgetLimits {
+            daily_tokens: env_limit("AI_BOARD_DAILY_TOKEN_BUDGET"),
+            monthly_tokens: env_limit("AI_BOARD_MONTHLY_TOKEN_BUDGET"),
+        }),
+    }
+}
+
+/// Effective limits for one user or board.
+pub async fn effective_limits(state: &AppState, scope: BudgetScope, scope_id: Uuid) -> BudgetLimits {
+    let overrides = state
+        .ai_budgets
+        .read()
+        .await
+        .get(&(scope, scope_id))
+        .copied()
+        .unwrap_or_default();
+    default_limits(scope).stricter(overrides)
+}
+
+/// Load all `ai_budgets` rows into the in-memory cache.
+///
+/// # Errors
+///
+/// Returns a database error if the query fails.
+pub async fn load_budgets(state: &AppState) -> Result<(), UsageError> {
+    let rows = sqlx::query_as::<_, (String, Uuid, Option<i64>, Option<i64>)>(
+        "SELECT scope, scope_id, daily_tokens, monthly_tokens FROM ai_budgets",
+    )
+    .fetch_all(&state.pool)
+    .await?;
+    let mut budgets = state.ai_budgets.write().await;
+    budgets.clear();
+    for (scope, scope_id, daily_tokens, monthly_tokens) in rows {
+        if let Some(scope) = BudgetScope::parse(&scope) {
+            budgets.insert((scope, scope_id), BudgetLimits { daily_tokens, monthly_tokens });
+        }
+    }
+    info!(count = budgets.len(), "usage: loaded AI budgets");
+    Ok(())
+}
+
+/// Store a budget override and update the cache. Unlimited limits remove it.
+///
+/// # Errors
+///
+/// Returns a database error if the write fails.
+pub async fn set_budget(
+    state: &AppState,
+    scope: BudgetScope,
+    scope_id: Uuid,
+    limits: BudgetLimits,
+) -> Result<(), UsageError> {
+    if limits.is_unlimited() {
+        sqlx::query("DELETE FROM ai_budgets WHERE scope = $1 AND scope_id = $2")
+            .bind(scope.as_str())
+            .bind(scope_id)
+            .execute(&state.pool)
+            .await?;
+        state.ai_budgets.write().await.remove(&(scope, scope_id));
+        return Ok(());
+    }
+    sqlx::query(
+        "INSERT INTO ai_budgets (scope, scope_id, daily_tokens, monthly_tokens)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (scope, scope_id)
+         DO UPDATE SET daily_tokens = EXCLUDED.daily_tokens, monthly_tokens = EXCLUDED.monthly_tokens",
+    )
+    .bind(scope.as_str())
+    .bind(scope_id)
+    .bind(limits.daily_tokens)
+    .bind(limits.monthly_tokens)
+    .execute(&state.pool)
+    .await?;
+    state
+        .ai_budgets
+        .write()
+        .await
+        .insert((scope, scope_id), limits);
+    Ok(())
+}
+
+/// Reject the call if the user's or board's budget for the current period is
+/// used up.
+///
+/// # Errors
+///
+/// Returns `BudgetExceeded` for the first exhausted budget, or a database
+/// error if summing the ledger fails.
+pub async fn check_budgets(state: &AppState, user_id: Uuid, board_id: Uuid) -> Result<(), UsageError> {
+    let user_limits = effective_limits(state, BudgetScope::User, user_id).await;
+    let board_limits = effective_limits(state, BudgetScope::Board, board_id).await;
+    if user_limits.is_unlimited() && board_limits.is_unlimited() {
+        return Ok(());
+    }
+    let (user_usage, board_usage) = period_usage(&state.pool, user_id, board_id, now_ms()).await?;
+    if let Some(err) = exceeded(BudgetScope::User, user_limits, user_usage) {
+        return Err(err);
+    }
+    if let Some(err) = exceeded(BudgetScope::Board, board_limits, board_usage) {
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// The first period of `limits` that `usage` has used up, if any.
+#[must_use]
+pub fn exceeded(scope: BudgetScope, limits: BudgetLimits, usage: PeriodUsage) -> Option<UsageError> {
+    let periods = [
+        ("daily", limits.daily_tokens, usage.daily_tokens),
+        ("monthly", limits.monthly_tokens, usage.monthly_tokens),
+    ];
+    periods.into_iter().find_map(|(period, limit, used)| {
+        let limit = limit?;
+        (used >= limit).then_some(UsageError::BudgetExceeded { scope: scope.as_str(), period, limit, used })
+    })
+}
+
+/// Per-row ledger expression matching [`ChatResponse::billable_tokens`]:
+/// cache reads count at a tenth, rounded up.
+const BILLABLE_TOKENS_SQL: &str = "input_tokens + (cache_read_tokens + 9) / 10 + cache_creation_tokens + output_tokens";
+
+/// Billable tokens spent by the user and on the board in the current day and
+/// month.
+///
+/// # Errors
+///
+/// Returns a database error if the query fails.
+pub async fn period_usage(
+    pool: &PgPool,
+    user_id: Uuid,
+    board_id: Uuid,
+    now_ms: i64,
+) -> Result<(PeriodUsage, PeriodUsage), UsageError> {
+    let (day_start, month_start) = period_starts(now_ms);
+    let sql = format!(
+        "SELECT
+            COALESCE(SUM({BILLABLE_TOKENS_SQL}) FILTER (WHERE user_id = $1 AND ts >= $3), 0)::BIGINT,
+            COALESCE(SUM({BILLABLE_TOKENS_SQL}) FILTER (WHERE user_id = $1), 0)::BIGINT,
+            COALESCE(SUM({BILLABLE_TOKENS_SQL}) FILTER (WHERE board_id = $2 AND ts >= $3), 0)::BIGINT,
+            COALESCE(SUM({BILLABLE_TOKENS_SQL}) FILTER (WHERE board_id = $2), 0)::BIGINT
+         FROM ai_usage
+         WHERE ts >= $4 AND (user_id = $1 OR board_id = $2)"
+    );
+    let (user_day, user_month, board_day, board_month) = sqlx::query_as::<_, (i64, i64, i64, i64)>(&sql)
+        .bind(user_id)
+        .bind(board_id)
+        .bind(day_start)
+        .bind(month_start)
+        .fetch_one(pool)
+        .await?;
+    Ok((
+        PeriodUsage { daily_tokens: user_day, monthly_tokens: user_month },
+        PeriodUsage { daily_tokens: board_day, monthly_tokens: board_month },
+    ))
+}
+
+/// Start of the UTC day and month containing `now_ms`, in milliseconds.
+#[must_use]
+pub fn period_starts(now_ms: i64) -> (i64, i64) {
+    let day_start = now_ms - now_ms.rem_euclid(MS_PER_DAY);
+    let month_start = time::OffsetDateTime::from_unix_timestamp(day_start / 1000)
+        .ok()
+        .and_then(|dt| dt.date().replace_day(1).ok())
+        .map_or(day_start, |date| date.midnight().assume_utc().unix_timestamp() * 1000);
+    (day_start, month_start)
+}
+
+fn now_ms() -> i64 {
+    let Ok(dur) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) else {
+        return 0;
+    };
+    i64::try_from(dur.as_millis()).unwrap_or(0)
+}
+
+// =============================================================================
+// REPORTS
+// =============================================================================
+
+/// Whose usage a report covers.
+#[derive(Debug, Clone, Copy)]
+pub enum ReportScope {
+    /// One user's calls across all boards, broken down by board.
+    User(Uuid),
+    /// All calls on one board, broken down by user.
+    Board(Uuid),
+}
+
+/// Aggregated ledger totals.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageTotals {
+    pub calls: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cost_usd: f64,
+}
+
+/// Totals for one day, model, user, or board.
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageBucket {
+    pub key: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+/// Usage report for the dashboard.
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReport {
+    /// Start of the reported window in milliseconds since the Unix epoch.
+    pub since: i64,
+    pub totals: UsageTotals,
+    pub by_day: Vec<UsageBucket>,
+    pub by_model: Vec<UsageBucket>,
+    /// Per-board totals for user reports, per-user totals for board reports.
+    pub breakdown: Vec<UsageBucket>,
+    /// Effective budget for the report's user or board.
+    pub limits: BudgetLimits,
+    /// Spend counted against `limits` in the current day and month.
+    pub current: PeriodUsage,
+}
+
+/// Build a usage report covering the last `days` days.
+///
+/// # Errors
+///
+/// Returns a database error if a query fails.
+pub async fn usage_report(state: &AppState, scope: ReportScope, days: i64) -> Result<UsageReport, UsageError> {
+    let now = now_ms();
+    let since = now - days.max(1) * MS_PER_DAY;
+    let (filter_column, scope_id, breakdown_column, budget_scope) = match scope {
+        ReportScope::User(id) => ("user_id", id, "board_id::text", BudgetScope::User),
+        ReportScope::Board(id) => ("board_id", id, "user_id::text", BudgetScope::Board),
+    };
+
+    let day_key = "to_char(to_timestamp(ts / 1000.0) AT TIME ZONE 'UTC', 'YYYY-MM-DD')";
+    let by_day = grouped_totals(&state.pool, filter_column, scope_id, since, day_key).await?;
+    let by_model = grouped_totals(&state.pool, filter_column, scope_id, since, "model").await?;
+    let breakdown = grouped_totals(&state.pool, filter_column, scope_id, since, breakdown_column).await?;
+    let totals = by_day
+        .iter()
+        .fold(UsageTotals::default(), |mut acc, bucket| {
+            acc.calls += bucket.totals.calls;
+            acc.input_tokens += bucket.totals.input_tokens;
+            acc.output_tokens += bucket.totals.output_tokens;
+            acc.cache_read_tokens += bucket.totals.cache_read_tokens;
+            acc.cache_creation_tokens += bucket.totals.cache_creation_tokens;
+            acc.cost_usd += bucket.totals.cost_usd;
+            acc
+        });
+
+    // The period query keys on both columns; the unused side gets a nil ID.
+    let (user_id, board_id) = match scope {
+        ReportScope::User(id) => (id, Uuid::nil()),
+        ReportScope::Board(id) => (Uuid::nil(), id),
+    };
+    let (user_usage, board_usage) = period_usage(&state.pool, user_id, board_id, now).await?;
+    let current = match scope {
+        ReportScope::User(_) => user_usage,
+        ReportScope::Board(_) => board_usage,
+    };
+
+    Ok(UsageReport {
+        since,
+        totals,
+        by_day,
+        by_model,
+        breakdown,
+        limits: effective_limits(state, budget_scope, scope_id).await,
+        current,
+    })
+}
+
+/// Sum ledger rows matching `filter_column = scope_id` since `since`,
+/// grouped by `key_expr`. Both column arguments are fixed SQL fragments.
+async fn grouped_totals(
+    pool: &PgPool,
+    filter_column: &str,
+    scope_id: Uuid,
+    since: i64,
+    key_expr: &str,
+) -> Result<Vec<UsageBucket>, UsageError> {
+    let sql = format!(
+        "SELECT {key_expr} AS key,
+                COUNT(*),
+                COALESCE(SUM(input_tokens), 0)::BIGINT,
+                COALESCE(SUM(output_tokens), 0)::BIGINT,
+                COALESCE(SUM(cache_read_tokens), 0)::BIGINT,
+                COALESCE(SUM(cache_creation_tokens), 0)::BIGINT,
+                COALESCE(SUM(cost_usd), 0)::DOUBLE PRECISION
+         FROM ai_usage
+         WHERE {filter_column} = $1 AND ts >= $2
+         GROUP BY key
+         ORDER BY key"
+    );
+    let rows = sqlx::query_as::<_, (String, i64, i64, i64, i64, i64, f64)>(&sql)
+        .bind(scope_id)
+        .bind(since)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(
+            |(key, calls, input_tokens, output_tokens, cache_read_tokens, cache_creation_tokens, cost_usd)| {
+                UsageBucket {
+                    key,
+                    totals: UsageTotals {
+                        calls,
+                        input_tokens,
+                        output_tokens,
+                        cache_read_tokens,
+                        cache_creation_tokens,
+                        cost_usd,
+                    },
+                }
+            },
+        )
+        .collect())
+}
+
+#[cfg(test)]
+#[path = "usage_test.rs"]
+mod tests;
@@ -7,7 +7,7 @@
 //! has its own in-memory object store, connected clients, and dirty set
 //! for debounced persistence.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
@@ -19,11 +19,42 @@ use crate::frame::Frame;
 use crate::llm::LlmChat;
 use crate::llm::types::Message;
 use crate::rate_limit::RateLimiter;
+use crate::services::ai::{AiPreview, PromptCancel};
 use crate::services::auth::GitHubConfig;
+use crate::services::history::UserHistory;
+use crate::services::revert::PromptLog;
+use crate::services::usage::{BudgetLimits, BudgetScope};
 
 /// AI conversation history keyed by `(session_id, board_id)`.
 pub type AiSessionMessages = Arc<RwLock<HashMap<(Uuid, Uuid), Vec<Message>>>>;
 
+/// Cancel handles for running AI prompts keyed by request frame ID.
+pub type AiPromptCancels = Arc<RwLock<HashMap<Uuid, PromptCancel>>>;
+
+/// Unapplied AI previews keyed by their `ai:preview` request frame ID.
+pub type AiPreviews = Arc<RwLock<HashMap<Uuid, AiPreview>>>;
+
+/// AI token budget overrides keyed by scope and user or board ID.
+pub type AiBudgets = Arc<RwLock<HashMap<(BudgetScope, Uuid), BudgetLimits>>>;
+
+/// Undo/redo stacks keyed by `(board_id, user_id)`.
+pub type UserHistories = Arc<RwLock<HashMap<(Uuid, Uuid), UserHistory>>>;
+
+/// Per-prompt revert effects keyed by AI trace ID.
+pub type PromptLogs = Arc<RwLock<HashMap<Uuid, PromptLog>>>;
+
+/// Rendered dashboard thumbnails keyed by board ID.
+pub type BoardThumbnails = Arc<RwLock<HashMap<Uuid, BoardThumbnail>>>;
+
+/// A cached dashboard thumbnail.
+#[derive(Debug, Clone)]
+pub struct BoardThumbnail {
+    /// Fingerprint of the object versions the image was rendered from.
+    pub fingerprint: u64,
+    /// PNG-encoded image.
+    pub png: axum::body::Bytes,
+}
+
 // =============================================================================
 // BOARD OBJECT
 // =============================================================================
@@ -59,6 +90,44 @@ pub struct BoardObject {
     pub group_id: Option<Uuid>,
 }
 
+/// Per-field write versions for one object, used for field-level LWW.
+///
+/// Fields are `"geometry"` (x, y, width, height), `"rotation"`, `"z_index"`,
+/// `"group_id"`, and `"props.<key>"` for each props key. A field never written
+/// since tracking began reports `floor`, the object version at that point.
+#[derive(Debug, Clone, Default)]
+pub struct FieldVersions {
+    /// Object version when tracking began; untracked fields report this.
+    pub floor: i32,
+    /// Object version that last wrote each field.
+    pub fields: HashMap<String, i32>,
+}
+
+impl FieldVersions {
+    /// Start tracking an object whose current version is `version`.
+    #[must_use]
+    pub fn starting_at(version: i32) -> Self {
+        Self { floor: version, fields: HashMap::new() }
+    }
+
+    /// Version that last wrote `field`.
+    #[must_use]
+    pub fn version_of(&self, field: &str) -> i32 {
+        self.fields.get(field).copied().unwrap_or(self.floor)
+    }
+}
+
+/// Revision history for one collaboratively edited text field.
+#[derive(Debug, Clone, Default)]
+pub struct TextLog {
+    /// Revision reached by the most recent operation.
+    pub rev: u64,
+    /// Operations that produced the last `history.len()` revisions, oldest first.
+    pub history: VecDeque<frames::text_ops::TextOp>,
+    /// Text the log last produced; a mismatch means the field changed elsewhere.
+    pub text: String,
+}
+
 // =============================================================================
 // BOARD STATE
 // =============================================================================
@@ -110,6 +179,12 @@ pub struct BoardState {
     pub viewports: HashMap<Uuid, ClientViewport>,
     /// Object IDs modified since last flush.
     pub dirty: HashSet<Uuid>,
+    /// Field-level write versions for objects edited since the board loaded.
+    pub field_versions: HashMap<Uuid, FieldVersions>,
+    /// Collaborative text logs keyed by object ID, then props field name.
+    pub text_logs: HashMap<Uuid, HashMap<String, TextLog>>,
+    /// Scratch copy used to dry-run AI tools; never written to Postgres.
+    pub scratch: bool,
 }
 
 impl BoardState {
@@ -122,6 +197,9 @@ impl BoardState {
             users: HashMap::new(),
             viewports: HashMap::new(),
             dirty: HashSet::new(),
+            field_versions: HashMap::new(),
+            text_logs: HashMap::new(),
+            scratch: false,
         }
     }
 }
@@ -152,6 +230,18 @@ pub struct AppState {
     pub rate_limiter: RateLimiter,
     /// AI conversation memory scoped to active websocket session and board.
     pub ai_session_messages: AiSessionMessages,
+    /// Running AI prompts that a `cancel` frame can stop.
+    pub ai_prompt_cancels: AiPromptCancels,
+    /// AI previews awaiting `ai:apply` or `ai:discard`.
+    pub ai_previews: AiPreviews,
+    /// Cached `ai_budgets` rows, loaded at startup and updated on write.
+    pub ai_budgets: AiBudgets,
+    /// Per-user undo/redo history, kept across reconnects.
+    pub histories: UserHistories,
+    /// What each AI prompt changed, for `ai:revert`.
+    pub prompt_logs: PromptLogs,
+    /// Dashboard thumbnails, re-rendered when a board's objects change.
+    pub thumbnails: BoardThumbnails,
     /// Optional GitHub OAuth config. `None` disables OAuth endpoints.
     pub github: Option<GitHubConfig>,
 }
@@ -168,6 +258,12 @@ impl AppState {
             llm,
             rate_limiter: RateLimiter::new(),
             ai_session_messages: Arc::new(RwLock::new(HashMap::new())),
+            ai_prompt_cancels: Arc::new(RwLock::new(HashMap::new())),
+            ai_previews: Arc::new(RwLock::new(HashMap::new())),
+            ai_budgets: Arc::new(RwLock::new(HashMap::new())),
+            histories: Arc::new(RwLock::new(HashMap::new())),
+            prompt_logs: Arc::new(RwLock::new(HashMap::new())),
+            thumbnails: Arc::new(RwLock::new(HashMap::new())),
             github,
         }
     }
@@ -61,6 +61,11 @@ pub fn prefix_display(syscall: &str) -> PrefixDisplay {
             label: "SAVE",
             color: "#ff69b4",
         },
+        "text" => PrefixDisplay {
+            letter: "X",
+            label: "TEXT",
+            color: "#f4d35e",
+        },
         _ => PrefixDisplay {
             letter: "-",
             label: "OTHER",
@@ -112,7 +117,7 @@ impl TraceFilter {
     #[must_use]
     pub fn include_all() -> Self {
         let include_prefixes = [
-            "board", "object", "ai", "tool", "chat", "cursor", "save", "other",
+            "board", "object", "ai", "tool", "chat", "cursor", "save", "text", "other",
         ]
         .into_iter()
         .map(str::to_owned)